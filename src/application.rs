@@ -1,16 +1,25 @@
+use std::path::{Path, PathBuf};
+
 use anyhow::{Context, Result};
-use iced::widget::text_input::Id;
+use iced::widget::text_input::{Id, Value};
+use iced::keyboard::key::Named;
+use iced::keyboard::{self, Key};
 use iced::widget::{
-    container, row, text_input, Stack
+    button, checkbox, column, container, responsive, row, text, text_editor, text_input, Column, Row, Stack
 };
-use iced::{Element, Subscription, Task, Theme};
+use iced::advanced::widget::operation::{Focusable, Outcome};
+use iced::advanced::widget::{self, operate, Operation};
+use iced::{clipboard, event, window, Alignment, Element, Event, Length, Padding, Point, Rectangle, Subscription, Task, Theme, Vector};
 
-use crate::molecule::{AtomId, MoleculeId};
-use crate::{canvas, toolbar};
+use crate::chemistry::{Abbreviation, Composition};
+use crate::cursor_input::CursorInput;
+use crate::formats::{Sequence, Structure};
+use crate::molecule::{AtomId, MoleculeId, Token, GREEK_LETTERS};
+use crate::{canvas, chemistry, formats, inspector, lookup, recent, session, templates, toolbar};
 
 pub fn main() -> iced::Result {
     iced::application(
-        "MolCanvas",
+        Application::title,
         Application::update,
         Application::view,
         )
@@ -18,33 +27,144 @@ pub fn main() -> iced::Result {
         .theme(|_| Theme::Dark)
         .antialiasing(true)
         .centered()
+        .exit_on_close_request(false)
         .run()
 }
 
+/// how long dropped files wait for the cursor to be seen before going in the middle of the view
+const DROP_WAIT: std::time::Duration = std::time::Duration::from_millis(500);
 
 struct Application {
     mol_canvas: canvas::MolCanvas,
     toolbar: toolbar::Toolbar,
+    inspector: inspector::Inspector,
     text_input: Option<InputHandler>,
     text_input_id: Id,
+    /// the editor open on free text
+    text_edit: Option<TextEdit>,
+    /// wraps the text editor, which has no id of its own to be focused by
+    text_edit_id: container::Id,
+    /// text typed into the find bar, which is shown while this is some
+    find: Option<String>,
+    find_input_id: Id,
+    /// text that matches in the find bar are replaced with
+    replace: String,
+    /// whether replacing is limited to the selected atoms
+    replace_in_selection: bool,
+    /// pasted text that could be something other than SMILES, or structures recognised in a
+    /// dropped or pasted image, waiting to be confirmed
+    pending_paste: Option<PendingPaste>,
+    /// opened on the first image copied and kept open, as on X11 copied images are only there
+    /// for as long as the app holding them keeps them
+    clipboard: Option<arboard::Clipboard>,
+    /// molecules last copied, kept by the app so they paste whole into whatever document is open
+    copied: Option<canvas::Clipboard>,
+    /// files dropped on the window, waiting to be placed where the cursor is next seen
+    dropped: Vec<PathBuf>,
+    /// set when the session couldn't be saved on close, so the next close goes ahead without it
+    unsaved_close: bool,
+}
+
+#[derive(Debug, Clone)]
+struct PendingPaste {
+    /// question the paste is confirmed with
+    prompt: String,
+    structures: Vec<Structure>,
+    position: Point,
 }
 
 #[derive(Debug, Clone)]
 struct InputHandler {
     placeholder: String,
     value: String,
+    /// value restored when the input is cancelled
+    original: String,
+    /// start and end of the text the input's cursor covers, in characters, where text inserted
+    /// from the formatting toolbar goes
+    cursor: (usize, usize),
     molecule_id: MoleculeId,
     atom_id: AtomId,
     callback: fn(MoleculeId, AtomId, String) -> canvas::Message,
+    /// canvas position of an atom added for the input, which is removed if the input is cancelled
+    /// and replaced by the structure if its label is a condensed formula
+    added_at: Option<Point>,
+}
+
+#[derive(Debug)]
+struct TextEdit {
+    target: canvas::TextTarget,
+    content: text_editor::Content,
+    /// text the target held when the editor opened, nothing is written if it's unchanged
+    original: String,
+}
+
+impl TextEdit {
+    /// the editor always ends its text with a newline, which isn't part of what was written
+    fn text(&self) -> String {
+        let text = self.content.text();
+        text.strip_suffix('\n').unwrap_or(&text).to_string()
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     MolCanvas(Vec<canvas::Message>),
     Toolbar(toolbar::Message),
+    Inspector(inspector::Message),
+    /// the compounds a reference was looked up as, or why it couldn't be
+    ReferenceResolved(Result<Vec<lookup::Compound>, String>),
     TextInputSpawn(String, MoleculeId, AtomId, fn(MoleculeId, AtomId, String) -> canvas::Message),
+    /// submits the open input, or adds an atom at a canvas position to type a label or condensed
+    /// formula into
+    TextInputNew(Point),
     TextInputChange(String),
+    TextInputCursor(usize, usize),
+    /// replaces the text under the relabel input's cursor
+    TextInputInsert(String),
+    /// replaces the label being typed with a suggested completion
+    TextInputComplete(String),
+    /// completes the label being typed with the first suggestion
+    TextInputCompleteFirst,
     TextInputSubmit,
+    TextInputCancel,
+    /// submits the open text editor and opens one on other text
+    TextEditOpen(canvas::TextTarget),
+    TextEditAction(text_editor::Action),
+    /// types text at the text editor's cursor
+    TextEditInsert(String),
+    TextEditSubmit,
+    TextEditCancel,
+    FindOpen,
+    FindChange(String),
+    FindNext,
+    FindPrevious,
+    FindClose,
+    ReplaceChange(String),
+    ReplaceInSelection(bool),
+    ReplaceAll,
+    Undo,
+    Redo,
+    FileDropped(PathBuf),
+    /// opens the dropped files at a canvas position, or in the middle of the view when the cursor
+    /// wasn't seen in time
+    PlaceDropped(Option<Point>),
+    /// opens the relabel input on the atom with keyboard focus
+    RelabelFocused,
+    /// writes a molecule to the clipboard and closes the copy menu
+    CopyMolecule(MoleculeId, canvas::CopyFormat),
+    /// keeps the selected molecules to paste and writes them to the clipboard as an SD file
+    CopySelection,
+    /// writes the selection, or the whole drawing, to the clipboard as an image
+    CopyImage,
+    /// reads the clipboard to paste at a canvas position
+    PasteRequested(Point),
+    Paste(Option<String>, Point),
+    /// the structures recognised in a dropped or pasted image, to offer at a canvas position
+    StructuresRecognized(Result<Option<Vec<Structure>>, String>, Point),
+    ConfirmPaste,
+    CancelPaste,
+    /// saves the session before closing the window
+    CloseRequested(window::Id),
     Error(String)
 }
 
@@ -67,16 +187,199 @@ impl From<anyhow::Error> for Message {
 }
 
 impl Application {
+    const CONTEXT_MENU_WIDTH: f32 = 140.0;
+    /// narrowest the label completions are shown, so abbreviations and their previews fit under
+    /// short labels
+    const COMPLETIONS_WIDTH: f32 = 240.0;
+
     fn new() -> Self {
+        let mut inspector = inspector::Inspector::default();
+        // the built in table is still usable, so a bad element file is only reported
+        if let Err(error) = chemistry::load_overrides() {
+            inspector.set_status(format!("{:#}", error));
+        }
+        inspector.set_templates(templates::list());
+        inspector.set_recent_files(recent::list());
+
+        // an unreadable session is reported and the app opens empty
+        let mut mol_canvas = canvas::MolCanvas::default();
+        let mut toolbar = toolbar::Toolbar::default();
+        let restored = session::load().and_then(|session| match session {
+            Some(session) => {
+                toolbar.update(toolbar::Message::ToolChanged(session.tool));
+                mol_canvas.restore_session(session)
+            }
+            None => Ok(()),
+        });
+        if let Err(error) = restored {
+            inspector.set_status(format!("{:#}", error));
+        }
+
         Self {
-            mol_canvas: canvas::MolCanvas::default(),
-            toolbar: toolbar::Toolbar::default(),
+            mol_canvas,
+            toolbar,
+            inspector,
             text_input: None,
             text_input_id: Id::unique(),
+            text_edit: None,
+            text_edit_id: container::Id::unique(),
+            find: None,
+            find_input_id: Id::unique(),
+            replace: String::new(),
+            replace_in_selection: false,
+            pending_paste: None,
+            clipboard: None,
+            copied: None,
+            dropped: vec![],
+            unsaved_close: false,
+        }
+    }
+
+    /// screen readers announce the window title, so it carries what is focused or selected
+    fn title(&self) -> String {
+        match self.mol_canvas.describe_selection() {
+            Some(description) => format!("MolCanvas - {}", description),
+            None => "MolCanvas".to_string(),
         }
     }
 
     fn update(&mut self, message: Message) -> Task<Message> {
+        /// adds the structures in a file to the document centred on a canvas position, reporting
+        /// unreadable files in the inspector rather than treating them as a bug
+        fn open_file(application: &mut Application, path: &Path, position: Point) -> Result<Task<Message>> {
+            // svgs exported of a whole drawing bring its shapes and text along with its molecules
+            match session::read_project(path) {
+                Ok(Some(project)) => {
+                    application.inspector.set_status("Opened project".to_string());
+                    application.mol_canvas.update(vec![canvas::Message::ImportProject(Box::new(project), position)])
+                        .context("while opening file")?;
+                    remember(application, path);
+
+                    return Ok(Task::none());
+                }
+                Ok(None) => {}
+                Err(error) => {
+                    application.inspector.set_status(format!("{:#}", error));
+                    return Ok(Task::none());
+                }
+            }
+
+            // reactions are laid out around arrows rather than side by side
+            if formats::is_reaction_file(path) {
+                match formats::read_reactions(path) {
+                    Ok(reactions) => {
+                        application.inspector.set_status(format!("Opened {} reactions", reactions.len()));
+                        application.mol_canvas.update(vec![canvas::Message::PasteReactions(reactions, position)])
+                            .context("while opening file")?;
+                        remember(application, path);
+                    }
+                    Err(error) => application.inspector.set_status(format!("{:#}", error)),
+                }
+
+                return Ok(Task::none());
+            }
+
+            match formats::read_file(path) {
+                Ok(structures) => {
+                    application.inspector.set_status(format!("Opened {} structures", structures.len()));
+                    application.mol_canvas.update(vec![canvas::Message::PasteStructures(structures, position)])
+                        .context("while opening file")?;
+                    remember(application, path);
+                }
+                // pictures that weren't exported from here are put behind the drawing to trace over
+                Err(error) => match formats::read_reference_image(path) {
+                    Ok(image) => {
+                        application.inspector.set_status("Opened image to trace over".to_string());
+                        application.mol_canvas.update(vec![canvas::Message::ImportBackground(image, position)])
+                            .context("while opening file")?;
+                        remember(application, path);
+
+                        let path = path.to_path_buf();
+                        return Ok(recognize(position, move || formats::recognize_structures(&path)));
+                    }
+                    Err(_) => application.inspector.set_status(format!("{:#}", error)),
+                },
+            }
+
+            Ok(Task::none())
+        }
+
+        /// puts a file that opened at the front of the recent files, which are only a convenience
+        /// so not keeping the list is just reported
+        fn remember(application: &mut Application, path: &Path) {
+            if let Err(error) = recent::add(path) {
+                application.inspector.set_status(format!("{:#}", error));
+            }
+            application.inspector.set_recent_files(recent::list());
+        }
+
+        /// puts an image pasted from the clipboard behind the drawing to trace over, recognising
+        /// the structures in it like those in a dropped image
+        fn paste_image(application: &mut Application, position: Point) -> Result<Task<Message>> {
+            let Ok(image) = clipboard(application)?.get_image() else {
+                application.inspector.set_status("Clipboard has no structures to paste".to_string());
+                return Ok(Task::none());
+            };
+            let bitmap = formats::Bitmap {
+                width: image.width,
+                height: image.height,
+                pixels: image.bytes.into_owned(),
+            };
+
+            let image = formats::bitmap_to_png(&bitmap)
+                .context("while encoding pasted image")
+                .and_then(|png| Ok((formats::png_reference_image(&png)?, png)));
+            match image {
+                Ok((image, png)) => {
+                    application.inspector.set_status("Pasted image to trace over".to_string());
+                    application.mol_canvas.update(vec![canvas::Message::ImportBackground(image, position)])
+                        .context("while pasting image")?;
+
+                    Ok(recognize(position, move || formats::recognize_png(&png)))
+                }
+                Err(error) => {
+                    application.inspector.set_status(format!("{:#}", error));
+                    Ok(Task::none())
+                }
+            }
+        }
+
+        /// structures recognised in an image are offered as molecules, with the image left behind
+        /// them to check against
+        fn recognize(
+            position: Point,
+            work: impl FnOnce() -> Result<Option<Vec<Structure>>> + Send + 'static,
+        ) -> Task<Message> {
+            Task::perform(background(move || work().map_err(|error| format!("{:#}", error))), move |structures| {
+                Message::StructuresRecognized(structures, position)
+            })
+        }
+
+        /// the system clipboard, opened the first time it's used
+        fn clipboard(application: &mut Application) -> Result<&mut arboard::Clipboard> {
+            if application.clipboard.is_none() {
+                application.clipboard = Some(arboard::Clipboard::new().context("while opening clipboard")?);
+            }
+
+            Ok(application.clipboard.as_mut().expect("clipboard was just opened"))
+        }
+
+        /// renders the selection, or the whole drawing, onto the clipboard, returning how many
+        /// molecules are in the image
+        fn copy_image(application: &mut Application) -> Result<usize> {
+            let (bitmap, count) = application.mol_canvas.clipboard_image()?;
+
+            clipboard(application)?
+                .set_image(arboard::ImageData {
+                    width: bitmap.width,
+                    height: bitmap.height,
+                    bytes: bitmap.pixels.into(),
+                })
+                .context("while copying image")?;
+
+            Ok(count)
+        }
+
         fn handle_message(application: &mut Application, message: Message) -> Result<Task<Message>> {
             match message {
                 Message::MolCanvas(message) => {
@@ -84,16 +387,260 @@ impl Application {
                 }
                 Message::Toolbar(message) => {
                     application.toolbar.update(message.clone());
-                    let toolbar::Message::ToolChanged(tool) = &message;
 
-                    application.mol_canvas.update(vec![canvas::Message::ToolChanged(*tool)]).context("while handling application message Toolbar")?;
+                    if let toolbar::Message::ToolChanged(tool) = message {
+                        application.mol_canvas.update(vec![canvas::Message::ToolChanged(tool)]).context("while handling application message Toolbar")?;
+                    }
                 }
+                Message::Inspector(message) => {
+                    let messages = match application.inspector.update(message) {
+                        inspector::Message::AnnotateFunctionalGroups(annotate) => vec![canvas::Message::AnnotateFunctionalGroups(annotate)],
+                        inspector::Message::LewisStructures(lewis_structures) => vec![canvas::Message::LewisStructures(lewis_structures)],
+                        inspector::Message::BondCrossings(bond_crossings) => vec![canvas::Message::BondCrossings(bond_crossings)],
+                        inspector::Message::PartialBondDash(dash_pattern) => vec![canvas::Message::PartialBondDash(dash_pattern)],
+                        inspector::Message::LabelFont(label_font) => vec![canvas::Message::ChangeLabelFont(label_font)],
+                        inspector::Message::DisplayMode(molecule_id, display_mode) => vec![canvas::Message::ChangeDisplayMode(molecule_id, display_mode)],
+                        inspector::Message::BondType(molecule_id, bond_id, bond_type) => vec![canvas::Message::ChangeBondType(molecule_id, bond_id, bond_type)],
+                        inspector::Message::BondLengthInput(..)
+                        | inspector::Message::BondLengthSubmit(..)
+                        | inspector::Message::BondAngleInput(..)
+                        | inspector::Message::BondAngleSubmit(..)
+                        | inspector::Message::AtomMapInput(..)
+                        | inspector::Message::AtomMapSubmit(..)
+                        | inspector::Message::ReactionYieldInput(..)
+                        | inspector::Message::ReactionYieldSubmit(..)
+                        | inspector::Message::ShapeWidthInput(..)
+                        | inspector::Message::ShapeWidthSubmit(..)
+                        | inspector::Message::ReactionConditionInput(..)
+                        | inspector::Message::ReactionConditionSubmit(..)
+                        | inspector::Message::DocumentBondLengthInput(_)
+                        | inspector::Message::DocumentBondLengthSubmit
+                        | inspector::Message::ScaleFactorInput(_)
+                        | inspector::Message::ScaleFactorSubmit => vec![],
+                        inspector::Message::SetBondLength(molecule_id, bond_id, length) => vec![canvas::Message::SetBondLength(molecule_id, bond_id, length)],
+                        inspector::Message::SetBondAngle(molecule_id, atom_id, degrees) => vec![canvas::Message::SetBondAngle(molecule_id, atom_id, degrees)],
+                        inspector::Message::SetReactionConditions(shape_id, conditions) => vec![canvas::Message::SetReactionConditions(shape_id, conditions)],
+                        inspector::Message::SetAtomMap(molecule_id, atom_id, map) => vec![canvas::Message::SetAtomMap(molecule_id, atom_id, map)],
+                        inspector::Message::SetDocumentBondLength(length) => vec![canvas::Message::SetDocumentBondLength(length)],
+                        inspector::Message::ScaleSelection(factor) => vec![canvas::Message::ScaleSelection(factor)],
+                        inspector::Message::FlipFragment(molecule_id, bond_id) => vec![canvas::Message::FlipFragment(molecule_id, bond_id)],
+                        inspector::Message::CurveBond(molecule_id, bond_id, curvature) => vec![canvas::Message::CurveBond(molecule_id, bond_id, curvature)],
+                        inspector::Message::ResolveOverlaps => vec![canvas::Message::ResolveOverlaps],
+                        inspector::Message::Arrange(arrangement) => vec![canvas::Message::Arrange(arrangement)],
+                        inspector::Message::RouteMode(route_mode) => vec![canvas::Message::RouteMode(route_mode)],
+                        inspector::Message::ArrangeRoutes => vec![canvas::Message::ArrangeRoutes],
+                        inspector::Message::ToggleRoute(_) => vec![],
+                        inspector::Message::SelectRouteStep(arrow_id) => vec![canvas::Message::SelectShape(Some(arrow_id))],
+                        inspector::Message::NewLayerInput(_) | inspector::Message::NewLayerSubmit => vec![],
+                        inspector::Message::NewLayer(name) => vec![canvas::Message::NewLayer(name)],
+                        inspector::Message::ActiveLayer(layer_id) => vec![canvas::Message::SetActiveLayer(layer_id)],
+                        inspector::Message::LayerVisible(layer_id, visible) => vec![canvas::Message::SetLayerVisible(layer_id, visible)],
+                        inspector::Message::LayerLocked(layer_id, locked) => vec![canvas::Message::SetLayerLocked(layer_id, locked)],
+                        inspector::Message::BackgroundOpacity(opacity) => vec![canvas::Message::BackgroundOpacity(opacity)],
+                        inspector::Message::BackgroundExported(exported) => vec![canvas::Message::BackgroundExported(exported)],
+                        inspector::Message::BackgroundLocked(locked) => vec![canvas::Message::BackgroundLocked(locked)],
+                        inspector::Message::RemoveBackground => vec![canvas::Message::RemoveBackground],
+                        inspector::Message::SetShapeStyle(shape_id, style) => vec![canvas::Message::SetShapeStyle(shape_id, style)],
+                        inspector::Message::DeleteShape(shape_id) => vec![canvas::Message::DeleteShape(shape_id)],
+                        inspector::Message::MoveToLayer(molecule_id, layer_id) => vec![canvas::Message::MoveToLayer(molecule_id, layer_id)],
+                        inspector::Message::LockMolecule(molecule_id) => vec![canvas::Message::SetMoleculeLocked(molecule_id, true)],
+                        inspector::Message::UnlockAll => vec![canvas::Message::UnlockAll],
+                        inspector::Message::NameInput(..) | inspector::Message::NameSubmit(_) => vec![],
+                        inspector::Message::RenameMolecule(molecule_id, name) => vec![canvas::Message::RenameMolecule(molecule_id, name)],
+                        inspector::Message::CaptionInput(..) | inspector::Message::CaptionSubmit(_) => vec![],
+                        inspector::Message::SetCaption(molecule_id, caption) => vec![canvas::Message::SetCaption(molecule_id, caption)],
+                        inspector::Message::MetadataInput(..) | inspector::Message::MetadataSubmit(_) => vec![],
+                        inspector::Message::SetCas(molecule_id, cas) => vec![canvas::Message::SetCas(molecule_id, cas)],
+                        inspector::Message::SetNotes(molecule_id, notes) => vec![canvas::Message::SetNotes(molecule_id, notes)],
+                        inspector::Message::TagsInput(..) | inspector::Message::TagsSubmit(_) => vec![],
+                        inspector::Message::SetTags(molecule_id, tags) => vec![canvas::Message::SetTags(molecule_id, tags)],
+                        inspector::Message::SetSource(molecule_id, source) => vec![canvas::Message::SetSource(molecule_id, source)],
+                        inspector::Message::AddInstance(molecule_id) => vec![canvas::Message::AddInstance(molecule_id)],
+                        inspector::Message::UnlinkInstance(molecule_id) => vec![canvas::Message::UnlinkInstance(molecule_id)],
+                        inspector::Message::FilterTag(tag, selected) => vec![canvas::Message::FilterTag(tag, selected)],
+                        inspector::Message::HideUnmatched(hide) => vec![canvas::Message::HideUnmatched(hide)],
+                        inspector::Message::MoleculeNames(molecule_names) => vec![canvas::Message::MoleculeNames(molecule_names)],
+                        inspector::Message::CompoundNumbers(compound_numbers) => vec![canvas::Message::CompoundNumbers(compound_numbers)],
+                        inspector::Message::InteractionDistances(distances) => vec![canvas::Message::InteractionDistances(distances)],
+                        inspector::Message::InkWidth(ink_width) => vec![canvas::Message::InkWidth(ink_width)],
+                        inspector::Message::InkColor(ink_color) => vec![canvas::Message::InkColor(ink_color)],
+                        inspector::Message::BondAnnotationInput(..) | inspector::Message::BondAnnotationSubmit(..) => vec![],
+                        inspector::Message::AnnotateBond(molecule_id, bond_id, annotation) => vec![canvas::Message::AnnotateBond(molecule_id, bond_id, annotation)],
+                        inspector::Message::MarkDisconnection(molecule_id, bond_id, disconnection) => vec![canvas::Message::MarkDisconnection(molecule_id, bond_id, disconnection)],
+                        inspector::Message::BondAnnotations(bond_annotations) => vec![canvas::Message::BondAnnotations(bond_annotations)],
+                        inspector::Message::MetalCounts(metal_counts) => vec![canvas::Message::MetalCounts(metal_counts)],
+                        inspector::Message::LetterWithPrevious(molecule_id, letters) => vec![canvas::Message::LetterWithPrevious(molecule_id, letters)],
+                        inspector::Message::SetChargeBracket(molecule_id, charge_bracket) => vec![canvas::Message::SetChargeBracket(molecule_id, charge_bracket)],
+                        inspector::Message::ResizeChargeBracket(molecule_id, padding) => vec![canvas::Message::ResizeChargeBracket(molecule_id, padding)],
+                        inspector::Message::JumpToMolecule(molecule_id) => vec![canvas::Message::JumpToMolecule(molecule_id)],
+                        inspector::Message::SelectParentMolecule => vec![canvas::Message::SelectParentMolecule],
+                        inspector::Message::GrowSelection => vec![canvas::Message::GrowSelection],
+                        inspector::Message::ExportPathInput(_)
+                        | inspector::Message::ExportMarginInput(_)
+                        | inspector::Message::ExportMarginSubmit
+                        | inspector::Message::ExportResolutionInput(_)
+                        | inspector::Message::ExportResolutionSubmit => vec![],
+                        inspector::Message::ExportMargin(margin) => vec![canvas::Message::ExportMargin(margin)],
+                        inspector::Message::ExportResolution(resolution) => vec![canvas::Message::ExportResolution(resolution)],
+                        inspector::Message::CopyImage => return Ok(Task::done(Message::CopyImage)),
+                        inspector::Message::ZoomToFit => vec![canvas::Message::ZoomToFit],
+                        inspector::Message::Zoom(scaling) => vec![canvas::Message::ZoomTo(scaling)],
+                        inspector::Message::VersionNameInput(_) | inspector::Message::VersionNameSubmit => vec![],
+                        inspector::Message::SaveVersion(name) => vec![canvas::Message::SaveVersion(name)],
+                        inspector::Message::CompareVersion(index) => vec![canvas::Message::CompareVersion(index)],
+                        inspector::Message::RestoreVersion(index) => vec![canvas::Message::RestoreVersion(index)],
+                        inspector::Message::DeleteVersion(index) => vec![canvas::Message::DeleteVersion(index)],
+                        inspector::Message::TemplateNameInput(_) | inspector::Message::TemplateNameSubmit => vec![],
+                        inspector::Message::SaveTemplate(name) => {
+                            let saved = templates::path_for(&name)
+                                .and_then(|path| application.mol_canvas.export_image(&path))
+                                .context("while saving template");
+
+                            let status = match saved {
+                                Ok(_) => format!("Saved template {}", name),
+                                Err(error) => format!("{:#}", error),
+                            };
+                            application.inspector.set_status(status);
+                            application.inspector.set_templates(templates::list());
+
+                            vec![]
+                        }
+                        inspector::Message::ReferenceInput(_) | inspector::Message::ReferenceSubmit => vec![],
+                        inspector::Message::InsertReference(reference) => {
+                            application.inspector.set_status(format!("Looking up {}", reference.trim()));
+
+                            return Ok(Task::perform(
+                                background(move || lookup::resolve(&reference).map_err(|error| format!("{:#}", error))),
+                                Message::ReferenceResolved,
+                            ));
+                        }
+                        inspector::Message::SequenceInput(_)
+                        | inspector::Message::SequenceKind(_)
+                        | inspector::Message::SequenceCondensed(_)
+                        | inspector::Message::SequenceSubmit => vec![],
+                        inspector::Message::InsertSequence(text, kind, condensed) => {
+                            // HELM strings say what kind of chain they are themselves
+                            let sequences = match formats::is_helm(&text) {
+                                true => formats::parse_helm(&text),
+                                false => Sequence::parse(&text, kind).map(|sequence| vec![sequence]),
+                            };
+                            let compounds = sequences.and_then(|sequences| {
+                                sequences
+                                    .into_iter()
+                                    .map(|sequence| {
+                                        let structure = match condensed {
+                                            true => sequence.condensed_structure(),
+                                            false => sequence.structure()?,
+                                        };
+
+                                        Ok(lookup::Compound {
+                                            name: sequence.name(),
+                                            structures: vec![structure],
+                                            source: formats::write_helm(&sequence),
+                                        })
+                                    })
+                                    .collect::<Result<Vec<_>, formats::Error>>()
+                            });
+
+                            match compounds {
+                                Ok(compounds) => {
+                                    let names = compounds.iter().map(|compound| compound.name.clone()).collect::<Vec<_>>();
+                                    application.inspector.set_status(format!("Inserted {}", names.join(", ")));
+                                    vec![canvas::Message::InsertCompounds(compounds)]
+                                }
+                                Err(error) => {
+                                    application.inspector.set_status(error.to_string());
+                                    vec![]
+                                }
+                            }
+                        }
+                        inspector::Message::InsertClipart(clipart) => vec![canvas::Message::InsertClipart(clipart)],
+                        inspector::Message::OpenRecent(path) => {
+                            let center = application.mol_canvas.view_center();
+                            return open_file(application, &path, center).context("while opening recent file");
+                        }
+                        inspector::Message::InsertTemplate(path) => {
+                            let center = application.mol_canvas.view_center();
+                            return open_file(application, &path, center).context("while inserting template");
+                        }
+                        inspector::Message::Export(path) => {
+                            let path = Path::new(&path);
+                            let extension = path
+                                .extension()
+                                .and_then(|extension| extension.to_str())
+                                .map(str::to_lowercase)
+                                .unwrap_or_default();
+                            let exported = match extension.as_str() {
+                                "svg" | "png" => application.mol_canvas.export_image(path),
+                                "sdf" | "sd" => application.mol_canvas.export_sdf(path),
+                                "rsmi" | "rxn" | "rdf" => application.mol_canvas.export_reactions(path),
+                                _ => application.mol_canvas.export_csv(path),
+                            };
+                            let exported_items = match extension.as_str() {
+                                "rsmi" | "rxn" | "rdf" => "reactions",
+                                _ => "molecules",
+                            };
+
+                            // a failed write is reported in the inspector rather than treated as a bug
+                            let status = match exported {
+                                Ok(count) => format!("Exported {} {}", count, exported_items),
+                                Err(error) => format!("{:#}", error),
+                            };
+                            application.inspector.set_status(status);
+
+                            vec![]
+                        }
+                    };
+
+                    application.mol_canvas.update(messages).context("while handling application message Inspector")?;
+                }
+                // a failed lookup is reported in the inspector, it's often just a name PubChem doesn't know
+                Message::ReferenceResolved(compounds) => match compounds {
+                    Ok(compounds) => {
+                        application.inspector.set_status(format!("Inserted {} compounds from PubChem", compounds.len()));
+                        application.mol_canvas.update(vec![canvas::Message::InsertCompounds(compounds)])
+                            .context("while handling application message ReferenceResolved")?;
+                    }
+                    Err(error) => application.inspector.set_status(error),
+                },
                 Message::TextInputSpawn(value, molecule_id, atom_id, callback) => {
                     // if let Some(InputHandler { value, molecule_id, atom_id, callback, .. }) = &application.text_input {
                     //     application.mol_canvas.update(callback(*molecule_id, *atom_id, value.to_string()));
                     // };
 
-                    application.text_input = Some(InputHandler { placeholder: "label: ".to_string(), value, molecule_id, atom_id, callback });
+                    // focusing the input moves its cursor to the end
+                    let end = Value::new(&value).len();
+                    application.text_input = Some(InputHandler {
+                        placeholder: "label: ".to_string(),
+                        value: value.clone(),
+                        original: value,
+                        cursor: (end, end),
+                        molecule_id,
+                        atom_id,
+                        callback,
+                        added_at: None,
+                    });
+                    return Ok(text_input::focus(application.text_input_id.clone()));
+                }
+                Message::TextInputNew(_) if application.text_input.is_some() => {
+                    return Ok(Task::done(Message::TextInputSubmit));
+                }
+                Message::TextInputNew(_) if !application.mol_canvas.can_add_molecules() => {}
+                Message::TextInputNew(position) => {
+                    let (molecule_id, atom_id) = (MoleculeId::new(), AtomId::new());
+                    application.mol_canvas.update(vec![canvas::Message::AddMoleculeWithAtom(molecule_id, atom_id, String::new(), position)])
+                        .context("while handling application message TextInputNew")?;
+
+                    application.text_input = Some(InputHandler {
+                        placeholder: "label or formula: ".to_string(),
+                        value: String::new(),
+                        original: String::new(),
+                        cursor: (0, 0),
+                        molecule_id,
+                        atom_id,
+                        callback: canvas::Message::RelabelAtom,
+                        added_at: Some(position),
+                    });
                     return Ok(text_input::focus(application.text_input_id.clone()));
                 }
                 Message::TextInputChange(text) => {
@@ -103,12 +650,331 @@ impl Application {
                             .context("while handling application message TextInputChange")?;
                     };
                 }
+                Message::TextInputCursor(start, end) => {
+                    if let Some(input_handler) = application.text_input.as_mut() {
+                        input_handler.cursor = (start, end);
+                    }
+                }
+                Message::TextInputInsert(text) => {
+                    let Some(InputHandler { value, cursor, molecule_id, atom_id, callback, .. }) = application.text_input.as_mut() else {
+                        return Ok(Task::none());
+                    };
+
+                    let characters = Value::new(value);
+                    let start = characters.until(cursor.0).to_string().len();
+                    let end = characters.until(cursor.1).to_string().len();
+                    value.replace_range(start..end, &text);
+
+                    let inserted = cursor.0 + Value::new(&text).len();
+                    *cursor = (inserted, inserted);
+                    application.mol_canvas.update(vec![callback(*molecule_id, *atom_id, value.to_string())])
+                        .context("while handling application message TextInputInsert")?;
+
+                    // focusing moves the cursor to the end, so it is put back after the inserted text
+                    return Ok(text_input::focus(application.text_input_id.clone())
+                        .chain(text_input::move_cursor_to(application.text_input_id.clone(), inserted)));
+                }
+                Message::TextInputComplete(completion) => {
+                    if let Some(InputHandler { value, cursor, molecule_id, atom_id, callback, .. }) = application.text_input.as_mut() {
+                        *value = completion;
+                        let end = Value::new(value).len();
+                        *cursor = (end, end);
+                        application.mol_canvas.update(vec![callback(*molecule_id, *atom_id, value.to_string())])
+                            .context("while handling application message TextInputComplete")?;
+                    };
+
+                    return Ok(Task::batch([
+                        text_input::focus(application.text_input_id.clone()),
+                        text_input::move_cursor_to_end(application.text_input_id.clone()),
+                    ]));
+                }
+                Message::TextInputCompleteFirst => {
+                    let completion = application.text_input
+                        .as_ref()
+                        .and_then(|input_handler| Composition::completions(&input_handler.value).into_iter().next());
+                    if let Some(completion) = completion {
+                        return Ok(Task::done(Message::TextInputComplete(completion)));
+                    }
+                }
                 Message::TextInputSubmit => {
                     // if let Some(InputHandler { value, molecule_id, atom_id, callback, .. }) = &application.text_input {
                     //     // application.mol_canvas.update(callback(*molecule_id, *atom_id, value.to_string()));
                     //     application.text_input = None;
                     // };
-                    application.text_input = None;
+                    let text_input = application.text_input.take();
+                    // typing an element's symbol uses it as much as its tool would
+                    if let Some(element) = text_input.as_ref().and_then(|input| chemistry::Element::from_symbol(input.value.trim())) {
+                        application.toolbar.used(toolbar::Tool::Atom(element.symbol));
+                    }
+
+                    if let Some(InputHandler { value, molecule_id, added_at: Some(position), .. }) = text_input {
+                        // a single atom such as "OH" stays a label
+                        if let Some(structure) = formats::parse_condensed(&value).ok().filter(|structure| structure.atoms.len() > 1) {
+                            application.mol_canvas.update(vec![
+                                canvas::Message::DeleteMolecule(molecule_id),
+                                canvas::Message::PasteStructures(vec![structure], position),
+                            ])
+                            .context("while handling application message TextInputSubmit")?;
+                        }
+                    };
+                }
+                Message::TextInputCancel => {
+                    match application.text_input.take() {
+                        Some(InputHandler { molecule_id, added_at: Some(_), .. }) => {
+                            application.mol_canvas.update(vec![canvas::Message::DeleteMolecule(molecule_id)])
+                                .context("while handling application message TextInputCancel")?;
+                        }
+                        Some(InputHandler { original, molecule_id, atom_id, callback, .. }) => {
+                            application.mol_canvas.update(vec![callback(molecule_id, atom_id, original)])
+                                .context("while handling application message TextInputCancel")?;
+                        }
+                        None => (),
+                    };
+                }
+                Message::TextEditOpen(target) => {
+                    if let Some(text_edit) = application.text_edit.take() {
+                        submit_text(application, text_edit).context("while handling application message TextEditOpen")?;
+                    }
+
+                    // text that's gone or on a hidden or locked layer isn't edited
+                    let Some(original) = application.mol_canvas.target_text(&target) else {
+                        return Ok(Task::none());
+                    };
+
+                    let mut content = text_editor::Content::with_text(&original);
+                    content.perform(text_editor::Action::Move(text_editor::Motion::DocumentEnd));
+                    application.text_edit = Some(TextEdit { target, content, original });
+
+                    return Ok(operate(focus_within(application.text_edit_id.clone())));
+                }
+                Message::TextEditAction(action) => {
+                    if let Some(text_edit) = application.text_edit.as_mut() {
+                        text_edit.content.perform(action);
+                    }
+                }
+                Message::TextEditInsert(text) => {
+                    if let Some(text_edit) = application.text_edit.as_mut() {
+                        text_edit.content.perform(text_editor::Action::Edit(text_editor::Edit::Paste(text.into())));
+                    }
+
+                    // pressing the formatting toolbar's buttons takes focus from the editor
+                    return Ok(operate(focus_within(application.text_edit_id.clone())));
+                }
+                Message::TextEditSubmit => {
+                    if let Some(text_edit) = application.text_edit.take() {
+                        submit_text(application, text_edit).context("while handling application message TextEditSubmit")?;
+                    }
+                }
+                Message::TextEditCancel => {
+                    application.text_edit = None;
+                }
+                Message::FindOpen => {
+                    application.find.get_or_insert_with(String::new);
+                    return Ok(text_input::focus(application.find_input_id.clone()));
+                }
+                Message::FindChange(query) => {
+                    application.mol_canvas.update(vec![canvas::Message::Find(query.clone())])
+                        .context("while handling application message FindChange")?;
+                    application.find = Some(query);
+                }
+                Message::FindNext => {
+                    application.mol_canvas.update(vec![canvas::Message::FindNext])
+                        .context("while handling application message FindNext")?;
+                }
+                Message::FindPrevious => {
+                    application.mol_canvas.update(vec![canvas::Message::FindPrevious])
+                        .context("while handling application message FindPrevious")?;
+                }
+                Message::FindClose => {
+                    application.find = None;
+                    application.mol_canvas.update(vec![canvas::Message::ClearFind])
+                        .context("while handling application message FindClose")?;
+                }
+                Message::ReplaceChange(replace) => {
+                    application.replace = replace;
+                }
+                Message::ReplaceInSelection(replace_in_selection) => {
+                    application.replace_in_selection = replace_in_selection;
+                }
+                Message::ReplaceAll => {
+                    if let Some(find) = &application.find {
+                        application.mol_canvas.update(vec![
+                            canvas::Message::ReplaceLabels(find.clone(), application.replace.clone(), application.replace_in_selection),
+                            // the replaced labels may no longer match
+                            canvas::Message::Find(find.clone()),
+                        ])
+                        .context("while handling application message ReplaceAll")?;
+                    }
+                }
+                // the relabel input refers to an atom and the text editor to text that undoing could remove
+                Message::Undo | Message::Redo if application.text_input.is_some() || application.text_edit.is_some() => {}
+                Message::Undo => {
+                    application.mol_canvas.update(vec![canvas::Message::Undo])
+                        .context("while handling application message Undo")?;
+                }
+                Message::Redo => {
+                    application.mol_canvas.update(vec![canvas::Message::Redo])
+                        .context("while handling application message Redo")?;
+                }
+                // windows aren't told where files are dropped, so they wait for the canvas to see the
+                // cursor, or for a moment to pass when it's not moved
+                Message::FileDropped(path) => {
+                    application.dropped.push(path);
+                    if application.dropped.len() == 1 {
+                        application.mol_canvas.update(vec![canvas::Message::AwaitDrop(true)])
+                            .context("while handling application message FileDropped")?;
+
+                        return Ok(Task::perform(background(|| std::thread::sleep(DROP_WAIT)), |()| Message::PlaceDropped(None)));
+                    }
+                }
+                Message::PlaceDropped(position) => {
+                    let dropped = std::mem::take(&mut application.dropped);
+                    if dropped.is_empty() {
+                        return Ok(Task::none());
+                    }
+                    application.mol_canvas.update(vec![canvas::Message::AwaitDrop(false)])
+                        .context("while handling application message PlaceDropped")?;
+
+                    let position = position.unwrap_or(application.mol_canvas.view_center());
+                    let tasks = dropped
+                        .iter()
+                        .map(|path| open_file(application, path, position))
+                        .collect::<Result<Vec<_>>>()
+                        .context("while handling application message PlaceDropped")?;
+
+                    return Ok(Task::batch(tasks));
+                }
+                Message::RelabelFocused => {
+                    if let Some((molecule_id, atom_id)) = application.mol_canvas.keyboard_focus() {
+                        let label = application.mol_canvas.atom_label(&molecule_id, &atom_id)
+                            .context("while handling application message RelabelFocused")?;
+
+                        return Ok(Task::done(Message::TextInputSpawn(label, molecule_id, atom_id, canvas::Message::RelabelAtom)));
+                    }
+                }
+                Message::CopyMolecule(molecule_id, format) => {
+                    application.mol_canvas.update(vec![canvas::Message::CloseContextMenu])
+                        .context("while handling application message CopyMolecule")?;
+
+                    // labels that can't be parsed are reported in the inspector rather than treated as a bug
+                    match application.mol_canvas.molecule_text(&molecule_id, format) {
+                        Ok(text) => {
+                            application.inspector.set_status(format!("Copied as {}", format));
+                            return Ok(clipboard::write(text));
+                        }
+                        Err(error) => application.inspector.set_status(format!("{:#}", error)),
+                    }
+                }
+                Message::CopySelection => {
+                    // labels that can't be written to an SD file are reported in the inspector
+                    // rather than treated as a bug
+                    match application.mol_canvas.copy_selection() {
+                        Ok(Some(copied)) => {
+                            application.inspector.set_status(format!("Copied {} molecules", copied.molecules().len()));
+                            let text = copied.text().to_string();
+                            application.copied = Some(copied);
+                            return Ok(clipboard::write(text));
+                        }
+                        Ok(None) => (),
+                        Err(error) => application.inspector.set_status(format!("{:#}", error)),
+                    }
+                }
+                Message::CopyImage => {
+                    // a drawing that can't be rendered or a clipboard that can't be opened is
+                    // reported in the inspector rather than treated as a bug
+                    let status = match copy_image(application) {
+                        Ok(count) => format!("Copied {} molecules as an image", count),
+                        Err(error) => format!("{:#}", error),
+                    };
+                    application.inspector.set_status(status);
+                }
+                Message::PasteRequested(position) => {
+                    // pasting into the relabel input, text editor, find bar or an inspector field isn't
+                    // for the canvas
+                    if application.text_input.is_none() && application.text_edit.is_none() && application.find.is_none() {
+                        return Ok(operate(unfocused())
+                            .then(move |()| clipboard::read().map(move |text| Message::Paste(text, position))));
+                    }
+                }
+                Message::Paste(text, position) => {
+                    let text = text.unwrap_or_default();
+
+                    // the clipboard may hold a picture of structures rather than text
+                    if text.trim().is_empty() {
+                        return paste_image(application, position).context("while handling application message Paste");
+                    }
+
+                    // molecules copied here are pasted whole rather than read back from the SD file
+                    if let Some(copied) = application.copied.as_ref().filter(|copied| copied.text() == text) {
+                        application.inspector.set_status(format!("Pasted {} molecules", copied.molecules().len()));
+                        application.mol_canvas.update(vec![canvas::Message::PasteMolecules(copied.clone(), position)])
+                            .context("while handling application message Paste")?;
+                        return Ok(Task::none());
+                    }
+
+                    // text that isn't SMILES or a molfile is reported in the inspector rather than treated as a bug
+                    match formats::parse_pasted(&text) {
+                        Ok(pasted) if !pasted.reactions.is_empty() => {
+                            application.inspector.set_status(format!("Pasted {} reactions", pasted.reactions.len()));
+                            application.mol_canvas.update(vec![canvas::Message::PasteReactions(pasted.reactions, position)])
+                                .context("while handling application message Paste")?;
+                        }
+                        Ok(pasted) if pasted.structures.is_empty() => {
+                            application.inspector.set_status("Clipboard has no structures to paste".to_string());
+                        }
+                        Ok(pasted) if pasted.ambiguous => {
+                            application.pending_paste = Some(PendingPaste {
+                                prompt: format!("Paste \"{}\" as SMILES?", text.trim().lines().next().unwrap_or_default()),
+                                structures: pasted.structures,
+                                position,
+                            });
+                        }
+                        Ok(pasted) => {
+                            application.inspector.set_status(format!("Pasted {} structures", pasted.structures.len()));
+                            application.mol_canvas.update(vec![canvas::Message::PasteStructures(pasted.structures, position)])
+                                .context("while handling application message Paste")?;
+                        }
+                        Err(error) => application.inspector.set_status(format!("Clipboard isn't SMILES or a molfile: {}", error)),
+                    }
+                }
+                Message::StructuresRecognized(structures, position) => match structures {
+                    Ok(Some(structures)) if !structures.is_empty() => {
+                        application.pending_paste = Some(PendingPaste {
+                            prompt: format!("Convert the {} structures recognised in the image?", structures.len()),
+                            structures,
+                            position,
+                        });
+                    }
+                    Ok(_) => {}
+                    Err(error) => application.inspector.set_status(error),
+                },
+                Message::ConfirmPaste => {
+                    if let Some(PendingPaste { structures, position, .. }) = application.pending_paste.take() {
+                        application.inspector.set_status(format!("Pasted {} structures", structures.len()));
+                        application.mol_canvas.update(vec![canvas::Message::PasteStructures(structures, position)])
+                            .context("while handling application message ConfirmPaste")?;
+                    }
+                }
+                Message::CancelPaste => {
+                    application.pending_paste = None;
+                }
+                Message::CloseRequested(id) => {
+                    // a session that can't be saved is reported and the window kept open once, so
+                    // closing again closes without it
+                    let saved = application
+                        .mol_canvas
+                        .session()
+                        .and_then(|session| session::save(&session))
+                        .context("while saving session");
+                    match saved {
+                        Err(error) if !application.unsaved_close => {
+                            application.unsaved_close = true;
+                            application
+                                .inspector
+                                .set_status(format!("{:#}; close again to quit without saving it", error));
+                        }
+                        _ => return Ok(window::close(id)),
+                    }
                 }
                 Message::Error(error) => {
                     panic!("{}", error)
@@ -125,30 +991,342 @@ impl Application {
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        Subscription::none()
+        let shortcuts = keyboard::on_key_press(|key, modifiers| match key {
+            Key::Character(c) if modifiers.command() => match c.as_str() {
+                "f" => Some(Message::FindOpen),
+                "z" if modifiers.shift() => Some(Message::Redo),
+                "c" if modifiers.shift() => Some(Message::CopyImage),
+                "c" => Some(Message::CopySelection),
+                "z" => Some(Message::Undo),
+                "y" => Some(Message::Redo),
+                "m" => Some(canvas::Message::SelectParentMolecule.into()),
+                "g" => Some(canvas::Message::GrowSelection.into()),
+                "0" => Some(canvas::Message::ZoomToFit.into()),
+                "1" => Some(canvas::Message::ZoomTo(1.0).into()),
+                _ => None,
+            },
+            _ => None,
+        });
+
+        let escape = if self.text_edit.is_some() {
+            keyboard::on_key_press(|key, _modifiers| match key {
+                Key::Named(Named::Escape) => Some(Message::TextEditCancel),
+                _ => None,
+            })
+        } else if self.text_input.is_some() {
+            keyboard::on_key_press(|key, _modifiers| match key {
+                Key::Named(Named::Escape) => Some(Message::TextInputCancel),
+                Key::Named(Named::Tab) => Some(Message::TextInputCompleteFirst),
+                _ => None,
+            })
+        } else if self.find.is_some() {
+            keyboard::on_key_press(|key, _modifiers| match key {
+                Key::Named(Named::Escape) => Some(Message::FindClose),
+                _ => None,
+            })
+        } else if self.pending_paste.is_some() {
+            keyboard::on_key_press(|key, _modifiers| match key {
+                Key::Named(Named::Escape) => Some(Message::CancelPaste),
+                Key::Named(Named::Enter) => Some(Message::ConfirmPaste),
+                _ => None,
+            })
+        } else {
+            keyboard::on_key_press(Self::keyboard_navigation)
+        };
+
+        let file_drop = event::listen_with(|event, _status, _window| match event {
+            Event::Window(window::Event::FileDropped(path)) => Some(Message::FileDropped(path)),
+            _ => None,
+        });
+
+        let animation = match self.mol_canvas.is_animating() {
+            true => window::frames().map(|now| canvas::Message::AnimationTick(now).into()),
+            false => Subscription::none(),
+        };
+
+        Subscription::batch([shortcuts, escape, file_drop, animation, window::close_requests().map(Message::CloseRequested)])
+    }
+
+    /// keys for drawing without a mouse, arrows move focus along bonds and draw bonds with shift,
+    /// and the toolbar's shortcuts pick tools
+    fn keyboard_navigation(key: Key, modifiers: keyboard::Modifiers) -> Option<Message> {
+        let direction = match key.as_ref() {
+            Key::Named(Named::ArrowRight) => Some(Vector::new(1.0, 0.0)),
+            Key::Named(Named::ArrowLeft) => Some(Vector::new(-1.0, 0.0)),
+            Key::Named(Named::ArrowUp) => Some(Vector::new(0.0, -1.0)),
+            Key::Named(Named::ArrowDown) => Some(Vector::new(0.0, 1.0)),
+            _ => None,
+        };
+
+        if modifiers.command() {
+            return None;
+        }
+
+        Some(match (key, direction) {
+            (_, Some(direction)) if modifiers.shift() => canvas::Message::SproutFromFocus(Some(direction)).into(),
+            (_, Some(direction)) => canvas::Message::MoveFocus(direction).into(),
+            (Key::Named(Named::Space), _) => canvas::Message::SproutFromFocus(None).into(),
+            (Key::Named(Named::Tab), _) if modifiers.shift() => canvas::Message::FocusPreviousMolecule.into(),
+            (Key::Named(Named::Tab), _) => canvas::Message::FocusNextMolecule.into(),
+            (Key::Named(Named::Enter), _) => Message::RelabelFocused,
+            (Key::Named(Named::Delete | Named::Backspace), _) => canvas::Message::DeleteFocused.into(),
+            (Key::Named(Named::Escape), _) => vec![canvas::Message::ClearFocus, canvas::Message::CloseContextMenu].into(),
+            (Key::Character(c), _) => Message::Toolbar(toolbar::Message::ToolChanged(toolbar::Tool::from_shortcut(c.as_str())?)),
+            _ => return None,
+        })
+    }
+
+    /// search box for atom labels, with the focused match out of the total, and a replace box below
+    fn find_bar(&self, query: &str) -> Element<'_, Message> {
+        let (current, total) = self.mol_canvas.search_position();
+
+        let find = row![
+            text_input("Find label", query)
+                .on_input(Message::FindChange)
+                .on_submit(Message::FindNext)
+                .id(self.find_input_id.clone())
+                .size(12)
+                .width(Length::Fill),
+            text(format!("{}/{}", current, total)).size(12),
+            button(text("<").size(12)).on_press_maybe((total > 0).then_some(Message::FindPrevious)),
+            button(text(">").size(12)).on_press_maybe((total > 0).then_some(Message::FindNext)),
+            button(text("x").size(12)).on_press(Message::FindClose),
+        ]
+        .spacing(5)
+        .align_y(Alignment::Center);
+
+        let replace = row![
+            text_input("Replace with", &self.replace)
+                .on_input(Message::ReplaceChange)
+                .on_submit(Message::ReplaceAll)
+                .size(12)
+                .width(Length::Fill),
+            checkbox("Selection only", self.replace_in_selection)
+                .on_toggle(Message::ReplaceInSelection)
+                .size(12)
+                .text_size(12),
+            button(text("Replace all").size(12)).on_press_maybe((!query.is_empty()).then_some(Message::ReplaceAll)),
+        ]
+        .spacing(5)
+        .align_y(Alignment::Center);
+
+        column![find, replace].spacing(5).into()
+    }
+
+    /// the relabel input, anchored over the atom being renamed
+    fn text_input_overlay(&self, input_handler: &InputHandler) -> Element<'_, Message> {
+        let InputHandler { placeholder, value, molecule_id, atom_id, .. } = input_handler.clone();
+        let scaling = self.mol_canvas.scaling();
+        let font_size = Token::FONT_SIZE * scaling;
+        let width = (value.chars().count().max(2) as f32 + 1.0) * font_size * 0.7 + 10.0;
+        let height = font_size + 10.0;
+
+        let text_input_id = self.text_input_id.clone();
+        let completions = Composition::completions(&value);
+        // labels are still accepted when they aren't chemistry, e.g. R groups, but are flagged
+        let problem = Composition::parse(&value).err().map(|error| error.to_string());
+
+        responsive(move |size| {
+            let position = match self.mol_canvas.atom_screen_position(&molecule_id, &atom_id, size) {
+                Ok(position) => position,
+                Err(_) => return self.mol_canvas.view(),
+            };
+
+            let text_input = text_input(&placeholder, &value)
+                .on_input(Message::TextInputChange)
+                .on_submit(Message::TextInputSubmit)
+                .id(text_input_id.clone())
+                .size(font_size)
+                .padding(4)
+                .width(Length::Fixed(width));
+            let text_input = CursorInput::new(text_input, &value, Message::TextInputCursor);
+
+            let dropdown = (!completions.is_empty()).then(|| {
+                let entries = completions.iter().map(|completion| {
+                    // abbreviations show what they stand for
+                    let preview = Abbreviation::from_label(completion).map(|abbreviation| {
+                        text(format!("{} {}", abbreviation.name, abbreviation.formula)).size(10).style(text::secondary)
+                    });
+
+                    button(row![text(completion.clone()).size(12)].push_maybe(preview).spacing(5).align_y(Alignment::Center))
+                        .style(button::text)
+                        .width(Length::Fill)
+                        .padding(2)
+                        .on_press(Message::TextInputComplete(completion.clone()))
+                        .into()
+                });
+
+                container(Column::with_children(entries))
+                    .style(container::rounded_box)
+                    .width(Length::Fixed(width.max(Self::COMPLETIONS_WIDTH)))
+            });
+            let feedback = problem.clone().map(|problem| text(problem).size(10).style(text::danger));
+
+            let input = column![text_input]
+                .push_maybe(dropdown)
+                .push_maybe(feedback)
+                .push(Self::formatting_toolbar(Message::TextInputInsert));
+            let anchored = container(input).padding(Padding {
+                top: (position.y - height / 2.0).max(0.0),
+                left: (position.x - font_size / 2.0).max(0.0),
+                ..Padding::ZERO
+            });
+
+            Stack::with_children(vec![self.mol_canvas.view(), anchored.into()]).into()
+        })
+        .into()
+    }
+
+    /// multi-line editor for free text, anchored with the middle of its top where the text starts
+    fn text_edit_overlay<'a>(&'a self, canvas: Element<'a, Message>, text_edit: &'a TextEdit) -> Element<'a, Message> {
+        let scaling = self.mol_canvas.scaling();
+        let font_size = Token::FONT_SIZE * scaling;
+        let longest = text_edit.content.lines().map(|line| line.chars().count()).max().unwrap_or_default();
+        let width = (longest.max(8) as f32 + 1.0) * font_size * 0.6 + 10.0;
+
+        let Some(position) = self.mol_canvas.target_screen_position(&text_edit.target, self.mol_canvas.viewport()) else {
+            return canvas;
+        };
+
+        // enter writes the text, shift+enter starts another line
+        let editor = text_editor(&text_edit.content)
+            .on_action(Message::TextEditAction)
+            .key_binding(|key_press| match key_press.key.as_ref() {
+                Key::Named(Named::Enter) if !key_press.modifiers.shift() => {
+                    Some(text_editor::Binding::Custom(Message::TextEditSubmit))
+                }
+                _ => text_editor::Binding::from_key_press(key_press),
+            })
+            .size(font_size)
+            .padding(4)
+            .width(width);
+
+        let input = column![container(editor).id(self.text_edit_id.clone()), Self::formatting_toolbar(Message::TextEditInsert)];
+        let anchored = container(input).padding(Padding {
+            top: (position.y - 4.0).max(0.0),
+            left: (position.x - width / 2.0).max(0.0),
+            ..Padding::ZERO
+        });
+
+        Stack::with_children(vec![canvas, anchored.into()]).into()
     }
 
-    fn view(&self) -> Element<Message> {
+    /// copy actions for a right clicked molecule, shown where it was clicked
+    fn context_menu_overlay(canvas: Element<'_, Message>, molecule_id: MoleculeId, position: Point) -> Element<'_, Message> {
+        let entries = canvas::CopyFormat::ALL.map(|format| {
+            button(text(format!("Copy as {}", format)).size(12))
+                .style(button::text)
+                .width(Length::Fill)
+                .on_press(Message::CopyMolecule(molecule_id, format))
+                .into()
+        });
+
+        let menu = container(Column::with_children(entries))
+            .style(container::rounded_box)
+            .padding(2)
+            .width(Length::Fixed(Self::CONTEXT_MENU_WIDTH));
+        let anchored = container(menu).padding(Padding {
+            top: position.y,
+            left: position.x,
+            ..Padding::ZERO
+        });
+
+        Stack::with_children(vec![canvas, anchored.into()]).into()
+    }
+
+    /// asks whether text that may not be SMILES should be pasted, along the bottom of the canvas
+    fn paste_confirmation<'a>(canvas: Element<'a, Message>, pending_paste: &PendingPaste) -> Element<'a, Message> {
+        let toast = container(
+            row![
+                text(pending_paste.prompt.clone()).size(12),
+                button(text("Paste").size(12)).on_press(Message::ConfirmPaste),
+                button(text("Cancel").size(12)).on_press(Message::CancelPaste),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+        )
+        .style(container::rounded_box)
+        .padding(5);
+
+        let anchored = container(toast).center_x(Length::Fill).align_bottom(Length::Fill).padding(10);
+
+        Stack::with_children(vec![canvas, anchored.into()]).into()
+    }
+
+    /// markup shortcuts shown under the relabel input and text editor
+    fn formatting_toolbar(on_insert: fn(String) -> Message) -> Element<'static, Message> {
+        let format_button = |label: String, insert: String| {
+            button(text(label).size(10))
+                .padding(Padding::new(2.0))
+                .on_press(on_insert(insert))
+                .into()
+        };
+
+        let markup = [("x²", "^"), ("x₂", "_"), ("I", "*")]
+            .into_iter()
+            .map(|(label, insert)| format_button(label.to_string(), insert.to_string()));
+
+        let greek = GREEK_LETTERS
+            .iter()
+            .take(4)
+            .map(|(_name, letter)| format_button(letter.to_string(), letter.to_string()));
+
+        Row::with_children(markup.chain(greek)).spacing(2).into()
+    }
+
+    fn view(&self) -> Element<'_, Message> {
         let canvas = match &self.text_input {
-            Some(InputHandler { placeholder, value, .. }) => {
-                let text_input = text_input(placeholder, value)
-                    .on_input(Message::TextInputChange)
-                    .on_submit(Message::TextInputSubmit)
-                    .id(self.text_input_id.clone());
-
-                Stack::with_children(vec!(
-                        self.mol_canvas.view(),
-                        text_input.into()
-                )).into()
-            }
-            None => {
-                self.mol_canvas.view()
-            }
+            Some(input_handler) => self.text_input_overlay(input_handler),
+            None => self.mol_canvas.view(),
+        };
+        let canvas = match &self.text_edit {
+            Some(text_edit) => self.text_edit_overlay(canvas, text_edit),
+            None => canvas,
+        };
+        let canvas = match self.mol_canvas.context_menu() {
+            Some((molecule_id, position)) => Self::context_menu_overlay(canvas, molecule_id, position),
+            None => canvas,
+        };
+        let canvas = match &self.pending_paste {
+            Some(pending_paste) => Self::paste_confirmation(canvas, pending_paste),
+            None => canvas,
+        };
+        let canvas = match self.toolbar.periodic_table() {
+            Some(periodic_table) => Stack::with_children(vec![canvas, periodic_table.map(Message::Toolbar)]).into(),
+            None => canvas,
+        };
+        let canvas = match &self.find {
+            Some(query) => column![self.find_bar(query), canvas].spacing(5).into(),
+            None => canvas,
         };
 
         let toolbar = self.toolbar.view().map(Message::Toolbar);
 
-        let content = row![toolbar, canvas];
+        let inspector = self.inspector
+            .view(
+                self.mol_canvas.selected_molecule(),
+                self.mol_canvas.selected_item(),
+                self.mol_canvas
+                    .selected_molecule()
+                    .is_some_and(|(molecule_id, _)| self.mol_canvas.is_instance(&molecule_id)),
+                self.mol_canvas.style(),
+                self.mol_canvas.layers(),
+                self.mol_canvas.active_layer(),
+                self.mol_canvas.background(),
+                self.mol_canvas.selected_shape(),
+                self.mol_canvas.tags(),
+                self.mol_canvas.tag_filter(),
+                self.mol_canvas.versions(),
+                self.mol_canvas.compared_version(),
+                self.mol_canvas.named_molecules(),
+                self.mol_canvas.summary().description.clone(),
+                self.mol_canvas.summary().unbalanced_atom_maps.clone(),
+                self.mol_canvas.summary().mass_imbalances.iter().map(|(_, imbalance)| imbalance.clone()).collect(),
+                self.mol_canvas.summary().routes.clone(),
+            )
+            .map(Message::Inspector);
+
+        let content = row![toolbar, canvas, inspector];
 
         container(content).padding(5).into()
     }
@@ -160,4 +1338,89 @@ impl Default for Application {
     }
 }
 
+/// writes edited text to what it was opened on, unless it's unchanged
+fn submit_text(application: &mut Application, text_edit: TextEdit) -> Result<()> {
+    let text = text_edit.text();
+    if text == text_edit.original {
+        return Ok(());
+    }
+
+    if let Some(message) = text_edit.target.write(text) {
+        application.mol_canvas.update(vec![message]).context("while writing edited text")?;
+    }
+
+    Ok(())
+}
+
+/// focuses what's focusable inside a container, and takes focus from everything else
+fn focus_within<T: Send + 'static>(id: container::Id) -> impl Operation<T> {
+    struct FocusWithin {
+        id: widget::Id,
+        inside: bool,
+    }
+
+    impl<T> Operation<T> for FocusWithin {
+        fn focusable(&mut self, state: &mut dyn Focusable, _id: Option<&widget::Id>) {
+            match self.inside {
+                true => state.focus(),
+                false => state.unfocus(),
+            }
+        }
+
+        fn container(
+            &mut self,
+            id: Option<&widget::Id>,
+            _bounds: Rectangle,
+            operate_on_children: &mut dyn FnMut(&mut dyn Operation<T>),
+        ) {
+            let entered = !self.inside && id == Some(&self.id);
+            self.inside |= entered;
+            operate_on_children(self);
+            self.inside &= !entered;
+        }
+    }
+
+    FocusWithin { id: id.into(), inside: false }
+}
+
+/// finishes only when no text input has focus, as the canvas sees keys typed into them too
+fn unfocused() -> impl Operation<()> {
+    struct Unfocused {
+        focused: bool,
+    }
+
+    impl Operation<()> for Unfocused {
+        fn focusable(&mut self, state: &mut dyn Focusable, _id: Option<&widget::Id>) {
+            self.focused |= state.is_focused();
+        }
 
+        fn container(
+            &mut self,
+            _id: Option<&widget::Id>,
+            _bounds: Rectangle,
+            operate_on_children: &mut dyn FnMut(&mut dyn Operation<()>),
+        ) {
+            operate_on_children(self);
+        }
+
+        fn finish(&self) -> Outcome<()> {
+            match self.focused {
+                true => Outcome::None,
+                false => Outcome::Some(()),
+            }
+        }
+    }
+
+    Unfocused { focused: false }
+}
+
+/// runs blocking work, like waiting on a command, on a thread of its own rather than the window's
+/// or the executor's
+async fn background<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> T {
+    let (sender, receiver) = iced::futures::channel::oneshot::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(work());
+    });
+
+    receiver.await.expect("background work panicked")
+}
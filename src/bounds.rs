@@ -58,13 +58,9 @@ impl Bounds {
         iced::Point::new(center.x, center.y)
     }
 
-    pub fn contains(&self, point: iced::Point) -> bool {
-        let point = Point::new(point.x, point.y);
-
-        let point = self.transform().inverse().unwrap().transform_point(point);
-
-        Rectangle::with_size(self.size)
-            .contains(iced::Point::new(point.x, point.y))
+    /// whether the point is inside the bounds or no further than the padding from their edges
+    pub fn contains_within(&self, point: iced::Point, padding: f32) -> bool {
+        self.distance_to(point) <= padding
     }
 
     pub fn draw(&self, frame: &mut Frame, stroke: Stroke) {
@@ -74,6 +70,16 @@ impl Bounds {
         frame.stroke(&path, stroke);
     }
 
+    /// smallest axis-aligned rectangle containing the bounds
+    pub fn bounding_rectangle(&self) -> Rectangle {
+        let (min, max) = self.points().fold(
+            (Point::new(f32::MAX, f32::MAX), Point::new(f32::MIN, f32::MIN)),
+            |(min, max), point| (min.min(point), max.max(point)),
+        );
+
+        Rectangle::new(iced::Point::new(min.x, min.y), Size::new(max.x - min.x, max.y - min.y))
+    }
+
     /// Returns the smallest axis-aligned rectangle that contains both bounds
     pub fn union(&self, bounds: &Bounds) -> Bounds {
         let mut points = self.points().chain(bounds.points());
@@ -82,17 +88,8 @@ impl Bounds {
         let mut max_corner = min_corner;
 
         let expand_bounds = |min_corner: &mut Point, max_corner: &mut Point, point: Point| {
-            if point.x < min_corner.x {
-                min_corner.x = point.x;
-            } else if point.y < min_corner.y {
-                min_corner.y = point.y;
-            }
-
-            if point.x > max_corner.x {
-                max_corner.x = point.x;
-            } else if point.y > max_corner.y {
-                max_corner.y = point.y;
-            }
+            *min_corner = min_corner.min(point);
+            *max_corner = max_corner.max(point);
         };
 
         for point in points {
@@ -108,26 +105,39 @@ impl Bounds {
     }
 
     pub fn intersects(&self, rect: &Rectangle) -> bool {
-        // comparisons to tell if point is inside rect in an axis
-        for cmp in [
-            |point: Point, rect: &Rectangle| point.x > rect.x,
-            |point: Point, rect: &Rectangle| point.y > rect.y,
-            |point: Point, rect: &Rectangle| point.x < rect.x + rect.width,
-            |point: Point, rect: &Rectangle| point.y < rect.y + rect.height,
-        ] {
-            let mut outside_line = true;
-            for point in self.points() {
-                if cmp(point, rect) {
-                    outside_line = false;
-                }
-            }
+        self.intersects_bounds(&Bounds::from(*rect))
+    }
 
-            if outside_line {
-                return false;
-            };
-        }
+    /// whether two bounds overlap, touching edges not counting, by looking for a gap between
+    /// them along the edges of either one
+    pub fn intersects_bounds(&self, bounds: &Bounds) -> bool {
+        let project = |bounds: &Bounds, axis: Vector<f32>| {
+            bounds.points().map(|point| point.to_vector().dot(axis)).fold((f32::MAX, f32::MIN), |(min, max), value| {
+                (min.min(value), max.max(value))
+            })
+        };
 
-        true
+        !self.axes().into_iter().chain(bounds.axes()).any(|axis| {
+            let (self_min, self_max) = project(self, axis);
+            let (min, max) = project(bounds, axis);
+
+            self_max <= min || max <= self_min
+        })
+    }
+
+    /// directions of the edges
+    fn axes(&self) -> [Vector<f32>; 2] {
+        let transform = self.transform();
+
+        [Vector::new(1.0, 0.0), Vector::new(0.0, 1.0)].map(|axis| transform.transform_vector(axis))
+    }
+
+    /// shortest distance from the point to the bounds, zero when it's inside
+    pub fn distance_to(&self, point: iced::Point) -> f32 {
+        let local = self.transform().inverse().unwrap().transform_point(Point::new(point.x, point.y));
+        let nearest = local.clamp(Point::zero(), Point::new(self.size.width, self.size.height));
+
+        (local - nearest).length()
     }
 
     pub fn is_contained(&self, rect: &Rectangle) -> bool {
@@ -170,3 +180,43 @@ impl Add<iced::Vector> for Bounds {
         }
     }
 }
+
+/// shortest distance from a point to a segment
+pub fn distance_to_segment(point: iced::Point, start: iced::Point, end: iced::Point) -> f32 {
+    let direction = end - start;
+    let length_squared = direction.x.powi(2) + direction.y.powi(2);
+    if length_squared < f32::EPSILON {
+        return point.distance(start);
+    }
+
+    let along = (point - start).x * direction.x + (point - start).y * direction.y;
+    let t = (along / length_squared).clamp(0.0, 1.0);
+
+    point.distance(start + direction * t)
+}
+
+/// whether any part of the segment lies inside the rectangle
+pub fn segment_crosses_rectangle(start: iced::Point, end: iced::Point, rectangle: &Rectangle) -> bool {
+    // clip the segment against each pair of edges in turn
+    let direction = end - start;
+    let mut entry: f32 = 0.0;
+    let mut exit: f32 = 1.0;
+
+    for (delta, low, high, origin) in [
+        (direction.x, rectangle.x, rectangle.x + rectangle.width, start.x),
+        (direction.y, rectangle.y, rectangle.y + rectangle.height, start.y),
+    ] {
+        if delta.abs() < f32::EPSILON {
+            if origin < low || origin > high {
+                return false;
+            }
+            continue;
+        }
+
+        let (a, b) = ((low - origin) / delta, (high - origin) / delta);
+        entry = entry.max(a.min(b));
+        exit = exit.min(a.max(b));
+    }
+
+    entry < exit
+}
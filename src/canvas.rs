@@ -1,25 +1,87 @@
+use std::f32::consts::PI;
+use std::time::Instant;
+
 use anyhow::{Context, Result};
 use derive_more::derive::{Add, AddAssign, Deref, Mul, MulAssign};
+use iced::keyboard;
 use iced::mouse;
 use iced::widget::canvas;
 use iced::widget::canvas::event::{self, Event};
+use iced::widget::canvas::path::lyon_path::math::Transform;
 use iced::widget::canvas::Stroke;
 use iced::widget::canvas::Style;
-use iced::widget::canvas::{Cache, Canvas, Frame, Geometry, Path};
-use iced::{Color, Element, Fill, Point, Rectangle, Renderer, Size, Theme, Vector};
+use iced::alignment::{Horizontal, Vertical};
+use iced::widget::canvas::{Cache, Canvas, Frame, Geometry, LineCap, LineDash, LineJoin, Path, Text};
+use iced::widget::{svg, Stack};
+use iced::{font, Color, Element, Fill, Font, Pixels, Point, Rectangle, Renderer, Size, Theme, Vector};
+use rustc_hash::{FxHashMap, FxHashSet};
 
+mod animation;
+mod annotation;
+mod arrange;
+mod background;
+mod clipart;
+mod clipboard;
+mod description;
+mod document_style;
 mod event_handler;
+mod export;
+mod history;
+mod ink;
+mod instance;
+mod interaction;
+mod layer;
+mod numbering;
+mod orbital;
+mod reaction;
+mod route;
+mod search;
 mod selection;
+mod shape;
 mod state;
+mod summary;
+mod tag_filter;
+mod timeline;
 
 use crate::application;
+use crate::lookup;
+use crate::session::{self, Session, SessionAnnotation, SessionBend, SessionCompound, SessionInk, SessionLayer, SessionOrbital, SessionSelection, SessionShape, SessionVersion};
 use crate::bounds::Bounds;
-use crate::molecule::{AtomId, AtomPosition, Bond, BondId, BondType, Molecule, MoleculeId};
+use crate::formats::{self, ReferenceImage, Structure, SvgWriter};
+use crate::chemistry::{find_functional_groups_in, mass_imbalance, Descriptors, FunctionalGroupMatch, MassImbalance, MolecularGraph};
+use crate::molecule::{
+    draw_bond, Atom, AtomId, AtomPosition, Bond, BondEnd, BondId, BondType, ChargeBracket, DisplayMode,
+    AnnotationId, InkId, InteractionId, LayerId, Molecule, MoleculeId, OrbitalId, ShapeId, TextOutline,
+};
+use crate::surface::Surface;
+use crate::thumbnail;
 use crate::toolbar::Tool;
 use event_handler::handle_event;
+pub use annotation::TextTarget;
+pub use arrange::Arrangement;
+pub use background::{Background, BackgroundDrag};
+pub use clipart::Clipart;
+pub use clipboard::Clipboard;
+pub use document_style::{DashPattern, DocumentStyle, LabelFont};
 pub use event_handler::{Action, MouseInteraction};
+pub use export::CopyFormat;
+pub use ink::InkColor;
+pub use layer::Layer;
+use orbital::Orbital;
+pub use orbital::OrbitalKind;
 pub use selection::{HoverSelection, Selection, SingleSelection};
+pub use shape::{ArrowSide, Shape, ShapeDrag, ShapeKind, ShapeStyle};
+pub use summary::Summary;
+pub use tag_filter::TagFilter;
+pub use timeline::Version;
+use animation::ViewAnimation;
+use background::BackgroundLayer;
+use history::{History, Snapshot};
+use ink::Ink;
+use interaction::InteractionKind;
+use search::Search;
 use state::State;
+use timeline::Timeline;
 
 #[derive(Default, Debug)]
 pub struct MolCanvas {
@@ -29,6 +91,35 @@ pub struct MolCanvas {
     action: Action,
     translation: Vector,
     scaling: Scaling,
+    annotate_functional_groups: bool,
+    /// number each reaction arrow by its step in its synthesis route
+    route_mode: bool,
+    search: Search,
+    history: History,
+    /// atom that keyboard navigation moves from and draws bonds onto
+    keyboard_focus: Option<AtomId>,
+    /// transition to a new view in progress, advanced every frame
+    animation: Option<ViewAnimation>,
+    /// size of the canvas widget when it last handled an event
+    viewport: Size,
+    timeline: Timeline,
+    /// version whose differences from the document are drawn over it
+    compared_version: Option<usize>,
+    /// molecule that was right clicked and where, while its copy menu is open
+    context_menu: Option<(MoleculeId, Point)>,
+    /// where the last tap was and how many times tapping there has moved on to the next item
+    tap_cycle: Option<(Point, usize)>,
+    /// atom the last bond drawn ended on, which a press on empty canvas next to it draws on from
+    chain_end: Option<(MoleculeId, AtomId)>,
+    /// modifier keys held, alt makes rectangle selections take molecules they touch
+    modifiers: keyboard::Modifiers,
+    /// image traced over, which isn't part of the document so undoing leaves it be
+    background: Option<Background>,
+    /// shape picked out for moving, resizing and restyling, apart from the molecule selection
+    selected_shape: Option<ShapeId>,
+    summary: Summary,
+    /// files were dropped, windows aren't told where, so they wait for the next cursor event
+    awaiting_drop: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Add, AddAssign, Mul, MulAssign, Deref)]
@@ -43,6 +134,7 @@ impl Default for Scaling {
 #[derive(Debug, Clone)]
 pub enum Message {
     AddMoleculeWithAtom(MoleculeId, AtomId, String, Point),
+    #[allow(dead_code)]
     AddAtom(MoleculeId, AtomId, String, Point),
     FinishBond(MoleculeId, AtomId, Point, BondType),
     NewBond(MoleculeId, AtomId, AtomId, BondType),
@@ -53,6 +145,46 @@ pub enum Message {
     DeleteMolecule(MoleculeId),
     DeleteAtom(MoleculeId, AtomId),
     DeleteBond(MoleculeId, BondId),
+    /// links two atoms, usually of different molecules, leaving the molecules separate
+    AddInteraction(AtomId, AtomId, InteractionKind),
+    DeleteInteraction(InteractionId),
+    /// gives two atoms on either side of a reaction arrow the same map number
+    MapAtoms(AtomId, AtomId),
+    /// takes an atom's map number off it and the atoms it was shared with
+    UnmapAtom(AtomId),
+    SetAtomMap(MoleculeId, AtomId, Option<u32>),
+    /// draws an orbital on an atom, or a π overlap across its bond to the second atom
+    AddOrbital(OrbitalKind, AtomId, Option<AtomId>),
+    DeleteOrbital(OrbitalId),
+    /// points an orbital at the cursor while its handle is dragged
+    TurnOrbital(OrbitalId, Point),
+    /// draws a freehand stroke through the points with the document's ink width and colour
+    AddInk(Vec<Point>),
+    DeleteInk(InkId),
+    /// draws a shape between two points with the document's ink width and colour, selecting it
+    AddShape(ShapeKind, Point, Point),
+    /// adds clipart at its usual size, scaled with the document's bond length, in the middle of
+    /// the view
+    InsertClipart(Clipart),
+    DeleteShape(ShapeId),
+    SetShapeStyle(ShapeId, ShapeStyle),
+    /// the yield and conditions written under an arrow
+    SetReactionConditions(ShapeId, formats::ReactionConditions),
+    /// writes text over or under an arrow, lengthening the arrow to fit it
+    SetArrowText(ShapeId, ArrowSide, String),
+    SelectShape(Option<ShapeId>),
+    /// writes text at a position on the annotation layer
+    AddAnnotation(Point, String),
+    SetAnnotationText(AnnotationId, String),
+    DeleteAnnotation(AnnotationId),
+    /// moves the text being dragged along with the cursor
+    DragAnnotation(Point),
+    RouteMode(bool),
+    /// lays out each synthesis route in place, its longest chain of steps in a row and the
+    /// branches leading into it in columns
+    ArrangeRoutes,
+    /// moves the part of the selected shape being dragged to the cursor
+    DragShape(Point),
     MoveSelection(Point),
     NewSelection(Selection),
     // MoveMolecule(MoleculeId, Point),
@@ -62,16 +194,260 @@ pub enum Message {
     ActionChanged(Action),
     Translated(Vector),
     Scaled(Scaling, Option<Vector>),
+    AnnotateFunctionalGroups(bool),
+    LewisStructures(bool),
+    BondCrossings(bool),
+    ExportMargin(f32),
+    /// sets how many pixels to a canvas unit pngs and copied images are rendered at
+    ExportResolution(f32),
+    /// puts an image centred on a point to trace over, replacing any there was
+    ImportBackground(ReferenceImage, Point),
+    RemoveBackground,
+    BackgroundOpacity(f32),
+    BackgroundExported(bool),
+    BackgroundLocked(bool),
+    /// drags the background to a canvas position, as the action says
+    DragBackground(Point),
+    PartialBondDash(DashPattern),
+    ChangeLabelFont(LabelFont),
+    ChangeDisplayMode(MoleculeId, DisplayMode),
+    SetBondLength(MoleculeId, BondId, f32),
+    SetBondAngle(MoleculeId, AtomId, f32),
+    /// changes the document bond length, scaling every molecule to match
+    SetDocumentBondLength(f32),
+    /// scales the selection around its middle, e.g. after importing a file drawn at another scale
+    ScaleSelection(f32),
+    FlipFragment(MoleculeId, BondId),
+    ResolveOverlaps,
+    /// lines up the selected molecules, or all of them, with the document's spacing
+    Arrange(Arrangement),
+    NewLayer(String),
+    SetActiveLayer(LayerId),
+    SetLayerVisible(LayerId, bool),
+    SetLayerLocked(LayerId, bool),
+    MoveToLayer(MoleculeId, LayerId),
+    SetMoleculeLocked(MoleculeId, bool),
+    UnlockAll,
+    Find(String),
+    FindNext,
+    FindPrevious,
+    ClearFind,
+    /// replaces text in atom labels, within the selection when the flag is set
+    ReplaceLabels(String, String, bool),
+    Undo,
+    Redo,
+    /// the molecules, shapes and text of a document saved as a project, centred on a point
+    ImportProject(Box<Session>, Point),
+    /// structures placed side by side, centred on a point
+    PasteStructures(Vec<Structure>, Point),
+    /// reactions laid out around a new arrow each, from a point down
+    PasteReactions(Vec<formats::Reaction>, Point),
+    /// pastes molecules copied from this or another document at a canvas position
+    PasteMolecules(Clipboard, Point),
+    /// compounds looked up from a reference, placed in view and named after their records
+    InsertCompounds(Vec<lookup::Compound>),
+    FocusNextMolecule,
+    FocusPreviousMolecule,
+    /// moves keyboard focus along the bond pointing closest to the direction
+    MoveFocus(Vector),
+    /// draws a bond from the focused atom, in the least crowded direction when none is given
+    SproutFromFocus(Option<Vector>),
+    DeleteFocused,
+    ClearFocus,
+    /// animates the view to show every visible molecule
+    ZoomToFit,
+    /// animates to a scaling, keeping the middle of the view in place
+    ZoomTo(f32),
+    AnimationTick(Instant),
+    ViewportResized(Size),
+    /// keeps a named copy of the document, numbered when the name is blank
+    SaveVersion(String),
+    RestoreVersion(usize),
+    CompareVersion(Option<usize>),
+    DeleteVersion(usize),
+    ReattachBond(MoleculeId, BondId, BondEnd, MoleculeId, AtomId),
+    DetachBond(MoleculeId, BondId, BondEnd, Point),
+    /// bends a bond through a point, straightening it when the point is on the straight bond
+    BendBond(MoleculeId, BondId, Point),
+    /// curves a bond to the side by a fraction of its length, zero straightening it
+    CurveBond(MoleculeId, BondId, f32),
+    /// names a molecule, a blank name removes it
+    RenameMolecule(MoleculeId, String),
+    /// sets the legend under a molecule, a blank caption removes it
+    SetCaption(MoleculeId, String),
+    /// compound metadata, a blank value removes it
+    SetCas(MoleculeId, String),
+    SetNotes(MoleculeId, String),
+    SetTags(MoleculeId, Vec<String>),
+    SetSource(MoleculeId, String),
+    /// picks or drops a tag to filter molecules by
+    FilterTag(String, bool),
+    /// hides molecules that don't match the tag filter rather than dimming them
+    HideUnmatched(bool),
+    MoleculeNames(bool),
+    CompoundNumbers(bool),
+    InteractionDistances(bool),
+    InkWidth(f32),
+    InkColor(InkColor),
+    /// sets the text beside a bond, a blank annotation removes it
+    AnnotateBond(MoleculeId, BondId, String),
+    /// marks or unmarks a retrosynthetic disconnection across a bond
+    MarkDisconnection(MoleculeId, BondId, bool),
+    BondAnnotations(bool),
+    MetalCounts(bool),
+    /// whether a molecule shares the compound number of the one before it, lettered as a series
+    LetterWithPrevious(MoleculeId, bool),
+    /// adds, changes or removes the brackets and overall charge around a molecule
+    SetChargeBracket(MoleculeId, Option<ChargeBracket>),
+    /// sets the space between a molecule and its charge bracket
+    ResizeChargeBracket(MoleculeId, f32),
+    /// adds a linked copy of a molecule beside it, which follows every edit made to the molecule
+    AddInstance(MoleculeId),
+    UnlinkInstance(MoleculeId),
+    /// selects a molecule and animates the view to it
+    JumpToMolecule(MoleculeId),
+    /// widens the selection from atoms and bonds to the molecules they're part of
+    SelectParentMolecule,
+    /// selects the smaller fragment on one side of a bond along with the bond, or only the bond
+    /// when it's in a ring
+    SelectSubstituent(MoleculeId, BondId),
+    /// adds the atoms and bonds one bond out from the selection
+    GrowSelection,
+    /// opens the copy menu on a molecule at a position on screen
+    OpenContextMenu(MoleculeId, Point),
+    CloseContextMenu,
+    /// records a tap and how deep into the items under it the hover target has been moved
+    CycleHover(Point, usize),
+    ModifiersChanged(keyboard::Modifiers),
+    /// starts or stops waiting to see where the cursor is to place dropped files there
+    AwaitDrop(bool),
+}
+
+impl Message {
+    /// whether the message edits the document in a single step that undo should return from,
+    /// relabelling as it is typed and dragging are left out as they arrive continuously
+    fn is_undoable(&self) -> bool {
+        matches!(
+            self,
+            Message::AddMoleculeWithAtom(..)
+                | Message::AddAtom(..)
+                | Message::AddInstance(..)
+                | Message::UnlinkInstance(..)
+                | Message::FinishBond(..)
+                | Message::NewBond(..)
+                | Message::ChangeBondType(..)
+                | Message::FlipBond(..)
+                | Message::ConnectMolecules(..)
+                | Message::DeleteMolecule(..)
+                | Message::DeleteAtom(..)
+                | Message::DeleteBond(..)
+                | Message::AddInteraction(..)
+                | Message::DeleteInteraction(..)
+                | Message::MapAtoms(..)
+                | Message::UnmapAtom(_)
+                | Message::SetAtomMap(..)
+                | Message::AddOrbital(..)
+                | Message::DeleteOrbital(..)
+                | Message::AddInk(..)
+                | Message::DeleteInk(..)
+                | Message::AddShape(..)
+                | Message::InsertClipart(_)
+                | Message::DeleteShape(..)
+                | Message::SetShapeStyle(..)
+                | Message::SetReactionConditions(..)
+                | Message::SetArrowText(..)
+                | Message::AddAnnotation(..)
+                | Message::SetAnnotationText(..)
+                | Message::RenameMolecule(..)
+                | Message::SetCaption(..)
+                | Message::SetCas(..)
+                | Message::SetNotes(..)
+                | Message::SetTags(..)
+                | Message::SetSource(..)
+                | Message::AnnotateBond(..)
+                | Message::DeleteAnnotation(..)
+                | Message::ArrangeRoutes
+                | Message::MarkDisconnection(..)
+                | Message::ChangeDisplayMode(..)
+                | Message::SetBondLength(..)
+                | Message::SetBondAngle(..)
+                | Message::SetDocumentBondLength(_)
+                | Message::ScaleSelection(_)
+                | Message::FlipFragment(..)
+                | Message::ResolveOverlaps
+                | Message::Arrange(_)
+                | Message::MoveToLayer(..)
+                | Message::ReplaceLabels(..)
+                | Message::ImportProject(..)
+                | Message::PasteStructures(..)
+                | Message::PasteReactions(..)
+                | Message::PasteMolecules(..)
+                | Message::InsertCompounds(..)
+                | Message::SproutFromFocus(..)
+                | Message::DeleteFocused
+                | Message::RestoreVersion(..)
+                | Message::LetterWithPrevious(..)
+                | Message::SetChargeBracket(..)
+                | Message::ReattachBond(..)
+                | Message::DetachBond(..)
+                | Message::BendBond(..)
+                | Message::CurveBond(..)
+        )
+    }
+
+    /// whether the message only changes the view, tool or selection, leaving the document and what
+    /// is shown of it as they were
+    fn is_view_only(&self) -> bool {
+        matches!(
+            self,
+            Message::SelectShape(_)
+                | Message::NewSelection(_)
+                | Message::ToolChanged(_)
+                | Message::ActionChanged(_)
+                | Message::Translated(_)
+                | Message::Scaled(..)
+                | Message::Find(_)
+                | Message::FindNext
+                | Message::FindPrevious
+                | Message::ClearFind
+                | Message::FocusNextMolecule
+                | Message::FocusPreviousMolecule
+                | Message::MoveFocus(_)
+                | Message::ClearFocus
+                | Message::ZoomToFit
+                | Message::ZoomTo(_)
+                | Message::AnimationTick(_)
+                | Message::ViewportResized(_)
+                | Message::CompareVersion(_)
+                | Message::JumpToMolecule(_)
+                | Message::SelectParentMolecule
+                | Message::SelectSubstituent(..)
+                | Message::GrowSelection
+                | Message::OpenContextMenu(..)
+                | Message::CloseContextMenu
+                | Message::CycleHover(..)
+                | Message::ModifiersChanged(_)
+                | Message::AwaitDrop(_)
+        )
+    }
 }
 
 impl MolCanvas {
     const MIN_SCALING: Scaling = Scaling(0.1);
     const MAX_SCALING: Scaling = Scaling(5.0);
+    /// how far text outlines flattened to find their bounds may stray from their curves, in
+    /// canvas units, a tenth of a logical pixel at the closest zoom so label bounds and the hit
+    /// tests made with them hold however far in the view is
+    pub const TEXT_TOLERANCE: f32 = 0.1 / Self::MAX_SCALING.0;
+    /// fraction of the view that zooming to fit fills
+    const FIT_MARGIN: f32 = 0.9;
 
     pub const MOLECULE_PADDING: f32 = 3.0;
     pub const ATOM_PADDING: f32 = 3.0;
     pub const BOND_PADDING: f32 = 3.0;
 
+    /// bond length of new documents, which structures are read in at before being scaled to the
+    /// document's
     pub const BOND_LENGTH: f32 = 30.0;
     pub const BOND_WIDTH: f32 = 1.0;
     pub const BOND_OFFSETS: f32 = 2.0;
@@ -82,8 +458,96 @@ impl MolCanvas {
     pub const DASH_BOND_OFFSETS: f32 = 4.0;
     pub const H_BOND_WIDTH: f32 = 3.0;
     pub const H_BOND_OFFSETS: f32 = 4.0;
+    pub const BOLD_WIDTH: f32 = 3.0;
+    pub const WAVY_PERIOD: f32 = 4.0;
+    pub const WAVY_AMPLITUDE: f32 = 1.0;
+    pub const DATIVE_HEAD_LENGTH: f32 = 4.0;
+    pub const DATIVE_HEAD_WIDTH: f32 = 3.0;
+
+    /// in logical pixels, so handles are the same size on screen at any zoom
+    pub const HANDLE_RADIUS: f32 = 3.0;
+    /// logical pixels beyond an item's bounds that still hit it, keeping small items easy to
+    /// pick when zoomed out
+    pub const HIT_TOLERANCE: f32 = 2.0;
+    pub const FOCUS_RADIUS: f32 = 8.0;
+
+    /// angle within which a dragged bond snaps onto the open sector bisector
+    pub const BISECTOR_SNAP_ANGLE: f32 = PI / 12.0;
+    /// dragged bonds away from the bisector point in steps of this angle
+    pub const BOND_ANGLE_STEP: f32 = PI / 12.0;
+
+    pub const BRIDGE_GAP: f32 = 3.0;
+    pub const BRIDGE_WIDTH: f32 = 10.0;
+
+    pub const FUNCTIONAL_GROUP_RADIUS: f32 = 6.0;
+
+    pub const ELECTRON_RADIUS: f32 = 0.6;
+    pub const ELECTRON_SEPARATION: f32 = 2.0;
+    pub const LONE_PAIR_DISTANCE: f32 = 7.0;
+    const COMPOUND_NUMBER_SIZE: f32 = 10.0;
+    /// space left around the drawing in exported images, unless the document sets its own
+    pub const EXPORT_MARGIN: f32 = 4.0;
+    /// pixels to a canvas unit in pngs and copied images, unless the document sets its own
+    pub const EXPORT_RESOLUTION: f32 = 4.0;
+    /// most pixels to a canvas unit a document can set, as a page drawn finer is too big to render
+    pub const MAX_EXPORT_RESOLUTION: f32 = 32.0;
+    /// space left between arranged molecules, in bond lengths
+    const ARRANGE_SPACING: f32 = 1.0;
+    /// space between the lines of text stacked under a molecule
+    const CAPTION_SPACING: f32 = 2.0;
+    /// size of the text written on reaction arrows
+    const ARROW_TEXT_SIZE: f32 = 8.0;
+    /// gap between a reaction arrow and the text over and under it, and between its lines
+    const ARROW_TEXT_GAP: f32 = 3.0;
+    /// distance in ångströms a standard bond stands for when labelling interactions
+    const BOND_LENGTH_ANGSTROMS: f32 = 1.5;
+    const DISTANCE_LABEL_SIZE: f32 = 7.0;
+    /// space between an interaction and its distance label
+    const DISTANCE_LABEL_GAP: f32 = 2.0;
+    /// width of new ink strokes until the document sets its own
+    pub const INK_WIDTH: f32 = 1.5;
+    /// width of the outlines of orbitals' lobes
+    const ORBITAL_WIDTH: f32 = 0.75;
+    /// size of the numbers mapping atoms across a reaction arrow
+    const ATOM_MAP_SIZE: f32 = 6.0;
+    /// colours map numbers cycle through, so atoms mapped to each other share one
+    const ATOM_MAP_COLORS: [Color; 6] = [
+        Color::from_rgb(0.12, 0.47, 0.71),
+        Color::from_rgb(0.17, 0.63, 0.17),
+        Color::from_rgb(1.0, 0.5, 0.05),
+        Color::from_rgb(0.58, 0.4, 0.74),
+        Color::from_rgb(0.09, 0.75, 0.81),
+        Color::from_rgb(0.89, 0.47, 0.76),
+    ];
+    /// colour of map numbers that don't pair a reactant atom with a product atom
+    const UNBALANCED_ATOM_MAP_COLOR: Color = Color::from_rgb(0.84, 0.15, 0.16);
+    /// width of the warning triangle under arrows whose reactions don't balance
+    const MASS_IMBALANCE_ICON_SIZE: f32 = 9.0;
+    const MASS_IMBALANCE_COLOR: Color = Color::from_rgb(0.96, 0.68, 0.1);
+    /// length of the arrow of a pasted reaction with little written over it, in bond lengths
+    const REACTION_ARROW_LENGTH: f32 = 3.0;
+    /// how far from an arrow's line a molecule can be drawn and still belong to its reaction, in
+    /// bond lengths
+    const REACTION_REACH: f32 = 2.0;
+    /// size of the step numbers drawn over reaction arrows in route mode
+    const ROUTE_STEP_SIZE: f32 = 8.0;
+    /// size in pixels of text shown beside the cursor, such as a drawn bond's angle and length
+    const CURSOR_READOUT_SIZE: f32 = 12.0;
+    /// distance in pixels of that text below and right of the cursor
+    const CURSOR_READOUT_OFFSET: f32 = 14.0;
 
     pub fn update(&mut self, messages: Vec<Message>) -> Result<()> {
+        // messages arriving together come from one gesture and are undone together, as are the
+        // deletions of a drag with the eraser after its first, which come without a change of action
+        let erasing_on = matches!(self.action, Action::Erasing { erased: true })
+            && !messages.iter().any(|message| matches!(message, Message::ActionChanged(_)));
+
+        let undoable = messages.iter().any(Message::is_undoable);
+        if undoable && !erasing_on {
+            self.history.record(self.state.snapshot());
+        }
+        let view_only = messages.iter().all(Message::is_view_only);
+
         for message in messages {
             match message {
                 Message::AddMoleculeWithAtom(molecule_id, atom_id, label, position) => {
@@ -110,6 +574,7 @@ impl MolCanvas {
 
                     molecule.add_atom(end_atom_id, "".to_string(), position)?;
                     molecule.add_bond(start_atom_id, end_atom_id, bond_type)?;
+                    self.chain_end = Some((molecule_id, end_atom_id));
 
                     self.cache.clear();
                 }
@@ -120,139 +585,3059 @@ impl MolCanvas {
                         .context("while handling ChangeBondType message")?;
                     molecule.add_bond(start_atom_id, end_atom_id, bond_type)?;
 
-                    self.cache.clear();
-                }
-                Message::ChangeBondType(molecule_id, bond_id, bond_type) => {
-                    let molecule = self
-                        .state
-                        .get_molecule_mut(&molecule_id)
-                        .context("while handling ChangeBondType message")?;
-                    molecule.change_bond_type(&bond_id, bond_type);
+                    self.cache.clear();
+                }
+                Message::ChangeBondType(molecule_id, bond_id, bond_type) => {
+                    let molecule = self
+                        .state
+                        .get_molecule_mut(&molecule_id)
+                        .context("while handling ChangeBondType message")?;
+                    molecule.change_bond_type(&bond_id, bond_type);
+
+                    self.cache.clear();
+                }
+                Message::FlipBond(molecule_id, bond_id) => {
+                    let molecule = self
+                        .state
+                        .get_molecule_mut(&molecule_id)
+                        .context("while handling FlipBond message")?;
+                    molecule.flip_bond(&bond_id);
+
+                    self.cache.clear();
+                }
+                Message::ConnectMolecules(
+                    molecule_id1,
+                    atom_id1,
+                    molecule_id2,
+                    atom_id2,
+                    bond_type,
+                ) => {
+                    self.state
+                        .connect_molecules(&molecule_id1, atom_id1, &molecule_id2, atom_id2, bond_type)
+                        .context("while handling ConnectMolecules message")?;
+
+                    self.cache.clear();
+                }
+                Message::RelabelAtom(mol_id, atom_id, text) => {
+                    let molecule = self
+                        .state
+                        .get_molecule_mut(&mol_id)
+                        .context("while handling RelabelAtom message")?;
+                    molecule.rename_atom(&atom_id, text)?;
+                    self.cache.clear();
+                }
+                Message::DeleteMolecule(molecule_id) => {
+                    self.state.delete_molecule(&molecule_id)?;
+
+                    self.cache.clear();
+                }
+                Message::DeleteAtom(molecule_id, atom_id) => {
+                    self.state.delete_atom(&molecule_id, atom_id)?;
+
+                    self.cache.clear();
+                }
+                Message::DeleteBond(molecule_id, bond_id) => {
+                    self.state.delete_bond(&molecule_id, bond_id)?;
+
+                    self.cache.clear();
+                }
+                Message::AddInteraction(start, end, kind) => {
+                    self.state
+                        .add_interaction(start, end, kind)
+                        .context("while handling AddInteraction message")?;
+
+                    self.cache.clear();
+                }
+                Message::DeleteInteraction(interaction_id) => {
+                    self.state
+                        .delete_interaction(&interaction_id)
+                        .context("while handling DeleteInteraction message")?;
+
+                    self.cache.clear();
+                }
+                Message::MapAtoms(first, second) => {
+                    self.state
+                        .map_atoms(first, second)
+                        .context("while handling MapAtoms message")?;
+
+                    self.cache.clear();
+                }
+                Message::UnmapAtom(atom_id) => {
+                    self.state
+                        .unmap_atom(&atom_id)
+                        .context("while handling UnmapAtom message")?;
+
+                    self.cache.clear();
+                }
+                Message::SetAtomMap(molecule_id, atom_id, map) => {
+                    self.state
+                        .set_atom_map(&molecule_id, &atom_id, map)
+                        .context("while handling SetAtomMap message")?;
+
+                    self.cache.clear();
+                }
+                Message::AddOrbital(kind, atom_id, partner) => {
+                    self.state
+                        .add_orbital(kind, atom_id, partner)
+                        .context("while handling AddOrbital message")?;
+
+                    self.cache.clear();
+                }
+                Message::DeleteOrbital(orbital_id) => {
+                    self.state
+                        .delete_orbital(&orbital_id)
+                        .context("while handling DeleteOrbital message")?;
+
+                    self.cache.clear();
+                }
+                Message::TurnOrbital(orbital_id, position) => {
+                    self.state
+                        .turn_orbital(&orbital_id, position)
+                        .context("while handling TurnOrbital message")?;
+
+                    self.cache.clear();
+                }
+                Message::AddInk(points) => {
+                    let style = self.state.style();
+                    let (width, color) = (style.ink_width, style.ink_color);
+                    self.state
+                        .add_ink(&points, width, color)
+                        .context("while handling AddInk message")?;
+
+                    self.cache.clear();
+                }
+                Message::DeleteInk(ink_id) => {
+                    self.state
+                        .delete_ink(&ink_id)
+                        .context("while handling DeleteInk message")?;
+
+                    self.cache.clear();
+                }
+                Message::AddShape(kind, start, end) => {
+                    let style = self.new_shape_style();
+                    let shape_id = self.state
+                        .add_shape(kind, start, end, style)
+                        .context("while handling AddShape message")?;
+                    self.state.new_selection(Selection::default());
+                    self.selected_shape = Some(shape_id);
+
+                    self.cache.clear();
+                }
+                Message::InsertClipart(clipart) => {
+                    if !self.state.can_annotate() {
+                        continue;
+                    }
+                    let size = clipart.size() * self.state.style().bond_length;
+                    let center = self.view_center();
+                    let start = Point::new(center.x - size.width / 2.0, center.y - size.height / 2.0);
+                    let end = Point::new(center.x + size.width / 2.0, center.y + size.height / 2.0);
+
+                    let style = self.new_shape_style();
+                    let shape_id = self.state
+                        .add_shape(ShapeKind::Clipart(clipart), start, end, style)
+                        .context("while handling InsertClipart message")?;
+                    self.state.new_selection(Selection::default());
+                    self.selected_shape = Some(shape_id);
+
+                    self.cache.clear();
+                }
+                Message::DeleteShape(shape_id) => {
+                    self.state
+                        .delete_shape(&shape_id)
+                        .context("while handling DeleteShape message")?;
+
+                    self.cache.clear();
+                }
+                Message::SetShapeStyle(shape_id, style) => {
+                    self.state
+                        .set_shape_style(&shape_id, style)
+                        .context("while handling SetShapeStyle message")?;
+
+                    self.cache.clear();
+                }
+                Message::SetReactionConditions(shape_id, conditions) => {
+                    self.state
+                        .set_reaction_conditions(&shape_id, conditions)
+                        .context("while handling SetReactionConditions message")?;
+
+                    self.cache.clear();
+                }
+                Message::SetArrowText(shape_id, side, text) => {
+                    self.state
+                        .set_arrow_text(&shape_id, side, text)
+                        .context("while handling SetArrowText message")?;
+
+                    // arrows are lengthened to fit what's written on them, but never shortened
+                    if let Some(length) = self.state.get_shape(&shape_id).map(|arrow| self.arrow_text_length(arrow)) {
+                        self.state
+                            .lengthen_shape(&shape_id, length)
+                            .context("while handling SetArrowText message")?;
+                    }
+
+                    self.cache.clear();
+                }
+                Message::SelectShape(shape_id) => {
+                    // a shape and molecules aren't selected together
+                    if shape_id.is_some() {
+                        self.state.new_selection(Selection::default());
+                    }
+                    self.selected_shape = shape_id;
+                }
+                Message::DragShape(position) => {
+                    if let (Action::AdjustingShape { drag, last }, Some(shape_id)) = (&mut self.action, self.selected_shape) {
+                        self.state
+                            .drag_shape(&shape_id, *drag, *last, position)
+                            .context("while handling DragShape message")?;
+                        *last = position;
+
+                        self.cache.clear();
+                    }
+                }
+                Message::AddAnnotation(position, text) => {
+                    if !self.state.can_annotate() {
+                        continue;
+                    }
+                    self.state
+                        .add_annotation(text, position)
+                        .context("while handling AddAnnotation message")?;
+
+                    self.cache.clear();
+                }
+                Message::SetAnnotationText(annotation_id, text) => {
+                    self.state
+                        .set_annotation_text(&annotation_id, text)
+                        .context("while handling SetAnnotationText message")?;
+
+                    self.cache.clear();
+                }
+                Message::DeleteAnnotation(annotation_id) => {
+                    self.state
+                        .delete_annotation(&annotation_id)
+                        .context("while handling DeleteAnnotation message")?;
+
+                    self.cache.clear();
+                }
+                Message::DragAnnotation(position) => {
+                    if let Action::MovingAnnotation { annotation_id, last } = &mut self.action {
+                        let Some(annotation) = self.state.get_annotation(annotation_id) else {
+                            continue;
+                        };
+                        let moved = annotation.position() + (position - *last);
+                        self.state
+                            .move_annotation(annotation_id, moved)
+                            .context("while handling DragAnnotation message")?;
+                        *last = position;
+
+                        self.cache.clear();
+                    }
+                }
+                Message::SetDocumentBondLength(length) => {
+                    self.state
+                        .set_bond_length(length)
+                        .context("while handling SetDocumentBondLength message")?;
+
+                    self.cache.clear();
+                }
+                Message::ScaleSelection(factor) => {
+                    self.state
+                        .scale_selection(factor)
+                        .context("while handling ScaleSelection message")?;
+
+                    self.cache.clear();
+                }
+                Message::SetBondLength(molecule_id, bond_id, length) => {
+                    self.state
+                        .get_molecule_mut(&molecule_id)
+                        .and_then(|molecule| molecule.set_bond_length(&bond_id, length))
+                        .context("while handling SetBondLength message")?;
+
+                    self.cache.clear();
+                }
+                Message::SetBondAngle(molecule_id, atom_id, degrees) => {
+                    self.state
+                        .get_molecule_mut(&molecule_id)
+                        .and_then(|molecule| molecule.set_bond_angle(&atom_id, degrees))
+                        .context("while handling SetBondAngle message")?;
+
+                    self.cache.clear();
+                }
+                Message::FlipFragment(molecule_id, bond_id) => {
+                    self.state
+                        .get_molecule_mut(&molecule_id)
+                        .and_then(|molecule| molecule.flip_fragment(&bond_id))
+                        .context("while handling FlipFragment message")?;
+
+                    self.cache.clear();
+                }
+                Message::ReattachBond(molecule_id, bond_id, end, target_molecule_id, atom_id) => {
+                    self.state
+                        .reattach_bond(&molecule_id, &bond_id, end, &target_molecule_id, atom_id)
+                        .context("while handling ReattachBond message")?;
+
+                    self.cache.clear();
+                }
+                Message::DetachBond(molecule_id, bond_id, end, position) => {
+                    self.state
+                        .detach_bond(&molecule_id, &bond_id, end, position)
+                        .context("while handling DetachBond message")?;
+
+                    self.cache.clear();
+                }
+                Message::BendBond(molecule_id, bond_id, position) => {
+                    self.state
+                        .bend_bond(&molecule_id, &bond_id, position, self.handle_radius())
+                        .context("while handling BendBond message")?;
+
+                    self.cache.clear();
+                }
+                Message::CurveBond(molecule_id, bond_id, curvature) => {
+                    self.state
+                        .get_molecule_mut(&molecule_id)
+                        .and_then(|molecule| molecule.curve_bond(&bond_id, curvature))
+                        .context("while handling CurveBond message")?;
+
+                    self.cache.clear();
+                }
+                Message::MoveSelection(position) => {
+                    if let Action::MovingSelection { last } = &mut self.action {
+                        self.state.move_selection(position - *last)?;
+                        *last = position;
+
+                        self.cache.clear();
+                    }
+                }
+                Message::NewSelection(selection) => {
+                    self.state.new_selection(selection);
+                    self.selected_shape = None;
+                }
+                Message::ToolChanged(tool) => {
+                    self.tool = tool;
+                }
+                Message::ActionChanged(action) => {
+                    // starting anything but a drag of the selected shape lets go of it
+                    if !matches!(action, Action::None | Action::AdjustingShape { .. }) {
+                        self.selected_shape = None;
+                    }
+                    self.action = action;
+                }
+                Message::Translated(translation) => {
+                    self.translation = translation;
+                    self.animation = None;
+
+                    self.cache.clear();
+                }
+                Message::Scaled(scaling, translation) => {
+                    self.scaling = scaling;
+                    self.animation = None;
+
+                    if let Some(translation) = translation {
+                        self.translation = translation;
+                    }
+
+                    self.cache.clear();
+                }
+                Message::AnnotateFunctionalGroups(annotate) => {
+                    self.annotate_functional_groups = annotate;
+
+                    self.cache.clear();
+                }
+                Message::RouteMode(route_mode) => {
+                    self.route_mode = route_mode;
+
+                    self.cache.clear();
+                }
+                Message::ArrangeRoutes => {
+                    self.arrange_routes().context("while handling ArrangeRoutes message")?;
+
+                    self.cache.clear();
+                }
+                Message::RenameMolecule(molecule_id, name) => {
+                    self.state
+                        .get_molecule_mut(&molecule_id)
+                        .context("while handling RenameMolecule message")?
+                        .set_name(name);
+
+                    self.cache.clear();
+                }
+                Message::SetCaption(molecule_id, caption) => {
+                    self.state
+                        .get_molecule_mut(&molecule_id)
+                        .context("while handling SetCaption message")?
+                        .set_caption(caption);
+
+                    self.cache.clear();
+                }
+                Message::SetCas(molecule_id, cas) => {
+                    self.state
+                        .get_molecule_mut(&molecule_id)
+                        .context("while handling SetCas message")?
+                        .metadata_mut()
+                        .set_cas(cas);
+                }
+                Message::SetNotes(molecule_id, notes) => {
+                    self.state
+                        .get_molecule_mut(&molecule_id)
+                        .context("while handling SetNotes message")?
+                        .metadata_mut()
+                        .set_notes(notes);
+                }
+                Message::SetTags(molecule_id, tags) => {
+                    self.state
+                        .get_molecule_mut(&molecule_id)
+                        .context("while handling SetTags message")?
+                        .metadata_mut()
+                        .set_tags(tags);
+
+                    // the molecule may now match the tag filter or not
+                    self.cache.clear();
+                }
+                Message::SetSource(molecule_id, source) => {
+                    self.state
+                        .get_molecule_mut(&molecule_id)
+                        .context("while handling SetSource message")?
+                        .metadata_mut()
+                        .set_source(source);
+                }
+                Message::FilterTag(tag, selected) => {
+                    self.state.filter_tag(tag, selected);
+
+                    self.cache.clear();
+                }
+                Message::HideUnmatched(hide) => {
+                    self.state.set_hide_unmatched(hide);
+
+                    self.cache.clear();
+                }
+                Message::MoleculeNames(molecule_names) => {
+                    self.state.style_mut().molecule_names = molecule_names;
+
+                    self.cache.clear();
+                }
+                Message::CompoundNumbers(compound_numbers) => {
+                    self.state.style_mut().compound_numbers = compound_numbers;
+
+                    self.cache.clear();
+                }
+                Message::InteractionDistances(interaction_distances) => {
+                    self.state.style_mut().interaction_distances = interaction_distances;
+
+                    self.cache.clear();
+                }
+                Message::InkWidth(ink_width) => {
+                    self.state.style_mut().ink_width = ink_width;
+                }
+                Message::InkColor(ink_color) => {
+                    self.state.style_mut().ink_color = ink_color;
+                }
+                Message::AnnotateBond(molecule_id, bond_id, annotation) => {
+                    self.state
+                        .get_molecule_mut(&molecule_id)
+                        .and_then(|molecule| molecule.set_bond_annotation(&bond_id, annotation))
+                        .context("while handling AnnotateBond message")?;
+
+                    self.cache.clear();
+                }
+                Message::MarkDisconnection(molecule_id, bond_id, disconnection) => {
+                    self.state
+                        .get_molecule_mut(&molecule_id)
+                        .and_then(|molecule| molecule.set_bond_disconnection(&bond_id, disconnection))
+                        .context("while handling MarkDisconnection message")?;
+
+                    self.cache.clear();
+                }
+                Message::BondAnnotations(bond_annotations) => {
+                    self.state.style_mut().bond_annotations = bond_annotations;
+
+                    self.cache.clear();
+                }
+                Message::MetalCounts(metal_counts) => {
+                    self.state.style_mut().metal_counts = metal_counts;
+
+                    self.cache.clear();
+                }
+                Message::LetterWithPrevious(molecule_id, letters_with_previous) => {
+                    self.state
+                        .get_molecule_mut(&molecule_id)
+                        .context("while handling LetterWithPrevious message")?
+                        .set_letters_with_previous(letters_with_previous);
+
+                    self.cache.clear();
+                }
+                Message::SetChargeBracket(molecule_id, charge_bracket) => {
+                    self.state
+                        .get_molecule_mut(&molecule_id)
+                        .context("while handling SetChargeBracket message")?
+                        .set_charge_bracket(charge_bracket);
+
+                    self.cache.clear();
+                }
+                Message::AddInstance(molecule_id) => {
+                    let width = self
+                        .state
+                        .get_molecule(&molecule_id)
+                        .context("while handling AddInstance message")?
+                        .outer_rectangle()
+                        .width;
+                    let offset = Vector::new(width + self.state.style().bond_length, 0.0);
+                    self.state.add_instance(&molecule_id, offset).context("while handling AddInstance message")?;
+
+                    self.cache.clear();
+                }
+                Message::UnlinkInstance(molecule_id) => {
+                    self.state.unlink_instance(&molecule_id);
+                }
+                Message::ResizeChargeBracket(molecule_id, padding) => {
+                    let molecule = self
+                        .state
+                        .get_molecule_mut(&molecule_id)
+                        .context("while handling ResizeChargeBracket message")?;
+                    let charge_bracket = molecule
+                        .charge_bracket()
+                        .map(|charge_bracket| ChargeBracket { padding, ..charge_bracket });
+                    molecule.set_charge_bracket(charge_bracket);
+
+                    self.cache.clear();
+                }
+                Message::JumpToMolecule(molecule_id) => {
+                    let center = self
+                        .state
+                        .get_molecule(&molecule_id)
+                        .context("while handling JumpToMolecule message")?
+                        .bounds()
+                        .bounding_rectangle()
+                        .center();
+                    self.state.new_selection(Selection::from_iter([SingleSelection::Molecule(molecule_id)]));
+                    self.animate_to(Point::ORIGIN - center, self.scaling);
+
+                    self.cache.clear();
+                }
+                Message::SelectParentMolecule => {
+                    let selection = self.state.selection().parent_molecules();
+                    self.state.new_selection(selection);
+
+                    self.cache.clear();
+                }
+                Message::SelectSubstituent(molecule_id, bond_id) => {
+                    let molecule = self
+                        .state
+                        .get_molecule(&molecule_id)
+                        .context("while handling SelectSubstituent message")?;
+                    let atom_ids = molecule.substituent(&bond_id).unwrap_or_default();
+                    let bond_ids = molecule
+                        .bonds()
+                        .filter(|(_, bond)| atom_ids.contains(&bond.start()) && atom_ids.contains(&bond.end()))
+                        .map(|(bond_id, _)| *bond_id)
+                        .collect::<Vec<_>>();
+
+                    let selection = std::iter::once(SingleSelection::Bond(molecule_id, bond_id))
+                        .chain(bond_ids.into_iter().map(|bond_id| SingleSelection::Bond(molecule_id, bond_id)))
+                        .chain(atom_ids.into_iter().map(|atom_id| SingleSelection::Atom(molecule_id, atom_id)))
+                        .collect();
+                    self.state.new_selection(selection);
+
+                    self.cache.clear();
+                }
+                Message::GrowSelection => {
+                    let selection = self
+                        .state
+                        .selection()
+                        .grow(&self.state)
+                        .context("while handling GrowSelection message")?;
+                    self.state.new_selection(selection);
+
+                    self.cache.clear();
+                }
+                Message::LewisStructures(lewis_structures) => {
+                    self.state.style_mut().lewis_structures = lewis_structures;
+
+                    self.cache.clear();
+                }
+                Message::BondCrossings(bond_crossings) => {
+                    self.state.style_mut().bond_crossings = bond_crossings;
+
+                    self.cache.clear();
+                }
+                Message::ExportMargin(margin) => {
+                    self.state.style_mut().export_margin = margin;
+                }
+                Message::ExportResolution(resolution) => {
+                    self.state.style_mut().export_resolution = resolution;
+                }
+                // the canvas is filled behind the molecules only without a background
+                Message::ImportBackground(image, center) => {
+                    self.background = Some(Background::new(image, center));
+
+                    self.cache.clear();
+                }
+                Message::RemoveBackground => {
+                    self.background = None;
+
+                    self.cache.clear();
+                }
+                Message::BackgroundOpacity(opacity) => {
+                    if let Some(background) = &mut self.background {
+                        background.set_opacity(opacity);
+                    }
+                }
+                Message::BackgroundExported(exported) => {
+                    if let Some(background) = &mut self.background {
+                        background.set_exported(exported);
+                    }
+                }
+                Message::BackgroundLocked(locked) => {
+                    if let Some(background) = &mut self.background {
+                        background.set_locked(locked);
+                    }
+                }
+                Message::DragBackground(position) => {
+                    if let (Action::AdjustingBackground { drag, last }, Some(background)) = (&mut self.action, &mut self.background) {
+                        background.drag(*drag, *last, position);
+                        *last = position;
+                    }
+                }
+                Message::PartialBondDash(dash_pattern) => {
+                    self.state.style_mut().partial_bond_dash = dash_pattern;
+
+                    self.cache.clear();
+                }
+                Message::Arrange(arrangement) => {
+                    let mut captions = FxHashMap::<MoleculeId, Rectangle>::default();
+                    for (molecule_id, caption, position) in self.captions() {
+                        let bounds = caption.bounds_at(position);
+                        captions
+                            .entry(molecule_id)
+                            .and_modify(|area| *area = area.union(&bounds))
+                            .or_insert(bounds);
+                    }
+
+                    let spacing = self.state.style().bond_length * Self::ARRANGE_SPACING;
+                    self.state
+                        .arrange(arrangement, spacing, &captions)
+                        .context("while handling Arrange message")?;
+
+                    self.cache.clear();
+                }
+                Message::ResolveOverlaps => {
+                    self.state
+                        .resolve_overlaps()
+                        .context("while handling ResolveOverlaps message")?;
+
+                    self.cache.clear();
+                }
+                Message::NewLayer(name) => {
+                    self.state.add_layer(name);
+                }
+                Message::SetActiveLayer(layer_id) => {
+                    self.state
+                        .set_active_layer(layer_id)
+                        .context("while handling SetActiveLayer message")?;
+                }
+                Message::SetLayerVisible(layer_id, visible) => {
+                    self.state
+                        .set_layer_visible(&layer_id, visible)
+                        .context("while handling SetLayerVisible message")?;
+
+                    self.cache.clear();
+                }
+                Message::SetLayerLocked(layer_id, locked) => {
+                    self.state
+                        .set_layer_locked(&layer_id, locked)
+                        .context("while handling SetLayerLocked message")?;
+                }
+                Message::MoveToLayer(molecule_id, layer_id) => {
+                    self.state
+                        .move_to_layer(&molecule_id, layer_id)
+                        .context("while handling MoveToLayer message")?;
+
+                    self.cache.clear();
+                }
+                Message::SetMoleculeLocked(molecule_id, locked) => {
+                    self.state
+                        .set_molecule_locked(&molecule_id, locked)
+                        .context("while handling SetMoleculeLocked message")?;
+                }
+                Message::UnlockAll => {
+                    self.state.unlock_all();
+                }
+                Message::Find(query) => {
+                    self.search = Search::new(&self.state, &query);
+                    self.center_on_match().context("while handling Find message")?;
+
+                    self.cache.clear();
+                }
+                Message::FindNext => {
+                    self.search.next();
+                    self.center_on_match().context("while handling FindNext message")?;
+
+                    self.cache.clear();
+                }
+                Message::FindPrevious => {
+                    self.search.previous();
+                    self.center_on_match().context("while handling FindPrevious message")?;
+
+                    self.cache.clear();
+                }
+                Message::ClearFind => {
+                    self.search = Search::default();
+                }
+                Message::ReplaceLabels(find, replace, selection_only) => {
+                    self.state
+                        .replace_labels(&find, &replace, selection_only)
+                        .context("while handling ReplaceLabels message")?;
+
+                    self.cache.clear();
+                }
+                Message::ImportProject(session, center) => {
+                    self.place_project(*session, center).context("while handling ImportProject message")?;
+
+                    self.cache.clear();
+                }
+                Message::InsertCompounds(compounds) => {
+                    let parts = compounds.iter().map(|compound| compound.structures.len()).collect::<Vec<_>>();
+                    let (records, structures): (Vec<_>, Vec<_>) = compounds
+                        .into_iter()
+                        .map(|compound| ((compound.name, compound.source), compound.structures))
+                        .unzip();
+                    let mut atom_ids = self
+                        .place_structures(structures.into_iter().flatten().collect(), self.view_center())
+                        .context("while handling InsertCompounds message")?
+                        .into_iter();
+
+                    // the name goes on the largest part of a compound, like the organic ion of a salt
+                    for ((name, source), parts) in records.into_iter().zip(parts) {
+                        let largest = atom_ids
+                            .by_ref()
+                            .take(parts)
+                            .max_by_key(|atom_ids| atom_ids.len())
+                            .and_then(|atom_ids| atom_ids.first().and_then(|atom_id| self.state.find_atom(atom_id)));
+
+                        if let Some(molecule) = largest.and_then(|molecule_id| self.state.get_molecule_mut(&molecule_id).ok()) {
+                            molecule.set_name(name);
+                            molecule.metadata_mut().set_source(source);
+                        }
+                    }
+
+                    self.cache.clear();
+                }
+                Message::PasteStructures(structures, center) => {
+                    self.place_structures(structures, center).context("while handling PasteStructures message")?;
+
+                    self.cache.clear();
+                }
+                Message::PasteReactions(reactions, position) => {
+                    self.place_reactions(reactions, position).context("while handling PasteReactions message")?;
+
+                    self.cache.clear();
+                }
+                Message::PasteMolecules(clipboard, position) => {
+                    self.place_molecules(&clipboard, position).context("while handling PasteMolecules message")?;
+
+                    self.cache.clear();
+                }
+                Message::FocusNextMolecule | Message::FocusPreviousMolecule => {
+                    let forward = matches!(message, Message::FocusNextMolecule);
+                    let molecule_id = self.keyboard_focus().map(|(molecule_id, _)| molecule_id);
+
+                    if let Some((molecule_id, atom_id)) = self.state.adjacent_molecule_atom(molecule_id.as_ref(), forward) {
+                        self.focus_atom(molecule_id, atom_id);
+                        self.center_on_atom(&molecule_id, &atom_id)
+                            .context("while handling FocusNextMolecule message")?;
+                    }
+
+                    self.cache.clear();
+                }
+                Message::MoveFocus(direction) => {
+                    self.move_focus(direction).context("while handling MoveFocus message")?;
+                }
+                Message::SproutFromFocus(direction) => {
+                    self.sprout_from_focus(direction).context("while handling SproutFromFocus message")?;
+
+                    self.cache.clear();
+                }
+                Message::DeleteFocused => {
+                    self.delete_focused().context("while handling DeleteFocused message")?;
+
+                    self.cache.clear();
+                }
+                Message::ClearFocus => {
+                    self.keyboard_focus = None;
+                }
+                Message::OpenContextMenu(molecule_id, position) => {
+                    self.context_menu = Some((molecule_id, position));
+                }
+                Message::CloseContextMenu => {
+                    self.context_menu = None;
+                }
+                Message::CycleHover(position, depth) => {
+                    self.tap_cycle = Some((position, depth));
+                }
+                Message::ModifiersChanged(modifiers) => {
+                    self.modifiers = modifiers;
+                }
+                Message::AwaitDrop(awaiting_drop) => {
+                    self.awaiting_drop = awaiting_drop;
+                }
+                Message::ZoomToFit => {
+                    self.zoom_to_fit();
+                }
+                Message::ZoomTo(scaling) => {
+                    let scaling = Scaling(scaling.clamp(*Self::MIN_SCALING, *Self::MAX_SCALING));
+                    self.animate_to(self.translation, scaling);
+                }
+                Message::AnimationTick(now) => {
+                    if let Some(animation) = self.animation {
+                        let ((translation, scaling), finished) = animation.at(now);
+                        self.translation = translation;
+                        self.scaling = scaling;
+
+                        if finished {
+                            self.animation = None;
+                        }
+
+                        self.cache.clear();
+                    }
+                }
+                Message::ViewportResized(size) => {
+                    self.viewport = size;
+                }
+                Message::SaveVersion(name) => {
+                    let name = match name.trim().is_empty() {
+                        true => format!("Version {}", self.timeline.len() + 1),
+                        false => name.trim().to_string(),
+                    };
+
+                    self.timeline.add(name, self.state.snapshot());
+                }
+                Message::RestoreVersion(index) => {
+                    if let Some(version) = self.timeline.get(index) {
+                        self.state.restore(version.snapshot().clone());
+                    }
+                    self.compared_version = None;
+
+                    self.cache.clear();
+                }
+                Message::CompareVersion(index) => {
+                    self.compared_version = index;
+                }
+                Message::DeleteVersion(index) => {
+                    self.timeline.remove(index);
+
+                    self.compared_version = match self.compared_version {
+                        Some(compared) if compared == index => None,
+                        Some(compared) if compared > index => Some(compared - 1),
+                        compared => compared,
+                    };
+                }
+                Message::Undo => {
+                    if let Some(snapshot) = self.history.undo(self.state.snapshot()) {
+                        self.state.restore(snapshot);
+                    }
+
+                    self.cache.clear();
+                }
+                Message::Redo => {
+                    if let Some(snapshot) = self.history.redo(self.state.snapshot()) {
+                        self.state.restore(snapshot);
+                    }
+
+                    self.cache.clear();
+                }
+                Message::ChangeLabelFont(label_font) => {
+                    self.state
+                        .set_label_font(label_font)
+                        .context("while handling ChangeLabelFont message")?;
+
+                    self.cache.clear();
+                }
+                Message::ChangeDisplayMode(molecule_id, display_mode) => {
+                    let molecule = self
+                        .state
+                        .get_molecule_mut(&molecule_id)
+                        .context("while handling ChangeDisplayMode message")?;
+                    molecule.set_display_mode(display_mode);
+
+                    self.cache.clear();
+                }
+            }
+        }
+
+        // instances follow whatever the messages did to their masters
+        if undoable && self.state.has_instances() && self.state.update_instances().context("while updating instances")? {
+            self.cache.clear();
+        }
+
+        if !view_only {
+            self.summarize();
+        }
+
+        Ok(())
+    }
+
+    pub fn view(&self) -> Element<'_, application::Message> {
+        let canvas = Canvas::new(self).width(Fill).height(Fill);
+
+        match &self.background {
+            Some(background) => {
+                let layer = BackgroundLayer {
+                    background,
+                    translation: self.translation,
+                    scaling: *self.scaling,
+                };
+
+                Stack::with_children(vec![Canvas::new(layer).width(Fill).height(Fill).into(), canvas.into()]).into()
+            }
+            None => canvas.into(),
+        }
+    }
+
+    pub fn background(&self) -> Option<&Background> {
+        self.background.as_ref()
+    }
+
+    /// the text a target holds, empty for new text, none when it's gone or can't be edited
+    pub fn target_text(&self, target: &TextTarget) -> Option<String> {
+        match target {
+            TextTarget::Annotation(annotation_id) => {
+                self.state.get_annotation(annotation_id).map(|annotation| annotation.text().to_string())
+            }
+            TextTarget::NewAnnotation(_) => self.state.can_annotate().then(String::new),
+            TextTarget::Arrow(shape_id, side) => self
+                .state
+                .get_shape(shape_id)
+                .filter(|shape| shape.kind() == ShapeKind::Arrow)
+                .map(|arrow| arrow.text(*side).to_string()),
+        }
+    }
+
+    /// where on screen a target's text starts, the middle of the top of its first line, which
+    /// over an arrow is a line's height above it when there's no text yet
+    pub fn target_screen_position(&self, target: &TextTarget, size: Size) -> Option<Point> {
+        let position = match target {
+            TextTarget::Annotation(annotation_id) => self.state.get_annotation(annotation_id)?.position(),
+            TextTarget::NewAnnotation(position) => *position,
+            TextTarget::Arrow(shape_id, side) => {
+                let arrow = self.state.get_shape(shape_id)?;
+                let (tail, head) = (arrow.start(), arrow.end());
+                let middle = tail + (head - tail) * 0.5;
+                let gap = Self::ARROW_TEXT_GAP;
+                let line_height = Self::ARROW_TEXT_SIZE * 1.2;
+                // lines over an arrow are laid out upwards from it, so its first line is the last one
+                match (side, self.arrow_text_layout(arrow, *side).last()) {
+                    (ArrowSide::Below, _) => middle + Self::under_arrow(tail, head) * gap,
+                    (ArrowSide::Above, Some((line, position))) => Point::new(position.x, line.bounds_at(*position).y),
+                    (ArrowSide::Above, None) => middle - Self::under_arrow(tail, head) * (gap + line_height),
+                }
+            }
+        };
+
+        Some(self.unproject(position, size))
+    }
+
+    /// the selected shape, while it's still in the document and editable
+    pub fn selected_shape(&self) -> Option<(ShapeId, &Shape)> {
+        let shape_id = self.selected_shape?;
+        self.state.get_shape(&shape_id).map(|shape| (shape_id, shape))
+    }
+
+    /// new shapes are outlined like ink
+    fn new_shape_style(&self) -> ShapeStyle {
+        let style = self.state.style();
+        ShapeStyle {
+            width: style.ink_width,
+            color: style.ink_color,
+            filled: false,
+        }
+    }
+
+    /// the background when exported images include it, they leave it out by default
+    fn exported_background(&self) -> Option<&Background> {
+        self.background.as_ref().filter(|background| background.is_exported())
+    }
+
+    pub fn style(&self) -> &DocumentStyle {
+        self.state.style()
+    }
+
+    pub fn layers(&self) -> impl Iterator<Item = (&LayerId, &Layer)> {
+        self.state.layers()
+    }
+
+    pub fn active_layer(&self) -> LayerId {
+        self.state.active_layer()
+    }
+
+    pub fn tags(&self) -> Vec<String> {
+        self.state.tags()
+    }
+
+    pub fn tag_filter(&self) -> &TagFilter {
+        self.state.tag_filter()
+    }
+
+    /// whether new molecules can be drawn, which they can't on a hidden or locked active layer
+    pub fn can_add_molecules(&self) -> bool {
+        self.state.can_add_molecules()
+    }
+
+    pub fn is_instance(&self, molecule_id: &MoleculeId) -> bool {
+        self.state.is_instance(molecule_id)
+    }
+
+    /// returns the selected item when only one is selected
+    pub fn selected_item(&self) -> Option<SingleSelection> {
+        self.state.selection().only()
+    }
+
+    /// returns the molecule containing the first selected item
+    pub fn selected_molecule(&self) -> Option<(MoleculeId, &Molecule)> {
+        let molecule_id = match self.state.selection().iter().next()? {
+            SingleSelection::Molecule(molecule_id)
+            | SingleSelection::Atom(molecule_id, _)
+            | SingleSelection::Bond(molecule_id, _) => molecule_id,
+        };
+
+        self.state
+            .get_molecule(molecule_id)
+            .ok()
+            .map(|molecule| (*molecule_id, molecule))
+    }
+
+    fn visible_region(&self, size: Size) -> Region {
+        let width = size.width / *self.scaling;
+        let height = size.height / *self.scaling;
+
+        Region {
+            rect: Rectangle {
+                x: -self.translation.x - width / 2.0,
+                y: -self.translation.y - height / 2.0,
+                width,
+                height,
+            },
+        }
+    }
+
+    fn project(&self, position: Point, size: Size) -> Point {
+        let region = self.visible_region(size);
+
+        Point::new(
+            position.x / *self.scaling + region.rect.x,
+            position.y / *self.scaling + region.rect.y,
+        )
+    }
+
+    /// inverse of project, maps a canvas position to a position within the widget
+    fn unproject(&self, position: Point, size: Size) -> Point {
+        let region = self.visible_region(size);
+
+        Point::new(
+            (position.x - region.rect.x) * *self.scaling,
+            (position.y - region.rect.y) * *self.scaling,
+        )
+    }
+
+    /// where an atom is drawn within a canvas widget of the given size
+    pub fn atom_screen_position(&self, molecule_id: &MoleculeId, atom_id: &AtomId, size: Size) -> Result<Point> {
+        let position = self
+            .state
+            .get_molecule(molecule_id)
+            .and_then(|molecule| molecule.atom_position(atom_id))
+            .context("while getting atom screen position")?;
+
+        Ok(self.unproject(position, size))
+    }
+
+    pub fn scaling(&self) -> f32 {
+        *self.scaling
+    }
+
+    /// size of the canvas widget when it last handled an event
+    pub fn viewport(&self) -> Size {
+        self.viewport
+    }
+
+    /// hit test tolerance converted to canvas units at the current zoom
+    fn hit_tolerance(&self) -> f32 {
+        Self::HIT_TOLERANCE / *self.scaling
+    }
+
+    /// how many times tapping at the position has moved past the first item under it, taps only
+    /// carry on cycling near where the last one was
+    fn tap_depth(&self, canvas_position: Point) -> Option<usize> {
+        self.tap_cycle
+            .filter(|(position, _)| position.distance(canvas_position) <= self.hit_tolerance())
+            .map(|(_, depth)| depth)
+    }
+
+    /// whether rectangle selections take every molecule they touch rather than only what they
+    /// contain
+    fn touching_selection(&self) -> bool {
+        self.modifiers.alt()
+    }
+
+    /// item under the position, stepping past the ones repeated taps there have cycled through
+    fn hovered(&self, canvas_position: Point) -> Result<HoverSelection> {
+        let depth = self.tap_depth(canvas_position).unwrap_or_default();
+        self.state.get_hovered(canvas_position, self.hit_tolerance(), depth)
+    }
+
+    /// bond handle radius converted to canvas units at the current zoom
+    fn handle_radius(&self) -> f32 {
+        Self::HANDLE_RADIUS / *self.scaling
+    }
+
+    /// writes the molecules on visible layers to a CSV file, returning how many were written
+    pub fn export_csv(&self, path: &std::path::Path) -> Result<usize> {
+        let molecules = self.state.molecules().map(|(_, molecule)| molecule).collect::<Vec<_>>();
+        let csv = export::molecules_csv(molecules.iter().copied());
+
+        std::fs::write(path, csv)
+            .with_context(|| format!("while writing {}", path.display()))
+            .context("while exporting CSV")?;
+
+        Ok(molecules.len())
+    }
+
+    /// writes the molecules on visible layers to an SD file with their names and metadata as data
+    /// fields, returning how many were written
+    pub fn export_sdf(&self, path: &std::path::Path) -> Result<usize> {
+        let molecules = self.state.molecules().map(|(_, molecule)| molecule).collect::<Vec<_>>();
+        let sdf = export::molecules_sdf(molecules.iter().copied(), self.state.style().bond_length)
+            .context("while exporting SD file")?;
+
+        std::fs::write(path, sdf)
+            .with_context(|| format!("while writing {}", path.display()))
+            .context("while exporting SD file")?;
+
+        Ok(molecules.len())
+    }
+
+    /// the molecules on visible layers grouped around each visible arrow
+    fn reaction_schemes(&self) -> Vec<reaction::ReactionScheme> {
+        let arrows = self
+            .state
+            .shapes()
+            .filter(|(_, shape)| shape.kind() == ShapeKind::Arrow)
+            .map(|(shape_id, shape)| (*shape_id, shape.start(), shape.end()))
+            .collect::<Vec<_>>();
+        let molecules = self
+            .state
+            .molecules()
+            .map(|(molecule_id, molecule)| (*molecule_id, molecule.outer_rectangle()))
+            .collect::<Vec<_>>();
+
+        reaction::find_reactions(&arrows, &molecules, self.state.style().bond_length * Self::REACTION_REACH)
+    }
+
+    /// reaction steps on visible layers chained into synthesis routes
+    fn routes(&self) -> Vec<route::Route> {
+        route::find_routes(self.reaction_schemes())
+    }
+
+    /// each synthesis route's steps with their arrow, written as the names or formulas of their
+    /// reactants and products and their yield, e.g. "EtOH → C2H4O (92%)"
+    fn route_overview(&self) -> Vec<Vec<(ShapeId, String)>> {
+        let describe = |molecule_ids: &[MoleculeId]| {
+            molecule_ids
+                .iter()
+                .filter_map(|molecule_id| self.state.get_molecule(molecule_id).ok())
+                .map(|molecule| match molecule.name() {
+                    Some(name) => name.to_string(),
+                    None => Descriptors::new(molecule).map_or_else(|_| "?".to_string(), |descriptors| descriptors.formula),
+                })
+                .collect::<Vec<_>>()
+                .join(" + ")
+        };
+
+        self.routes()
+            .into_iter()
+            .map(|route| {
+                route
+                    .steps
+                    .iter()
+                    .map(|step| {
+                        let yield_percent = self
+                            .state
+                            .get_shape(&step.arrow_id)
+                            .and_then(|arrow| arrow.conditions().yield_percent)
+                            .map(|yield_percent| format!(" ({}%)", yield_percent))
+                            .unwrap_or_default();
+
+                        (step.arrow_id, format!("{} → {}{}", describe(&step.reactants), describe(&step.products), yield_percent))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// lays out each synthesis route, keeping the top left corner of the region it was drawn in,
+    /// leaving routes with a locked molecule or arrow where they are
+    fn arrange_routes(&mut self) -> Result<()> {
+        let bond_length = self.state.style().bond_length;
+        let rectangles = self
+            .state
+            .molecules()
+            .map(|(molecule_id, molecule)| (*molecule_id, molecule.outer_rectangle()))
+            .collect::<FxHashMap<_, _>>();
+        let sizes = rectangles.iter().map(|(molecule_id, rectangle)| (*molecule_id, rectangle.size())).collect();
+
+        for route in self.routes() {
+            let layout = route::lay_out(
+                &route,
+                &sizes,
+                bond_length,
+                bond_length * (Self::REACTION_REACH + 1.0),
+                bond_length * Self::REACTION_ARROW_LENGTH,
+            );
+            let editable = layout
+                .molecules
+                .iter()
+                .all(|(molecule_id, _)| self.state.get_molecule(molecule_id).is_ok_and(|molecule| self.state.is_editable(molecule)))
+                && route.steps.iter().all(|step| self.state.get_shape(&step.arrow_id).is_some());
+            if !editable {
+                continue;
+            }
+
+            let region = |molecules: Vec<Rectangle>, arrows: Vec<Point>| {
+                molecules
+                    .into_iter()
+                    .chain(arrows.into_iter().map(|point| Rectangle::new(point, Size::ZERO)))
+                    .reduce(|a, b| a.union(&b))
+            };
+            let before = region(
+                layout.molecules.iter().filter_map(|(molecule_id, _)| rectangles.get(molecule_id).copied()).collect(),
+                route
+                    .steps
+                    .iter()
+                    .filter_map(|step| self.state.get_shape(&step.arrow_id))
+                    .flat_map(|arrow| [arrow.start(), arrow.end()])
+                    .collect(),
+            );
+            let after = region(
+                layout
+                    .molecules
+                    .iter()
+                    .map(|(molecule_id, center)| {
+                        let size = sizes[molecule_id];
+                        Rectangle::new(*center - Vector::new(size.width / 2.0, size.height / 2.0), size)
+                    })
+                    .collect(),
+                layout.arrows.iter().flat_map(|(_, tail, head)| [*tail, *head]).collect(),
+            );
+            let (Some(before), Some(after)) = (before, after) else {
+                continue;
+            };
+            let offset = before.position() - after.position();
+
+            for (molecule_id, center) in layout.molecules {
+                let molecule = self.state.get_molecule_mut(&molecule_id).context("while arranging routes")?;
+                let current = molecule.outer_rectangle().center();
+                molecule.move_molecule(center + offset - current);
+            }
+            for (arrow_id, tail, head) in layout.arrows {
+                let Some(arrow) = self.state.get_shape(&arrow_id) else {
+                    continue;
+                };
+                let (start, end) = (arrow.start(), arrow.end());
+                self.state
+                    .drag_shape(&arrow_id, ShapeDrag::Start, start, tail + offset)
+                    .context("while arranging routes")?;
+                self.state
+                    .drag_shape(&arrow_id, ShapeDrag::End, end, head + offset)
+                    .context("while arranging routes")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// writes the reactions around the arrows on visible layers to an RXN or RDF file or as a line
+    /// of reaction SMILES each, choosing the format from the extension, reading the molecules
+    /// around each arrow as its reactants, agents and products, returning how many were written
+    pub fn export_reactions(&self, path: &std::path::Path) -> Result<usize> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        let bond_length = self.state.style().bond_length;
+
+        let reactions = self
+            .reaction_schemes()
+            .iter()
+            .map(|scheme| {
+                let [reactants, agents, products] = [&scheme.reactants, &scheme.agents, &scheme.products].map(|molecule_ids| {
+                    molecule_ids
+                        .iter()
+                        .map(|molecule_id| self.state.get_molecule(molecule_id))
+                        .collect::<Result<Vec<_>>>()
+                });
+
+                // conditions are kept on the arrow
+                let conditions = self
+                    .state
+                    .get_shape(&scheme.arrow_id)
+                    .map(|shape| shape.conditions().clone())
+                    .unwrap_or_default();
+
+                Ok(([reactants?, agents?, products?], conditions))
+            })
+            .collect::<Result<Vec<_>>>()
+            .context("while exporting reactions")?;
+
+        let structures = || {
+            reactions
+                .iter()
+                .map(|([reactants, agents, products], conditions)| {
+                    export::reaction_structures(reactants, agents, products, conditions.clone())
+                })
+                .collect::<Result<Vec<_>>>()
+                .context("while exporting reactions")
+        };
+        let contents = match extension.as_str() {
+            "rxn" => match structures()?.as_slice() {
+                [reaction] => formats::write_rxn(reaction, bond_length),
+                _ => return Err(formats::Error::RxnReactionCount(reactions.len())).context("while exporting reactions"),
+            },
+            "rdf" => formats::write_rdf(&structures()?, bond_length),
+            _ => reactions
+                .iter()
+                .map(|([reactants, agents, products], _)| Ok(export::reaction_smiles(reactants, agents, products)? + "\n"))
+                .collect::<Result<String>>()
+                .context("while exporting reactions")?,
+        };
+
+        std::fs::write(path, contents)
+            .with_context(|| format!("while writing {}", path.display()))
+            .context("while exporting reactions")?;
+
+        Ok(reactions.len())
+    }
+
+    /// draws the molecules on visible layers to an SVG or PNG file with their structure embedded,
+    /// returning how many were drawn
+    pub fn export_image(&self, path: &std::path::Path) -> Result<usize> {
+        let (svg, molfile, count) = self.image(None).context("while exporting image")?;
+        formats::write_image(path, svg, &molfile, self.state.style().export_resolution).context("while exporting image")?;
+
+        Ok(count)
+    }
+
+    /// an svg of structures read from a file, drawn on an empty canvas of their own so the
+    /// document and its caches are left be
+    pub fn structures_svg(structures: Vec<Structure>) -> Result<String> {
+        let mut canvas = MolCanvas::default();
+        canvas.place_structures(structures, Point::ORIGIN).context("while drawing structures")?;
+
+        Ok(canvas.image(None).context("while drawing structures")?.0)
+    }
+
+    /// an svg of reactions read from a file, drawn on an empty canvas of their own
+    pub fn reactions_svg(reactions: Vec<formats::Reaction>) -> Result<String> {
+        let mut canvas = MolCanvas::default();
+        canvas.place_reactions(reactions, Point::ORIGIN).context("while drawing reactions")?;
+
+        Ok(canvas.image(None).context("while drawing reactions")?.0)
+    }
+
+    /// the selected molecules, or the whole drawing when nothing is selected, rendered for the
+    /// clipboard, with how many molecules are in it
+    pub fn clipboard_image(&self) -> Result<(formats::Bitmap, usize)> {
+        let selected = self.state.selection().iter().map(SingleSelection::molecule_id).collect::<Vec<_>>();
+        let only = (!selected.is_empty()).then_some(selected.as_slice());
+
+        let (svg, _, count) = self.image(only).context("while copying image")?;
+        let bitmap = formats::render_bitmap(&svg, self.state.style().export_resolution).context("while copying image")?;
+
+        Ok((bitmap, count))
+    }
+
+    /// the visible drawing as an svg, with a molfile of its molecules and how many there are.
+    /// Given molecules, only they and the text under and beside them are drawn
+    fn image(&self, only: Option<&[MoleculeId]>) -> Result<(String, String, usize)> {
+        let included = |molecule_id: &MoleculeId| only.is_none_or(|only| only.contains(molecule_id));
+        let whole = only.is_none();
+        let molecules = self
+            .state
+            .molecules()
+            .filter(|(molecule_id, _)| included(molecule_id))
+            .map(|(_, molecule)| molecule)
+            .collect::<Vec<_>>();
+        let captions = self
+            .captions()
+            .into_iter()
+            .filter(|(molecule_id, _, _)| included(molecule_id))
+            .collect::<Vec<_>>();
+        let region = molecules
+            .iter()
+            .map(|molecule| molecule.outer_rectangle())
+            .chain(captions.iter().map(|(_, caption, position)| caption.bounds_at(*position)))
+            .chain(
+                molecules
+                    .iter()
+                    .filter(|_| self.state.style().bond_annotations)
+                    .flat_map(|molecule| molecule.bond_annotations())
+                    .map(|(annotation, position)| annotation.bounds_at(position)),
+            )
+            .chain(
+                molecules
+                    .iter()
+                    .filter(|_| self.state.style().metal_counts)
+                    .flat_map(|molecule| molecule.metal_labels())
+                    .map(|(label, position)| label.bounds_at(position)),
+            )
+            .chain(self.state.orbitals().filter(|_| whole).map(|(_, orbital, start, end)| orbital.bounds(start, end)))
+            .chain(self.state.inks().filter(|_| whole).map(|(_, ink)| ink.bounds()))
+            .chain(self.state.shapes().filter(|_| whole).map(|(_, shape)| shape.bounds()))
+            .chain(
+                self.state
+                    .shapes()
+                    .filter(|(_, shape)| whole && shape.kind() == ShapeKind::Arrow)
+                    .flat_map(|(_, arrow)| ArrowSide::ALL.into_iter().flat_map(|side| self.arrow_text_layout(arrow, side)))
+                    .map(|(line, position)| line.bounds_at(position)),
+            )
+            .chain(self.state.annotations().filter(|_| whole).map(|(_, annotation)| annotation.bounds()))
+            .chain(self.exported_background().filter(|_| whole).map(Background::bounds))
+            .reduce(|a, b| a.union(&b))
+            .ok_or(formats::Error::NothingToExport)
+            .context("while drawing image")?
+            .expand(self.state.style().export_margin);
+
+        // images are drawn at a scaling of one, dark on light whatever the theme
+        let color = Color::BLACK;
+        let background = Color::WHITE;
+        let stroke = Stroke::default().with_color(color).with_width(Self::BOND_WIDTH);
+
+        let mut svg = SvgWriter::default();
+        if let Some(background) = self.exported_background().filter(|_| whole) {
+            background.export(&mut svg);
+        }
+        for molecule in &molecules {
+            molecule
+                .draw(&mut svg, &color, &stroke, &color, self.state.style())
+                .context("while drawing image")?;
+
+            if self.state.style().lewis_structures {
+                Self::draw_lone_pairs(&mut svg, molecule, color).context("while drawing image")?;
+            }
+        }
+        if whole {
+            self.draw_interactions(&mut svg, &stroke, &color).context("while drawing image")?;
+            self.draw_orbitals(&mut svg, color);
+            self.draw_atom_maps(&mut svg, false);
+            self.draw_arrow_text(&mut svg, color);
+            self.draw_inks(&mut svg, color);
+            for (_shape_id, shape) in self.state.shapes() {
+                shape.draw(&mut svg, color);
+            }
+            for (_annotation_id, annotation) in self.state.annotations() {
+                annotation.draw(&mut svg, color);
+            }
+        }
+        if self.state.style().bond_crossings {
+            self.draw_bond_crossings(&mut svg, &molecules, &stroke, &color, background, 1.0)
+                .context("while drawing image")?;
+        }
+        for (_, caption, position) in &captions {
+            caption.draw(&mut svg, *position, color);
+        }
+
+        let structure = Structure::from_molecules(molecules.iter().copied()).context("while drawing image")?;
+        let molfile = formats::write_molfile(&structure, self.state.style().bond_length);
+
+        // drawings of the whole document carry all of it, so the svg can be opened as a project
+        let project = match whole {
+            true => Some(session::write_project(&self.document_session(&self.state.snapshot())?.0)),
+            false => None,
+        };
+
+        Ok((svg.finish(region, background, &molfile, project.as_deref()), molfile, molecules.len()))
+    }
+
+    /// names, bold compound numbers and captions stacked under each visible molecule, with the
+    /// molecule and where the middle of each one's top edge goes
+    fn captions(&self) -> Vec<(MoleculeId, TextOutline, Point)> {
+        let style = self.state.style();
+        let numbers = match style.compound_numbers {
+            true => numbering::compound_numbers(self.state.molecules()).into_iter().collect(),
+            false => FxHashMap::default(),
+        };
+        let bold = Font { weight: font::Weight::Bold, ..style.label_font.font() };
+
+        let mut captions = vec![];
+        for (molecule_id, molecule) in self.state.molecules() {
+            let name = molecule.name_outline().filter(|_| style.molecule_names).cloned();
+            let number = numbers
+                .get(molecule_id)
+                .map(|number| TextOutline::new(number.clone(), Self::COMPOUND_NUMBER_SIZE, bold));
+            let caption = molecule.caption_outline().cloned();
+
+            let mut position = molecule.caption_position();
+            for outline in [name, number, caption].into_iter().flatten() {
+                let bounds = outline.bounds_at(position);
+                captions.push((*molecule_id, outline, position));
+                position.y = bounds.y + bounds.height + Self::CAPTION_SPACING;
+            }
+        }
+
+        captions
+    }
+
+    /// visible molecules that have been given a name, in drawing order, with their thumbnail
+    pub fn named_molecules(&self) -> impl Iterator<Item = (MoleculeId, &str, Option<svg::Handle>)> {
+        self.state.molecules().filter_map(|(molecule_id, molecule)| {
+            let thumbnail = self.summary.thumbnails.get(molecule_id).map(|(_, thumbnail)| thumbnail.clone());
+            Some((*molecule_id, molecule.name()?, thumbnail))
+        })
+    }
+
+    /// thumbnails of the named molecules, drawn again only for those whose drawing has changed
+    fn thumbnails(&mut self) -> FxHashMap<MoleculeId, (String, svg::Handle)> {
+        let mut previous = std::mem::take(&mut self.summary.thumbnails);
+        let named = self
+            .state
+            .molecules()
+            .filter(|(_, molecule)| molecule.name().is_some())
+            .map(|(molecule_id, _)| *molecule_id)
+            .collect::<Vec<_>>();
+
+        named
+            .into_iter()
+            .filter_map(|molecule_id| {
+                let (drawing, _, _) = self.image(Some(&[molecule_id])).ok()?;
+                let thumbnail = match previous.remove(&molecule_id) {
+                    Some((previous, thumbnail)) if previous == drawing => thumbnail,
+                    _ => thumbnail::from_svg(&drawing).ok()?,
+                };
+
+                Some((molecule_id, (drawing, thumbnail)))
+            })
+            .collect()
+    }
+
+    /// the molecule the copy menu is open on and where on screen, while it still exists
+    pub fn context_menu(&self) -> Option<(MoleculeId, Point)> {
+        self.context_menu
+            .filter(|(molecule_id, _)| self.state.molecules().any(|(id, _)| id == molecule_id))
+    }
+
+    /// a molecule written out as text for the clipboard
+    pub fn molecule_text(&self, molecule_id: &MoleculeId, format: CopyFormat) -> Result<String> {
+        let molecule = self.state.get_molecule(molecule_id).context("while copying molecule")?;
+
+        export::molecule_text(molecule, format, self.state.style().bond_length).with_context(|| format!("while copying molecule as {}", format))
+    }
+
+    /// what the inspector reads out about the document, as of its last change
+    pub fn summary(&self) -> &Summary {
+        &self.summary
+    }
+
+    fn summarize(&mut self) {
+        self.summary = Summary {
+            thumbnails: self.thumbnails(),
+            description: self.describe_document(),
+            unbalanced_atom_maps: self.unbalanced_atom_maps(),
+            mass_imbalances: self.describe_mass_imbalances(),
+            routes: self.route_overview(),
+        };
+    }
+
+    /// a sentence per visible molecule, for reading the document without seeing it
+    fn describe_document(&self) -> Vec<String> {
+        self.state
+            .molecules()
+            .enumerate()
+            .map(|(index, (_, molecule))| description::describe_molecule(index + 1, molecule))
+            .collect()
+    }
+
+    /// a sentence about the focused atom or the selected item
+    pub fn describe_selection(&self) -> Option<String> {
+        let selection = match self.keyboard_focus() {
+            Some((molecule_id, atom_id)) => SingleSelection::Atom(molecule_id, atom_id),
+            None => match self.state.selection().iter().count() {
+                0 => return None,
+                1 => self.state.selection().only()?,
+                count => return Some(format!("{} items selected", count)),
+            },
+        };
+
+        description::describe_selection(&self.state, selection).ok()
+    }
+
+    /// one based index of the focused search match and the number of matches
+    pub fn search_position(&self) -> (usize, usize) {
+        self.search.position()
+    }
+
+    /// the canvas point in the middle of the view
+    pub fn view_center(&self) -> Point {
+        Point::ORIGIN - self.translation
+    }
+
+    /// lines structures up left to right at the document's bond length, centred on the free space
+    /// nearest a point so they don't land on what's already drawn, then turns labels away from
+    /// what they overlap, returning the ids given to each structure's atoms. nothing is placed on
+    /// a hidden or locked layer
+    fn place_structures(&mut self, mut structures: Vec<Structure>, center: Point) -> Result<Vec<Vec<AtomId>>> {
+        if !self.state.can_add_molecules() {
+            return Ok(vec![]);
+        }
+        let bond_length = self.state.style().bond_length;
+        for structure in &mut structures {
+            structure.scale(bond_length / Self::BOND_LENGTH);
+        }
+
+        let widths = structures.iter().map(|structure| structure.bounds().width).collect::<Vec<_>>();
+        let total_width = widths.iter().sum::<f32>() + bond_length * widths.len().saturating_sub(1) as f32;
+        let height = structures.iter().map(|structure| structure.bounds().height).fold(0.0, f32::max);
+
+        // atom positions leave out their labels, which the clearance of a bond length makes room for
+        let size = Size::new(total_width + bond_length, height + bond_length);
+        let center = self.state.free_position(size, center, bond_length);
+        let mut left = center.x - total_width / 2.0;
+
+        let mut atom_ids = vec![];
+        for (structure, width) in structures.iter_mut().zip(widths) {
+            let bounds = structure.bounds();
+            structure.translate(Vector::new(left - bounds.x, center.y - bounds.center_y()));
+            left += width + bond_length;
+
+            atom_ids.push(self.state.add_structure(structure).context("while importing structures")?);
+        }
+
+        // laid out structures, e.g. from SMILES, put their labels wherever the layout left them
+        self.state.resolve_overlaps().context("while importing structures")?;
+
+        Ok(atom_ids)
+    }
+
+    /// adds copies of molecules copied from this or another document at the document's bond length,
+    /// centred on the free space nearest a point, unless the active layer is hidden or locked
+    fn place_molecules(&mut self, clipboard: &Clipboard, center: Point) -> Result<()> {
+        let Some(bounds) = clipboard.bounds().filter(|_| self.state.can_add_molecules()) else {
+            return Ok(());
+        };
+        let bond_length = self.state.style().bond_length;
+        let factor = bond_length / clipboard.bond_length();
+
+        let size = Size::new(bounds.width * factor + bond_length, bounds.height * factor + bond_length);
+        let center = self.state.free_position(size, center, bond_length);
+        for molecule in clipboard.molecules() {
+            self.state
+                .paste_molecule(molecule, bounds.center(), factor, center - bounds.center())
+                .context("while pasting molecules")?;
+        }
+
+        Ok(())
+    }
+
+    /// adds the molecules, shapes and text a project shows, scaled to the document's bond length
+    /// and kept where they are to each other, centred on the free space nearest a point
+    fn place_project(&mut self, session: Session, center: Point) -> Result<()> {
+        let mut project = MolCanvas::default();
+        let atom_ids = session.structure.atoms.iter().map(|_| AtomId::new()).collect();
+        project.restore_document(session, atom_ids).context("while placing project")?;
+
+        // only what the active and annotation layers take is added
+        let (can_add_molecules, can_annotate) = (self.state.can_add_molecules(), self.state.can_annotate());
+        let molecules = project.state.molecules().filter(|_| can_add_molecules).map(|(_, molecule)| molecule).collect::<Vec<_>>();
+        let shapes = project.state.shapes().filter(|_| can_annotate).map(|(_, shape)| shape).collect::<Vec<_>>();
+        let annotations = project.state.annotations().filter(|_| can_annotate).map(|(_, annotation)| annotation).collect::<Vec<_>>();
+        let bounds = molecules
+            .iter()
+            .map(|molecule| molecule.outer_rectangle())
+            .chain(shapes.iter().map(|shape| shape.bounds()))
+            .chain(annotations.iter().map(|annotation| annotation.bounds()))
+            .reduce(|a, b| a.union(&b));
+        let Some(bounds) = bounds else {
+            return Ok(());
+        };
+
+        let bond_length = self.state.style().bond_length;
+        let factor = bond_length / project.state.style().bond_length;
+        let size = Size::new(bounds.width * factor + bond_length, bounds.height * factor + bond_length);
+        let offset = self.state.free_position(size, center, bond_length) - bounds.center();
+        for molecule in molecules {
+            self.state.paste_molecule(molecule, bounds.center(), factor, offset).context("while placing project")?;
+        }
+        for shape in shapes {
+            self.state.paste_shape(shape, bounds.center(), factor, offset).context("while placing project")?;
+        }
+        for annotation in annotations {
+            self.state
+                .paste_annotation(annotation, bounds.center(), factor, offset)
+                .context("while placing project")?;
+        }
+
+        Ok(())
+    }
+
+    /// the selected molecules, or those with selected atoms or bonds, copied whole with an SD file
+    /// of them for the system clipboard, none when nothing is selected
+    pub fn copy_selection(&self) -> Result<Option<Clipboard>> {
+        let selected = self.state.selection().iter().map(SingleSelection::molecule_id).collect::<FxHashSet<_>>();
+        let molecules = self
+            .state
+            .molecules()
+            .filter(|(molecule_id, _)| selected.contains(molecule_id))
+            .map(|(_, molecule)| molecule.clone())
+            .collect::<Vec<_>>();
+        if molecules.is_empty() {
+            return Ok(None);
+        }
+
+        let bond_length = self.state.style().bond_length;
+        let text = export::molecules_sdf(molecules.iter(), bond_length).context("while copying selection")?;
+
+        Ok(Some(Clipboard::new(molecules, bond_length, text)))
+    }
+
+    /// lays out each reaction in a row at the document's bond length, its reactants and products
+    /// either side of a new arrow with its agents over it, centred on the free space nearest a
+    /// point and each one below the last, unless the layers they'd go on are hidden or locked
+    fn place_reactions(&mut self, reactions: Vec<formats::Reaction>, position: Point) -> Result<()> {
+        if !self.state.can_add_molecules() || !self.state.can_annotate() {
+            return Ok(());
+        }
+        let bond_length = self.state.style().bond_length;
+        let row_width = |structures: &[Structure]| {
+            structures.iter().map(|structure| structure.bounds().width).sum::<f32>()
+                + bond_length * structures.len().saturating_sub(1) as f32
+        };
+        let row_height = |structures: &[Structure]| structures.iter().map(|structure| structure.bounds().height).fold(0.0, f32::max);
+
+        let mut next = position;
+        for reaction in reactions {
+            let mut rows = [reaction.reactants, reaction.agents, reaction.products];
+            for structure in rows.iter_mut().flatten() {
+                structure.scale(bond_length / Self::BOND_LENGTH);
+            }
+            let [reactants, agents, products] = rows;
+
+            // the arrow reaches a bond length past its agents at each end, and each side of it is
+            // a bond length clear of the arrow
+            let arrow_length = (row_width(&agents) + bond_length * 2.0).max(bond_length * Self::REACTION_ARROW_LENGTH);
+            let [before, after] = [&reactants, &products].map(|side| match side.is_empty() {
+                true => 0.0,
+                false => row_width(side) + bond_length,
+            });
+            let total_width = before + arrow_length + after;
+            // agents stand half a bond length over the arrow
+            let half_height = (row_height(&reactants).max(row_height(&products)) / 2.0).max(row_height(&agents) + bond_length / 2.0);
+
+            let size = Size::new(total_width + bond_length, half_height * 2.0 + bond_length);
+            let center = self.state.free_position(size, next, bond_length);
+            let tail = Point::new(center.x - total_width / 2.0 + before, center.y);
+            let head = Point::new(tail.x + arrow_length, center.y);
+
+            let agents_left = tail.x + (arrow_length - row_width(&agents)) / 2.0;
+            let agents_center = center.y - bond_length / 2.0 - row_height(&agents) / 2.0;
+            let rows = [
+                (reactants, center.x - total_width / 2.0, center.y),
+                (agents, agents_left, agents_center),
+                (products, head.x + bond_length, center.y),
+            ];
+            for (structures, mut left, center_y) in rows {
+                for mut structure in structures {
+                    let bounds = structure.bounds();
+                    structure.translate(Vector::new(left - bounds.x, center_y - bounds.center_y()));
+                    left += bounds.width + bond_length;
+
+                    self.state.add_structure(&structure).context("while placing reaction")?;
+                }
+            }
+
+            let style = self.new_shape_style();
+            let arrow_id = self
+                .state
+                .add_shape(ShapeKind::Arrow, tail, head, style)
+                .context("while placing reaction")?;
+            self.state
+                .set_reaction_conditions(&arrow_id, reaction.conditions)
+                .context("while placing reaction")?;
+
+            // far enough apart that no molecule is taken to be beside the next reaction's arrow
+            next = Point::new(position.x, center.y + size.height + bond_length * Self::REACTION_REACH);
+        }
+
+        Ok(())
+    }
+
+    /// the whole document with the view, tool, selection and timeline, to open with next time
+    pub fn session(&self) -> Result<Session> {
+        let (mut session, indices) = self.document_session(&self.state.snapshot()).context("while saving session")?;
+        let molecules = self.state.all_molecules();
+
+        session.selection = self
+            .state
+            .selection()
+            .iter()
+            .filter_map(|selected| {
+                let molecule = molecules.get(&selected.molecule_id())?;
+                Some(match selected {
+                    SingleSelection::Molecule(_) => SessionSelection::Molecule(indices[molecule.atoms().next()?.0]),
+                    SingleSelection::Atom(_, atom_id) => SessionSelection::Atom(*indices.get(atom_id)?),
+                    SingleSelection::Bond(_, bond_id) => {
+                        let bond = molecule.get_bond(bond_id).ok()?;
+                        SessionSelection::Bond(*indices.get(&bond.start())?, *indices.get(&bond.end())?)
+                    }
+                })
+            })
+            .collect();
+
+        session.layers = self
+            .state
+            .layers()
+            .map(|(_, layer)| SessionLayer {
+                name: layer.name().to_string(),
+                visible: layer.is_visible(),
+                locked: layer.is_locked(),
+            })
+            .collect();
+        session.active_layer = self.state.layers().position(|(layer_id, _)| *layer_id == self.state.active_layer()).unwrap_or_default();
+        session.label_font = self.state.style().label_font;
+        session.translation = self.translation;
+        session.scaling = *self.scaling;
+        session.tool = self.tool;
+
+        for version in self.timeline.versions() {
+            let (version_session, version_indices) = self.document_session(version.snapshot()).context("while saving session")?;
+            let mut shared_atoms = version_indices
+                .iter()
+                .filter_map(|(atom_id, index)| Some((*index, *indices.get(atom_id)?)))
+                .collect::<Vec<_>>();
+            shared_atoms.sort();
+
+            session.versions.push(SessionVersion {
+                name: version.name().to_string(),
+                session: version_session,
+                shared_atoms,
+            });
+        }
+
+        Ok(session)
+    }
+
+    /// the molecules, ink and shapes of a snapshot of the document as a session, with the index
+    /// each atom is saved at
+    fn document_session(&self, snapshot: &Snapshot) -> Result<(Session, FxHashMap<AtomId, usize>)> {
+        let molecules = &snapshot.molecules;
+        let structure = Structure::from_molecules(molecules.values()).context("while saving document")?;
+
+        // atoms are numbered in the order the structure lists them
+        let indices = molecules
+            .values()
+            .flat_map(|molecule| molecule.atoms().map(|(atom_id, _)| *atom_id))
+            .enumerate()
+            .map(|(index, atom_id)| (atom_id, index))
+            .collect::<FxHashMap<_, _>>();
+
+        // layers are numbered in order, items on one that's missing being put on the first
+        let layer_indices = self
+            .state
+            .layers()
+            .enumerate()
+            .map(|(index, (layer_id, _))| (*layer_id, index))
+            .collect::<FxHashMap<_, _>>();
+        let layer_index = |layer_id: LayerId| layer_indices.get(&layer_id).copied().unwrap_or_default();
+
+        let compounds = molecules
+            .values()
+            .filter_map(|molecule| {
+                Some(SessionCompound {
+                    atom: indices[molecule.atoms().next()?.0],
+                    name: molecule.name().map(str::to_string),
+                    metadata: molecule.metadata().clone(),
+                    layer: layer_index(molecule.layer()),
+                    locked: molecule.is_locked(),
+                })
+            })
+            .collect();
+
+        let bends = molecules
+            .values()
+            .flat_map(|molecule| molecule.bonds())
+            .filter(|(_, bond)| !bond.waypoints().is_empty() || bond.curvature() != 0.0)
+            .filter_map(|(_, bond)| {
+                Some(SessionBend {
+                    start: *indices.get(&bond.start())?,
+                    end: *indices.get(&bond.end())?,
+                    waypoints: bond.waypoints().to_vec(),
+                    curvature: bond.curvature(),
+                })
+            })
+            .collect();
+
+        let disconnections = molecules
+            .values()
+            .flat_map(|molecule| molecule.bonds())
+            .filter(|(_, bond)| bond.is_disconnection())
+            .filter_map(|(_, bond)| Some((*indices.get(&bond.start())?, *indices.get(&bond.end())?)))
+            .collect();
+
+        let inks = snapshot
+            .inks
+            .values()
+            .map(|ink| SessionInk {
+                width: ink.width(),
+                color: ink.color(),
+                points: ink.points().to_vec(),
+                layer: layer_index(ink.layer()),
+            })
+            .collect();
+        let shapes = snapshot
+            .shapes
+            .values()
+            .map(|shape| SessionShape {
+                kind: shape.kind(),
+                start: shape.start(),
+                end: shape.end(),
+                style: shape.style(),
+                conditions: shape.conditions().clone(),
+                above: shape.text(ArrowSide::Above).to_string(),
+                below: shape.text(ArrowSide::Below).to_string(),
+                layer: layer_index(shape.layer()),
+            })
+            .collect();
+
+        let annotations = snapshot
+            .annotations
+            .values()
+            .map(|annotation| SessionAnnotation {
+                text: annotation.text().to_string(),
+                position: annotation.position(),
+                layer: layer_index(annotation.layer()),
+            })
+            .collect();
+
+        let orbitals = snapshot
+            .orbitals
+            .values()
+            .filter_map(|orbital| {
+                Some(SessionOrbital {
+                    kind: orbital.kind(),
+                    atom: *indices.get(&orbital.atom())?,
+                    partner: match orbital.partner() {
+                        Some(partner) => Some(*indices.get(&partner)?),
+                        None => None,
+                    },
+                    angle: orbital.angle(),
+                    size: orbital.size(),
+                })
+            })
+            .collect();
+
+        let session = Session {
+            bounds: structure.bounds(),
+            structure,
+            bond_length: snapshot.bond_length,
+            compounds,
+            bends,
+            disconnections,
+            inks,
+            shapes,
+            orbitals,
+            annotations,
+            ..Default::default()
+        };
+
+        Ok((session, indices))
+    }
+
+    /// puts the document, view, selection and timeline of a saved session back, without making an
+    /// undo step of it
+    pub fn restore_session(&mut self, mut session: Session) -> Result<()> {
+        let versions = std::mem::take(&mut session.versions);
+        let layers = session.layers.clone();
+        let atom_ids = session.structure.atoms.iter().map(|_| AtomId::new()).collect::<Vec<_>>();
+        self.restore_document(session, atom_ids.clone()).context("while restoring session")?;
+
+        // versions are put back on a canvas of their own with the document's layers, keeping the
+        // ids of the atoms they share with the document so the two are compared atom by atom
+        for version in versions {
+            let mut version_atom_ids = version.session.structure.atoms.iter().map(|_| AtomId::new()).collect::<Vec<_>>();
+            for (atom, document_atom) in version.shared_atoms {
+                if let (Some(version_atom_id), Some(atom_id)) = (version_atom_ids.get_mut(atom), atom_ids.get(document_atom)) {
+                    *version_atom_id = *atom_id;
+                }
+            }
+
+            let mut canvas = MolCanvas::default();
+            canvas
+                .restore_document(Session { layers: layers.clone(), ..version.session }, version_atom_ids)
+                .context("while restoring session")?;
+
+            let layer_ids = canvas
+                .state
+                .layers()
+                .zip(self.state.layers())
+                .map(|((version_layer_id, _), (layer_id, _))| (*version_layer_id, *layer_id))
+                .collect::<FxHashMap<_, _>>();
+            let mut snapshot = canvas.state.snapshot();
+            for molecule in snapshot.molecules.values_mut() {
+                molecule.set_layer(layer_ids.get(&molecule.layer()).copied().unwrap_or(self.state.active_layer()));
+            }
+            for ink in snapshot.inks.values_mut() {
+                ink.set_layer(layer_ids.get(&ink.layer()).copied().unwrap_or(self.state.active_layer()));
+            }
+            for shape in snapshot.shapes.values_mut() {
+                shape.set_layer(layer_ids.get(&shape.layer()).copied().unwrap_or(self.state.active_layer()));
+            }
+            for annotation in snapshot.annotations.values_mut() {
+                annotation.set_layer(layer_ids.get(&annotation.layer()).copied().unwrap_or(self.state.active_layer()));
+            }
+
+            self.timeline.add(version.name, snapshot);
+        }
+
+        Ok(())
+    }
+
+    /// puts a saved document with the view and selection it was saved with back, its atoms taking
+    /// the given ids in order
+    fn restore_document(&mut self, mut session: Session, atom_ids: Vec<AtomId>) -> Result<()> {
+        self.state.set_bond_length(session.bond_length).context("while restoring session")?;
+        self.state.set_label_font(session.label_font).context("while restoring session")?;
+
+        let read = session.structure.bounds();
+        let scale = match read.width.max(read.height) > f32::EPSILON {
+            true => session.bounds.width.max(session.bounds.height) / read.width.max(read.height),
+            false => 1.0,
+        };
+        session.structure.scale(scale);
+        session.structure.translate(session.bounds.center() - session.structure.bounds().center());
+        let atom_ids = self.state.add_structure_as(&session.structure, atom_ids).context("while restoring session")?;
+
+        let atom = |index: usize| {
+            let atom_id = atom_ids.get(index)?;
+            Some((self.state.find_atom(atom_id)?, *atom_id))
+        };
+        let selection = session
+            .selection
+            .iter()
+            .filter_map(|selected| match *selected {
+                SessionSelection::Molecule(index) => Some(SingleSelection::Molecule(atom(index)?.0)),
+                SessionSelection::Atom(index) => {
+                    let (molecule_id, atom_id) = atom(index)?;
+                    Some(SingleSelection::Atom(molecule_id, atom_id))
+                }
+                SessionSelection::Bond(start, end) => {
+                    let ((molecule_id, start), (_, end)) = (atom(start)?, atom(end)?);
+                    let molecule = self.state.get_molecule(&molecule_id).ok()?;
+                    let (bond_id, _) = molecule.bonds().find(|(_, bond)| {
+                        (bond.start(), bond.end()) == (start, end) || (bond.start(), bond.end()) == (end, start)
+                    })?;
+                    Some(SingleSelection::Bond(molecule_id, *bond_id))
+                }
+            })
+            .collect();
+        let compounds = session
+            .compounds
+            .into_iter()
+            .filter_map(|compound| Some((atom(compound.atom)?.0, compound)))
+            .collect::<Vec<_>>();
+        let bends = session
+            .bends
+            .into_iter()
+            .filter_map(|bend| Some((atom(bend.start)?, atom(bend.end)?.1, bend)))
+            .collect::<Vec<_>>();
+        let disconnections = session
+            .disconnections
+            .iter()
+            .filter_map(|(start, end)| Some((atom(*start)?, atom(*end)?.1)))
+            .collect::<Vec<_>>();
+        let orbitals = session
+            .orbitals
+            .into_iter()
+            .filter_map(|orbital| {
+                let partner = match orbital.partner {
+                    Some(partner) => Some(atom(partner)?.1),
+                    None => None,
+                };
+                Some(Orbital::new(orbital.kind, atom(orbital.atom)?.1, partner, orbital.angle, orbital.size))
+            })
+            .collect::<Vec<_>>();
+
+        let mut molecule_layers = vec![];
+        for (molecule_id, compound) in compounds {
+            let Ok(molecule) = self.state.get_molecule_mut(&molecule_id) else {
+                continue;
+            };
+            if let Some(name) = compound.name {
+                molecule.set_name(name);
+            }
+            *molecule.metadata_mut() = compound.metadata;
+            molecule.set_locked(compound.locked);
+            molecule_layers.push((molecule_id, compound.layer));
+        }
+
+        for ((molecule_id, start), end, bend) in bends {
+            let Ok(molecule) = self.state.get_molecule_mut(&molecule_id) else {
+                continue;
+            };
+            let Some((bond_id, bond)) = molecule.bonds().find(|(_, bond)| bond.atom_ids().all(|atom_id| [start, end].contains(&atom_id))) else {
+                continue;
+            };
+
+            // waypoints and curves are measured from the start atom, so they're turned around for a
+            // bond read back the other way
+            let (waypoints, curvature) = match bond.start() == start {
+                true => (bend.waypoints, bend.curvature),
+                false => (
+                    bend.waypoints.iter().rev().map(|waypoint| Vector::new(1.0 - waypoint.x, -waypoint.y)).collect(),
+                    -bend.curvature,
+                ),
+            };
+            let bond_id = *bond_id;
+            let bond = molecule.get_bond_mut(&bond_id)?;
+            match curvature != 0.0 {
+                true => bond.set_curvature(curvature),
+                false => bond.set_waypoints(waypoints),
+            }
+        }
+
+        for ((molecule_id, start), end) in disconnections {
+            let Ok(molecule) = self.state.get_molecule_mut(&molecule_id) else {
+                continue;
+            };
+            let Some((bond_id, _)) = molecule.bonds().find(|(_, bond)| bond.atom_ids().all(|atom_id| [start, end].contains(&atom_id))) else {
+                continue;
+            };
+            let bond_id = *bond_id;
+            molecule.get_bond_mut(&bond_id)?.set_disconnection(true);
+        }
+
+        for orbital in orbitals {
+            self.state.insert_orbital(orbital).context("while restoring session")?;
+        }
+
+        let mut ink_layers = vec![];
+        for ink in session.inks {
+            let ink_id = self.state.add_ink(&ink.points, ink.width, ink.color).context("while restoring session")?;
+            ink_layers.push((ink_id, ink.layer));
+        }
+        let mut shape_layers = vec![];
+        for shape in session.shapes {
+            let shape_id = self
+                .state
+                .add_shape(shape.kind, shape.start, shape.end, shape.style)
+                .context("while restoring session")?;
+            self.state
+                .set_reaction_conditions(&shape_id, shape.conditions)
+                .context("while restoring session")?;
+            for (side, text) in [(ArrowSide::Above, shape.above), (ArrowSide::Below, shape.below)] {
+                self.state.set_arrow_text(&shape_id, side, text).context("while restoring session")?;
+            }
+            shape_layers.push((shape_id, shape.layer));
+        }
+        let mut annotation_layers = vec![];
+        for annotation in session.annotations {
+            let annotation_id = self
+                .state
+                .add_annotation(annotation.text, annotation.position)
+                .context("while restoring session")?;
+            annotation_layers.push((annotation_id, annotation.layer));
+        }
+
+        // everything is added to the default layers first, as hidden and locked layers can't be
+        // drawn on, then moved to the session's
+        if !session.layers.is_empty() {
+            let layers = session
+                .layers
+                .into_iter()
+                .map(|session_layer| {
+                    let mut layer = Layer::new(session_layer.name);
+                    layer.set_visible(session_layer.visible);
+                    layer.set_locked(session_layer.locked);
+                    layer
+                })
+                .collect();
+            let layer_ids = self.state.set_layers(layers, session.active_layer);
+
+            for (molecule_id, layer) in molecule_layers {
+                if let Some(layer_id) = layer_ids.get(layer) {
+                    self.state.move_to_layer(&molecule_id, *layer_id).context("while restoring session")?;
+                }
+            }
+            for (ink_id, layer) in ink_layers {
+                if let Some(layer_id) = layer_ids.get(layer) {
+                    self.state.move_ink_to_layer(&ink_id, *layer_id).context("while restoring session")?;
+                }
+            }
+            for (shape_id, layer) in shape_layers {
+                if let Some(layer_id) = layer_ids.get(layer) {
+                    self.state.move_shape_to_layer(&shape_id, *layer_id).context("while restoring session")?;
+                }
+            }
+            for (annotation_id, layer) in annotation_layers {
+                if let Some(layer_id) = layer_ids.get(layer) {
+                    self.state.move_annotation_to_layer(&annotation_id, *layer_id).context("while restoring session")?;
+                }
+            }
+        }
+        self.state.new_selection(selection);
+
+        self.translation = session.translation;
+        self.scaling = Scaling(session.scaling.clamp(*Self::MIN_SCALING, *Self::MAX_SCALING));
+        self.tool = session.tool;
+        self.cache.clear();
+        self.summarize();
+
+        Ok(())
+    }
+
+    /// pans the view so the focused search match is in the middle
+    fn center_on_match(&mut self) -> Result<()> {
+        let Some((molecule_id, atom_id)) = self.search.current() else {
+            return Ok(());
+        };
+
+        self.center_on_atom(&molecule_id, &atom_id).context("while centering on search match")
+    }
+
+    fn center_on_atom(&mut self, molecule_id: &MoleculeId, atom_id: &AtomId) -> Result<()> {
+        let position = self
+            .state
+            .get_molecule(molecule_id)
+            .and_then(|molecule| molecule.atom_position(atom_id))
+            .context("while centering on atom")?;
+        self.animate_to(Point::ORIGIN - position, self.scaling);
+
+        Ok(())
+    }
+
+    /// starts moving the view towards a translation and scaling
+    fn animate_to(&mut self, translation: Vector, scaling: Scaling) {
+        self.animation = Some(ViewAnimation::new((self.translation, self.scaling), (translation, scaling)));
+    }
+
+    pub fn versions(&self) -> impl Iterator<Item = &Version> {
+        self.timeline.versions()
+    }
+
+    pub fn compared_version(&self) -> Option<usize> {
+        self.compared_version
+    }
+
+    /// marks what changed since the compared version, red where it was and green where it is now
+    fn draw_version_diff(&self, frame: &mut Frame, center: Vector, theme: &Theme) -> Result<()> {
+        let Some(version) = self.compared_version.and_then(|index| self.timeline.get(index)) else {
+            return Ok(());
+        };
+        let diff = timeline::diff(&version.snapshot().molecules, self.state.all_molecules())
+            .context("while drawing version diff")?;
+
+        let palette = theme.palette();
+        let changes = [
+            (palette.danger, &diff.removed_atoms, &diff.removed_bonds),
+            (palette.success, &diff.added_atoms, &diff.added_bonds),
+        ];
+
+        frame.with_save(|frame| {
+            frame.translate(center);
+            frame.scale(*self.scaling);
+            frame.translate(self.translation);
+
+            for (color, atoms, bonds) in changes {
+                for (start, end) in bonds {
+                    frame.stroke(
+                        &Path::line(*start, *end),
+                        Stroke::default()
+                            .with_color(Color { a: 0.6, ..color })
+                            .with_width(Self::BOLD_WIDTH * *self.scaling),
+                    );
+                }
+
+                for position in atoms {
+                    frame.fill(&Path::circle(*position, Self::FOCUS_RADIUS / 2.0), Color { a: 0.4, ..color });
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// hydrogen bonds are drawn like those within a molecule and other contacts as dashed lines,
+    /// labelled with their length when the document shows distances
+    fn draw_interactions(&self, frame: &mut impl Surface, stroke: &Stroke, color: &Color) -> Result<()> {
+        let style = self.state.style();
+
+        for (_interaction_id, interaction, [start_atom, end_atom], [start, end]) in self.state.interactions() {
+            match interaction.kind() {
+                InteractionKind::HydrogenBond => {
+                    draw_bond(frame, &Transform::identity(), start, end, &BondType::Hydrogen, stroke, color, style)
+                        .context("while drawing interactions")?;
+                }
+                InteractionKind::Contact => {
+                    let DashPattern { dash, gap } = style.partial_bond_dash;
+                    let segments = [dash, gap];
+
+                    frame.stroke(&Path::line(start, end), Stroke {
+                        line_dash: LineDash { segments: &segments, offset: 0 },
+                        ..*stroke
+                    });
+                }
+            }
+
+            if style.interaction_distances {
+                let distance = start_atom.distance(end_atom) / style.bond_length * Self::BOND_LENGTH_ANGSTROMS;
+                let label = TextOutline::new(format!("{:.1} Å", distance), Self::DISTANCE_LABEL_SIZE, style.label_font.font());
+                label.draw(frame, label.beside_line(start, end, Self::DISTANCE_LABEL_GAP), *color);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw_orbitals(&self, frame: &mut impl Surface, color: Color) {
+        for (_orbital_id, orbital, start, end) in self.state.orbitals() {
+            orbital.draw(frame, start, end, color, Self::ORBITAL_WIDTH);
+        }
+    }
+
+    /// atoms' map numbers in a colour for each number, marking those that don't balance when
+    /// `flag_unbalanced` is set
+    fn draw_atom_maps(&self, frame: &mut impl Surface, flag_unbalanced: bool) {
+        let unbalanced = match flag_unbalanced {
+            true => self.summary.unbalanced_atom_maps.as_slice(),
+            false => &[],
+        };
+        let font = self.state.style().label_font.font();
+
+        for (_, molecule) in self.state.molecules() {
+            for (_, map, corner) in molecule.atom_maps() {
+                let label = TextOutline::new(map.to_string(), Self::ATOM_MAP_SIZE, font);
+                let bounds = label.bounds_at(Point::ORIGIN);
+                let color = match unbalanced.contains(&map) {
+                    true => Self::UNBALANCED_ATOM_MAP_COLOR,
+                    false => Self::ATOM_MAP_COLORS[map as usize % Self::ATOM_MAP_COLORS.len()],
+                };
+
+                label.draw(frame, corner + Vector::new(bounds.width / 2.0, -bounds.height / 2.0), color);
+            }
+        }
+    }
+
+    /// map numbers that don't pair one reactant atom with one product atom of the same reaction,
+    /// in order
+    fn unbalanced_atom_maps(&self) -> Vec<u32> {
+        let mut counts: FxHashMap<u32, usize> = FxHashMap::default();
+        for (_, molecule) in self.state.molecules() {
+            for (_, map, _) in molecule.atom_maps() {
+                *counts.entry(map).or_default() += 1;
+            }
+        }
+
+        let side_maps = |molecule_ids: &[MoleculeId]| {
+            molecule_ids
+                .iter()
+                .filter_map(|molecule_id| self.state.get_molecule(molecule_id).ok())
+                .flat_map(|molecule| molecule.atom_maps().map(|(_, map, _)| map))
+                .collect::<Vec<_>>()
+        };
+        let crossing = self
+            .reaction_schemes()
+            .iter()
+            .flat_map(|scheme| {
+                let products = side_maps(&scheme.products);
+                side_maps(&scheme.reactants).into_iter().filter(move |map| products.contains(map))
+            })
+            .collect::<Vec<_>>();
+
+        // a number found twice, once on each side of an arrow, can only be once on each
+        let mut unbalanced = counts
+            .into_iter()
+            .filter(|(map, count)| *count != 2 || !crossing.contains(map))
+            .map(|(map, _)| map)
+            .collect::<Vec<_>>();
+        unbalanced.sort();
+
+        unbalanced
+    }
+
+    /// reactions whose products don't have the atoms of their reactants, by their number in the
+    /// order their arrows were drawn and with their arrow, leaving out those with labels that can't
+    /// be counted
+    fn mass_imbalances(&self) -> Vec<(usize, ShapeId, MassImbalance)> {
+        let molecules = |molecule_ids: &[MoleculeId]| {
+            molecule_ids
+                .iter()
+                .filter_map(|molecule_id| self.state.get_molecule(molecule_id).ok())
+                .collect::<Vec<_>>()
+        };
+
+        self.reaction_schemes()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, scheme)| !scheme.reactants.is_empty() && !scheme.products.is_empty())
+            .filter_map(|(index, scheme)| {
+                let imbalance = mass_imbalance(molecules(&scheme.reactants), molecules(&scheme.products)).ok()??;
+                Some((index + 1, scheme.arrow_id, imbalance))
+            })
+            .collect()
+    }
+
+    /// a line for each reaction that doesn't balance, saying what its products are missing or
+    /// have too many of, with its arrow
+    fn describe_mass_imbalances(&self) -> Vec<(ShapeId, String)> {
+        self.mass_imbalances()
+            .into_iter()
+            .map(|(number, arrow_id, imbalance)| {
+                let differences = [("are missing", imbalance.missing), ("have extra", imbalance.extra)]
+                    .into_iter()
+                    .filter(|(_, formula)| !formula.is_empty())
+                    .map(|(difference, formula)| format!("{} {}", difference, formula))
+                    .collect::<Vec<_>>();
+
+                (arrow_id, format!("Reaction {}: products {}", number, differences.join(", ")))
+            })
+            .collect()
+    }
+
+    /// a warning triangle under the middle of each arrow whose reaction doesn't balance, below its
+    /// conditions
+    fn draw_mass_imbalances(&self, frame: &mut impl Surface) {
+        let size = Self::MASS_IMBALANCE_ICON_SIZE;
+        let height = size * 3.0_f32.sqrt() / 2.0;
+        let mark = TextOutline::new("!".to_string(), size * 0.7, self.state.style().label_font.font());
+        let mark_center = mark.bounds_at(Point::ORIGIN).center() - Point::ORIGIN;
+
+        for (arrow_id, _) in &self.summary.mass_imbalances {
+            let Some(arrow) = self.state.get_shape(arrow_id) else {
+                continue;
+            };
+            let conditions_height = self
+                .arrow_text_lines(arrow, ArrowSide::Below)
+                .iter()
+                .map(|line| line.bounds_at(Point::ORIGIN).height + Self::ARROW_TEXT_GAP)
+                .sum::<f32>();
+            let (tail, head) = (arrow.start(), arrow.end());
+            let center = tail + (head - tail) * 0.5 + Self::under_arrow(tail, head) * size + Vector::new(0.0, conditions_height);
+
+            let triangle = Path::new(|builder| {
+                builder.move_to(center + Vector::new(0.0, -height / 2.0));
+                builder.line_to(center + Vector::new(size / 2.0, height / 2.0));
+                builder.line_to(center + Vector::new(-size / 2.0, height / 2.0));
+                builder.close();
+            });
+            frame.fill(&triangle, Self::MASS_IMBALANCE_COLOR);
+            mark.draw(frame, center + Vector::new(0.0, height / 8.0) - mark_center, Color::BLACK);
+        }
+    }
+
+    /// unit vector across an arrow to the side below it, whichever way it points, where its
+    /// conditions go clear of agents written over it
+    fn under_arrow(tail: Point, head: Point) -> Vector {
+        let length = tail.distance(head).max(f32::EPSILON);
+        let direction = (head - tail) * (1.0 / length);
+        match direction.x >= 0.0 {
+            true => Vector::new(-direction.y, direction.x),
+            false => Vector::new(direction.y, -direction.x),
+        }
+    }
+
+    /// the text written on a side of an arrow, with the catalyst, solvent, temperature, time and
+    /// yield following it under the arrow
+    fn arrow_text_lines(&self, arrow: &Shape, side: ArrowSide) -> Vec<TextOutline> {
+        let font = self.state.style().label_font.font();
+        let text = arrow.text(side);
+        let text = (!text.trim().is_empty()).then(|| TextOutline::markup(text.to_string(), Self::ARROW_TEXT_SIZE, font));
+        let conditions = match side {
+            ArrowSide::Above => vec![],
+            ArrowSide::Below => arrow.conditions().summary(),
+        };
+
+        text.into_iter()
+            .chain(conditions.into_iter().map(|line| TextOutline::new(line, Self::ARROW_TEXT_SIZE, font)))
+            .collect()
+    }
+
+    /// where the text on a side of an arrow goes, by the middle of each line's top edge, stacked
+    /// away from the middle of the arrow, or beside it when it points up or down
+    fn arrow_text_layout(&self, arrow: &Shape, side: ArrowSide) -> Vec<(TextOutline, Point)> {
+        let (tail, head) = (arrow.start(), arrow.end());
+        let under = Self::under_arrow(tail, head);
+        let mut lines = self.arrow_text_lines(arrow, side);
+        let (away, step) = match side {
+            ArrowSide::Above => {
+                lines.reverse();
+                (under * -1.0, -1.0)
+            }
+            ArrowSide::Below => (under, 1.0),
+        };
+        let mut edge = tail + (head - tail) * 0.5 + away * Self::ARROW_TEXT_GAP;
+
+        lines
+            .into_iter()
+            .map(|line| {
+                let bounds = line.bounds_at(Point::ORIGIN);
+                let top = match side {
+                    ArrowSide::Above => -bounds.y - bounds.height,
+                    ArrowSide::Below => -bounds.y,
+                };
+                let position = edge + Vector::new(away.x * bounds.width / 2.0, top);
+                edge = edge + Vector::new(0.0, step * (bounds.height + Self::ARROW_TEXT_GAP));
+
+                (line, position)
+            })
+            .collect()
+    }
+
+    /// how long an arrow has to be for the text on it to fit, along it when it's written over and
+    /// under it, or beside it when it points up or down
+    fn arrow_text_length(&self, arrow: &Shape) -> f32 {
+        let vertical = Self::under_arrow(arrow.start(), arrow.end()).x.abs() > 0.5;
+        let extent = ArrowSide::ALL
+            .into_iter()
+            .map(|side| {
+                let sizes = self.arrow_text_lines(arrow, side).into_iter().map(|line| line.bounds_at(Point::ORIGIN).size());
+                match vertical {
+                    true => sizes.map(|size| size.height + Self::ARROW_TEXT_GAP).sum::<f32>() * 2.0,
+                    false => sizes.map(|size| size.width).fold(0.0, f32::max),
+                }
+            })
+            .fold(0.0, f32::max);
+
+        match extent > 0.0 {
+            true => extent + Self::ARROW_TEXT_GAP * 4.0,
+            false => 0.0,
+        }
+    }
+
+    /// the editable arrow with text at a position, or within the tolerance of one, with the side
+    /// the text is or would be written on
+    pub fn arrow_text_at(&self, position: Point) -> Option<(ShapeId, ArrowSide)> {
+        let tolerance = self.hit_tolerance();
+
+        self.state
+            .shapes()
+            .filter(|(shape_id, shape)| shape.kind() == ShapeKind::Arrow && self.state.get_shape(shape_id).is_some())
+            .filter_map(|(shape_id, arrow)| {
+                let on_text = ArrowSide::ALL.into_iter().find(|side| {
+                    self.arrow_text_layout(arrow, *side)
+                        .iter()
+                        .any(|(line, top)| line.bounds_at(*top).expand(tolerance).contains(position))
+                });
+                let on_arrow = (arrow.distance_to(position) <= tolerance).then(|| {
+                    let (tail, head) = (arrow.start(), arrow.end());
+                    let offset = position - (tail + (head - tail) * 0.5);
+                    let under = Self::under_arrow(tail, head);
+                    match offset.x * under.x + offset.y * under.y > 0.0 {
+                        true => ArrowSide::Below,
+                        false => ArrowSide::Above,
+                    }
+                });
+
+                Some((*shape_id, on_text.or(on_arrow)?))
+            })
+            .last()
+    }
+
+    /// the text written over and under each reaction arrow, then its conditions under it
+    fn draw_arrow_text(&self, frame: &mut impl Surface, color: Color) {
+        for (_, shape) in self.state.shapes().filter(|(_, shape)| shape.kind() == ShapeKind::Arrow) {
+            for side in ArrowSide::ALL {
+                for (line, position) in self.arrow_text_layout(shape, side) {
+                    line.draw(frame, position, color);
+                }
+            }
+        }
+    }
+
+    /// the number of each reaction arrow's step in its route, in a circle over its tail
+    fn draw_route_steps(&self, frame: &mut impl Surface, color: Color) {
+        let font = self.state.style().label_font.font();
+
+        for route in self.routes() {
+            for (number, step) in route.steps.iter().enumerate() {
+                let Some(arrow) = self.state.get_shape(&step.arrow_id) else {
+                    continue;
+                };
+                let (tail, head) = (arrow.start(), arrow.end());
+                let center = tail - Self::under_arrow(tail, head) * (Self::ROUTE_STEP_SIZE + Self::ARROW_TEXT_GAP);
+
+                let label = TextOutline::new((number + 1).to_string(), Self::ROUTE_STEP_SIZE, font);
+                let bounds = label.bounds_at(Point::ORIGIN);
+                frame.stroke(&Path::circle(center, Self::ROUTE_STEP_SIZE * 0.9), Stroke::default().with_color(color));
+                label.draw(frame, center - Vector::new(0.0, bounds.y + bounds.height / 2.0), color);
+            }
+        }
+    }
+
+    /// strokes in their own colours, those in the text colour following `color`
+    fn draw_inks(&self, frame: &mut impl Surface, color: Color) {
+        for (_ink_id, ink) in self.state.inks() {
+            frame.stroke(&ink.path(), Self::ink_stroke(ink.width(), ink.color().color(color)));
+        }
+    }
+
+    fn ink_stroke(width: f32, color: Color) -> Stroke<'static> {
+        Stroke::default()
+            .with_width(width)
+            .with_color(color)
+            .with_line_cap(LineCap::Round)
+            .with_line_join(LineJoin::Round)
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.animation.is_some()
+    }
+
+    fn zoom_to_fit(&mut self) {
+        let rectangles = self
+            .state
+            .molecules()
+            .map(|(_, molecule)| molecule.outer_rectangle())
+            .collect::<Vec<_>>();
+        let Some(rectangle) = rectangles.iter().copied().reduce(|a, b| a.union(&b)) else {
+            return;
+        };
+
+        let scaling = match rectangle.width > 0.0 && rectangle.height > 0.0 {
+            true => (self.viewport.width / rectangle.width)
+                .min(self.viewport.height / rectangle.height) * Self::FIT_MARGIN,
+            false => *self.scaling,
+        };
+        let scaling = Scaling(scaling.clamp(*Self::MIN_SCALING, *Self::MAX_SCALING));
+
+        self.animate_to(Point::ORIGIN - rectangle.center(), scaling);
+    }
+
+    pub fn atom_label(&self, molecule_id: &MoleculeId, atom_id: &AtomId) -> Result<String> {
+        self.state.get_atom(molecule_id, atom_id).map(Atom::label).context("while getting atom label")
+    }
+
+    /// the atom with keyboard focus and the molecule it is currently part of, while it is visible
+    pub fn keyboard_focus(&self) -> Option<(MoleculeId, AtomId)> {
+        let atom_id = self.keyboard_focus?;
+
+        self.state.find_atom(&atom_id).map(|molecule_id| (molecule_id, atom_id))
+    }
+
+    /// focuses an atom and selects it, so the inspector follows keyboard navigation
+    fn focus_atom(&mut self, molecule_id: MoleculeId, atom_id: AtomId) {
+        self.keyboard_focus = Some(atom_id);
+        self.state.new_selection(Selection::from_iter([SingleSelection::Atom(molecule_id, atom_id)]));
+    }
+
+    fn move_focus(&mut self, direction: Vector) -> Result<()> {
+        let Some((molecule_id, atom_id)) = self.keyboard_focus() else {
+            // the first key press starts keyboard navigation from the first molecule
+            if let Some((molecule_id, atom_id)) = self.state.adjacent_molecule_atom(None, true) {
+                self.focus_atom(molecule_id, atom_id);
+                self.center_on_atom(&molecule_id, &atom_id).context("while moving focus")?;
+                self.cache.clear();
+            }
+            return Ok(());
+        };
+
+        if let Some(neighbour_id) = self
+            .state
+            .neighbour_towards(&molecule_id, &atom_id, direction)
+            .context("while moving focus")?
+        {
+            self.focus_atom(molecule_id, neighbour_id);
+            self.cache.clear();
+        }
+
+        Ok(())
+    }
+
+    /// bonds a new atom onto the focused atom and focuses it, or starts a molecule in the middle
+    /// of the view when nothing is focused
+    fn sprout_from_focus(&mut self, direction: Option<Vector>) -> Result<()> {
+        let Some((molecule_id, atom_id)) = self.keyboard_focus() else {
+            if !self.state.can_add_molecules() {
+                return Ok(());
+            }
+            let molecule_id = MoleculeId::new();
+            let atom_id = AtomId::new();
+
+            self.state
+                .add_molecule_with_atom(molecule_id, atom_id, "".to_string(), self.view_center())
+                .context("while sprouting from focus")?;
+            self.focus_atom(molecule_id, atom_id);
+
+            return Ok(());
+        };
+
+        let molecule = self.state.get_molecule(&molecule_id).context("while sprouting from focus")?;
+        if !self.state.is_editable(molecule) {
+            return Ok(());
+        }
+
+        let direction = match direction {
+            Some(direction) => direction,
+            None => molecule.sprout_direction(&atom_id).context("while sprouting from focus")?,
+        };
+        let start = molecule.atom_position(&atom_id).context("while sprouting from focus")?;
+        let bond_type = match self.tool {
+            Tool::Bond(bond_type) => bond_type,
+            _ => BondType::Normal(1),
+        };
+
+        let end = Bond::fixed_length(start, direction, self.state.style().bond_length);
+
+        let molecule = self.state.get_molecule_mut(&molecule_id).context("while sprouting from focus")?;
+        let end_atom_id = AtomId::new();
+        molecule
+            .add_atom(end_atom_id, "".to_string(), end)
+            .context("while sprouting from focus")?;
+        molecule.add_bond(atom_id, end_atom_id, bond_type).context("while sprouting from focus")?;
+        self.focus_atom(molecule_id, end_atom_id);
+
+        Ok(())
+    }
+
+    /// deletes the focused atom, handing focus to one of its neighbours
+    fn delete_focused(&mut self) -> Result<()> {
+        let Some((molecule_id, atom_id)) = self.keyboard_focus() else {
+            return Ok(());
+        };
+
+        let molecule = self.state.get_molecule(&molecule_id).context("while deleting focused atom")?;
+        if !self.state.is_editable(molecule) {
+            return Ok(());
+        }
+        let neighbour_id = molecule.get_directly_connected(atom_id).next();
+
+        self.state.delete_atom(&molecule_id, atom_id).context("while deleting focused atom")?;
+        self.keyboard_focus = neighbour_id;
+
+        Ok(())
+    }
+
+    fn draw_functional_groups(frame: &mut Frame, molecule: &Molecule, color: Color) -> Result<()> {
+        // molecules with labels that fail to parse simply go unannotated
+        let Ok(graph) = MolecularGraph::new(molecule) else {
+            return Ok(());
+        };
+
+        for FunctionalGroupMatch { group, atoms } in find_functional_groups_in(&graph) {
+            let positions = atoms
+                .iter()
+                .map(|atom_id| molecule.atom_position(atom_id))
+                .collect::<Result<Vec<_>>>()
+                .context("while drawing functional groups")?;
+
+            for position in &positions {
+                frame.fill(
+                    &Path::circle(*position, Self::FUNCTIONAL_GROUP_RADIUS),
+                    Color { a: 0.2, ..color },
+                );
+            }
+
+            let sum = positions
+                .iter()
+                .fold(Vector::ZERO, |sum, position| sum + Vector::new(position.x, position.y));
+            let centroid = Point::ORIGIN + sum * (1.0 / positions.len() as f32);
+
+            frame.fill_text(Text {
+                content: group.name().to_string(),
+                position: centroid + Vector::new(0.0, Self::FUNCTIONAL_GROUP_RADIUS),
+                color,
+                size: Pixels(6.0),
+                horizontal_alignment: Horizontal::Center,
+                vertical_alignment: Vertical::Top,
+                ..Default::default()
+            });
+        }
+
+        Ok(())
+    }
+
+    /// draws the non-bonding electron pairs of labelled atoms in the directions furthest from bonds
+    fn draw_lone_pairs(frame: &mut impl Surface, molecule: &Molecule, color: Color) -> Result<()> {
+        // molecules with labels that fail to parse are drawn without lone pairs
+        let Ok(graph) = MolecularGraph::new(molecule) else {
+            return Ok(());
+        };
+
+        for (index, atom) in graph.atoms() {
+            let element = atom.composition.attachment();
+            if !(5..=7).contains(&element.valence_electrons) {
+                continue;
+            }
+
+            let lone_pairs = element.lone_pairs(graph.bond_valence(index), graph.hydrogens(index));
+            let position = molecule.atom_position(&atom.atom_id).context("while drawing lone pairs")?;
+
+            let bond_angles = graph
+                .neighbours(index)
+                .map(|(neighbour, _)| {
+                    let direction = molecule.atom_position(&graph.atom(neighbour).atom_id)? - position;
+                    Ok(direction.y.atan2(direction.x))
+                })
+                .collect::<Result<Vec<_>>>()
+                .context("while drawing lone pairs")?;
+
+            // right, down, left then up, ordered by how far they are from any bond
+            let mut candidates: Vec<f32> = (0..4).map(|n| n as f32 * PI / 2.0).collect();
+            let clearance = |angle: f32| {
+                bond_angles
+                    .iter()
+                    .map(|bond_angle| {
+                        let difference = (angle - bond_angle).rem_euclid(2.0 * PI);
+                        difference.min(2.0 * PI - difference)
+                    })
+                    .fold(PI, f32::min)
+            };
+            candidates.sort_by(|a, b| clearance(*b).total_cmp(&clearance(*a)));
+
+            for angle in candidates.into_iter().take(lone_pairs as usize) {
+                let direction = Vector::new(angle.cos(), angle.sin());
+                let normal = Vector::new(-direction.y, direction.x);
+                let center = position + direction * Self::LONE_PAIR_DISTANCE;
+
+                for side in [-0.5, 0.5] {
+                    let dot = Path::circle(
+                        center + normal * (side * Self::ELECTRON_SEPARATION),
+                        Self::ELECTRON_RADIUS,
+                    );
+                    frame.fill(&dot, color);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// gaps every bond where a later drawn bond crosses it, then redraws the crossing bond over the gap
+    fn draw_bond_crossings(
+        &self,
+        frame: &mut impl Surface,
+        molecules: &[&Molecule],
+        stroke: &Stroke,
+        color: &Color,
+        background: Color,
+        scaling: f32,
+    ) -> Result<()> {
+        let mut segments = vec![];
+        for (index, molecule) in molecules.iter().enumerate() {
+            for (bond_id, bond) in molecule.bonds() {
+                let points = molecule.bond_points(bond_id).context("while drawing bond crossings")?;
+
+                segments.extend(points.windows(2).map(|pair| (index, *bond_id, bond, pair[0], pair[1])));
+            }
+        }
+
+        for (i, (_, _, lower, lower_start, lower_end)) in segments.iter().enumerate() {
+            for (index, bond_id, upper, upper_start, upper_end) in &segments[i + 1..] {
+                if upper.atom_ids().any(|atom_id| lower.atom_ids().any(|other| other == atom_id)) {
+                    continue;
+                }
+
+                let Some(crossing) = segment_intersection(*lower_start, *lower_end, *upper_start, *upper_end) else {
+                    continue;
+                };
+
+                let direction = *lower_end - *lower_start;
+                let direction = direction * (Self::BRIDGE_GAP / direction.x.hypot(direction.y));
+
+                frame.stroke(
+                    &Path::line(crossing - direction, crossing + direction),
+                    Stroke::default().with_color(background).with_width(Self::BRIDGE_WIDTH * scaling),
+                );
+
+                molecules[*index]
+                    .draw_bond(frame, bond_id, stroke, color, self.state.style())
+                    .context("while drawing bond crossings")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// outlines the selected shape with handles on the points that resize it
+    fn draw_shape_handles(&self, center: Vector, frame: &mut Frame, color: Color) {
+        let Some((_, shape)) = self.selected_shape() else {
+            return;
+        };
+
+        frame.with_save(|frame| {
+            frame.translate(center);
+            frame.scale(*self.scaling);
+            frame.translate(self.translation);
+
+            let bounds = shape.bounds();
+            frame.stroke_rectangle(
+                bounds.position(),
+                bounds.size(),
+                Stroke::default().with_color(Color { a: 0.5, ..color }).with_width(1.0),
+            );
+            for handle in [shape.start(), shape.end()] {
+                frame.fill(&Path::circle(handle, self.handle_radius()), Color { a: 0.7, ..color });
+            }
+        });
+    }
 
-                    self.cache.clear();
-                }
-                Message::FlipBond(molecule_id, bond_id) => {
-                    let molecule = self
-                        .state
-                        .get_molecule_mut(&molecule_id)
-                        .context("while handling FlipBond message")?;
-                    molecule.flip_bond(&bond_id);
+    /// the handles orbitals are turned by, shown while an orbital tool is picked
+    fn draw_orbital_handles(&self, center: Vector, frame: &mut Frame, color: Color) {
+        if !matches!(self.tool, Tool::Orbital(_)) {
+            return;
+        }
 
-                    self.cache.clear();
-                }
-                Message::ConnectMolecules(
-                    molecule_id1,
-                    atom_id1,
-                    molecule_id2,
-                    atom_id2,
-                    bond_type,
-                ) => {
-                    let molecule2 = self
-                        .state
-                        .remove_molecule(&molecule_id2)
-                        .context("while getting molecule2")
-                        .context("while handling ConnectMolecules message")?;
-                    let molecule1 = self
-                        .state
-                        .get_molecule_mut(&molecule_id1)
-                        .context("while getting molecule1")
-                        .context("while handling ConnectMolecules message")?;
+        frame.with_save(|frame| {
+            frame.translate(center);
+            frame.scale(*self.scaling);
+            frame.translate(self.translation);
 
-                    molecule1.extend(molecule2);
+            for (_orbital_id, orbital, start, end) in self.state.orbitals() {
+                frame.fill(&Path::circle(orbital.handle(start, end), self.handle_radius()), Color { a: 0.7, ..color });
+            }
+        });
+    }
 
-                    molecule1.add_bond(atom_id1, atom_id2, bond_type)?;
+    /// draws the end points of a selected bond and the bond being re-anchored
+    /// outlines an unlocked background with the handle that scales it at its corner
+    fn draw_background_handles(&self, center: Vector, frame: &mut Frame, color: Color) {
+        let Some(background) = self.background.as_ref().filter(|background| !background.is_locked()) else {
+            return;
+        };
 
-                    self.cache.clear();
-                }
-                Message::RelabelAtom(mol_id, atom_id, text) => {
-                    let molecule = self
-                        .state
-                        .get_molecule_mut(&mol_id)
-                        .context("while handling RelabelAtom message")?;
-                    molecule.rename_atom(&atom_id, text)?;
-                    self.cache.clear();
-                }
-                Message::DeleteMolecule(molecule_id) => {
-                    self.state.remove_molecule(&molecule_id)?;
+        frame.with_save(|frame| {
+            frame.translate(center);
+            frame.scale(*self.scaling);
+            frame.translate(self.translation);
 
-                    self.cache.clear();
-                }
-                Message::DeleteAtom(molecule_id, atom_id) => {
-                    self.state.delete_atom(&molecule_id, atom_id)?;
+            let bounds = background.bounds();
+            frame.stroke_rectangle(
+                bounds.position(),
+                bounds.size(),
+                Stroke::default().with_color(Color { a: 0.5, ..color }).with_width(1.0),
+            );
+            frame.fill(&Path::circle(background.scale_handle(), self.handle_radius()), Color { a: 0.7, ..color });
+        });
+    }
 
-                    self.cache.clear();
-                }
-                Message::DeleteBond(molecule_id, bond_id) => {
-                    self.state.delete_bond(&molecule_id, bond_id)?;
+    fn draw_bond_handles(
+        &self,
+        canvas_position: Option<Point>,
+        hover_selection: HoverSelection,
+        center: Vector,
+        frame: &mut Frame,
+        color: Color,
+    ) -> Result<()> {
+        let Some((molecule_id, bond_id, handles)) =
+            self.state.bond_handles().context("while drawing bond handles")?
+        else {
+            return Ok(());
+        };
+        let bend_handle = self.state.bend_handle().context("while drawing bond handles")?;
 
-                    self.cache.clear();
-                }
-                Message::MoveSelection(position) => {
-                    if let Action::MovingSelection { last } = &mut self.action {
-                        self.state.move_selection(position - *last)?;
-                        *last = position;
+        let preview = match (self.action.clone(), canvas_position) {
+            (Action::DraggingBondHandle { end, .. }, Some(canvas_position)) => {
+                let molecule = self.state.get_molecule(&molecule_id)?;
+                let bond = molecule.get_bond(&bond_id)?;
+                let fixed = molecule.atom_position(&bond.atom(end.other()))?;
 
-                        self.cache.clear();
-                    }
-                }
-                Message::NewSelection(selection) => {
-                    self.state.new_selection(selection);
-                }
-                Message::ToolChanged(tool) => {
-                    self.tool = tool;
-                }
-                Message::ActionChanged(action) => {
-                    self.action = action;
-                }
-                Message::Translated(translation) => {
-                    self.translation = translation;
+                let target = match hover_selection.selection() {
+                    Some(SingleSelection::Atom(hov_molecule_id, hov_atom_id)) => self
+                        .state
+                        .get_molecule(&hov_molecule_id)?
+                        .atom_position(&hov_atom_id)?,
+                    _ => canvas_position,
+                };
 
-                    self.cache.clear();
-                }
-                Message::Scaled(scaling, translation) => {
-                    self.scaling = scaling;
+                Some(vec![fixed, target])
+            }
+            (Action::DraggingBendHandle { .. }, Some(canvas_position)) => {
+                let molecule = self.state.get_molecule(&molecule_id)?;
+                let bond = molecule.get_bond(&bond_id)?;
 
-                    if let Some(translation) = translation {
-                        self.translation = translation;
-                    }
+                Some(vec![
+                    molecule.atom_position(&bond.start())?,
+                    canvas_position,
+                    molecule.atom_position(&bond.end())?,
+                ])
+            }
+            _ => None,
+        };
 
-                    self.cache.clear();
-                }
+        frame.with_save(|frame| {
+            frame.translate(center);
+            frame.scale(*self.scaling);
+            frame.translate(self.translation);
+
+            for (_end, position) in handles {
+                frame.fill(&Path::circle(position, self.handle_radius()), Color { a: 0.7, ..color });
             }
-        }
+            // the bend handle is hollow to tell it apart from the ends
+            if let Some((_, _, position)) = bend_handle {
+                frame.stroke(
+                    &Path::circle(position, self.handle_radius()),
+                    Stroke::default().with_color(Color { a: 0.7, ..color }).with_width(2.0),
+                );
+            }
+
+            if let Some(points) = preview {
+                frame.stroke(
+                    &Path::new(|builder| {
+                        builder.move_to(points[0]);
+                        for point in &points[1..] {
+                            builder.line_to(*point);
+                        }
+                    }),
+                    Stroke::default().with_color(color).with_width(Self::BOND_WIDTH * *self.scaling),
+                );
+            }
+        });
 
         Ok(())
     }
 
-    pub fn view(&self) -> Element<application::Message> {
-        Canvas::new(self).width(Fill).height(Fill).into()
-    }
+    /// direction of a bond being drawn from an atom, following the open sector bisector until the
+    /// cursor is dragged far enough away from it
+    fn pending_bond_direction(
+        &self,
+        molecule_id: &MoleculeId,
+        atom_id: &AtomId,
+        start: Point,
+        canvas_position: Point,
+    ) -> Result<Vector> {
+        let bisector = self
+            .state
+            .get_molecule(molecule_id)
+            .and_then(|molecule| molecule.sprout_direction(atom_id))
+            .context("while getting pending bond direction")?;
 
-    fn visible_region(&self, size: Size) -> Region {
-        let width = size.width / *self.scaling;
-        let height = size.height / *self.scaling;
+        let direction = canvas_position - start;
+        let length = direction.x.hypot(direction.y);
+        if length < self.state.style().bond_length / 2.0 {
+            return Ok(bisector);
+        }
 
-        Region {
-            rect: Rectangle {
-                x: -self.translation.x - width / 2.0,
-                y: -self.translation.y - height / 2.0,
-                width,
-                height,
-            },
+        let angle = (direction.y.atan2(direction.x) - bisector.y.atan2(bisector.x)).rem_euclid(2.0 * PI);
+        if angle.min(2.0 * PI - angle) < Self::BISECTOR_SNAP_ANGLE {
+            return Ok(bisector);
         }
+
+        let angle = (direction.y.atan2(direction.x) / Self::BOND_ANGLE_STEP).round() * Self::BOND_ANGLE_STEP;
+
+        Ok(Vector::new(angle.cos(), angle.sin()))
     }
 
-    fn project(&self, position: Point, size: Size) -> Point {
-        let region = self.visible_region(size);
+    /// atom at the end of the last bond drawn, when the position is within a bond length of it so
+    /// pressing there carries the chain on from it
+    fn chain_end_near(&self, position: Point) -> Option<(MoleculeId, AtomId, Point)> {
+        let (molecule_id, atom_id) = self.chain_end?;
+        let atom_position = self
+            .state
+            .get_molecule(&molecule_id)
+            .and_then(|molecule| molecule.atom_position(&atom_id))
+            .ok()?;
 
-        Point::new(
-            position.x / *self.scaling + region.rect.x,
-            position.y / *self.scaling + region.rect.y,
-        )
+        (atom_position.distance(position) <= self.state.style().bond_length)
+            .then_some((molecule_id, atom_id, atom_position))
     }
 
+    /// draws the bond being dragged out, with its angle to the atom's other bonds and its length
+    /// next to the cursor
+    #[allow(clippy::too_many_arguments)]
     fn draw_pending_bond(
         &self,
+        cursor_position: Option<Point>,
         canvas_position: Option<Point>,
         hover_selection: HoverSelection,
         center: Vector,
@@ -279,7 +3664,10 @@ impl MolCanvas {
         let Some(canvas_position) = canvas_position else {
             return Ok(());
         };
-        let end = match hover_selection.selection() {
+        // both ends leave the labels of their atoms the way the finished bond will, the target
+        // atom being the hovered one or a new unlabelled atom a bond length away
+        let origin = molecule.position() + atom.position();
+        let (end, bond_end) = match hover_selection.selection() {
             Some(SingleSelection::Atom(hov_molecule_id, hov_atom_id)) if hov_atom_id != atom_id => {
                 let hov_molecule = self
                     .state
@@ -291,14 +3679,20 @@ impl MolCanvas {
                     .context("while getting hovered atom")
                     .context("while drawing pending bond")?;
 
-                hov_molecule.position()
-                    + hov_atom.bond_start(AtomPosition::from(hov_molecule.position(), start))
+                (
+                    hov_molecule.position() + hov_atom.position(),
+                    hov_molecule.position() + hov_atom.bond_start(AtomPosition::from(hov_molecule.position(), origin)),
+                )
+            }
+            _ => {
+                let end = Bond::fixed_length(
+                    origin,
+                    self.pending_bond_direction(&molecule_id, &atom_id, start, canvas_position)
+                        .context("while drawing pending bond")?,
+                    self.state.style().bond_length,
+                );
+                (end, end)
             }
-            _ => Bond::fixed_length(
-                molecule.position() + atom.position(),
-                canvas_position - start,
-                Self::BOND_LENGTH,
-            ),
         };
 
         let bond_start =
@@ -310,10 +3704,99 @@ impl MolCanvas {
             frame.translate(self.translation);
 
             molecule
-                .draw_pending_bond(frame, bond_start, end, &bond_type, stroke, color)
+                .draw_pending_bond(frame, bond_start, bond_end, &bond_type, stroke, color, self.state.style())
                 .expect("error in frame with_save")
         });
 
+        let Some(cursor_position) = cursor_position else {
+            return Ok(());
+        };
+        // an atom with no bonds yet measures from the horizontal, counterclockwise as y points down
+        let angle = match molecule.bond_angle_towards(&atom_id, end).context("while drawing pending bond")? {
+            Some(angle) => angle,
+            None => (origin.y - end.y).atan2(end.x - origin.x).to_degrees(),
+        };
+        let length = origin.distance(end) / self.state.style().bond_length * Self::BOND_LENGTH_ANGSTROMS;
+
+        frame.fill_text(Text {
+            content: format!("{:.0}°  {:.2} Å", angle, length),
+            position: cursor_position + Vector::new(Self::CURSOR_READOUT_OFFSET, Self::CURSOR_READOUT_OFFSET),
+            color: *color,
+            size: Pixels(Self::CURSOR_READOUT_SIZE),
+            ..Default::default()
+        });
+
+        Ok(())
+    }
+
+    /// the shape being dragged out, as it will be added
+    fn draw_pending_shape(&self, canvas_position: Option<Point>, center: Vector, frame: &mut Frame, color: Color) {
+        let (&Action::DrawingShape { kind, start }, Some(end)) = (&self.action, canvas_position) else {
+            return;
+        };
+        let style = self.new_shape_style();
+
+        frame.with_save(|frame| {
+            frame.translate(center);
+            frame.scale(*self.scaling);
+            frame.translate(self.translation);
+
+            Shape::draw_pending(kind, start, end, style, frame, color);
+        });
+    }
+
+    /// the stroke being drawn, as it will be added
+    fn draw_pending_ink(&self, center: Vector, frame: &mut Frame, color: Color) {
+        let Action::Inking { points } = &self.action else {
+            return;
+        };
+        let style = self.state.style();
+
+        frame.with_save(|frame| {
+            frame.translate(center);
+            frame.scale(*self.scaling);
+            frame.translate(self.translation);
+
+            frame.stroke(&Ink::smooth_path(points), Self::ink_stroke(style.ink_width, style.ink_color.color(color)));
+        });
+    }
+
+    /// dashed line from the atom an interaction is being dragged from, or the atom being mapped, to
+    /// the hovered atom or the cursor
+    fn draw_pending_interaction(
+        &self,
+        canvas_position: Option<Point>,
+        hover_selection: HoverSelection,
+        center: Vector,
+        frame: &mut Frame,
+        stroke: &Stroke,
+    ) -> Result<()> {
+        let (&(Action::LinkingAtoms { start, .. } | Action::MappingAtoms { start, .. }), Some(canvas_position)) = (&self.action, canvas_position) else {
+            return Ok(());
+        };
+        let end = match hover_selection.selection() {
+            Some(SingleSelection::Atom(molecule_id, atom_id)) => self
+                .state
+                .get_molecule(&molecule_id)
+                .and_then(|molecule| molecule.atom_position(&atom_id))
+                .context("while drawing pending interaction")?,
+            _ => canvas_position,
+        };
+
+        let DashPattern { dash, gap } = self.state.style().partial_bond_dash;
+        let segments = [dash, gap];
+
+        frame.with_save(|frame| {
+            frame.translate(center);
+            frame.scale(*self.scaling);
+            frame.translate(self.translation);
+
+            frame.stroke(&Path::line(start, end), Stroke {
+                line_dash: LineDash { segments: &segments, offset: 0 },
+                ..*stroke
+            });
+        });
+
         Ok(())
     }
 }
@@ -342,19 +3825,28 @@ impl canvas::Program<application::Message> for MolCanvas {
         let cursor_position = cursor.position_in(bounds);
         let canvas_position = cursor_position.map(|point| self.project(point, bounds.size()));
         let hover_selection = canvas_position
-            .map(|point| self.state.get_hovered(point).expect("error while drawing"))
+            .map(|point| self.hovered(point).expect("error while drawing"))
             .unwrap_or_default();
 
         let center = Vector::new(bounds.width / 2.0, bounds.height / 2.0);
 
         let color = theme.palette().text;
+        // stroke widths are in logical pixels and ignore the frame's scale, so widths given in
+        // canvas units are zoomed here to stay in proportion with the drawing
         let stroke = Stroke::default()
             .with_color(color)
             .with_width(Self::BOND_WIDTH * *self.scaling);
 
+        // molecules that don't match the tag filter fade into the background
+        let dimmed = Color { a: TagFilter::DIMMED_ALPHA, ..color };
+        let dimmed_stroke = stroke.with_color(dimmed);
+
         let molecules = self.cache.draw(renderer, bounds.size(), |frame| {
-            let background = Path::rectangle(Point::ORIGIN, frame.size());
-            frame.fill(&background, theme.palette().background);
+            // a background fills the canvas from the layer under it
+            if self.background.is_none() {
+                let background = Path::rectangle(Point::ORIGIN, frame.size());
+                frame.fill(&background, theme.palette().background);
+            }
 
             frame.with_save(|frame| {
                 frame.translate(center);
@@ -362,10 +3854,58 @@ impl canvas::Program<application::Message> for MolCanvas {
                 frame.translate(self.translation);
 
                 let region = self.visible_region(frame.size());
+                let molecules = region.cull(self.state.molecules()).collect::<Vec<_>>();
 
-                for (_id, molecule) in region.cull(self.state.molecules()) {
+                for (_id, molecule) in &molecules {
+                    let (color, stroke) = match self.state.tag_filter().is_dimmed(molecule) {
+                        true => (dimmed, dimmed_stroke),
+                        false => (color, stroke),
+                    };
                     molecule
-                        .draw(frame, &theme.palette().text, &stroke, &color)
+                        .draw(frame, &color, &stroke, &color, self.state.style())
+                        .expect("error in frame with_save");
+
+                    if self.state.style().lewis_structures {
+                        Self::draw_lone_pairs(frame, molecule, color)
+                            .context("while drawing lone pairs")
+                            .expect("error in frame with_save");
+                    }
+
+                    if self.annotate_functional_groups {
+                        Self::draw_functional_groups(frame, molecule, theme.palette().primary)
+                            .context("while annotating functional groups")
+                            .expect("error in frame with_save");
+                    }
+                }
+
+                self.draw_interactions(frame, &stroke, &color).expect("error in frame with_save");
+                self.draw_orbitals(frame, color);
+                self.draw_atom_maps(frame, true);
+                self.draw_arrow_text(frame, color);
+                self.draw_mass_imbalances(frame);
+                if self.route_mode {
+                    self.draw_route_steps(frame, theme.palette().primary);
+                }
+                self.draw_inks(frame, color);
+                for (_shape_id, shape) in self.state.shapes() {
+                    shape.draw(frame, color);
+                }
+                for (_annotation_id, annotation) in self.state.annotations() {
+                    annotation.draw(frame, color);
+                }
+
+                for (molecule_id, caption, position) in self.captions() {
+                    let is_dimmed = self
+                        .state
+                        .get_molecule(&molecule_id)
+                        .is_ok_and(|molecule| self.state.tag_filter().is_dimmed(molecule));
+                    caption.draw(frame, position, if is_dimmed { dimmed } else { color });
+                }
+
+                if self.state.style().bond_crossings {
+                    let molecules = molecules.iter().map(|(_id, molecule)| *molecule).collect::<Vec<_>>();
+
+                    self.draw_bond_crossings(frame, &molecules, &stroke, &color, theme.palette().background, *self.scaling)
                         .expect("error in frame with_save");
                 }
             });
@@ -375,6 +3915,7 @@ impl canvas::Program<application::Message> for MolCanvas {
             let mut frame = Frame::new(renderer, bounds.size());
 
             self.draw_pending_bond(
+                cursor_position,
                 canvas_position,
                 hover_selection,
                 center,
@@ -383,6 +3924,10 @@ impl canvas::Program<application::Message> for MolCanvas {
                 &color,
             )
             .expect("error while drawing");
+            self.draw_pending_interaction(canvas_position, hover_selection, center, &mut frame, &stroke)
+                .expect("error while drawing");
+            self.draw_pending_ink(center, &mut frame, color);
+            self.draw_pending_shape(canvas_position, center, &mut frame, color);
 
             let draw_from_bounds = |frame: &mut Frame, bounds: Bounds, stroke: Stroke| {
                 frame.with_save(|frame| {
@@ -414,6 +3959,63 @@ impl canvas::Program<application::Message> for MolCanvas {
                 );
             }
 
+            for (molecule_id, atom_id) in self.search.matches() {
+                let Ok(atom) = self.state.get_atom(molecule_id, atom_id) else {
+                    continue;
+                };
+                let Ok(molecule) = self.state.get_molecule(molecule_id) else {
+                    continue;
+                };
+
+                let width = match self.search.current() == Some((*molecule_id, *atom_id)) {
+                    true => 3.0,
+                    false => 1.5,
+                };
+
+                draw_from_bounds(
+                    &mut frame,
+                    atom.bounds() + molecule.position().into(),
+                    Stroke {
+                        style: Style::Solid(theme.palette().primary),
+                        width,
+                        ..Default::default()
+                    },
+                );
+            }
+
+            self.draw_version_diff(&mut frame, center, theme).expect("error while drawing");
+
+            if let Some((molecule_id, atom_id)) = self.keyboard_focus() {
+                let position = self
+                    .state
+                    .get_molecule(&molecule_id)
+                    .and_then(|molecule| molecule.atom_position(&atom_id))
+                    .expect("error while drawing");
+
+                frame.with_save(|frame| {
+                    frame.translate(center);
+                    frame.scale(*self.scaling);
+                    frame.translate(self.translation);
+
+                    frame.stroke(
+                        &Path::circle(position, Self::FOCUS_RADIUS),
+                        Stroke::default().with_color(theme.palette().primary).with_width(2.0),
+                    );
+                });
+            }
+
+            self.draw_bond_handles(
+                canvas_position,
+                hover_selection,
+                center,
+                &mut frame,
+                theme.palette().primary,
+            )
+            .expect("error while drawing");
+            self.draw_background_handles(center, &mut frame, theme.palette().primary);
+            self.draw_shape_handles(center, &mut frame, theme.palette().primary);
+            self.draw_orbital_handles(center, &mut frame, theme.palette().primary);
+
             if let Action::DrawingSelection { start } = self.action {
                 if let Some(canvas_position) = canvas_position {
                     // draw outline of selecting rectangle
@@ -441,6 +4043,21 @@ impl canvas::Program<application::Message> for MolCanvas {
                             },
                         );
                     });
+
+                    if let Some(cursor_position) = cursor_position {
+                        let mode = match self.touching_selection() {
+                            true => "touching",
+                            false => "inside (alt for touching)",
+                        };
+
+                        frame.fill_text(Text {
+                            content: mode.to_string(),
+                            position: cursor_position + Vector::new(Self::CURSOR_READOUT_OFFSET, Self::CURSOR_READOUT_OFFSET),
+                            color: Color { a: 0.7, ..theme.palette().text },
+                            size: Pixels(Self::CURSOR_READOUT_SIZE),
+                            ..Default::default()
+                        });
+                    }
                 }
             } else {
                 let hover_bounds = hover_selection
@@ -497,3 +4114,20 @@ impl Region {
         molecules.filter(move |(_molecule_id, molecule)| molecule.bounds().intersects(&self.rect))
     }
 }
+
+/// point where two line segments cross, ignoring crossings at their end points
+fn segment_intersection(a_start: Point, a_end: Point, b_start: Point, b_end: Point) -> Option<Point> {
+    let a = a_end - a_start;
+    let b = b_end - b_start;
+    let denominator = a.x * b.y - a.y * b.x;
+    if denominator.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let offset = b_start - a_start;
+    let t = (offset.x * b.y - offset.y * b.x) / denominator;
+    let u = (offset.x * a.y - offset.y * a.x) / denominator;
+
+    let inside = |value: f32| value > 0.05 && value < 0.95;
+    (inside(t) && inside(u)).then(|| a_start + a * t)
+}
@@ -0,0 +1,51 @@
+use std::time::{Duration, Instant};
+
+use iced::Vector;
+
+use super::Scaling;
+
+/// eased transition of the view between two translations and scalings
+#[derive(Debug, Clone, Copy)]
+pub struct ViewAnimation {
+    from: (Vector, Scaling),
+    to: (Vector, Scaling),
+    start: Instant,
+}
+
+impl ViewAnimation {
+    const DURATION: Duration = Duration::from_millis(250);
+
+    pub fn new(from: (Vector, Scaling), to: (Vector, Scaling)) -> Self {
+        Self {
+            from,
+            to,
+            start: Instant::now(),
+        }
+    }
+
+    /// the view at an instant, and whether the animation has finished
+    pub fn at(&self, now: Instant) -> ((Vector, Scaling), bool) {
+        let progress = now.saturating_duration_since(self.start).as_secs_f32() / Self::DURATION.as_secs_f32();
+        if progress >= 1.0 {
+            return (self.to, true);
+        }
+
+        let t = ease_in_out(progress);
+        let (from_translation, from_scaling) = self.from;
+        let (to_translation, to_scaling) = self.to;
+
+        // scaling is interpolated geometrically so zooming in and out feel equally fast
+        let scaling = *from_scaling * (*to_scaling / *from_scaling).powf(t);
+        let translation = from_translation + (to_translation - from_translation) * t;
+
+        ((translation, Scaling(scaling)), false)
+    }
+}
+
+/// cubic easing, slow at both ends
+fn ease_in_out(t: f32) -> f32 {
+    match t < 0.5 {
+        true => 4.0 * t * t * t,
+        false => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+    }
+}
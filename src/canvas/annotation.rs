@@ -0,0 +1,98 @@
+use iced::{Color, Font, Point, Rectangle};
+
+use crate::molecule::{AnnotationId, LayerId, ShapeId, TextOutline, Token};
+use crate::surface::Surface;
+
+use super::{ArrowSide, Message};
+
+/// free text placed over the structures, e.g. a note on a scheme, written with the markup labels
+/// use, so "*t*-Bu" or "\beta-D-glucose" read as they would in a label
+#[derive(Debug, Clone)]
+pub struct Annotation {
+    text: String,
+    /// middle of the top edge of the first line
+    position: Point,
+    layer: LayerId,
+    outline: TextOutline,
+}
+
+impl Annotation {
+    pub fn new(text: String, position: Point, font: Font, layer: LayerId) -> Self {
+        Self {
+            outline: TextOutline::markup(text.clone(), Token::FONT_SIZE, font),
+            text,
+            position,
+            layer,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn set_text(&mut self, text: String, font: Font) {
+        self.outline = TextOutline::markup(text.clone(), Token::FONT_SIZE, font);
+        self.text = text;
+    }
+
+    /// lays the text out again in another font
+    pub fn set_font(&mut self, font: Font) {
+        self.outline = TextOutline::markup(self.text.clone(), Token::FONT_SIZE, font);
+    }
+
+    pub fn position(&self) -> Point {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    pub fn layer(&self) -> LayerId {
+        self.layer
+    }
+
+    pub fn set_layer(&mut self, layer: LayerId) {
+        self.layer = layer;
+    }
+
+    pub fn bounds(&self) -> Rectangle {
+        self.outline.bounds_at(self.position)
+    }
+
+    /// distance from a point to the box around the text, zero inside it
+    pub fn distance_to(&self, position: Point) -> f32 {
+        let bounds = self.bounds();
+        let dx = (bounds.x - position.x).max(position.x - bounds.x - bounds.width).max(0.0);
+        let dy = (bounds.y - position.y).max(position.y - bounds.y - bounds.height).max(0.0);
+
+        dx.hypot(dy)
+    }
+
+    pub fn draw(&self, frame: &mut impl Surface, color: Color) {
+        self.outline.draw(frame, self.position, color);
+    }
+}
+
+/// text on the canvas that's written in the text editor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextTarget {
+    Annotation(AnnotationId),
+    /// text not written yet, to go at a canvas position
+    NewAnnotation(Point),
+    /// the text over or under an arrow
+    Arrow(ShapeId, ArrowSide),
+}
+
+impl TextTarget {
+    /// the message writing edited text to the target, text left empty being deleted
+    pub fn write(self, text: String) -> Option<Message> {
+        match (self, text.trim().is_empty()) {
+            (Self::Arrow(shape_id, side), _) => Some(Message::SetArrowText(shape_id, side, text)),
+            (Self::Annotation(annotation_id), true) => Some(Message::DeleteAnnotation(annotation_id)),
+            (Self::Annotation(annotation_id), false) => Some(Message::SetAnnotationText(annotation_id, text)),
+            (Self::NewAnnotation(_), true) => None,
+            (Self::NewAnnotation(position), false) => Some(Message::AddAnnotation(position, text)),
+        }
+    }
+}
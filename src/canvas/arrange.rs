@@ -0,0 +1,60 @@
+use iced::{Point, Rectangle, Vector};
+
+/// how molecules are lined up by the arrange command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arrangement {
+    /// rows and columns, as close to square as the count allows
+    Grid,
+    Row,
+}
+
+impl Arrangement {
+    pub const ALL: [Arrangement; 2] = [Arrangement::Grid, Arrangement::Row];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Arrangement::Grid => "Grid",
+            Arrangement::Row => "Row",
+        }
+    }
+
+    /// how far to move each of the rectangles, in order, so they're centred in cells `spacing`
+    /// apart whose top left corner is `origin`, each column as wide as its widest rectangle and
+    /// each row as tall as its tallest
+    pub fn offsets(&self, rectangles: &[Rectangle], origin: Point, spacing: f32) -> Vec<Vector> {
+        let columns = match self {
+            Arrangement::Grid => (rectangles.len() as f32).sqrt().ceil().max(1.0) as usize,
+            Arrangement::Row => rectangles.len().max(1),
+        };
+
+        let mut widths = vec![0.0f32; columns];
+        let mut heights = vec![0.0f32; rectangles.len().div_ceil(columns)];
+        for (index, rectangle) in rectangles.iter().enumerate() {
+            widths[index % columns] = widths[index % columns].max(rectangle.width);
+            heights[index / columns] = heights[index / columns].max(rectangle.height);
+        }
+
+        let starts = |sizes: &[f32], start: f32| {
+            sizes
+                .iter()
+                .scan(start, |next, size| {
+                    let current = *next;
+                    *next += size + spacing;
+                    Some(current)
+                })
+                .collect::<Vec<_>>()
+        };
+        let lefts = starts(&widths, origin.x);
+        let tops = starts(&heights, origin.y);
+
+        rectangles
+            .iter()
+            .enumerate()
+            .map(|(index, rectangle)| {
+                let (column, row) = (index % columns, index / columns);
+                let center = Point::new(lefts[column] + widths[column] / 2.0, tops[row] + heights[row] / 2.0);
+                center - rectangle.center()
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,147 @@
+use iced::advanced::svg::Svg;
+use iced::mouse;
+use iced::widget::canvas::{self, Frame, Geometry};
+use iced::widget::svg::Handle;
+use iced::{Point, Rectangle, Renderer, Theme, Vector};
+
+use crate::application;
+use crate::formats::{ReferenceImage, SvgWriter};
+
+/// an image to trace over, drawn faintly under the molecules where no tool picks it
+#[derive(Debug, Clone)]
+pub struct Background {
+    href: String,
+    /// the image wrapped in an svg, since iced's image widget isn't enabled
+    handle: Handle,
+    bounds: Rectangle,
+    opacity: f32,
+    /// whether exported images include it
+    exported: bool,
+    /// whether it stays put, unlocked it's moved by dragging it and scaled by its corner handle
+    locked: bool,
+}
+
+/// what dragging an unlocked background changes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BackgroundDrag {
+    Move,
+    /// follows the cursor with the bottom right corner
+    Scale,
+}
+
+impl Background {
+    /// how strongly new backgrounds are drawn
+    const OPACITY: f32 = 0.4;
+    /// narrowest a background is scaled down to, in canvas units
+    const MIN_WIDTH: f32 = 10.0;
+
+    /// an image centred on a point, a canvas unit to each pixel
+    pub fn new(image: ReferenceImage, center: Point) -> Self {
+        let svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}"><image width="{width}" height="{height}" href="{href}"/></svg>"#,
+            width = image.size.width,
+            height = image.size.height,
+            href = image.href,
+        );
+
+        Self {
+            href: image.href,
+            handle: Handle::from_memory(svg.into_bytes()),
+            bounds: Rectangle::new(center - Vector::new(image.size.width / 2.0, image.size.height / 2.0), image.size),
+            opacity: Self::OPACITY,
+            exported: false,
+            locked: true,
+        }
+    }
+
+    pub fn bounds(&self) -> Rectangle {
+        self.bounds
+    }
+
+    pub fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity.clamp(0.0, 1.0);
+    }
+
+    pub fn is_exported(&self) -> bool {
+        self.exported
+    }
+
+    pub fn set_exported(&mut self, exported: bool) {
+        self.exported = exported;
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    pub fn set_locked(&mut self, locked: bool) {
+        self.locked = locked;
+    }
+
+    /// where the handle scaling the background is drawn and grabbed
+    pub fn scale_handle(&self) -> Point {
+        Point::new(self.bounds.x + self.bounds.width, self.bounds.y + self.bounds.height)
+    }
+
+    pub fn drag(&mut self, drag: BackgroundDrag, from: Point, to: Point) {
+        match drag {
+            BackgroundDrag::Move => {
+                self.bounds.x += to.x - from.x;
+                self.bounds.y += to.y - from.y;
+            }
+            // the top left corner stays put and the aspect ratio is kept
+            BackgroundDrag::Scale => {
+                let aspect = self.bounds.height / self.bounds.width;
+                let width = (to.x - self.bounds.x)
+                    .max((to.y - self.bounds.y) / aspect)
+                    .max(Self::MIN_WIDTH);
+
+                self.bounds.width = width;
+                self.bounds.height = width * aspect;
+            }
+        }
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        frame.draw_svg(self.bounds, Svg::new(self.handle.clone()).opacity(self.opacity));
+    }
+
+    pub fn export(&self, svg: &mut SvgWriter) {
+        svg.image(&self.href, self.bounds, self.opacity);
+    }
+}
+
+/// draws the background under the canvas in a layer of its own, images are drawn over every shape
+/// in the layer they're in
+pub struct BackgroundLayer<'a> {
+    pub background: &'a Background,
+    pub translation: Vector,
+    pub scaling: f32,
+}
+
+impl canvas::Program<application::Message> for BackgroundLayer<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        frame.fill_rectangle(Point::ORIGIN, frame.size(), theme.palette().background);
+
+        frame.translate(Vector::new(bounds.width / 2.0, bounds.height / 2.0));
+        frame.scale(self.scaling);
+        frame.translate(self.translation);
+        self.background.draw(&mut frame);
+
+        vec![frame.into_geometry()]
+    }
+}
@@ -0,0 +1,204 @@
+use std::f32::consts::PI;
+use std::fmt;
+
+use iced::widget::canvas::{Path, Stroke};
+use iced::{Color, Font, Point, Rectangle, Size};
+
+use crate::molecule::TextOutline;
+use crate::surface::Surface;
+
+/// lab equipment and labelled arrows drawn as vector outlines, for teaching schemes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Clipart {
+    RoundBottomFlask,
+    ErlenmeyerFlask,
+    TlcPlate,
+    Column,
+    HeatArrow,
+    LightArrow,
+    /// open arrows pointing from a target back to what it's made from, unlabelled or labelled as
+    /// a functional group interconversion or a carbon–carbon disconnection
+    RetroArrow,
+    FgiRetroArrow,
+    CcRetroArrow,
+}
+
+impl Clipart {
+    pub const ALL: [Clipart; 9] = [
+        Self::RoundBottomFlask,
+        Self::ErlenmeyerFlask,
+        Self::TlcPlate,
+        Self::Column,
+        Self::HeatArrow,
+        Self::LightArrow,
+        Self::RetroArrow,
+        Self::FgiRetroArrow,
+        Self::CcRetroArrow,
+    ];
+
+    /// segments the bulb of a round bottom flask is drawn with
+    const BULB_SEGMENTS: usize = 48;
+    /// an arrow's head is this long and wide at a width of one, as for arrow shapes
+    const HEAD_LENGTH: f32 = 5.0;
+    const HEAD_WIDTH: f32 = 3.0;
+    /// gap between the two lines of a retrosynthetic arrow at a width of one
+    const RETRO_GAP: f32 = 2.5;
+
+    /// size when inserted, in bond lengths
+    pub fn size(&self) -> Size {
+        match self {
+            // square, so the bulb comes out round
+            Self::RoundBottomFlask => Size::new(2.0, 2.0),
+            Self::ErlenmeyerFlask => Size::new(2.0, 2.5),
+            Self::TlcPlate => Size::new(1.5, 2.5),
+            Self::Column => Size::new(1.0, 4.0),
+            Self::HeatArrow | Self::LightArrow | Self::RetroArrow | Self::FgiRetroArrow | Self::CcRetroArrow => {
+                Size::new(3.0, 1.0)
+            }
+        }
+    }
+
+    /// name without spaces, as saved in sessions
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::RoundBottomFlask => "round-bottom-flask",
+            Self::ErlenmeyerFlask => "erlenmeyer-flask",
+            Self::TlcPlate => "tlc-plate",
+            Self::Column => "column",
+            Self::HeatArrow => "heat-arrow",
+            Self::LightArrow => "light-arrow",
+            Self::RetroArrow => "retro-arrow",
+            Self::FgiRetroArrow => "fgi-retro-arrow",
+            Self::CcRetroArrow => "cc-retro-arrow",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|clipart| clipart.name() == name)
+    }
+
+    /// draws the figure stretched over a box
+    pub fn draw(&self, frame: &mut impl Surface, rectangle: Rectangle, width: f32, color: Color) {
+        let stroke = Stroke::default().with_width(width).with_color(color);
+        // figures are laid out in a unit square, stretched over the box
+        let at = |x: f32, y: f32| Point::new(rectangle.x + x * rectangle.width, rectangle.y + y * rectangle.height);
+        let polyline = |points: &[(f32, f32)]| {
+            Path::new(|builder| {
+                for (index, (x, y)) in points.iter().enumerate() {
+                    match index {
+                        0 => builder.move_to(at(*x, *y)),
+                        _ => builder.line_to(at(*x, *y)),
+                    }
+                }
+            })
+        };
+
+        match self {
+            Self::RoundBottomFlask => {
+                // a bulb around (0.5, 0.64) meeting the neck where it's 0.2 wide
+                let (center, radius) = ((0.5, 0.64), 0.34);
+                let meeting = (0.1_f32 / radius).asin();
+                let mut points = vec![(0.4, 0.0)];
+                points.extend((0..=Self::BULB_SEGMENTS).map(|index| {
+                    let angle = -meeting - (2.0 * PI - 2.0 * meeting) * index as f32 / Self::BULB_SEGMENTS as f32;
+                    (center.0 + radius * angle.sin(), center.1 - radius * angle.cos())
+                }));
+                points.push((0.6, 0.0));
+
+                frame.stroke(&polyline(&points), stroke);
+            }
+            Self::ErlenmeyerFlask => {
+                let outline = [(0.4, 0.0), (0.4, 0.35), (0.05, 1.0), (0.95, 1.0), (0.6, 0.35), (0.6, 0.0)];
+                frame.stroke(&polyline(&outline), stroke);
+            }
+            Self::TlcPlate => {
+                let thin = Stroke::default().with_width(width / 2.0).with_color(color);
+                frame.stroke(&Path::rectangle(rectangle.position(), rectangle.size()), stroke);
+                // the baseline spots are put on and the solvent front they're run up to
+                frame.stroke(&polyline(&[(0.1, 0.85), (0.9, 0.85)]), thin);
+                frame.stroke(&polyline(&[(0.1, 0.12), (0.9, 0.12)]), thin);
+
+                // two lanes and a co-spot of both
+                let radius = 0.06 * rectangle.width.min(rectangle.height);
+                for (x, y) in [(0.25, 0.6), (0.5, 0.35), (0.75, 0.6), (0.75, 0.35)] {
+                    frame.fill(&Path::circle(at(x, y), radius), color);
+                }
+            }
+            Self::Column => {
+                let left = [(0.3, 0.0), (0.3, 0.75), (0.45, 0.85), (0.45, 1.0)];
+                let right = left.map(|(x, y)| (1.0 - x, y));
+                frame.stroke(&polyline(&left), stroke);
+                frame.stroke(&polyline(&right), stroke);
+                // the top of the packing and the stopcock
+                frame.stroke(&polyline(&[(0.3, 0.3), (0.7, 0.3)]), stroke);
+                frame.stroke(&polyline(&[(0.3, 0.92), (0.7, 0.92)]), stroke);
+            }
+            Self::HeatArrow | Self::LightArrow => {
+                let (start, end) = (at(0.0, 0.75), at(1.0, 0.75));
+                let head_length = (Self::HEAD_LENGTH * width.max(1.0)).min(rectangle.width);
+                let head_width = Self::HEAD_WIDTH * width.max(1.0);
+                let back = Point::new(end.x - head_length, end.y);
+
+                frame.stroke(&Path::line(start, back), stroke);
+                frame.fill(
+                    &Path::new(|builder| {
+                        builder.move_to(end);
+                        builder.line_to(Point::new(back.x, back.y - head_width));
+                        builder.line_to(Point::new(back.x, back.y + head_width));
+                        builder.close();
+                    }),
+                    color,
+                );
+
+                let label = match self {
+                    Self::HeatArrow => "heat",
+                    _ => "hν",
+                };
+                TextOutline::new(label.to_string(), rectangle.height * 0.45, Font::DEFAULT).draw(frame, at(0.5, 0.0), color);
+            }
+            Self::RetroArrow | Self::FgiRetroArrow | Self::CcRetroArrow => {
+                let (start, end) = (at(0.0, 0.75), at(1.0, 0.75));
+                let gap = Self::RETRO_GAP * width.max(1.0);
+                let head_length = (Self::HEAD_LENGTH * width.max(1.0)).min(rectangle.width);
+                let head_width = Self::HEAD_WIDTH * width.max(1.0) + gap;
+
+                // the two lines stop where they meet the open head
+                let meet = head_length * gap / head_width;
+                for side in [-gap, gap] {
+                    frame.stroke(&Path::line(Point::new(start.x, start.y + side), Point::new(end.x - meet, end.y + side)), stroke);
+                }
+                frame.stroke(
+                    &Path::new(|builder| {
+                        builder.move_to(Point::new(end.x - head_length, end.y - head_width));
+                        builder.line_to(end);
+                        builder.line_to(Point::new(end.x - head_length, end.y + head_width));
+                    }),
+                    stroke,
+                );
+
+                let label = match self {
+                    Self::FgiRetroArrow => "FGI",
+                    Self::CcRetroArrow => "C–C",
+                    _ => return,
+                };
+                TextOutline::new(label.to_string(), rectangle.height * 0.45, Font::DEFAULT).draw(frame, at(0.5, 0.0), color);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Clipart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RoundBottomFlask => write!(f, "Round bottom flask"),
+            Self::ErlenmeyerFlask => write!(f, "Erlenmeyer flask"),
+            Self::TlcPlate => write!(f, "TLC plate"),
+            Self::Column => write!(f, "Column"),
+            Self::HeatArrow => write!(f, "Heat arrow"),
+            Self::LightArrow => write!(f, "Light arrow"),
+            Self::RetroArrow => write!(f, "Retrosynthetic arrow"),
+            Self::FgiRetroArrow => write!(f, "FGI retrosynthetic arrow"),
+            Self::CcRetroArrow => write!(f, "C–C retrosynthetic arrow"),
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use iced::Rectangle;
+
+use crate::molecule::Molecule;
+
+/// molecules copied whole, with their names, metadata and how they're drawn, kept by the app
+/// rather than the document so they can be pasted after another document is opened, e.g. a
+/// restored version, and given new ids when they are
+#[derive(Debug, Clone)]
+pub struct Clipboard {
+    molecules: Vec<Molecule>,
+    /// bond length of the document they were copied from, which they're scaled from when pasted
+    bond_length: f32,
+    /// the SD file written to the system clipboard with them, so pasting that text pastes these
+    text: String,
+}
+
+impl Clipboard {
+    pub fn new(molecules: Vec<Molecule>, bond_length: f32, text: String) -> Self {
+        Self { molecules, bond_length, text }
+    }
+
+    pub fn molecules(&self) -> &[Molecule] {
+        &self.molecules
+    }
+
+    pub fn bond_length(&self) -> f32 {
+        self.bond_length
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// area the molecules cover together, with the text under them
+    pub fn bounds(&self) -> Option<Rectangle> {
+        self.molecules.iter().map(Molecule::outer_rectangle).reduce(|a, b| a.union(&b))
+    }
+}
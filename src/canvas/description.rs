@@ -0,0 +1,100 @@
+use anyhow::{Context, Result};
+
+use crate::chemistry::Descriptors;
+use crate::molecule::{AtomId, Molecule, MoleculeId};
+
+use super::selection::SingleSelection;
+use super::state::State;
+
+/// plain sentence summarising a molecule, numbered in drawing order
+pub fn describe_molecule(number: usize, molecule: &Molecule) -> String {
+    let formula = match Descriptors::new(molecule) {
+        Ok(descriptors) => descriptors.formula,
+        Err(_) => "unrecognised formula".to_string(),
+    };
+    let locked = match molecule.is_locked() {
+        true => ", locked",
+        false => "",
+    };
+
+    let name = match molecule.name() {
+        Some(name) => format!(" ({})", name),
+        None => String::new(),
+    };
+
+    format!(
+        "Molecule {}{}: {}, {}, {}{}",
+        number,
+        name,
+        formula,
+        counted(molecule.atoms().count(), "atom"),
+        counted(molecule.bonds().count(), "bond"),
+        locked,
+    )
+}
+
+fn counted(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("1 {}", noun),
+        count => format!("{} {}s", count, noun),
+    }
+}
+
+/// plain sentence describing a selected item and what it is connected to
+pub fn describe_selection(state: &State, selection: SingleSelection) -> Result<String> {
+    let molecule_id = selection.molecule_id();
+    let number = molecule_number(state, &molecule_id);
+    let molecule = state.get_molecule(&molecule_id).context("while describing selection")?;
+
+    Ok(match selection {
+        SingleSelection::Molecule(_) => describe_molecule(number, molecule),
+        SingleSelection::Atom(_, atom_id) => {
+            let neighbours = molecule
+                .get_directly_connected(atom_id)
+                .map(|neighbour_id| atom_name(molecule, &neighbour_id))
+                .collect::<Result<Vec<_>>>()
+                .context("while describing selection")?;
+            let bonded = match neighbours.split_last() {
+                None => "not bonded".to_string(),
+                Some((last, [])) => format!("bonded to {}", last),
+                Some((last, rest)) => format!("bonded to {} and {}", rest.join(", "), last),
+            };
+
+            format!(
+                "Atom {} in molecule {}, {}",
+                atom_name(molecule, &atom_id).context("while describing selection")?,
+                number,
+                bonded,
+            )
+        }
+        SingleSelection::Bond(_, bond_id) => {
+            let bond = molecule.get_bond(&bond_id).context("while describing selection")?;
+
+            format!(
+                "{} bond between {} and {} in molecule {}",
+                bond.bond_type(),
+                atom_name(molecule, &bond.start()).context("while describing selection")?,
+                atom_name(molecule, &bond.end()).context("while describing selection")?,
+                number,
+            )
+        }
+    })
+}
+
+/// position of a molecule among the visible ones, counting from one
+fn molecule_number(state: &State, molecule_id: &MoleculeId) -> usize {
+    state
+        .molecules()
+        .position(|(id, _)| id == molecule_id)
+        .map_or(0, |index| index + 1)
+}
+
+/// an atom's label, unlabelled atoms being carbons
+fn atom_name(molecule: &Molecule, atom_id: &AtomId) -> Result<String> {
+    let label = molecule.get_atom(atom_id)?.label();
+
+    Ok(match label.is_empty() {
+        true => "C".to_string(),
+        false => label,
+    })
+}
@@ -0,0 +1,115 @@
+use std::fmt;
+
+use iced::Font;
+
+use super::ink::InkColor;
+use super::MolCanvas;
+
+/// document wide settings affecting how structures are drawn
+#[derive(Debug, Clone)]
+pub struct DocumentStyle {
+    /// length new bonds are drawn at and imported structures are scaled to
+    pub bond_length: f32,
+    /// draw bonds as shared electron pairs and show lone pairs
+    pub lewis_structures: bool,
+    /// leave a gap in bonds where another bond crosses over them
+    pub bond_crossings: bool,
+    pub partial_bond_dash: DashPattern,
+    pub label_font: LabelFont,
+    /// draw molecules' names under them
+    pub molecule_names: bool,
+    /// number molecules in bold under them, in reading order
+    pub compound_numbers: bool,
+    /// label interactions with the distance between their atoms
+    pub interaction_distances: bool,
+    /// draw the text entered for bonds beside them
+    pub bond_annotations: bool,
+    /// write the oxidation state and electron counts of metal atoms under them
+    pub metal_counts: bool,
+    /// space left around the drawing in exported images
+    pub export_margin: f32,
+    /// pixels to a canvas unit in exported pngs and copied images
+    pub export_resolution: f32,
+    /// width and colour of new ink strokes
+    pub ink_width: f32,
+    pub ink_color: InkColor,
+}
+
+impl Default for DocumentStyle {
+    fn default() -> Self {
+        Self {
+            bond_length: MolCanvas::BOND_LENGTH,
+            lewis_structures: false,
+            bond_crossings: false,
+            partial_bond_dash: DashPattern::default(),
+            label_font: LabelFont::default(),
+            molecule_names: false,
+            compound_numbers: false,
+            interaction_distances: false,
+            bond_annotations: false,
+            metal_counts: false,
+            export_margin: MolCanvas::EXPORT_MARGIN,
+            export_resolution: MolCanvas::EXPORT_RESOLUTION,
+            ink_width: MolCanvas::INK_WIDTH,
+            ink_color: InkColor::default(),
+        }
+    }
+}
+
+/// lengths of the dashes and gaps of a dashed bond line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DashPattern {
+    pub dash: f32,
+    pub gap: f32,
+}
+
+impl Default for DashPattern {
+    fn default() -> Self {
+        Self { dash: 2.0, gap: 1.5 }
+    }
+}
+
+/// font family used for atom labels, named families are looked up among the system fonts
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LabelFont {
+    #[default]
+    SansSerif,
+    Serif,
+    Monospace,
+    Named(&'static str),
+}
+
+impl LabelFont {
+    pub const ALL: [LabelFont; 7] = [
+        Self::SansSerif,
+        Self::Serif,
+        Self::Monospace,
+        Self::Named("Arial"),
+        Self::Named("Helvetica"),
+        Self::Named("Times New Roman"),
+        Self::Named("DejaVu Sans"),
+    ];
+
+    pub fn font(&self) -> Font {
+        match self {
+            Self::SansSerif => Font::DEFAULT,
+            Self::Serif => Font {
+                family: iced::font::Family::Serif,
+                ..Font::DEFAULT
+            },
+            Self::Monospace => Font::MONOSPACE,
+            Self::Named(name) => Font::with_name(name),
+        }
+    }
+}
+
+impl fmt::Display for LabelFont {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SansSerif => write!(f, "Sans serif"),
+            Self::Serif => write!(f, "Serif"),
+            Self::Monospace => write!(f, "Monospace"),
+            Self::Named(name) => write!(f, "{}", name),
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use super::{
-    HoverSelection, Message, MolCanvas, Scaling, SingleSelection
+    BackgroundDrag, HoverSelection, InteractionKind, Message, MolCanvas, Scaling, SingleSelection
 };
 use anyhow::{Context, Result};
 use iced::keyboard::key::Named;
@@ -8,8 +8,10 @@ use iced::{mouse, Point, Size};
 use iced::{Rectangle, Vector};
 
 use crate::application;
-use crate::molecule::{Atom, AtomId, Bond, BondType, MoleculeId};
-use crate::toolbar::ToolAction;
+use crate::molecule::{AnnotationId, Atom, AtomId, Bond, BondEnd, BondId, BondType, MoleculeId, OrbitalId};
+use crate::toolbar::{Tool, ToolAction};
+
+use super::{OrbitalKind, ShapeDrag, ShapeKind, TextTarget};
 
 pub fn handle_event(
     mol_canvas: &MolCanvas,
@@ -17,6 +19,34 @@ pub fn handle_event(
     event: Event,
     bounds: Rectangle,
     cursor: mouse::Cursor,
+) -> (event::Status, Option<application::Message>) {
+    let (status, message) = handle_canvas_event(mol_canvas, prev_interaction, event, bounds, cursor);
+
+    // the canvas only learns its size from the events it is sent, zooming to fit needs it, so a
+    // new size is passed on alongside whatever the event does
+    if bounds.size() == mol_canvas.viewport {
+        return (status, message);
+    }
+
+    let resized = Message::ViewportResized(bounds.size());
+    let message = match message {
+        None => resized.into(),
+        Some(application::Message::MolCanvas(messages)) => {
+            std::iter::once(resized).chain(messages).collect::<Vec<_>>().into()
+        }
+        // other messages leave the new size to be picked up by the next event
+        Some(message) => message,
+    };
+
+    (status, Some(message))
+}
+
+fn handle_canvas_event(
+    mol_canvas: &MolCanvas,
+    prev_interaction: &mut MouseInteraction,
+    event: Event,
+    bounds: Rectangle,
+    cursor: mouse::Cursor,
 ) -> (event::Status, Option<application::Message>) {
     if let Event::Mouse(mouse::Event::WheelScrolled { delta }) = event {
         return (
@@ -25,17 +55,72 @@ pub fn handle_event(
         );
     };
 
+    // dropped files go under the cursor once it's seen again
+    if mol_canvas.awaiting_drop {
+        if let (Event::Mouse(_), Some(position)) = (&event, cursor.position_in(bounds)) {
+            let position = mol_canvas.project(position, bounds.size());
+            return (event::Status::Ignored, Some(application::Message::PlaceDropped(Some(position))));
+        }
+    }
+
+    // pasting places structures under the cursor, or in the middle of the view when it's elsewhere
+    if let Event::Keyboard(iced::keyboard::Event::KeyPressed { key: iced::keyboard::Key::Character(c), modifiers, .. }) = &event {
+        if c.as_str() == "v" && modifiers.command() {
+            let position = cursor
+                .position_in(bounds)
+                .map_or(mol_canvas.view_center(), |position| mol_canvas.project(position, bounds.size()));
+
+            return (event::Status::Captured, Some(application::Message::PasteRequested(position)));
+        }
+    }
+
+    // holding alt while drawing a selection rectangle switches what it selects
+    if let Event::Keyboard(iced::keyboard::Event::ModifiersChanged(modifiers)) = event {
+        return (event::Status::Ignored, Some(Message::ModifiersChanged(modifiers).into()));
+    }
+
+    // only renaming and erasing the hovered item are handled here, other keys and every key while
+    // an atom has keyboard focus are left for keyboard navigation
+    if let Event::Keyboard(keyboard_event) = &event {
+        let hover_shortcut = matches!(
+            keyboard_event,
+            iced::keyboard::Event::KeyPressed { key: iced::keyboard::Key::Named(Named::Enter | Named::Delete), .. }
+        );
+
+        if !hover_shortcut || mol_canvas.keyboard_focus().is_some() {
+            return (event::Status::Ignored, None);
+        }
+    }
+
     let Some(cursor_position) = cursor.position_in(bounds) else {
         return (event::Status::Ignored, None);
     };
 
     let canvas_position = mol_canvas.project(cursor_position, bounds.size());
-    let hover_selection = match mol_canvas.state.get_hovered(canvas_position) {
+    let hover_selection = match mol_canvas.hovered(canvas_position) {
         Ok(value) => value,
         Err(error) => return (event::Status::Captured, Some(error.into()))
     };
 
-    let tool_action = tool_action_from_event(mol_canvas, prev_interaction, event, hover_selection);
+    // right clicking a molecule opens its copy menu, any other click closes an open one
+    match event {
+        Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)) => {
+            let message = match hover_selection.selection() {
+                Some(selection) => Message::OpenContextMenu(selection.molecule_id(), cursor_position),
+                None => Message::CloseContextMenu,
+            };
+            return (event::Status::Captured, Some(message.into()));
+        }
+        Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) if mol_canvas.context_menu.is_some() => {
+            return (event::Status::Captured, Some(Message::CloseContextMenu.into()));
+        }
+        _ => {}
+    }
+
+    let tool_action = match tool_action_from_event(mol_canvas, prev_interaction, event, canvas_position, hover_selection) {
+        Ok(value) => value,
+        Err(error) => return (event::Status::Captured, Some(error.into()))
+    };
 
     let message = match message_from_tool_action(
         mol_canvas,
@@ -119,19 +204,190 @@ fn get_mouse_interaction(
     }
 }
 
+/// dragging the end points of a selected bond takes priority over the cursor and select tools
+fn bond_handle_action(
+    mol_canvas: &MolCanvas,
+    interaction: MouseInteraction,
+    canvas_position: Point,
+) -> Result<Option<ToolAction>> {
+    Ok(match (interaction, &mol_canvas.action) {
+        (MouseInteraction::MouseReleased | MouseInteraction::MouseTapped, Action::DraggingBondHandle { .. }) => {
+            Some(ToolAction::BondHandleFinish)
+        }
+        (MouseInteraction::MouseReleased | MouseInteraction::MouseTapped, Action::DraggingBendHandle { .. }) => {
+            Some(ToolAction::BendHandleFinish)
+        }
+        (MouseInteraction::MouseDown, _) if matches!(mol_canvas.tool, Tool::Cursor | Tool::Select) => {
+            let state = &mol_canvas.state;
+            let radius = mol_canvas.handle_radius();
+
+            match state.bond_handle_at(canvas_position, radius).context("while getting bond handle action")? {
+                Some((molecule_id, bond_id, end)) => Some(ToolAction::BondHandleStart(molecule_id, bond_id, end)),
+                None => state
+                    .bend_handle_at(canvas_position, radius)
+                    .context("while getting bond handle action")?
+                    .map(|(molecule_id, bond_id)| ToolAction::BendHandleStart(molecule_id, bond_id)),
+            }
+        }
+        _ => None,
+    })
+}
+
+/// alt clicking a bond with the cursor or select tools takes the substituent on its smaller side,
+/// ready to be dragged away, and releasing keeps it rather than cycling to the item under the cursor
+fn substituent_action(
+    mol_canvas: &MolCanvas,
+    interaction: MouseInteraction,
+    hover_selection: &HoverSelection,
+) -> Option<ToolAction> {
+    if !mol_canvas.modifiers.alt() || !matches!(mol_canvas.tool, Tool::Cursor | Tool::Select) {
+        return None;
+    }
+    let Some(SingleSelection::Bond(molecule_id, bond_id)) = hover_selection.selection() else {
+        return None;
+    };
+
+    match interaction {
+        MouseInteraction::MouseDown => Some(ToolAction::SelectSubstituent(molecule_id, bond_id)),
+        MouseInteraction::MouseTapped => Some(ToolAction::None),
+        _ => None,
+    }
+}
+
+/// dragging an unlocked background or its corner handle takes priority over the cursor and select
+/// tools, except over atoms and bonds
+fn background_action(
+    mol_canvas: &MolCanvas,
+    interaction: MouseInteraction,
+    canvas_position: Point,
+    over_item: bool,
+) -> Option<ToolAction> {
+    match (interaction, &mol_canvas.action) {
+        (MouseInteraction::MouseReleased | MouseInteraction::MouseTapped, Action::AdjustingBackground { .. }) => {
+            Some(ToolAction::None)
+        }
+        (MouseInteraction::MouseDown, _) if matches!(mol_canvas.tool, Tool::Cursor | Tool::Select) => {
+            let background = mol_canvas.background.as_ref().filter(|background| !background.is_locked())?;
+
+            if background.scale_handle().distance(canvas_position) <= mol_canvas.handle_radius() {
+                Some(ToolAction::BackgroundDragStart(BackgroundDrag::Scale))
+            } else if background.bounds().contains(canvas_position) && !over_item {
+                Some(ToolAction::BackgroundDragStart(BackgroundDrag::Move))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// pressing with the cursor or select tool on a shape picks it up to move, or on a handle of the
+/// selected shape to resize it, except over atoms and bonds
+fn shape_action(
+    mol_canvas: &MolCanvas,
+    interaction: MouseInteraction,
+    canvas_position: Point,
+    over_item: bool,
+) -> Option<ToolAction> {
+    match (interaction, &mol_canvas.action) {
+        (MouseInteraction::MouseReleased | MouseInteraction::MouseTapped, Action::AdjustingShape { .. }) => {
+            Some(ToolAction::None)
+        }
+        (MouseInteraction::MouseDown, _) if matches!(mol_canvas.tool, Tool::Cursor | Tool::Select) => {
+            let handle = mol_canvas.selected_shape().and_then(|(shape_id, shape)| {
+                [(ShapeDrag::Start, shape.start()), (ShapeDrag::End, shape.end())]
+                    .into_iter()
+                    .find(|(_, handle)| handle.distance(canvas_position) <= mol_canvas.handle_radius())
+                    .map(|(drag, _)| ToolAction::ShapeDragStart(shape_id, drag))
+            });
+
+            handle.or_else(|| {
+                (!over_item).then_some(())?;
+                let shape_id = mol_canvas.state.shape_at(canvas_position, mol_canvas.hit_tolerance())?;
+                Some(ToolAction::ShapeDragStart(shape_id, ShapeDrag::Move))
+            })
+        }
+        _ => None,
+    }
+}
+
+/// pressing with the cursor or select tool on text picks it up to move, except over atoms and bonds
+fn annotation_action(
+    mol_canvas: &MolCanvas,
+    interaction: MouseInteraction,
+    canvas_position: Point,
+    over_item: bool,
+) -> Option<ToolAction> {
+    match (interaction, &mol_canvas.action) {
+        (MouseInteraction::MouseReleased | MouseInteraction::MouseTapped, Action::MovingAnnotation { .. }) => {
+            Some(ToolAction::None)
+        }
+        (MouseInteraction::MouseDown, _) if matches!(mol_canvas.tool, Tool::Cursor | Tool::Select) && !over_item => {
+            let annotation_id = mol_canvas.state.annotation_at(canvas_position, mol_canvas.hit_tolerance())?;
+            Some(ToolAction::AnnotationDragStart(annotation_id))
+        }
+        _ => None,
+    }
+}
+
+/// pressing an orbital tool on an orbital's handle starts turning it
+fn orbital_action(mol_canvas: &MolCanvas, interaction: MouseInteraction, canvas_position: Point) -> Option<ToolAction> {
+    match (interaction, &mol_canvas.action) {
+        (MouseInteraction::MouseReleased | MouseInteraction::MouseTapped, Action::TurningOrbital { .. }) => {
+            Some(ToolAction::None)
+        }
+        (MouseInteraction::MouseDown, _) if matches!(mol_canvas.tool, Tool::Orbital(_)) => mol_canvas
+            .state
+            .orbitals()
+            .find(|(_, orbital, start, end)| orbital.handle(*start, *end).distance(canvas_position) <= mol_canvas.handle_radius())
+            .map(|(orbital_id, ..)| ToolAction::OrbitalTurnStart(*orbital_id)),
+        _ => None,
+    }
+}
+
 fn tool_action_from_event(
     mol_canvas: &MolCanvas,
     prev_interaction: &mut MouseInteraction,
     event: Event,
+    canvas_position: Point,
     hover_selection: HoverSelection,
-) -> ToolAction {
-    match event {
+) -> Result<ToolAction> {
+    Ok(match event {
         Event::Mouse(mouse_event) => {
             let interaction = get_mouse_interaction(prev_interaction, mouse_event);
 
+            if let Some(tool_action) = bond_handle_action(mol_canvas, interaction, canvas_position)? {
+                return Ok(tool_action);
+            }
+
+            let over_item = mol_canvas
+                .state
+                .item_at(canvas_position, mol_canvas.hit_tolerance())
+                .context("while getting tool action")?;
+
+            if let Some(tool_action) = substituent_action(mol_canvas, interaction, &hover_selection) {
+                return Ok(tool_action);
+            }
+
+            if let Some(tool_action) = orbital_action(mol_canvas, interaction, canvas_position) {
+                return Ok(tool_action);
+            }
+
+            if let Some(tool_action) = annotation_action(mol_canvas, interaction, canvas_position, over_item) {
+                return Ok(tool_action);
+            }
+
+            if let Some(tool_action) = shape_action(mol_canvas, interaction, canvas_position, over_item) {
+                return Ok(tool_action);
+            }
+
+            if let Some(tool_action) = background_action(mol_canvas, interaction, canvas_position, over_item) {
+                return Ok(tool_action);
+            }
+
             mol_canvas
                 .tool
-                .action(interaction, mol_canvas.state.selection(), &hover_selection)
+                .action(interaction, mol_canvas.state.selection(), &hover_selection, over_item)
         }
         Event::Keyboard(iced::keyboard::Event::KeyPressed { key, .. }) => match key {
             iced::keyboard::Key::Named(Named::Enter) => ToolAction::Rename,
@@ -139,14 +395,14 @@ fn tool_action_from_event(
             _ => ToolAction::None,
         },
         _ => ToolAction::None,
-    }
+    })
 }
 
 fn cursor_dragged(
     mol_canvas: &MolCanvas,
     cursor_position: Point,
     canvas_position: Point,
-    _hover_selection: HoverSelection,
+    hover_selection: HoverSelection,
 ) -> Result<Vec<Message>> {
     Ok(match mol_canvas.action {
         Action::Panning { translation, start } => {
@@ -166,12 +422,77 @@ fn cursor_dragged(
                 Point::new(f32::min(start.x, canvas_position.x), f32::min(start.y, canvas_position.y)),
                 Size::new(f32::abs(start.x - canvas_position.x), f32::abs(start.y - canvas_position.y)));
 
-            vec![Message::NewSelection(mol_canvas.state.get_selection(rect)?)]
+            vec![Message::NewSelection(mol_canvas.state.get_selection(rect, mol_canvas.touching_selection())?)]
         }
-        Action::Erasing | Action::DrawingBond { .. } | Action::None => vec![]
+        // scrubbing deletes atoms and bonds along the way, a molecule is only erased whole when
+        // pressed on so passing over its empty space leaves it be
+        Action::Erasing { erased } => match erase_hovered(mol_canvas, canvas_position, hover_selection, false) {
+            Some(deletion) if !erased => vec![Message::ActionChanged(Action::Erasing { erased: true }), deletion],
+            Some(deletion) => vec![deletion],
+            None => vec![],
+        },
+        Action::AdjustingBackground { .. } => vec![Message::DragBackground(canvas_position)],
+        Action::AdjustingShape { .. } => vec![Message::DragShape(canvas_position)],
+        Action::MovingAnnotation { .. } => vec![Message::DragAnnotation(canvas_position)],
+        Action::TurningOrbital { orbital_id } => vec![Message::TurnOrbital(orbital_id, canvas_position)],
+        Action::Inking { ref points } => {
+            let points = points.iter().copied().chain([canvas_position]).collect();
+            vec![Message::ActionChanged(Action::Inking { points })]
+        }
+        Action::DrawingBond { .. }
+        | Action::DraggingBondHandle { .. }
+        | Action::DraggingBendHandle { .. }
+        | Action::LinkingAtoms { .. }
+        | Action::MappingAtoms { .. }
+        | Action::DrawingShape { .. }
+        | Action::None => vec![]
     })
 }
 
+/// deletes the hovered item, or the interaction, orbital, ink, text or shape under the position
+/// when nothing is hovered
+fn erase_hovered(
+    mol_canvas: &MolCanvas,
+    canvas_position: Point,
+    hover_selection: HoverSelection,
+    whole_molecules: bool,
+) -> Option<Message> {
+    match hover_selection.selection() {
+        Some(SingleSelection::Atom(molecule_id, atom_id)) => Some(Message::DeleteAtom(molecule_id, atom_id)),
+        Some(SingleSelection::Molecule(molecule_id)) if whole_molecules => Some(Message::DeleteMolecule(molecule_id)),
+        Some(SingleSelection::Bond(molecule_id, bond_id)) => Some(Message::DeleteBond(molecule_id, bond_id)),
+        Some(SingleSelection::Molecule(_)) => None,
+        None => mol_canvas
+            .state
+            .interaction_at(canvas_position, mol_canvas.hit_tolerance())
+            .map(Message::DeleteInteraction)
+            .or_else(|| {
+                mol_canvas
+                    .state
+                    .orbital_at(canvas_position, mol_canvas.hit_tolerance())
+                    .map(Message::DeleteOrbital)
+            })
+            .or_else(|| {
+                mol_canvas
+                    .state
+                    .ink_at(canvas_position, mol_canvas.hit_tolerance())
+                    .map(Message::DeleteInk)
+            })
+            .or_else(|| {
+                mol_canvas
+                    .state
+                    .annotation_at(canvas_position, mol_canvas.hit_tolerance())
+                    .map(Message::DeleteAnnotation)
+            })
+            .or_else(|| {
+                mol_canvas
+                    .state
+                    .shape_at(canvas_position, mol_canvas.hit_tolerance())
+                    .map(Message::DeleteShape)
+            }),
+    }
+}
+
 fn message_from_tool_action(
     mol_canvas: &MolCanvas,
     tool_action: ToolAction,
@@ -198,6 +519,21 @@ fn message_from_tool_action(
                 messages.push(Message::NewSelection(hover_selection.into()));
             }
         }
+        ToolAction::CycleSelect => {
+            // tapping again where the last tap was moves on from atom to bond to molecule, reaching
+            // items hidden under others
+            let depth = mol_canvas.tap_depth(canvas_position).map_or(0, |depth| depth + 1);
+            let hover_selection = mol_canvas
+                .state
+                .get_hovered(canvas_position, mol_canvas.hit_tolerance(), depth)
+                .context("while getting message from CycleSelect tool action")?;
+
+            messages.push(Message::ActionChanged(Action::None));
+            messages.push(Message::CycleHover(canvas_position, depth));
+            if !mol_canvas.state.selection().contains(&hover_selection) {
+                messages.push(Message::NewSelection(hover_selection.into()));
+            }
+        }
         ToolAction::DragSelectStart => {
             messages.push(Message::ActionChanged(Action::DrawingSelection {
                 start: canvas_position,
@@ -214,25 +550,22 @@ fn message_from_tool_action(
                 start: cursor_position,
             }));
         }
+        ToolAction::SelectSubstituent(molecule_id, bond_id) => {
+            messages.push(Message::SelectSubstituent(molecule_id, bond_id));
+            messages.push(Message::ActionChanged(Action::MovingSelection {
+                last: canvas_position
+            }));
+        }
         ToolAction::StartMove => {
             messages.push(Message::ActionChanged(Action::MovingSelection {
                 last: canvas_position
             }));
         }
         ToolAction::Erase => {
-            messages.push(Message::ActionChanged(Action::Erasing));
-            match hover_selection.selection() {
-                Some(SingleSelection::Atom(molecule_id, atom_id)) => {
-                    messages.push(Message::DeleteAtom(molecule_id, atom_id))
-                }
-                Some(SingleSelection::Molecule(molecule_id)) => {
-                    messages.push(Message::DeleteMolecule(molecule_id))
-                }
-                Some(SingleSelection::Bond(molecule_id, bond_id)) => {
-                    messages.push(Message::DeleteBond(molecule_id, bond_id))
-                }
-                None => (),
-            }
+            let deletion = erase_hovered(mol_canvas, canvas_position, hover_selection, true);
+
+            messages.push(Message::ActionChanged(Action::Erasing { erased: deletion.is_some() }));
+            messages.extend(deletion);
         }
         ToolAction::BondStart(bond_type) => match hover_selection.selection() {
             Some(SingleSelection::Atom(molecule_id, atom_id)) => {
@@ -257,18 +590,31 @@ fn message_from_tool_action(
                     _ => messages.push(Message::ChangeBondType(molecule_id, bond_id, bond_type))
                 }
             }
-            None | Some(SingleSelection::Molecule(_)) => {
-                let molecule_id = MoleculeId::new();
-                let atom_id = AtomId::new();
+            // pressing on empty canvas beside the end of the last bond drawn carries the chain on
+            None | Some(SingleSelection::Molecule(_)) => match mol_canvas.chain_end_near(canvas_position) {
+                Some((molecule_id, atom_id, start)) => {
+                    messages.push(Message::ActionChanged(Action::DrawingBond {
+                        molecule_id,
+                        atom_id,
+                        start,
+                        bond_type,
+                    }));
+                }
+                // nothing is drawn onto a hidden or locked layer
+                None if mol_canvas.state.can_add_molecules() => {
+                    let molecule_id = MoleculeId::new();
+                    let atom_id = AtomId::new();
 
-                messages.push(Message::ActionChanged(Action::DrawingBond {
-                    molecule_id,
-                    atom_id,
-                    start: canvas_position,
-                    bond_type,
-                }));
-                messages.push(Message::AddMoleculeWithAtom(molecule_id, atom_id, "".to_string(), canvas_position));
-            }
+                    messages.push(Message::ActionChanged(Action::DrawingBond {
+                        molecule_id,
+                        atom_id,
+                        start: canvas_position,
+                        bond_type,
+                    }));
+                    messages.push(Message::AddMoleculeWithAtom(molecule_id, atom_id, "".to_string(), canvas_position));
+                }
+                None => (),
+            },
         },
         ToolAction::BondFinish => {
             if let Action::DrawingBond {
@@ -278,8 +624,30 @@ fn message_from_tool_action(
                 bond_type,
             } = mol_canvas.action {
                 match hover_selection.selection() {
+                    // clicking an atom without dragging sprouts a bond in the least crowded direction
+                    Some(SingleSelection::Atom(_hov_molecule_id, hov_atom_id))
+                        if hov_atom_id == atom_id =>
+                        {
+                            let direction = mol_canvas
+                                .state
+                                .get_molecule(&molecule_id)
+                                .and_then(|molecule| molecule.sprout_direction(&atom_id))
+                                .context("while getting message from BondFinish tool action")?;
+
+                            messages.push(Message::FinishBond(
+                                molecule_id,
+                                atom_id,
+                                start + direction * mol_canvas.state.style().bond_length,
+                                bond_type,
+                            ))
+                        }
+                    // hydrogen bonds between molecules are kept as interactions, without merging them
                     Some(SingleSelection::Atom(hov_molecule_id, hov_atom_id))
-                        if hov_atom_id != atom_id =>
+                        if hov_molecule_id != molecule_id && bond_type == BondType::Hydrogen =>
+                        {
+                            messages.push(Message::AddInteraction(atom_id, hov_atom_id, InteractionKind::HydrogenBond));
+                        }
+                    Some(SingleSelection::Atom(hov_molecule_id, hov_atom_id)) =>
                         {
                             if hov_molecule_id == molecule_id {
                                 messages.push(Message::NewBond(molecule_id, atom_id, hov_atom_id, bond_type));
@@ -296,18 +664,169 @@ fn message_from_tool_action(
                             }
                         }
                     _ => {
-                        let end = Bond::fixed_length(
-                            start,
-                            canvas_position - start,
-                            MolCanvas::BOND_LENGTH,
-                        );
+                        let direction = mol_canvas
+                            .pending_bond_direction(&molecule_id, &atom_id, start, canvas_position)
+                            .context("while getting message from BondFinish tool action")?;
+                        let end = Bond::fixed_length(start, direction, mol_canvas.state.style().bond_length);
 
                         messages.push(Message::FinishBond(molecule_id, atom_id, end, bond_type))
                     }
                 }
             }
         }
-        ToolAction::Rename => match hover_selection.selection() {
+        ToolAction::BackgroundDragStart(drag) => {
+            messages.push(Message::ActionChanged(Action::AdjustingBackground {
+                drag,
+                last: canvas_position,
+            }));
+        }
+        ToolAction::BondHandleStart(molecule_id, bond_id, end) => {
+            messages.push(Message::ActionChanged(Action::DraggingBondHandle {
+                molecule_id,
+                bond_id,
+                end,
+            }));
+        }
+        ToolAction::BondHandleFinish => {
+            if let Action::DraggingBondHandle { molecule_id, bond_id, end } = mol_canvas.action {
+                match hover_selection.selection() {
+                    Some(SingleSelection::Atom(hov_molecule_id, hov_atom_id)) => {
+                        messages.push(Message::ReattachBond(molecule_id, bond_id, end, hov_molecule_id, hov_atom_id));
+                    }
+                    _ => {
+                        messages.push(Message::DetachBond(molecule_id, bond_id, end, canvas_position));
+                    }
+                }
+                messages.push(Message::ActionChanged(Action::None));
+            }
+        }
+        ToolAction::BendHandleStart(molecule_id, bond_id) => {
+            messages.push(Message::ActionChanged(Action::DraggingBendHandle { molecule_id, bond_id }));
+        }
+        ToolAction::BendHandleFinish => {
+            if let Action::DraggingBendHandle { molecule_id, bond_id } = mol_canvas.action {
+                messages.push(Message::BendBond(molecule_id, bond_id, canvas_position));
+                messages.push(Message::ActionChanged(Action::None));
+            }
+        }
+        ToolAction::InteractionStart => {
+            if let Some(SingleSelection::Atom(molecule_id, atom_id)) = hover_selection.selection() {
+                let start = mol_canvas
+                    .state
+                    .get_molecule(&molecule_id)
+                    .and_then(|molecule| molecule.atom_position(&atom_id))
+                    .context("while getting message from InteractionStart tool action")?;
+
+                messages.push(Message::ActionChanged(Action::LinkingAtoms { atom_id, start }));
+            }
+        }
+        ToolAction::InteractionFinish => {
+            if let Action::LinkingAtoms { atom_id, .. } = mol_canvas.action {
+                match hover_selection.selection() {
+                    Some(SingleSelection::Atom(_, hov_atom_id)) if hov_atom_id != atom_id => {
+                        messages.push(Message::AddInteraction(atom_id, hov_atom_id, InteractionKind::Contact));
+                    }
+                    _ => (),
+                }
+                messages.push(Message::ActionChanged(Action::None));
+            }
+        }
+        // pressing an unmapped atom starts mapping it, pressing a mapped one clears its number
+        ToolAction::MapStart => match (&mol_canvas.action, hover_selection.selection()) {
+            (Action::MappingAtoms { .. }, _) => (),
+            (_, Some(SingleSelection::Atom(hov_molecule_id, hov_atom_id))) => {
+                let molecule = mol_canvas
+                    .state
+                    .get_molecule(&hov_molecule_id)
+                    .context("while getting message from MapStart tool action")?;
+                let atom = molecule.get_atom(&hov_atom_id).context("while getting message from MapStart tool action")?;
+                let start = molecule
+                    .atom_position(&hov_atom_id)
+                    .context("while getting message from MapStart tool action")?;
+
+                match atom.map() {
+                    Some(_) => {
+                        messages.push(Message::UnmapAtom(hov_atom_id));
+                        messages.push(Message::ActionChanged(Action::None));
+                    }
+                    None => messages.push(Message::ActionChanged(Action::MappingAtoms { atom_id: hov_atom_id, start })),
+                }
+            }
+            _ => messages.push(Message::ActionChanged(Action::None)),
+        },
+        // releasing over the first atom leaves it waiting for a click on the second
+        ToolAction::MapFinish => match (&mol_canvas.action, hover_selection.selection()) {
+            (Action::MappingAtoms { atom_id, .. }, Some(SingleSelection::Atom(_, hov_atom_id))) if hov_atom_id == *atom_id => (),
+            (Action::MappingAtoms { atom_id, .. }, Some(SingleSelection::Atom(_, hov_atom_id))) => {
+                messages.push(Message::MapAtoms(*atom_id, hov_atom_id));
+                messages.push(Message::ActionChanged(Action::None));
+            }
+            _ => messages.push(Message::ActionChanged(Action::None)),
+        },
+        ToolAction::Hold => (),
+        ToolAction::InkStart => {
+            if mol_canvas.state.can_annotate() {
+                messages.push(Message::ActionChanged(Action::Inking { points: vec![canvas_position] }));
+            }
+        }
+        ToolAction::InkFinish => {
+            if let Action::Inking { points } = &mol_canvas.action {
+                // a tap leaves no mark
+                if points.len() > 1 {
+                    messages.push(Message::AddInk(points.iter().copied().chain([canvas_position]).collect()));
+                }
+                messages.push(Message::ActionChanged(Action::None));
+            }
+        }
+        ToolAction::ShapeStart(kind) => {
+            if mol_canvas.state.can_annotate() {
+                messages.push(Message::ActionChanged(Action::DrawingShape { kind, start: canvas_position }));
+            }
+        }
+        ToolAction::ShapeFinish => {
+            if let Action::DrawingShape { kind, start } = mol_canvas.action {
+                // a tap leaves no shape
+                if start.distance(canvas_position) > mol_canvas.hit_tolerance() {
+                    messages.push(Message::AddShape(kind, start, canvas_position));
+                }
+                messages.push(Message::ActionChanged(Action::None));
+            }
+        }
+        ToolAction::ShapeDragStart(shape_id, drag) => {
+            messages.push(Message::ActionChanged(Action::AdjustingShape { drag, last: canvas_position }));
+            messages.push(Message::SelectShape(Some(shape_id)));
+        }
+        ToolAction::AnnotationDragStart(annotation_id) => {
+            messages.push(Message::ActionChanged(Action::MovingAnnotation { annotation_id, last: canvas_position }));
+        }
+        ToolAction::Annotate => {
+            let annotation_id = mol_canvas.state.annotation_at(canvas_position, mol_canvas.hit_tolerance());
+            let target = match (annotation_id, mol_canvas.arrow_text_at(canvas_position)) {
+                (Some(annotation_id), _) => TextTarget::Annotation(annotation_id),
+                (None, Some((shape_id, side))) => TextTarget::Arrow(shape_id, side),
+                (None, None) => TextTarget::NewAnnotation(canvas_position),
+            };
+
+            return Ok(Some(application::Message::TextEditOpen(target)));
+        }
+        ToolAction::OrbitalTurnStart(orbital_id) => {
+            messages.push(Message::ActionChanged(Action::TurningOrbital { orbital_id }));
+        }
+        // p and sp3 orbitals go on the tapped atom, π overlaps across the tapped bond
+        ToolAction::OrbitalPlace(kind) => match (kind, hover_selection.selection()) {
+            (OrbitalKind::Pi, Some(SingleSelection::Bond(hov_molecule_id, hov_bond_id))) => {
+                let bond = mol_canvas
+                    .state
+                    .get_bond(&hov_molecule_id, &hov_bond_id)
+                    .context("while getting message from OrbitalPlace tool action")?;
+                messages.push(Message::AddOrbital(kind, bond.start(), Some(bond.end())));
+            }
+            (OrbitalKind::P | OrbitalKind::Sp3, Some(SingleSelection::Atom(_, hov_atom_id))) => {
+                messages.push(Message::AddOrbital(kind, hov_atom_id, None));
+            }
+            _ => (),
+        },
+        ToolAction::Rename | ToolAction::Label => match hover_selection.selection() {
             Some(SingleSelection::Atom(hov_molecule_id, hov_atom_id)) => {
                 let label = mol_canvas
                     .state
@@ -321,15 +840,19 @@ fn message_from_tool_action(
                         Message::RelabelAtom,
                 )))
             }
+            _ if matches!(tool_action, ToolAction::Label) => {
+                return Ok(Some(application::Message::TextInputNew(canvas_position)))
+            }
             _ => return Ok(Some(application::Message::TextInputSubmit)),
         },
         ToolAction::AtomDraw(label) => match hover_selection.selection() {
             Some(SingleSelection::Atom(hov_molecule_id, hov_atom_id)) => {
                 messages.push(Message::RelabelAtom(hov_molecule_id, hov_atom_id, label));
             }
-            _ => {
+            _ if mol_canvas.state.can_add_molecules() => {
                 messages.push(Message::AddMoleculeWithAtom(MoleculeId::new(), AtomId::new(), label, canvas_position));
             }
+            _ => (),
         },
     }
 
@@ -358,11 +881,62 @@ pub enum Action {
     DrawingSelection {
         start: Point,
     },
-    Erasing,
+    Erasing {
+        /// whether the press or drag has deleted anything yet, later deletions of the drag are
+        /// undone with the first
+        erased: bool,
+    },
     DrawingBond {
         molecule_id: MoleculeId,
         atom_id: AtomId,
         start: Point,
         bond_type: BondType,
     },
+    DraggingBondHandle {
+        molecule_id: MoleculeId,
+        bond_id: BondId,
+        end: BondEnd,
+    },
+    /// dragging the middle of a bond to bend it
+    DraggingBendHandle {
+        molecule_id: MoleculeId,
+        bond_id: BondId,
+    },
+    /// dragging an interaction out from an atom
+    LinkingAtoms {
+        atom_id: AtomId,
+        start: Point,
+    },
+    /// waiting for the atom to map to an atom, either by dragging or by clicking it
+    MappingAtoms {
+        atom_id: AtomId,
+        start: Point,
+    },
+    AdjustingBackground {
+        drag: BackgroundDrag,
+        last: Point,
+    },
+    /// dragging out a new shape from where the press was
+    DrawingShape {
+        kind: ShapeKind,
+        start: Point,
+    },
+    /// moving or resizing the selected shape
+    AdjustingShape {
+        drag: ShapeDrag,
+        last: Point,
+    },
+    /// dragging text to move it
+    MovingAnnotation {
+        annotation_id: AnnotationId,
+        last: Point,
+    },
+    /// dragging an orbital's handle to point it
+    TurningOrbital {
+        orbital_id: OrbitalId,
+    },
+    /// drawing a freehand stroke through the points the cursor has passed
+    Inking {
+        points: Vec<Point>,
+    },
 }
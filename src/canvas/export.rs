@@ -0,0 +1,222 @@
+use std::fmt;
+
+use anyhow::{Context, Result};
+use rustc_hash::FxHashMap;
+
+use crate::chemistry::{to_smiles, Descriptors, MolecularGraph};
+use crate::formats::{write_helm, write_inchi, write_molfile, write_reaction_smiles, Reaction, ReactionConditions, Sequence, Structure};
+use crate::molecule::{AtomId, Molecule};
+
+const CSV_HEADER: &[&str] = &[
+    "Molecule",
+    "Formula",
+    "MW",
+    "SMILES",
+    "Unsaturation",
+    "H-bond donors",
+    "H-bond acceptors",
+    "Rotatable bonds",
+    "TPSA",
+    "cLogP",
+];
+
+/// one row per molecule with its formula, SMILES and descriptors, cells are left empty when
+/// a molecule's labels can't be parsed
+pub fn molecules_csv<'a>(molecules: impl Iterator<Item = &'a Molecule>) -> String {
+    let mut csv = csv_row(CSV_HEADER.iter().map(|cell| cell.to_string()));
+
+    for (index, molecule) in molecules.enumerate() {
+        let mut row = vec![(index + 1).to_string()];
+
+        if let Ok(graph) = MolecularGraph::new(molecule) {
+            let descriptors = Descriptors::from_graph(&graph);
+
+            row.extend([
+                descriptors.formula.clone(),
+                format!("{:.2}", descriptors.molecular_weight),
+                to_smiles(&graph).unwrap_or_default(),
+                format!("{}", descriptors.degrees_of_unsaturation),
+                descriptors.h_bond_donors.to_string(),
+                descriptors.h_bond_acceptors.to_string(),
+                descriptors.rotatable_bonds.to_string(),
+                format!("{:.1}", descriptors.tpsa),
+                format!("{:.2}", descriptors.clogp),
+            ]);
+        }
+
+        csv.push_str(&csv_row(row.into_iter()));
+    }
+
+    csv
+}
+
+/// one record per molecule, its name and metadata written as data fields after the molfile
+pub fn molecules_sdf<'a>(molecules: impl Iterator<Item = &'a Molecule>, bond_length: f32) -> Result<String> {
+    let mut sdf = String::new();
+
+    for molecule in molecules {
+        let structure = Structure::from_molecules(std::iter::once(molecule)).context("while writing SD file")?;
+        sdf.push_str(&write_molfile(&structure, bond_length));
+
+        let metadata = molecule.metadata();
+        let tags = metadata.tags().join(", ");
+        let fields = [
+            ("Name", molecule.name()),
+            ("CAS", metadata.cas()),
+            ("Tags", Some(tags.as_str()).filter(|tags| !tags.is_empty())),
+            ("Notes", metadata.notes()),
+            ("Source", metadata.source()),
+        ];
+        for (field, value) in fields {
+            let Some(value) = value else {
+                continue;
+            };
+            // a blank line ends a data item, so blank lines inside the value are left out
+            let lines = value.lines().filter(|line| !line.trim().is_empty()).collect::<Vec<_>>();
+            sdf.push_str(&format!(">  <{}>\n{}\n\n", field, lines.join("\n")));
+        }
+
+        sdf.push_str("$$$$\n");
+    }
+
+    Ok(sdf)
+}
+
+/// reaction SMILES for the molecules around an arrow, failing when any of their labels can't be
+/// parsed
+pub fn reaction_smiles(reactants: &[&Molecule], agents: &[&Molecule], products: &[&Molecule]) -> Result<String> {
+    let smiles = |molecules: &[&Molecule]| {
+        molecules
+            .iter()
+            .map(|molecule| {
+                let graph = MolecularGraph::new(molecule).context("while writing reaction SMILES")?;
+                to_smiles(&graph).context("while writing reaction SMILES")
+            })
+            .collect::<Result<Vec<_>>>()
+    };
+
+    Ok(write_reaction_smiles(&smiles(reactants)?, &smiles(agents)?, &smiles(products)?))
+}
+
+/// the structures of the molecules around an arrow, to write to reaction files
+pub fn reaction_structures(
+    reactants: &[&Molecule],
+    agents: &[&Molecule],
+    products: &[&Molecule],
+    conditions: ReactionConditions,
+) -> Result<Reaction> {
+    let structures = |molecules: &[&Molecule]| {
+        molecules
+            .iter()
+            .map(|molecule| Structure::from_molecules(std::iter::once(*molecule)))
+            .collect::<Result<Vec<_>>>()
+    };
+
+    Ok(Reaction {
+        reactants: structures(reactants)?,
+        agents: structures(agents)?,
+        products: structures(products)?,
+        conditions,
+    })
+}
+
+/// text formats a single molecule can be copied to the clipboard as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyFormat {
+    Smiles,
+    Molfile,
+    /// generated by Open Babel
+    Inchi,
+    /// condensed peptide and nucleic acid chains
+    Helm,
+}
+
+impl CopyFormat {
+    pub const ALL: [CopyFormat; 4] = [CopyFormat::Smiles, CopyFormat::Molfile, CopyFormat::Inchi, CopyFormat::Helm];
+}
+
+impl fmt::Display for CopyFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CopyFormat::Smiles => write!(f, "SMILES"),
+            CopyFormat::Molfile => write!(f, "molfile"),
+            CopyFormat::Inchi => write!(f, "InChI"),
+            CopyFormat::Helm => write!(f, "HELM"),
+        }
+    }
+}
+
+/// a molecule written out as text, failing when its labels can't be parsed
+pub fn molecule_text(molecule: &Molecule, format: CopyFormat, bond_length: f32) -> Result<String> {
+    Ok(match format {
+        CopyFormat::Smiles => {
+            let graph = MolecularGraph::new(molecule).context("while writing SMILES")?;
+            to_smiles(&graph).context("while writing SMILES")?
+        }
+        CopyFormat::Molfile => {
+            let structure = Structure::from_molecules(std::iter::once(molecule)).context("while writing molfile")?;
+            write_molfile(&structure, bond_length)
+        }
+        CopyFormat::Inchi => {
+            let structure = Structure::from_molecules(std::iter::once(molecule)).context("while writing InChI")?;
+            write_inchi(&write_molfile(&structure, bond_length))?
+        }
+        CopyFormat::Helm => {
+            let sequence = Sequence::from_chain(&chain_labels(molecule)).context("while writing HELM")?;
+            write_helm(&sequence)
+        }
+    })
+}
+
+/// labels along a molecule drawn as an unbranched chain, from whichever end was drawn first, or
+/// none when it branches or has a ring
+fn chain_labels(molecule: &Molecule) -> Vec<String> {
+    let mut neighbours: FxHashMap<AtomId, Vec<AtomId>> = FxHashMap::default();
+    for (_, bond) in molecule.bonds() {
+        neighbours.entry(bond.start()).or_default().push(bond.end());
+        neighbours.entry(bond.end()).or_default().push(bond.start());
+    }
+    if neighbours.values().any(|atoms| atoms.len() > 2) || molecule.bonds().count() + 1 != molecule.atoms().count() {
+        return vec![];
+    }
+
+    let Some(mut atom_id) = molecule
+        .atoms()
+        .map(|(atom_id, _)| *atom_id)
+        .find(|atom_id| neighbours.get(atom_id).is_none_or(|atoms| atoms.len() < 2))
+    else {
+        return vec![];
+    };
+
+    let mut labels = vec![];
+    let mut previous = None;
+    loop {
+        let Ok(atom) = molecule.get_atom(&atom_id) else {
+            break;
+        };
+        labels.push(atom.label());
+
+        let next = neighbours.get(&atom_id).and_then(|atoms| atoms.iter().find(|next| Some(**next) != previous));
+        match next {
+            Some(next) => {
+                previous = Some(atom_id);
+                atom_id = *next;
+            }
+            None => break,
+        }
+    }
+
+    labels
+}
+
+fn csv_row(cells: impl Iterator<Item = String>) -> String {
+    let cells = cells.map(|cell| {
+        if cell.contains([',', '"', '\n']) {
+            format!("\"{}\"", cell.replace('"', "\"\""))
+        } else {
+            cell
+        }
+    });
+
+    cells.collect::<Vec<_>>().join(",") + "\n"
+}
@@ -0,0 +1,60 @@
+use crate::molecule::{AnnotationId, FxIndexMap, InkId, InteractionId, Molecule, MoleculeId, OrbitalId, ShapeId};
+
+use super::annotation::Annotation;
+use super::ink::Ink;
+use super::instance::Instance;
+use super::orbital::Orbital;
+use super::shape::Shape;
+use super::interaction::Interaction;
+
+/// the document's molecules, the interactions between them, the orbitals on them and the ink,
+/// shapes and text drawn over them at one point in time
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub molecules: FxIndexMap<MoleculeId, Molecule>,
+    pub interactions: FxIndexMap<InteractionId, Interaction>,
+    pub instances: Vec<Instance>,
+    pub inks: FxIndexMap<InkId, Ink>,
+    pub shapes: FxIndexMap<ShapeId, Shape>,
+    pub orbitals: FxIndexMap<OrbitalId, Orbital>,
+    pub annotations: FxIndexMap<AnnotationId, Annotation>,
+    /// document bond length the molecules were drawn at
+    pub bond_length: f32,
+}
+
+/// snapshots taken before each undoable edit
+#[derive(Debug, Default)]
+pub struct History {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+}
+
+impl History {
+    /// oldest snapshots are dropped past this many
+    const LIMIT: usize = 100;
+
+    /// remembers the document as it was before an edit, forgetting anything undone
+    pub fn record(&mut self, snapshot: Snapshot) {
+        if self.undo.len() == Self::LIMIT {
+            self.undo.remove(0);
+        }
+
+        self.undo.push(snapshot);
+        self.redo.clear();
+    }
+
+    /// returns the document to restore, keeping the current one for redo
+    pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let snapshot = self.undo.pop()?;
+        self.redo.push(current);
+
+        Some(snapshot)
+    }
+
+    pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let snapshot = self.redo.pop()?;
+        self.undo.push(current);
+
+        Some(snapshot)
+    }
+}
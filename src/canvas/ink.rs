@@ -0,0 +1,146 @@
+use std::fmt;
+
+use iced::widget::canvas::Path;
+use iced::{Color, Point, Rectangle, Size};
+
+use crate::bounds::distance_to_segment;
+use crate::molecule::LayerId;
+
+/// a freehand stroke drawn over the structures, for circling parts of them or handwriting notes
+#[derive(Debug, Clone)]
+pub struct Ink {
+    points: Vec<Point>,
+    width: f32,
+    color: InkColor,
+    layer: LayerId,
+}
+
+/// colours ink is drawn in, the first following the theme's text
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum InkColor {
+    #[default]
+    Text,
+    Red,
+    Blue,
+    Green,
+    Orange,
+}
+
+impl Ink {
+    /// points closer than this to the last one kept are dropped, evening out a shaky hand
+    pub const MIN_SPACING: f32 = 1.5;
+
+    pub fn new(points: &[Point], width: f32, color: InkColor, layer: LayerId) -> Self {
+        Self {
+            points: Self::thin(points),
+            width,
+            color,
+            layer,
+        }
+    }
+
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn color(&self) -> InkColor {
+        self.color
+    }
+
+    pub fn layer(&self) -> LayerId {
+        self.layer
+    }
+
+    pub fn set_layer(&mut self, layer: LayerId) {
+        self.layer = layer;
+    }
+
+    /// the stroke's curve, see `smooth_path`
+    pub fn path(&self) -> Path {
+        Self::smooth_path(&self.points)
+    }
+
+    pub fn bounds(&self) -> Rectangle {
+        let (min, max) = self.points.iter().fold(
+            (Point::new(f32::MAX, f32::MAX), Point::new(f32::MIN, f32::MIN)),
+            |(min, max), point| (Point::new(min.x.min(point.x), min.y.min(point.y)), Point::new(max.x.max(point.x), max.y.max(point.y))),
+        );
+        let padding = self.width / 2.0;
+
+        Rectangle::new(
+            Point::new(min.x - padding, min.y - padding),
+            Size::new(max.x - min.x + self.width, max.y - min.y + self.width),
+        )
+    }
+
+    /// distance from a point to the nearest part of the stroke's line, ignoring its width
+    pub fn distance_to(&self, position: Point) -> f32 {
+        self.points
+            .windows(2)
+            .map(|pair| distance_to_segment(position, pair[0], pair[1]))
+            .fold(f32::MAX, f32::min)
+    }
+
+    /// a curve through the midpoints between points, bending towards each point, so the corners
+    /// of a hand drawn polyline come out rounded
+    pub fn smooth_path(points: &[Point]) -> Path {
+        Path::new(|builder| {
+            let Some((first, rest)) = points.split_first() else {
+                return;
+            };
+            builder.move_to(*first);
+
+            for pair in rest.windows(2) {
+                let middle = Point::new((pair[0].x + pair[1].x) / 2.0, (pair[0].y + pair[1].y) / 2.0);
+                builder.quadratic_curve_to(pair[0], middle);
+            }
+            if let Some(last) = rest.last() {
+                builder.line_to(*last);
+            }
+        })
+    }
+
+    fn thin(points: &[Point]) -> Vec<Point> {
+        let mut thinned: Vec<Point> = vec![];
+        for (index, point) in points.iter().enumerate() {
+            let last = index == points.len() - 1;
+            match thinned.last() {
+                Some(previous) if !last && previous.distance(*point) < Self::MIN_SPACING => {}
+                _ => thinned.push(*point),
+            }
+        }
+
+        thinned
+    }
+}
+
+impl InkColor {
+    pub const ALL: [InkColor; 5] = [Self::Text, Self::Red, Self::Blue, Self::Green, Self::Orange];
+
+    /// the colour drawn, `text` being the theme's or black in exports
+    pub fn color(&self, text: Color) -> Color {
+        match self {
+            Self::Text => text,
+            Self::Red => Color::from_rgb8(0xD3, 0x2F, 0x2F),
+            Self::Blue => Color::from_rgb8(0x19, 0x76, 0xD2),
+            Self::Green => Color::from_rgb8(0x38, 0x8E, 0x3C),
+            Self::Orange => Color::from_rgb8(0xF5, 0x7C, 0x00),
+        }
+    }
+}
+
+impl fmt::Display for InkColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text => write!(f, "Text"),
+            Self::Red => write!(f, "Red"),
+            Self::Blue => write!(f, "Blue"),
+            Self::Green => write!(f, "Green"),
+            Self::Orange => write!(f, "Orange"),
+        }
+    }
+}
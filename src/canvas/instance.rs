@@ -0,0 +1,25 @@
+use rustc_hash::FxHashMap;
+
+use crate::molecule::{AtomId, BondId, Molecule};
+
+/// a molecule kept as a linked copy of another, its master, by which of its atoms and bonds stand
+/// for which of the master's, so both are found again after molecules split and merge
+#[derive(Debug, Clone)]
+pub struct Instance {
+    /// the master's atoms to the instance's
+    pub atoms: FxHashMap<AtomId, AtomId>,
+    /// the master's bonds to the instance's
+    pub bonds: FxHashMap<BondId, BondId>,
+    /// the master as it was last copied, to tell edits to the master from edits to the instance
+    pub master: Molecule,
+}
+
+impl Instance {
+    pub fn new(master: Molecule) -> Self {
+        Self {
+            atoms: FxHashMap::default(),
+            bonds: FxHashMap::default(),
+            master,
+        }
+    }
+}
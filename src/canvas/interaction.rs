@@ -0,0 +1,40 @@
+use crate::molecule::AtomId;
+
+/// a non-covalent link between two atoms, usually of different molecules, kept out of both
+/// molecules so linking them doesn't merge them into one
+#[derive(Debug, Clone)]
+pub struct Interaction {
+    start: AtomId,
+    end: AtomId,
+    kind: InteractionKind,
+}
+
+/// how an interaction is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InteractionKind {
+    HydrogenBond,
+    /// a plain dashed line, for π-stacking, salt bridges and other contacts
+    Contact,
+}
+
+impl Interaction {
+    pub fn new(start: AtomId, end: AtomId, kind: InteractionKind) -> Self {
+        Self { start, end, kind }
+    }
+
+    pub fn start(&self) -> AtomId {
+        self.start
+    }
+
+    pub fn end(&self) -> AtomId {
+        self.end
+    }
+
+    pub fn kind(&self) -> InteractionKind {
+        self.kind
+    }
+
+    pub fn atom_ids(&self) -> impl Iterator<Item = AtomId> {
+        [self.start, self.end].into_iter()
+    }
+}
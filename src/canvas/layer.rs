@@ -0,0 +1,47 @@
+/// named group of molecules that are shown, hidden or locked together
+#[derive(Debug, Clone)]
+pub struct Layer {
+    name: String,
+    visible: bool,
+    locked: bool,
+}
+
+impl Layer {
+    /// layers every new document starts with, the first being active
+    pub const DEFAULT_NAMES: [&'static str; 3] = ["Structures", "Mechanism arrows", Self::ANNOTATIONS];
+    /// layer ink is drawn on when there is one
+    pub const ANNOTATIONS: &'static str = "Annotations";
+
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            visible: true,
+            locked: false,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    pub fn set_locked(&mut self, locked: bool) {
+        self.locked = locked;
+    }
+
+    /// whether the layer's molecules can be hovered, selected and edited
+    pub fn is_editable(&self) -> bool {
+        self.visible && !self.locked
+    }
+}
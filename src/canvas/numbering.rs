@@ -0,0 +1,57 @@
+use iced::Rectangle;
+
+use crate::molecule::{Molecule, MoleculeId};
+
+/// bold numbers for compounds in reading order, rows from top to bottom and left to right along
+/// a row, molecules lettered with the one before them share its number as a series (3a, 3b)
+pub fn compound_numbers<'a>(molecules: impl Iterator<Item = (&'a MoleculeId, &'a Molecule)>) -> Vec<(MoleculeId, String)> {
+    let mut molecules = molecules
+        .map(|(molecule_id, molecule)| (*molecule_id, molecule, molecule.bounds().bounding_rectangle()))
+        .collect::<Vec<_>>();
+    molecules.sort_by(|(_, _, a), (_, _, b)| a.y.total_cmp(&b.y));
+
+    // a molecule joins a row when its middle is level with the first molecule of the row
+    let mut rows: Vec<Vec<(MoleculeId, &Molecule, Rectangle)>> = vec![];
+    for molecule in molecules {
+        match rows.last_mut() {
+            Some(row) if molecule.2.center_y() <= row[0].2.y + row[0].2.height => row.push(molecule),
+            _ => rows.push(vec![molecule]),
+        }
+    }
+
+    let mut series: Vec<Vec<MoleculeId>> = vec![];
+    for row in &mut rows {
+        row.sort_by(|(_, _, a), (_, _, b)| a.x.total_cmp(&b.x));
+
+        for (molecule_id, molecule, _) in row.iter() {
+            match series.last_mut() {
+                Some(last) if molecule.letters_with_previous() => last.push(*molecule_id),
+                _ => series.push(vec![*molecule_id]),
+            }
+        }
+    }
+
+    series
+        .into_iter()
+        .enumerate()
+        .flat_map(|(index, members)| {
+            let number = index + 1;
+            let lettered = members.len() > 1;
+
+            members.into_iter().enumerate().map(move |(position, molecule_id)| match lettered {
+                true => (molecule_id, format!("{}{}", number, series_letter(position))),
+                false => (molecule_id, number.to_string()),
+            })
+        })
+        .collect()
+}
+
+/// a, b, ... z, then aa, ab, ...
+fn series_letter(position: usize) -> String {
+    let letter = (b'a' + (position % 26) as u8) as char;
+
+    match position / 26 {
+        0 => letter.to_string(),
+        prefix => format!("{}{}", series_letter(prefix - 1), letter),
+    }
+}
@@ -0,0 +1,229 @@
+use std::f32::consts::PI;
+use std::fmt;
+
+use iced::widget::canvas::path::arc::Elliptical;
+use iced::widget::canvas::{Path, Stroke};
+use iced::{Color, Point, Radians, Rectangle, Size, Vector};
+
+use crate::bounds::distance_to_segment;
+use crate::molecule::AtomId;
+use crate::surface::Surface;
+
+/// a p or sp3 orbital drawn on an atom, or a π overlap across the bond between two atoms, which
+/// follows its atoms as they move
+#[derive(Debug, Clone)]
+pub struct Orbital {
+    kind: OrbitalKind,
+    atom: AtomId,
+    /// the other atom of a π overlap's bond
+    partner: Option<AtomId>,
+    /// direction the shaded lobe points in, in radians, unused by π overlaps which stand
+    /// square to their bond
+    angle: f32,
+    /// length of the shaded lobe from the atom, or a π overlap's height above its bond
+    size: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrbitalKind {
+    P,
+    Sp3,
+    Pi,
+}
+
+impl Orbital {
+    /// orbitals are never turned smaller than this
+    pub const MIN_SIZE: f32 = 5.0;
+    /// opacity of the shaded lobe, telling the phases apart
+    const SHADE_OPACITY: f32 = 0.35;
+    /// half the width of a lobe, relative to its length
+    const LOBE_WIDTH: f32 = 0.45;
+    /// length of an sp3 orbital's back lobe, relative to its front one
+    const BACK_LOBE: f32 = 0.3;
+
+    pub fn new(kind: OrbitalKind, atom: AtomId, partner: Option<AtomId>, angle: f32, size: f32) -> Self {
+        Self {
+            kind,
+            atom,
+            partner,
+            angle,
+            size: size.max(Self::MIN_SIZE),
+        }
+    }
+
+    pub fn kind(&self) -> OrbitalKind {
+        self.kind
+    }
+
+    pub fn atom(&self) -> AtomId {
+        self.atom
+    }
+
+    pub fn partner(&self) -> Option<AtomId> {
+        self.partner
+    }
+
+    pub fn angle(&self) -> f32 {
+        self.angle
+    }
+
+    pub fn size(&self) -> f32 {
+        self.size
+    }
+
+    pub fn atom_ids(&self) -> impl Iterator<Item = AtomId> {
+        [Some(self.atom), self.partner].into_iter().flatten()
+    }
+
+    /// where the orbital's orientation handle is, given the positions of its atoms
+    pub fn handle(&self, start: Point, end: Option<Point>) -> Point {
+        match end {
+            Some(end) => Self::middle(start, end) + Self::normal(start, end) * self.size,
+            None => start + Vector::new(self.angle.cos(), self.angle.sin()) * self.size,
+        }
+    }
+
+    /// points the orbital at a position and stretches it to reach it, a π overlap only taking
+    /// its height from how far the position is from its bond
+    pub fn turn(&mut self, start: Point, end: Option<Point>, position: Point) {
+        let anchor = end.map_or(start, |end| Self::middle(start, end));
+        let offset = position - anchor;
+
+        self.angle = offset.y.atan2(offset.x);
+        self.size = offset.x.hypot(offset.y).max(Self::MIN_SIZE);
+    }
+
+    /// a box around every lobe, with room to spare
+    pub fn bounds(&self, start: Point, end: Option<Point>) -> Rectangle {
+        let (center, extent) = match end {
+            Some(end) => (Self::middle(start, end), start.distance(end) / 2.0 + self.size * 1.3),
+            None => (start, self.size),
+        };
+
+        Rectangle::new(center - Vector::new(extent, extent), Size::new(extent * 2.0, extent * 2.0))
+    }
+
+    /// distance from a point to the nearest lobe, roughly, zero inside one
+    pub fn distance_to(&self, position: Point, start: Point, end: Option<Point>) -> f32 {
+        match end {
+            Some(end) => {
+                let offset = Self::normal(start, end) * (self.size / 2.0);
+                let distance = [offset, offset * -1.0]
+                    .into_iter()
+                    .map(|offset| distance_to_segment(position, start + offset, end + offset))
+                    .fold(f32::MAX, f32::min);
+
+                (distance - self.size / 2.0).max(0.0)
+            }
+            None => {
+                let direction = Vector::new(self.angle.cos(), self.angle.sin());
+                let back = match self.kind {
+                    OrbitalKind::Sp3 => self.size * Self::BACK_LOBE,
+                    _ => self.size,
+                };
+                let distance = distance_to_segment(position, start - direction * back, start + direction * self.size);
+
+                (distance - self.size * Self::LOBE_WIDTH / 2.0).max(0.0)
+            }
+        }
+    }
+
+    /// draws the orbital on its atoms, shading the lobe of one phase
+    pub fn draw(&self, frame: &mut impl Surface, start: Point, end: Option<Point>, color: Color, width: f32) {
+        let stroke = Stroke::default().with_width(width).with_color(color);
+        let shade = Color { a: color.a * Self::SHADE_OPACITY, ..color };
+
+        let (front, back) = match (self.kind, end) {
+            (OrbitalKind::Pi, Some(end)) => {
+                let normal = Self::normal(start, end);
+                let direction = end - start;
+                let lobe = |side: f32| {
+                    Path::new(|builder| {
+                        builder.ellipse(Elliptical {
+                            center: Self::middle(start, end) + normal * (side * self.size / 2.0),
+                            radii: Vector::new(direction.x.hypot(direction.y) / 2.0 + self.size * 0.3, self.size / 2.0),
+                            rotation: Radians(direction.y.atan2(direction.x)),
+                            start_angle: Radians(0.0),
+                            end_angle: Radians(2.0 * PI),
+                        })
+                    })
+                };
+
+                (lobe(1.0), lobe(-1.0))
+            }
+            _ => {
+                let direction = Vector::new(self.angle.cos(), self.angle.sin());
+                let back = match self.kind {
+                    OrbitalKind::Sp3 => self.size * Self::BACK_LOBE,
+                    _ => self.size,
+                };
+
+                (Self::lobe(start, direction, self.size), Self::lobe(start, direction * -1.0, back))
+            }
+        };
+
+        frame.fill(&front, shade);
+        frame.stroke(&front, stroke);
+        frame.stroke(&back, stroke);
+    }
+
+    /// a balloon from an atom out to a rounded tip
+    fn lobe(origin: Point, direction: Vector, length: f32) -> Path {
+        let normal = Vector::new(-direction.y, direction.x) * (length * Self::LOBE_WIDTH);
+        let tip = origin + direction * length;
+
+        Path::new(|builder| {
+            builder.move_to(origin);
+            builder.bezier_curve_to(origin + direction * (length * 0.25) + normal * 1.1, tip + direction * (length * 0.05) + normal * 1.1, tip);
+            builder.bezier_curve_to(tip + direction * (length * 0.05) - normal * 1.1, origin + direction * (length * 0.25) - normal * 1.1, origin);
+            builder.close();
+        })
+    }
+
+    fn middle(start: Point, end: Point) -> Point {
+        Point::new((start.x + end.x) / 2.0, (start.y + end.y) / 2.0)
+    }
+
+    /// unit vector square to a bond, on its upper side when it runs left to right
+    fn normal(start: Point, end: Point) -> Vector {
+        let direction = end - start;
+        let length = direction.x.hypot(direction.y).max(f32::EPSILON);
+        Vector::new(direction.y, -direction.x) * (1.0 / length)
+    }
+}
+
+impl OrbitalKind {
+    pub const ALL: [OrbitalKind; 3] = [Self::P, Self::Sp3, Self::Pi];
+
+    /// name without spaces, as saved in sessions, e.g. "sp3"
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::P => "p",
+            Self::Sp3 => "sp3",
+            Self::Pi => "pi",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    /// length of a new orbital, relative to the document's bond length
+    pub fn size(&self) -> f32 {
+        match self {
+            Self::P => 0.8,
+            Self::Sp3 => 0.9,
+            Self::Pi => 0.6,
+        }
+    }
+}
+
+impl fmt::Display for OrbitalKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::P => write!(f, "p orbital"),
+            Self::Sp3 => write!(f, "sp³ orbital"),
+            Self::Pi => write!(f, "π overlap"),
+        }
+    }
+}
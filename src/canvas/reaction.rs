@@ -0,0 +1,111 @@
+use iced::{Point, Rectangle, Vector};
+
+use crate::molecule::{MoleculeId, ShapeId};
+
+/// the molecules drawn around one reaction arrow, each in order along the arrow
+#[derive(Debug, Clone)]
+pub struct ReactionScheme {
+    pub arrow_id: ShapeId,
+    pub reactants: Vec<MoleculeId>,
+    pub agents: Vec<MoleculeId>,
+    pub products: Vec<MoleculeId>,
+}
+
+/// where a molecule lies relative to an arrow
+struct Placement {
+    /// distance of the molecule's centre along the arrow from its tail
+    along: f32,
+    /// distance from the molecule to the arrow's tail when it's behind it, or its head when it's
+    /// past it, so of two arrows in line with it the one it's further to the side of is further
+    gap: f32,
+    /// whether the molecule is beside the arrow, close enough to its line to belong to it
+    beside: bool,
+}
+
+/// groups the molecules around each arrow, given as its id, tail and head: those behind its tail are
+/// reactants, those over or under it are agents and those past its head are products. A molecule
+/// belongs to the nearest arrow on each side, so the product of one step is the reactant of the
+/// next, and molecules written over an arrow are only ever its agents
+pub fn find_reactions(arrows: &[(ShapeId, Point, Point)], molecules: &[(MoleculeId, Rectangle)], reach: f32) -> Vec<ReactionScheme> {
+    let placements = arrows
+        .iter()
+        .map(|(_, tail, head)| {
+            molecules
+                .iter()
+                .map(|(_, rectangle)| place(*tail, *head, *rectangle, reach))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let lengths = arrows.iter().map(|(_, tail, head)| tail.distance(*head)).collect::<Vec<_>>();
+
+    let is_agent = |arrow: usize, molecule: usize| {
+        let placement = &placements[arrow][molecule];
+        placement.beside && (0.0..=lengths[arrow]).contains(&placement.along)
+    };
+    let agents = (0..molecules.len())
+        .filter(|molecule| (0..arrows.len()).any(|arrow| is_agent(arrow, *molecule)))
+        .collect::<Vec<_>>();
+
+    // the arrow with the nearest tail in front of each molecule, or the nearest head behind it
+    let nearest = |behind: bool| {
+        (0..molecules.len())
+            .map(|molecule| match agents.contains(&molecule) {
+                true => None,
+                false => (0..arrows.len())
+                    .filter(|arrow| {
+                        let placement = &placements[*arrow][molecule];
+                        placement.beside && if behind { placement.along < 0.0 } else { placement.along > lengths[*arrow] }
+                    })
+                    .min_by(|a, b| placements[*a][molecule].gap.total_cmp(&placements[*b][molecule].gap)),
+            })
+            .collect::<Vec<_>>()
+    };
+    let reactant_of = nearest(true);
+    let product_of = nearest(false);
+
+    (0..arrows.len())
+        .map(|arrow| {
+            let in_order = |mut members: Vec<usize>| {
+                members.sort_by(|a, b| placements[arrow][*a].along.total_cmp(&placements[arrow][*b].along));
+                members.into_iter().map(|molecule| molecules[molecule].0).collect()
+            };
+
+            ReactionScheme {
+                arrow_id: arrows[arrow].0,
+                reactants: in_order((0..molecules.len()).filter(|molecule| reactant_of[*molecule] == Some(arrow)).collect()),
+                agents: in_order((0..molecules.len()).filter(|molecule| is_agent(arrow, *molecule)).collect()),
+                products: in_order((0..molecules.len()).filter(|molecule| product_of[*molecule] == Some(arrow)).collect()),
+            }
+        })
+        .collect()
+}
+
+/// where a molecule's bounds lie relative to an arrow, the molecule being beside the arrow when
+/// its bounds come within `reach` of the arrow's line
+fn place(tail: Point, head: Point, rectangle: Rectangle, reach: f32) -> Placement {
+    let length = tail.distance(head);
+    let direction = match length > f32::EPSILON {
+        true => (head - tail) * (1.0 / length),
+        false => Vector::new(1.0, 0.0),
+    };
+    let normal = Vector::new(-direction.y, direction.x);
+
+    let offset = rectangle.center() - tail;
+    let along = offset.x * direction.x + offset.y * direction.y;
+    let across = offset.x * normal.x + offset.y * normal.y;
+    // half the rectangle's extent along the arrow and across it
+    let half_along = (direction.x.abs() * rectangle.width + direction.y.abs() * rectangle.height) / 2.0;
+    let half_across = (normal.x.abs() * rectangle.width + normal.y.abs() * rectangle.height) / 2.0;
+
+    let gap_along = match along < 0.0 {
+        true => -along - half_along,
+        false => along - length - half_along,
+    };
+    let gap_across = (across.abs() - half_across).max(0.0);
+
+    Placement {
+        along,
+        gap: gap_along.max(0.0).hypot(gap_across),
+        beside: across.abs() <= half_across + reach,
+    }
+}
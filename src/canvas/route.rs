@@ -0,0 +1,363 @@
+use iced::{Point, Rectangle, Size, Vector};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use super::reaction::ReactionScheme;
+use crate::molecule::{FxIndexMap, MoleculeId, ShapeId};
+
+/// reaction steps chained by the molecules one step makes and another uses, ordered so each step
+/// comes after the steps making its reactants
+#[derive(Debug, Clone)]
+pub struct Route {
+    pub steps: Vec<ReactionScheme>,
+}
+
+/// where a route's molecules and arrows go once it is laid out
+#[derive(Debug, Clone, Default)]
+pub struct RouteLayout {
+    /// centre of each molecule's bounds
+    pub molecules: Vec<(MoleculeId, Point)>,
+    /// tail and head of each arrow
+    pub arrows: Vec<(ShapeId, Point, Point)>,
+}
+
+/// which way a line of steps points, each line going back from its last step
+#[derive(Debug, Clone, Copy)]
+enum Direction {
+    Right,
+    Down,
+}
+
+impl Direction {
+    fn along(self) -> Vector {
+        match self {
+            Direction::Right => Vector::new(1.0, 0.0),
+            Direction::Down => Vector::new(0.0, 1.0),
+        }
+    }
+
+    /// unit vector to the side agents are written on, away from the conditions
+    fn over(self) -> Vector {
+        match self {
+            Direction::Right => Vector::new(0.0, -1.0),
+            Direction::Down => Vector::new(1.0, 0.0),
+        }
+    }
+
+    /// the way branches leading into a line go
+    fn turned(self) -> Self {
+        match self {
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Right,
+        }
+    }
+
+    fn extent(self, size: Size) -> f32 {
+        match self {
+            Direction::Right => size.width,
+            Direction::Down => size.height,
+        }
+    }
+
+    fn breadth(self, size: Size) -> f32 {
+        match self {
+            Direction::Right => size.height,
+            Direction::Down => size.width,
+        }
+    }
+}
+
+/// steps that make each molecule, a molecule only ever being the product of one arrow
+fn producers(steps: &[ReactionScheme]) -> FxHashMap<MoleculeId, usize> {
+    steps
+        .iter()
+        .enumerate()
+        .flat_map(|(step, scheme)| scheme.products.iter().map(move |product| (*product, step)))
+        .collect()
+}
+
+/// groups reaction steps into routes, two steps being in the same route when a product of one is
+/// a reactant of the other, each route in the order of its first arrow
+pub fn find_routes(schemes: Vec<ReactionScheme>) -> Vec<Route> {
+    fn root(parents: &mut [usize], mut step: usize) -> usize {
+        while parents[step] != step {
+            parents[step] = parents[parents[step]];
+            step = parents[step];
+        }
+        step
+    }
+
+    let producers = producers(&schemes);
+    let mut parents = (0..schemes.len()).collect::<Vec<_>>();
+    for (consumer, scheme) in schemes.iter().enumerate() {
+        for producer in scheme.reactants.iter().filter_map(|reactant| producers.get(reactant)) {
+            let (a, b) = (root(&mut parents, *producer), root(&mut parents, consumer));
+            parents[a.max(b)] = a.min(b);
+        }
+    }
+
+    let mut groups = FxIndexMap::<usize, Vec<usize>>::default();
+    for step in 0..schemes.len() {
+        groups.entry(root(&mut parents, step)).or_default().push(step);
+    }
+
+    groups
+        .into_values()
+        .map(|mut remaining| {
+            // a step goes once every step making its reactants has, or next anyway when the steps
+            // go round in a circle
+            let mut order = Vec::with_capacity(remaining.len());
+            while !remaining.is_empty() {
+                let ready = remaining
+                    .iter()
+                    .position(|step| {
+                        schemes[*step]
+                            .reactants
+                            .iter()
+                            .filter_map(|reactant| producers.get(reactant))
+                            .all(|producer| producer == step || !remaining.contains(producer))
+                    })
+                    .unwrap_or(0);
+                order.push(remaining.remove(ready));
+            }
+
+            Route {
+                steps: order.into_iter().map(|step| schemes[step].clone()).collect(),
+            }
+        })
+        .collect()
+}
+
+/// lays a route out from its last steps back: the longest chain of steps runs in a row, each step
+/// before it further left, and branches making other reactants run in a column down to them,
+/// their own branches running in rows again. Routes with several last steps get a row each,
+/// one under the other. Molecules are `spacing` apart, those that branches lead to `clearance`
+/// apart so no other molecule is beside the branch's arrow, and arrows are at least
+/// `arrow_length` long, reaching `spacing` past their agents
+pub fn lay_out(route: &Route, sizes: &FxHashMap<MoleculeId, Size>, spacing: f32, clearance: f32, arrow_length: f32) -> RouteLayout {
+    let steps = &route.steps;
+    let producers = producers(steps);
+
+    // steps come after those making their reactants
+    let mut depths = vec![0; steps.len()];
+    for (step, scheme) in steps.iter().enumerate() {
+        depths[step] = 1 + scheme
+            .reactants
+            .iter()
+            .filter_map(|reactant| producers.get(reactant))
+            .filter(|producer| **producer < step)
+            .map(|producer| depths[*producer])
+            .max()
+            .unwrap_or(0);
+    }
+
+    let mut layout = Layout {
+        steps,
+        sizes,
+        producers,
+        depths,
+        spacing,
+        clearance,
+        arrow_length,
+        centers: FxIndexMap::default(),
+        arrows: vec![],
+        laid_out: FxHashSet::default(),
+    };
+
+    let last_steps = (0..steps.len())
+        .filter(|step| !steps.iter().any(|scheme| steps[*step].products.iter().any(|product| scheme.reactants.contains(product))))
+        .collect::<Vec<_>>();
+    // steps going round in a circle have no last step, so start from the last one listed
+    let last_steps = match last_steps.is_empty() {
+        true => (steps.len().checked_sub(1)).into_iter().collect(),
+        false => last_steps,
+    };
+
+    let mut bottom = None::<f32>;
+    for step in last_steps {
+        let [first_molecule, first_arrow] = [layout.centers.len(), layout.arrows.len()];
+
+        let head = Point::ORIGIN;
+        let mut edge = head + Direction::Right.along() * spacing;
+        for product in &steps[step].products {
+            if !layout.centers.contains_key(product) {
+                edge = layout.place(*product, edge, Direction::Right) + Direction::Right.along() * spacing;
+            }
+        }
+        layout.step(step, head, Direction::Right);
+
+        // each row starts clear of the one before
+        let Some(bounds) = layout.bounds(first_molecule, first_arrow) else {
+            continue;
+        };
+        let offset = match bottom {
+            Some(bottom) => Vector::new(0.0, bottom + clearance - bounds.y),
+            None => Vector::ZERO,
+        };
+        layout.shift(first_molecule, first_arrow, offset);
+        bottom = Some(bounds.y + bounds.height + offset.y);
+    }
+
+    RouteLayout {
+        molecules: layout.centers.into_iter().collect(),
+        arrows: layout.arrows,
+    }
+}
+
+struct Layout<'a> {
+    steps: &'a [ReactionScheme],
+    sizes: &'a FxHashMap<MoleculeId, Size>,
+    producers: FxHashMap<MoleculeId, usize>,
+    /// number of steps in the longest chain ending with each step
+    depths: Vec<usize>,
+    spacing: f32,
+    clearance: f32,
+    arrow_length: f32,
+    centers: FxIndexMap<MoleculeId, Point>,
+    arrows: Vec<(ShapeId, Point, Point)>,
+    laid_out: FxHashSet<usize>,
+}
+
+impl Layout<'_> {
+    fn size(&self, molecule_id: &MoleculeId) -> Size {
+        self.sizes.get(molecule_id).copied().unwrap_or(Size::ZERO)
+    }
+
+    /// puts a molecule just past a point, returning its far edge
+    fn place(&mut self, molecule_id: MoleculeId, edge: Point, direction: Direction) -> Point {
+        let extent = direction.extent(self.size(&molecule_id));
+        self.centers.insert(molecule_id, edge + direction.along() * (extent / 2.0));
+        edge + direction.along() * extent
+    }
+
+    /// the step a reactant comes from when it hasn't been laid out yet
+    fn producer(&self, molecule_id: &MoleculeId) -> Option<usize> {
+        self.producers
+            .get(molecule_id)
+            .copied()
+            .filter(|producer| !self.laid_out.contains(producer))
+    }
+
+    /// lays out a step with its arrow ending at `head`, its products already placed, then the
+    /// steps making its reactants
+    fn step(&mut self, step: usize, head: Point, direction: Direction) {
+        if !self.laid_out.insert(step) {
+            return;
+        }
+        let steps = self.steps;
+        let scheme = &steps[step];
+        let (spacing, over) = (self.spacing, direction.over());
+
+        let agents = scheme
+            .agents
+            .iter()
+            .filter(|agent| !self.centers.contains_key(*agent))
+            .copied()
+            .collect::<Vec<_>>();
+        let agents_extent = agents.iter().map(|agent| direction.extent(self.size(agent))).sum::<f32>()
+            + spacing * agents.len().saturating_sub(1) as f32;
+        let agents_breadth = agents.iter().map(|agent| direction.breadth(self.size(agent))).fold(0.0, f32::max);
+
+        let length = self.arrow_length.max(agents_extent + spacing * 2.0);
+        let tail = head - direction.along() * length;
+        self.arrows.push((scheme.arrow_id, tail, head));
+
+        // agents stand half a spacing off the arrow
+        let mut edge = tail + direction.along() * ((length - agents_extent) / 2.0) + over * ((spacing + agents_breadth) / 2.0);
+        for agent in agents {
+            edge = self.place(agent, edge, direction) + direction.along() * spacing;
+        }
+
+        // other products go beside the one the next step uses
+        let placed = scheme.products.iter().find_map(|product| Some((*product, *self.centers.get(product)?)));
+        if let Some((product, center)) = placed {
+            let mut side = center - over * (direction.breadth(self.size(&product)) / 2.0 + spacing);
+            for product in scheme.products.iter().copied() {
+                if self.centers.contains_key(&product) {
+                    continue;
+                }
+                let breadth = direction.breadth(self.size(&product));
+                self.centers.insert(product, side - over * (breadth / 2.0));
+                side = side - over * (breadth + spacing);
+            }
+        }
+
+        // the reactant carrying on the longest chain goes furthest back, so its step can carry on
+        // in line, and branches lead to the others
+        let main = scheme
+            .reactants
+            .iter()
+            .filter_map(|reactant| Some((*reactant, self.producer(reactant)?)))
+            .max_by_key(|(_, producer)| self.depths[*producer])
+            .map(|(reactant, _)| reactant);
+        let mut reactants = scheme.reactants.clone();
+        if let Some(main) = main {
+            reactants.retain(|reactant| *reactant != main);
+            reactants.insert(0, main);
+        }
+
+        let backwards = |distance: f32| direction.along() * -distance;
+        let mut edge = tail;
+        let mut branched = false;
+        let mut branches = vec![];
+        for reactant in reactants.iter().rev() {
+            let branches_here = Some(*reactant) != main && self.producer(reactant).is_some();
+            let gap = match branches_here || branched {
+                true => self.clearance,
+                false => spacing,
+            };
+            if self.centers.contains_key(reactant) {
+                continue;
+            }
+
+            let extent = direction.extent(self.size(reactant));
+            let center = edge + backwards(gap + extent / 2.0);
+            self.centers.insert(*reactant, center);
+            edge = center + backwards(extent / 2.0);
+            branched = branches_here;
+
+            if branches_here {
+                branches.push(*reactant);
+            }
+        }
+
+        if let Some(main) = main {
+            let center = self.centers[&main];
+            let extent = direction.extent(self.size(&main));
+            if let Some(producer) = self.producer(&main) {
+                self.step(producer, center + backwards(extent / 2.0 + spacing), direction);
+            }
+        }
+        let turned = direction.turned();
+        for reactant in branches {
+            let center = self.centers[&reactant];
+            let extent = turned.extent(self.size(&reactant));
+            if let Some(producer) = self.producer(&reactant) {
+                self.step(producer, center - turned.along() * (extent / 2.0 + spacing), turned);
+            }
+        }
+    }
+
+    /// the bounds of the molecules and arrows laid out since the given counts of each
+    fn bounds(&self, first_molecule: usize, first_arrow: usize) -> Option<Rectangle> {
+        let molecules = self.centers.iter().skip(first_molecule).map(|(molecule_id, center)| {
+            let size = self.size(molecule_id);
+            Rectangle::new(*center - Vector::new(size.width / 2.0, size.height / 2.0), size)
+        });
+        let arrows = self.arrows[first_arrow..]
+            .iter()
+            .flat_map(|(_, tail, head)| [*tail, *head])
+            .map(|point| Rectangle::new(point, Size::ZERO));
+
+        molecules.chain(arrows).reduce(|a, b| a.union(&b))
+    }
+
+    fn shift(&mut self, first_molecule: usize, first_arrow: usize, offset: Vector) {
+        for (_, center) in self.centers.iter_mut().skip(first_molecule) {
+            *center = *center + offset;
+        }
+        for (_, tail, head) in &mut self.arrows[first_arrow..] {
+            *tail = *tail + offset;
+            *head = *head + offset;
+        }
+    }
+}
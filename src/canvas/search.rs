@@ -0,0 +1,59 @@
+use crate::molecule::{AtomId, MoleculeId};
+
+use super::state::State;
+
+/// atoms whose labels contain the searched text, with one of them focused
+#[derive(Debug, Default, Clone)]
+pub struct Search {
+    matches: Vec<(MoleculeId, AtomId)>,
+    current: usize,
+}
+
+impl Search {
+    /// finds matching atoms on visible layers in document order
+    pub fn new(state: &State, query: &str) -> Self {
+        if query.is_empty() {
+            return Self::default();
+        }
+
+        let matches = state
+            .molecules()
+            .flat_map(|(molecule_id, molecule)| {
+                molecule
+                    .atoms()
+                    .filter(|(_, atom)| atom.label().contains(query))
+                    .map(move |(atom_id, _)| (*molecule_id, *atom_id))
+            })
+            .collect();
+
+        Self { matches, current: 0 }
+    }
+
+    pub fn matches(&self) -> impl Iterator<Item = &(MoleculeId, AtomId)> {
+        self.matches.iter()
+    }
+
+    pub fn current(&self) -> Option<(MoleculeId, AtomId)> {
+        self.matches.get(self.current).copied()
+    }
+
+    /// one based index of the focused match and the number of matches
+    pub fn position(&self) -> (usize, usize) {
+        match self.matches.len() {
+            0 => (0, 0),
+            len => (self.current + 1, len),
+        }
+    }
+
+    pub fn next(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = (self.current + 1) % self.matches.len();
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = (self.current + self.matches.len() - 1) % self.matches.len();
+        }
+    }
+}
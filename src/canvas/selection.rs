@@ -46,10 +46,68 @@ impl Selection {
         self.0.clear()
     }
 
+    /// returns the selected item if exactly one item is selected
+    pub fn only(&self) -> Option<SingleSelection> {
+        match self.0.as_slice() {
+            [single_selection] => Some(*single_selection),
+            _ => None,
+        }
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &SingleSelection> {
         self.0.iter()
     }
 
+    /// the whole molecules the selected atoms, bonds and molecules are part of, each once
+    pub fn parent_molecules(&self) -> Selection {
+        let mut molecule_ids: Vec<MoleculeId> = vec![];
+        for single_selection in &self.0 {
+            if !molecule_ids.contains(&single_selection.molecule_id()) {
+                molecule_ids.push(single_selection.molecule_id());
+            }
+        }
+
+        molecule_ids.into_iter().map(SingleSelection::Molecule).collect()
+    }
+
+    /// adds the atoms one bond further out from the selected atoms and bonds, with the bonds
+    /// reaching them, so repeating it takes in a substituent shell by shell
+    pub fn grow(&self, state: &State) -> Result<Selection> {
+        let mut grown = self.0.clone();
+        let mut add = |single_selection: SingleSelection| {
+            if !grown.contains(&single_selection) {
+                grown.push(single_selection);
+            }
+        };
+
+        for single_selection in &self.0 {
+            let molecule_id = single_selection.molecule_id();
+            if self.0.contains(&SingleSelection::Molecule(molecule_id)) {
+                continue;
+            }
+            let molecule = state.get_molecule(&molecule_id).context("while growing selection")?;
+
+            let atom_ids = match single_selection {
+                SingleSelection::Molecule(_) => continue,
+                SingleSelection::Atom(_, atom_id) => vec![*atom_id],
+                SingleSelection::Bond(_, bond_id) => {
+                    let bond = molecule.get_bond(bond_id).context("while growing selection")?;
+                    vec![bond.start(), bond.end()]
+                }
+            };
+
+            for (bond_id, bond) in molecule.bonds() {
+                if atom_ids.contains(&bond.start()) || atom_ids.contains(&bond.end()) {
+                    add(SingleSelection::Atom(molecule_id, bond.start()));
+                    add(SingleSelection::Atom(molecule_id, bond.end()));
+                    add(SingleSelection::Bond(molecule_id, *bond_id));
+                }
+            }
+        }
+
+        Ok(Selection(grown))
+    }
+
     pub fn bounds(&self, state: &State) -> Result<Vec<Bounds>> {
         self.0.iter().map(|s| s.bounds(state).context("while getting selection's bounds")).collect::<Result<Vec<_>>>()
     }
@@ -70,6 +128,7 @@ impl Selection {
         }
     }
 
+    #[allow(dead_code)]
     pub fn remove(&mut self, single_selection: SingleSelection) {
         match single_selection {
             SingleSelection::Molecule(molecule_id) => self.0.retain(|item| matches!(item, SingleSelection::Molecule(mol_id) | SingleSelection::Atom(mol_id, _) | SingleSelection::Bond(mol_id, _) if molecule_id == *mol_id)),
@@ -110,6 +169,14 @@ pub enum SingleSelection {
 }
 
 impl SingleSelection {
+    pub fn molecule_id(&self) -> MoleculeId {
+        match self {
+            SingleSelection::Molecule(molecule_id)
+            | SingleSelection::Atom(molecule_id, _)
+            | SingleSelection::Bond(molecule_id, _) => *molecule_id,
+        }
+    }
+
     pub fn bounds(&self, state: &State) -> Result<Bounds> {
         Ok(match self {
             Self::Molecule(molecule_id) => {
@@ -0,0 +1,355 @@
+use std::f32::consts::PI;
+use std::fmt;
+
+use iced::widget::canvas::path::arc::Elliptical;
+use iced::widget::canvas::{Path, Stroke};
+use iced::{Color, Point, Radians, Rectangle, Size, Vector};
+
+use crate::bounds::distance_to_segment;
+use crate::formats::ReactionConditions;
+use crate::molecule::LayerId;
+use crate::surface::Surface;
+
+use super::clipart::Clipart;
+use super::ink::InkColor;
+
+/// a box, ellipse, line, arrow or piece of clipart drawn over the structures, for framing parts
+/// of a scheme
+#[derive(Debug, Clone)]
+pub struct Shape {
+    kind: ShapeKind,
+    /// opposite corners of a box, ellipse or clipart's bounds, or the ends of a line or arrow, which points
+    /// at the end
+    start: Point,
+    end: Point,
+    style: ShapeStyle,
+    layer: LayerId,
+    /// how the reaction an arrow stands for was run
+    conditions: ReactionConditions,
+    /// markup written over an arrow, e.g. its reagents, and under it before its conditions
+    above: String,
+    below: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShapeKind {
+    Rectangle,
+    Ellipse,
+    Line,
+    Arrow,
+    Clipart(Clipart),
+}
+
+/// how a shape's outline and inside are drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapeStyle {
+    pub width: f32,
+    pub color: InkColor,
+    /// fills boxes and ellipses with a faint wash of the outline's colour
+    pub filled: bool,
+}
+
+/// the side of an arrow text is written on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrowSide {
+    Above,
+    Below,
+}
+
+/// the part of a shape a drag takes hold of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShapeDrag {
+    Move,
+    Start,
+    End,
+}
+
+impl Shape {
+    /// opacity of the wash filling a shape
+    const FILL_OPACITY: f32 = 0.2;
+    /// an arrow's head is this long and wide at a width of one, growing with the width
+    const HEAD_LENGTH: f32 = 5.0;
+    const HEAD_WIDTH: f32 = 3.0;
+
+    pub fn new(kind: ShapeKind, start: Point, end: Point, style: ShapeStyle, layer: LayerId) -> Self {
+        Self {
+            kind,
+            start,
+            end,
+            style,
+            layer,
+            conditions: ReactionConditions::default(),
+            above: String::new(),
+            below: String::new(),
+        }
+    }
+
+    pub fn kind(&self) -> ShapeKind {
+        self.kind
+    }
+
+    pub fn start(&self) -> Point {
+        self.start
+    }
+
+    pub fn end(&self) -> Point {
+        self.end
+    }
+
+    pub fn style(&self) -> ShapeStyle {
+        self.style
+    }
+
+    pub fn set_style(&mut self, style: ShapeStyle) {
+        self.style = style;
+    }
+
+    pub fn layer(&self) -> LayerId {
+        self.layer
+    }
+
+    pub fn set_layer(&mut self, layer: LayerId) {
+        self.layer = layer;
+    }
+
+    pub fn conditions(&self) -> &ReactionConditions {
+        &self.conditions
+    }
+
+    pub fn set_conditions(&mut self, conditions: ReactionConditions) {
+        self.conditions = conditions;
+    }
+
+    pub fn text(&self, side: ArrowSide) -> &str {
+        match side {
+            ArrowSide::Above => &self.above,
+            ArrowSide::Below => &self.below,
+        }
+    }
+
+    pub fn set_text(&mut self, side: ArrowSide, text: String) {
+        match side {
+            ArrowSide::Above => self.above = text,
+            ArrowSide::Below => self.below = text,
+        }
+    }
+
+    /// moves the end out so the shape is at least `length` long, pointing right when it has no
+    /// length to keep the direction of
+    pub fn lengthen_to(&mut self, length: f32) {
+        if self.length() >= length {
+            return;
+        }
+
+        let direction = match self.length() > f32::EPSILON {
+            true => self.direction(),
+            false => Vector::new(1.0, 0.0),
+        };
+        self.end = self.start + direction * length;
+    }
+
+    /// the box spanned by the two points, without the outline's width
+    fn rectangle(&self) -> Rectangle {
+        let top_left = Point::new(self.start.x.min(self.end.x), self.start.y.min(self.end.y));
+        Rectangle::new(top_left, Size::new((self.end.x - self.start.x).abs(), (self.end.y - self.start.y).abs()))
+    }
+
+    /// scales the shape about a point, then moves it
+    pub fn transform(&mut self, center: Point, factor: f32, offset: Vector) {
+        self.start = center + (self.start - center) * factor + offset;
+        self.end = center + (self.end - center) * factor + offset;
+    }
+
+    pub fn bounds(&self) -> Rectangle {
+        self.rectangle().expand(self.style.width / 2.0 + self.head_width())
+    }
+
+    /// moves the part of the shape held by a drag along with the cursor
+    pub fn drag(&mut self, drag: ShapeDrag, last: Point, position: Point) {
+        let offset = position - last;
+        match drag {
+            ShapeDrag::Move => {
+                self.start = self.start + offset;
+                self.end = self.end + offset;
+            }
+            ShapeDrag::Start => self.start = position,
+            ShapeDrag::End => self.end = position,
+        }
+    }
+
+    /// distance from a point to the shape's outline, or zero inside a filled box or ellipse or
+    /// anywhere on clipart
+    pub fn distance_to(&self, position: Point) -> f32 {
+        let rectangle = self.rectangle();
+        let distance = match self.kind {
+            ShapeKind::Line | ShapeKind::Arrow => distance_to_segment(position, self.start, self.end),
+            ShapeKind::Rectangle => {
+                let [top_left, bottom_right] = [rectangle.position(), rectangle.position() + Vector::new(rectangle.width, rectangle.height)];
+                let [top_right, bottom_left] = [Point::new(bottom_right.x, top_left.y), Point::new(top_left.x, bottom_right.y)];
+                match self.style.filled && rectangle.contains(position) {
+                    true => 0.0,
+                    false => [(top_left, top_right), (top_right, bottom_right), (bottom_right, bottom_left), (bottom_left, top_left)]
+                        .into_iter()
+                        .map(|(start, end)| distance_to_segment(position, start, end))
+                        .fold(f32::MAX, f32::min),
+                }
+            }
+            ShapeKind::Clipart(_) => match rectangle.contains(position) {
+                true => 0.0,
+                false => {
+                    let dx = (rectangle.x - position.x).max(position.x - rectangle.x - rectangle.width).max(0.0);
+                    let dy = (rectangle.y - position.y).max(position.y - rectangle.y - rectangle.height).max(0.0);
+                    dx.hypot(dy)
+                }
+            },
+            ShapeKind::Ellipse => {
+                // measured along the ray from the centre, close enough for picking
+                let center = rectangle.center();
+                let radii = Vector::new(rectangle.width.max(f32::EPSILON) / 2.0, rectangle.height.max(f32::EPSILON) / 2.0);
+                let offset = position - center;
+                let scaled = (offset.x / radii.x).hypot(offset.y / radii.y);
+                let length = offset.x.hypot(offset.y);
+                match self.style.filled && scaled <= 1.0 {
+                    true => 0.0,
+                    false if scaled == 0.0 => radii.x.min(radii.y),
+                    false => (length - length / scaled).abs(),
+                }
+            }
+        };
+
+        (distance - self.style.width / 2.0).max(0.0)
+    }
+
+    /// draws the shape, `text` being the colour of the theme's text or black in exports
+    pub fn draw(&self, frame: &mut impl Surface, text: Color) {
+        let color = self.style.color.color(text);
+        if let ShapeKind::Clipart(clipart) = self.kind {
+            clipart.draw(frame, self.rectangle(), self.style.width, color);
+            return;
+        }
+
+        let stroke = Stroke::default().with_width(self.style.width).with_color(color);
+        let path = self.path();
+
+        if self.style.filled && matches!(self.kind, ShapeKind::Rectangle | ShapeKind::Ellipse) {
+            frame.fill(&path, Color { a: color.a * Self::FILL_OPACITY, ..color });
+        }
+        frame.stroke(&path, stroke);
+
+        if let Some(head) = self.head() {
+            frame.fill(&head, color);
+        }
+    }
+
+    /// a shape of any kind being drawn from one point to another
+    pub fn draw_pending(kind: ShapeKind, start: Point, end: Point, style: ShapeStyle, frame: &mut impl Surface, text: Color) {
+        // the layer only matters once the shape is in the document
+        Self::new(kind, start, end, style, LayerId::new()).draw(frame, text);
+    }
+
+    fn path(&self) -> Path {
+        let rectangle = self.rectangle();
+        match self.kind {
+            ShapeKind::Rectangle | ShapeKind::Clipart(_) => Path::rectangle(rectangle.position(), rectangle.size()),
+            ShapeKind::Ellipse => Path::new(|builder| {
+                builder.ellipse(Elliptical {
+                    center: rectangle.center(),
+                    radii: Vector::new(rectangle.width / 2.0, rectangle.height / 2.0),
+                    rotation: Radians(0.0),
+                    start_angle: Radians(0.0),
+                    end_angle: Radians(2.0 * PI),
+                })
+            }),
+            // an arrow's line stops at the back of its head, so it doesn't poke out of the point
+            ShapeKind::Arrow => {
+                let direction = self.direction();
+                Path::line(self.start, self.end - direction * self.head_length().min(self.length()))
+            }
+            ShapeKind::Line => Path::line(self.start, self.end),
+        }
+    }
+
+    fn head(&self) -> Option<Path> {
+        if self.kind != ShapeKind::Arrow || self.length() <= f32::EPSILON {
+            return None;
+        }
+
+        let direction = self.direction();
+        let normal = Vector::new(-direction.y, direction.x);
+        let back = self.end - direction * self.head_length();
+
+        Some(Path::new(|builder| {
+            builder.move_to(self.end);
+            builder.line_to(back + normal * self.head_width());
+            builder.line_to(back - normal * self.head_width());
+            builder.close();
+        }))
+    }
+
+    fn length(&self) -> f32 {
+        self.start.distance(self.end)
+    }
+
+    fn direction(&self) -> Vector {
+        let offset = self.end - self.start;
+        match self.length() > f32::EPSILON {
+            true => offset * (1.0 / self.length()),
+            false => Vector::ZERO,
+        }
+    }
+
+    fn head_length(&self) -> f32 {
+        Self::HEAD_LENGTH * self.style.width.max(1.0)
+    }
+
+    /// half the width of an arrow's head, nothing for other shapes
+    fn head_width(&self) -> f32 {
+        match self.kind {
+            ShapeKind::Arrow => Self::HEAD_WIDTH * self.style.width.max(1.0),
+            _ => 0.0,
+        }
+    }
+}
+
+impl ArrowSide {
+    pub const ALL: [ArrowSide; 2] = [Self::Above, Self::Below];
+
+    /// name as saved in sessions
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Above => "above",
+            Self::Below => "below",
+        }
+    }
+}
+
+impl ShapeKind {
+    pub const ALL: [ShapeKind; 4] = [Self::Rectangle, Self::Ellipse, Self::Line, Self::Arrow];
+
+    /// name without spaces, as saved in sessions, e.g. "box" or "clipart/tlc-plate"
+    pub fn name(&self) -> String {
+        match self {
+            Self::Clipart(clipart) => format!("clipart/{}", clipart.name()),
+            _ => self.to_string().to_lowercase(),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.strip_prefix("clipart/") {
+            Some(clipart) => Clipart::from_name(clipart).map(Self::Clipart),
+            None => Self::ALL.into_iter().find(|kind| kind.name() == name),
+        }
+    }
+}
+
+impl fmt::Display for ShapeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rectangle => write!(f, "Box"),
+            Self::Ellipse => write!(f, "Ellipse"),
+            Self::Line => write!(f, "Line"),
+            Self::Arrow => write!(f, "Arrow"),
+            Self::Clipart(clipart) => clipart.fmt(f),
+        }
+    }
+}
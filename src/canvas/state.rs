@@ -1,45 +1,1201 @@
+use std::f32::consts::FRAC_PI_2;
+
 use anyhow::Context;
 use anyhow::Result;
 use iced::Point;
 use iced::Rectangle;
+use iced::Size;
 use iced::Vector;
-use crate::bounds::Bounds;
+use rustc_hash::FxHashMap;
+use crate::bounds::{distance_to_segment, segment_crosses_rectangle, Bounds};
+use crate::formats::{ReactionConditions, Structure};
 use crate::molecule;
 use crate::molecule::Bond;
+use crate::molecule::Direction;
+use crate::molecule::BondEnd;
 use crate::molecule::BondId;
+use crate::molecule::BondType;
 use crate::molecule::MoleculePosition;
 use crate::molecule::Atom;
+use crate::molecule::FxIndexMap;
+use crate::molecule::LayerId;
 use crate::molecule::Molecule;
 use crate::molecule::AtomId;
 use crate::molecule::MoleculeId;
-use rustc_hash::FxHashMap;
+use crate::molecule::InteractionId;
+use crate::molecule::InkId;
+use crate::molecule::ShapeId;
+use crate::molecule::OrbitalId;
+use crate::molecule::AnnotationId;
+
+use super::arrange::Arrangement;
+use super::document_style::{DocumentStyle, LabelFont};
+use super::history::Snapshot;
+use super::annotation::Annotation;
+use super::ink::{Ink, InkColor};
+use super::instance::Instance;
+use super::interaction::{Interaction, InteractionKind};
+use super::layer::Layer;
+use super::orbital::{Orbital, OrbitalKind};
+use super::shape::{ArrowSide, Shape, ShapeDrag, ShapeKind, ShapeStyle};
+use super::tag_filter::TagFilter;
+use super::selection::HoverSelection;
+use super::selection::SingleSelection;
+use super::Selection;
+
+/// the selected bond and the canvas positions of its two ends
+pub type BondHandles = (MoleculeId, BondId, [(BondEnd, Point); 2]);
+
+/// an interaction, the canvas positions of its two atoms and of its ends, where it leaves their
+/// labels
+pub type InteractionSegment<'a> = (&'a InteractionId, &'a Interaction, [Point; 2], [Point; 2]);
+
+/// an orbital and the canvas positions of its atom and of a π overlap's partner
+pub type OrbitalPlacement<'a> = (&'a OrbitalId, &'a Orbital, Point, Option<Point>);
+
+/// a bond's molecule, the atoms it joins and its canvas space end points
+type BondSegment = (MoleculeId, [AtomId; 2], Point, Point);
+
+/// a selected item by the atoms or bond it holds, which outlast molecules splitting and merging
+enum Selected {
+    Molecule(Vec<AtomId>),
+    Atom(AtomId),
+    Bond(BondId),
+}
+
+/// molecules selected whole, and the atoms selected out of the others
+type SelectedAtoms = (Vec<MoleculeId>, FxIndexMap<MoleculeId, Vec<AtomId>>);
+
+#[derive(Debug)]
+pub struct State {
+    molecules: FxIndexMap<MoleculeId, Molecule>,
+    selection: Selection,
+    style: DocumentStyle,
+    layers: FxIndexMap<LayerId, Layer>,
+    /// layer that new molecules are added to
+    active_layer: LayerId,
+    /// hydrogen bonds and other contacts between molecules, kept apart so the molecules aren't
+    /// merged
+    interactions: FxIndexMap<InteractionId, Interaction>,
+    tag_filter: TagFilter,
+    /// molecules drawn as linked copies of others, which follow every edit made to them
+    instances: Vec<Instance>,
+    /// freehand strokes drawn over the molecules
+    inks: FxIndexMap<InkId, Ink>,
+    /// boxes, ellipses, lines and arrows drawn over the molecules
+    shapes: FxIndexMap<ShapeId, Shape>,
+    /// orbitals drawn on atoms and π overlaps across bonds
+    orbitals: FxIndexMap<OrbitalId, Orbital>,
+    /// free text placed over the molecules
+    annotations: FxIndexMap<AnnotationId, Annotation>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        let layers = FxIndexMap::from_iter(
+            Layer::DEFAULT_NAMES.map(|name| (LayerId::new(), Layer::new(name.to_string()))),
+        );
+        let active_layer = *layers.keys().next().expect("default layers are not empty");
+
+        Self {
+            molecules: FxIndexMap::default(),
+            selection: Selection::default(),
+            style: DocumentStyle::default(),
+            layers,
+            active_layer,
+            interactions: FxIndexMap::default(),
+            tag_filter: TagFilter::default(),
+            instances: vec![],
+            inks: FxIndexMap::default(),
+            shapes: FxIndexMap::default(),
+            orbitals: FxIndexMap::default(),
+            annotations: FxIndexMap::default(),
+        }
+    }
+}
+
+impl State {
+    /// times the whole document is revisited when resolving overlaps
+    const RESOLVE_PASSES: usize = 3;
+    /// cost of a bond crossing a label, relative to the area of two overlapping labels
+    const BOND_OVERLAP_COST: f32 = 50.0;
+    /// steps out from the starting point searched for free space
+    const FREE_SPACE_STEPS: i32 = 20;
+
+    // pub fn add_molecule(&mut self, molecule_id: MoleculeId, position: Point) {
+    //     self.molecules
+    //         .insert(molecule_id, Molecule::new(position));
+    // }
+
+    /// starts a molecule on the active layer, which can't be hidden or locked, see `can_add_molecules`
+    pub fn add_molecule_with_atom(&mut self, molecule_id: MoleculeId, atom_id: AtomId, label: String, position: Point) -> Result<()> {
+        let active_layer = self.active_layer;
+        if !self.can_add_molecules() {
+            return Err(molecule::Error::LayerNotEditable(active_layer)).context("while adding molecule with atoms");
+        }
+        // new molecules have no tags, so ones that don't match are dimmed rather than hidden
+        self.tag_filter.set_hide(false);
+
+        let molecule = Molecule::new(position, atom_id, label, self.style.label_font.font(), active_layer)
+            .context("while adding molecule with atoms")?;
+        if self.molecules.insert(molecule_id, molecule).is_some() {
+            return Err(molecule::Error::MoleculeCollision(molecule_id)).context("while adding molecule with atoms")
+        };
+        Ok(())
+    }
+
+    /// adds a copy of a molecule from this or another document, with new ids for it, its atoms and
+    /// its bonds so it doesn't collide with the original, scaled by `factor` about `center` then
+    /// moved by `offset` onto the active layer and into the document's label font
+    pub fn paste_molecule(&mut self, copied: &Molecule, center: Point, factor: f32, offset: Vector) -> Result<MoleculeId> {
+        let active_layer = self.active_layer;
+        if !self.can_add_molecules() {
+            return Err(molecule::Error::LayerNotEditable(active_layer)).context("while pasting molecule");
+        }
+        self.tag_filter.set_hide(false);
+
+        let mut molecule = copied.clone();
+        molecule
+            .copy_structure(copied, &mut FxHashMap::default(), &mut FxHashMap::default())
+            .context("while pasting molecule")?;
+        molecule.scale(center, factor).context("while pasting molecule")?;
+        molecule.move_molecule(offset);
+        molecule.set_layer(active_layer);
+        molecule.set_locked(false);
+        molecule.set_label_font(self.style.label_font.font()).context("while pasting molecule")?;
+
+        let molecule_id = MoleculeId::new();
+        self.molecules.insert(molecule_id, molecule);
+
+        Ok(molecule_id)
+    }
+
+    /// adds a structure read from a file as a new molecule, with its atoms where the structure
+    /// puts them on the canvas, returning the ids given to the atoms in order
+    pub fn add_structure(&mut self, structure: &Structure) -> Result<Vec<AtomId>> {
+        let atom_ids = structure.atoms.iter().map(|_| AtomId::new()).collect::<Vec<_>>();
+
+        self.add_structure_as(structure, atom_ids)
+    }
+
+    /// adds a structure with the given ids for its atoms, in the order the structure lists them
+    pub fn add_structure_as(&mut self, structure: &Structure, atom_ids: Vec<AtomId>) -> Result<Vec<AtomId>> {
+        let Some(((label, position), atom_id)) = structure.atoms.first().zip(atom_ids.first()) else {
+            return Ok(atom_ids);
+        };
+
+        let molecule_id = MoleculeId::new();
+        self.add_molecule_with_atom(molecule_id, *atom_id, label.clone(), *position)
+            .context("while adding structure")?;
+        let molecule = self.get_molecule_mut(&molecule_id).context("while adding structure")?;
+
+        for ((label, position), atom_id) in structure.atoms.iter().zip(&atom_ids).skip(1) {
+            molecule.add_atom(*atom_id, label.clone(), *position).context("while adding structure")?;
+        }
+        for (start, end, bond_type) in &structure.bonds {
+            molecule
+                .add_bond(atom_ids[*start], atom_ids[*end], *bond_type)
+                .context("while adding structure")?;
+        }
+        for (index, map) in &structure.maps {
+            molecule.get_atom_mut(&atom_ids[*index]).context("while adding structure")?.set_map(Some(*map));
+        }
+
+        // structures written as several fragments become separate molecules
+        let fragments = molecule.split_all_fragments().context("while adding structure")?;
+        self.insert_fragments(molecule_id, fragments);
+
+        Ok(atom_ids)
+    }
+
+    /// whether the active layer is visible and unlocked, so molecules can be drawn on it
+    pub fn can_add_molecules(&self) -> bool {
+        self.layers.get(&self.active_layer).is_some_and(Layer::is_editable)
+    }
+
+    /// molecules on visible layers that aren't hidden by the tag filter
+    pub fn molecules(&self) -> impl Iterator<Item = (&MoleculeId, &Molecule)> {
+        self.molecules.iter().filter(|(_, molecule)| {
+            self.layers.get(&molecule.layer()).is_some_and(Layer::is_visible) && !self.tag_filter.is_hidden(molecule)
+        })
+    }
+
+    /// unlocked molecules on visible, unlocked layers
+    fn editable_molecules(&self) -> impl Iterator<Item = (&MoleculeId, &Molecule)> {
+        self.molecules.iter().filter(|(_, molecule)| self.is_editable(molecule))
+    }
+
+    pub fn is_editable(&self, molecule: &Molecule) -> bool {
+        !molecule.is_locked() && self.layers.get(&molecule.layer()).is_some_and(Layer::is_editable)
+    }
+
+    /// visible molecule an atom belongs to, which changes when molecules are split or merged
+    pub fn find_atom(&self, atom_id: &AtomId) -> Option<MoleculeId> {
+        self.molecules()
+            .find(|(_, molecule)| molecule.get_atom(atom_id).is_ok())
+            .map(|(molecule_id, _)| *molecule_id)
+    }
+
+    /// first atom of the editable molecule after or before the given one, wrapping around
+    pub fn adjacent_molecule_atom(&self, molecule_id: Option<&MoleculeId>, forward: bool) -> Option<(MoleculeId, AtomId)> {
+        let mut molecules = self.editable_molecules().collect::<Vec<_>>();
+        if !forward {
+            molecules.reverse();
+        }
+
+        let start = molecule_id
+            .and_then(|molecule_id| molecules.iter().position(|(id, _)| *id == molecule_id))
+            .map_or(0, |index| index + 1);
+
+        molecules
+            .iter()
+            .cycle()
+            .skip(start)
+            .take(molecules.len())
+            .find_map(|(molecule_id, molecule)| {
+                molecule.atoms().next().map(|(atom_id, _)| (**molecule_id, *atom_id))
+            })
+    }
+
+    /// bonded neighbour of an atom lying closest to a direction, ignoring any more than a right
+    /// angle away from it
+    pub fn neighbour_towards(&self, molecule_id: &MoleculeId, atom_id: &AtomId, direction: Vector) -> Result<Option<AtomId>> {
+        let molecule = self.get_molecule(molecule_id).context("while getting neighbour")?;
+        let position = molecule.atom_position(atom_id).context("while getting neighbour")?;
+
+        let mut best = None;
+        let mut best_alignment = 0.0;
+        for neighbour_id in molecule.get_directly_connected(*atom_id) {
+            let offset = molecule.atom_position(&neighbour_id).context("while getting neighbour")? - position;
+            let alignment = (offset.x * direction.x + offset.y * direction.y) / offset.x.hypot(offset.y);
+
+            if alignment > best_alignment {
+                best_alignment = alignment;
+                best = Some(neighbour_id);
+            }
+        }
+
+        Ok(best)
+    }
+
+    pub fn layers(&self) -> impl Iterator<Item = (&LayerId, &Layer)> {
+        self.layers.iter()
+    }
+
+    pub fn active_layer(&self) -> LayerId {
+        self.active_layer
+    }
+
+    fn get_layer_mut(&mut self, layer_id: &LayerId) -> Result<&mut Layer> {
+        self.layers
+            .get_mut(layer_id)
+            .ok_or(molecule::Error::LayerMissing(*layer_id))
+            .context("while getting layer")
+    }
+
+    pub fn add_layer(&mut self, name: String) -> LayerId {
+        let layer_id = LayerId::new();
+        self.layers.insert(layer_id, Layer::new(name));
+        self.active_layer = layer_id;
+
+        layer_id
+    }
+
+    pub fn set_active_layer(&mut self, layer_id: LayerId) -> Result<()> {
+        self.get_layer_mut(&layer_id).context("while setting active layer")?;
+        self.active_layer = layer_id;
+
+        Ok(())
+    }
+
+    pub fn set_layer_visible(&mut self, layer_id: &LayerId, visible: bool) -> Result<()> {
+        // selected items may no longer be editable
+        self.selection.clear();
+        self.get_layer_mut(layer_id).context("while setting layer visibility")?.set_visible(visible);
+
+        Ok(())
+    }
+
+    pub fn set_layer_locked(&mut self, layer_id: &LayerId, locked: bool) -> Result<()> {
+        self.selection.clear();
+        self.get_layer_mut(layer_id).context("while locking layer")?.set_locked(locked);
+
+        Ok(())
+    }
+
+    pub fn set_molecule_locked(&mut self, molecule_id: &MoleculeId, locked: bool) -> Result<()> {
+        self.selection.clear();
+        self.get_molecule_mut(molecule_id).context("while locking molecule")?.set_locked(locked);
+
+        Ok(())
+    }
+
+    pub fn unlock_all(&mut self) {
+        for molecule in self.molecules.values_mut() {
+            molecule.set_locked(false);
+        }
+    }
+
+    /// puts other layers in place of the document's, e.g. those of a saved session, returning their
+    /// ids in order. the active layer is given by its index, and items on layers that are gone
+    /// move to the first
+    pub fn set_layers(&mut self, layers: Vec<Layer>, active_layer: usize) -> Vec<LayerId> {
+        let layers = layers.into_iter().map(|layer| (LayerId::new(), layer)).collect::<FxIndexMap<_, _>>();
+        let Some(first) = layers.keys().next().copied() else {
+            return vec![];
+        };
+
+        self.layers = layers;
+        self.active_layer = self.layers.get_index(active_layer).map_or(first, |(layer_id, _)| *layer_id);
+        self.selection.clear();
+
+        for molecule in self.molecules.values_mut().filter(|molecule| !self.layers.contains_key(&molecule.layer())) {
+            molecule.set_layer(first);
+        }
+        for ink in self.inks.values_mut().filter(|ink| !self.layers.contains_key(&ink.layer())) {
+            ink.set_layer(first);
+        }
+        for shape in self.shapes.values_mut().filter(|shape| !self.layers.contains_key(&shape.layer())) {
+            shape.set_layer(first);
+        }
+        for annotation in self.annotations.values_mut().filter(|annotation| !self.layers.contains_key(&annotation.layer())) {
+            annotation.set_layer(first);
+        }
+
+        self.layers.keys().copied().collect()
+    }
+
+    pub fn move_to_layer(&mut self, molecule_id: &MoleculeId, layer_id: LayerId) -> Result<()> {
+        self.get_layer_mut(&layer_id).context("while moving molecule to layer")?;
+        self.get_molecule_mut(molecule_id).context("while moving molecule to layer")?.set_layer(layer_id);
+
+        Ok(())
+    }
+
+    /// every molecule, including those on hidden layers
+    pub fn all_molecules(&self) -> &FxIndexMap<MoleculeId, Molecule> {
+        &self.molecules
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            molecules: self.molecules.clone(),
+            interactions: self.interactions.clone(),
+            instances: self.instances.clone(),
+            inks: self.inks.clone(),
+            shapes: self.shapes.clone(),
+            orbitals: self.orbitals.clone(),
+            annotations: self.annotations.clone(),
+            bond_length: self.style.bond_length,
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.selection.clear();
+        self.molecules = snapshot.molecules;
+        self.interactions = snapshot.interactions;
+        self.instances = snapshot.instances;
+        self.inks = snapshot.inks;
+        self.shapes = snapshot.shapes;
+        self.orbitals = snapshot.orbitals;
+        self.annotations = snapshot.annotations;
+        self.style.bond_length = snapshot.bond_length;
+    }
+
+    /// interactions whose atoms are both on visible layers
+    pub fn interactions(&self) -> impl Iterator<Item = InteractionSegment<'_>> {
+        self.interactions.iter().filter_map(|(interaction_id, interaction)| {
+            let (atoms, ends) = self.interaction_ends(interaction)?;
+            Some((interaction_id, interaction, atoms, ends))
+        })
+    }
+
+    fn interaction_ends(&self, interaction: &Interaction) -> Option<([Point; 2], [Point; 2])> {
+        let [start, end] = [interaction.start(), interaction.end()].map(|atom_id| {
+            let molecule_id = self.find_atom(&atom_id)?;
+            let molecule = self.molecules.get(&molecule_id)?;
+            Some((molecule, atom_id, molecule.atom_position(&atom_id).ok()?))
+        });
+        let ((start_molecule, start_id, start), (end_molecule, end_id, end)) = start.zip(end)?;
+
+        Some((
+            [start, end],
+            [
+                start_molecule.bond_start_towards(&start_id, end).ok()?,
+                end_molecule.bond_start_towards(&end_id, start).ok()?,
+            ],
+        ))
+    }
+
+    /// map numbers of atoms in every molecule, including those on hidden layers
+    fn atom_map_numbers(&self) -> impl Iterator<Item = u32> + '_ {
+        self.molecules.values().flat_map(|molecule| molecule.atom_maps().map(|(_, map, _)| map))
+    }
+
+    /// gives two atoms the same map number, the first's if it has one, then the second's, or else
+    /// one no atom has yet
+    pub fn map_atoms(&mut self, first: AtomId, second: AtomId) -> Result<()> {
+        let mut atoms = vec![];
+        for atom_id in [first, second] {
+            let molecule_id = self
+                .find_atom(&atom_id)
+                .ok_or(molecule::Error::AtomMissing(atom_id))
+                .context("while mapping atoms")?;
+            atoms.push((molecule_id, atom_id));
+        }
+
+        let existing = atoms
+            .iter()
+            .find_map(|(molecule_id, atom_id)| self.get_atom(molecule_id, atom_id).ok()?.map());
+        let map = existing.unwrap_or_else(|| self.atom_map_numbers().max().unwrap_or_default() + 1);
+
+        for (molecule_id, atom_id) in atoms {
+            self.set_atom_map(&molecule_id, &atom_id, Some(map)).context("while mapping atoms")?;
+        }
+
+        Ok(())
+    }
+
+    pub fn set_atom_map(&mut self, molecule_id: &MoleculeId, atom_id: &AtomId, map: Option<u32>) -> Result<()> {
+        self.get_molecule_mut(molecule_id)
+            .and_then(|molecule| molecule.get_atom_mut(atom_id))
+            .context("while setting atom map")?
+            .set_map(map);
+
+        Ok(())
+    }
+
+    /// takes an atom's map number off it and every other atom with the same number
+    pub fn unmap_atom(&mut self, atom_id: &AtomId) -> Result<()> {
+        let molecule_id = self
+            .find_atom(atom_id)
+            .ok_or(molecule::Error::AtomMissing(*atom_id))
+            .context("while unmapping atom")?;
+        let Some(map) = self.get_atom(&molecule_id, atom_id).context("while unmapping atom")?.map() else {
+            return Ok(());
+        };
+
+        for molecule in self.molecules.values_mut() {
+            let mapped = molecule
+                .atom_maps()
+                .filter(|(_, other, _)| *other == map)
+                .map(|(atom_id, ..)| atom_id)
+                .collect::<Vec<_>>();
+            for atom_id in mapped {
+                molecule.get_atom_mut(&atom_id).context("while unmapping atom")?.set_map(None);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// links two atoms without merging their molecules
+    pub fn add_interaction(&mut self, start: AtomId, end: AtomId, kind: InteractionKind) -> Result<InteractionId> {
+        for atom_id in [start, end] {
+            self.find_atom(&atom_id)
+                .ok_or(molecule::Error::AtomMissing(atom_id))
+                .context("while adding interaction")?;
+        }
+
+        let interaction_id = InteractionId::new();
+        self.interactions.insert(interaction_id, Interaction::new(start, end, kind));
+
+        Ok(interaction_id)
+    }
+
+    pub fn delete_interaction(&mut self, interaction_id: &InteractionId) -> Result<()> {
+        self.interactions
+            .shift_remove(interaction_id)
+            .ok_or(molecule::Error::InteractionMissing(*interaction_id))
+            .context("while deleting interaction")?;
+
+        Ok(())
+    }
+
+    /// the interaction passing closest to a point, if any is within the tolerance
+    pub fn interaction_at(&self, position: Point, tolerance: f32) -> Option<InteractionId> {
+        self.interactions()
+            .map(|(interaction_id, _, _, [start, end])| (interaction_id, distance_to_segment(position, start, end)))
+            .filter(|(_, distance)| *distance <= tolerance)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(interaction_id, _)| *interaction_id)
+    }
+
+    /// orbitals whose atoms are all in the document
+    pub fn orbitals(&self) -> impl Iterator<Item = OrbitalPlacement<'_>> {
+        self.orbitals.iter().filter_map(|(orbital_id, orbital)| {
+            let start = self.atom_position(&orbital.atom())?;
+            let end = match orbital.partner() {
+                Some(partner) => Some(self.atom_position(&partner)?),
+                None => None,
+            };
+            Some((orbital_id, orbital, start, end))
+        })
+    }
+
+    fn atom_position(&self, atom_id: &AtomId) -> Option<Point> {
+        let molecule_id = self.find_atom(atom_id)?;
+        self.molecules.get(&molecule_id)?.atom_position(atom_id).ok()
+    }
+
+    /// draws an orbital on an atom, or a π overlap across its bond to a partner, sized to the
+    /// document's bond length and pointing away from the atom's bonds
+    pub fn add_orbital(&mut self, kind: OrbitalKind, atom_id: AtomId, partner: Option<AtomId>) -> Result<OrbitalId> {
+        for atom_id in [Some(atom_id), partner].into_iter().flatten() {
+            self.find_atom(&atom_id)
+                .ok_or(molecule::Error::AtomMissing(atom_id))
+                .context("while adding orbital")?;
+        }
+
+        let angle = match kind {
+            OrbitalKind::Sp3 => {
+                let molecule_id = self
+                    .find_atom(&atom_id)
+                    .ok_or(molecule::Error::AtomMissing(atom_id))
+                    .context("while adding orbital")?;
+                let direction = self
+                    .get_molecule(&molecule_id)
+                    .and_then(|molecule| molecule.sprout_direction(&atom_id))
+                    .context("while adding orbital")?;
+                direction.y.atan2(direction.x)
+            }
+            // p orbitals stand upright
+            OrbitalKind::P | OrbitalKind::Pi => -FRAC_PI_2,
+        };
+
+        let orbital_id = OrbitalId::new();
+        let size = kind.size() * self.style.bond_length;
+        self.orbitals.insert(orbital_id, Orbital::new(kind, atom_id, partner, angle, size));
+
+        Ok(orbital_id)
+    }
+
+    /// puts back an orbital as it was, e.g. from a saved session
+    pub fn insert_orbital(&mut self, orbital: Orbital) -> Result<OrbitalId> {
+        for atom_id in orbital.atom_ids() {
+            self.find_atom(&atom_id)
+                .ok_or(molecule::Error::AtomMissing(atom_id))
+                .context("while inserting orbital")?;
+        }
+
+        let orbital_id = OrbitalId::new();
+        self.orbitals.insert(orbital_id, orbital);
+
+        Ok(orbital_id)
+    }
+
+    pub fn delete_orbital(&mut self, orbital_id: &OrbitalId) -> Result<()> {
+        self.orbitals
+            .shift_remove(orbital_id)
+            .ok_or(molecule::Error::OrbitalMissing(*orbital_id))
+            .context("while deleting orbital")?;
+
+        Ok(())
+    }
+
+    /// points an orbital at a position, see `Orbital::turn`
+    pub fn turn_orbital(&mut self, orbital_id: &OrbitalId, position: Point) -> Result<()> {
+        let orbital = self
+            .orbitals
+            .get(orbital_id)
+            .ok_or(molecule::Error::OrbitalMissing(*orbital_id))
+            .context("while turning orbital")?;
+        let start = self
+            .atom_position(&orbital.atom())
+            .ok_or(molecule::Error::AtomMissing(orbital.atom()))
+            .context("while turning orbital")?;
+        let end = match orbital.partner() {
+            Some(partner) => Some(
+                self.atom_position(&partner)
+                    .ok_or(molecule::Error::AtomMissing(partner))
+                    .context("while turning orbital")?,
+            ),
+            None => None,
+        };
+
+        if let Some(orbital) = self.orbitals.get_mut(orbital_id) {
+            orbital.turn(start, end, position);
+        }
+
+        Ok(())
+    }
+
+    /// the orbital nearest a point, if any is within the tolerance of its lobes
+    pub fn orbital_at(&self, position: Point, tolerance: f32) -> Option<OrbitalId> {
+        self.orbitals()
+            .map(|(orbital_id, orbital, start, end)| (orbital_id, orbital.distance_to(position, start, end)))
+            .filter(|(_, distance)| *distance <= tolerance)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(orbital_id, _)| *orbital_id)
+    }
+
+    /// ink on visible layers
+    pub fn inks(&self) -> impl Iterator<Item = (&InkId, &Ink)> {
+        self.inks.iter().filter(|(_, ink)| self.layers.get(&ink.layer()).is_some_and(Layer::is_visible))
+    }
+
+    /// the annotations layer, or the active layer when there isn't one
+    fn annotation_layer(&self) -> LayerId {
+        self.layers
+            .iter()
+            .find(|(_, layer)| layer.name() == Layer::ANNOTATIONS)
+            .map_or(self.active_layer, |(layer_id, _)| *layer_id)
+    }
+
+    /// whether the annotation layer is visible and unlocked, so ink and shapes can be drawn on it
+    pub fn can_annotate(&self) -> bool {
+        self.layers.get(&self.annotation_layer()).is_some_and(Layer::is_editable)
+    }
+
+    /// the annotation layer to draw on, which can't be hidden or locked
+    fn editable_annotation_layer(&self) -> Result<LayerId> {
+        let layer_id = self.annotation_layer();
+        match self.can_annotate() {
+            true => Ok(layer_id),
+            false => Err(molecule::Error::LayerNotEditable(layer_id).into()),
+        }
+    }
+
+    /// draws a stroke on the annotation layer
+    pub fn add_ink(&mut self, points: &[Point], width: f32, color: InkColor) -> Result<InkId> {
+        let layer_id = self.editable_annotation_layer().context("while adding ink")?;
+
+        let ink_id = InkId::new();
+        self.inks.insert(ink_id, Ink::new(points, width, color, layer_id));
+
+        Ok(ink_id)
+    }
+
+    pub fn move_ink_to_layer(&mut self, ink_id: &InkId, layer_id: LayerId) -> Result<()> {
+        self.get_layer_mut(&layer_id).context("while moving ink to layer")?;
+        self.inks
+            .get_mut(ink_id)
+            .ok_or(molecule::Error::InkMissing(*ink_id))
+            .context("while moving ink to layer")?
+            .set_layer(layer_id);
+
+        Ok(())
+    }
+
+    pub fn delete_ink(&mut self, ink_id: &InkId) -> Result<()> {
+        self.inks
+            .shift_remove(ink_id)
+            .ok_or(molecule::Error::InkMissing(*ink_id))
+            .context("while deleting ink")?;
+
+        Ok(())
+    }
+
+    /// the stroke on an editable layer passing closest to a point, if any is within the tolerance
+    /// of its edge
+    pub fn ink_at(&self, position: Point, tolerance: f32) -> Option<InkId> {
+        self.inks
+            .iter()
+            .filter(|(_, ink)| self.layers.get(&ink.layer()).is_some_and(Layer::is_editable))
+            .map(|(ink_id, ink)| (ink_id, ink.distance_to(position) - ink.width() / 2.0))
+            .filter(|(_, distance)| *distance <= tolerance)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(ink_id, _)| *ink_id)
+    }
+
+    /// shapes on visible layers
+    pub fn shapes(&self) -> impl Iterator<Item = (&ShapeId, &Shape)> {
+        self.shapes.iter().filter(|(_, shape)| self.layers.get(&shape.layer()).is_some_and(Layer::is_visible))
+    }
+
+    /// a shape on a visible, unlocked layer
+    pub fn get_shape(&self, shape_id: &ShapeId) -> Option<&Shape> {
+        self.shapes
+            .get(shape_id)
+            .filter(|shape| self.layers.get(&shape.layer()).is_some_and(Layer::is_editable))
+    }
+
+    fn get_shape_mut(&mut self, shape_id: &ShapeId) -> Result<&mut Shape> {
+        self.shapes
+            .get_mut(shape_id)
+            .ok_or(molecule::Error::ShapeMissing(*shape_id))
+            .context("while getting shape")
+    }
+
+    /// draws a shape on the annotation layer
+    pub fn add_shape(&mut self, kind: ShapeKind, start: Point, end: Point, style: ShapeStyle) -> Result<ShapeId> {
+        let layer_id = self.editable_annotation_layer().context("while adding shape")?;
+
+        let shape_id = ShapeId::new();
+        self.shapes.insert(shape_id, Shape::new(kind, start, end, style, layer_id));
+
+        Ok(shape_id)
+    }
+
+    /// adds a copy of a shape from another document on the annotation layer, scaled about a point
+    /// and then moved
+    pub fn paste_shape(&mut self, copied: &Shape, center: Point, factor: f32, offset: Vector) -> Result<ShapeId> {
+        let layer_id = self.editable_annotation_layer().context("while pasting shape")?;
+
+        let mut shape = copied.clone();
+        shape.transform(center, factor, offset);
+        shape.set_layer(layer_id);
+
+        let shape_id = ShapeId::new();
+        self.shapes.insert(shape_id, shape);
+
+        Ok(shape_id)
+    }
+
+    pub fn move_shape_to_layer(&mut self, shape_id: &ShapeId, layer_id: LayerId) -> Result<()> {
+        self.get_layer_mut(&layer_id).context("while moving shape to layer")?;
+        self.get_shape_mut(shape_id).context("while moving shape to layer")?.set_layer(layer_id);
+
+        Ok(())
+    }
+
+    pub fn delete_shape(&mut self, shape_id: &ShapeId) -> Result<()> {
+        self.shapes
+            .shift_remove(shape_id)
+            .ok_or(molecule::Error::ShapeMissing(*shape_id))
+            .context("while deleting shape")?;
+
+        Ok(())
+    }
+
+    pub fn set_shape_style(&mut self, shape_id: &ShapeId, style: ShapeStyle) -> Result<()> {
+        self.get_shape_mut(shape_id).context("while setting shape style")?.set_style(style);
+
+        Ok(())
+    }
+
+    pub fn set_reaction_conditions(&mut self, shape_id: &ShapeId, conditions: ReactionConditions) -> Result<()> {
+        self.get_shape_mut(shape_id)
+            .context("while setting reaction conditions")?
+            .set_conditions(conditions);
+
+        Ok(())
+    }
+
+    pub fn set_arrow_text(&mut self, shape_id: &ShapeId, side: ArrowSide, text: String) -> Result<()> {
+        self.get_shape_mut(shape_id).context("while setting arrow text")?.set_text(side, text);
+
+        Ok(())
+    }
+
+    pub fn lengthen_shape(&mut self, shape_id: &ShapeId, length: f32) -> Result<()> {
+        self.get_shape_mut(shape_id).context("while lengthening shape")?.lengthen_to(length);
+
+        Ok(())
+    }
+
+    pub fn drag_shape(&mut self, shape_id: &ShapeId, drag: ShapeDrag, last: Point, position: Point) -> Result<()> {
+        self.get_shape_mut(shape_id).context("while dragging shape")?.drag(drag, last, position);
+
+        Ok(())
+    }
+
+    /// the topmost shape on an editable layer within the tolerance of a point, inside filled ones
+    /// counting as on them
+    pub fn shape_at(&self, position: Point, tolerance: f32) -> Option<ShapeId> {
+        self.shapes
+            .iter()
+            .rev()
+            .filter(|(_, shape)| self.layers.get(&shape.layer()).is_some_and(Layer::is_editable))
+            .find(|(_, shape)| shape.distance_to(position) <= tolerance)
+            .map(|(shape_id, _)| *shape_id)
+    }
+
+    /// text on visible layers
+    pub fn annotations(&self) -> impl Iterator<Item = (&AnnotationId, &Annotation)> {
+        self.annotations
+            .iter()
+            .filter(|(_, annotation)| self.layers.get(&annotation.layer()).is_some_and(Layer::is_visible))
+    }
+
+    /// text on a visible, unlocked layer
+    pub fn get_annotation(&self, annotation_id: &AnnotationId) -> Option<&Annotation> {
+        self.annotations
+            .get(annotation_id)
+            .filter(|annotation| self.layers.get(&annotation.layer()).is_some_and(Layer::is_editable))
+    }
+
+    fn get_annotation_mut(&mut self, annotation_id: &AnnotationId) -> Result<&mut Annotation> {
+        self.annotations
+            .get_mut(annotation_id)
+            .ok_or(molecule::Error::AnnotationMissing(*annotation_id))
+            .context("while getting annotation")
+    }
+
+    /// writes text on the annotation layer, in the document's label font
+    pub fn add_annotation(&mut self, text: String, position: Point) -> Result<AnnotationId> {
+        let layer_id = self.editable_annotation_layer().context("while adding annotation")?;
+
+        let annotation_id = AnnotationId::new();
+        let annotation = Annotation::new(text, position, self.style.label_font.font(), layer_id);
+        self.annotations.insert(annotation_id, annotation);
+
+        Ok(annotation_id)
+    }
+
+    /// adds a copy of text from another document on the annotation layer, in the document's label
+    /// font, with its position scaled about a point and then moved
+    pub fn paste_annotation(&mut self, copied: &Annotation, center: Point, factor: f32, offset: Vector) -> Result<AnnotationId> {
+        let layer_id = self.editable_annotation_layer().context("while pasting annotation")?;
+
+        let mut annotation = copied.clone();
+        annotation.set_position(center + (copied.position() - center) * factor + offset);
+        annotation.set_font(self.style.label_font.font());
+        annotation.set_layer(layer_id);
+
+        let annotation_id = AnnotationId::new();
+        self.annotations.insert(annotation_id, annotation);
+
+        Ok(annotation_id)
+    }
+
+    pub fn set_annotation_text(&mut self, annotation_id: &AnnotationId, text: String) -> Result<()> {
+        let font = self.style.label_font.font();
+        self.get_annotation_mut(annotation_id)
+            .context("while setting annotation text")?
+            .set_text(text, font);
+
+        Ok(())
+    }
+
+    pub fn move_annotation(&mut self, annotation_id: &AnnotationId, position: Point) -> Result<()> {
+        self.get_annotation_mut(annotation_id)
+            .context("while moving annotation")?
+            .set_position(position);
+
+        Ok(())
+    }
+
+    pub fn move_annotation_to_layer(&mut self, annotation_id: &AnnotationId, layer_id: LayerId) -> Result<()> {
+        self.get_layer_mut(&layer_id).context("while moving annotation to layer")?;
+        self.get_annotation_mut(annotation_id)
+            .context("while moving annotation to layer")?
+            .set_layer(layer_id);
+
+        Ok(())
+    }
+
+    pub fn delete_annotation(&mut self, annotation_id: &AnnotationId) -> Result<()> {
+        self.annotations
+            .shift_remove(annotation_id)
+            .ok_or(molecule::Error::AnnotationMissing(*annotation_id))
+            .context("while deleting annotation")?;
+
+        Ok(())
+    }
 
-use super::selection::HoverSelection;
-use super::selection::SingleSelection;
-use super::Selection;
+    /// the topmost text on an editable layer within the tolerance of a point
+    pub fn annotation_at(&self, position: Point, tolerance: f32) -> Option<AnnotationId> {
+        self.annotations
+            .iter()
+            .rev()
+            .filter(|(_, annotation)| self.layers.get(&annotation.layer()).is_some_and(Layer::is_editable))
+            .find(|(_, annotation)| annotation.distance_to(position) <= tolerance)
+            .map(|(annotation_id, _)| *annotation_id)
+    }
 
-#[derive(Default, Debug)]
-pub struct State {
-    molecules: FxHashMap<MoleculeId, Molecule>,
-    selection: Selection,
-}
+    /// drops interactions and orbitals with an atom that no longer exists
+    fn prune_interactions(&mut self) {
+        let molecules = &self.molecules;
+        let exists = |atom_id: AtomId| molecules.values().any(|molecule| molecule.get_atom(&atom_id).is_ok());
+        self.interactions.retain(|_, interaction| interaction.atom_ids().all(exists));
+        self.orbitals.retain(|_, orbital| orbital.atom_ids().all(exists));
+    }
 
-impl State {
-    // pub fn add_molecule(&mut self, molecule_id: MoleculeId, position: Point) {
-    //     self.molecules
-    //         .insert(molecule_id, Molecule::new(position));
-    // }
+    /// relabels selected atoms labelled `find` as `replace`, or every editable atom when
+    /// `selection_only` is false, returning how many labels changed
+    pub fn replace_labels(&mut self, find: &str, replace: &str, selection_only: bool) -> Result<usize> {
+        if find.is_empty() {
+            return Ok(0);
+        }
 
-    pub fn add_molecule_with_atom(&mut self, molecule_id: MoleculeId, atom_id: AtomId, label: String, position: Point) -> Result<()> {
-        let molecule = Molecule::new(position, atom_id, label).context("while adding molecule with atoms")?;
-        if self.molecules.insert(molecule_id, molecule).is_some() {
-            return Err(molecule::Error::MoleculeCollision(molecule_id)).context("while adding molecule with atoms")
+        let atoms: Vec<(MoleculeId, AtomId)> = if selection_only {
+            self.selection
+                .iter()
+                .map(|item| match *item {
+                    SingleSelection::Molecule(molecule_id) => self
+                        .get_molecule(&molecule_id)
+                        .map(|molecule| molecule.atoms().map(|(atom_id, _)| (molecule_id, *atom_id)).collect()),
+                    SingleSelection::Atom(molecule_id, atom_id) => Ok(vec![(molecule_id, atom_id)]),
+                    SingleSelection::Bond(molecule_id, bond_id) => self
+                        .get_bond(&molecule_id, &bond_id)
+                        .map(|bond| bond.atom_ids().map(|atom_id| (molecule_id, atom_id)).collect()),
+                })
+                .collect::<Result<Vec<Vec<_>>>>()
+                .context("while replacing labels")?
+                .into_iter()
+                .flatten()
+                .collect()
+        } else {
+            self.editable_molecules()
+                .flat_map(|(molecule_id, molecule)| molecule.atoms().map(|(atom_id, _)| (*molecule_id, *atom_id)))
+                .collect()
         };
+
+        let mut replaced = 0;
+        for (molecule_id, atom_id) in atoms {
+            let molecule = self.get_molecule_mut(&molecule_id).context("while replacing labels")?;
+            let label = molecule.get_atom(&atom_id).context("while replacing labels")?.label();
+
+            // only whole labels are replaced, so replacing C with Cl leaves Cl as it is
+            if label == find {
+                molecule
+                    .rename_atom(&atom_id, replace.to_string())
+                    .context("while replacing labels")?;
+                replaced += 1;
+            }
+        }
+
+        Ok(replaced)
+    }
+
+    pub fn style(&self) -> &DocumentStyle {
+        &self.style
+    }
+
+    pub fn style_mut(&mut self) -> &mut DocumentStyle {
+        &mut self.style
+    }
+
+    /// adds a linked copy of a molecule, moved aside from it
+    pub fn add_instance(&mut self, master_id: &MoleculeId, offset: Vector) -> Result<MoleculeId> {
+        let master = self.get_molecule(master_id).context("while adding instance")?;
+        let mut instance = Instance::new(master.clone());
+        let mut molecule = master.clone();
+        molecule.move_molecule(offset);
+        molecule
+            .copy_structure(master, &mut instance.atoms, &mut instance.bonds)
+            .context("while adding instance")?;
+
+        let molecule_id = MoleculeId::new();
+        self.molecules.insert(molecule_id, molecule);
+        self.instances.push(instance);
+
+        Ok(molecule_id)
+    }
+
+    pub fn has_instances(&self) -> bool {
+        !self.instances.is_empty()
+    }
+
+    pub fn is_instance(&self, molecule_id: &MoleculeId) -> bool {
+        self.instances.iter().any(|instance| self.instance_id(instance) == Some(*molecule_id))
+    }
+
+    /// leaves a linked copy as a molecule of its own, which no longer follows its master
+    pub fn unlink_instance(&mut self, molecule_id: &MoleculeId) {
+        let instances = std::mem::take(&mut self.instances);
+        self.instances = instances
+            .into_iter()
+            .filter(|instance| self.instance_id(instance) != Some(*molecule_id))
+            .collect();
+    }
+
+    /// copies the atoms and bonds of each master that was edited to its instances again, unlinking
+    /// instances whose master is gone or that were edited themselves. returns whether any molecule
+    /// changed
+    pub fn update_instances(&mut self) -> Result<bool> {
+        let instances = std::mem::take(&mut self.instances);
+        let mut changed = false;
+
+        for mut instance in instances {
+            let master_id = instance.atoms.keys().find_map(|atom_id| self.molecule_with_atom(atom_id));
+            let (Some(master_id), Some(instance_id)) = (master_id, self.instance_id(&instance)) else {
+                continue;
+            };
+            let molecule = self.get_molecule(&instance_id).context("while updating instances")?;
+            let unedited = instance.master.same_structure(
+                molecule,
+                |atom_id| instance.atoms.get(atom_id).copied(),
+                |bond_id| instance.bonds.get(bond_id).copied(),
+            );
+            if master_id == instance_id || !unedited {
+                continue;
+            }
+
+            let master = self.get_molecule(&master_id).context("while updating instances")?;
+            if !master.same_structure(&instance.master, |atom_id| Some(*atom_id), |bond_id| Some(*bond_id)) {
+                let master = master.clone();
+                self.get_molecule_mut(&instance_id)
+                    .context("while updating instances")?
+                    .copy_structure(&master, &mut instance.atoms, &mut instance.bonds)
+                    .context("while updating instances")?;
+                instance.master = master;
+                changed = true;
+            }
+            self.instances.push(instance);
+        }
+
+        Ok(changed)
+    }
+
+    fn instance_id(&self, instance: &Instance) -> Option<MoleculeId> {
+        instance.atoms.values().find_map(|atom_id| self.molecule_with_atom(atom_id))
+    }
+
+    /// molecule an atom belongs to, whether or not it's visible
+    fn molecule_with_atom(&self, atom_id: &AtomId) -> Option<MoleculeId> {
+        self.molecules
+            .iter()
+            .find(|(_, molecule)| molecule.get_atom(atom_id).is_ok())
+            .map(|(molecule_id, _)| *molecule_id)
+    }
+
+    pub fn tag_filter(&self) -> &TagFilter {
+        &self.tag_filter
+    }
+
+    pub fn filter_tag(&mut self, tag: String, selected: bool) {
+        // selected items may be hidden
+        self.selection.clear();
+        self.tag_filter.set_tag(tag, selected);
+    }
+
+    pub fn set_hide_unmatched(&mut self, hide: bool) {
+        self.selection.clear();
+        self.tag_filter.set_hide(hide);
+    }
+
+    /// tags given to any molecule and those picked in the filter, in alphabetical order
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags = self
+            .molecules
+            .values()
+            .flat_map(|molecule| molecule.metadata().tags())
+            .chain(self.tag_filter.tags())
+            .cloned()
+            .collect::<Vec<_>>();
+        tags.sort();
+        tags.dedup();
+
+        tags
+    }
+
+    pub fn set_label_font(&mut self, label_font: LabelFont) -> Result<()> {
+        self.style.label_font = label_font;
+
+        for molecule in self.molecules.values_mut() {
+            molecule.set_label_font(label_font.font()).context("while setting label font")?;
+        }
+        for annotation in self.annotations.values_mut() {
+            annotation.set_font(label_font.font());
+        }
+
         Ok(())
     }
 
-    pub fn molecules(&self) -> impl Iterator<Item = (&MoleculeId, &Molecule)> {
-        self.molecules.iter()
+    /// changes the document bond length, scaling every molecule around the origin so the drawing
+    /// keeps its proportions
+    pub fn set_bond_length(&mut self, length: f32) -> Result<()> {
+        let factor = length / self.style.bond_length;
+        self.style.bond_length = length;
+
+        for molecule in self.molecules.values_mut() {
+            molecule.scale(Point::ORIGIN, factor).context("while setting document bond length")?;
+        }
+
+        Ok(())
+    }
+
+    /// rewrites atom labels in whichever direction collides least with the labels and bonds around them
+    pub fn resolve_overlaps(&mut self) -> Result<()> {
+        for _ in 0..Self::RESOLVE_PASSES {
+            let mut labels = self.label_rectangles();
+            let bonds = self.bond_segments().context("while resolving overlaps")?;
+            let mut changed = false;
+
+            for i in 0..labels.len() {
+                let (molecule_id, atom_id, rectangle) = labels[i];
+                let cost = |rectangle: &Rectangle| {
+                    let label_cost: f32 = labels
+                        .iter()
+                        .enumerate()
+                        .filter(|(j, _)| *j != i)
+                        .filter_map(|(_, (_, _, other))| rectangle.intersection(other))
+                        .map(|overlap| overlap.area())
+                        .sum();
+                    let bond_cost = bonds
+                        .iter()
+                        .filter(|(bond_molecule_id, atom_ids, ..)| {
+                            !(*bond_molecule_id == molecule_id && atom_ids.contains(&atom_id))
+                        })
+                        .filter(|(_, _, start, end)| segment_crosses_rectangle(*start, *end, rectangle))
+                        .count() as f32
+                        * Self::BOND_OVERLAP_COST;
+
+                    label_cost + bond_cost
+                };
+
+                let current_cost = cost(&rectangle);
+                if current_cost == 0.0 {
+                    continue;
+                }
+
+                let molecule = self.get_molecule(&molecule_id).context("while resolving overlaps")?;
+                if !self.is_editable(molecule) {
+                    continue;
+                }
+
+                let offset: Vector = molecule.position().into();
+                let atom = molecule.get_atom(&atom_id).context("while resolving overlaps")?;
+
+                let best = Direction::ALL
+                    .into_iter()
+                    .filter_map(|direction| {
+                        let rectangle = atom.label_bounds_towards(direction)? + offset;
+                        Some((direction, rectangle, cost(&rectangle)))
+                    })
+                    .min_by(|a, b| a.2.total_cmp(&b.2));
+
+                if let Some((direction, rectangle, best_cost)) = best {
+                    if best_cost < current_cost {
+                        self.get_molecule_mut(&molecule_id)
+                            .and_then(|molecule| molecule.set_label_direction(&atom_id, direction))
+                            .context("while resolving overlaps")?;
+                        labels[i].2 = rectangle;
+                        changed = true;
+                    }
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// canvas space bounds of every non-empty atom label
+    fn label_rectangles(&self) -> Vec<(MoleculeId, AtomId, Rectangle)> {
+        let mut labels = vec![];
+
+        for (molecule_id, molecule) in self.molecules() {
+            let offset: Vector = molecule.position().into();
+            for (atom_id, atom) in molecule.atoms() {
+                if let Some(rectangle) = atom.label_bounds() {
+                    labels.push((*molecule_id, *atom_id, rectangle + offset));
+                }
+            }
+        }
+
+        labels
+    }
+
+    /// canvas space segments of every bond, one to each piece of a bent bond, trimmed to the edges
+    /// of the labels they join
+    fn bond_segments(&self) -> Result<Vec<BondSegment>> {
+        let mut segments = vec![];
+
+        for (molecule_id, molecule) in self.molecules() {
+            for (bond_id, bond) in molecule.bonds() {
+                let path = molecule.bond_path(bond_id).context("while getting bond segments")?;
+
+                segments.extend(path.windows(2).map(|pair| (*molecule_id, [bond.start(), bond.end()], pair[0], pair[1])));
+            }
+        }
+
+        Ok(segments)
     }
 
     pub fn selection(&self) -> &Selection {
@@ -50,33 +1206,147 @@ impl State {
         self.selection = selection;
     }
 
-    pub fn move_selection(&mut self, translation: Vector) -> Result<()> {
-        for item in self.selection.clone() {
-            match item {
+    /// the selection as whole molecules and the atoms picked out of the others, with every atom
+    /// appearing once however many selected items share it
+    fn selected_atoms(&self) -> Result<SelectedAtoms> {
+        let mut whole = vec![];
+        let mut partial: FxIndexMap<MoleculeId, Vec<AtomId>> = FxIndexMap::default();
+        for item in self.selection.iter() {
+            let (molecule_id, atom_ids) = match *item {
                 SingleSelection::Molecule(molecule_id) => {
-                    let molecule = self.get_molecule_mut(&molecule_id).context("while moving selection")?;
-                    molecule.move_molecule(translation);
-                }
-                SingleSelection::Atom(molecule_id, atom_id) => {
-                    let molecule = self.get_molecule_mut(&molecule_id).context("while moving selection")?;
-                    molecule.move_atom(&atom_id, translation).context("while moving selection")?;
+                    if !whole.contains(&molecule_id) {
+                        whole.push(molecule_id);
+                    }
+                    continue;
                 }
+                SingleSelection::Atom(molecule_id, atom_id) => (molecule_id, vec![atom_id]),
                 SingleSelection::Bond(molecule_id, bond_id) => {
-                    let molecule = self.get_molecule_mut(&molecule_id).context("while moving selection")?;
-                    molecule.move_bond(&bond_id, translation).context("while moving selection")?;
+                    let bond = self.get_bond(&molecule_id, &bond_id).context("while getting selected atoms")?;
+                    (molecule_id, bond.atom_ids().collect())
+                }
+            };
+
+            let selected = partial.entry(molecule_id).or_default();
+            for atom_id in atom_ids {
+                if !selected.contains(&atom_id) {
+                    selected.push(atom_id);
                 }
             }
         }
+        // atoms of selected molecules go along with the rest of their molecule
+        partial.retain(|molecule_id, _| !whole.contains(molecule_id));
+
+        Ok((whole, partial))
+    }
+
+    pub fn move_selection(&mut self, translation: Vector) -> Result<()> {
+        let (whole, partial) = self.selected_atoms().context("while moving selection")?;
+
+        for molecule_id in whole {
+            let molecule = self.get_molecule_mut(&molecule_id).context("while moving selection")?;
+            molecule.move_molecule(translation);
+        }
+        for (molecule_id, atom_ids) in partial {
+            let molecule = self.get_molecule_mut(&molecule_id).context("while moving selection")?;
+            molecule.move_atoms(&atom_ids, translation).context("while moving selection")?;
+        }
 
         Ok(())
     }
 
-    pub fn molecules_at(&self, position: Point) -> impl Iterator<Item = (&MoleculeId, &Molecule, Bounds)> {
-        self.molecules
-            .iter()
+    /// the point nearest `center`, on a grid `step` apart, with room for an area of `size` around it
+    /// that no visible molecule covers, or `center` when none is found nearby
+    pub fn free_position(&self, size: Size, center: Point, step: f32) -> Point {
+        let occupied = self.molecules().map(|(_, molecule)| molecule.outer_rectangle()).collect::<Vec<_>>();
+        let is_free = |point: Point| {
+            let area = Rectangle::new(point - Vector::new(size.width, size.height) * 0.5, size);
+            !occupied.iter().any(|rectangle| rectangle.intersects(&area))
+        };
+
+        for ring in 0..=Self::FREE_SPACE_STEPS {
+            let mut candidates = (-ring..=ring)
+                .flat_map(|x| (-ring..=ring).map(move |y| (x, y)))
+                .filter(|(x, y)| x.abs().max(y.abs()) == ring)
+                .map(|(x, y)| center + Vector::new(x as f32, y as f32) * step)
+                .collect::<Vec<_>>();
+            candidates.sort_by(|a, b| a.distance(center).total_cmp(&b.distance(center)));
+
+            if let Some(point) = candidates.into_iter().find(|point| is_free(*point)) {
+                return point;
+            }
+        }
+
+        center
+    }
+
+    /// lines up the molecules with anything selected, or every editable molecule when nothing is,
+    /// in the order they were added, from the top left corner of where they were. `captions` are
+    /// the areas of the text under molecules, which is kept clear of the next row
+    pub fn arrange(&mut self, arrangement: Arrangement, spacing: f32, captions: &FxHashMap<MoleculeId, Rectangle>) -> Result<()> {
+        let selected = self.selection.iter().map(SingleSelection::molecule_id).collect::<Vec<_>>();
+        let molecules = self
+            .editable_molecules()
+            .filter(|(molecule_id, _)| selected.is_empty() || selected.contains(molecule_id))
+            .map(|(molecule_id, molecule)| {
+                let rectangle = molecule.outer_rectangle();
+                let rectangle = captions.get(molecule_id).map_or(rectangle, |caption| rectangle.union(caption));
+                (*molecule_id, rectangle)
+            })
+            .collect::<Vec<_>>();
+        let Some(region) = molecules.iter().map(|(_, rectangle)| *rectangle).reduce(|a, b| a.union(&b)) else {
+            return Ok(());
+        };
+
+        let rectangles = molecules.iter().map(|(_, rectangle)| *rectangle).collect::<Vec<_>>();
+        let offsets = arrangement.offsets(&rectangles, region.position(), spacing);
+        for ((molecule_id, _), offset) in molecules.into_iter().zip(offsets) {
+            self.get_molecule_mut(&molecule_id).context("while arranging molecules")?.move_molecule(offset);
+        }
+
+        Ok(())
+    }
+
+    /// scales the selection by `factor` around the middle of its atoms, keeping labels their size
+    pub fn scale_selection(&mut self, factor: f32) -> Result<()> {
+        let (whole, partial) = self.selected_atoms().context("while scaling selection")?;
+
+        let mut positions = vec![];
+        for molecule_id in &whole {
+            let molecule = self.get_molecule(molecule_id).context("while scaling selection")?;
+            for (atom_id, _) in molecule.atoms() {
+                positions.push(molecule.atom_position(atom_id).context("while scaling selection")?);
+            }
+        }
+        for (molecule_id, atom_ids) in &partial {
+            let molecule = self.get_molecule(molecule_id).context("while scaling selection")?;
+            for atom_id in atom_ids {
+                positions.push(molecule.atom_position(atom_id).context("while scaling selection")?);
+            }
+        }
+        if positions.is_empty() {
+            return Ok(());
+        }
+
+        let sum = positions.iter().fold(Vector::ZERO, |sum, position| sum + Vector::new(position.x, position.y));
+        let center = Point::ORIGIN + sum * (1.0 / positions.len() as f32);
+
+        for molecule_id in &whole {
+            let molecule = self.get_molecule_mut(molecule_id).context("while scaling selection")?;
+            molecule.scale(center, factor).context("while scaling selection")?;
+        }
+        for (molecule_id, atom_ids) in partial {
+            let molecule = self.get_molecule_mut(&molecule_id).context("while scaling selection")?;
+            molecule.scale_atoms(&atom_ids, center, factor).context("while scaling selection")?;
+        }
+
+        Ok(())
+    }
+
+    pub fn molecules_at(&self, position: Point, tolerance: f32) -> impl Iterator<Item = (&MoleculeId, &Molecule, Bounds)> {
+        self.editable_molecules()
             .filter_map(move |(molecule_id, molecule)| {
                 let bounds = molecule.bounds();
-                if bounds.contains(position) {
+                if bounds.contains_within(position, tolerance) {
                     Some((molecule_id, molecule, bounds))
                 } else {
                     None
@@ -106,12 +1376,99 @@ impl State {
 
     pub fn remove_molecule(&mut self, molecule_id: &MoleculeId) -> Result<Molecule> {
         self.selection.clear();
-        self.molecules.remove(molecule_id)
+        self.molecules.shift_remove(molecule_id)
             .ok_or(molecule::Error::MoleculeMissing(*molecule_id)).context("while removing molecule")
     }
 
+    /// removes a molecule along with its interactions, unlike `remove_molecule` which leaves them
+    /// for molecules being merged
+    pub fn delete_molecule(&mut self, molecule_id: &MoleculeId) -> Result<()> {
+        self.remove_molecule(molecule_id).context("while deleting molecule")?;
+        self.prune_interactions();
+
+        Ok(())
+    }
+
+    /// adds the fragments split off a molecule under ids derived from it, so that splitting it the
+    /// same way again after an undo gives them the same ids as before
+    fn insert_fragments(&mut self, molecule_id: MoleculeId, fragments: impl IntoIterator<Item = Molecule>) {
+        for fragment in fragments {
+            let fragment_id = match fragment.atoms().next() {
+                Some((atom_id, _)) => MoleculeId::fragment(molecule_id, *atom_id),
+                None => MoleculeId::new(),
+            };
+            self.molecules.insert(fragment_id, fragment);
+        }
+    }
+
+    /// the selection by what it holds, to be found again with `reselect` once molecules have been
+    /// split or merged
+    fn selected(&self) -> Vec<Selected> {
+        self.selection
+            .iter()
+            .filter_map(|item| match *item {
+                SingleSelection::Molecule(molecule_id) => self
+                    .molecules
+                    .get(&molecule_id)
+                    .map(|molecule| Selected::Molecule(molecule.atoms().map(|(atom_id, _)| *atom_id).collect())),
+                SingleSelection::Atom(_, atom_id) => Some(Selected::Atom(atom_id)),
+                SingleSelection::Bond(_, bond_id) => Some(Selected::Bond(bond_id)),
+            })
+            .collect()
+    }
+
+    /// selects again what was selected under the ids of the molecules now holding it, a molecule
+    /// split apart is selected as all its fragments and deleted items are left out
+    fn reselect(&mut self, selected: Vec<Selected>) {
+        let mut selection = vec![];
+        for item in selected {
+            for (molecule_id, molecule) in &self.molecules {
+                let found = match &item {
+                    Selected::Molecule(atom_ids) => atom_ids
+                        .iter()
+                        .any(|atom_id| molecule.get_atom(atom_id).is_ok())
+                        .then_some(SingleSelection::Molecule(*molecule_id)),
+                    Selected::Atom(atom_id) => molecule
+                        .get_atom(atom_id)
+                        .is_ok()
+                        .then_some(SingleSelection::Atom(*molecule_id, *atom_id)),
+                    Selected::Bond(bond_id) => molecule
+                        .get_bond(bond_id)
+                        .is_ok()
+                        .then_some(SingleSelection::Bond(*molecule_id, *bond_id)),
+                };
+
+                if let Some(found) = found.filter(|found| !selection.contains(found)) {
+                    selection.push(found);
+                }
+            }
+        }
+
+        self.selection = Selection::from_iter(selection);
+    }
+
+    /// bonds an atom of one molecule to an atom of another, merging them into the first
+    pub fn connect_molecules(
+        &mut self,
+        molecule_id: &MoleculeId,
+        atom_id: AtomId,
+        other_molecule_id: &MoleculeId,
+        other_atom_id: AtomId,
+        bond_type: BondType,
+    ) -> Result<()> {
+        let selected = self.selected();
+        let other = self.remove_molecule(other_molecule_id).context("while connecting molecules")?;
+        let molecule = self.get_molecule_mut(molecule_id).context("while connecting molecules")?;
+
+        molecule.extend(other);
+        molecule.add_bond(atom_id, other_atom_id, bond_type).context("while connecting molecules")?;
+        self.reselect(selected);
+
+        Ok(())
+    }
+
     pub fn delete_atom(&mut self, molecule_id: &MoleculeId, atom_id: AtomId) -> Result<()> {
-        self.selection.clear();
+        let selected = self.selected();
         let molecule = self.get_molecule_mut(molecule_id).context("while deleting atom")?;
         let detached_molecules = molecule.delete_atom(atom_id).context("while delting atom")?;
 
@@ -119,74 +1476,199 @@ impl State {
             self.remove_molecule(molecule_id)?;
         }
 
-        for molecule in detached_molecules {
-            self.molecules.insert(MoleculeId::new(), molecule).context("while inserting detached molecules")
-                .context("while deleting atom")?;
-        }
+        self.insert_fragments(*molecule_id, detached_molecules);
+        self.prune_interactions();
+        self.reselect(selected);
 
         Ok(())
     }
 
     pub fn delete_bond(&mut self, molecule_id: &MoleculeId, bond_id: BondId) -> Result<()> {
-        self.selection.clear();
+        let selected = self.selected();
         let molecule = self.get_molecule_mut(molecule_id)?;
         let detached_molecules = molecule.delete_bond(bond_id)?;
 
-        for molecule in detached_molecules {
-            self.molecules.insert(MoleculeId::new(), molecule);
+        self.insert_fragments(*molecule_id, detached_molecules);
+        self.reselect(selected);
+
+        Ok(())
+    }
+
+    /// end points of the selected bond when the selection is exactly one bond
+    pub fn bond_handles(&self) -> Result<Option<BondHandles>> {
+        let Some(SingleSelection::Bond(molecule_id, bond_id)) = self.selection.only() else {
+            return Ok(None);
+        };
+
+        let molecule = self.get_molecule(&molecule_id).context("while getting bond handles")?;
+        let bond = molecule.get_bond(&bond_id).context("while getting bond handles")?;
+
+        let handles = [BondEnd::Start, BondEnd::End]
+            .map(|end| molecule.atom_position(&bond.atom(end)).map(|position| (end, position)));
+        let [start, end] = handles;
+
+        Ok(Some((molecule_id, bond_id, [start?, end?])))
+    }
+
+    pub fn bond_handle_at(&self, position: Point, radius: f32) -> Result<Option<(MoleculeId, BondId, BondEnd)>> {
+        let Some((molecule_id, bond_id, handles)) = self.bond_handles()? else {
+            return Ok(None);
+        };
+
+        Ok(handles
+            .into_iter()
+            .find(|(_end, handle)| handle.distance(position) < radius)
+            .map(|(end, _handle)| (molecule_id, bond_id, end)))
+    }
+
+    /// where the selected bond's bend is dragged from when the selection is exactly one bond
+    pub fn bend_handle(&self) -> Result<Option<(MoleculeId, BondId, Point)>> {
+        let Some(SingleSelection::Bond(molecule_id, bond_id)) = self.selection.only() else {
+            return Ok(None);
+        };
+
+        let molecule = self.get_molecule(&molecule_id).context("while getting bend handle")?;
+        let handle = molecule.bend_handle(&bond_id).context("while getting bend handle")?;
+
+        Ok(Some((molecule_id, bond_id, handle)))
+    }
+
+    pub fn bend_handle_at(&self, position: Point, radius: f32) -> Result<Option<(MoleculeId, BondId)>> {
+        Ok(self
+            .bend_handle()?
+            .filter(|(_, _, handle)| handle.distance(position) < radius)
+            .map(|(molecule_id, bond_id, _)| (molecule_id, bond_id)))
+    }
+
+    /// bends a bond through a position, or straightens it when the position is within the
+    /// tolerance of the line between its atoms' middle
+    pub fn bend_bond(&mut self, molecule_id: &MoleculeId, bond_id: &BondId, position: Point, tolerance: f32) -> Result<()> {
+        let molecule = self.get_molecule_mut(molecule_id).context("while bending bond")?;
+        let bond = molecule.get_bond(bond_id).context("while bending bond")?;
+        let start = molecule.atom_position(&bond.start()).context("while bending bond")?;
+        let end = molecule.atom_position(&bond.end()).context("while bending bond")?;
+
+        let straight = distance_to_segment(position, start, end) <= tolerance;
+        molecule
+            .bend_bond(bond_id, (!straight).then_some(position))
+            .context("while bending bond")
+    }
+
+    /// moves one end of a bond onto an atom, merging the molecules if they differ
+    pub fn reattach_bond(
+        &mut self,
+        molecule_id: &MoleculeId,
+        bond_id: &BondId,
+        end: BondEnd,
+        target_molecule_id: &MoleculeId,
+        atom_id: AtomId,
+    ) -> Result<()> {
+        let selected = self.selected();
+
+        if target_molecule_id != molecule_id {
+            let target = self.remove_molecule(target_molecule_id).context("while reattaching bond")?;
+            self.get_molecule_mut(molecule_id).context("while reattaching bond")?.extend(target);
         }
 
+        let molecule = self.get_molecule_mut(molecule_id).context("while reattaching bond")?;
+        let detached_molecules = molecule.reattach_bond(bond_id, end, atom_id).context("while reattaching bond")?;
+
+        self.insert_fragments(*molecule_id, detached_molecules);
+        self.reselect(selected);
+
         Ok(())
     }
 
-    /// prioritises closeness to center of bounding box
-    pub fn get_hovered(&self, canvas_position: Point) -> Result<HoverSelection> {
-        let mut selection_candidate: Option<(SingleSelection, Vector)> = None;
-        let mut candidate_rating = f32::MAX;
+    /// pulls one end of a bond off its atom onto a new atom at the position
+    pub fn detach_bond(&mut self, molecule_id: &MoleculeId, bond_id: &BondId, end: BondEnd, position: Point) -> Result<()> {
+        let atom_id = AtomId::new();
+        let molecule = self.get_molecule_mut(molecule_id).context("while detaching bond")?;
+        molecule.add_atom(atom_id, "".to_string(), position).context("while detaching bond")?;
 
-        for (molecule_id, molecule, bounds) in self.molecules_at(canvas_position) {
-            let rating = bounds.center().distance(canvas_position);
-            for (atom_id, _atom, bounds) in molecule.atoms_at(canvas_position) {
-                let rating = bounds.center().distance(canvas_position);
+        self.reattach_bond(molecule_id, bond_id, end, molecule_id, atom_id)
+            .context("while detaching bond")
+    }
 
-                if rating < candidate_rating {
-                    candidate_rating = rating;
-                    selection_candidate = Some((
-                            SingleSelection::Atom(*molecule_id, *atom_id),
-                            molecule.atom_position(atom_id).unwrap() - canvas_position
-                    ));
-                }
-            }
-            for (bond_id, _bond, bounds) in molecule.bonds_at(canvas_position).context("while getting hovered")? {
-                let rating = bounds.center().distance(canvas_position);
+    /// prioritises atoms over bonds over molecules, then atoms by closeness to their position,
+    /// bonds by distance to the line between their atoms and molecules by closeness to center of
+    /// bounding box, items count as hovered within the tolerance of their bounds, the depth steps
+    /// through the items at the position in that order
+    pub fn get_hovered(&self, canvas_position: Point, tolerance: f32, depth: usize) -> Result<HoverSelection> {
+        let mut atoms = vec![];
+        let mut bonds = vec![];
+        let mut molecules = vec![];
 
-                if rating < candidate_rating {
-                    candidate_rating = rating;
-                    selection_candidate = Some((
-                            SingleSelection::Bond(*molecule_id, *bond_id),
-                            molecule.bond_position(bond_id).unwrap() - canvas_position
-                    ));
-                }
+        for (molecule_id, molecule, bounds) in self.molecules_at(canvas_position, tolerance) {
+            for (atom_id, _atom, _bounds) in molecule.atoms_at(canvas_position, tolerance) {
+                let position = molecule.atom_position(atom_id).context("while getting hovered")?;
+                atoms.push((
+                    position.distance(canvas_position),
+                    SingleSelection::Atom(*molecule_id, *atom_id),
+                    position - canvas_position,
+                ));
             }
-
-            if rating < candidate_rating && matches!(selection_candidate, Some((SingleSelection::Molecule(_), _)) | None) {
-                candidate_rating = rating;
-                selection_candidate = Some((
-                        SingleSelection::Molecule(*molecule_id),
-                        <MoleculePosition as Into<Point>>::into(molecule.position()) - canvas_position
+            for (bond_id, _bond, _bounds) in molecule.bonds_at(canvas_position, tolerance).context("while getting hovered")? {
+                let distance = molecule
+                    .bond_points(bond_id)
+                    .context("while getting hovered")?
+                    .windows(2)
+                    .map(|pair| distance_to_segment(canvas_position, pair[0], pair[1]))
+                    .fold(f32::INFINITY, f32::min);
+                bonds.push((
+                    distance,
+                    SingleSelection::Bond(*molecule_id, *bond_id),
+                    molecule.bond_position(bond_id).unwrap() - canvas_position,
                 ));
             }
+
+            molecules.push((
+                bounds.center().distance(canvas_position),
+                SingleSelection::Molecule(*molecule_id),
+                <MoleculePosition as Into<Point>>::into(molecule.position()) - canvas_position,
+            ));
+        }
+
+        let mut candidates = vec![];
+        for mut group in [atoms, bonds, molecules] {
+            group.sort_by(|a, b| a.0.total_cmp(&b.0));
+            candidates.extend(group.into_iter().map(|(_, selection, offset)| (selection, offset)));
         }
 
-        Ok(HoverSelection::from(selection_candidate))
+        if candidates.is_empty() {
+            return Ok(HoverSelection::default());
+        }
+
+        let index = depth % candidates.len();
+        Ok(HoverSelection::from(Some(candidates.swap_remove(index))))
+    }
+
+    /// whether an atom or bond is under the position, rather than only the bounds of a molecule
+    pub fn item_at(&self, canvas_position: Point, tolerance: f32) -> Result<bool> {
+        let hovered = self.get_hovered(canvas_position, tolerance, 0).context("while getting item")?;
+
+        Ok(matches!(hovered.selection(), Some(SingleSelection::Atom(..) | SingleSelection::Bond(..))))
     }
 
-    pub fn get_selection(&self, rect: Rectangle) -> Result<Selection> {
+    /// items inside the rectangle, or when `touching` whole molecules with any atom touching it
+    pub fn get_selection(&self, rect: Rectangle, touching: bool) -> Result<Selection> {
         let mut selection = Vec::new();
 
-        for (molecule_id, molecule) in &self.molecules {
+        for (molecule_id, molecule) in self.editable_molecules() {
             let bounds = molecule.bounds();
-            if bounds.is_contained(&rect) {
+            if touching {
+                let mut touched = molecule
+                    .atoms()
+                    .any(|(_, atom)| (atom.bounds() + molecule.position().into()).intersects(&rect));
+                // a bond crossing the rectangle touches it even when both its atoms are outside
+                for (_, bond) in molecule.bonds() {
+                    let start = molecule.atom_position(&bond.start()).context("while getting selection")?;
+                    let end = molecule.atom_position(&bond.end()).context("while getting selection")?;
+                    touched |= segment_crosses_rectangle(start, end, &rect);
+                }
+                if touched {
+                    selection.push(SingleSelection::Molecule(*molecule_id));
+                }
+            } else if bounds.is_contained(&rect) {
                 selection.push(SingleSelection::Molecule(*molecule_id));
             } else if bounds.intersects(&rect) {
                 for (atom_id, atom) in molecule.atoms() {
@@ -202,3 +1684,151 @@ impl State {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn move_selection_moves_atoms_and_their_bonds_once() -> Result<()> {
+        let mut state = State::default();
+        let molecule_id = MoleculeId::new();
+        let atom_ids = [AtomId::new(), AtomId::new(), AtomId::new(), AtomId::new()];
+
+        state.add_molecule_with_atom(molecule_id, atom_ids[0], String::new(), Point::new(0.0, 0.0))?;
+        let molecule = state.get_molecule_mut(&molecule_id)?;
+        for (index, atom_id) in atom_ids.iter().enumerate().skip(1) {
+            molecule.add_atom(*atom_id, String::new(), Point::new(10.0 * index as f32, 0.0))?;
+            molecule.add_bond(atom_ids[index - 1], *atom_id, BondType::Normal(1))?;
+        }
+        let bond_id = |start: AtomId| {
+            molecule
+                .bonds()
+                .find(|(_, bond)| bond.start() == start)
+                .map(|(bond_id, _)| *bond_id)
+                .context("bond not found")
+        };
+
+        // the middle atom is selected along with both of its bonds, which take in the atoms on
+        // either side of it but not the last atom
+        let selection = Selection::from_iter([
+            SingleSelection::Atom(molecule_id, atom_ids[1]),
+            SingleSelection::Bond(molecule_id, bond_id(atom_ids[0])?),
+            SingleSelection::Bond(molecule_id, bond_id(atom_ids[1])?),
+        ]);
+        let before = atom_ids.map(|atom_id| molecule.atom_position(&atom_id));
+        state.new_selection(selection);
+
+        let delta = Vector::new(3.0, -4.0);
+        state.move_selection(delta)?;
+
+        let molecule = state.get_molecule(&molecule_id)?;
+        for (index, (atom_id, before)) in atom_ids.iter().zip(before).enumerate() {
+            let expected = match index {
+                3 => before?,
+                _ => before? + delta,
+            };
+            assert_eq!(molecule.atom_position(atom_id)?, expected);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn instances_follow_their_master_until_edited_themselves() -> Result<()> {
+        let mut state = State::default();
+        let master_id = MoleculeId::new();
+        let atom_id = AtomId::new();
+        state.add_molecule_with_atom(master_id, atom_id, "C".to_string(), Point::ORIGIN)?;
+        let instance_id = state.add_instance(&master_id, Vector::new(50.0, 0.0))?;
+
+        state.get_molecule_mut(&master_id)?.rename_atom(&atom_id, "N".to_string())?;
+        assert!(state.update_instances()?);
+        let labels = |state: &State, molecule_id| -> Result<Vec<String>> {
+            Ok(state.get_molecule(molecule_id)?.atoms().map(|(_, atom)| atom.label()).collect())
+        };
+        assert_eq!(labels(&state, &instance_id)?, ["N"]);
+
+        // renaming the instance's own atom unlinks it, rather than being overwritten by the master
+        let instance_atom_id = *state.get_molecule(&instance_id)?.atoms().next().context("atom not found")?.0;
+        state.get_molecule_mut(&instance_id)?.rename_atom(&instance_atom_id, "O".to_string())?;
+        assert!(!state.update_instances()?);
+        assert!(!state.is_instance(&instance_id));
+
+        state.get_molecule_mut(&master_id)?.rename_atom(&atom_id, "S".to_string())?;
+        assert!(!state.update_instances()?);
+        assert_eq!(labels(&state, &instance_id)?, ["O"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn pasted_molecules_get_ids_of_their_own() -> Result<()> {
+        let mut state = State::default();
+        let molecule_id = MoleculeId::new();
+        let atom_ids = [AtomId::new(), AtomId::new()];
+        state.add_molecule_with_atom(molecule_id, atom_ids[0], "C".to_string(), Point::ORIGIN)?;
+        let molecule = state.get_molecule_mut(&molecule_id)?;
+        molecule.add_atom(atom_ids[1], "O".to_string(), Point::new(10.0, 0.0))?;
+        molecule.add_bond(atom_ids[0], atom_ids[1], BondType::Normal(1))?;
+        let copied = state.get_molecule(&molecule_id)?.clone();
+
+        // pasting back into the document the molecule came from, twice, leaves no ids shared
+        let first = state.paste_molecule(&copied, Point::ORIGIN, 1.0, Vector::new(0.0, 20.0))?;
+        let second = state.paste_molecule(&copied, Point::ORIGIN, 2.0, Vector::new(0.0, 40.0))?;
+
+        let ids = |molecule_id| -> Result<(Vec<AtomId>, Vec<BondId>)> {
+            let molecule = state.get_molecule(molecule_id)?;
+            Ok((molecule.atoms().map(|(atom_id, _)| *atom_id).collect(), molecule.bonds().map(|(bond_id, _)| *bond_id).collect()))
+        };
+        let (atoms, bonds) = [molecule_id, first, second].iter().map(ids).collect::<Result<(Vec<_>, Vec<_>)>>()?;
+        assert_ne!(first, second);
+        assert_eq!(atoms.concat().into_iter().collect::<rustc_hash::FxHashSet<_>>().len(), 6);
+        assert_eq!(bonds.concat().into_iter().collect::<rustc_hash::FxHashSet<_>>().len(), 3);
+
+        // bonds join the pasted atoms rather than the originals, and the scaling carries over
+        let pasted = state.get_molecule(&second)?;
+        let (_, bond) = pasted.bonds().next().context("bond not found")?;
+        assert!(bond.atom_ids().all(|atom_id| pasted.get_atom(&atom_id).is_ok()));
+        let [start, end] = [bond.start(), bond.end()].map(|atom_id| pasted.atom_position(&atom_id));
+        assert_eq!(start?.distance(end?), 20.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn exported_projects_bring_their_shapes_and_text_along() -> Result<()> {
+        use crate::canvas::MolCanvas;
+
+        let mut document = MolCanvas::default();
+        document.state.add_molecule_with_atom(MoleculeId::new(), AtomId::new(), "C".to_string(), Point::ORIGIN)?;
+        let style = ShapeStyle { width: 2.0, color: InkColor::default(), filled: false };
+        let arrow_id = document.state.add_shape(ShapeKind::Arrow, Point::new(20.0, 0.0), Point::new(80.0, 0.0), style)?;
+        document.state.set_arrow_text(&arrow_id, ArrowSide::Above, "H2O".to_string())?;
+        document.state.add_annotation("quant.".to_string(), Point::new(0.0, 40.0))?;
+
+        let (svg, _, _) = document.image(None)?;
+        let path = std::env::temp_dir().join(format!("molecular-canvas-{}.svg", uuid::Uuid::new_v4()));
+        std::fs::write(&path, svg)?;
+        let project = crate::session::read_project(&path);
+        std::fs::remove_file(&path)?;
+        let project = project?.context("svg has no project")?;
+
+        // placed twice over, everything comes in again with where it is to the rest kept
+        let mut canvas = MolCanvas::default();
+        for _ in 0..2 {
+            canvas.place_project(project.clone(), Point::ORIGIN)?;
+        }
+        assert_eq!(canvas.state.molecules().count(), 2);
+        assert_eq!(canvas.state.annotations().filter(|(_, annotation)| annotation.text() == "quant.").count(), 2);
+        let arrows = canvas.state.shapes().map(|(_, shape)| shape).collect::<Vec<_>>();
+        assert_eq!(arrows.len(), 2);
+        assert!(arrows.iter().all(|arrow| arrow.text(ArrowSide::Above) == "H2O"));
+        for (_, molecule) in canvas.state.molecules() {
+            let atom = molecule.atoms().next().map(|(atom_id, _)| *atom_id).context("atom not found")?;
+            let position = molecule.atom_position(&atom)?;
+            assert!(arrows.iter().any(|arrow| arrow.start().distance(position + Vector::new(20.0, 0.0)) < 0.5));
+        }
+
+        Ok(())
+    }
+}
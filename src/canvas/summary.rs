@@ -0,0 +1,20 @@
+use iced::widget::svg::Handle;
+use rustc_hash::FxHashMap;
+
+use crate::molecule::{MoleculeId, ShapeId};
+
+/// what the inspector reads out about the whole document, worked out again only when the
+/// document changes rather than every time the inspector is drawn
+#[derive(Debug, Default)]
+pub struct Summary {
+    /// the drawing of each named molecule and the thumbnail made from it, for the compound registry
+    pub thumbnails: FxHashMap<MoleculeId, (String, Handle)>,
+    /// a sentence per visible molecule
+    pub description: Vec<String>,
+    /// map numbers that don't pair a reactant atom with a product atom, in order
+    pub unbalanced_atom_maps: Vec<u32>,
+    /// the arrow of each reaction that doesn't balance, with a line saying how
+    pub mass_imbalances: Vec<(ShapeId, String)>,
+    /// each synthesis route's steps with their arrow
+    pub routes: Vec<Vec<(ShapeId, String)>>,
+}
@@ -0,0 +1,46 @@
+use crate::molecule::Molecule;
+
+/// tags picked to narrow the document down to, molecules with none of them being dimmed or hidden
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagFilter {
+    tags: Vec<String>,
+    /// hides molecules that don't match rather than dimming them
+    hide: bool,
+}
+
+impl TagFilter {
+    /// how opaque molecules that don't match are drawn when they're dimmed
+    pub const DIMMED_ALPHA: f32 = 0.25;
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn set_tag(&mut self, tag: String, selected: bool) {
+        self.tags.retain(|existing| *existing != tag);
+        if selected {
+            self.tags.push(tag);
+        }
+    }
+
+    pub fn hide(&self) -> bool {
+        self.hide
+    }
+
+    pub fn set_hide(&mut self, hide: bool) {
+        self.hide = hide;
+    }
+
+    /// whether a molecule has any of the picked tags, every molecule matching when none are
+    pub fn matches(&self, molecule: &Molecule) -> bool {
+        self.tags.is_empty() || molecule.metadata().tags().iter().any(|tag| self.tags.contains(tag))
+    }
+
+    pub fn is_hidden(&self, molecule: &Molecule) -> bool {
+        self.hide && !self.matches(molecule)
+    }
+
+    pub fn is_dimmed(&self, molecule: &Molecule) -> bool {
+        !self.hide && !self.matches(molecule)
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+use iced::Point;
+use rustc_hash::FxHashMap;
+
+use crate::molecule::{AtomId, BondType, FxIndexMap, Molecule, MoleculeId};
+
+use super::history::Snapshot;
+
+type Molecules = FxIndexMap<MoleculeId, Molecule>;
+
+/// named copies of the document that are kept until deleted, unlike undo history
+#[derive(Debug, Default)]
+pub struct Timeline {
+    versions: Vec<Version>,
+}
+
+#[derive(Debug)]
+pub struct Version {
+    name: String,
+    snapshot: Snapshot,
+}
+
+/// atoms and bonds that differ between two versions, at the positions they are drawn
+#[derive(Debug, Default)]
+pub struct Diff {
+    pub added_atoms: Vec<Point>,
+    pub removed_atoms: Vec<Point>,
+    pub added_bonds: Vec<(Point, Point)>,
+    pub removed_bonds: Vec<(Point, Point)>,
+}
+
+impl Timeline {
+    pub fn add(&mut self, name: String, snapshot: Snapshot) {
+        self.versions.push(Version { name, snapshot });
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.versions.len() {
+            self.versions.remove(index);
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Version> {
+        self.versions.get(index)
+    }
+
+    pub fn versions(&self) -> impl Iterator<Item = &Version> {
+        self.versions.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.versions.len()
+    }
+}
+
+impl Version {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn snapshot(&self) -> &Snapshot {
+        &self.snapshot
+    }
+
+    pub fn molecule_count(&self) -> usize {
+        self.snapshot.molecules.len()
+    }
+}
+
+/// compares two versions of the document, atoms that were moved or relabelled count as both
+/// removed and added, as do bonds whose type or ends changed. bonds are told apart by their atoms,
+/// so a bond drawn again between the same atoms is the same bond
+pub fn diff(old: &Molecules, new: &Molecules) -> Result<Diff> {
+    let old_atoms = atoms(old).context("while diffing versions")?;
+    let new_atoms = atoms(new).context("while diffing versions")?;
+    let old_bonds = bonds(old, &old_atoms);
+    let new_bonds = bonds(new, &new_atoms);
+
+    let mut diff = Diff::default();
+
+    for (atom_id, old_atom) in &old_atoms {
+        if new_atoms.get(atom_id) != Some(old_atom) {
+            diff.removed_atoms.push(old_atom.0);
+        }
+    }
+    for (atom_id, new_atom) in &new_atoms {
+        if old_atoms.get(atom_id) != Some(new_atom) {
+            diff.added_atoms.push(new_atom.0);
+        }
+    }
+
+    for (atoms, old_bond) in &old_bonds {
+        if new_bonds.get(atoms) != Some(old_bond) {
+            diff.removed_bonds.push((old_bond.0, old_bond.1));
+        }
+    }
+    for (atoms, new_bond) in &new_bonds {
+        if old_bonds.get(atoms) != Some(new_bond) {
+            diff.added_bonds.push((new_bond.0, new_bond.1));
+        }
+    }
+
+    Ok(diff)
+}
+
+/// position and label of every atom
+fn atoms(molecules: &Molecules) -> Result<FxHashMap<AtomId, (Point, String)>> {
+    let mut atoms = FxHashMap::default();
+
+    for molecule in molecules.values() {
+        for (atom_id, atom) in molecule.atoms() {
+            atoms.insert(*atom_id, (molecule.atom_position(atom_id)?, atom.label()));
+        }
+    }
+
+    Ok(atoms)
+}
+
+/// end positions and type of every bond, by its atoms in either order
+fn bonds(
+    molecules: &Molecules,
+    atoms: &FxHashMap<AtomId, (Point, String)>,
+) -> FxHashMap<(AtomId, AtomId), (Point, Point, BondType)> {
+    molecules
+        .values()
+        .flat_map(|molecule| molecule.bonds())
+        .filter_map(|(_, bond)| {
+            let (start, _) = atoms.get(&bond.start())?;
+            let (end, _) = atoms.get(&bond.end())?;
+
+            let atom_ids = (bond.start().min(bond.end()), bond.start().max(bond.end()));
+
+            Some((atom_ids, (*start, *end, bond.bond_type())))
+        })
+        .collect()
+}
@@ -0,0 +1,21 @@
+mod abbreviation;
+mod balance;
+mod composition;
+mod descriptors;
+mod element;
+mod error;
+mod functional_groups;
+mod graph;
+mod metal_center;
+mod smiles;
+
+pub use abbreviation::Abbreviation;
+pub use balance::{mass_imbalance, MassImbalance};
+pub use composition::Composition;
+pub use descriptors::Descriptors;
+pub use element::{load_overrides, Element};
+pub use error::Error;
+pub use functional_groups::{find_functional_groups, find_functional_groups_in, FunctionalGroupMatch};
+pub use graph::MolecularGraph;
+pub use metal_center::MetalCenter;
+pub use smiles::to_smiles;
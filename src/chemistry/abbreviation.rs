@@ -0,0 +1,94 @@
+/// a shorthand label standing for a group of atoms, e.g. "OMe" for a methoxy group
+///
+/// labels that are also element symbols, such as Ac and Pr, are left meaning the element, apart from
+/// Ts which is taken from tennessine as it never turns up in structures
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Abbreviation {
+    pub label: &'static str,
+    pub name: &'static str,
+    /// the group written as a condensed label, starting with the atom bonds attach to
+    pub formula: &'static str,
+}
+
+impl Abbreviation {
+    const fn new(label: &'static str, name: &'static str, formula: &'static str) -> Self {
+        Self { label, name, formula }
+    }
+
+    pub fn all() -> &'static [Abbreviation] {
+        ABBREVIATIONS
+    }
+
+    pub fn from_label(label: &str) -> Option<Abbreviation> {
+        ABBREVIATIONS.iter().find(|abbreviation| abbreviation.label == label).copied()
+    }
+}
+
+const ABBREVIATIONS: &[Abbreviation] = &[
+    Abbreviation::new("Me", "methyl", "CH3"),
+    Abbreviation::new("Et", "ethyl", "C2H5"),
+    Abbreviation::new("iPr", "isopropyl", "CH(CH3)2"),
+    Abbreviation::new("Bu", "butyl", "C4H9"),
+    Abbreviation::new("tBu", "tert-butyl", "C(CH3)3"),
+    Abbreviation::new("Ph", "phenyl", "C6H5"),
+    Abbreviation::new("Bn", "benzyl", "CH2C6H5"),
+    Abbreviation::new("OMe", "methoxy", "OCH3"),
+    Abbreviation::new("OEt", "ethoxy", "OC2H5"),
+    Abbreviation::new("OPh", "phenoxy", "OC6H5"),
+    Abbreviation::new("OAc", "acetoxy", "OCOCH3"),
+    Abbreviation::new("NMe2", "dimethylamino", "N(CH3)2"),
+    Abbreviation::new("CO2Me", "methyl ester", "CO2CH3"),
+    Abbreviation::new("CO2Et", "ethyl ester", "CO2C2H5"),
+    // protecting groups
+    Abbreviation::new("Bz", "benzoyl", "COC6H5"),
+    Abbreviation::new("Piv", "pivaloyl", "COC(CH3)3"),
+    Abbreviation::new("Boc", "tert-butoxycarbonyl", "CO2C(CH3)3"),
+    Abbreviation::new("Cbz", "benzyloxycarbonyl", "CO2CH2C6H5"),
+    Abbreviation::new("Fmoc", "fluorenylmethoxycarbonyl", "CO2CH2C13H9"),
+    Abbreviation::new("NHBoc", "Boc amine", "NHCO2C(CH3)3"),
+    Abbreviation::new("NHCbz", "Cbz amine", "NHCO2CH2C6H5"),
+    Abbreviation::new("Ms", "mesyl", "SO2CH3"),
+    Abbreviation::new("Ts", "tosyl", "SO2C6H4CH3"),
+    Abbreviation::new("Tf", "triflyl", "SO2CF3"),
+    Abbreviation::new("OMs", "mesylate", "OSO2CH3"),
+    Abbreviation::new("OTs", "tosylate", "OSO2C6H4CH3"),
+    Abbreviation::new("OTf", "triflate", "OSO2CF3"),
+    Abbreviation::new("TMS", "trimethylsilyl", "Si(CH3)3"),
+    Abbreviation::new("TBS", "tert-butyldimethylsilyl", "Si(CH3)2C(CH3)3"),
+    Abbreviation::new("TIPS", "triisopropylsilyl", "Si(CH(CH3)2)3"),
+    Abbreviation::new("OTBS", "silyl ether", "OSi(CH3)2C(CH3)3"),
+    Abbreviation::new("PMB", "para-methoxybenzyl", "CH2C6H4OCH3"),
+    Abbreviation::new("MOM", "methoxymethyl", "CH2OCH3"),
+    Abbreviation::new("THP", "tetrahydropyranyl", "C5H9O"),
+    Abbreviation::new("OBn", "benzyloxy", "OCH2C6H5"),
+    // residues of condensed peptide and nucleic acid chains, bonded at both ends
+    Abbreviation::new("Ala", "alanine residue", "NHCH(CH3)CO"),
+    Abbreviation::new("Arg", "arginine residue", "NHCH(C3H6NHC(NH)NH2)CO"),
+    Abbreviation::new("Asn", "asparagine residue", "NHCH(CH2CONH2)CO"),
+    Abbreviation::new("Asp", "aspartic acid residue", "NHCH(CH2CO2H)CO"),
+    Abbreviation::new("Cys", "cysteine residue", "NHCH(CH2SH)CO"),
+    Abbreviation::new("Gln", "glutamine residue", "NHCH(C2H4CONH2)CO"),
+    Abbreviation::new("Glu", "glutamic acid residue", "NHCH(C2H4CO2H)CO"),
+    Abbreviation::new("Gly", "glycine residue", "NHCH2CO"),
+    Abbreviation::new("His", "histidine residue", "NHCH(CH2C3H3N2)CO"),
+    Abbreviation::new("Ile", "isoleucine residue", "NHCH(CH(CH3)C2H5)CO"),
+    Abbreviation::new("Leu", "leucine residue", "NHCH(CH2CH(CH3)2)CO"),
+    Abbreviation::new("Lys", "lysine residue", "NHCH(C4H8NH2)CO"),
+    Abbreviation::new("Met", "methionine residue", "NHCH(C2H4SCH3)CO"),
+    Abbreviation::new("Phe", "phenylalanine residue", "NHCH(CH2C6H5)CO"),
+    Abbreviation::new("Pro", "proline residue", "NC4H7CO"),
+    Abbreviation::new("Ser", "serine residue", "NHCH(CH2OH)CO"),
+    Abbreviation::new("Thr", "threonine residue", "NHCH(CH(OH)CH3)CO"),
+    Abbreviation::new("Trp", "tryptophan residue", "NHCH(CH2C8H6N)CO"),
+    Abbreviation::new("Tyr", "tyrosine residue", "NHCH(CH2C6H4OH)CO"),
+    Abbreviation::new("Val", "valine residue", "NHCH(CH(CH3)2)CO"),
+    Abbreviation::new("dA", "deoxyadenosine residue", "OC10H11N5O2"),
+    Abbreviation::new("dC", "deoxycytidine residue", "OC9H11N3O3"),
+    Abbreviation::new("dG", "deoxyguanosine residue", "OC10H11N5O3"),
+    Abbreviation::new("dT", "thymidine residue", "OC10H12N2O4"),
+    Abbreviation::new("rA", "adenosine residue", "OC10H11N5O3"),
+    Abbreviation::new("rC", "cytidine residue", "OC9H11N3O4"),
+    Abbreviation::new("rG", "guanosine residue", "OC10H11N5O4"),
+    Abbreviation::new("rU", "uridine residue", "OC9H10N2O5"),
+    Abbreviation::new("p", "phosphodiester", "PO2H"),
+];
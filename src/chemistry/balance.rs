@@ -0,0 +1,56 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+
+use crate::molecule::Molecule;
+
+use super::descriptors::{element_counts, hill_formula};
+use super::{Element, MolecularGraph};
+
+/// atoms a reaction's products are missing and those they have that its reactants don't, as
+/// formulas, empty when there are none
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MassImbalance {
+    pub missing: String,
+    pub extra: String,
+}
+
+/// compares the atoms of each element on either side of a reaction, giving nothing when they
+/// balance
+pub fn mass_imbalance<'a>(
+    reactants: impl IntoIterator<Item = &'a Molecule>,
+    products: impl IntoIterator<Item = &'a Molecule>,
+) -> Result<Option<MassImbalance>> {
+    // products' atoms count up and reactants' count down
+    let mut differences: BTreeMap<&'static str, (Element, i64)> = BTreeMap::new();
+    let mut count = |molecule: &Molecule, sign: i64| -> Result<()> {
+        let graph = MolecularGraph::new(molecule).context("while balancing reaction")?;
+        for (symbol, (element, count)) in element_counts(&graph) {
+            differences.entry(symbol).or_insert((element, 0)).1 += sign * i64::from(count);
+        }
+        Ok(())
+    };
+    for molecule in reactants {
+        count(molecule, -1)?;
+    }
+    for molecule in products {
+        count(molecule, 1)?;
+    }
+
+    let side = |sign: i64| {
+        differences
+            .iter()
+            .filter(|(_, (_, difference))| difference.signum() == sign)
+            .map(|(symbol, (element, difference))| (*symbol, (*element, difference.unsigned_abs() as u32)))
+            .collect::<BTreeMap<_, _>>()
+    };
+    let (missing, extra) = (side(-1), side(1));
+    if missing.is_empty() && extra.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(MassImbalance {
+        missing: hill_formula(&missing),
+        extra: hill_formula(&extra),
+    }))
+}
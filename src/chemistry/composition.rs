@@ -0,0 +1,294 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+use super::{Abbreviation, Element, Error};
+
+/// the atoms described by a single atom label, e.g. "CO2H" or "NH2"
+#[derive(Debug, Clone, PartialEq)]
+pub struct Composition {
+    /// element that bonds attach to
+    attachment: Element,
+    /// heavy atoms written in the label other than the attachment
+    substituents: Vec<(Element, u32)>,
+    /// hydrogens written in the label
+    hydrogens: u32,
+    /// whether hydrogens should be added to satisfy the attachment's valence
+    implicit_hydrogens: bool,
+    /// total of the charges written in the label
+    charge: i32,
+}
+
+impl Composition {
+    /// parses an atom label, an empty label is treated as a carbon
+    pub fn parse(label: &str) -> Result<Self, Error> {
+        if label.is_empty() {
+            return Ok(Self {
+                attachment: Element::from_symbol("C").unwrap_or(Element::CARBON),
+                substituents: vec![],
+                hydrogens: 0,
+                implicit_hydrogens: true,
+                charge: 0,
+            });
+        }
+
+        let stripped = strip_annotations(label);
+        if let (true, Some(c)) = (stripped.is_empty(), label.chars().next()) {
+            return Err(Error::UnexpectedCharacter(c));
+        }
+        let abbreviation = Abbreviation::from_label(&stripped);
+        let stripped = match abbreviation {
+            Some(abbreviation) => abbreviation.formula.to_string(),
+            None => stripped,
+        };
+
+        let mut chars = stripped.chars().peekable();
+        let elements = parse_group(&mut chars)?;
+        if chars.next().is_some() {
+            return Err(Error::UnbalancedParentheses);
+        }
+
+        // a bare element symbol such as "N" or "Cl" gets its hydrogens implied
+        let implicit_hydrogens = abbreviation.is_none() && Element::from_symbol(label).is_some();
+
+        let mut attachment = None;
+        let mut substituents: Vec<(Element, u32)> = vec![];
+        let mut hydrogens = 0;
+
+        for (element, mut count) in elements {
+            if element == Element::HYDROGEN {
+                hydrogens += count;
+                continue;
+            }
+
+            if attachment.is_none() {
+                attachment = Some(element);
+                count -= 1;
+            }
+
+            if count > 0 {
+                match substituents.iter_mut().find(|(existing, _)| *existing == element) {
+                    Some((_, existing_count)) => *existing_count += count,
+                    None => substituents.push((element, count)),
+                }
+            }
+        }
+
+        let attachment = match attachment {
+            Some(attachment) => attachment,
+            // label made up only of hydrogens, e.g. "H" or "H2"
+            None => {
+                hydrogens -= 1;
+                Element::hydrogen()
+            }
+        };
+
+        Ok(Self {
+            attachment,
+            substituents,
+            hydrogens,
+            implicit_hydrogens,
+            charge: label_charge(label),
+        })
+    }
+
+    /// labels the one being typed could become, completing its last element symbol or the whole
+    /// label to an abbreviation, with the closest first
+    pub fn completions(label: &str) -> Vec<String> {
+        const MAX_COMPLETIONS: usize = 8;
+
+        let mut completions: Vec<String> = Abbreviation::all()
+            .iter()
+            .filter(|abbreviation| !label.is_empty() && abbreviation.label.starts_with(label))
+            .map(|abbreviation| abbreviation.label.to_string())
+            .collect();
+
+        // the symbol being typed starts at the last capital, e.g. "Cl" in "CCl"
+        if let Some(start) = label.rfind(|c: char| c.is_ascii_uppercase()) {
+            let (head, partial) = label.split_at(start);
+            if partial.chars().all(|c| c.is_ascii_alphabetic()) {
+                for element in Element::all().iter().filter(|element| element.symbol.starts_with(partial)) {
+                    let completion = format!("{}{}", head, element.symbol);
+                    // Ts is both an abbreviation and a symbol
+                    if !completions.contains(&completion) {
+                        completions.push(completion);
+                    }
+                }
+            }
+        }
+
+        completions.retain(|completion| completion != label);
+        completions.sort_by_key(String::len);
+        completions.truncate(MAX_COMPLETIONS);
+
+        completions
+    }
+
+    pub fn attachment(&self) -> Element {
+        self.attachment
+    }
+
+    /// number of hydrogens on the atom given the total order of its bonds
+    pub fn hydrogens(&self, bond_valence: u32) -> u32 {
+        if self.implicit_hydrogens {
+            (self.attachment.valence as u32).saturating_sub(bond_valence)
+        } else {
+            self.hydrogens
+        }
+    }
+
+    pub fn charge(&self) -> i32 {
+        self.charge
+    }
+
+    pub fn has_substituents(&self) -> bool {
+        !self.substituents.is_empty()
+    }
+
+    /// all elements making up the atom and their counts, including hydrogens
+    pub fn element_counts(&self, bond_valence: u32) -> impl Iterator<Item = (Element, u32)> + '_ {
+        let hydrogens = self.hydrogens(bond_valence);
+
+        [(self.attachment, 1)]
+            .into_iter()
+            .chain(self.substituents.iter().copied())
+            .chain((hydrogens > 0).then_some((Element::hydrogen(), hydrogens)))
+    }
+}
+
+/// removes charges, radicals, isotopes, superscripts and italics, and unwraps subscript markup,
+/// leaving only the elements and their counts
+fn strip_annotations(label: &str) -> String {
+    let mut stripped = String::new();
+    let mut chars = label.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '^' | '_' => {
+                let group: String = match chars.next() {
+                    Some('{') => chars.by_ref().take_while(|c| *c != '}').collect(),
+                    Some(c) => c.to_string(),
+                    None => String::new(),
+                };
+
+                if c == '_' {
+                    stripped.push_str(&group);
+                }
+            }
+            _ if c.is_ascii_digit() => {
+                let mut digits = c.to_string();
+                while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                    digits.push(digit);
+                }
+
+                // isotopes before a symbol are dropped
+                if !(stripped.is_empty() || stripped.ends_with('(')) {
+                    stripped.push_str(&digits);
+                }
+            }
+            '+' | '-' => {
+                // along with the magnitude of the charge
+                while chars.next_if(char::is_ascii_digit).is_some() {}
+            }
+            '.' | '•' | '*' => (),
+            _ => stripped.push(c),
+        }
+    }
+
+    stripped
+}
+
+/// total of the charges in a label, written after the symbol with the magnitude after the sign, e.g.
+/// "Fe+3", or superscripted with it before, e.g. "O^{2-}"
+fn label_charge(label: &str) -> i32 {
+    let mut charge = 0;
+    let mut chars = label.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '^' | '_' => {
+                let group: String = match chars.next() {
+                    Some('{') => chars.by_ref().take_while(|c| *c != '}').collect(),
+                    Some(c) => c.to_string(),
+                    None => String::new(),
+                };
+
+                let sign = match group.chars().last() {
+                    Some('+') => 1,
+                    Some('-') => -1,
+                    _ => continue,
+                };
+                if c == '^' {
+                    charge += sign * group[..group.len() - 1].parse::<i32>().unwrap_or(1);
+                }
+            }
+            // a sign joining two parts of a label isn't a charge, e.g. "t-Bu"
+            '+' | '-' if chars.peek().is_some_and(|next| next.is_alphabetic()) => (),
+            '+' | '-' => {
+                let mut digits = String::new();
+                while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                    digits.push(digit);
+                }
+
+                let sign = if c == '+' { 1 } else { -1 };
+                charge += sign * digits.parse::<i32>().unwrap_or(1);
+            }
+            _ => (),
+        }
+    }
+
+    charge
+}
+
+/// parses elements with counts until the end of the input or a closing parenthesis
+fn parse_group(chars: &mut Peekable<Chars>) -> Result<Vec<(Element, u32)>, Error> {
+    let mut elements = vec![];
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            '(' => {
+                chars.next();
+                let group = parse_group(chars)?;
+                if chars.next() != Some(')') {
+                    return Err(Error::UnbalancedParentheses);
+                }
+                let count = parse_count(chars);
+                elements.extend(group.into_iter().map(|(element, n)| (element, n * count)));
+            }
+            ')' => break,
+            _ if c.is_ascii_uppercase() => {
+                chars.next();
+                let mut symbol = c.to_string();
+                if let Some(&lower) = chars.peek() {
+                    if lower.is_ascii_lowercase() {
+                        symbol.push(lower);
+                        // prefer the two letter symbol, falling back on the single letter
+                        if Element::from_symbol(&symbol).is_some() {
+                            chars.next();
+                        } else {
+                            symbol.pop();
+                        }
+                    }
+                }
+
+                let element =
+                    Element::from_symbol(&symbol).ok_or(Error::UnknownSymbol(symbol))?;
+                let count = parse_count(chars);
+                elements.push((element, count));
+            }
+            _ => return Err(Error::UnexpectedCharacter(c)),
+        }
+    }
+
+    Ok(elements)
+}
+
+fn parse_count(chars: &mut Peekable<Chars>) -> u32 {
+    let mut count = None;
+
+    while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+        chars.next();
+        count = Some(count.unwrap_or(0) * 10 + digit);
+    }
+
+    count.unwrap_or(1)
+}
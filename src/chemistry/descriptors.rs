@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+
+use crate::molecule::Molecule;
+
+use super::{Element, MolecularGraph};
+
+/// computed properties of a molecule
+#[derive(Debug, Clone, PartialEq)]
+pub struct Descriptors {
+    pub formula: String,
+    pub molecular_weight: f32,
+    pub degrees_of_unsaturation: f32,
+    pub h_bond_donors: u32,
+    pub h_bond_acceptors: u32,
+    pub rotatable_bonds: u32,
+    /// topological polar surface area in Å²
+    pub tpsa: f32,
+    /// crude atom contribution estimate of logP
+    pub clogp: f32,
+}
+
+impl Descriptors {
+    pub fn new(molecule: &Molecule) -> Result<Self> {
+        let graph = MolecularGraph::new(molecule).context("while computing descriptors")?;
+
+        Ok(Self::from_graph(&graph))
+    }
+
+    pub fn from_graph(graph: &MolecularGraph) -> Self {
+        let counts = element_counts(graph);
+
+        let count = |symbol: &str| counts.get(symbol).map(|(_, count)| *count).unwrap_or(0) as f32;
+        let halogens: f32 = counts
+            .values()
+            .filter(|(element, _)| element.is_halogen())
+            .map(|(_, count)| *count as f32)
+            .sum();
+
+        let degrees_of_unsaturation = (2.0 * (count("C") + count("Si"))
+            + 2.0
+            + count("N")
+            + count("P")
+            - count("H")
+            - halogens)
+            / 2.0;
+
+        let mut h_bond_donors = 0;
+        let mut h_bond_acceptors = 0;
+        let mut tpsa = 0.0;
+        let mut clogp = 0.0;
+
+        for (index, atom) in graph.atoms() {
+            let hydrogens = graph.hydrogens(index);
+            let max_order = graph.max_bond_order(index);
+
+            for (element, count) in atom.composition.element_counts(graph.bond_valence(index)) {
+                if matches!(element.symbol, "N" | "O") {
+                    h_bond_acceptors += count;
+                }
+
+                clogp += count as f32 * logp_contribution(element, max_order);
+            }
+
+            let polar = matches!(atom.composition.attachment().symbol, "N" | "O");
+            if polar && hydrogens > 0 {
+                h_bond_donors += 1;
+            }
+
+            tpsa += polar_surface_contribution(atom.composition.attachment(), hydrogens, max_order);
+        }
+
+        let rotatable_bonds = graph
+            .bonds()
+            .filter(|(index, bond)| {
+                bond.order == 1
+                    && graph.heavy_degree(bond.atoms.0) > 1
+                    && graph.heavy_degree(bond.atoms.1) > 1
+                    && !graph.is_ring_bond(*index)
+            })
+            .count() as u32;
+
+        Self {
+            formula: hill_formula(&counts),
+            molecular_weight: counts
+                .values()
+                .map(|(element, count)| element.mass * *count as f32)
+                .sum(),
+            degrees_of_unsaturation,
+            h_bond_donors,
+            h_bond_acceptors,
+            rotatable_bonds,
+            tpsa,
+            clogp,
+        }
+    }
+
+    /// number of Lipinski rule of five criteria the molecule fails
+    pub fn rule_of_five_violations(&self) -> u32 {
+        [
+            self.h_bond_donors > 5,
+            self.h_bond_acceptors > 10,
+            self.molecular_weight > 500.0,
+            self.clogp > 5.0,
+        ]
+        .into_iter()
+        .filter(|violation| *violation)
+        .count() as u32
+    }
+}
+
+pub(super) fn element_counts(graph: &MolecularGraph) -> BTreeMap<&'static str, (Element, u32)> {
+    let mut counts = BTreeMap::new();
+
+    for (index, atom) in graph.atoms() {
+        for (element, count) in atom.composition.element_counts(graph.bond_valence(index)) {
+            counts.entry(element.symbol).or_insert((element, 0)).1 += count;
+        }
+    }
+
+    counts
+}
+
+/// formats the formula in Hill order, carbon then hydrogen then alphabetical
+pub(super) fn hill_formula(counts: &BTreeMap<&'static str, (Element, u32)>) -> String {
+    let has_carbon = counts.contains_key("C");
+    let mut symbols: Vec<_> = counts.keys().copied().collect();
+
+    if has_carbon {
+        symbols.retain(|symbol| !matches!(*symbol, "C" | "H"));
+        symbols.insert(0, "C");
+        if counts.contains_key("H") {
+            symbols.insert(1, "H");
+        }
+    }
+
+    symbols
+        .into_iter()
+        .map(|symbol| match counts[symbol].1 {
+            1 => symbol.to_string(),
+            count => format!("{symbol}{count}"),
+        })
+        .collect()
+}
+
+/// Ertl contributions for nitrogen and oxygen by hydrogen count and bond order
+fn polar_surface_contribution(element: Element, hydrogens: u32, max_order: u8) -> f32 {
+    match (element.symbol, hydrogens, max_order) {
+        ("N", 2.., _) => 26.02,
+        ("N", 1, 2) => 23.85,
+        ("N", 1, _) => 12.03,
+        ("N", 0, 3) => 23.79,
+        ("N", 0, 2) => 12.36,
+        ("N", 0, _) => 3.24,
+        ("O", 1.., _) => 20.23,
+        ("O", 0, 2) => 17.07,
+        ("O", 0, _) => 9.23,
+        _ => 0.0,
+    }
+}
+
+fn logp_contribution(element: Element, max_order: u8) -> f32 {
+    match (element.symbol, max_order) {
+        ("C", 0 | 1) => 0.5,
+        ("C", _) => 0.3,
+        ("N", _) => -1.0,
+        ("O", _) => -0.9,
+        ("S", _) => 0.6,
+        ("F", _) => 0.4,
+        ("Cl", _) => 0.9,
+        ("Br", _) => 1.1,
+        ("I", _) => 1.4,
+        _ => 0.0,
+    }
+}
@@ -0,0 +1,440 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+
+use crate::config;
+
+use super::Error;
+
+/// an element's properties, from the built in table or the user's element file
+#[derive(Debug, Clone, Copy)]
+pub struct Element {
+    pub atomic_number: u8,
+    pub symbol: &'static str,
+    pub name: &'static str,
+    /// standard atomic weight, or the mass number of the longest lived isotope for elements
+    /// without one
+    pub mass: f32,
+    /// valence used to fill in implicit hydrogens
+    pub valence: u8,
+    /// electrons in the outer shell of the neutral atom
+    pub valence_electrons: u8,
+    /// on the Pauling scale, unknown for the lighter noble gases and superheavy elements
+    pub electronegativity: Option<f32>,
+    /// CPK colour as 0xRRGGBB, following Jmol
+    pub color: u32,
+    /// naturally occurring isotopes, listed for the elements common in organic chemistry
+    pub isotopes: &'static [Isotope],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Isotope {
+    pub mass_number: u16,
+    pub mass: f32,
+    /// fraction of the element's atoms found in nature
+    pub abundance: f32,
+}
+
+/// the built in table with the user's element file applied, set once at startup
+static TABLE: OnceLock<Vec<Element>> = OnceLock::new();
+
+/// environment variable naming an element file to use instead of the one in the config directory
+const FILE_VARIABLE: &str = "MOLECULAR_CANVAS_ELEMENTS";
+
+impl Element {
+    pub const CARBON: Element = Element::new(6, "C", "Carbon", 12.011, 4, 4, Some(2.55), 0x909090, CARBON_ISOTOPES);
+    pub const HYDROGEN: Element =
+        Element::new(1, "H", "Hydrogen", 1.008, 1, 1, Some(2.20), 0xFFFFFF, HYDROGEN_ISOTOPES);
+
+    #[allow(clippy::too_many_arguments)]
+    const fn new(
+        atomic_number: u8,
+        symbol: &'static str,
+        name: &'static str,
+        mass: f32,
+        valence: u8,
+        valence_electrons: u8,
+        electronegativity: Option<f32>,
+        color: u32,
+        isotopes: &'static [Isotope],
+    ) -> Self {
+        Self {
+            atomic_number,
+            symbol,
+            name,
+            mass,
+            valence,
+            valence_electrons,
+            electronegativity,
+            color,
+            isotopes,
+        }
+    }
+
+    /// every known element, including symbols added in the user's element file
+    pub fn all() -> &'static [Element] {
+        TABLE.get().map(Vec::as_slice).unwrap_or(ELEMENTS)
+    }
+
+    pub fn from_symbol(symbol: &str) -> Option<Element> {
+        Self::all().iter().find(|element| element.symbol == symbol).copied()
+    }
+
+    /// hydrogen as the user's element file leaves it, for its mass and isotopes
+    pub fn hydrogen() -> Element {
+        Self::from_symbol(Self::HYDROGEN.symbol).unwrap_or(Self::HYDROGEN)
+    }
+
+    /// number of non-bonding electron pairs on the neutral atom
+    pub fn lone_pairs(&self, bond_valence: u32, hydrogens: u32) -> u32 {
+        (self.valence_electrons as u32).saturating_sub(bond_valence + hydrogens) / 2
+    }
+
+    pub fn is_halogen(&self) -> bool {
+        matches!(self.symbol, "F" | "Cl" | "Br" | "I")
+    }
+
+    /// column of the periodic table, none for the lanthanides and actinides after lanthanum and
+    /// actinium, which are put in group 3
+    pub fn group(&self) -> Option<u8> {
+        match self.atomic_number {
+            1 => Some(1),
+            2 => Some(18),
+            number @ (3..=4 | 11..=12 | 19..=20 | 37..=38 | 55..=56 | 87..=88) => Some(2 - number % 2),
+            number @ 5..=10 => Some(number + 8),
+            number @ 13..=18 => Some(number),
+            number @ 21..=36 => Some(number - 18),
+            number @ 39..=54 => Some(number - 36),
+            57 | 89 => Some(3),
+            number @ 72..=86 => Some(number - 68),
+            number @ 104..=118 => Some(number - 100),
+            _ => None,
+        }
+    }
+
+    /// row of the periodic table
+    pub fn period(&self) -> u8 {
+        match self.atomic_number {
+            0..=2 => 1,
+            3..=10 => 2,
+            11..=18 => 3,
+            19..=36 => 4,
+            37..=54 => 5,
+            55..=86 => 6,
+            _ => 7,
+        }
+    }
+
+    /// everything but the nonmetals, metalloids and noble gases
+    pub fn is_metal(&self) -> bool {
+        !matches!(self.atomic_number, 0..=2 | 5..=10 | 14..=18 | 32..=36 | 51..=54 | 85..=86 | 117..=118)
+    }
+
+    /// metals of groups 3 to 12, whose d electrons are counted
+    pub fn is_transition_metal(&self) -> bool {
+        self.is_metal() && matches!(self.group(), Some(3..=12))
+    }
+
+    /// red, green and blue parts of the CPK colour
+    pub fn rgb(&self) -> [u8; 3] {
+        let [_, red, green, blue] = self.color.to_be_bytes();
+        [red, green, blue]
+    }
+}
+
+/// elements are the same when their symbols are, so one changed by the user's element file still
+/// matches the built in constants
+impl PartialEq for Element {
+    fn eq(&self, other: &Self) -> bool {
+        self.symbol == other.symbol
+    }
+}
+
+impl Isotope {
+    const fn new(mass_number: u16, mass: f32, abundance: f32) -> Self {
+        Self {
+            mass_number,
+            mass,
+            abundance,
+        }
+    }
+}
+
+/// the user's element file, named by the environment variable or in the config directory, and
+/// whether it was named explicitly
+fn overrides_path() -> Option<(PathBuf, bool)> {
+    if let Some(path) = std::env::var_os(FILE_VARIABLE) {
+        return Some((path.into(), true));
+    }
+
+    Some((config::dir()?.join("elements.conf"), false))
+}
+
+/// applies the user's element file over the built in table, doing nothing when there isn't one
+///
+/// each line starts with a symbol followed by `field=value` pairs changing that element, or adding
+/// it when a new symbol copies another with `like`, e.g. `D like=H name=Deuterium mass=2.014`.
+/// the fields are `like`, `name`, `number`, `mass`, `valence`, `electrons`, `electronegativity` and
+/// `color`, and lines starting with `#` are comments
+pub fn load_overrides() -> Result<()> {
+    let Some((path, explicit)) = overrides_path() else {
+        return Ok(());
+    };
+
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound && !explicit => return Ok(()),
+        Err(error) => {
+            return Err(error)
+                .with_context(|| format!("while reading {}", path.display()))
+                .context("while loading element overrides")
+        }
+    };
+
+    let table = parse_overrides(&text)
+        .with_context(|| format!("while reading {}", path.display()))
+        .context("while loading element overrides")?;
+    // loading twice keeps the first table
+    let _ = TABLE.set(table);
+
+    Ok(())
+}
+
+fn parse_overrides(text: &str) -> Result<Vec<Element>> {
+    let mut table = ELEMENTS.to_vec();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+
+        let mut tokens = line.split_whitespace();
+        let Some(symbol) = tokens.next() else {
+            continue;
+        };
+
+        let fields = tokens
+            .map(|token| token.split_once('=').ok_or(Error::InvalidElementEntry(token.to_string())))
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(|| format!("while reading line {}", index + 1))?;
+
+        let element = override_element(&table, symbol, &fields)
+            .with_context(|| format!("while reading line {}", index + 1))?;
+
+        match table.iter_mut().find(|existing| existing.symbol == symbol) {
+            Some(existing) => *existing = element,
+            None => table.push(element),
+        }
+    }
+
+    Ok(table)
+}
+
+/// an element of the table with the fields of one line of the element file applied
+fn override_element(table: &[Element], symbol: &str, fields: &[(&str, &str)]) -> Result<Element, Error> {
+    let find = |symbol: &str| table.iter().find(|element| element.symbol == symbol).copied();
+
+    let mut element = match fields.iter().find(|(key, _)| *key == "like") {
+        // a copy describes a different nuclide, so the original's isotopes don't carry over
+        Some((_, like)) => Element {
+            symbol: leak(symbol),
+            isotopes: &[],
+            ..find(like).ok_or(Error::UnknownElement(like.to_string()))?
+        },
+        None => find(symbol).ok_or(Error::UnknownElement(symbol.to_string()))?,
+    };
+
+    for (key, value) in fields {
+        match *key {
+            "like" => (),
+            "name" => element.name = leak(value),
+            "number" => element.atomic_number = parse_value(key, value)?,
+            "mass" => element.mass = parse_value(key, value)?,
+            "valence" => element.valence = parse_value(key, value)?,
+            "electrons" => element.valence_electrons = parse_value(key, value)?,
+            "electronegativity" => element.electronegativity = Some(parse_value(key, value)?),
+            "color" => {
+                element.color = u32::from_str_radix(value.trim_start_matches('#'), 16)
+                    .map_err(|_| Error::InvalidElementValue(key.to_string(), value.to_string()))?
+            }
+            _ => return Err(Error::UnknownElementField(key.to_string())),
+        }
+    }
+
+    Ok(element)
+}
+
+fn parse_value<T: FromStr>(key: &str, value: &str) -> Result<T, Error> {
+    value
+        .parse()
+        .map_err(|_| Error::InvalidElementValue(key.to_string(), value.to_string()))
+}
+
+/// the table lives for the whole run, so text from the element file is leaked once at startup
+fn leak(text: &str) -> &'static str {
+    text.to_string().leak()
+}
+
+const ELEMENTS: &[Element] = &[
+    Element::HYDROGEN,
+    Element::new(2, "He", "Helium", 4.0026, 0, 2, None, 0xD9FFFF, &[]),
+    Element::new(3, "Li", "Lithium", 6.94, 1, 1, Some(0.98), 0xCC80FF, LITHIUM_ISOTOPES),
+    Element::new(4, "Be", "Beryllium", 9.0122, 2, 2, Some(1.57), 0xC2FF00, &[]),
+    Element::new(5, "B", "Boron", 10.81, 3, 3, Some(2.04), 0xFFB5B5, BORON_ISOTOPES),
+    Element::CARBON,
+    Element::new(7, "N", "Nitrogen", 14.007, 3, 5, Some(3.04), 0x3050F8, NITROGEN_ISOTOPES),
+    Element::new(8, "O", "Oxygen", 15.999, 2, 6, Some(3.44), 0xFF0D0D, OXYGEN_ISOTOPES),
+    Element::new(9, "F", "Fluorine", 18.998, 1, 7, Some(3.98), 0x90E050, FLUORINE_ISOTOPES),
+    Element::new(10, "Ne", "Neon", 20.180, 0, 8, None, 0xB3E3F5, &[]),
+    Element::new(11, "Na", "Sodium", 22.990, 1, 1, Some(0.93), 0xAB5CF2, SODIUM_ISOTOPES),
+    Element::new(12, "Mg", "Magnesium", 24.305, 2, 2, Some(1.31), 0x8AFF00, &[]),
+    Element::new(13, "Al", "Aluminium", 26.982, 3, 3, Some(1.61), 0xBFA6A6, &[]),
+    Element::new(14, "Si", "Silicon", 28.085, 4, 4, Some(1.90), 0xF0C8A0, SILICON_ISOTOPES),
+    Element::new(15, "P", "Phosphorus", 30.974, 3, 5, Some(2.19), 0xFF8000, PHOSPHORUS_ISOTOPES),
+    Element::new(16, "S", "Sulfur", 32.06, 2, 6, Some(2.58), 0xFFFF30, SULFUR_ISOTOPES),
+    Element::new(17, "Cl", "Chlorine", 35.45, 1, 7, Some(3.16), 0x1FF01F, CHLORINE_ISOTOPES),
+    Element::new(18, "Ar", "Argon", 39.95, 0, 8, None, 0x80D1E3, &[]),
+    Element::new(19, "K", "Potassium", 39.098, 1, 1, Some(0.82), 0x8F40D4, &[]),
+    Element::new(20, "Ca", "Calcium", 40.078, 2, 2, Some(1.00), 0x3DFF00, &[]),
+    Element::new(21, "Sc", "Scandium", 44.956, 3, 3, Some(1.36), 0xE6E6E6, &[]),
+    Element::new(22, "Ti", "Titanium", 47.867, 4, 4, Some(1.54), 0xBFC2C7, &[]),
+    Element::new(23, "V", "Vanadium", 50.942, 3, 5, Some(1.63), 0xA6A6AB, &[]),
+    Element::new(24, "Cr", "Chromium", 51.996, 3, 6, Some(1.66), 0x8A99C7, &[]),
+    Element::new(25, "Mn", "Manganese", 54.938, 2, 7, Some(1.55), 0x9C7AC7, &[]),
+    Element::new(26, "Fe", "Iron", 55.845, 2, 8, Some(1.83), 0xE06633, &[]),
+    Element::new(27, "Co", "Cobalt", 58.933, 2, 9, Some(1.88), 0xF090A0, &[]),
+    Element::new(28, "Ni", "Nickel", 58.693, 2, 10, Some(1.91), 0x50D050, &[]),
+    Element::new(29, "Cu", "Copper", 63.546, 2, 11, Some(1.90), 0xC88033, &[]),
+    Element::new(30, "Zn", "Zinc", 65.38, 2, 12, Some(1.65), 0x7D80B0, &[]),
+    Element::new(31, "Ga", "Gallium", 69.723, 3, 3, Some(1.81), 0xC28F8F, &[]),
+    Element::new(32, "Ge", "Germanium", 72.630, 4, 4, Some(2.01), 0x668F8F, &[]),
+    Element::new(33, "As", "Arsenic", 74.922, 3, 5, Some(2.18), 0xBD80E3, &[]),
+    Element::new(34, "Se", "Selenium", 78.971, 2, 6, Some(2.55), 0xFFA100, &[]),
+    Element::new(35, "Br", "Bromine", 79.904, 1, 7, Some(2.96), 0xA62929, BROMINE_ISOTOPES),
+    Element::new(36, "Kr", "Krypton", 83.798, 0, 8, Some(3.00), 0x5CB8D1, &[]),
+    Element::new(37, "Rb", "Rubidium", 85.468, 1, 1, Some(0.82), 0x702EB0, &[]),
+    Element::new(38, "Sr", "Strontium", 87.62, 2, 2, Some(0.95), 0x00FF00, &[]),
+    Element::new(39, "Y", "Yttrium", 88.906, 3, 3, Some(1.22), 0x94FFFF, &[]),
+    Element::new(40, "Zr", "Zirconium", 91.224, 4, 4, Some(1.33), 0x94E0E0, &[]),
+    Element::new(41, "Nb", "Niobium", 92.906, 5, 5, Some(1.6), 0x73C2C9, &[]),
+    Element::new(42, "Mo", "Molybdenum", 95.95, 6, 6, Some(2.16), 0x54B5B5, &[]),
+    Element::new(43, "Tc", "Technetium", 98.0, 7, 7, Some(1.9), 0x3B9E9E, &[]),
+    Element::new(44, "Ru", "Ruthenium", 101.07, 3, 8, Some(2.2), 0x248F8F, &[]),
+    Element::new(45, "Rh", "Rhodium", 102.91, 3, 9, Some(2.28), 0x0A7D8C, &[]),
+    Element::new(46, "Pd", "Palladium", 106.42, 2, 10, Some(2.20), 0x006985, &[]),
+    Element::new(47, "Ag", "Silver", 107.87, 1, 11, Some(1.93), 0xC0C0C0, &[]),
+    Element::new(48, "Cd", "Cadmium", 112.41, 2, 12, Some(1.69), 0xFFD98F, &[]),
+    Element::new(49, "In", "Indium", 114.82, 3, 3, Some(1.78), 0xA67573, &[]),
+    Element::new(50, "Sn", "Tin", 118.71, 4, 4, Some(1.96), 0x668080, &[]),
+    Element::new(51, "Sb", "Antimony", 121.76, 3, 5, Some(2.05), 0x9E63B5, &[]),
+    Element::new(52, "Te", "Tellurium", 127.60, 2, 6, Some(2.1), 0xD47A00, &[]),
+    Element::new(53, "I", "Iodine", 126.90, 1, 7, Some(2.66), 0x940094, IODINE_ISOTOPES),
+    Element::new(54, "Xe", "Xenon", 131.29, 0, 8, Some(2.6), 0x429EB0, &[]),
+    Element::new(55, "Cs", "Caesium", 132.91, 1, 1, Some(0.79), 0x57178F, &[]),
+    Element::new(56, "Ba", "Barium", 137.33, 2, 2, Some(0.89), 0x00C900, &[]),
+    Element::new(57, "La", "Lanthanum", 138.91, 3, 3, Some(1.10), 0x70D4FF, &[]),
+    Element::new(58, "Ce", "Cerium", 140.12, 3, 3, Some(1.12), 0xFFFFC7, &[]),
+    Element::new(59, "Pr", "Praseodymium", 140.91, 3, 3, Some(1.13), 0xD9FFC7, &[]),
+    Element::new(60, "Nd", "Neodymium", 144.24, 3, 3, Some(1.14), 0xC7FFC7, &[]),
+    Element::new(61, "Pm", "Promethium", 145.0, 3, 3, Some(1.13), 0xA3FFC7, &[]),
+    Element::new(62, "Sm", "Samarium", 150.36, 3, 3, Some(1.17), 0x8FFFC7, &[]),
+    Element::new(63, "Eu", "Europium", 151.96, 3, 3, Some(1.2), 0x61FFC7, &[]),
+    Element::new(64, "Gd", "Gadolinium", 157.25, 3, 3, Some(1.20), 0x45FFC7, &[]),
+    Element::new(65, "Tb", "Terbium", 158.93, 3, 3, Some(1.1), 0x30FFC7, &[]),
+    Element::new(66, "Dy", "Dysprosium", 162.50, 3, 3, Some(1.22), 0x1FFFC7, &[]),
+    Element::new(67, "Ho", "Holmium", 164.93, 3, 3, Some(1.23), 0x00FF9C, &[]),
+    Element::new(68, "Er", "Erbium", 167.26, 3, 3, Some(1.24), 0x00E675, &[]),
+    Element::new(69, "Tm", "Thulium", 168.93, 3, 3, Some(1.25), 0x00D452, &[]),
+    Element::new(70, "Yb", "Ytterbium", 173.05, 3, 3, Some(1.1), 0x00BF38, &[]),
+    Element::new(71, "Lu", "Lutetium", 174.97, 3, 3, Some(1.27), 0x00AB24, &[]),
+    Element::new(72, "Hf", "Hafnium", 178.49, 4, 4, Some(1.3), 0x4DC2FF, &[]),
+    Element::new(73, "Ta", "Tantalum", 180.95, 5, 5, Some(1.5), 0x4DA6FF, &[]),
+    Element::new(74, "W", "Tungsten", 183.84, 6, 6, Some(2.36), 0x2194D6, &[]),
+    Element::new(75, "Re", "Rhenium", 186.21, 7, 7, Some(1.9), 0x267DAB, &[]),
+    Element::new(76, "Os", "Osmium", 190.23, 4, 8, Some(2.2), 0x266696, &[]),
+    Element::new(77, "Ir", "Iridium", 192.22, 3, 9, Some(2.20), 0x175487, &[]),
+    Element::new(78, "Pt", "Platinum", 195.08, 2, 10, Some(2.28), 0xD0D0E0, &[]),
+    Element::new(79, "Au", "Gold", 196.97, 1, 11, Some(2.54), 0xFFD123, &[]),
+    Element::new(80, "Hg", "Mercury", 200.59, 2, 12, Some(2.00), 0xB8B8D0, &[]),
+    Element::new(81, "Tl", "Thallium", 204.38, 3, 3, Some(1.62), 0xA6544D, &[]),
+    Element::new(82, "Pb", "Lead", 207.2, 4, 4, Some(2.33), 0x575961, &[]),
+    Element::new(83, "Bi", "Bismuth", 208.98, 3, 5, Some(2.02), 0x9E4FB5, &[]),
+    Element::new(84, "Po", "Polonium", 209.0, 2, 6, Some(2.0), 0xAB5C00, &[]),
+    Element::new(85, "At", "Astatine", 210.0, 1, 7, Some(2.2), 0x754F45, &[]),
+    Element::new(86, "Rn", "Radon", 222.0, 0, 8, Some(2.2), 0x428296, &[]),
+    Element::new(87, "Fr", "Francium", 223.0, 1, 1, Some(0.7), 0x420066, &[]),
+    Element::new(88, "Ra", "Radium", 226.0, 2, 2, Some(0.9), 0x007D00, &[]),
+    Element::new(89, "Ac", "Actinium", 227.0, 3, 3, Some(1.1), 0x70ABFA, &[]),
+    Element::new(90, "Th", "Thorium", 232.04, 4, 3, Some(1.3), 0x00BAFF, &[]),
+    Element::new(91, "Pa", "Protactinium", 231.04, 3, 3, Some(1.5), 0x00A1FF, &[]),
+    Element::new(92, "U", "Uranium", 238.03, 4, 3, Some(1.38), 0x008FFF, &[]),
+    Element::new(93, "Np", "Neptunium", 237.0, 3, 3, Some(1.36), 0x0080FF, &[]),
+    Element::new(94, "Pu", "Plutonium", 244.0, 3, 3, Some(1.28), 0x006BFF, &[]),
+    Element::new(95, "Am", "Americium", 243.0, 3, 3, Some(1.13), 0x545CF2, &[]),
+    Element::new(96, "Cm", "Curium", 247.0, 3, 3, Some(1.28), 0x785CE3, &[]),
+    Element::new(97, "Bk", "Berkelium", 247.0, 3, 3, Some(1.3), 0x8A4FE3, &[]),
+    Element::new(98, "Cf", "Californium", 251.0, 3, 3, Some(1.3), 0xA136D4, &[]),
+    Element::new(99, "Es", "Einsteinium", 252.0, 3, 3, Some(1.3), 0xB31FD4, &[]),
+    Element::new(100, "Fm", "Fermium", 257.0, 3, 3, Some(1.3), 0xB31FBA, &[]),
+    Element::new(101, "Md", "Mendelevium", 258.0, 3, 3, Some(1.3), 0xB30DA6, &[]),
+    Element::new(102, "No", "Nobelium", 259.0, 3, 3, Some(1.3), 0xBD0D87, &[]),
+    Element::new(103, "Lr", "Lawrencium", 266.0, 3, 3, Some(1.3), 0xC70066, &[]),
+    Element::new(104, "Rf", "Rutherfordium", 267.0, 0, 4, None, 0xCC0059, &[]),
+    Element::new(105, "Db", "Dubnium", 268.0, 0, 5, None, 0xD1004F, &[]),
+    Element::new(106, "Sg", "Seaborgium", 269.0, 0, 6, None, 0xD90045, &[]),
+    Element::new(107, "Bh", "Bohrium", 270.0, 0, 7, None, 0xE00038, &[]),
+    Element::new(108, "Hs", "Hassium", 277.0, 0, 8, None, 0xE6002E, &[]),
+    Element::new(109, "Mt", "Meitnerium", 278.0, 0, 9, None, 0xEB0026, &[]),
+    Element::new(110, "Ds", "Darmstadtium", 281.0, 0, 10, None, 0xEB0026, &[]),
+    Element::new(111, "Rg", "Roentgenium", 282.0, 0, 11, None, 0xEB0026, &[]),
+    Element::new(112, "Cn", "Copernicium", 285.0, 0, 12, None, 0xEB0026, &[]),
+    Element::new(113, "Nh", "Nihonium", 286.0, 0, 3, None, 0xEB0026, &[]),
+    Element::new(114, "Fl", "Flerovium", 289.0, 0, 4, None, 0xEB0026, &[]),
+    Element::new(115, "Mc", "Moscovium", 290.0, 0, 5, None, 0xEB0026, &[]),
+    Element::new(116, "Lv", "Livermorium", 293.0, 0, 6, None, 0xEB0026, &[]),
+    Element::new(117, "Ts", "Tennessine", 294.0, 0, 7, None, 0xEB0026, &[]),
+    Element::new(118, "Og", "Oganesson", 294.0, 0, 8, None, 0xEB0026, &[]),
+];
+
+const HYDROGEN_ISOTOPES: &[Isotope] = &[
+    Isotope::new(1, 1.007825, 0.99988),
+    Isotope::new(2, 2.014102, 0.00012),
+];
+const LITHIUM_ISOTOPES: &[Isotope] = &[Isotope::new(6, 6.015123, 0.0759), Isotope::new(7, 7.016003, 0.9241)];
+const BORON_ISOTOPES: &[Isotope] = &[Isotope::new(10, 10.01294, 0.199), Isotope::new(11, 11.0093, 0.801)];
+const CARBON_ISOTOPES: &[Isotope] = &[Isotope::new(12, 12.0, 0.9893), Isotope::new(13, 13.00336, 0.0107)];
+const NITROGEN_ISOTOPES: &[Isotope] = &[
+    Isotope::new(14, 14.00307, 0.99636),
+    Isotope::new(15, 15.00011, 0.00364),
+];
+const OXYGEN_ISOTOPES: &[Isotope] = &[
+    Isotope::new(16, 15.99492, 0.99757),
+    Isotope::new(17, 16.99913, 0.00038),
+    Isotope::new(18, 17.99916, 0.00205),
+];
+const FLUORINE_ISOTOPES: &[Isotope] = &[Isotope::new(19, 18.9984, 1.0)];
+const SODIUM_ISOTOPES: &[Isotope] = &[Isotope::new(23, 22.98977, 1.0)];
+const SILICON_ISOTOPES: &[Isotope] = &[
+    Isotope::new(28, 27.97693, 0.92223),
+    Isotope::new(29, 28.97649, 0.04685),
+    Isotope::new(30, 29.97377, 0.03092),
+];
+const PHOSPHORUS_ISOTOPES: &[Isotope] = &[Isotope::new(31, 30.97376, 1.0)];
+const SULFUR_ISOTOPES: &[Isotope] = &[
+    Isotope::new(32, 31.97207, 0.9499),
+    Isotope::new(33, 32.97146, 0.0075),
+    Isotope::new(34, 33.96787, 0.0425),
+    Isotope::new(36, 35.96708, 0.0001),
+];
+const CHLORINE_ISOTOPES: &[Isotope] = &[
+    Isotope::new(35, 34.96885, 0.7576),
+    Isotope::new(37, 36.9659, 0.2424),
+];
+const BROMINE_ISOTOPES: &[Isotope] = &[
+    Isotope::new(79, 78.91834, 0.5069),
+    Isotope::new(81, 80.91629, 0.4931),
+];
+const IODINE_ISOTOPES: &[Isotope] = &[Isotope::new(127, 126.9045, 1.0)];
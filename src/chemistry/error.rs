@@ -0,0 +1,21 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("unknown element symbol \"{0}\"")]
+    UnknownSymbol(String),
+    #[error("unexpected character '{0}' in label")]
+    UnexpectedCharacter(char),
+    #[error("unbalanced parentheses in label")]
+    UnbalancedParentheses,
+    #[error("condensed labels can't be written as SMILES")]
+    CondensedLabel,
+    #[error("unknown element \"{0}\", new symbols need like=<symbol> to copy")]
+    UnknownElement(String),
+    #[error("unknown element field \"{0}\"")]
+    UnknownElementField(String),
+    #[error("invalid {0} \"{1}\"")]
+    InvalidElementValue(String, String),
+    #[error("expected field=value, found \"{0}\"")]
+    InvalidElementEntry(String),
+}
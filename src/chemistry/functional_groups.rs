@@ -0,0 +1,338 @@
+use anyhow::{Context, Result};
+
+use crate::molecule::{AtomId, Molecule};
+
+use super::{Composition, MolecularGraph};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FunctionalGroup {
+    CarboxylicAcid,
+    Ester,
+    Amide,
+    Aldehyde,
+    Ketone,
+    Alcohol,
+    Enol,
+    PrimaryAmine,
+    SecondaryAmine,
+    TertiaryAmine,
+    Nitrile,
+    Thiol,
+    Ether,
+    Alkene,
+    Alkyne,
+    Halide,
+}
+
+impl FunctionalGroup {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::CarboxylicAcid => "carboxylic acid",
+            Self::Ester => "ester",
+            Self::Amide => "amide",
+            Self::Aldehyde => "aldehyde",
+            Self::Ketone => "ketone",
+            Self::Alcohol => "alcohol",
+            Self::Enol => "enol/phenol",
+            Self::PrimaryAmine => "primary amine",
+            Self::SecondaryAmine => "secondary amine",
+            Self::TertiaryAmine => "tertiary amine",
+            Self::Nitrile => "nitrile",
+            Self::Thiol => "thiol",
+            Self::Ether => "ether",
+            Self::Alkene => "alkene",
+            Self::Alkyne => "alkyne",
+            Self::Halide => "halide",
+        }
+    }
+
+    /// typical pKa of the most acidic proton, or of the conjugate acid for amines
+    pub fn pka(&self) -> Option<f32> {
+        match self {
+            Self::CarboxylicAcid => Some(4.8),
+            Self::Ester => Some(25.0),
+            Self::Amide => Some(17.0),
+            Self::Aldehyde => Some(17.0),
+            Self::Ketone => Some(19.0),
+            Self::Alcohol => Some(16.0),
+            Self::Enol => Some(10.0),
+            Self::PrimaryAmine => Some(10.6),
+            Self::SecondaryAmine => Some(11.0),
+            Self::TertiaryAmine => Some(9.8),
+            Self::Thiol => Some(10.5),
+            Self::Alkyne => Some(25.0),
+            Self::Nitrile | Self::Ether | Self::Alkene | Self::Halide => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionalGroupMatch {
+    pub group: FunctionalGroup,
+    pub atoms: Vec<AtomId>,
+}
+
+/// finds all functional groups in a molecule, each group is reported once per set of atoms
+pub fn find_functional_groups(molecule: &Molecule) -> Result<Vec<FunctionalGroupMatch>> {
+    let graph = MolecularGraph::new(molecule).context("while finding functional groups")?;
+
+    Ok(find_functional_groups_in(&graph))
+}
+
+/// finds all functional groups in a molecule's graph, for callers that have already built it
+pub fn find_functional_groups_in(graph: &MolecularGraph) -> Vec<FunctionalGroupMatch> {
+    let mut matches: Vec<FunctionalGroupMatch> = vec![];
+
+    let mut push_match = |group: FunctionalGroup, atoms: Vec<AtomId>| {
+        let duplicate = matches.iter().any(|found| {
+            found.group == group
+                && found.atoms.len() == atoms.len()
+                && found.atoms.iter().all(|atom_id| atoms.contains(atom_id))
+        });
+
+        if !duplicate {
+            matches.push(FunctionalGroupMatch { group, atoms });
+        }
+    };
+
+    for (index, atom) in graph.atoms() {
+        for pattern in PATTERNS {
+            if let Some(neighbours) = pattern.matches(graph, index) {
+                let atoms = [atom.atom_id]
+                    .into_iter()
+                    .chain(neighbours.into_iter().map(|n| graph.atom(n).atom_id))
+                    .collect();
+                push_match(pattern.group, atoms);
+            }
+        }
+
+        // groups written out as a condensed label, e.g. "CO2H"
+        for (label, group) in LABEL_GROUPS {
+            if Composition::parse(label).is_ok_and(|composition| composition == atom.composition) {
+                push_match(*group, vec![atom.atom_id]);
+            }
+        }
+    }
+
+    matches
+}
+
+const LABEL_GROUPS: &[(&str, FunctionalGroup)] = &[
+    ("CO2H", FunctionalGroup::CarboxylicAcid),
+    ("COOH", FunctionalGroup::CarboxylicAcid),
+    ("CHO", FunctionalGroup::Aldehyde),
+    ("CN", FunctionalGroup::Nitrile),
+    ("CONH2", FunctionalGroup::Amide),
+    ("CH2OH", FunctionalGroup::Alcohol),
+];
+
+#[derive(Debug, Clone, Copy)]
+enum Hydrogens {
+    Any,
+    Exactly(u32),
+    AtLeast(u32),
+}
+
+/// constraints on a single atom, similar to a SMARTS atom primitive
+#[derive(Debug, Clone, Copy)]
+struct AtomSpec {
+    /// allowed attachment elements, empty allows any
+    elements: &'static [&'static str],
+    hydrogens: Hydrogens,
+    /// whether the atom has only single bonds
+    saturated: Option<bool>,
+    /// whether the atom is double bonded to an oxygen
+    carbonyl: Option<bool>,
+}
+
+impl AtomSpec {
+    const fn element(elements: &'static [&'static str]) -> Self {
+        Self {
+            elements,
+            hydrogens: Hydrogens::Any,
+            saturated: None,
+            carbonyl: None,
+        }
+    }
+
+    const fn hydrogens(self, hydrogens: Hydrogens) -> Self {
+        Self { hydrogens, ..self }
+    }
+
+    const fn saturated(self, saturated: bool) -> Self {
+        Self {
+            saturated: Some(saturated),
+            ..self
+        }
+    }
+
+    const fn carbonyl(self, carbonyl: bool) -> Self {
+        Self {
+            carbonyl: Some(carbonyl),
+            ..self
+        }
+    }
+
+    fn matches(&self, graph: &MolecularGraph, index: usize) -> bool {
+        let symbol = graph.atom(index).composition.attachment().symbol;
+
+        if !self.elements.is_empty() && !self.elements.contains(&symbol) {
+            return false;
+        }
+        if symbol == "H" {
+            return false;
+        }
+
+        let hydrogens = graph.hydrogens(index);
+        let hydrogens_match = match self.hydrogens {
+            Hydrogens::Any => true,
+            Hydrogens::Exactly(n) => hydrogens == n,
+            Hydrogens::AtLeast(n) => hydrogens >= n,
+        };
+
+        let saturated = graph.max_bond_order(index) <= 1;
+        let carbonyl = graph.neighbours(index).any(|(neighbour, bond)| {
+            bond.order == 2
+                && graph.atom(neighbour).composition.attachment().symbol == "O"
+        });
+
+        hydrogens_match
+            && self.saturated.is_none_or(|value| value == saturated)
+            && self.carbonyl.is_none_or(|value| value == carbonyl)
+    }
+}
+
+/// a central atom and the neighbours it must be bonded to, similar to a SMARTS pattern
+struct Pattern {
+    group: FunctionalGroup,
+    center: AtomSpec,
+    /// bond order and spec of each required neighbour
+    neighbours: &'static [(u8, AtomSpec)],
+}
+
+impl Pattern {
+    /// returns the neighbour indices matched to the pattern's neighbours
+    fn matches(&self, graph: &MolecularGraph, index: usize) -> Option<Vec<usize>> {
+        if !self.center.matches(graph, index) {
+            return None;
+        }
+
+        let candidates: Vec<(usize, u8)> = graph
+            .neighbours(index)
+            .map(|(neighbour, bond)| (neighbour, bond.order))
+            .collect();
+
+        let mut assigned = vec![];
+        self.assign(graph, &candidates, &mut assigned)
+            .then_some(assigned)
+    }
+
+    /// backtracking search assigning each pattern neighbour to a distinct graph neighbour
+    fn assign(&self, graph: &MolecularGraph, candidates: &[(usize, u8)], assigned: &mut Vec<usize>) -> bool {
+        let Some((order, spec)) = self.neighbours.get(assigned.len()) else {
+            return true;
+        };
+
+        for (neighbour, bond_order) in candidates {
+            if assigned.contains(neighbour) || bond_order != order || !spec.matches(graph, *neighbour) {
+                continue;
+            }
+
+            assigned.push(*neighbour);
+            if self.assign(graph, candidates, assigned) {
+                return true;
+            }
+            assigned.pop();
+        }
+
+        false
+    }
+}
+
+const CARBON: AtomSpec = AtomSpec::element(&["C"]);
+const OXYGEN: AtomSpec = AtomSpec::element(&["O"]);
+const NITROGEN: AtomSpec = AtomSpec::element(&["N"]);
+const ALKYL: AtomSpec = CARBON.carbonyl(false);
+
+const PATTERNS: &[Pattern] = &[
+    Pattern {
+        group: FunctionalGroup::CarboxylicAcid,
+        center: CARBON,
+        neighbours: &[(2, OXYGEN), (1, OXYGEN.hydrogens(Hydrogens::AtLeast(1)))],
+    },
+    Pattern {
+        group: FunctionalGroup::Ester,
+        center: CARBON,
+        neighbours: &[(2, OXYGEN), (1, OXYGEN.hydrogens(Hydrogens::Exactly(0)))],
+    },
+    Pattern {
+        group: FunctionalGroup::Amide,
+        center: CARBON,
+        neighbours: &[(2, OXYGEN), (1, NITROGEN)],
+    },
+    Pattern {
+        group: FunctionalGroup::Aldehyde,
+        center: CARBON.hydrogens(Hydrogens::AtLeast(1)),
+        neighbours: &[(2, OXYGEN)],
+    },
+    Pattern {
+        group: FunctionalGroup::Ketone,
+        center: CARBON.hydrogens(Hydrogens::Exactly(0)),
+        neighbours: &[(2, OXYGEN), (1, CARBON), (1, CARBON)],
+    },
+    Pattern {
+        group: FunctionalGroup::Alcohol,
+        center: OXYGEN.hydrogens(Hydrogens::Exactly(1)),
+        neighbours: &[(1, ALKYL.saturated(true))],
+    },
+    Pattern {
+        group: FunctionalGroup::Enol,
+        center: OXYGEN.hydrogens(Hydrogens::Exactly(1)),
+        neighbours: &[(1, ALKYL.saturated(false))],
+    },
+    Pattern {
+        group: FunctionalGroup::PrimaryAmine,
+        center: NITROGEN.hydrogens(Hydrogens::Exactly(2)).saturated(true),
+        neighbours: &[(1, ALKYL)],
+    },
+    Pattern {
+        group: FunctionalGroup::SecondaryAmine,
+        center: NITROGEN.hydrogens(Hydrogens::Exactly(1)).saturated(true),
+        neighbours: &[(1, ALKYL), (1, ALKYL)],
+    },
+    Pattern {
+        group: FunctionalGroup::TertiaryAmine,
+        center: NITROGEN.hydrogens(Hydrogens::Exactly(0)).saturated(true),
+        neighbours: &[(1, ALKYL), (1, ALKYL), (1, ALKYL)],
+    },
+    Pattern {
+        group: FunctionalGroup::Nitrile,
+        center: CARBON,
+        neighbours: &[(3, NITROGEN)],
+    },
+    Pattern {
+        group: FunctionalGroup::Thiol,
+        center: AtomSpec::element(&["S"]).hydrogens(Hydrogens::Exactly(1)),
+        neighbours: &[(1, CARBON)],
+    },
+    Pattern {
+        group: FunctionalGroup::Ether,
+        center: OXYGEN.hydrogens(Hydrogens::Exactly(0)),
+        neighbours: &[(1, ALKYL), (1, ALKYL)],
+    },
+    Pattern {
+        group: FunctionalGroup::Alkene,
+        center: CARBON,
+        neighbours: &[(2, CARBON)],
+    },
+    Pattern {
+        group: FunctionalGroup::Alkyne,
+        center: CARBON,
+        neighbours: &[(3, CARBON)],
+    },
+    Pattern {
+        group: FunctionalGroup::Halide,
+        center: AtomSpec::element(&["F", "Cl", "Br", "I"]),
+        neighbours: &[(1, CARBON)],
+    },
+];
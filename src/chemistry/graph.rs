@@ -0,0 +1,163 @@
+use anyhow::{Context, Result};
+use rustc_hash::FxHashMap;
+
+use crate::molecule::{AtomId, BondType, Molecule};
+
+use super::Composition;
+
+/// index based view of a molecule's connectivity used for chemical analysis
+#[derive(Debug, Clone)]
+pub struct MolecularGraph {
+    atoms: Vec<GraphAtom>,
+    bonds: Vec<GraphBond>,
+    /// donor and acceptor of each dative bond, which aren't part of the connectivity
+    datives: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphAtom {
+    pub atom_id: AtomId,
+    pub composition: Composition,
+    pub map: Option<u32>,
+    /// indices into the graph's bonds
+    pub bonds: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GraphBond {
+    pub atoms: (usize, usize),
+    pub order: u8,
+}
+
+impl MolecularGraph {
+    pub fn new(molecule: &Molecule) -> Result<Self> {
+        let mut indices = FxHashMap::default();
+        let mut atoms = vec![];
+
+        for (atom_id, atom) in molecule.atoms() {
+            let composition = Composition::parse(&atom.label())
+                .with_context(|| format!("while parsing label \"{}\"", atom.label()))
+                .context("while building molecular graph")?;
+
+            indices.insert(*atom_id, atoms.len());
+            atoms.push(GraphAtom {
+                atom_id: *atom_id,
+                composition,
+                map: atom.map(),
+                bonds: vec![],
+            });
+        }
+
+        let mut bonds = vec![];
+        let mut datives = vec![];
+
+        for (_bond_id, bond) in molecule.bonds() {
+            if bond.bond_type() == BondType::Dative {
+                datives.push((indices[&bond.start()], indices[&bond.end()]));
+            }
+
+            let order = bond.bond_type().order();
+            // non-covalent bonds take no part in the connectivity
+            if order == 0 {
+                continue;
+            }
+
+            let start = indices[&bond.start()];
+            let end = indices[&bond.end()];
+
+            atoms[start].bonds.push(bonds.len());
+            atoms[end].bonds.push(bonds.len());
+            bonds.push(GraphBond {
+                atoms: (start, end),
+                order,
+            });
+        }
+
+        Ok(Self { atoms, bonds, datives })
+    }
+
+    pub fn atoms(&self) -> impl Iterator<Item = (usize, &GraphAtom)> {
+        self.atoms.iter().enumerate()
+    }
+
+    pub fn bonds(&self) -> impl Iterator<Item = (usize, &GraphBond)> {
+        self.bonds.iter().enumerate()
+    }
+
+    pub fn atom(&self, index: usize) -> &GraphAtom {
+        &self.atoms[index]
+    }
+
+    pub fn bond(&self, index: usize) -> &GraphBond {
+        &self.bonds[index]
+    }
+
+    /// atoms bonded to the atom paired with the bond connecting them
+    pub fn neighbours(&self, index: usize) -> impl Iterator<Item = (usize, &GraphBond)> {
+        self.atoms[index].bonds.iter().map(move |bond_index| {
+            let bond = &self.bonds[*bond_index];
+            let neighbour = if bond.atoms.0 == index { bond.atoms.1 } else { bond.atoms.0 };
+
+            (neighbour, bond)
+        })
+    }
+
+    /// atoms giving a pair of electrons to the atom through dative bonds
+    pub fn donors(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        self.datives.iter().filter(move |(_, acceptor)| *acceptor == index).map(|(donor, _)| *donor)
+    }
+
+    pub fn index_of(&self, atom_id: &AtomId) -> Option<usize> {
+        self.atoms.iter().position(|atom| atom.atom_id == *atom_id)
+    }
+
+    /// sum of the orders of bonds to the atom
+    pub fn bond_valence(&self, index: usize) -> u32 {
+        self.neighbours(index).map(|(_, bond)| bond.order as u32).sum()
+    }
+
+    pub fn hydrogens(&self, index: usize) -> u32 {
+        self.atoms[index].composition.hydrogens(self.bond_valence(index))
+    }
+
+    /// number of non-hydrogen atoms attached, counting atoms written in the label as one
+    pub fn heavy_degree(&self, index: usize) -> usize {
+        let atom = &self.atoms[index];
+
+        atom.bonds.len() + atom.composition.has_substituents() as usize
+    }
+
+    pub fn max_bond_order(&self, index: usize) -> u8 {
+        self.neighbours(index).map(|(_, bond)| bond.order).max().unwrap_or(0)
+    }
+
+    /// a bond is in a ring if its atoms are still connected without it
+    pub fn is_ring_bond(&self, bond_index: usize) -> bool {
+        let (start, end) = self.bonds[bond_index].atoms;
+
+        let mut visited = vec![false; self.atoms.len()];
+        let mut stack = vec![start];
+        visited[start] = true;
+
+        while let Some(current) = stack.pop() {
+            for &next_bond in &self.atoms[current].bonds {
+                if next_bond == bond_index {
+                    continue;
+                }
+
+                let (a, b) = self.bonds[next_bond].atoms;
+                let neighbour = if a == current { b } else { a };
+
+                if neighbour == end {
+                    return true;
+                }
+                if !visited[neighbour] {
+                    visited[neighbour] = true;
+                    stack.push(neighbour);
+                }
+            }
+        }
+
+        false
+    }
+}
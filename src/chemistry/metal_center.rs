@@ -0,0 +1,105 @@
+use std::fmt;
+
+use anyhow::{Context, Result};
+
+use crate::molecule::{AtomId, Molecule};
+
+use super::{Element, MolecularGraph};
+
+/// a metal atom's oxidation state and electron counts by the ionic model, where the ligand keeps
+/// both electrons of a covalent bond to the metal and a dative bond's donor gives a pair
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MetalCenter {
+    pub element: Element,
+    pub oxidation_state: i32,
+    /// d electrons left on a transition metal, none for other metals or impossibly high
+    /// oxidation states
+    pub d_electrons: Option<u8>,
+    /// valence electrons around a transition metal, its d electrons and those its ligands give,
+    /// for checking the 18 electron rule
+    pub electron_count: Option<u32>,
+}
+
+impl MetalCenter {
+    /// the count for an atom, none when it isn't a metal
+    pub fn new(molecule: &Molecule, atom_id: &AtomId) -> Result<Option<Self>> {
+        let graph = MolecularGraph::new(molecule).context("while counting metal electrons")?;
+
+        Ok(graph.index_of(atom_id).and_then(|index| Self::from_graph(&graph, index)))
+    }
+
+    pub fn from_graph(graph: &MolecularGraph, index: usize) -> Option<Self> {
+        let composition = &graph.atom(index).composition;
+        let element = composition.attachment();
+        if !element.is_metal() {
+            return None;
+        }
+
+        // X type ligands take one electron from the metal per bond order, bonds between metals
+        // leave each with one
+        let mut oxidation_state = composition.charge();
+        let mut ligand_electrons = 0;
+        for (neighbour, bond) in graph.neighbours(index) {
+            let order = bond.order as i32;
+            match graph.atom(neighbour).composition.attachment().is_metal() {
+                true => ligand_electrons += order,
+                false => {
+                    oxidation_state += order;
+                    ligand_electrons += 2 * order;
+                }
+            }
+        }
+        // L type ligands give a pair without changing the oxidation state
+        ligand_electrons += 2 * graph.donors(index).count() as i32;
+
+        let d_electrons = match (element.is_transition_metal(), element.group()) {
+            (true, Some(group)) => u8::try_from(group as i32 - oxidation_state).ok(),
+            _ => None,
+        };
+        let electron_count = d_electrons.and_then(|d_electrons| u32::try_from(d_electrons as i32 + ligand_electrons).ok());
+
+        Some(Self {
+            element,
+            oxidation_state,
+            d_electrons,
+            electron_count,
+        })
+    }
+}
+
+/// e.g. "Fe(II), d6, 18 e"
+impl fmt::Display for MetalCenter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({})", self.element.symbol, roman_numeral(self.oxidation_state))?;
+        if let Some(d_electrons) = self.d_electrons {
+            write!(f, ", d{}", d_electrons)?;
+        }
+        if let Some(electron_count) = self.electron_count {
+            write!(f, ", {} e", electron_count)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// oxidation states are written in roman numerals, with a minus sign for negative ones and 0 for
+/// zero
+fn roman_numeral(number: i32) -> String {
+    const NUMERALS: [(u32, &str); 4] = [(10, "X"), (9, "IX"), (5, "V"), (4, "IV")];
+
+    if number == 0 {
+        return "0".to_string();
+    }
+
+    let mut remaining = number.unsigned_abs();
+    let mut numeral = if number < 0 { "−".to_string() } else { String::new() };
+    for (value, letters) in NUMERALS {
+        while remaining >= value {
+            numeral.push_str(letters);
+            remaining -= value;
+        }
+    }
+    numeral.push_str(&"I".repeat(remaining as usize));
+
+    numeral
+}
@@ -0,0 +1,168 @@
+use rustc_hash::FxHashMap;
+
+use super::{Error, MolecularGraph};
+
+/// elements that can be written without brackets when their hydrogens are implied
+const ORGANIC_SUBSET: &[&str] = &["B", "C", "N", "O", "P", "S", "F", "Cl", "Br", "I"];
+
+/// writes a SMILES string for the graph, disconnected fragments are separated by '.'
+///
+/// atoms are visited in graph order so the output is repeatable but not canonical
+pub fn to_smiles(graph: &MolecularGraph) -> Result<String, Error> {
+    let atom_count = graph.atoms().count();
+    let mut visited = vec![false; atom_count];
+    let mut ring_bonds = vec![];
+    let mut fragments = vec![];
+
+    for root in 0..atom_count {
+        if visited[root] {
+            continue;
+        }
+
+        find_ring_bonds(graph, root, None, &mut visited, &mut ring_bonds);
+    }
+
+    let mut written = vec![false; atom_count];
+    let mut open_rings = FxHashMap::default();
+
+    for root in 0..atom_count {
+        if written[root] {
+            continue;
+        }
+
+        let mut smiles = String::new();
+        write_atom(graph, root, None, &ring_bonds, &mut written, &mut open_rings, &mut smiles)?;
+        fragments.push(smiles);
+    }
+
+    Ok(fragments.join("."))
+}
+
+/// marks the bonds that close rings, which are those a depth first walk finds leading back to
+/// an atom it has already visited, walking in the same order atoms are written
+fn find_ring_bonds(
+    graph: &MolecularGraph,
+    index: usize,
+    via: Option<usize>,
+    visited: &mut [bool],
+    ring_bonds: &mut Vec<usize>,
+) {
+    visited[index] = true;
+
+    for &bond in &graph.atom(index).bonds {
+        if Some(bond) == via || ring_bonds.contains(&bond) {
+            continue;
+        }
+
+        let (a, b) = graph.bond(bond).atoms;
+        let neighbour = if a == index { b } else { a };
+
+        if visited[neighbour] {
+            ring_bonds.push(bond);
+        } else {
+            find_ring_bonds(graph, neighbour, Some(bond), visited, ring_bonds);
+        }
+    }
+}
+
+fn write_atom(
+    graph: &MolecularGraph,
+    index: usize,
+    via: Option<usize>,
+    ring_bonds: &[usize],
+    written: &mut [bool],
+    open_rings: &mut FxHashMap<usize, u32>,
+    smiles: &mut String,
+) -> Result<(), Error> {
+    written[index] = true;
+    smiles.push_str(&atom_symbol(graph, index)?);
+
+    for &bond in &graph.atom(index).bonds {
+        if !ring_bonds.contains(&bond) {
+            continue;
+        }
+
+        match open_rings.remove(&bond) {
+            Some(digit) => {
+                smiles.push_str(bond_symbol(graph.bond(bond).order));
+                smiles.push_str(&ring_digit(digit));
+            }
+            None => {
+                let digit = (1..).find(|digit| !open_rings.values().any(|open| open == digit)).unwrap_or(1);
+                open_rings.insert(bond, digit);
+                smiles.push_str(&ring_digit(digit));
+            }
+        }
+    }
+
+    let branches = graph
+        .atom(index)
+        .bonds
+        .iter()
+        .filter(|bond| Some(**bond) != via && !ring_bonds.contains(bond))
+        .filter_map(|&bond| {
+            let (a, b) = graph.bond(bond).atoms;
+            let neighbour = if a == index { b } else { a };
+            (!written[neighbour]).then_some((bond, neighbour))
+        })
+        .collect::<Vec<_>>();
+
+    for (i, (bond, neighbour)) in branches.iter().enumerate() {
+        // another branch may have reached this atom first
+        if written[*neighbour] {
+            continue;
+        }
+
+        let last = i + 1 == branches.len();
+        if !last {
+            smiles.push('(');
+        }
+        smiles.push_str(bond_symbol(graph.bond(*bond).order));
+        write_atom(graph, *neighbour, Some(*bond), ring_bonds, written, open_rings, smiles)?;
+        if !last {
+            smiles.push(')');
+        }
+    }
+
+    Ok(())
+}
+
+fn atom_symbol(graph: &MolecularGraph, index: usize) -> Result<String, Error> {
+    let composition = &graph.atom(index).composition;
+    if composition.has_substituents() {
+        return Err(Error::CondensedLabel);
+    }
+
+    let attachment = composition.attachment();
+    let hydrogens = graph.hydrogens(index);
+    let implied = (attachment.valence as u32).saturating_sub(graph.bond_valence(index));
+    let map = graph.atom(index).map;
+    if hydrogens == implied && map.is_none() && ORGANIC_SUBSET.contains(&attachment.symbol) {
+        return Ok(attachment.symbol.to_string());
+    }
+
+    // mapped atoms are always bracketed to write their number, e.g. "[CH3:1]"
+    let symbol = attachment.symbol;
+    let map = map.map(|map| format!(":{}", map)).unwrap_or_default();
+    Ok(match hydrogens {
+        0 => format!("[{}{}]", symbol, map),
+        1 => format!("[{}H{}]", symbol, map),
+        hydrogens => format!("[{}H{}{}]", symbol, hydrogens, map),
+    })
+}
+
+fn bond_symbol(order: u8) -> &'static str {
+    match order {
+        2 => "=",
+        3 => "#",
+        4 => "$",
+        _ => "",
+    }
+}
+
+fn ring_digit(digit: u32) -> String {
+    match digit {
+        0..=9 => digit.to_string(),
+        _ => format!("%{}", digit),
+    }
+}
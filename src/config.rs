@@ -0,0 +1,10 @@
+use std::path::PathBuf;
+
+/// the app's directory in the user's config directory, which may not exist yet
+pub fn dir() -> Option<PathBuf> {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(config.join("molecular-canvas"))
+}
@@ -0,0 +1,113 @@
+use iced::advanced::layout::{self, Layout};
+use iced::advanced::renderer;
+use iced::advanced::widget::{tree, Operation, Tree, Widget};
+use iced::advanced::{Clipboard, Shell};
+use iced::widget::text_input::{cursor, State, TextInput, Value};
+use iced::{event, mouse, Element, Event, Length, Rectangle, Renderer, Size, Theme};
+
+type Paragraph = <Renderer as iced::advanced::text::Renderer>::Paragraph;
+
+/// a text input that reports the text its cursor covers whenever the cursor moves, as the cursor
+/// can't be read from outside the input and text inserted from buttons belongs there
+pub struct CursorInput<'a, Message> {
+    input: TextInput<'a, Message>,
+    value: Value,
+    on_cursor: Box<dyn Fn(usize, usize) -> Message + 'a>,
+}
+
+impl<'a, Message: Clone> CursorInput<'a, Message> {
+    /// the cursor is given as the start and end of its selection, in characters, which are the
+    /// same when nothing is selected
+    pub fn new(input: TextInput<'a, Message>, value: &str, on_cursor: impl Fn(usize, usize) -> Message + 'a) -> Self {
+        Self {
+            input,
+            value: Value::new(value),
+            on_cursor: Box::new(on_cursor),
+        }
+    }
+
+    fn cursor(&self, tree: &Tree) -> (usize, usize) {
+        match tree.state.downcast_ref::<State<Paragraph>>().cursor().state(&self.value) {
+            cursor::State::Index(index) => (index, index),
+            cursor::State::Selection { start, end } => (start.min(end), start.max(end)),
+        }
+    }
+}
+
+impl<Message: Clone> Widget<Message, Theme, Renderer> for CursorInput<'_, Message> {
+    fn tag(&self) -> tree::Tag {
+        Widget::<Message, Theme, Renderer>::tag(&self.input)
+    }
+
+    fn state(&self) -> tree::State {
+        Widget::<Message, Theme, Renderer>::state(&self.input)
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        Widget::<Message, Theme, Renderer>::diff(&self.input, tree);
+    }
+
+    fn size(&self) -> Size<Length> {
+        Widget::<Message, Theme, Renderer>::size(&self.input)
+    }
+
+    fn layout(&self, tree: &mut Tree, renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        Widget::<Message, Theme, Renderer>::layout(&self.input, tree, renderer, limits)
+    }
+
+    fn operate(&self, tree: &mut Tree, layout: Layout<'_>, renderer: &Renderer, operation: &mut dyn Operation) {
+        Widget::<Message, Theme, Renderer>::operate(&self.input, tree, layout, renderer, operation);
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) -> event::Status {
+        let before = self.cursor(tree);
+        let status = self.input.on_event(tree, event, layout, cursor, renderer, clipboard, shell, viewport);
+
+        let after = self.cursor(tree);
+        if after != before {
+            shell.publish((self.on_cursor)(after.0, after.1));
+        }
+
+        status
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        Widget::<Message, Theme, Renderer>::draw(&self.input, tree, renderer, theme, style, layout, cursor, viewport);
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        Widget::<Message, Theme, Renderer>::mouse_interaction(&self.input, tree, layout, cursor, viewport, renderer)
+    }
+}
+
+impl<'a, Message: Clone + 'a> From<CursorInput<'a, Message>> for Element<'a, Message> {
+    fn from(cursor_input: CursorInput<'a, Message>) -> Self {
+        Element::new(cursor_input)
+    }
+}
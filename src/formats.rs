@@ -0,0 +1,359 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use iced::{Point, Rectangle, Size, Vector};
+use rustc_hash::FxHashMap;
+
+use crate::molecule::{BondType, Molecule};
+
+mod chemfig;
+mod condensed;
+mod error;
+mod helm;
+mod inchi;
+mod layout;
+mod mol2;
+mod molfile;
+mod raster;
+mod reaction;
+mod recognition;
+mod rxn;
+mod sequence;
+mod smiles;
+mod svg;
+
+pub use chemfig::{is_chemfig, parse_chemfig};
+pub use condensed::parse_condensed;
+pub use error::Error;
+pub use helm::{is_helm, parse_helm, write_helm};
+pub use inchi::write_inchi;
+pub use mol2::{is_mol2, parse_mol2};
+pub use molfile::{parse_molfile, write_molfile};
+pub use raster::{bitmap_to_png, Bitmap, Thumbnailable};
+pub use reaction::{is_reaction_smiles, parse_reaction_smiles, write_reaction_smiles, Reaction, ReactionConditions};
+pub use recognition::{recognize_png, recognize_structures};
+pub use rxn::{is_rxn, parse_rxn, write_rdf, write_rxn};
+pub use sequence::{Sequence, SequenceKind};
+pub use smiles::parse_smiles;
+pub use svg::{embedded_project, SvgWriter};
+
+/// atoms and bonds read from a file, before they are placed on the canvas
+#[derive(Debug, Clone, Default)]
+pub struct Structure {
+    /// labels and positions, with the y axis pointing down as on the canvas
+    pub atoms: Vec<(String, Point)>,
+    /// indices into the atoms
+    pub bonds: Vec<(usize, usize, BondType)>,
+    /// atom map numbers by atom index, matching atoms across a reaction
+    pub maps: FxHashMap<usize, u32>,
+}
+
+impl Structure {
+    /// every atom and bond of the molecules, at their positions on the canvas
+    pub fn from_molecules<'a>(molecules: impl Iterator<Item = &'a Molecule>) -> Result<Self> {
+        let mut structure = Self::default();
+
+        for molecule in molecules {
+            let mut indices = FxHashMap::default();
+            for (atom_id, atom) in molecule.atoms() {
+                indices.insert(*atom_id, structure.atoms.len());
+                if let Some(map) = atom.map() {
+                    structure.maps.insert(structure.atoms.len(), map);
+                }
+                structure.atoms.push((atom.label(), molecule.atom_position(atom_id)?));
+            }
+
+            for (_, bond) in molecule.bonds() {
+                let (Some(start), Some(end)) = (indices.get(&bond.start()), indices.get(&bond.end())) else {
+                    continue;
+                };
+                structure.bonds.push((*start, *end, bond.bond_type()));
+            }
+        }
+
+        Ok(structure)
+    }
+
+    pub fn bounds(&self) -> Rectangle {
+        let mut points = self.atoms.iter().map(|(_, position)| *position);
+        let Some(first) = points.next() else {
+            return Rectangle::default();
+        };
+
+        points.fold(Rectangle::new(first, iced::Size::ZERO), |bounds, point| {
+            bounds.union(&Rectangle::new(point, iced::Size::ZERO))
+        })
+    }
+
+    /// scales the distances between atoms around the origin
+    pub fn scale(&mut self, factor: f32) {
+        for (_, position) in &mut self.atoms {
+            *position = Point::new(position.x * factor, position.y * factor);
+        }
+    }
+
+    pub fn translate(&mut self, translation: Vector) {
+        for (_, position) in &mut self.atoms {
+            *position = *position + translation;
+        }
+    }
+}
+
+/// structures read from pasted text
+#[derive(Debug, Clone)]
+pub struct PastedStructures {
+    pub structures: Vec<Structure>,
+    /// reactions laid out around arrows, pasted instead of the structures
+    pub reactions: Vec<Reaction>,
+    /// whether the text could as well be a word or label as SMILES, so pasting it is confirmed first
+    pub ambiguous: bool,
+}
+
+/// reads a HELM string, a chemfig snippet, a molfile, SD, MOL2, RXN or RDF file copied from
+/// another document, reaction SMILES, or else the first SMILES on each line of pasted text
+pub fn parse_pasted(text: &str) -> Result<PastedStructures, Error> {
+    if text.trim_start().starts_with("InChI=") {
+        return Err(Error::InchiUnsupported);
+    }
+    // HELM chains are pasted condensed so they copy back to the same string
+    if is_helm(text) {
+        return Ok(PastedStructures {
+            structures: parse_helm(text)?.iter().map(Sequence::condensed_structure).collect(),
+            reactions: vec![],
+            ambiguous: false,
+        });
+    }
+    if is_chemfig(text) {
+        return Ok(PastedStructures {
+            structures: parse_chemfig(text)?,
+            reactions: vec![],
+            ambiguous: false,
+        });
+    }
+    if is_mol2(text) {
+        return Ok(PastedStructures {
+            structures: parse_mol2(text)?,
+            reactions: vec![],
+            ambiguous: false,
+        });
+    }
+    // reaction files hold molfiles, so they're told apart first
+    if is_rxn(text) {
+        return Ok(PastedStructures {
+            structures: vec![],
+            reactions: parse_rxn(text)?,
+            ambiguous: false,
+        });
+    }
+    if text.contains("M  END") {
+        return Ok(PastedStructures {
+            structures: molfile::parse_sdf(text)?,
+            reactions: vec![],
+            ambiguous: false,
+        });
+    }
+    if is_reaction_smiles(text) {
+        return Ok(PastedStructures {
+            structures: vec![],
+            reactions: parse_reaction_smiles(text)?,
+            ambiguous: false,
+        });
+    }
+
+    let lines = text.lines().filter(|line| !line.trim().is_empty()).collect::<Vec<_>>();
+    let smiles = lines.iter().filter_map(|line| line.split_whitespace().next()).collect::<Vec<_>>();
+
+    let structures = smiles
+        .iter()
+        .map(|smiles| parse_smiles(smiles))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+    // text after the SMILES is dropped, and text with no bonds, branches, rings or brackets
+    // reads the same as a word
+    let trailing_text = lines.iter().any(|line| line.split_whitespace().nth(1).is_some());
+    let only_letters = smiles.iter().all(|smiles| smiles.chars().all(|c| c.is_ascii_alphabetic()));
+
+    Ok(PastedStructures {
+        structures,
+        reactions: vec![],
+        ambiguous: trailing_text || only_letters,
+    })
+}
+
+/// reads every structure in a file, choosing the format from its extension
+pub fn read_file(path: &Path) -> Result<Vec<Structure>> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    if !matches!(extension.as_str(), "mol" | "sdf" | "sd" | "mol2" | "smi" | "smiles" | "tex" | "svg" | "png") {
+        return Err(Error::UnsupportedExtension(extension)).context("while reading structure file");
+    }
+
+    // exported images carry the molfile they were drawn from
+    if extension == "png" {
+        let contents = std::fs::read(path).with_context(|| format!("while reading {}", path.display()))?;
+        let molfile = raster::embedded_molfile(&contents)
+            .context("while reading png")?
+            .ok_or(Error::NoEmbeddedStructure)
+            .context("while reading png")?;
+
+        return Ok(vec![molfile::parse_molfile(&molfile)?]);
+    }
+
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("while reading {}", path.display()))?;
+
+    let structures = match extension.as_str() {
+        "mol" => vec![molfile::parse_molfile(&contents)?],
+        "sdf" | "sd" => molfile::parse_sdf(&contents)?,
+        "mol2" => parse_mol2(&contents)?,
+        // every chemfig formula in a LaTeX document
+        "tex" => parse_chemfig(&contents)?,
+        "svg" => {
+            let molfile = svg::embedded_molfile(&contents)
+                .ok_or(Error::NoEmbeddedStructure)
+                .context("while reading svg")?;
+            vec![molfile::parse_molfile(molfile)?]
+        }
+        "smi" | "smiles" => contents
+            .lines()
+            .filter_map(|line| line.split_whitespace().next())
+            .map(parse_smiles)
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .flatten()
+            .collect(),
+        _ => unreachable!("extension checked above"),
+    };
+
+    Ok(structures)
+}
+
+/// whether a file holds reactions, which are read with [`read_reactions`] rather than as
+/// structures
+pub fn is_reaction_file(path: &Path) -> bool {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    matches!(extension.as_str(), "rsmi" | "rxn" | "rdf")
+}
+
+/// reads the reaction of an RXN file, every reaction of an RDF file or the reaction SMILES on
+/// each line of a file
+pub fn read_reactions(path: &Path) -> Result<Vec<Reaction>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("while reading {}", path.display()))?;
+
+    match is_rxn(&contents) {
+        true => parse_rxn(&contents).context("while reading RXN file"),
+        false => parse_reaction_smiles(&contents).context("while reading reaction SMILES"),
+    }
+}
+
+/// a png or jpeg to trace over, kept as a data url so it's drawn and exported as part of an svg
+#[derive(Debug, Clone)]
+pub struct ReferenceImage {
+    pub href: String,
+    /// size in pixels
+    pub size: Size,
+}
+
+/// reads a png or jpeg to trace over
+pub fn read_reference_image(path: &Path) -> Result<ReferenceImage> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    let mime = match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        _ => return Err(Error::UnsupportedReferenceImage(extension)).context("while reading reference image"),
+    };
+
+    let contents = std::fs::read(path).with_context(|| format!("while reading {}", path.display()))?;
+
+    reference_image(mime, &contents).context("while reading reference image")
+}
+
+/// a png pasted from the clipboard to trace over
+pub fn png_reference_image(png: &[u8]) -> Result<ReferenceImage> {
+    reference_image("image/png", png).context("while reading pasted image")
+}
+
+fn reference_image(mime: &str, contents: &[u8]) -> Result<ReferenceImage, Error> {
+    let size = imagesize::blob_size(contents).map_err(|_| Error::UnreadableImageSize)?;
+
+    Ok(ReferenceImage {
+        href: format!("data:{};base64,{}", mime, STANDARD.encode(contents)),
+        size: Size::new(size.width as f32, size.height as f32),
+    })
+}
+
+/// writes a drawing to an svg or png file, choosing the format from its extension, with the
+/// molfile embedded so the image can be opened again
+pub fn write_image(path: &Path, svg: String, molfile: &str, resolution: f32) -> Result<()> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    let contents = match extension.as_str() {
+        "svg" => svg.into_bytes(),
+        "png" => raster::svg_to_png(&svg, molfile, resolution).context("while rendering png")?,
+        _ => return Err(Error::UnsupportedImageExtension(extension)).context("while writing image"),
+    };
+
+    std::fs::write(path, contents).with_context(|| format!("while writing {}", path.display()))
+}
+
+/// renders a drawing for the clipboard at a number of pixels to a canvas unit
+pub fn render_bitmap(svg: &str, resolution: f32) -> Result<Bitmap> {
+    raster::svg_to_bitmap(svg, resolution).context("while rendering bitmap")
+}
+
+/// whether a file is an image a thumbnail is drawn from as it is, rather than a structure file
+/// whose structures are drawn first
+pub fn is_image_file(path: &Path) -> bool {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    matches!(extension.as_str(), "svg" | "png")
+}
+
+/// a small png preview of a drawing
+pub fn svg_thumbnail(svg: &str, side: u32) -> Result<Vec<u8>> {
+    raster::thumbnail(Thumbnailable::Svg(svg), side).context("while drawing thumbnail")
+}
+
+/// a small png preview of an svg or png image file
+pub fn thumbnail(path: &Path, side: u32) -> Result<Vec<u8>> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    let contents = std::fs::read(path).with_context(|| format!("while reading {}", path.display()))?;
+    let image = match extension.as_str() {
+        "svg" => Thumbnailable::Svg(std::str::from_utf8(&contents).context("while reading svg")?),
+        "png" => Thumbnailable::Png(&contents),
+        _ => return Err(Error::UnsupportedImageExtension(extension)).context("while drawing thumbnail"),
+    };
+
+    raster::thumbnail(image, side).context("while drawing thumbnail")
+}
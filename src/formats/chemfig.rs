@@ -0,0 +1,455 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+use iced::{Point, Vector};
+use rustc_hash::FxHashMap;
+
+use crate::canvas::MolCanvas;
+use crate::molecule::BondType;
+
+use super::{Error, Structure};
+
+const COMMAND: &str = "\\chemfig";
+/// ring atoms closer than this many bond lengths are the same atom, which closes each ring and
+/// joins fused rings along the bond they share
+const SAME_ATOM: f32 = 0.1;
+
+/// whether text holds a `\chemfig` command
+pub fn is_chemfig(text: &str) -> bool {
+    text.contains(COMMAND)
+}
+
+/// reads the molecule of each `\chemfig{...}` command in text, e.g. `\chemfig{*6(-=-=-=)}` or
+/// `\chemfig{H_3C-C(=[1]O)-[7]OH}`
+pub fn parse_chemfig(text: &str) -> Result<Vec<Structure>, Error> {
+    let mut structures = vec![];
+    let mut rest = text;
+
+    while let Some(start) = rest.find(COMMAND) {
+        rest = rest[start + COMMAND.len()..].trim_start();
+        // options such as `\chemfig[line width=1pt]{...}` change nothing read here
+        if rest.starts_with('[') {
+            let end = rest.find(']').ok_or(Error::UnbalancedChemfig)?;
+            rest = rest[end + 1..].trim_start();
+        }
+
+        let (formula, after) = braced(rest)?;
+        structures.push(parse_formula(formula)?);
+        rest = after;
+    }
+
+    Ok(structures)
+}
+
+/// the text inside the braces text starts with, and the text after them
+fn braced(text: &str) -> Result<(&str, &str), Error> {
+    if !text.starts_with('{') {
+        return Err(Error::UnbalancedChemfig);
+    }
+
+    let mut depth = 0;
+    for (index, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ => continue,
+        }
+        if depth == 0 {
+            return Ok((&text[1..index], &text[index + 1..]));
+        }
+    }
+
+    Err(Error::UnbalancedChemfig)
+}
+
+fn parse_formula(formula: &str) -> Result<Structure, Error> {
+    let mut parser = Parser {
+        chars: formula.chars().peekable(),
+        structure: Structure::default(),
+        hooks: FxHashMap::default(),
+    };
+
+    parser.chain(None, 0.0)?;
+    // a closing parenthesis with no branch to close
+    if parser.chars.next().is_some() {
+        return Err(Error::UnbalancedChemfig);
+    }
+
+    // chemfig has the y axis pointing up and bonds one unit long
+    let mut structure = parser.structure;
+    for (_, position) in &mut structure.atoms {
+        *position = Point::new(position.x * MolCanvas::BOND_LENGTH, -position.y * MolCanvas::BOND_LENGTH);
+    }
+
+    Ok(structure)
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    structure: Structure,
+    /// atoms waiting for the other end of a `?` bond, by the bond's name
+    hooks: FxHashMap<String, usize>,
+}
+
+/// how a ring is turned
+#[derive(Debug, Clone, Copy)]
+enum RingStart {
+    /// opening the formula, with its first bond going straight down
+    Alone,
+    /// carrying on in the direction of the bond at this angle that led to its first atom
+    Chain(f32),
+    /// sharing the bond at this angle that led to its first atom in another ring, and turning
+    /// the other way from it
+    Fused { angle: f32, turn: f32 },
+}
+
+impl Parser<'_> {
+    /// reads atoms, bonds, branches and rings up to the end of the formula or a closing
+    /// parenthesis, starting from an atom if there is one, each bond going off at the angle of
+    /// the one before unless it gives its own
+    fn chain(&mut self, mut previous: Option<usize>, mut angle: f32) -> Result<(), Error> {
+        loop {
+            self.skip_whitespace();
+            match self.chars.peek().copied() {
+                None | Some(')') => return Ok(()),
+                Some('(') => {
+                    self.chars.next();
+                    self.chain(previous, angle)?;
+                    self.expect(')')?;
+                }
+                Some('*') => {
+                    let ring_start = match previous {
+                        Some(_) => RingStart::Chain(angle),
+                        None => RingStart::Alone,
+                    };
+                    let start = match previous {
+                        Some(previous) => previous,
+                        None => self.add_atom(String::new(), Point::ORIGIN),
+                    };
+                    self.ring(start, ring_start)?;
+                    previous = Some(start);
+                }
+                Some('?') => {
+                    let atom = previous.ok_or(Error::UnexpectedChemfigCharacter('?'))?;
+                    self.hook(atom)?;
+                }
+                Some(c) if is_bond(c) => {
+                    let (bond_type, reversed) = self.bond();
+                    let (bond_angle, length) = self.bond_options(angle)?;
+                    angle = bond_angle;
+
+                    let start = match previous {
+                        Some(previous) => previous,
+                        None => self.add_atom(String::new(), Point::ORIGIN),
+                    };
+                    let position = self.structure.atoms[start].1 + direction(angle) * length;
+                    let label = self.label();
+                    let end = self.add_atom(label, position);
+                    self.push_bond(start, end, bond_type, reversed);
+                    previous = Some(end);
+                }
+                Some(c) => {
+                    let label = self.label();
+                    // atoms only start a formula or follow a bond
+                    if previous.is_some() || label.is_empty() && self.chars.peek() == Some(&c) {
+                        return Err(Error::UnexpectedChemfigCharacter(c));
+                    }
+                    previous = Some(self.add_atom(label, Point::ORIGIN));
+                }
+            }
+        }
+    }
+
+    /// reads a ring such as `*6(-=-=-=)` from the atom it starts at, going round its atoms
+    /// anticlockwise unless it's fused to another
+    fn ring(&mut self, start: usize, ring_start: RingStart) -> Result<(), Error> {
+        self.expect('*')?;
+        // rings drawn with a circle inside have their bonds written the same
+        if self.chars.peek() == Some(&'*') {
+            self.chars.next();
+        }
+        let mut size = String::new();
+        while let Some(digit) = self.chars.next_if(char::is_ascii_digit) {
+            size.push(digit);
+        }
+        let size = size.parse::<usize>().ok().filter(|size| *size >= 3).ok_or(Error::InvalidChemfigRing)?;
+        self.expect('(')?;
+
+        let step = 360.0 / size as f32;
+        let (first, turn) = match ring_start {
+            RingStart::Alone => (-90.0, 1.0),
+            RingStart::Chain(angle) => (angle - (90.0 - 180.0 / size as f32), 1.0),
+            RingStart::Fused { angle, turn } => (angle + turn * step, turn),
+        };
+        let bond_angle = |index: usize| first + turn * index as f32 * step;
+
+        let mut positions = vec![self.structure.atoms[start].1];
+        for index in 0..size - 1 {
+            positions.push(positions[index] + direction(bond_angle(index)));
+        }
+        let center = positions.iter().fold(Vector::ZERO, |sum, position| sum + (*position - Point::ORIGIN)) * (1.0 / size as f32);
+        let outward = |position: Point| {
+            let offset = position - (Point::ORIGIN + center);
+            offset.y.atan2(offset.x).to_degrees()
+        };
+
+        let mut vertex = 0;
+        let mut current = start;
+        loop {
+            self.skip_whitespace();
+            match self.chars.peek().copied() {
+                None => return Err(Error::UnbalancedChemfig),
+                Some(')') => {
+                    self.chars.next();
+                    return Ok(());
+                }
+                // branches point away from the middle of the ring
+                Some('(') => {
+                    self.chars.next();
+                    self.chain(Some(current), outward(positions[vertex % size]))?;
+                    self.expect(')')?;
+                }
+                Some('*') => {
+                    let ring_start = match vertex {
+                        0 => RingStart::Chain(outward(positions[0])),
+                        _ => RingStart::Fused {
+                            angle: bond_angle(vertex - 1),
+                            turn: -turn,
+                        },
+                    };
+                    self.ring(current, ring_start)?;
+                }
+                Some('?') => self.hook(current)?,
+                Some(c) if is_bond(c) && vertex < size => {
+                    let (bond_type, reversed) = self.bond();
+                    // a ring's bonds can't be turned or stretched
+                    self.bond_options(0.0)?;
+
+                    vertex += 1;
+                    let label = self.label();
+                    let end = self.ring_atom(label, positions[vertex % size]);
+                    self.push_bond(current, end, bond_type, reversed);
+                    current = end;
+                }
+                Some(c) => return Err(Error::UnexpectedChemfigCharacter(c)),
+            }
+        }
+    }
+
+    /// a bond's type, and whether it's written pointing back at the atom before, such as a wedge
+    /// widening towards it
+    fn bond(&mut self) -> (BondType, bool) {
+        match self.chars.next() {
+            Some('=') => (BondType::Normal(2), false),
+            Some('~') => (BondType::Normal(3), false),
+            Some(c @ ('<' | '>')) => {
+                let bond_type = match self.chars.next_if(|c| matches!(c, ':' | '|')) {
+                    Some(':') => BondType::Dash,
+                    _ => BondType::Wedge,
+                };
+                (bond_type, c == '>')
+            }
+            _ => (BondType::Normal(1), false),
+        }
+    }
+
+    /// the angle in degrees and length of a bond, from options such as `[2]`, `[:30]`, `[::60]`
+    /// or `[,1.5]`, the angle being in eighths of a turn, absolute or relative to `angle`
+    fn bond_options(&mut self, angle: f32) -> Result<(f32, f32), Error> {
+        if self.chars.peek() != Some(&'[') {
+            return Ok((angle, 1.0));
+        }
+        self.chars.next();
+
+        let mut options = String::new();
+        loop {
+            match self.chars.next() {
+                Some(']') => break,
+                Some(c) => options.push(c),
+                None => return Err(Error::UnbalancedChemfig),
+            }
+        }
+
+        let mut fields = options.split(',').map(str::trim);
+        let invalid = || Error::InvalidChemfigOptions(options.clone());
+        let bond_angle = match fields.next().unwrap_or_default() {
+            "" => angle,
+            field => match (field.strip_prefix("::"), field.strip_prefix(':')) {
+                (Some(relative), _) => angle + relative.parse::<f32>().map_err(|_| invalid())?,
+                (None, Some(absolute)) => absolute.parse::<f32>().map_err(|_| invalid())?,
+                (None, None) => field.parse::<f32>().map_err(|_| invalid())? * 45.0,
+            },
+        };
+        let length = match fields.next().unwrap_or_default() {
+            "" => 1.0,
+            field => field.parse::<f32>().map_err(|_| invalid())?,
+        };
+
+        Ok((bond_angle, length))
+    }
+
+    /// the label of the atom at the reader, empty for carbon or when there's none, with TeX
+    /// markup such as `H_3` or `N^+` or `\oplus` turned into plain text
+    fn label(&mut self) -> String {
+        let mut raw = String::new();
+        while let Some(&c) = self.chars.peek() {
+            match c {
+                c if is_bond(c) || matches!(c, '(' | ')' | '*' | '?' | '[' | ']' | ',') => break,
+                // a sub or superscript takes the character after it, even a sign
+                '^' | '_' => {
+                    self.chars.next();
+                    match self.chars.peek() {
+                        Some('{') => raw.push_str(&self.group()),
+                        Some(_) => raw.extend(self.chars.next()),
+                        None => {}
+                    }
+                }
+                '{' => raw.push_str(&self.group()),
+                // names given to atoms for tikz to draw to
+                '@' => {
+                    self.chars.next();
+                    if self.chars.peek() == Some(&'{') {
+                        self.group();
+                    }
+                }
+                _ => {
+                    raw.push(c);
+                    self.chars.next();
+                }
+            }
+        }
+
+        let mut label = String::new();
+        let mut chars = raw.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    let mut command = String::new();
+                    while let Some(letter) = chars.next_if(char::is_ascii_alphabetic) {
+                        command.push(letter);
+                    }
+                    match command.as_str() {
+                        "oplus" => label.push('+'),
+                        "ominus" => label.push('-'),
+                        _ => {}
+                    }
+                }
+                '{' | '}' | '$' | '|' => {}
+                c if c.is_whitespace() => {}
+                c => label.push(c),
+            }
+        }
+
+        // carbons are left unlabelled
+        match label.as_str() {
+            "C" => String::new(),
+            _ => label,
+        }
+    }
+
+    /// a braced group with its braces, read whole so the bonds and parentheses in it belong to
+    /// the label
+    fn group(&mut self) -> String {
+        let mut group = String::new();
+        let mut depth = 0;
+        for c in self.chars.by_ref() {
+            group.push(c);
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                break;
+            }
+        }
+
+        group
+    }
+
+    /// `?` or `?[name]` after an atom, bonding it to the atom before it with the same name
+    fn hook(&mut self, atom: usize) -> Result<(), Error> {
+        self.expect('?')?;
+        let mut name = String::new();
+        if self.chars.next_if_eq(&'[').is_some() {
+            loop {
+                match self.chars.next() {
+                    Some(']') => break,
+                    Some(c) => name.push(c),
+                    None => return Err(Error::UnbalancedChemfig),
+                }
+            }
+        }
+        let name = name.split(',').next().unwrap_or_default().trim();
+        let name = if name.is_empty() { "a" } else { name };
+
+        match self.hooks.remove(name) {
+            Some(other) => self.push_bond(other, atom, BondType::Normal(1), false),
+            None => {
+                self.hooks.insert(name.to_string(), atom);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn add_atom(&mut self, label: String, position: Point) -> usize {
+        self.structure.atoms.push((label, position));
+        self.structure.atoms.len() - 1
+    }
+
+    /// the atom already at a ring position, labelled if it wasn't, or else a new one
+    fn ring_atom(&mut self, label: String, position: Point) -> usize {
+        let existing = self.structure.atoms.iter().position(|(_, existing)| existing.distance(position) < SAME_ATOM);
+
+        match existing {
+            Some(index) => {
+                if !label.is_empty() {
+                    self.structure.atoms[index].0 = label;
+                }
+                index
+            }
+            None => self.add_atom(label, position),
+        }
+    }
+
+    /// bonds two atoms unless they're already bonded, as the bond fused rings share is written
+    /// in both
+    fn push_bond(&mut self, start: usize, end: usize, bond_type: BondType, reversed: bool) {
+        let bonded = self
+            .structure
+            .bonds
+            .iter()
+            .any(|(a, b, _)| (*a, *b) == (start, end) || (*a, *b) == (end, start));
+        if start == end || bonded {
+            return;
+        }
+
+        let (start, end) = if reversed { (end, start) } else { (start, end) };
+        self.structure.bonds.push((start, end, bond_type));
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), Error> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) if c == ')' || expected == ')' => Err(Error::UnbalancedChemfig),
+            Some(c) => Err(Error::UnexpectedChemfigCharacter(c)),
+            None => Err(Error::UnbalancedChemfig),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+}
+
+fn is_bond(c: char) -> bool {
+    matches!(c, '-' | '=' | '~' | '<' | '>')
+}
+
+/// unit vector at an angle in degrees, anticlockwise from the x axis with the y axis up
+fn direction(angle: f32) -> Vector {
+    let radians = angle.to_radians();
+    Vector::new(radians.cos(), radians.sin())
+}
@@ -0,0 +1,267 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::chemistry::Element;
+use crate::molecule::BondType;
+
+use super::{layout, Error, Structure};
+
+/// a piece of a condensed formula, e.g. "CH2", "O2", "(CH3)2" or "="
+enum Unit {
+    /// copies of an element, the last carrying the hydrogens written after it
+    Atom { element: Element, count: u32, hydrogens: u32 },
+    /// hydrogens written before any atom, as in "HOCH2CH2OH"
+    Hydrogens(u32),
+    Group { units: Vec<Unit>, count: u32 },
+    Bond(u8),
+}
+
+/// atoms and bonds built up from the units, with bond orders counted as numbers
+#[derive(Default)]
+struct Builder {
+    elements: Vec<Element>,
+    hydrogens: Vec<u32>,
+    bonds: Vec<(usize, usize, u8)>,
+    /// atom the next atom bonds to
+    previous: Option<usize>,
+    /// groups written before the atom they hang from, e.g. "(CH3)3" in "(CH3)3COH"
+    dangling: Vec<usize>,
+    leading_hydrogens: u32,
+    pending_order: Option<u8>,
+}
+
+/// reads a condensed structural formula such as "CH3CH2OH" or "(CH3)2CHCO2H", laying out the
+/// chain it describes
+pub fn parse_condensed(formula: &str) -> Result<Structure, Error> {
+    let mut chars = formula.chars().peekable();
+    let units = parse_units(&mut chars)?;
+    if chars.next().is_some() {
+        return Err(Error::UnbalancedFormula);
+    }
+
+    let mut builder = Builder::default();
+    builder.add_units(&units)?;
+    builder.fill_bonds();
+
+    Ok(builder.structure())
+}
+
+/// reads units until the end of the formula or a closing parenthesis
+fn parse_units(chars: &mut Peekable<Chars>) -> Result<Vec<Unit>, Error> {
+    let mut units = vec![];
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            '(' => {
+                chars.next();
+                let group = parse_units(chars)?;
+                if chars.next() != Some(')') {
+                    return Err(Error::UnbalancedFormula);
+                }
+                units.push(Unit::Group { units: group, count: parse_count(chars) });
+            }
+            ')' => break,
+            '-' | '=' | '#' | '≡' => {
+                chars.next();
+                let order = match c {
+                    '-' => 1,
+                    '=' => 2,
+                    _ => 3,
+                };
+                units.push(Unit::Bond(order));
+            }
+            _ if c.is_ascii_uppercase() => {
+                chars.next();
+                let mut symbol = c.to_string();
+                if let Some(lower) = chars.next_if(char::is_ascii_lowercase) {
+                    symbol.push(lower);
+                }
+                let element = Element::from_symbol(&symbol).ok_or(Error::UnknownFormulaSymbol(symbol))?;
+                let count = parse_count(chars);
+
+                if element == Element::HYDROGEN {
+                    units.push(Unit::Hydrogens(count));
+                    continue;
+                }
+
+                // the hydrogens of "CH3", but not the mercury of "CHg"
+                let mut lookahead = chars.clone();
+                let hydrogens = match (lookahead.next(), lookahead.next()) {
+                    (Some('H'), next) if !next.is_some_and(|c| c.is_ascii_lowercase()) => {
+                        chars.next();
+                        parse_count(chars)
+                    }
+                    _ => 0,
+                };
+
+                units.push(Unit::Atom { element, count, hydrogens });
+            }
+            _ => return Err(Error::UnexpectedFormulaCharacter(c)),
+        }
+    }
+
+    Ok(units)
+}
+
+fn parse_count(chars: &mut Peekable<Chars>) -> u32 {
+    let mut count = None;
+    while let Some(digit) = chars.next_if(char::is_ascii_digit).and_then(|c| c.to_digit(10)) {
+        count = Some(count.unwrap_or(0) * 10 + digit);
+    }
+
+    count.unwrap_or(1)
+}
+
+impl Builder {
+    /// valence an atom has left after its hydrogens and bonds
+    fn remaining(&self, atom: usize) -> i64 {
+        let bonded: i64 = self
+            .bonds
+            .iter()
+            .filter(|(start, end, _)| *start == atom || *end == atom)
+            .map(|(_, _, order)| *order as i64)
+            .sum();
+
+        self.elements[atom].valence as i64 - self.hydrogens[atom] as i64 - bonded
+    }
+
+    /// valence left counting the extra bonds sulfur and phosphorus can take, as in sulfones and
+    /// phosphates
+    fn expanded_remaining(&self, atom: usize) -> i64 {
+        let extra = match self.elements[atom].symbol {
+            "S" => 4,
+            "P" => 2,
+            _ => 0,
+        };
+
+        self.remaining(atom) + extra
+    }
+
+    fn check_valence(&self, atom: usize) -> Result<(), Error> {
+        match self.expanded_remaining(atom) < 0 {
+            true => Err(Error::FormulaValence(self.elements[atom].symbol.to_string())),
+            false => Ok(()),
+        }
+    }
+
+    fn add_units(&mut self, units: &[Unit]) -> Result<(), Error> {
+        for (index, unit) in units.iter().enumerate() {
+            let followed = units[index + 1..].iter().any(|unit| !matches!(unit, Unit::Bond(_)));
+
+            match unit {
+                Unit::Bond(order) => self.pending_order = Some(*order),
+                Unit::Hydrogens(count) => self.leading_hydrogens += count,
+                Unit::Atom { element, count, hydrogens } => {
+                    // copies of a heteroatom, as in "CO2H" or "SO2", all hang from the atom before
+                    let anchor = self.previous.filter(|_| *element != Element::CARBON);
+                    for copy in 1..=*count {
+                        if anchor.is_some() {
+                            self.previous = anchor;
+                        }
+                        let last = copy == *count;
+                        self.add_atom(*element, if last { *hydrogens } else { 0 }, followed || !last)?;
+                    }
+                }
+                Unit::Group { units, count } => {
+                    for _ in 0..*count {
+                        self.add_group(units, *count)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// one copy of a parenthesised group, which carries the chain on when it is repeated and has
+    /// room to, as in "(CH2)3", and otherwise hangs from the atom before it
+    fn add_group(&mut self, units: &[Unit], count: u32) -> Result<(), Error> {
+        let anchor = self.previous;
+        let dangling = std::mem::take(&mut self.dangling);
+        let first = self.elements.len();
+
+        self.add_units(units)?;
+
+        let continues = count > 1 && self.previous.is_some_and(|end| self.remaining(end) > 0);
+        match anchor {
+            Some(_) if continues => (),
+            Some(_) => self.previous = anchor,
+            None => {
+                self.dangling = dangling;
+                if first < self.elements.len() {
+                    self.dangling.push(first);
+                }
+                self.previous = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn add_atom(&mut self, element: Element, hydrogens: u32, followed: bool) -> Result<(), Error> {
+        let index = self.elements.len();
+        self.elements.push(element);
+        self.hydrogens.push(hydrogens + std::mem::take(&mut self.leading_hydrogens));
+        let order = self.pending_order.take();
+
+        let Some(previous) = self.previous else {
+            for dangling in std::mem::take(&mut self.dangling) {
+                self.bonds.push((dangling, index, 1));
+            }
+            self.previous = Some(index);
+            return self.check_valence(index);
+        };
+
+        self.bonds.push((previous, index, order.unwrap_or(1)));
+
+        // an oxygen or sulfur with nothing of its own between two atoms, as in "CH3COCH3", is
+        // double bonded to the atom before rather than carrying the chain on
+        let double_bonded = order.is_none()
+            && element.valence == 2
+            && hydrogens == 0
+            && followed
+            && self.expanded_remaining(previous) >= 2;
+
+        if double_bonded {
+            if let Some(bond) = self.bonds.last_mut() {
+                bond.2 = 2;
+            }
+        } else if element.valence > 1 {
+            self.previous = Some(index);
+        }
+
+        self.check_valence(previous)?;
+        self.check_valence(index)
+    }
+
+    /// raises the order of bonds between atoms both left with spare valence, as in "CH3CN"
+    fn fill_bonds(&mut self) {
+        for index in 0..self.bonds.len() {
+            let (start, end, _) = self.bonds[index];
+            while self.bonds[index].2 < 3 && self.remaining(start) > 0 && self.remaining(end) > 0 {
+                self.bonds[index].2 += 1;
+            }
+        }
+    }
+
+    fn structure(self) -> Structure {
+        let pairs = self.bonds.iter().map(|(start, end, _)| (*start, *end)).collect::<Vec<_>>();
+        let positions = layout::layout(self.elements.len(), &pairs);
+
+        Structure {
+            atoms: self
+                .elements
+                .iter()
+                // carbons are drawn skeletally
+                .map(|element| if *element == Element::CARBON { String::new() } else { element.symbol.to_string() })
+                .zip(positions)
+                .collect(),
+            bonds: self
+                .bonds
+                .iter()
+                .map(|(start, end, order)| (*start, *end, BondType::Normal(*order)))
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("can't open files with extension \"{0}\"")]
+    UnsupportedExtension(String),
+    #[error("molfile is missing its counts line")]
+    MissingCountsLine,
+    #[error("invalid atom on line {0}")]
+    InvalidAtom(usize),
+    #[error("invalid bond on line {0}")]
+    InvalidBond(usize),
+    #[error("unexpected character '{0}' in SMILES")]
+    UnexpectedCharacter(char),
+    #[error("unclosed bracket atom in SMILES")]
+    UnclosedBracket,
+    #[error("invalid atom map number \"{0}\" in SMILES")]
+    InvalidAtomMap(String),
+    #[error("unbalanced branch in SMILES")]
+    UnbalancedBranch,
+    #[error("ring closure {0} is never closed")]
+    UnclosedRing(u32),
+    #[error("unexpected character '{0}' in formula")]
+    UnexpectedFormulaCharacter(char),
+    #[error("unknown element symbol \"{0}\" in formula")]
+    UnknownFormulaSymbol(String),
+    #[error("unbalanced parentheses in formula")]
+    UnbalancedFormula,
+    #[error("too many bonds to {0} in formula")]
+    FormulaValence(String),
+    #[error("pasting InChI isn't supported, paste SMILES instead")]
+    InchiUnsupported,
+    #[error("couldn't generate InChI: {0}")]
+    InchiFailed(String),
+    #[error("enter a sequence of residues")]
+    EmptySequence,
+    #[error("unknown residue \"{0}\" in sequence")]
+    UnknownResidue(String),
+    #[error("sequence has {0} residues, at most {1} can be drawn")]
+    SequenceTooLong(usize, usize),
+    #[error("invalid HELM string")]
+    InvalidHelm,
+    #[error("HELM polymer \"{0}\" isn't a peptide or nucleic acid")]
+    UnsupportedHelmPolymer(String),
+    #[error("HELM nucleotide \"{0}\" has a phosphate where none can be drawn")]
+    UnsupportedHelmMonomer(String),
+    #[error("HELM nucleic acid mixes ribose and deoxyribose")]
+    MixedHelmSugars,
+    #[error("molecule isn't a condensed chain of residues")]
+    NotASequence,
+    #[error("unexpected character '{0}' in chemfig")]
+    UnexpectedChemfigCharacter(char),
+    #[error("unbalanced parentheses or braces in chemfig")]
+    UnbalancedChemfig,
+    #[error("chemfig rings need a size of at least 3")]
+    InvalidChemfigRing,
+    #[error("invalid chemfig bond options \"[{0}]\"")]
+    InvalidChemfigOptions(String),
+    #[error("reaction SMILES \"{0}\" isn't reactants>agents>products")]
+    InvalidReactionSmiles(String),
+    #[error("RXN file is missing its counts line")]
+    MissingRxnCounts,
+    #[error("RXN file lists {0} molecules but has {1}")]
+    RxnMoleculeCount(usize, usize),
+    #[error("RXN files hold one reaction but there are {0}, export to an RDF file instead")]
+    RxnReactionCount(usize),
+    #[error("can't export images with extension \"{0}\"")]
+    UnsupportedImageExtension(String),
+    #[error("there are no molecules to export")]
+    NothingToExport,
+    #[error("image is too large to render")]
+    RenderFailed,
+    #[error("invalid svg")]
+    Svg(#[from] resvg::usvg::Error),
+    #[error("couldn't encode png")]
+    Png(#[from] png::EncodingError),
+    #[error("couldn't decode png")]
+    PngDecoding(#[from] png::DecodingError),
+    #[error("can't trace over images with extension \"{0}\", use png or jpeg")]
+    UnsupportedReferenceImage(String),
+    #[error("couldn't read the size of the image")]
+    UnreadableImageSize,
+    #[error("structure recognition failed: {0}")]
+    RecognitionFailed(String),
+    #[error("image has no embedded structure, only images exported from MolCanvas can be opened")]
+    NoEmbeddedStructure,
+}
@@ -0,0 +1,113 @@
+use super::sequence::{Residue, Sequence, SequenceKind};
+use super::Error;
+
+/// whether text is a HELM string, starting with a peptide or nucleic acid polymer such as
+/// "PEPTIDE1{" or "RNA1{"
+pub fn is_helm(text: &str) -> bool {
+    let text = text.trim_start();
+    ["PEPTIDE", "RNA", "CHEM", "BLOB"].iter().any(|polymer_type| {
+        text.strip_prefix(polymer_type)
+            .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()) && rest.contains('{'))
+    })
+}
+
+/// reads the simple polymers of a HELM string, e.g. "PEPTIDE1{A.C.D}$$$$" or
+/// "RNA1{[dR](A)P.[dR](T)}$$$$", leaving out the connections between them
+pub fn parse_helm(helm: &str) -> Result<Vec<Sequence>, Error> {
+    let polymers = helm.trim().split('$').next().unwrap_or_default();
+
+    polymers.split('|').map(parse_polymer).collect()
+}
+
+/// the HELM string of a single chain, numbered as its only polymer
+pub fn write_helm(sequence: &Sequence) -> String {
+    let monomers = sequence.residues.iter().map(|residue| match sequence.kind {
+        SequenceKind::Peptide => residue.code.to_string(),
+        SequenceKind::Dna => format!("[dR]({})", residue.code),
+        SequenceKind::Rna => format!("R({})", residue.code),
+    });
+
+    let (polymer_type, separator) = match sequence.kind {
+        SequenceKind::Peptide => ("PEPTIDE", "."),
+        // each nucleotide's phosphate links it to the next
+        SequenceKind::Dna | SequenceKind::Rna => ("RNA", "P."),
+    };
+
+    format!("{}1{{{}}}$$$$", polymer_type, monomers.collect::<Vec<_>>().join(separator))
+}
+
+/// e.g. "PEPTIDE1{A.C.D}"
+fn parse_polymer(polymer: &str) -> Result<Sequence, Error> {
+    let (name, rest) = polymer.trim().split_once('{').ok_or(Error::InvalidHelm)?;
+    let monomers = rest.strip_suffix('}').ok_or(Error::InvalidHelm)?;
+    let polymer_type = name.trim_end_matches(|c: char| c.is_ascii_digit());
+
+    match polymer_type {
+        "PEPTIDE" => {
+            let residues = monomers.split('.').map(peptide_monomer).collect::<Result<Vec<_>, _>>()?;
+
+            Sequence::new(SequenceKind::Peptide, residues)
+        }
+        "RNA" => nucleic_acid(monomers),
+        _ => Err(Error::UnsupportedHelmPolymer(name.to_string())),
+    }
+}
+
+/// a one letter code, or a longer code in brackets such as "[Ala]"
+fn peptide_monomer(monomer: &str) -> Result<Residue, Error> {
+    let code = monomer.strip_prefix('[').and_then(|code| code.strip_suffix(']')).unwrap_or(monomer);
+    let mut chars = code.chars();
+    let letter = match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    };
+
+    SequenceKind::Peptide
+        .residues()
+        .iter()
+        .find(|residue| match letter {
+            Some(c) => residue.code == c,
+            None => residue.label.eq_ignore_ascii_case(code),
+        })
+        .copied()
+        .ok_or(Error::UnknownResidue(code.to_string()))
+}
+
+/// nucleotides written as sugar, base and phosphate, e.g. "R(A)P.R(U)" for RNA or
+/// "[dR](A)P.[dR](T)" for DNA, with a phosphate between each nucleotide and none at the ends
+fn nucleic_acid(monomers: &str) -> Result<Sequence, Error> {
+    let nucleotides = monomers.split('.').collect::<Vec<_>>();
+    let mut kind = None;
+    let mut residues = vec![];
+
+    for (index, nucleotide) in nucleotides.iter().enumerate() {
+        let (sugar, rest) = nucleotide.split_once('(').ok_or(Error::InvalidHelm)?;
+        let (base, phosphate) = rest.split_once(')').ok_or(Error::InvalidHelm)?;
+
+        let sugar_kind = match sugar {
+            "R" => SequenceKind::Rna,
+            "[dR]" => SequenceKind::Dna,
+            _ => return Err(Error::UnknownResidue(sugar.to_string())),
+        };
+        if *kind.get_or_insert(sugar_kind) != sugar_kind {
+            return Err(Error::MixedHelmSugars);
+        }
+
+        let last = index == nucleotides.len() - 1;
+        match (phosphate, last) {
+            ("P", false) | ("", true) => {}
+            _ => return Err(Error::UnsupportedHelmMonomer(nucleotide.to_string())),
+        }
+
+        let base = base.trim_start_matches('[').trim_end_matches(']');
+        let residue = sugar_kind
+            .residues()
+            .iter()
+            .find(|residue| base.len() == 1 && base.starts_with(residue.code))
+            .copied()
+            .ok_or(Error::UnknownResidue(base.to_string()))?;
+        residues.push(residue);
+    }
+
+    Sequence::new(kind.ok_or(Error::InvalidHelm)?, residues)
+}
@@ -0,0 +1,40 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result};
+
+use super::Error;
+
+/// command InChIs are generated with, as they need the IUPAC's canonical numbering of atoms, which
+/// Open Babel builds in
+const OPEN_BABEL: &str = "obabel";
+
+/// the standard InChI of the molecule in a molfile
+pub fn write_inchi(molfile: &str) -> Result<String> {
+    let mut child = Command::new(OPEN_BABEL)
+        .args(["-imol", "-oinchi"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("while running {}", OPEN_BABEL))
+        .context("while writing InChI")?;
+
+    // the molfile is written and closed before the output is read, so Open Babel sees it end
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(molfile.as_bytes()).context("while writing InChI")?;
+    }
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("while running {}", OPEN_BABEL))
+        .context("while writing InChI")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    match stdout.lines().map(str::trim).find(|line| line.starts_with("InChI=")) {
+        Some(inchi) => Ok(inchi.to_string()),
+        None => {
+            let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            Err(Error::InchiFailed(message)).context("while writing InChI")
+        }
+    }
+}
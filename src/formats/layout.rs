@@ -0,0 +1,108 @@
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+
+use iced::{Point, Vector};
+
+use crate::canvas::MolCanvas;
+
+/// rounds of relaxation after the initial placement
+const ITERATIONS: usize = 400;
+/// atoms further apart than this many bond lengths don't push each other away
+const REPULSION_RANGE: f32 = 3.0;
+/// furthest an atom moves in one round, in bond lengths
+const MAX_MOVEMENT: f32 = 0.5;
+
+/// positions atoms that have no coordinates, growing chains outwards in a zig-zag and then
+/// relaxing bonds towards the canvas bond length while pushing nearby atoms apart
+pub fn layout(atom_count: usize, bonds: &[(usize, usize)]) -> Vec<Point> {
+    let mut positions = initial_positions(atom_count, bonds);
+    let length = MolCanvas::BOND_LENGTH;
+
+    for iteration in 0..ITERATIONS {
+        let step = 0.1 * (1.0 - iteration as f32 / ITERATIONS as f32);
+        let mut forces = vec![Vector::ZERO; atom_count];
+
+        for &(start, end) in bonds {
+            let offset = positions[end] - positions[start];
+            let distance = offset.x.hypot(offset.y).max(f32::EPSILON);
+            let force = offset * ((distance - length) / distance);
+
+            forces[start] = forces[start] + force;
+            forces[end] = forces[end] - force;
+        }
+
+        for a in 0..atom_count {
+            for b in a + 1..atom_count {
+                let offset = positions[b] - positions[a];
+                let distance = offset.x.hypot(offset.y).max(f32::EPSILON);
+                if distance > length * REPULSION_RANGE {
+                    continue;
+                }
+
+                let force = offset * (length * length / (distance * distance * distance));
+                forces[a] = forces[a] - force;
+                forces[b] = forces[b] + force;
+            }
+        }
+
+        // atoms that start out almost on top of each other would otherwise be flung apart
+        for (position, force) in positions.iter_mut().zip(forces) {
+            let movement = force * step;
+            let distance = movement.x.hypot(movement.y);
+            *position = match distance > length * MAX_MOVEMENT {
+                true => *position + movement * (length * MAX_MOVEMENT / distance),
+                false => *position + movement,
+            };
+        }
+    }
+
+    positions
+}
+
+/// places atoms breadth first, spreading each atom's new neighbours away from the bond it was
+/// reached by
+fn initial_positions(atom_count: usize, bonds: &[(usize, usize)]) -> Vec<Point> {
+    let mut neighbours = vec![vec![]; atom_count];
+    for &(start, end) in bonds {
+        neighbours[start].push(end);
+        neighbours[end].push(start);
+    }
+
+    let mut positions = vec![Point::ORIGIN; atom_count];
+    let mut placed = vec![false; atom_count];
+    // angle of the bond each atom was reached by, none for roots, and how deep it is
+    let mut arrival = vec![(None, 0_usize); atom_count];
+    let length = MolCanvas::BOND_LENGTH;
+
+    for root in 0..atom_count {
+        if placed[root] {
+            continue;
+        }
+        placed[root] = true;
+        // separate disconnected parts
+        positions[root] = Point::new(root as f32 * length * 0.5, 0.0);
+
+        let mut queue = VecDeque::from([root]);
+        while let Some(atom) = queue.pop_front() {
+            let children = neighbours[atom].iter().copied().filter(|child| !placed[*child]).collect::<Vec<_>>();
+            let (incoming, depth) = arrival[atom];
+
+            for (k, child) in children.iter().enumerate() {
+                let angle = match (incoming, children.len()) {
+                    (None, n) => -PI / 6.0 + 2.0 * PI * k as f32 / n as f32,
+                    // zig-zag single chains
+                    (Some(incoming), 1) if depth % 2 == 0 => incoming + PI / 3.0,
+                    (Some(incoming), 1) => incoming - PI / 3.0,
+                    (Some(incoming), n) => incoming + PI + 2.0 * PI * (k + 1) as f32 / (n + 1) as f32,
+                };
+
+                positions[*child] = positions[atom] + Vector::new(angle.cos(), angle.sin()) * length;
+                arrival[*child] = (Some(angle), depth + 1);
+                placed[*child] = true;
+                queue.push_back(*child);
+            }
+        }
+    }
+
+    positions
+}
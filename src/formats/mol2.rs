@@ -0,0 +1,124 @@
+use iced::Point;
+use rustc_hash::FxHashMap;
+
+use crate::chemistry::Element;
+use crate::molecule::BondType;
+
+use super::molfile::{atom_label, scale_to_bond_length};
+use super::{Error, Structure};
+
+const MOLECULE_RECORD: &str = "@<TRIPOS>MOLECULE";
+const ATOM_RECORD: &str = "@<TRIPOS>ATOM";
+const BOND_RECORD: &str = "@<TRIPOS>BOND";
+
+/// whether text is a Tripos MOL2 file
+pub fn is_mol2(text: &str) -> bool {
+    text.contains(MOLECULE_RECORD)
+}
+
+/// reads every molecule of a Tripos MOL2 file, looking at its atoms from above so the z axis is
+/// dropped, scaled so their bonds are the canvas bond length
+pub fn parse_mol2(contents: &str) -> Result<Vec<Structure>, Error> {
+    let lines = contents.lines().enumerate().collect::<Vec<_>>();
+
+    lines
+        .split(|(_, line)| line.trim() == MOLECULE_RECORD)
+        .skip(1)
+        .map(parse_molecule)
+        .collect()
+}
+
+/// the lines of one molecule, numbered from zero through the whole file
+fn parse_molecule(lines: &[(usize, &str)]) -> Result<Structure, Error> {
+    let mut structure = Structure::default();
+    // atoms are referred to by their own ids, which needn't count up from one
+    let mut indices = FxHashMap::default();
+
+    for (index, line) in record(lines, ATOM_RECORD) {
+        let line_number = index + 1;
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+        let (Some(id), Some(name), Some(x), Some(y), Some(atom_type)) = (
+            fields.first(),
+            fields.get(1),
+            fields.get(2).and_then(|x| x.parse::<f32>().ok()),
+            fields.get(3).and_then(|y| y.parse::<f32>().ok()),
+            fields.get(5),
+        ) else {
+            return Err(Error::InvalidAtom(line_number));
+        };
+
+        indices.insert(*id, structure.atoms.len());
+        // MOL2 files have the y axis pointing up
+        structure.atoms.push((label(name, atom_type), Point::new(x, -y)));
+    }
+
+    for (index, line) in record(lines, BOND_RECORD) {
+        let line_number = index + 1;
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+        let (Some(start), Some(end), Some(bond_type)) = (
+            fields.get(1).and_then(|start| indices.get(start)),
+            fields.get(2).and_then(|end| indices.get(end)),
+            fields.get(3),
+        ) else {
+            return Err(Error::InvalidBond(line_number));
+        };
+        // an atom can't be bonded to itself
+        if start == end {
+            return Err(Error::InvalidBond(line_number));
+        }
+
+        let bond_type = match *bond_type {
+            "2" => BondType::Normal(2),
+            "3" => BondType::Normal(3),
+            "ar" => BondType::PartialDouble,
+            // atoms listed as not connected are left unbonded
+            "nc" => continue,
+            _ => BondType::Normal(1),
+        };
+        structure.bonds.push((*start, *end, bond_type));
+    }
+
+    scale_to_bond_length(&mut structure);
+
+    Ok(structure)
+}
+
+/// the lines after a record's heading up to the next record
+fn record<'a>(lines: &'a [(usize, &'a str)], heading: &'a str) -> impl Iterator<Item = (usize, &'a str)> + 'a {
+    lines
+        .iter()
+        .skip_while(move |(_, line)| line.trim() != heading)
+        .skip(1)
+        .take_while(|(_, line)| !line.trim_start().starts_with("@<TRIPOS>"))
+        .filter(|(_, line)| !line.trim().is_empty())
+        .copied()
+}
+
+/// the element of a SYBYL atom type such as "C.ar" or "N.4", falling back to the letters the
+/// atom's name starts with for generic types such as "Any" or "Hal"
+fn label(name: &str, atom_type: &str) -> String {
+    let type_symbol = atom_type.split('.').next().unwrap_or_default();
+
+    let element = Element::from_symbol(type_symbol).or_else(|| {
+        // names such as "CL1" or "HG12" are tried as two letters, then one
+        let letters = name.chars().take_while(char::is_ascii_alphabetic).collect::<String>();
+        (1..=letters.len().min(2))
+            .rev()
+            .find_map(|length| Element::from_symbol(&capitalize(&letters[..length])))
+    });
+
+    match element {
+        // quaternary nitrogens are the only SYBYL type with a whole charge
+        Some(element) if atom_type == "N.4" => atom_label(element.symbol, 1),
+        Some(element) => atom_label(element.symbol, 0),
+        None => atom_type.to_string(),
+    }
+}
+
+fn capitalize(letters: &str) -> String {
+    let mut chars = letters.chars();
+    chars
+        .next()
+        .map(|first| first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase())
+        .unwrap_or_default()
+}
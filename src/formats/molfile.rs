@@ -0,0 +1,262 @@
+use iced::Point;
+
+use crate::canvas::MolCanvas;
+use crate::chemistry::Element;
+use crate::molecule::BondType;
+
+use super::{Error, Structure};
+
+/// lines before the counts line
+const HEADER_LINES: usize = 3;
+/// length of a bond in written molfiles, in angstroms
+const WRITTEN_BOND_LENGTH: f32 = 1.5;
+/// most charges a single charge line holds
+const CHARGES_PER_LINE: usize = 8;
+
+/// reads every record of an SD file
+pub fn parse_sdf(contents: &str) -> Result<Vec<Structure>, Error> {
+    contents
+        .split("$$$$")
+        .enumerate()
+        // the separator's line ending belongs to it rather than the next record's header, whose
+        // first line, the title, may be blank
+        .map(|(index, record)| match index {
+            0 => record,
+            _ => record.strip_prefix("\r\n").or_else(|| record.strip_prefix('\n')).unwrap_or(record),
+        })
+        .filter(|record| !record.trim().is_empty())
+        .map(parse_molfile)
+        .collect()
+}
+
+/// reads a V2000 molfile, scaling it so its bonds are the canvas bond length
+pub fn parse_molfile(contents: &str) -> Result<Structure, Error> {
+    let lines = contents.lines().collect::<Vec<_>>();
+
+    let counts_index = HEADER_LINES;
+    let counts = lines.get(counts_index).ok_or(Error::MissingCountsLine)?;
+    let atom_count = fixed_field(counts, 0, 3).ok_or(Error::MissingCountsLine)?;
+    let bond_count = fixed_field(counts, 3, 6).ok_or(Error::MissingCountsLine)?;
+
+    let mut structure = Structure::default();
+    let mut charges = vec![0; atom_count];
+
+    for (i, line) in lines.iter().skip(counts_index + 1).take(atom_count).enumerate() {
+        let line_number = counts_index + 2 + i;
+        let mut fields = line.split_whitespace();
+        let x = fields.next().and_then(|x| x.parse::<f32>().ok());
+        let y = fields.next().and_then(|y| y.parse::<f32>().ok());
+        let _z = fields.next();
+        let symbol = fields.next();
+        let (Some(x), Some(y), Some(symbol)) = (x, y, symbol) else {
+            return Err(Error::InvalidAtom(line_number));
+        };
+
+        // the old charge field counts down from 4
+        let _mass_difference = fields.next();
+        charges[i] = match fields.next().and_then(|charge| charge.parse::<i32>().ok()) {
+            Some(charge @ 1..=7) if charge != 4 => 4 - charge,
+            _ => 0,
+        };
+        // the atom-atom mapping number is eight fields after the charge
+        if let Some(map) = fields.nth(7).and_then(|map| map.parse::<u32>().ok()).filter(|map| *map > 0) {
+            structure.maps.insert(i, map);
+        }
+
+        // molfiles have the y axis pointing up
+        structure.atoms.push((symbol.to_string(), Point::new(x, -y)));
+    }
+
+    let bond_start = counts_index + 1 + atom_count;
+    for (i, line) in lines.iter().skip(bond_start).take(bond_count).enumerate() {
+        let line_number = bond_start + 1 + i;
+        let start = fixed_field(line, 0, 3).filter(|start| (1..=atom_count).contains(start));
+        let end = fixed_field(line, 3, 6).filter(|end| (1..=atom_count).contains(end));
+        let order = fixed_field(line, 6, 9);
+        let stereo = fixed_field(line, 9, 12).unwrap_or(0);
+        let (Some(start), Some(end), Some(order)) = (start, end, order) else {
+            return Err(Error::InvalidBond(line_number));
+        };
+        if start == end {
+            return Err(Error::InvalidBond(line_number));
+        }
+
+        let bond_type = match (order, stereo) {
+            (1, 1) => BondType::Wedge,
+            (1, 6) => BondType::Dash,
+            (1, 4) => BondType::Wavy,
+            (2, _) => BondType::Normal(2),
+            (3, _) => BondType::Normal(3),
+            (4, _) => BondType::PartialDouble,
+            (8, _) => BondType::Hydrogen,
+            // coordination bonds are a later addition most readers accept in V2000 files
+            (9, _) => BondType::Dative,
+            _ => BondType::Normal(1),
+        };
+
+        structure.bonds.push((start - 1, end - 1, bond_type));
+    }
+
+    // charge lines in the properties block replace the old charge field
+    let mut aliases = vec![];
+    let mut properties = lines.iter().skip(bond_start + bond_count);
+    while let Some(line) = properties.next() {
+        if line.starts_with("M  END") {
+            break;
+        }
+        // an alias is written on the line after the atom it names
+        if let Some(atom) = line.strip_prefix("A  ").and_then(|atom| atom.trim().parse::<usize>().ok()) {
+            if let Some(alias) = properties.next() {
+                aliases.push((atom.wrapping_sub(1), alias.trim().to_string()));
+            }
+        }
+        if let Some(entries) = line.strip_prefix("M  CHG") {
+            let values = entries.split_whitespace().skip(1).filter_map(|value| value.parse::<i32>().ok()).collect::<Vec<_>>();
+            for pair in values.chunks(2) {
+                if let [atom, charge] = pair {
+                    if let Some(existing) = charges.get_mut((*atom as usize).wrapping_sub(1)) {
+                        *existing = *charge;
+                    }
+                }
+            }
+        }
+    }
+
+    for ((label, _), charge) in structure.atoms.iter_mut().zip(charges) {
+        *label = atom_label(label, charge);
+    }
+    for (atom, alias) in aliases {
+        if let Some((label, _)) = structure.atoms.get_mut(atom) {
+            *label = alias;
+        }
+    }
+
+    scale_to_bond_length(&mut structure);
+
+    Ok(structure)
+}
+
+/// writes a V2000 molfile of a structure drawn with bonds `bond_length` long, with labels that
+/// aren't an element and a charge kept as aliases
+pub fn write_molfile(structure: &Structure, bond_length: f32) -> String {
+    let scale = WRITTEN_BOND_LENGTH / bond_length;
+    let mut molfile = format!(
+        "\n  MolCanvas\n\n{:>3}{:>3}  0  0  0  0  0  0  0  0999 V2000\n",
+        structure.atoms.len(),
+        structure.bonds.len(),
+    );
+
+    let mut charges = vec![];
+    let mut aliases = vec![];
+    for (index, (label, position)) in structure.atoms.iter().enumerate() {
+        let (symbol, charge) = match symbol_and_charge(label) {
+            Some(symbol_and_charge) => symbol_and_charge,
+            None => {
+                aliases.push((index + 1, label));
+                ("C", 0)
+            }
+        };
+        if charge != 0 {
+            charges.push((index + 1, charge));
+        }
+
+        // molfiles have the y axis pointing up
+        molfile.push_str(&format!(
+            "{:>10.4}{:>10.4}{:>10.4} {:<3} 0  0  0  0  0  0  0  0  0{:>3}  0  0\n",
+            position.x * scale,
+            -position.y * scale,
+            0.0,
+            symbol,
+            structure.maps.get(&index).copied().unwrap_or_default(),
+        ));
+    }
+
+    for (start, end, bond_type) in &structure.bonds {
+        let (order, stereo) = match bond_type {
+            BondType::Normal(order @ 1..=3) => (*order, 0),
+            BondType::Wedge => (1, 1),
+            BondType::Dash => (1, 6),
+            BondType::Wavy => (1, 4),
+            BondType::PartialDouble => (4, 0),
+            BondType::Hydrogen => (8, 0),
+            BondType::Dative => (9, 0),
+            BondType::Normal(_) | BondType::Bold => (1, 0),
+        };
+
+        molfile.push_str(&format!("{:>3}{:>3}{:>3}{:>3}\n", start + 1, end + 1, order, stereo));
+    }
+
+    for (atom, alias) in aliases {
+        molfile.push_str(&format!("A  {:>3}\n{}\n", atom, alias));
+    }
+    for line in charges.chunks(CHARGES_PER_LINE) {
+        let entries = line.iter().map(|(atom, charge)| format!(" {:>3} {:>3}", atom, charge)).collect::<String>();
+        molfile.push_str(&format!("M  CHG{:>3}{}\n", line.len(), entries));
+    }
+    molfile.push_str("M  END\n");
+
+    molfile
+}
+
+/// element symbol and charge of a label written by `atom_label`, unlabelled atoms being carbons
+fn symbol_and_charge(label: &str) -> Option<(&'static str, i32)> {
+    let (symbol, charge) = match label.find(['+', '-']) {
+        Some(sign_index) => {
+            let sign = if label[sign_index..].starts_with('+') { 1 } else { -1 };
+            let magnitude = match &label[sign_index + 1..] {
+                "" => 1,
+                magnitude => magnitude.parse::<i32>().ok()?,
+            };
+            (&label[..sign_index], sign * magnitude)
+        }
+        None => (label, 0),
+    };
+
+    let element = match symbol {
+        "" => Element::CARBON,
+        symbol => Element::from_symbol(symbol)?,
+    };
+
+    Some((element.symbol, charge))
+}
+
+/// parses a right aligned number in the columns `start..end`
+fn fixed_field(line: &str, start: usize, end: usize) -> Option<usize> {
+    line.get(start..end.min(line.len()))?.trim().parse().ok()
+}
+
+/// carbons are left unlabelled, charges are written after the symbol
+pub(super) fn atom_label(symbol: &str, charge: i32) -> String {
+    let symbol = match symbol {
+        "C" if charge == 0 => "",
+        symbol => symbol,
+    };
+
+    // digits after the sign are read as the charge's magnitude, before it as a subscript
+    let sign = if charge > 0 { '+' } else { '-' };
+    match charge.abs() {
+        0 => symbol.to_string(),
+        1 => format!("{}{}", symbol, sign),
+        magnitude => format!("{}{}{}", symbol, sign, magnitude),
+    }
+}
+
+/// scales the structure so its average bond is the canvas bond length
+pub(super) fn scale_to_bond_length(structure: &mut Structure) {
+    let lengths = structure
+        .bonds
+        .iter()
+        .map(|(start, end, _)| structure.atoms[*start].1.distance(structure.atoms[*end].1))
+        .filter(|length| *length > f32::EPSILON)
+        .collect::<Vec<_>>();
+    if lengths.is_empty() {
+        return;
+    }
+
+    let average = lengths.iter().sum::<f32>() / lengths.len() as f32;
+    let scale = MolCanvas::BOND_LENGTH / average;
+
+    for (_, position) in &mut structure.atoms {
+        *position = Point::new(position.x * scale, position.y * scale);
+    }
+}
@@ -0,0 +1,135 @@
+use resvg::tiny_skia::{FilterQuality, IntSize, Pixmap, PixmapPaint, Transform};
+use resvg::usvg::{Options, Tree};
+
+use super::Error;
+
+/// keyword of the text chunk the molfile is embedded in
+pub const MOLFILE_KEYWORD: &str = "molfile";
+
+/// the molfile in a png's text chunks
+pub fn embedded_molfile(png: &[u8]) -> Result<Option<String>, Error> {
+    let reader = png::Decoder::new(png).read_info()?;
+    let info = reader.info();
+
+    let latin1 = info.uncompressed_latin1_text.iter().map(|chunk| (&chunk.keyword, chunk.text.clone()));
+    let compressed = info.compressed_latin1_text.iter().filter_map(|chunk| Some((&chunk.keyword, chunk.get_text().ok()?)));
+    let utf8 = info.utf8_text.iter().filter_map(|chunk| Some((&chunk.keyword, chunk.get_text().ok()?)));
+
+    Ok(latin1
+        .chain(compressed)
+        .chain(utf8)
+        .find(|(keyword, _)| *keyword == MOLFILE_KEYWORD)
+        .map(|(_, text)| text))
+}
+
+/// renders an svg to a png at a number of pixels to a canvas unit, with the molfile in a text chunk
+pub fn svg_to_png(svg: &str, molfile: &str, resolution: f32) -> Result<Vec<u8>, Error> {
+    let tree = Tree::from_str(svg, &Options::default())?;
+    let pixmap = render(&tree, resolution)?;
+
+    encode_png(&pixmap, Some(molfile))
+}
+
+/// an image as rows of pixels, each its red, green, blue and alpha
+#[derive(Debug, Clone)]
+pub struct Bitmap {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+/// renders an svg at a number of pixels to a canvas unit
+pub fn svg_to_bitmap(svg: &str, resolution: f32) -> Result<Bitmap, Error> {
+    let tree = Tree::from_str(svg, &Options::default())?;
+    let pixmap = render(&tree, resolution)?;
+
+    Ok(Bitmap {
+        width: pixmap.width() as usize,
+        height: pixmap.height() as usize,
+        pixels: unpremultiplied(&pixmap),
+    })
+}
+
+/// a png of at most `side` pixels across of an svg or png image, drawn offscreen so it doesn't
+/// touch the canvas' caches
+pub fn thumbnail(image: Thumbnailable, side: u32) -> Result<Vec<u8>, Error> {
+    let pixmap = match image {
+        Thumbnailable::Svg(svg) => {
+            let tree = Tree::from_str(svg, &Options::default())?;
+            render(&tree, fit_scale(tree.size().width(), tree.size().height(), side))?
+        }
+        Thumbnailable::Png(png) => {
+            let source = Pixmap::decode_png(png)?;
+            let scale = fit_scale(source.width() as f32, source.height() as f32, side);
+            let size = IntSize::from_wh(source.width(), source.height())
+                .and_then(|size| size.scale_by(scale))
+                .ok_or(Error::RenderFailed)?;
+
+            let mut pixmap = Pixmap::new(size.width(), size.height()).ok_or(Error::RenderFailed)?;
+            let paint = PixmapPaint {
+                quality: FilterQuality::Bicubic,
+                ..PixmapPaint::default()
+            };
+            pixmap.draw_pixmap(0, 0, source.as_ref(), &paint, Transform::from_scale(scale, scale), None);
+            pixmap
+        }
+    };
+
+    encode_png(&pixmap, None)
+}
+
+/// an image a thumbnail can be drawn from
+pub enum Thumbnailable<'a> {
+    Svg(&'a str),
+    Png(&'a [u8]),
+}
+
+/// the scale fitting an image into a square of `side` pixels, never enlarging it
+fn fit_scale(width: f32, height: f32, side: u32) -> f32 {
+    (side as f32 / width.max(height)).min(1.0)
+}
+
+fn render(tree: &Tree, scale: f32) -> Result<Pixmap, Error> {
+    let size = tree.size().to_int_size().scale_by(scale).ok_or(Error::RenderFailed)?;
+    let mut pixmap = Pixmap::new(size.width(), size.height()).ok_or(Error::RenderFailed)?;
+    resvg::render(tree, Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+
+    Ok(pixmap)
+}
+
+/// the pixmap stores premultiplied colours, pngs and the clipboard don't
+fn unpremultiplied(pixmap: &Pixmap) -> Vec<u8> {
+    pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect()
+}
+
+/// a png of a bitmap, like an image pasted from the clipboard
+pub fn bitmap_to_png(bitmap: &Bitmap) -> Result<Vec<u8>, Error> {
+    encode_rgba(bitmap.width as u32, bitmap.height as u32, &bitmap.pixels, None)
+}
+
+fn encode_png(pixmap: &Pixmap, molfile: Option<&str>) -> Result<Vec<u8>, Error> {
+    encode_rgba(pixmap.width(), pixmap.height(), &unpremultiplied(pixmap), molfile)
+}
+
+fn encode_rgba(width: u32, height: u32, data: &[u8], molfile: Option<&str>) -> Result<Vec<u8>, Error> {
+    let mut png = vec![];
+    let mut encoder = png::Encoder::new(&mut png, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    if let Some(molfile) = molfile {
+        encoder.add_text_chunk(MOLFILE_KEYWORD.to_string(), molfile.to_string())?;
+    }
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(data)?;
+    writer.finish()?;
+
+    Ok(png)
+}
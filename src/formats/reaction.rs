@@ -0,0 +1,103 @@
+use super::{parse_smiles, Error, Structure};
+
+/// the molecules on either side of a reaction arrow and those written over it
+#[derive(Debug, Clone, Default)]
+pub struct Reaction {
+    pub reactants: Vec<Structure>,
+    pub agents: Vec<Structure>,
+    pub products: Vec<Structure>,
+    pub conditions: ReactionConditions,
+}
+
+/// how a reaction was run, written under its arrow, each field left empty when not given
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReactionConditions {
+    /// percentage of the product obtained
+    pub yield_percent: Option<f32>,
+    pub temperature: String,
+    pub time: String,
+    pub solvent: String,
+    pub catalyst: String,
+}
+
+impl ReactionConditions {
+    /// names fields are saved and exported under
+    pub const FIELDS: [&'static str; 5] = ["yield", "temperature", "time", "solvent", "catalyst"];
+
+    /// the fields that are given by their names
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let values = [
+            self.yield_percent.map(|yield_percent| yield_percent.to_string()).unwrap_or_default(),
+            self.temperature.clone(),
+            self.time.clone(),
+            self.solvent.clone(),
+            self.catalyst.clone(),
+        ];
+
+        Self::FIELDS
+            .into_iter()
+            .zip(values)
+            .filter(|(_, value)| !value.trim().is_empty())
+            .collect()
+    }
+
+    /// sets a field by its name, ignoring case, and returns whether there is one by that name
+    pub fn set_field(&mut self, name: &str, value: &str) -> bool {
+        let value = value.trim().to_string();
+        match name.to_lowercase().as_str() {
+            "yield" => self.yield_percent = value.trim_end_matches('%').trim().parse().ok(),
+            "temperature" => self.temperature = value,
+            "time" => self.time = value,
+            "solvent" => self.solvent = value,
+            "catalyst" => self.catalyst = value,
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// short lines to write under an arrow, the catalyst and solvent, then the temperature and
+    /// time, then the yield, e.g. "Pd/C, EtOH", "25 °C, 2 h" and "95%"
+    pub fn summary(&self) -> Vec<String> {
+        let join = |values: [&str; 2]| values.into_iter().filter(|value| !value.trim().is_empty()).collect::<Vec<_>>().join(", ");
+        let yield_percent = self.yield_percent.map(|yield_percent| format!("{}%", yield_percent)).unwrap_or_default();
+
+        [join([&self.catalyst, &self.solvent]), join([&self.temperature, &self.time]), yield_percent]
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+}
+
+/// whether the first word on every line of text is reaction SMILES, e.g. `CC(=O)O.OCC>[H+]>CC(=O)OCC`
+pub fn is_reaction_smiles(text: &str) -> bool {
+    let mut words = text.lines().filter_map(|line| line.split_whitespace().next()).peekable();
+    words.peek().is_some() && words.all(|word| word.matches('>').count() == 2)
+}
+
+/// reads the reaction SMILES at the start of each line, laying out each component as its own
+/// structure
+pub fn parse_reaction_smiles(text: &str) -> Result<Vec<Reaction>, Error> {
+    text.lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(|word| {
+            let [reactants, agents, products] = word
+                .split('>')
+                .collect::<Vec<_>>()
+                .try_into()
+                .map_err(|_| Error::InvalidReactionSmiles(word.to_string()))?;
+
+            Ok(Reaction {
+                reactants: parse_smiles(reactants)?,
+                agents: parse_smiles(agents)?,
+                products: parse_smiles(products)?,
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// reaction SMILES from the SMILES of each component
+pub fn write_reaction_smiles(reactants: &[String], agents: &[String], products: &[String]) -> String {
+    format!("{}>{}>{}", reactants.join("."), agents.join("."), products.join("."))
+}
@@ -0,0 +1,64 @@
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use uuid::Uuid;
+
+use super::{molfile, parse_smiles, Error, Structure};
+
+/// environment variable naming a command that recognises chemical structures in images
+///
+/// the command is run with the image's path as its only argument and prints a molfile or SD file,
+/// or SMILES one to a line
+const COMMAND_VARIABLE: &str = "MOLECULAR_CANVAS_OCSR";
+
+/// structures the user's recognition command finds in an image, none when there's no command
+///
+/// the command is waited on, so recognition is run off the window's thread
+pub fn recognize_structures(path: &Path) -> Result<Option<Vec<Structure>>> {
+    let Some(command) = std::env::var_os(COMMAND_VARIABLE) else {
+        return Ok(None);
+    };
+
+    let output = Command::new(&command)
+        .arg(path)
+        .output()
+        .with_context(|| format!("while running {}", command.to_string_lossy()))
+        .context("while recognising structures")?;
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(Error::RecognitionFailed(message)).context("while recognising structures");
+    }
+
+    let text = String::from_utf8_lossy(&output.stdout);
+    let structures = match text.contains("M  END") {
+        true => molfile::parse_sdf(&text)?,
+        false => text
+            .lines()
+            .filter_map(|line| line.split_whitespace().next())
+            .map(parse_smiles)
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .flatten()
+            .collect(),
+    };
+
+    Ok(Some(structures))
+}
+
+/// structures the user's recognition command finds in a png that isn't a file, like an image
+/// pasted from the clipboard, which is written to a temporary file for the command to read
+pub fn recognize_png(png: &[u8]) -> Result<Option<Vec<Structure>>> {
+    if std::env::var_os(COMMAND_VARIABLE).is_none() {
+        return Ok(None);
+    }
+
+    let path = std::env::temp_dir().join(format!("molecular-canvas-{}.png", Uuid::new_v4()));
+    std::fs::write(&path, png)
+        .with_context(|| format!("while writing {}", path.display()))
+        .context("while recognising structures")?;
+    let structures = recognize_structures(&path);
+    let _ = std::fs::remove_file(&path);
+
+    structures
+}
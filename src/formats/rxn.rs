@@ -0,0 +1,104 @@
+use super::molfile::{parse_molfile, write_molfile};
+use super::{Error, Reaction, ReactionConditions};
+
+const RXN_HEADER: &str = "$RXN";
+const RDF_HEADER: &str = "$RDFILE";
+const MOLECULE_HEADER: &str = "$MOL";
+/// an RDF data field's name, its value following on a `$DATUM` line
+const DATA_TYPE: &str = "$DTYPE";
+const DATUM: &str = "$DATUM";
+/// lines after the `$RXN` line up to and including the counts line
+const HEADER_LINES: usize = 4;
+
+/// whether text is an MDL RXN or RDF file
+pub fn is_rxn(text: &str) -> bool {
+    let start = text.trim_start();
+    start.starts_with(RXN_HEADER) || start.starts_with(RDF_HEADER)
+}
+
+/// reads the reaction of an RXN file, or each reaction of an RDF file with the conditions among
+/// the data fields written after it
+pub fn parse_rxn(contents: &str) -> Result<Vec<Reaction>, Error> {
+    let lines = contents.lines().collect::<Vec<_>>();
+
+    lines
+        .split(|line| line.starts_with(RXN_HEADER))
+        .skip(1)
+        .map(parse_reaction)
+        .collect()
+}
+
+/// the lines of one reaction after its `$RXN` line, its counts line listing reactants, products
+/// and, when a third count is written, agents
+fn parse_reaction(lines: &[&str]) -> Result<Reaction, Error> {
+    let counts = lines.get(HEADER_LINES - 1).ok_or(Error::MissingRxnCounts)?;
+    let count = |start: usize| counts.get(start..(start + 3).min(counts.len())).and_then(|field| field.trim().parse::<usize>().ok());
+    let (Some(reactant_count), Some(product_count)) = (count(0), count(3)) else {
+        return Err(Error::MissingRxnCounts);
+    };
+    let agent_count = count(6).unwrap_or(0);
+
+    // each molfile runs from its `$MOL` line to the next, the last one's `M  END` ending it
+    let mut molecules = lines[HEADER_LINES..]
+        .split(|line| line.starts_with(MOLECULE_HEADER))
+        .skip(1)
+        .map(|molfile| parse_molfile(&molfile.join("\n")))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let expected = reactant_count + product_count + agent_count;
+    if molecules.len() != expected {
+        return Err(Error::RxnMoleculeCount(expected, molecules.len()));
+    }
+
+    let agents = molecules.split_off(reactant_count + product_count);
+    let products = molecules.split_off(reactant_count);
+
+    // other programs' fields are left out
+    let mut conditions = ReactionConditions::default();
+    for (line, next) in lines.iter().zip(lines.iter().skip(1)) {
+        if let (Some(name), Some(value)) = (line.strip_prefix(DATA_TYPE), next.strip_prefix(DATUM)) {
+            conditions.set_field(name.trim(), value);
+        }
+    }
+
+    Ok(Reaction {
+        reactants: molecules,
+        agents,
+        products,
+        conditions,
+    })
+}
+
+/// writes an RXN file of a reaction drawn with bonds `bond_length` long, its agents counted
+/// after its products only when it has some and its conditions summed up in the comment line
+pub fn write_rxn(reaction: &Reaction, bond_length: f32) -> String {
+    let mut counts = format!("{:>3}{:>3}", reaction.reactants.len(), reaction.products.len());
+    if !reaction.agents.is_empty() {
+        counts.push_str(&format!("{:>3}", reaction.agents.len()));
+    }
+
+    let comment = reaction.conditions.summary().join("; ");
+    let mut rxn = format!("{}\n\n  MolCanvas\n{}\n{}\n", RXN_HEADER, comment, counts);
+    for structure in reaction.reactants.iter().chain(&reaction.products).chain(&reaction.agents) {
+        rxn.push_str(MOLECULE_HEADER);
+        rxn.push('\n');
+        rxn.push_str(&write_molfile(structure, bond_length));
+    }
+
+    rxn
+}
+
+/// writes an RDF file with a record for each reaction, followed by a data field for each of its
+/// conditions
+pub fn write_rdf(reactions: &[Reaction], bond_length: f32) -> String {
+    let mut rdf = format!("{} 1\n$DATM\n", RDF_HEADER);
+    for reaction in reactions {
+        rdf.push_str("$RFMT\n");
+        rdf.push_str(&write_rxn(reaction, bond_length));
+        for (name, value) in reaction.conditions.fields() {
+            rdf.push_str(&format!("{} {}\n{} {}\n", DATA_TYPE, name.to_uppercase(), DATUM, value));
+        }
+    }
+
+    rdf
+}
@@ -0,0 +1,307 @@
+use std::f32::consts::PI;
+use std::fmt;
+
+use iced::{Point, Vector};
+
+use crate::canvas::MolCanvas;
+use crate::molecule::BondType;
+
+use super::{parse_smiles, Error, Structure};
+
+/// residues in a sequence past which laying out every atom holds up the window too long
+const MAX_RESIDUES: usize = 100;
+
+/// the kind of chain a sequence is read as
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceKind {
+    #[default]
+    Peptide,
+    Dna,
+    Rna,
+}
+
+impl SequenceKind {
+    pub const ALL: [SequenceKind; 3] = [Self::Peptide, Self::Dna, Self::Rna];
+
+    pub(super) fn residues(&self) -> &'static [Residue] {
+        match self {
+            Self::Peptide => AMINO_ACIDS,
+            Self::Dna => DEOXYNUCLEOTIDES,
+            Self::Rna => RIBONUCLEOTIDES,
+        }
+    }
+}
+
+impl fmt::Display for SequenceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Peptide => write!(f, "Peptide"),
+            Self::Dna => write!(f, "DNA"),
+            Self::Rna => write!(f, "RNA"),
+        }
+    }
+}
+
+/// an amino acid or nucleotide a sequence is made of
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct Residue {
+    pub(super) code: char,
+    /// three letter code of an amino acid, or the abbreviation a nucleoside is drawn with in
+    /// condensed chains
+    pub(super) label: &'static str,
+    /// SMILES of an amino acid from its amide nitrogen to its carbonyl carbon, or of a
+    /// nucleotide's base from the nitrogen bonded to the sugar, using ring numbers from 2
+    smiles: &'static str,
+}
+
+impl Residue {
+    const fn new(code: char, label: &'static str, smiles: &'static str) -> Self {
+        Self { code, label, smiles }
+    }
+}
+
+const AMINO_ACIDS: &[Residue] = &[
+    Residue::new('A', "Ala", "NC(C)C(=O)"),
+    Residue::new('R', "Arg", "NC(CCCNC(=N)N)C(=O)"),
+    Residue::new('N', "Asn", "NC(CC(N)=O)C(=O)"),
+    Residue::new('D', "Asp", "NC(CC(=O)O)C(=O)"),
+    Residue::new('C', "Cys", "NC(CS)C(=O)"),
+    Residue::new('Q', "Gln", "NC(CCC(N)=O)C(=O)"),
+    Residue::new('E', "Glu", "NC(CCC(=O)O)C(=O)"),
+    Residue::new('G', "Gly", "NCC(=O)"),
+    Residue::new('H', "His", "NC(CC1=CN=CN1)C(=O)"),
+    Residue::new('I', "Ile", "NC(C(C)CC)C(=O)"),
+    Residue::new('L', "Leu", "NC(CC(C)C)C(=O)"),
+    Residue::new('K', "Lys", "NC(CCCCN)C(=O)"),
+    Residue::new('M', "Met", "NC(CCSC)C(=O)"),
+    Residue::new('F', "Phe", "NC(CC1=CC=CC=C1)C(=O)"),
+    // the side chain closes a ring back onto the amide nitrogen
+    Residue::new('P', "Pro", "N1CCCC1C(=O)"),
+    Residue::new('S', "Ser", "NC(CO)C(=O)"),
+    Residue::new('T', "Thr", "NC(C(C)O)C(=O)"),
+    Residue::new('W', "Trp", "NC(CC1=CNC2=CC=CC=C12)C(=O)"),
+    Residue::new('Y', "Tyr", "NC(CC1=CC=C(O)C=C1)C(=O)"),
+    Residue::new('V', "Val", "NC(C(C)C)C(=O)"),
+];
+
+const ADENINE: &str = "N2C=NC3=C2N=CN=C3N";
+const GUANINE: &str = "N2C=NC3=C2N=C(N)NC3=O";
+const CYTOSINE: &str = "N2C=CC(N)=NC2=O";
+const THYMINE: &str = "N2C=C(C)C(=O)NC2=O";
+const URACIL: &str = "N2C=CC(=O)NC2=O";
+
+const DEOXYNUCLEOTIDES: &[Residue] = &[
+    Residue::new('A', "dA", ADENINE),
+    Residue::new('C', "dC", CYTOSINE),
+    Residue::new('G', "dG", GUANINE),
+    Residue::new('T', "dT", THYMINE),
+];
+
+const RIBONUCLEOTIDES: &[Residue] = &[
+    Residue::new('A', "rA", ADENINE),
+    Residue::new('C', "rC", CYTOSINE),
+    Residue::new('G', "rG", GUANINE),
+    Residue::new('U', "rU", URACIL),
+];
+
+/// a peptide, DNA or RNA chain read from one or three letter codes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sequence {
+    pub(super) kind: SequenceKind,
+    pub(super) residues: Vec<Residue>,
+}
+
+impl Sequence {
+    /// reads one letter codes such as "ACDG" or "ATGC", or for peptides three letter codes such
+    /// as "Ala-Gly-Ser", ignoring spaces and dashes between residues
+    pub fn parse(text: &str, kind: SequenceKind) -> Result<Self, Error> {
+        let codes = text.chars().filter(|c| !c.is_whitespace() && *c != '-').collect::<String>();
+        if codes.is_empty() {
+            return Err(Error::EmptySequence);
+        }
+
+        // three letter codes are told apart by their lower case letters
+        let three_letter = kind == SequenceKind::Peptide && codes.chars().any(|c| c.is_ascii_lowercase());
+        let residues = match three_letter {
+            true => {
+                let chars = codes.chars().collect::<Vec<_>>();
+                chars
+                    .chunks(3)
+                    .map(|chunk| {
+                        let code = chunk.iter().collect::<String>();
+                        AMINO_ACIDS
+                            .iter()
+                            .find(|residue| residue.label.eq_ignore_ascii_case(&code))
+                            .copied()
+                            .ok_or(Error::UnknownResidue(code))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?
+            }
+            false => codes
+                .chars()
+                .map(|code| {
+                    kind.residues()
+                        .iter()
+                        .find(|residue| residue.code == code.to_ascii_uppercase())
+                        .copied()
+                        .ok_or(Error::UnknownResidue(code.to_string()))
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+        };
+
+        Self::new(kind, residues)
+    }
+
+    pub(super) fn new(kind: SequenceKind, residues: Vec<Residue>) -> Result<Self, Error> {
+        if residues.len() > MAX_RESIDUES {
+            return Err(Error::SequenceTooLong(residues.len(), MAX_RESIDUES));
+        }
+
+        Ok(Self { kind, residues })
+    }
+
+    /// reads back a condensed chain from the labels along it, e.g. "H", "Ala", "Gly", "OH", in
+    /// either direction for peptides
+    pub fn from_chain(labels: &[String]) -> Result<Self, Error> {
+        let mut labels = labels.iter().map(String::as_str).collect::<Vec<_>>();
+        if labels.first() == Some(&"OH") {
+            labels.reverse();
+        }
+
+        let residues = labels
+            .iter()
+            .enumerate()
+            // terminal caps and the phosphates between nucleosides are implied by the kind
+            .filter(|(index, label)| match **label {
+                "H" | "OH" => *index != 0 && *index != labels.len() - 1,
+                "p" => false,
+                _ => true,
+            })
+            .map(|(_, label)| {
+                SequenceKind::ALL
+                    .iter()
+                    .find_map(|kind| {
+                        let residue = kind.residues().iter().find(|residue| residue.label == *label)?;
+                        Some((*kind, *residue))
+                    })
+                    .ok_or(Error::NotASequence)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let Some((kind, _)) = residues.first().copied() else {
+            return Err(Error::NotASequence);
+        };
+        if residues.iter().any(|(residue_kind, _)| *residue_kind != kind) {
+            return Err(Error::NotASequence);
+        }
+
+        Self::new(kind, residues.into_iter().map(|(_, residue)| residue).collect())
+    }
+
+    /// e.g. "Ala-Gly-Ser" for a peptide or "5'-ATGC-3'" for a nucleic acid
+    pub fn name(&self) -> String {
+        match self.kind {
+            SequenceKind::Peptide => self.residues.iter().map(|residue| residue.label).collect::<Vec<_>>().join("-"),
+            SequenceKind::Dna | SequenceKind::Rna => {
+                format!("5'-{}-3'", self.residues.iter().map(|residue| residue.code).collect::<String>())
+            }
+        }
+    }
+
+    /// every atom of the chain, from the N terminus of a peptide or the 5' end of a nucleic acid,
+    /// each residue laid out on its own and then lined up along the backbone
+    pub fn structure(&self) -> Result<Structure, Error> {
+        let last = self.residues.len() - 1;
+        let fragments = self.residues.iter().enumerate().map(|(index, residue)| match self.kind {
+            // the amide nitrogen of the next residue, or the hydroxyl of the C terminus
+            SequenceKind::Peptide => format!("{}{}", residue.smiles, if index == last { "O" } else { "N" }),
+            SequenceKind::Dna | SequenceKind::Rna => {
+                let previous = if index == 0 { "O" } else { "P(=O)(O)O" };
+                // the 2' carbon of ribose carries a hydroxyl
+                let carbon = if self.kind == SequenceKind::Rna { "C(O)" } else { "C" };
+                let next = if index == last { "" } else { "P" };
+
+                format!("{}CC1OC({}){}C1O{}", previous, residue.smiles, carbon, next)
+            }
+        });
+
+        let mut structure = Structure::default();
+        let mut start = Point::ORIGIN;
+        for (index, fragment) in fragments.enumerate() {
+            let mut residue = parse_smiles(&fragment)?.into_iter().next().ok_or(Error::EmptySequence)?;
+            // side chains alternate above and below a peptide's backbone, bases all go one way
+            let flip = self.kind == SequenceKind::Peptide && index % 2 == 1;
+            let end = align(&mut residue, start, flip);
+
+            // the last atom stands in for the first of the next residue, which takes its place
+            // and its index
+            let offset = structure.atoms.len();
+            if index != last {
+                residue.atoms.pop();
+            }
+            structure.atoms.extend(residue.atoms);
+            structure
+                .bonds
+                .extend(residue.bonds.into_iter().map(|(start, end, bond_type)| (start + offset, end + offset, bond_type)));
+            start = end;
+        }
+
+        Ok(structure)
+    }
+
+    /// the chain drawn HELM style, one labelled atom to each residue and for nucleic acids each
+    /// phosphate, capped with the terminal hydrogens and hydroxyl
+    pub fn condensed_structure(&self) -> Structure {
+        let mut labels = vec!["H"];
+        match self.kind {
+            SequenceKind::Peptide => {
+                labels.extend(self.residues.iter().map(|residue| residue.label));
+                labels.push("OH");
+            }
+            SequenceKind::Dna | SequenceKind::Rna => {
+                for (index, residue) in self.residues.iter().enumerate() {
+                    if index > 0 {
+                        labels.push("p");
+                    }
+                    labels.push(residue.label);
+                }
+                labels.push("H");
+            }
+        }
+
+        // a zig-zag running to the right
+        let length = MolCanvas::BOND_LENGTH;
+        let positions = (0..labels.len()).map(|index| {
+            Point::new(index as f32 * length * (PI / 6.0).cos(), (index % 2) as f32 * length * (PI / 6.0).sin())
+        });
+
+        let bonds = (1..labels.len()).map(|index| (index - 1, index, BondType::Normal(1))).collect();
+
+        Structure {
+            atoms: labels.into_iter().map(str::to_string).zip(positions).collect(),
+            bonds,
+            ..Default::default()
+        }
+    }
+}
+
+/// turns a residue laid out on its own so its first atom is at the start and its last atom is
+/// straight to the right of it, mirroring it top to bottom if flipped, returning where the last
+/// atom ends up
+fn align(residue: &mut Structure, start: Point, flip: bool) -> Point {
+    let (Some((_, first)), Some((_, last))) = (residue.atoms.first().cloned(), residue.atoms.last().cloned()) else {
+        return start;
+    };
+    let direction = last - first;
+    let angle = -direction.y.atan2(direction.x);
+    let (sin, cos) = angle.sin_cos();
+    let sign = if flip { -1.0 } else { 1.0 };
+
+    for (_, position) in &mut residue.atoms {
+        let offset = *position - first;
+        let rotated = Vector::new(offset.x * cos - offset.y * sin, offset.x * sin + offset.y * cos);
+        *position = start + Vector::new(rotated.x, rotated.y * sign);
+    }
+
+    start + Vector::new(direction.x.hypot(direction.y), 0.0)
+}
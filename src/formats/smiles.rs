@@ -0,0 +1,253 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+use rustc_hash::FxHashMap;
+
+use crate::molecule::BondType;
+
+use super::{layout, Error, Structure};
+
+/// an atom as written in SMILES
+struct SmilesAtom {
+    label: String,
+    aromatic: bool,
+    /// bracket atoms with hydrogens written, e.g. the "[nH]" of pyrrole, take no part in double bonds
+    explicit_hydrogens: bool,
+    /// atom map number written after a colon, e.g. the 1 of "[CH3:1]"
+    map: Option<u32>,
+}
+
+/// reads a SMILES string, laying out each '.' separated component as its own structure
+pub fn parse_smiles(smiles: &str) -> Result<Vec<Structure>, Error> {
+    smiles
+        .split('.')
+        .filter(|component| !component.is_empty())
+        .map(parse_component)
+        .collect()
+}
+
+fn parse_component(smiles: &str) -> Result<Structure, Error> {
+    let mut atoms: Vec<SmilesAtom> = vec![];
+    let mut bonds: Vec<(usize, usize, Option<char>)> = vec![];
+    let mut previous: Option<usize> = None;
+    let mut branches: Vec<Option<usize>> = vec![];
+    let mut pending_bond: Option<char> = None;
+    let mut rings: FxHashMap<u32, (usize, Option<char>)> = FxHashMap::default();
+
+    let mut chars = smiles.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let atom = match c {
+            '(' => {
+                branches.push(previous);
+                continue;
+            }
+            ')' => {
+                previous = branches.pop().ok_or(Error::UnbalancedBranch)?;
+                continue;
+            }
+            '-' | '=' | '#' | '$' | ':' | '/' | '\\' => {
+                pending_bond = Some(c);
+                continue;
+            }
+            '0'..='9' | '%' => {
+                let number = ring_number(c, &mut chars).ok_or(Error::UnexpectedCharacter(c))?;
+                let atom = previous.ok_or(Error::UnexpectedCharacter(c))?;
+
+                match rings.remove(&number) {
+                    // a ring can't close on the atom it opened at
+                    Some((opening, _)) if opening == atom => return Err(Error::UnexpectedCharacter(c)),
+                    Some((opening, opening_bond)) => bonds.push((opening, atom, opening_bond.or(pending_bond.take()))),
+                    None => {
+                        rings.insert(number, (atom, pending_bond.take()));
+                    }
+                }
+                continue;
+            }
+            '[' => bracket_atom(&mut chars)?,
+            '*' => SmilesAtom {
+                label: "R".to_string(),
+                aromatic: false,
+                explicit_hydrogens: false,
+                map: None,
+            },
+            _ => organic_atom(c, &mut chars)?,
+        };
+
+        let index = atoms.len();
+        atoms.push(atom);
+        if let Some(previous) = previous {
+            bonds.push((previous, index, pending_bond.take()));
+        }
+        previous = Some(index);
+    }
+
+    if !branches.is_empty() {
+        return Err(Error::UnbalancedBranch);
+    }
+    if let Some(number) = rings.keys().next() {
+        return Err(Error::UnclosedRing(*number));
+    }
+
+    let bond_types = kekulize(&atoms, &bonds);
+    let positions = layout::layout(atoms.len(), &bonds.iter().map(|(start, end, _)| (*start, *end)).collect::<Vec<_>>());
+
+    let maps = atoms
+        .iter()
+        .enumerate()
+        .filter_map(|(index, atom)| Some((index, atom.map?)))
+        .collect();
+
+    Ok(Structure {
+        maps,
+        atoms: atoms.into_iter().map(|atom| atom.label).zip(positions).collect(),
+        bonds: bonds
+            .iter()
+            .zip(bond_types)
+            .map(|((start, end, _), bond_type)| (*start, *end, bond_type))
+            .collect(),
+    })
+}
+
+fn ring_number(c: char, chars: &mut Peekable<Chars>) -> Option<u32> {
+    if c != '%' {
+        return c.to_digit(10);
+    }
+
+    let tens = chars.next()?.to_digit(10)?;
+    let ones = chars.next()?.to_digit(10)?;
+    Some(tens * 10 + ones)
+}
+
+/// atoms of the organic subset, written without brackets
+fn organic_atom(c: char, chars: &mut Peekable<Chars>) -> Result<SmilesAtom, Error> {
+    let symbol = match c {
+        'B' if chars.next_if_eq(&'r').is_some() => "Br",
+        'C' if chars.next_if_eq(&'l').is_some() => "Cl",
+        'B' | 'C' | 'N' | 'O' | 'P' | 'S' | 'F' | 'I' => &c.to_string(),
+        'b' | 'c' | 'n' | 'o' | 'p' | 's' => &c.to_ascii_uppercase().to_string(),
+        _ => return Err(Error::UnexpectedCharacter(c)),
+    };
+
+    Ok(SmilesAtom {
+        // carbons are drawn skeletally
+        label: if symbol == "C" { String::new() } else { symbol.to_string() },
+        aromatic: c.is_ascii_lowercase(),
+        explicit_hydrogens: false,
+        map: None,
+    })
+}
+
+/// atoms written in brackets, kept as written apart from chirality and atom maps
+fn bracket_atom(chars: &mut Peekable<Chars>) -> Result<SmilesAtom, Error> {
+    let mut contents = String::new();
+    loop {
+        match chars.next() {
+            Some(']') => break,
+            Some(c) => contents.push(c),
+            None => return Err(Error::UnclosedBracket),
+        }
+    }
+
+    let (contents, map) = match contents.split_once(':') {
+        Some((contents, map)) => (contents, Some(map.parse().map_err(|_| Error::InvalidAtomMap(map.to_string()))?)),
+        None => (contents.as_str(), None),
+    };
+    let contents = contents.replace('@', "");
+    let symbol_start = contents.find(|c: char| !c.is_ascii_digit()).unwrap_or(contents.len());
+    let (isotope, rest) = contents.split_at(symbol_start);
+
+    let aromatic = rest.starts_with(|c: char| c.is_ascii_lowercase());
+    let mut label = isotope.to_string();
+    let mut rest_chars = rest.chars();
+    if let Some(first) = rest_chars.next() {
+        label.push(first.to_ascii_uppercase());
+    }
+    label.push_str(rest_chars.as_str());
+
+    // a plain carbon only bracketed for its chirality is still drawn skeletally
+    let label = match label.as_str() {
+        "C" | "CH" => String::new(),
+        _ => label,
+    };
+
+    Ok(SmilesAtom {
+        explicit_hydrogens: rest.contains('H'),
+        label,
+        aromatic,
+        map,
+    })
+}
+
+/// chooses alternating single and double bonds for aromatic rings, searching for a way to give
+/// every aromatic atom that needs one exactly one double bond
+fn kekulize(atoms: &[SmilesAtom], bonds: &[(usize, usize, Option<char>)]) -> Vec<BondType> {
+    let needs_double = |atom: &SmilesAtom| {
+        atom.aromatic && !atom.explicit_hydrogens && matches!(atom.label.as_str(), "" | "N" | "B" | "P")
+    };
+    let is_aromatic = |(start, end, symbol): &(usize, usize, Option<char>)| {
+        matches!(symbol, Some(':') | None) && atoms[*start].aromatic && atoms[*end].aromatic
+    };
+
+    // bonds that could be double, by the atoms they join
+    let candidates = bonds
+        .iter()
+        .enumerate()
+        .filter(|(_, bond)| is_aromatic(bond) && needs_double(&atoms[bond.0]) && needs_double(&atoms[bond.1]))
+        .map(|(index, (start, end, _))| (index, *start, *end))
+        .collect::<Vec<_>>();
+
+    let mut paired = atoms.iter().map(|atom| !needs_double(atom)).collect::<Vec<_>>();
+    let mut doubles = vec![false; bonds.len()];
+    if !pair_atoms(&candidates, &mut paired.clone(), &mut doubles, false) {
+        pair_atoms(&candidates, &mut paired, &mut doubles, true);
+    }
+
+    bonds
+        .iter()
+        .zip(doubles)
+        .map(|((_, _, symbol), double)| match symbol {
+            Some('=') => BondType::Normal(2),
+            Some('#') => BondType::Normal(3),
+            Some('$') => BondType::Normal(4),
+            _ if double => BondType::Normal(2),
+            _ => BondType::Normal(1),
+        })
+        .collect()
+}
+
+/// pairs the first unpaired atom with each of its unpaired neighbours in turn until every atom
+/// is paired, returning false if there is no way to do so, unless `lenient` in which case atoms
+/// that can't be paired are left with single bonds
+fn pair_atoms(candidates: &[(usize, usize, usize)], paired: &mut [bool], doubles: &mut [bool], lenient: bool) -> bool {
+    let Some(atom) = paired.iter().position(|paired| !paired) else {
+        return true;
+    };
+
+    paired[atom] = true;
+    for &(bond, start, end) in candidates {
+        let neighbour = match (start == atom, end == atom) {
+            (true, _) => end,
+            (_, true) => start,
+            _ => continue,
+        };
+        if paired[neighbour] {
+            continue;
+        }
+
+        paired[neighbour] = true;
+        doubles[bond] = true;
+        if pair_atoms(candidates, paired, doubles, lenient) {
+            return true;
+        }
+        paired[neighbour] = false;
+        doubles[bond] = false;
+    }
+
+    if lenient {
+        return pair_atoms(candidates, paired, doubles, lenient);
+    }
+
+    paired[atom] = false;
+    false
+}
@@ -0,0 +1,152 @@
+use std::fmt::Write;
+
+use iced::widget::canvas::path::lyon_path::Event;
+use iced::widget::canvas::{LineCap, LineJoin, Path, Stroke, Style};
+use iced::{Color, Rectangle};
+
+use crate::surface::Surface;
+
+/// namespace of the element the molfile is embedded in
+pub const NAMESPACE: &str = "urn:molecular-canvas";
+/// element the molfile is embedded in, inside the svg's metadata
+pub const MOLFILE_ELEMENT: &str = "mc:molfile";
+/// element the whole document is embedded in beside the molfile, when the svg is of all of it
+pub const PROJECT_ELEMENT: &str = "mc:project";
+
+/// collects drawn paths as svg elements, in canvas units
+#[derive(Debug, Default)]
+pub struct SvgWriter {
+    elements: String,
+}
+
+impl SvgWriter {
+    /// wraps the drawing in an svg document showing `region`, with the molfile, and the project
+    /// it's drawn from when there is one, in its metadata
+    pub fn finish(self, region: Rectangle, background: Color, molfile: &str, project: Option<&str>) -> String {
+        let project = project
+            .map(|project| format!("<{element}><![CDATA[{project}]]></{element}>", element = PROJECT_ELEMENT))
+            .unwrap_or_default();
+
+        format!(
+            concat!(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:mc=\"{namespace}\" ",
+                "width=\"{width}\" height=\"{height}\" viewBox=\"{x} {y} {width} {height}\">\n",
+                "<metadata><{element}><![CDATA[{molfile}]]></{element}>{project}</metadata>\n",
+                "<rect x=\"{x}\" y=\"{y}\" width=\"{width}\" height=\"{height}\" fill=\"{background}\"/>\n",
+                "{elements}</svg>\n",
+            ),
+            namespace = NAMESPACE,
+            element = MOLFILE_ELEMENT,
+            x = region.x,
+            y = region.y,
+            width = region.width,
+            height = region.height,
+            molfile = molfile,
+            project = project,
+            background = hex(background),
+            elements = self.elements,
+        )
+    }
+
+    /// draws an image given by a url, such as a data url, stretched over `bounds`
+    pub fn image(&mut self, href: &str, bounds: Rectangle, opacity: f32) {
+        let _ = writeln!(
+            self.elements,
+            "<image x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" opacity=\"{}\" preserveAspectRatio=\"none\" href=\"{}\"/>",
+            bounds.x, bounds.y, bounds.width, bounds.height, opacity, href,
+        );
+    }
+}
+
+impl Surface for SvgWriter {
+    fn fill(&mut self, path: &Path, color: Color) {
+        let _ = writeln!(
+            self.elements,
+            "<path d=\"{}\" fill=\"{}\" fill-opacity=\"{}\"/>",
+            path_data(path),
+            hex(color),
+            color.a,
+        );
+    }
+
+    fn stroke(&mut self, path: &Path, stroke: Stroke<'_>) {
+        // gradients aren't used by any drawing, so they are exported as black
+        let color = match stroke.style {
+            Style::Solid(color) => color,
+            Style::Gradient(_) => Color::BLACK,
+        };
+        let line_cap = match stroke.line_cap {
+            LineCap::Butt => "butt",
+            LineCap::Square => "square",
+            LineCap::Round => "round",
+        };
+        let line_join = match stroke.line_join {
+            LineJoin::Miter => "miter",
+            LineJoin::Round => "round",
+            LineJoin::Bevel => "bevel",
+        };
+        let dash = match stroke.line_dash.segments {
+            [] => String::new(),
+            segments => format!(
+                " stroke-dasharray=\"{}\"",
+                segments.iter().map(|segment| segment.to_string()).collect::<Vec<_>>().join(" "),
+            ),
+        };
+
+        let _ = writeln!(
+            self.elements,
+            "<path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-opacity=\"{}\" stroke-width=\"{}\" stroke-linecap=\"{}\" stroke-linejoin=\"{}\"{}/>",
+            path_data(path),
+            hex(color),
+            color.a,
+            stroke.width,
+            line_cap,
+            line_join,
+            dash,
+        );
+    }
+}
+
+/// svg path commands for a canvas path
+fn path_data(path: &Path) -> String {
+    let mut data = String::new();
+
+    for event in path.raw().iter() {
+        let _ = match event {
+            Event::Begin { at } => write!(data, "M{} {} ", at.x, at.y),
+            Event::Line { to, .. } => write!(data, "L{} {} ", to.x, to.y),
+            Event::Quadratic { ctrl, to, .. } => write!(data, "Q{} {} {} {} ", ctrl.x, ctrl.y, to.x, to.y),
+            Event::Cubic { ctrl1, ctrl2, to, .. } => {
+                write!(data, "C{} {} {} {} {} {} ", ctrl1.x, ctrl1.y, ctrl2.x, ctrl2.y, to.x, to.y)
+            }
+            Event::End { close: true, .. } => write!(data, "Z "),
+            Event::End { close: false, .. } => Ok(()),
+        };
+    }
+
+    data.trim_end().to_string()
+}
+
+/// the molfile embedded in an svg written by `SvgWriter`
+pub fn embedded_molfile(svg: &str) -> Option<&str> {
+    embedded(svg, MOLFILE_ELEMENT)
+}
+
+/// the project embedded in an svg written by `SvgWriter` of a whole document
+pub fn embedded_project(svg: &str) -> Option<&str> {
+    embedded(svg, PROJECT_ELEMENT)
+}
+
+fn embedded<'a>(svg: &'a str, element: &str) -> Option<&'a str> {
+    let open = format!("<{}><![CDATA[", element);
+    let start = svg.find(&open)? + open.len();
+    let end = start + svg[start..].find("]]>")?;
+
+    Some(&svg[start..end])
+}
+
+fn hex(color: Color) -> String {
+    let [r, g, b, _] = color.into_rgba8();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
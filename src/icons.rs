@@ -0,0 +1,80 @@
+use std::path::PathBuf;
+
+use iced::widget::svg::Handle;
+use rustc_hash::FxHashMap;
+
+use crate::config;
+
+/// icons built into the binary, named as the toolbar names its tools
+const BUILTIN: [(&str, &[u8]); 26] = [
+    ("cursor-pointer", include_bytes!("../resources/cursor-pointer.svg")),
+    ("square-dashed", include_bytes!("../resources/square-dashed.svg")),
+    ("drag-hand-gesture", include_bytes!("../resources/drag-hand-gesture.svg")),
+    ("erase-solid", include_bytes!("../resources/erase-solid.svg")),
+    ("single", include_bytes!("../resources/single.svg")),
+    ("double", include_bytes!("../resources/double.svg")),
+    ("triple", include_bytes!("../resources/triple.svg")),
+    ("quadruple", include_bytes!("../resources/quadruple.svg")),
+    ("wedge", include_bytes!("../resources/wedge.svg")),
+    ("dash", include_bytes!("../resources/dash.svg")),
+    ("bold", include_bytes!("../resources/bold.svg")),
+    ("wavy", include_bytes!("../resources/wavy.svg")),
+    ("dative", include_bytes!("../resources/dative.svg")),
+    ("hydrogen-bond", include_bytes!("../resources/hydrogen-bond.svg")),
+    ("interaction", include_bytes!("../resources/interaction.svg")),
+    ("ink", include_bytes!("../resources/ink.svg")),
+    ("rectangle", include_bytes!("../resources/rectangle.svg")),
+    ("ellipse", include_bytes!("../resources/ellipse.svg")),
+    ("line", include_bytes!("../resources/line.svg")),
+    ("arrow", include_bytes!("../resources/arrow.svg")),
+    ("p-orbital", include_bytes!("../resources/p-orbital.svg")),
+    ("sp3-orbital", include_bytes!("../resources/sp3-orbital.svg")),
+    ("pi-orbital", include_bytes!("../resources/pi-orbital.svg")),
+    ("atom-map", include_bytes!("../resources/atom-map.svg")),
+    ("input-field", include_bytes!("../resources/input-field.svg")),
+    ("text", include_bytes!("../resources/text.svg")),
+];
+
+/// the toolbar's icons, a user's theme replacing whichever built in icons it has an svg file for,
+/// e.g. "wedge.svg" in the "icons" directory of the config directory
+#[derive(Debug, Clone)]
+pub struct IconTheme {
+    handles: FxHashMap<&'static str, Handle>,
+}
+
+impl IconTheme {
+    /// the built in icons with any the user's theme replaces
+    pub fn load() -> Self {
+        let directory = config::dir().map(|dir| dir.join("icons")).filter(|dir| dir.is_dir());
+
+        Self::new(directory)
+    }
+
+    fn new(directory: Option<PathBuf>) -> Self {
+        let handles = BUILTIN
+            .iter()
+            .map(|(name, bytes)| {
+                let themed = directory.as_ref().map(|directory| directory.join(format!("{}.svg", name)));
+                let handle = match themed.filter(|path| path.is_file()) {
+                    Some(path) => Handle::from_path(path),
+                    None => Handle::from_memory(*bytes),
+                };
+
+                (*name, handle)
+            })
+            .collect();
+
+        Self { handles }
+    }
+
+    /// the icon with a name, empty when there's none
+    pub fn icon(&self, name: &str) -> Handle {
+        self.handles.get(name).cloned().unwrap_or_else(|| Handle::from_memory(&[][..]))
+    }
+}
+
+impl Default for IconTheme {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
@@ -0,0 +1,1546 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use iced::widget::svg::Handle;
+use iced::widget::{
+    button, checkbox, column, container, horizontal_rule, pick_list, radio, row, scrollable, slider, svg, text, text_input,
+    Column,
+};
+use iced::{Alignment, Color, Element, Length};
+
+use crate::canvas::{
+    Arrangement, Background, Clipart, DashPattern, DocumentStyle, InkColor, LabelFont, Layer, MolCanvas, Shape, ShapeKind,
+    ShapeStyle, SingleSelection, TagFilter, Version,
+};
+use crate::chemistry::{find_functional_groups, Composition, Descriptors, MetalCenter};
+use crate::formats::{ReactionConditions, SequenceKind};
+use crate::molecule::{
+    AtomId, Bond, BondId, BondType, ChargeBracket, DisplayMode, LayerId, Molecule, MoleculeId, ShapeId, TextOutline,
+};
+use crate::recent::RecentFile;
+use crate::templates::Template;
+
+#[derive(Debug, Clone)]
+pub struct Inspector {
+    annotate_functional_groups: bool,
+    route_mode: bool,
+    /// routes whose steps are hidden in the overview, by their number
+    collapsed_routes: Vec<usize>,
+    /// text being typed into the bond length field of a bond
+    bond_length: Option<(BondId, String)>,
+    /// text being typed into the annotation field of a bond
+    bond_annotation: Option<(BondId, String)>,
+    /// text being typed into the bond angle field of an atom
+    bond_angle: Option<(AtomId, String)>,
+    /// text being typed into the map number field of an atom
+    atom_map: Option<(AtomId, String)>,
+    /// text being typed into the yield field of a reaction arrow
+    reaction_yield: Option<(ShapeId, String)>,
+    /// outline width the selected shape's slider is being dragged to
+    shape_width: Option<(ShapeId, f32)>,
+    /// text being typed into one of the other condition fields of a reaction arrow, by the
+    /// field's name
+    reaction_condition: Option<(ShapeId, &'static str, String)>,
+    /// name being typed for a molecule
+    name: Option<(MoleculeId, String)>,
+    /// caption being typed for a molecule
+    caption: Option<(MoleculeId, String)>,
+    /// text being typed into the CAS number, notes or source field of a molecule, by the field's
+    /// name
+    metadata: Option<(MoleculeId, &'static str, String)>,
+    /// comma separated tags being typed for a molecule
+    tags: Option<(MoleculeId, String)>,
+    /// text being typed into the document bond length field
+    document_bond_length: Option<String>,
+    /// text being typed into the selection scale factor field
+    scale_factor: Option<String>,
+    /// name being typed for a new layer
+    new_layer: String,
+    /// file exports are written to, its extension choosing between CSV, SDF, SVG and PNG
+    export_path: String,
+    /// text being typed into the export margin field
+    export_margin: Option<String>,
+    /// text being typed into the export resolution field
+    export_resolution: Option<String>,
+    /// outcome of the last export or file import
+    status: Option<String>,
+    /// name being typed for a new version
+    version_name: String,
+    /// name being typed to save the document as a template
+    template_name: String,
+    templates: Vec<Template>,
+    recent_files: Vec<RecentFile>,
+    /// DOI, CAS number or compound name being typed to look up
+    reference: String,
+    /// residue codes being typed to insert as a chain
+    sequence: String,
+    sequence_kind: SequenceKind,
+    /// draws the chain one labelled atom to each residue rather than atom by atom
+    sequence_condensed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    AnnotateFunctionalGroups(bool),
+    LewisStructures(bool),
+    BondCrossings(bool),
+    PartialBondDash(DashPattern),
+    LabelFont(LabelFont),
+    InkWidth(f32),
+    InkColor(InkColor),
+    DisplayMode(MoleculeId, DisplayMode),
+    BondType(MoleculeId, BondId, BondType),
+    BondLengthInput(BondId, String),
+    BondLengthSubmit(MoleculeId, BondId),
+    SetBondLength(MoleculeId, BondId, f32),
+    BondAngleInput(AtomId, String),
+    BondAngleSubmit(MoleculeId, AtomId),
+    AtomMapInput(AtomId, String),
+    AtomMapSubmit(MoleculeId, AtomId),
+    /// numbers an atom to match its counterpart across a reaction arrow, or clears its number
+    SetAtomMap(MoleculeId, AtomId, Option<u32>),
+    SetBondAngle(MoleculeId, AtomId, f32),
+    DocumentBondLengthInput(String),
+    DocumentBondLengthSubmit,
+    /// rescales the whole document to a new bond length
+    SetDocumentBondLength(f32),
+    ScaleFactorInput(String),
+    ScaleFactorSubmit,
+    ScaleSelection(f32),
+    FlipFragment(MoleculeId, BondId),
+    CurveBond(MoleculeId, BondId, f32),
+    ResolveOverlaps,
+    Arrange(Arrangement),
+    RouteMode(bool),
+    ArrangeRoutes,
+    /// shows or hides the steps of a route in the overview
+    ToggleRoute(usize),
+    /// selects the arrow of a route's step
+    SelectRouteStep(ShapeId),
+    NewLayerInput(String),
+    NewLayerSubmit,
+    NewLayer(String),
+    ActiveLayer(LayerId),
+    LayerVisible(LayerId, bool),
+    LayerLocked(LayerId, bool),
+    BackgroundOpacity(f32),
+    BackgroundExported(bool),
+    /// unlocking the background lets it be moved and scaled on the canvas
+    BackgroundLocked(bool),
+    RemoveBackground,
+    SetShapeStyle(ShapeId, ShapeStyle),
+    ShapeWidthInput(ShapeId, f32),
+    /// sets the width a shape's slider was let go at, keeping the rest of its style
+    ShapeWidthSubmit(ShapeId, ShapeStyle),
+    ReactionYieldInput(ShapeId, String),
+    /// sets the yield typed for an arrow, keeping the rest of its conditions
+    ReactionYieldSubmit(ShapeId, ReactionConditions),
+    ReactionConditionInput(ShapeId, &'static str, String),
+    /// sets the condition typed for an arrow, keeping the rest of its conditions
+    ReactionConditionSubmit(ShapeId, ReactionConditions),
+    SetReactionConditions(ShapeId, ReactionConditions),
+    DeleteShape(ShapeId),
+    MoveToLayer(MoleculeId, LayerId),
+    LockMolecule(MoleculeId),
+    UnlockAll,
+    NameInput(MoleculeId, String),
+    NameSubmit(MoleculeId),
+    RenameMolecule(MoleculeId, String),
+    CaptionInput(MoleculeId, String),
+    CaptionSubmit(MoleculeId),
+    SetCaption(MoleculeId, String),
+    MetadataInput(MoleculeId, &'static str, String),
+    /// sets the CAS number, notes or source typed for a molecule
+    MetadataSubmit(MoleculeId),
+    SetCas(MoleculeId, String),
+    SetNotes(MoleculeId, String),
+    TagsInput(MoleculeId, String),
+    TagsSubmit(MoleculeId),
+    SetTags(MoleculeId, Vec<String>),
+    SetSource(MoleculeId, String),
+    FilterTag(String, bool),
+    HideUnmatched(bool),
+    MoleculeNames(bool),
+    CompoundNumbers(bool),
+    InteractionDistances(bool),
+    BondAnnotationInput(BondId, String),
+    BondAnnotationSubmit(MoleculeId, BondId),
+    AnnotateBond(MoleculeId, BondId, String),
+    MarkDisconnection(MoleculeId, BondId, bool),
+    BondAnnotations(bool),
+    MetalCounts(bool),
+    LetterWithPrevious(MoleculeId, bool),
+    SetChargeBracket(MoleculeId, Option<ChargeBracket>),
+    ResizeChargeBracket(MoleculeId, f32),
+    AddInstance(MoleculeId),
+    UnlinkInstance(MoleculeId),
+    JumpToMolecule(MoleculeId),
+    SelectParentMolecule,
+    GrowSelection,
+    ExportPathInput(String),
+    ExportMarginInput(String),
+    ExportMarginSubmit,
+    /// sets the space left around exported images
+    ExportMargin(f32),
+    ExportResolutionInput(String),
+    ExportResolutionSubmit,
+    /// sets how many pixels to a canvas unit pngs are exported at
+    ExportResolution(f32),
+    Export(String),
+    /// copies the selection, or the whole drawing, to the clipboard as an image
+    CopyImage,
+    ZoomToFit,
+    Zoom(f32),
+    VersionNameInput(String),
+    VersionNameSubmit,
+    SaveVersion(String),
+    CompareVersion(Option<usize>),
+    RestoreVersion(usize),
+    DeleteVersion(usize),
+    TemplateNameInput(String),
+    TemplateNameSubmit,
+    /// saves the whole document to the templates library
+    SaveTemplate(String),
+    /// adds a template's structures to the document
+    InsertTemplate(PathBuf),
+    /// adds a flask, TLC plate or other piece of lab clipart to the document
+    InsertClipart(Clipart),
+    /// adds the structures of a file opened before to the document
+    OpenRecent(PathBuf),
+    ReferenceInput(String),
+    ReferenceSubmit,
+    /// looks up a CAS number or compound name and adds what's found to the document
+    InsertReference(String),
+    SequenceInput(String),
+    SequenceKind(SequenceKind),
+    SequenceCondensed(bool),
+    SequenceSubmit,
+    /// builds a peptide or nucleic acid chain from its residue codes, condensed or atom by atom
+    InsertSequence(String, SequenceKind, bool),
+}
+
+impl Default for Inspector {
+    fn default() -> Self {
+        Self {
+            annotate_functional_groups: false,
+            route_mode: false,
+            collapsed_routes: vec![],
+            bond_length: None,
+            bond_annotation: None,
+            document_bond_length: None,
+            scale_factor: None,
+            bond_angle: None,
+            atom_map: None,
+            reaction_yield: None,
+            shape_width: None,
+            reaction_condition: None,
+            name: None,
+            caption: None,
+            metadata: None,
+            tags: None,
+            new_layer: String::new(),
+            export_path: "molecules.csv".to_string(),
+            export_margin: None,
+            export_resolution: None,
+            status: None,
+            version_name: String::new(),
+            template_name: String::new(),
+            templates: vec![],
+            recent_files: vec![],
+            reference: String::new(),
+            sequence: String::new(),
+            sequence_kind: SequenceKind::default(),
+            sequence_condensed: false,
+        }
+    }
+}
+
+impl Inspector {
+    const WIDTH: f32 = 200.0;
+    const ZOOM_PRESETS: [f32; 3] = [0.5, 1.0, 2.0];
+
+    pub fn set_status(&mut self, status: String) {
+        self.status = Some(status);
+    }
+
+    pub fn set_templates(&mut self, templates: Vec<Template>) {
+        self.templates = templates;
+    }
+
+    pub fn set_recent_files(&mut self, recent_files: Vec<RecentFile>) {
+        self.recent_files = recent_files;
+    }
+
+    /// submitting a numeric field turns into the message setting its value
+    pub fn update(&mut self, message: Message) -> Message {
+        match &message {
+            Message::AnnotateFunctionalGroups(annotate) => {
+                self.annotate_functional_groups = *annotate;
+            }
+            Message::RouteMode(route_mode) => {
+                self.route_mode = *route_mode;
+            }
+            Message::ToggleRoute(route) => {
+                match self.collapsed_routes.iter().position(|collapsed| collapsed == route) {
+                    Some(index) => {
+                        self.collapsed_routes.remove(index);
+                    }
+                    None => self.collapsed_routes.push(*route),
+                }
+            }
+            Message::BondLengthInput(bond_id, value) => {
+                self.bond_length = Some((*bond_id, value.clone()));
+            }
+            Message::BondLengthSubmit(molecule_id, bond_id) => {
+                let value = self.bond_length.take().filter(|(editing, _)| editing == bond_id);
+                if let Some(length) = value.and_then(|(_, value)| value.trim().parse().ok()) {
+                    return Message::SetBondLength(*molecule_id, *bond_id, length);
+                }
+            }
+            Message::BondAngleInput(atom_id, value) => {
+                self.bond_angle = Some((*atom_id, value.clone()));
+            }
+            Message::BondAngleSubmit(molecule_id, atom_id) => {
+                let value = self.bond_angle.take().filter(|(editing, _)| editing == atom_id);
+                if let Some(degrees) = value.and_then(|(_, value)| value.trim().parse().ok()) {
+                    return Message::SetBondAngle(*molecule_id, *atom_id, degrees);
+                }
+            }
+            Message::AtomMapInput(atom_id, value) => {
+                self.atom_map = Some((*atom_id, value.clone()));
+            }
+            Message::AtomMapSubmit(molecule_id, atom_id) => {
+                let value = self.atom_map.take().filter(|(editing, _)| editing == atom_id);
+                // clearing the field unmaps the atom
+                match value.as_ref().map(|(_, value)| value.trim()) {
+                    Some("") => return Message::SetAtomMap(*molecule_id, *atom_id, None),
+                    Some(value) => {
+                        if let Some(map) = value.parse::<u32>().ok().filter(|map| *map > 0) {
+                            return Message::SetAtomMap(*molecule_id, *atom_id, Some(map));
+                        }
+                    }
+                    None => (),
+                }
+            }
+            Message::ReactionYieldInput(shape_id, value) => {
+                self.reaction_yield = Some((*shape_id, value.clone()));
+            }
+            Message::ReactionYieldSubmit(shape_id, conditions) => {
+                let value = self.reaction_yield.take().filter(|(editing, _)| editing == shape_id);
+                // a blank field clears the yield, anything but a percentage is ignored
+                let yield_percent = value.and_then(|(_, value)| match value.trim().trim_end_matches('%').trim() {
+                    "" => Some(None),
+                    value => value.parse::<f32>().ok().filter(|percent| (0.0..=100.0).contains(percent)).map(Some),
+                });
+                if let Some(yield_percent) = yield_percent {
+                    return Message::SetReactionConditions(*shape_id, ReactionConditions { yield_percent, ..conditions.clone() });
+                }
+            }
+            Message::ShapeWidthInput(shape_id, width) => {
+                self.shape_width = Some((*shape_id, *width));
+            }
+            Message::ShapeWidthSubmit(shape_id, style) => {
+                if let Some((_, width)) = self.shape_width.take().filter(|(editing, _)| editing == shape_id) {
+                    return Message::SetShapeStyle(*shape_id, ShapeStyle { width, ..*style });
+                }
+            }
+            Message::ReactionConditionInput(shape_id, field, value) => {
+                self.reaction_condition = Some((*shape_id, field, value.clone()));
+            }
+            Message::ReactionConditionSubmit(shape_id, conditions) => {
+                if let Some((_, field, value)) = self.reaction_condition.take().filter(|(editing, ..)| editing == shape_id) {
+                    let mut conditions = conditions.clone();
+                    conditions.set_field(field, &value);
+                    return Message::SetReactionConditions(*shape_id, conditions);
+                }
+            }
+            Message::NameInput(molecule_id, value) => {
+                self.name = Some((*molecule_id, value.clone()));
+            }
+            Message::NameSubmit(molecule_id) => {
+                if let Some((_, value)) = self.name.take().filter(|(editing, _)| editing == molecule_id) {
+                    return Message::RenameMolecule(*molecule_id, value);
+                }
+            }
+            Message::CaptionInput(molecule_id, value) => {
+                self.caption = Some((*molecule_id, value.clone()));
+            }
+            Message::CaptionSubmit(molecule_id) => {
+                if let Some((_, value)) = self.caption.take().filter(|(editing, _)| editing == molecule_id) {
+                    return Message::SetCaption(*molecule_id, value);
+                }
+            }
+            Message::MetadataInput(molecule_id, field, value) => {
+                self.metadata = Some((*molecule_id, field, value.clone()));
+            }
+            Message::MetadataSubmit(molecule_id) => {
+                match self.metadata.take().filter(|(editing, ..)| editing == molecule_id) {
+                    Some((_, "cas", value)) => return Message::SetCas(*molecule_id, value),
+                    Some((_, "notes", value)) => return Message::SetNotes(*molecule_id, value),
+                    Some((_, "source", value)) => return Message::SetSource(*molecule_id, value),
+                    _ => (),
+                }
+            }
+            Message::BondAnnotationInput(bond_id, value) => {
+                self.bond_annotation = Some((*bond_id, value.clone()));
+            }
+            Message::BondAnnotationSubmit(molecule_id, bond_id) => {
+                if let Some((_, value)) = self.bond_annotation.take().filter(|(editing, _)| editing == bond_id) {
+                    return Message::AnnotateBond(*molecule_id, *bond_id, value);
+                }
+            }
+            // the order replaces whatever was being typed
+            Message::AnnotateBond(_, bond_id, _) => {
+                self.bond_annotation = self.bond_annotation.take().filter(|(editing, _)| editing != bond_id);
+            }
+            Message::TagsInput(molecule_id, value) => {
+                self.tags = Some((*molecule_id, value.clone()));
+            }
+            Message::TagsSubmit(molecule_id) => {
+                if let Some((_, value)) = self.tags.take().filter(|(editing, _)| editing == molecule_id) {
+                    return Message::SetTags(*molecule_id, value.split(',').map(str::to_string).collect());
+                }
+            }
+            Message::DocumentBondLengthInput(value) => {
+                self.document_bond_length = Some(value.clone());
+            }
+            Message::DocumentBondLengthSubmit => {
+                let length = self.document_bond_length.take().and_then(|value| value.trim().parse::<f32>().ok());
+                if let Some(length) = length.filter(|length| length.is_finite() && *length > 0.0) {
+                    return Message::SetDocumentBondLength(length);
+                }
+            }
+            Message::ScaleFactorInput(value) => {
+                self.scale_factor = Some(value.clone());
+            }
+            Message::ScaleFactorSubmit => {
+                let factor = self.scale_factor.take().and_then(|value| value.trim().parse::<f32>().ok());
+                if let Some(factor) = factor.filter(|factor| factor.is_finite() && *factor > 0.0) {
+                    return Message::ScaleSelection(factor);
+                }
+            }
+            Message::ExportPathInput(path) => {
+                self.export_path = path.clone();
+            }
+            Message::ExportMarginInput(value) => {
+                self.export_margin = Some(value.clone());
+            }
+            Message::ExportMarginSubmit => {
+                let margin = self.export_margin.take().and_then(|value| value.trim().parse::<f32>().ok());
+                if let Some(margin) = margin.filter(|margin| margin.is_finite() && *margin >= 0.0) {
+                    return Message::ExportMargin(margin);
+                }
+            }
+            Message::ExportResolutionInput(value) => {
+                self.export_resolution = Some(value.clone());
+            }
+            Message::ExportResolutionSubmit => {
+                let resolution = self.export_resolution.take().and_then(|value| value.trim().parse::<f32>().ok());
+                if let Some(resolution) = resolution.filter(|resolution| resolution.is_finite() && *resolution > 0.0) {
+                    return Message::ExportResolution(resolution.min(MolCanvas::MAX_EXPORT_RESOLUTION));
+                }
+            }
+            Message::NewLayerInput(name) => {
+                self.new_layer = name.clone();
+            }
+            Message::VersionNameInput(name) => {
+                self.version_name = name.clone();
+            }
+            Message::VersionNameSubmit => {
+                return Message::SaveVersion(std::mem::take(&mut self.version_name));
+            }
+            Message::TemplateNameInput(name) => {
+                self.template_name = name.clone();
+            }
+            Message::TemplateNameSubmit => {
+                let name = std::mem::take(&mut self.template_name);
+                if !name.trim().is_empty() {
+                    return Message::SaveTemplate(name.trim().to_string());
+                }
+            }
+            Message::ReferenceInput(reference) => {
+                self.reference = reference.clone();
+            }
+            Message::ReferenceSubmit => {
+                let reference = std::mem::take(&mut self.reference);
+                if !reference.trim().is_empty() {
+                    return Message::InsertReference(reference.trim().to_string());
+                }
+            }
+            Message::SequenceInput(sequence) => {
+                self.sequence = sequence.clone();
+            }
+            Message::SequenceKind(kind) => {
+                self.sequence_kind = *kind;
+            }
+            Message::SequenceCondensed(condensed) => {
+                self.sequence_condensed = *condensed;
+            }
+            Message::SequenceSubmit => {
+                let sequence = std::mem::take(&mut self.sequence);
+                if !sequence.trim().is_empty() {
+                    return Message::InsertSequence(sequence.trim().to_string(), self.sequence_kind, self.sequence_condensed);
+                }
+            }
+            Message::NewLayerSubmit => {
+                let name = std::mem::take(&mut self.new_layer);
+                if !name.trim().is_empty() {
+                    return Message::NewLayer(name.trim().to_string());
+                }
+            }
+            Message::LewisStructures(_)
+            | Message::BondCrossings(_)
+            | Message::PartialBondDash(_)
+            | Message::LabelFont(_)
+            | Message::InkWidth(_)
+            | Message::InkColor(_)
+            | Message::DisplayMode(..)
+            | Message::BondType(..)
+            | Message::SetBondLength(..)
+            | Message::SetBondAngle(..)
+            | Message::SetAtomMap(..)
+            | Message::SetReactionConditions(..)
+            | Message::SetDocumentBondLength(_)
+            | Message::ScaleSelection(_)
+            | Message::FlipFragment(..)
+            | Message::CurveBond(..)
+            | Message::ResolveOverlaps
+            | Message::Arrange(_)
+            | Message::ArrangeRoutes
+            | Message::SelectRouteStep(_)
+            | Message::NewLayer(_)
+            | Message::ActiveLayer(_)
+            | Message::LayerVisible(..)
+            | Message::LayerLocked(..)
+            | Message::BackgroundOpacity(_)
+            | Message::BackgroundExported(_)
+            | Message::BackgroundLocked(_)
+            | Message::RemoveBackground
+            | Message::SetShapeStyle(..)
+            | Message::DeleteShape(_)
+            | Message::MoveToLayer(..)
+            | Message::LockMolecule(_)
+            | Message::UnlockAll
+            | Message::RenameMolecule(..)
+            | Message::SetCaption(..)
+            | Message::SetCas(..)
+            | Message::SetNotes(..)
+            | Message::SetTags(..)
+            | Message::SetSource(..)
+            | Message::FilterTag(..)
+            | Message::HideUnmatched(_)
+            | Message::MoleculeNames(_)
+            | Message::CompoundNumbers(_)
+            | Message::InteractionDistances(_)
+            | Message::MarkDisconnection(..)
+            | Message::BondAnnotations(_)
+            | Message::MetalCounts(_)
+            | Message::LetterWithPrevious(..)
+            | Message::SetChargeBracket(..)
+            | Message::ResizeChargeBracket(..)
+            | Message::AddInstance(_)
+            | Message::UnlinkInstance(_)
+            | Message::JumpToMolecule(_)
+            | Message::SelectParentMolecule
+            | Message::GrowSelection
+            | Message::ExportMargin(_)
+            | Message::ExportResolution(_)
+            | Message::Export(_)
+            | Message::CopyImage
+            | Message::ZoomToFit
+            | Message::Zoom(_)
+            | Message::SaveVersion(_)
+            | Message::CompareVersion(_)
+            | Message::RestoreVersion(_)
+            | Message::DeleteVersion(_)
+            | Message::SaveTemplate(_)
+            | Message::InsertTemplate(_)
+            | Message::InsertClipart(_)
+            | Message::OpenRecent(_)
+            | Message::InsertReference(_)
+            | Message::InsertSequence(..) => {}
+        }
+
+        message
+    }
+
+    fn property<'a>(name: &'a str, value: String) -> Element<'a, Message> {
+        row![
+            text(name).width(Length::Fill),
+            text(value),
+        ]
+        .into()
+    }
+
+    fn properties<'a>(molecule: &Molecule) -> Column<'a, Message> {
+        let descriptors = match Descriptors::new(molecule) {
+            Ok(descriptors) => descriptors,
+            Err(error) => return column![text(format!("{:#}", error))],
+        };
+
+        column![
+            Self::property("Formula", descriptors.formula.clone()),
+            Self::property("MW", format!("{:.2}", descriptors.molecular_weight)),
+            Self::property("Unsaturation", format!("{}", descriptors.degrees_of_unsaturation)),
+            Self::property("H-bond donors", descriptors.h_bond_donors.to_string()),
+            Self::property("H-bond acceptors", descriptors.h_bond_acceptors.to_string()),
+            Self::property("Rotatable bonds", descriptors.rotatable_bonds.to_string()),
+            Self::property("TPSA", format!("{:.1}", descriptors.tpsa)),
+            Self::property("cLogP", format!("{:.2}", descriptors.clogp)),
+            Self::property(
+                "Ro5 violations",
+                descriptors.rule_of_five_violations().to_string()
+            ),
+        ]
+    }
+
+    /// named molecules with their thumbnails, each jumping to its molecule when pressed
+    fn compounds<'a>(compounds: impl Iterator<Item = (MoleculeId, &'a str, Option<Handle>)>) -> Column<'a, Message> {
+        let rows = compounds
+            .map(|(molecule_id, name, thumbnail)| {
+                let thumbnail = thumbnail.map(|handle| svg(handle).width(48).height(48));
+
+                button(
+                    row![]
+                        .push_maybe(thumbnail)
+                        .push(text(name).size(12))
+                        .spacing(5)
+                        .align_y(Alignment::Center),
+                )
+                .style(button::text)
+                .width(Length::Fill)
+                .on_press(Message::JumpToMolecule(molecule_id))
+                .into()
+            })
+            .collect::<Vec<_>>();
+
+        let rows = match rows.is_empty() {
+            true => vec![text("No named molecules").size(12).into()],
+            false => rows,
+        };
+
+        column![text("Compounds"), horizontal_rule(1)].extend(rows).spacing(5)
+    }
+
+    /// the document read out as sentences, one per molecule
+    fn description<'a>(description: Vec<String>) -> Column<'a, Message> {
+        let lines = match description.is_empty() {
+            true => vec!["Empty document".to_string()],
+            false => description,
+        };
+
+        column![text("Description"), horizontal_rule(1)]
+            .extend(lines.into_iter().map(|line| text(line).size(12).into()))
+            .spacing(5)
+    }
+
+    fn functional_groups<'a>(molecule: &Molecule) -> Column<'a, Message> {
+        let groups = match find_functional_groups(molecule) {
+            Ok(groups) => groups,
+            Err(error) => return column![text(format!("{:#}", error))],
+        };
+
+        Column::with_children(groups.into_iter().map(|found| {
+            let pka = found
+                .group
+                .pka()
+                .map(|pka| format!("pKa ~{:.1}", pka))
+                .unwrap_or_default();
+
+            Self::property(found.group.name(), pka)
+        }))
+    }
+
+    fn document(&self, style: &DocumentStyle) -> Column<'_, Message> {
+        let dash_pattern = style.partial_bond_dash;
+
+        column![
+            text("Document"),
+            horizontal_rule(1),
+            checkbox("Annotate functional groups", self.annotate_functional_groups)
+                .on_toggle(Message::AnnotateFunctionalGroups),
+            checkbox("Lewis structures", style.lewis_structures)
+                .on_toggle(Message::LewisStructures),
+            checkbox("Bridge bond crossings", style.bond_crossings)
+                .on_toggle(Message::BondCrossings),
+            checkbox("Show molecule names", style.molecule_names)
+                .on_toggle(Message::MoleculeNames),
+            checkbox("Number compounds", style.compound_numbers)
+                .on_toggle(Message::CompoundNumbers),
+            checkbox("Show interaction distances", style.interaction_distances)
+                .on_toggle(Message::InteractionDistances),
+            checkbox("Show bond annotations", style.bond_annotations)
+                .on_toggle(Message::BondAnnotations),
+            checkbox("Show metal oxidation states", style.metal_counts)
+                .on_toggle(Message::MetalCounts),
+            text("Partial bond dashes"),
+            slider(0.5..=5.0, dash_pattern.dash, move |dash| {
+                Message::PartialBondDash(DashPattern { dash, ..dash_pattern })
+            })
+            .step(0.5),
+            slider(0.5..=5.0, dash_pattern.gap, move |gap| {
+                Message::PartialBondDash(DashPattern { gap, ..dash_pattern })
+            })
+            .step(0.5),
+            row![
+                text("Label font").width(Length::Fill),
+                pick_list(LabelFont::ALL, Some(style.label_font), Message::LabelFont).text_size(12),
+            ]
+            .align_y(Alignment::Center),
+            row![
+                text("Ink").width(Length::Fill),
+                pick_list(InkColor::ALL, Some(style.ink_color), Message::InkColor).text_size(12),
+            ]
+            .align_y(Alignment::Center),
+            slider(0.5..=5.0, style.ink_width, Message::InkWidth)
+            .step(0.5),
+            Self::numeric_field(
+                "Bond length",
+                self.document_bond_length.as_ref(),
+                Ok(style.bond_length),
+                true,
+                Message::DocumentBondLengthInput,
+                Message::DocumentBondLengthSubmit,
+            ),
+            Self::numeric_field(
+                "Scale selection",
+                self.scale_factor.as_ref(),
+                Ok(1.0),
+                true,
+                Message::ScaleFactorInput,
+                Message::ScaleFactorSubmit,
+            ),
+            row![button(text("Fit").size(12)).on_press(Message::ZoomToFit)]
+            .extend(Self::ZOOM_PRESETS.map(|scaling| {
+                button(text(format!("{}%", scaling * 100.0)).size(12)).on_press(Message::Zoom(scaling)).into()
+            }))
+            .spacing(5)
+            .align_y(Alignment::Center),
+            row![
+                button(text("Resolve overlaps").size(12)).on_press(Message::ResolveOverlaps),
+                button(text("Unlock all").size(12)).on_press(Message::UnlockAll),
+            ]
+            .spacing(5),
+            row![text("Arrange").size(12).width(Length::Fill)]
+                .extend(Arrangement::ALL.map(|arrangement| {
+                    button(text(arrangement.name()).size(12)).on_press(Message::Arrange(arrangement)).into()
+                }))
+                .spacing(5)
+                .align_y(Alignment::Center),
+            Self::numeric_field(
+                "Export margin",
+                self.export_margin.as_ref(),
+                Ok(style.export_margin),
+                true,
+                Message::ExportMarginInput,
+                Message::ExportMarginSubmit,
+            ),
+            Self::numeric_field(
+                "Export resolution",
+                self.export_resolution.as_ref(),
+                Ok(style.export_resolution),
+                true,
+                Message::ExportResolutionInput,
+                Message::ExportResolutionSubmit,
+            ),
+            row![
+                text_input("Export path", &self.export_path)
+                    .on_input(Message::ExportPathInput)
+                    .size(12)
+                    .width(Length::Fill),
+                button(text("Export").size(12))
+                    .on_press_maybe((!self.export_path.is_empty()).then(|| Message::Export(self.export_path.clone()))),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+            button(text("Copy as image (Ctrl+Shift+C)").size(12)).on_press(Message::CopyImage),
+        ]
+        .push_maybe(self.status.as_ref().map(|status| text(status).size(12)))
+        .spacing(5)
+    }
+
+    fn layers<'a>(&'a self, layers: impl Iterator<Item = (&'a LayerId, &'a Layer)>, active_layer: LayerId) -> Column<'a, Message> {
+        let rows = layers.map(|(layer_id, layer)| {
+            let layer_id = *layer_id;
+
+            row![
+                radio(layer.name(), layer_id, Some(active_layer), Message::ActiveLayer)
+                    .size(12)
+                    .text_size(12)
+                    .width(Length::Fill),
+                checkbox("Show", layer.is_visible())
+                    .on_toggle(move |visible| Message::LayerVisible(layer_id, visible))
+                    .size(12)
+                    .text_size(12),
+                checkbox("Lock", layer.is_locked())
+                    .on_toggle(move |locked| Message::LayerLocked(layer_id, locked))
+                    .size(12)
+                    .text_size(12),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center)
+            .into()
+        });
+
+        column![text("Layers"), horizontal_rule(1)]
+            .extend(rows)
+            .push(
+                text_input("New layer", &self.new_layer)
+                    .on_input(Message::NewLayerInput)
+                    .on_submit(Message::NewLayerSubmit)
+                    .size(12),
+            )
+            .spacing(5)
+    }
+
+    /// every tag in the document to narrow it down to, only shown when there are tags
+    fn tag_filter<'a>(tags: Vec<String>, tag_filter: &TagFilter) -> Option<Column<'a, Message>> {
+        if tags.is_empty() {
+            return None;
+        }
+
+        let rows = tags.into_iter().map(|tag| {
+            checkbox(tag.clone(), tag_filter.tags().contains(&tag))
+                .on_toggle(move |selected| Message::FilterTag(tag.clone(), selected))
+                .size(12)
+                .text_size(12)
+                .into()
+        });
+
+        Some(
+            column![text("Filter by tag"), horizontal_rule(1)]
+                .extend(rows)
+                .push(
+                    checkbox("Hide molecules without these tags", tag_filter.hide())
+                        .on_toggle(Message::HideUnmatched)
+                        .size(12)
+                        .text_size(12),
+                )
+                .spacing(5),
+        )
+    }
+
+    /// the image being traced over, only shown when there is one
+    fn background(background: &Background) -> Column<'_, Message> {
+        column![
+            text("Background"),
+            horizontal_rule(1),
+            row![
+                text("Opacity").size(12).width(Length::Fill),
+                slider(0.1..=1.0, background.opacity(), Message::BackgroundOpacity)
+                    .step(0.05)
+                    .width(Length::FillPortion(2)),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+            row![
+                checkbox("Export", background.is_exported())
+                    .on_toggle(Message::BackgroundExported)
+                    .size(12)
+                    .text_size(12),
+                checkbox("Lock", background.is_locked())
+                    .on_toggle(Message::BackgroundLocked)
+                    .size(12)
+                    .text_size(12),
+                button(text("Remove").size(12)).on_press(Message::RemoveBackground),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(5)
+    }
+
+    /// the selected shape's outline and fill, only shown when a shape is selected
+    fn shape<'a>(&'a self, shape_id: ShapeId, shape: &'a Shape) -> Column<'a, Message> {
+        let style = shape.style();
+        // the width only changes once the slider is let go, so a drag is undone in one step
+        let width = match self.shape_width {
+            Some((editing, width)) if editing == shape_id => width,
+            _ => style.width,
+        };
+        let fillable = matches!(shape.kind(), ShapeKind::Rectangle | ShapeKind::Ellipse);
+
+        column![
+            text(shape.kind().to_string()),
+            horizontal_rule(1),
+            row![
+                text("Color").size(12).width(Length::Fill),
+                pick_list(InkColor::ALL, Some(style.color), move |color| {
+                    Message::SetShapeStyle(shape_id, ShapeStyle { color, ..style })
+                })
+                .text_size(12),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+            row![
+                text("Width").size(12).width(Length::Fill),
+                slider(0.5..=5.0, width, move |width| Message::ShapeWidthInput(shape_id, width))
+                    .on_release(Message::ShapeWidthSubmit(shape_id, style))
+                    .step(0.5)
+                    .width(Length::FillPortion(2)),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+            row![
+                checkbox("Filled", style.filled)
+                    .on_toggle_maybe(fillable.then_some(move |filled| Message::SetShapeStyle(shape_id, ShapeStyle { filled, ..style })))
+                    .size(12)
+                    .text_size(12),
+                button(text("Delete").size(12)).on_press(Message::DeleteShape(shape_id)),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+        ]
+        .push_maybe((shape.kind() == ShapeKind::Arrow).then(|| self.reaction_conditions(shape_id, shape.conditions())))
+        .spacing(5)
+    }
+
+    /// the yield and conditions written under a reaction arrow
+    fn reaction_conditions<'a>(&'a self, shape_id: ShapeId, conditions: &ReactionConditions) -> Column<'a, Message> {
+        let yield_percent = match &self.reaction_yield {
+            Some((editing, value)) if *editing == shape_id => value.clone(),
+            _ => conditions.yield_percent.map(|percent| percent.to_string()).unwrap_or_default(),
+        };
+        let field = |name: &'a str, input: Element<'a, Message>| {
+            row![text(name).size(12).width(Length::Fill), input].align_y(Alignment::Center)
+        };
+        // each field takes effect on enter, by the name it's saved under
+        let text_field = |name: &'a str, key: &'static str, value: &str| {
+            let value = match &self.reaction_condition {
+                Some((editing, field, value)) if *editing == shape_id && *field == key => value.clone(),
+                _ => value.to_string(),
+            };
+
+            field(
+                name,
+                text_input("", &value)
+                    .on_input(move |value| Message::ReactionConditionInput(shape_id, key, value))
+                    .on_submit(Message::ReactionConditionSubmit(shape_id, conditions.clone()))
+                    .size(12)
+                    .width(Length::FillPortion(2))
+                    .into(),
+            )
+        };
+
+        column![
+            text("Conditions").size(12),
+            field(
+                "Yield (%)",
+                text_input("", &yield_percent)
+                    .on_input(move |value| Message::ReactionYieldInput(shape_id, value))
+                    .on_submit(Message::ReactionYieldSubmit(shape_id, conditions.clone()))
+                    .size(12)
+                    .width(Length::FillPortion(2))
+                    .into(),
+            ),
+            text_field("Temperature", "temperature", &conditions.temperature),
+            text_field("Time", "time", &conditions.time),
+            text_field("Solvent", "solvent", &conditions.solvent),
+            text_field("Catalyst", "catalyst", &conditions.catalyst),
+        ]
+        .spacing(5)
+    }
+
+    /// saved versions, each of which can be compared with the document or restored
+    fn history<'a>(&'a self, versions: impl Iterator<Item = &'a Version>, compared: Option<usize>) -> Column<'a, Message> {
+        let rows = versions.enumerate().map(|(index, version)| {
+            let compare = match compared == Some(index) {
+                true => button(text("Hide").size(12)).on_press(Message::CompareVersion(None)),
+                false => button(text("Diff").size(12)).on_press(Message::CompareVersion(Some(index))),
+            };
+
+            row![
+                column![
+                    text(version.name()).size(12),
+                    text(format!("{} molecules", version.molecule_count())).size(10),
+                ]
+                .width(Length::Fill),
+                compare,
+                button(text("Restore").size(12)).on_press(Message::RestoreVersion(index)),
+                button(text("x").size(12)).on_press(Message::DeleteVersion(index)),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center)
+            .into()
+        });
+
+        column![text("History"), horizontal_rule(1)]
+            .extend(rows)
+            .push(
+                row![
+                    text_input("Version name", &self.version_name)
+                        .on_input(Message::VersionNameInput)
+                        .on_submit(Message::VersionNameSubmit)
+                        .size(12)
+                        .width(Length::Fill),
+                    button(text("Save").size(12)).on_press(Message::VersionNameSubmit),
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center),
+            )
+            .spacing(5)
+    }
+
+    /// documents saved to start from, each added to the document when pressed, and lab clipart
+    fn templates(&self) -> Column<'_, Message> {
+        let rows = self.templates.iter().map(|template| {
+            let thumbnail = template
+                .thumbnail
+                .clone()
+                .map(|handle| svg(handle).width(48).height(48));
+
+            row![]
+                .push_maybe(thumbnail)
+                .push(text(&template.name).size(12).width(Length::Fill))
+                .push(button(text("Insert").size(12)).on_press(Message::InsertTemplate(template.path.clone())))
+                .spacing(5)
+                .align_y(Alignment::Center)
+                .into()
+        });
+
+        column![text("Templates"), horizontal_rule(1)]
+            .extend(rows)
+            .push(
+                row![
+                    text_input("Template name", &self.template_name)
+                        .on_input(Message::TemplateNameInput)
+                        .on_submit(Message::TemplateNameSubmit)
+                        .size(12)
+                        .width(Length::Fill),
+                    button(text("Save as template").size(12)).on_press(Message::TemplateNameSubmit),
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center),
+            )
+            .push(
+                row![
+                    text("Clipart").size(12).width(Length::Fill),
+                    pick_list(Clipart::ALL, None::<Clipart>, Message::InsertClipart)
+                        .placeholder("Insert clipart")
+                        .text_size(12),
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center),
+            )
+            .spacing(5)
+    }
+
+    /// files opened before, each opened again when pressed, none when nothing has been opened
+    fn recent_files(&self) -> Option<Column<'_, Message>> {
+        if self.recent_files.is_empty() {
+            return None;
+        }
+
+        let rows = self.recent_files.iter().map(|recent| {
+            let thumbnail = recent
+                .thumbnail
+                .clone()
+                .map(|handle| svg(handle).width(48).height(48));
+
+            row![]
+                .push_maybe(thumbnail)
+                .push(text(&recent.name).size(12).width(Length::Fill))
+                .push(button(text("Open").size(12)).on_press(Message::OpenRecent(recent.path.clone())))
+                .spacing(5)
+                .align_y(Alignment::Center)
+                .into()
+        });
+
+        Some(column![text("Recent files"), horizontal_rule(1)].extend(rows).spacing(5))
+    }
+
+    /// compounds looked up in PubChem by CAS number or name, or named in the title of a DOI
+    fn reference(&self) -> Column<'_, Message> {
+        column![
+            text("Insert from reference"),
+            horizontal_rule(1),
+            row![
+                text_input("DOI, CAS number or name", &self.reference)
+                    .on_input(Message::ReferenceInput)
+                    .on_submit(Message::ReferenceSubmit)
+                    .size(12)
+                    .width(Length::Fill),
+                button(text("Insert").size(12)).on_press(Message::ReferenceSubmit),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(5)
+    }
+
+    /// peptides and nucleic acids built from their residue codes
+    fn sequence(&self) -> Column<'_, Message> {
+        column![
+            text("Insert sequence"),
+            horizontal_rule(1),
+            row![
+                pick_list(SequenceKind::ALL, Some(self.sequence_kind), Message::SequenceKind).text_size(12),
+                checkbox("Condensed", self.sequence_condensed).on_toggle(Message::SequenceCondensed).size(12).text_size(12),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+            row![
+                text_input("ACDG, Ala-Gly, ATGC or HELM", &self.sequence)
+                    .on_input(Message::SequenceInput)
+                    .on_submit(Message::SequenceSubmit)
+                    .size(12)
+                    .width(Length::Fill),
+                button(text("Insert").size(12)).on_press(Message::SequenceSubmit),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(5)
+    }
+
+    /// CAS number, tags, notes and source of a compound, each taking effect on enter
+    fn metadata<'a>(&self, molecule_id: MoleculeId, molecule: &Molecule) -> Column<'a, Message> {
+        let metadata = molecule.metadata();
+        let tags = match &self.tags {
+            Some((editing, value)) if *editing == molecule_id => value.clone(),
+            _ => metadata.tags().join(", "),
+        };
+        let field = |placeholder: &str, name: &'static str, value: Option<&str>| {
+            let value = match &self.metadata {
+                Some((editing, field, typed)) if *editing == molecule_id && *field == name => typed.as_str(),
+                _ => value.unwrap_or_default(),
+            };
+            text_input(placeholder, value)
+                .on_input(move |value| Message::MetadataInput(molecule_id, name, value))
+                .on_submit(Message::MetadataSubmit(molecule_id))
+                .size(12)
+        };
+
+        column![
+            field("CAS number", "cas", metadata.cas()),
+            text_input("Tags, separated by commas", &tags)
+                .on_input(move |value| Message::TagsInput(molecule_id, value))
+                .on_submit(Message::TagsSubmit(molecule_id))
+                .size(12),
+            field("Notes", "notes", metadata.notes()),
+            field("Source", "source", metadata.source()),
+        ]
+        .spacing(5)
+    }
+
+    fn display_modes<'a>(molecule_id: MoleculeId, molecule: &Molecule) -> Column<'a, Message> {
+        Column::with_children(DisplayMode::ALL.into_iter().map(|display_mode| {
+            radio(
+                display_mode.name(),
+                display_mode,
+                Some(molecule.display_mode()),
+                move |display_mode| Message::DisplayMode(molecule_id, display_mode),
+            )
+            .into()
+        }))
+        .spacing(2)
+    }
+
+    /// toggles the brackets around a molecule, stepping their charge and sizing them while shown
+    fn charge_bracket<'a>(molecule_id: MoleculeId, molecule: &Molecule) -> Column<'a, Message> {
+        let Some(charge_bracket) = molecule.charge_bracket() else {
+            return column![checkbox("Charge bracket", false)
+                .on_toggle(move |_| Message::SetChargeBracket(molecule_id, Some(ChargeBracket::new(1))))];
+        };
+
+        let step = move |step: i32| {
+            Message::SetChargeBracket(
+                molecule_id,
+                Some(ChargeBracket { charge: charge_bracket.charge + step, ..charge_bracket }),
+            )
+        };
+
+        column![
+            checkbox("Charge bracket", true).on_toggle(move |_| Message::SetChargeBracket(molecule_id, None)),
+            row![
+                text(format!("Charge {}", charge_bracket.charge)).size(12).width(Length::Fill),
+                button(text("−").size(12)).on_press(step(-1)),
+                button(text("+").size(12)).on_press(step(1)),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+            slider(0.0..=ChargeBracket::MAX_PADDING, charge_bracket.padding, move |padding| {
+                Message::ResizeChargeBracket(molecule_id, padding)
+            })
+            .step(1.0),
+        ]
+        .spacing(5)
+    }
+
+    /// an editable number, showing the typed text while editing and the current value otherwise
+    fn numeric_field<'a>(
+        name: &'a str,
+        editing: Option<&String>,
+        value: Result<f32>,
+        editable: bool,
+        on_input: impl Fn(String) -> Message + 'a,
+        on_submit: Message,
+    ) -> Element<'a, Message> {
+        let value = match value {
+            Ok(value) if editable => value,
+            Ok(value) => return Self::property(name, format!("{:.1}", value)),
+            Err(_) => return Self::property(name, "-".to_string()),
+        };
+        let value = editing.cloned().unwrap_or_else(|| format!("{:.1}", value));
+
+        row![
+            text(name).width(Length::Fill),
+            text_input("", &value)
+                .on_input(on_input)
+                .on_submit(on_submit)
+                .size(12)
+                .width(Length::Fixed(60.0)),
+        ]
+        .align_y(Alignment::Center)
+        .into()
+    }
+
+    fn bond<'a>(&'a self, molecule_id: MoleculeId, bond_id: BondId, molecule: &Molecule) -> Column<'a, Message> {
+        let Ok(bond) = molecule.get_bond(&bond_id) else {
+            return column![];
+        };
+        // resonance bonds sit between single and double
+        let order = match bond.bond_type() {
+            BondType::PartialDouble => "1.5".to_string(),
+            bond_type => bond_type.order().to_string(),
+        };
+        let annotation = match &self.bond_annotation {
+            Some((editing, value)) if *editing == bond_id => value.as_str(),
+            _ => bond.annotation().map(TextOutline::content).unwrap_or_default(),
+        };
+
+        column![
+            text("Bond"),
+            horizontal_rule(1),
+            pick_list(BondType::ALL, Some(bond.bond_type()), move |bond_type| {
+                Message::BondType(molecule_id, bond_id, bond_type)
+            })
+            .text_size(12),
+            Self::numeric_field(
+                "Length",
+                self.bond_length
+                    .as_ref()
+                    .filter(|(editing, _)| *editing == bond_id)
+                    .map(|(_, value)| value),
+                molecule.bond_length(&bond_id),
+                // stretching a ring bond would distort the ring
+                !molecule.is_ring_bond(&bond_id),
+                move |value| Message::BondLengthInput(bond_id, value),
+                Message::BondLengthSubmit(molecule_id, bond_id),
+            ),
+            button(text("Flip substituent").size(12)).on_press_maybe(
+                (!molecule.is_ring_bond(&bond_id)).then_some(Message::FlipFragment(molecule_id, bond_id))
+            ),
+            // a curved bond's curve is dragged from its middle like a bend
+            row![
+                button(text("Curve").size(12)).on_press_maybe(
+                    (bond.curvature() == 0.0).then_some(Message::CurveBond(molecule_id, bond_id, Bond::DEFAULT_CURVATURE))
+                ),
+                button(text("Straighten").size(12)).on_press_maybe(
+                    (bond.curvature() != 0.0 || !bond.waypoints().is_empty())
+                        .then_some(Message::CurveBond(molecule_id, bond_id, 0.0))
+                ),
+            ]
+            .spacing(5),
+            row![
+                text_input("Annotation", annotation)
+                    .on_input(move |value| Message::BondAnnotationInput(bond_id, value))
+                    .on_submit(Message::BondAnnotationSubmit(molecule_id, bond_id))
+                    .size(12),
+                button(text("Order").size(12)).on_press(Message::AnnotateBond(molecule_id, bond_id, order)),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+            checkbox("Disconnection", bond.is_disconnection())
+                .on_toggle(move |disconnection| Message::MarkDisconnection(molecule_id, bond_id, disconnection)),
+        ]
+        .spacing(5)
+    }
+
+    fn atom<'a>(&'a self, molecule_id: MoleculeId, atom_id: AtomId, molecule: &Molecule) -> Column<'a, Message> {
+        column![
+            text("Atom"),
+            horizontal_rule(1),
+            Self::numeric_field(
+                "Angle",
+                self.bond_angle
+                    .as_ref()
+                    .filter(|(editing, _)| *editing == atom_id)
+                    .map(|(_, value)| value),
+                molecule.bond_angle(&atom_id),
+                molecule
+                    .angle_bonds(&atom_id)
+                    .is_ok_and(|bonds| bonds.iter().all(|(bond_id, _)| !molecule.is_ring_bond(bond_id))),
+                move |value| Message::BondAngleInput(atom_id, value),
+                Message::BondAngleSubmit(molecule_id, atom_id),
+            ),
+            self.atom_map(molecule_id, atom_id, molecule),
+            Self::element(atom_id, molecule),
+        ]
+        .spacing(5)
+    }
+
+    /// the number matching the atom to its counterpart across a reaction arrow
+    fn atom_map<'a>(&'a self, molecule_id: MoleculeId, atom_id: AtomId, molecule: &Molecule) -> Element<'a, Message> {
+        let map = molecule.get_atom(&atom_id).ok().and_then(|atom| atom.map());
+        let value = match &self.atom_map {
+            Some((editing, value)) if *editing == atom_id => value.clone(),
+            _ => map.map(|map| map.to_string()).unwrap_or_default(),
+        };
+
+        row![
+            text("Map").width(Length::Fill),
+            text_input("-", &value)
+                .on_input(move |value| Message::AtomMapInput(atom_id, value))
+                .on_submit(Message::AtomMapSubmit(molecule_id, atom_id))
+                .size(12)
+                .width(Length::Fixed(60.0)),
+        ]
+        .align_y(Alignment::Center)
+        .into()
+    }
+
+    /// map numbers not shared by one reactant atom and one product atom of a reaction, and
+    /// reactions whose products don't have the atoms of their reactants
+    fn reaction_warnings<'a>(unbalanced_atom_maps: Vec<u32>, mass_imbalances: Vec<String>) -> Option<Column<'a, Message>> {
+        let atom_maps = (!unbalanced_atom_maps.is_empty()).then(|| {
+            let maps = unbalanced_atom_maps.iter().map(u32::to_string).collect::<Vec<_>>().join(", ");
+            format!("Unbalanced atom maps: {}", maps)
+        });
+        let warnings = atom_maps.into_iter().chain(mass_imbalances).collect::<Vec<_>>();
+
+        (!warnings.is_empty()).then(|| {
+            column![text("Reaction warnings"), horizontal_rule(1)]
+                .extend(warnings.into_iter().map(|warning| text(warning).size(12).style(text::danger).into()))
+                .spacing(5)
+        })
+    }
+
+    /// the steps of each synthesis route, a step selecting its arrow when clicked, and each route
+    /// collapsing to its heading
+    fn routes<'a>(&self, routes: Vec<Vec<(ShapeId, String)>>) -> Option<Column<'a, Message>> {
+        if routes.is_empty() {
+            return None;
+        }
+
+        let rows = routes.into_iter().enumerate().flat_map(|(route, steps)| {
+            let collapsed = self.collapsed_routes.contains(&route);
+            let heading = format!(
+                "{} Route {} ({} {})",
+                if collapsed { "▸" } else { "▾" },
+                route + 1,
+                steps.len(),
+                if steps.len() == 1 { "step" } else { "steps" },
+            );
+            let heading = button(text(heading).size(12))
+                .style(button::text)
+                .width(Length::Fill)
+                .on_press(Message::ToggleRoute(route));
+
+            let steps = steps
+                .into_iter()
+                .enumerate()
+                .filter(move |_| !collapsed)
+                .map(|(number, (arrow_id, step))| {
+                    button(text(format!("{}. {}", number + 1, step)).size(12))
+                        .style(button::text)
+                        .width(Length::Fill)
+                        .padding([2, 15])
+                        .on_press(Message::SelectRouteStep(arrow_id))
+                        .into()
+                });
+
+            std::iter::once(heading.into()).chain(steps).collect::<Vec<Element<'a, Message>>>()
+        });
+
+        Some(
+            column![
+                text("Synthesis routes"),
+                horizontal_rule(1),
+                row![
+                    checkbox("Route mode", self.route_mode).on_toggle(Message::RouteMode).text_size(12),
+                    button(text("Arrange").size(12)).on_press(Message::ArrangeRoutes),
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center),
+            ]
+            .extend(rows)
+            .spacing(5),
+        )
+    }
+
+    /// data for the element bonds attach to in the atom's label
+    fn element<'a>(atom_id: AtomId, molecule: &Molecule) -> Column<'a, Message> {
+        let composition = molecule
+            .get_atom(&atom_id)
+            .and_then(|atom| Ok(Composition::parse(&atom.label())?));
+        let element = match composition {
+            Ok(composition) => composition.attachment(),
+            Err(error) => return column![text(format!("{:#}", error))],
+        };
+
+        let [red, green, blue] = element.rgb();
+        let swatch = container(text(""))
+            .width(12)
+            .height(12)
+            .style(move |_| container::Style::default().background(Color::from_rgb8(red, green, blue)));
+
+        let isotopes = element.isotopes.iter().map(|isotope| {
+            Self::property(
+                "",
+                format!(
+                    "{}{} {:.4} ({:.2}%)",
+                    isotope.mass_number,
+                    element.symbol,
+                    isotope.mass,
+                    isotope.abundance * 100.0
+                ),
+            )
+        });
+
+        // counted by the ionic model, for metals only
+        let metal_center = MetalCenter::new(molecule, &atom_id).ok().flatten();
+        let metal_properties = metal_center.into_iter().flat_map(|metal_center| {
+            let oxidation_state = match metal_center.oxidation_state {
+                state if state > 0 => format!("+{}", state),
+                state => state.to_string(),
+            };
+            let optional = |count: Option<u32>| count.map_or_else(|| "-".to_string(), |count| count.to_string());
+
+            [
+                Self::property("Oxidation state", oxidation_state),
+                Self::property("d electrons", optional(metal_center.d_electrons.map(u32::from))),
+                Self::property("Electron count", optional(metal_center.electron_count)),
+            ]
+        });
+
+        column![
+            row![
+                text(element.name).width(Length::Fill),
+                text(element.atomic_number.to_string()),
+                swatch,
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+            Self::property("Mass", format!("{:.3}", element.mass)),
+            Self::property(
+                "Electronegativity",
+                element
+                    .electronegativity
+                    .map_or_else(|| "-".to_string(), |electronegativity| format!("{:.2}", electronegativity))
+            ),
+            Self::property("Valence", element.valence.to_string()),
+        ]
+        .extend(metal_properties)
+        .extend(isotopes)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn view<'a>(
+        &'a self,
+        molecule: Option<(MoleculeId, &Molecule)>,
+        selected: Option<SingleSelection>,
+        is_instance: bool,
+        style: &DocumentStyle,
+        layers: impl Iterator<Item = (&'a LayerId, &'a Layer)>,
+        active_layer: LayerId,
+        background: Option<&'a Background>,
+        shape: Option<(ShapeId, &'a Shape)>,
+        tags: Vec<String>,
+        tag_filter: &TagFilter,
+        versions: impl Iterator<Item = &'a Version>,
+        compared_version: Option<usize>,
+        compounds: impl Iterator<Item = (MoleculeId, &'a str, Option<Handle>)>,
+        description: Vec<String>,
+        unbalanced_atom_maps: Vec<u32>,
+        mass_imbalances: Vec<String>,
+        routes: Vec<Vec<(ShapeId, String)>>,
+    ) -> Element<'a, Message> {
+        let item = match (selected, molecule) {
+            (Some(SingleSelection::Bond(molecule_id, bond_id)), Some((_, molecule))) => {
+                Some(self.bond(molecule_id, bond_id, molecule))
+            }
+            (Some(SingleSelection::Atom(molecule_id, atom_id)), Some((_, molecule))) => {
+                Some(self.atom(molecule_id, atom_id, molecule))
+            }
+            _ => None,
+        };
+        let item = item.map(|item| {
+            item.push(button(text("Select molecule (Ctrl+M)").size(12)).on_press(Message::SelectParentMolecule))
+                .push(button(text("Grow selection (Ctrl+G)").size(12)).on_press(Message::GrowSelection))
+        });
+
+        let name = match (&self.name, molecule) {
+            (Some((editing, value)), Some((molecule_id, _))) if *editing == molecule_id => value.clone(),
+            (_, Some((_, molecule))) => molecule.name().unwrap_or_default().to_string(),
+            (_, None) => String::new(),
+        };
+        let caption = match (&self.caption, molecule) {
+            (Some((editing, value)), Some((molecule_id, _))) if *editing == molecule_id => value.clone(),
+            (_, Some((_, molecule))) => molecule.caption().unwrap_or_default().to_string(),
+            (_, None) => String::new(),
+        };
+
+        let content = match molecule {
+            Some((molecule_id, molecule)) => column![
+                text_input("Name", &name)
+                    .on_input(move |value| Message::NameInput(molecule_id, value))
+                    .on_submit(Message::NameSubmit(molecule_id))
+                    .size(12),
+                text_input("Caption", &caption)
+                    .on_input(move |value| Message::CaptionInput(molecule_id, value))
+                    .on_submit(Message::CaptionSubmit(molecule_id))
+                    .size(12),
+                self.metadata(molecule_id, molecule),
+                checkbox("Letter with previous compound", molecule.letters_with_previous())
+                    .on_toggle(move |letters| Message::LetterWithPrevious(molecule_id, letters)),
+                Self::properties(molecule),
+                text("Display"),
+                horizontal_rule(1),
+                Self::display_modes(molecule_id, molecule),
+                Self::charge_bracket(molecule_id, molecule),
+                row![
+                    button(text("Move to active layer").size(12)).on_press_maybe(
+                        (molecule.layer() != active_layer).then_some(Message::MoveToLayer(molecule_id, active_layer))
+                    ),
+                    button(text("Lock").size(12)).on_press(Message::LockMolecule(molecule_id)),
+                ]
+                .spacing(5),
+                row![
+                    button(text("Add linked instance").size(12)).on_press(Message::AddInstance(molecule_id)),
+                    button(text("Unlink").size(12)).on_press_maybe(is_instance.then_some(Message::UnlinkInstance(molecule_id))),
+                ]
+                .spacing(5),
+                text("Functional groups"),
+                horizontal_rule(1),
+                Self::functional_groups(molecule),
+            ]
+            .spacing(5),
+            None => column![text("No molecule selected")],
+        };
+
+        let panel = column![text("Properties"), horizontal_rule(1), content]
+            .push_maybe(item)
+            .push(self.document(style))
+            .push(self.layers(layers, active_layer))
+            .push_maybe(background.map(Self::background))
+            .push_maybe(shape.map(|(shape_id, shape)| self.shape(shape_id, shape)))
+            .push_maybe(Self::tag_filter(tags, tag_filter))
+            .push(Self::compounds(compounds))
+            .push_maybe(self.routes(routes))
+            .push_maybe(Self::reaction_warnings(unbalanced_atom_maps, mass_imbalances))
+            .push(self.history(versions, compared_version))
+            .push(self.templates())
+            .push_maybe(self.recent_files())
+            .push(self.reference())
+            .push(self.sequence())
+            .push(Self::description(description))
+            .spacing(5);
+
+        // the panel is longer than most windows are tall, so it scrolls
+        scrollable(container(panel).padding(5))
+            .width(Length::Fixed(Self::WIDTH))
+            .height(Length::Fill)
+            .into()
+    }
+}
@@ -0,0 +1,278 @@
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use thiserror::Error;
+
+use crate::formats::{parse_smiles, Structure};
+
+/// PubChem's REST service for compounds found by name, which takes CAS numbers as names too
+const PUBCHEM: &str = "https://pubchem.ncbi.nlm.nih.gov/rest/pug/compound/name";
+/// CrossRef's REST service for the works DOIs name
+const CROSSREF: &str = "https://api.crossref.org/works";
+/// seconds a request waits for an answer before giving up
+const TIMEOUT: &str = "20";
+/// most words of a work's title looked up in PubChem, as each is a request of its own
+const MAX_TITLE_NAMES: usize = 8;
+/// words too common in titles to be compound names
+const TITLE_STOP_WORDS: [&str; 24] = [
+    "about", "after", "against", "among", "analysis", "approach", "based", "between", "catalysed", "catalyzed",
+    "derived", "effect", "efficient", "enantioselective", "from", "highly", "novel", "reaction", "reactions",
+    "study", "synthesis", "through", "total", "using",
+];
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("enter a DOI, CAS number or compound name to look up")]
+    EmptyQuery,
+    #[error("PubChem has no compound matching \"{0}\"")]
+    NotFound(String),
+    #[error("CrossRef has no work with the DOI \"{0}\"")]
+    DoiNotFound(String),
+    #[error("PubChem has no compounds named in the title of \"{0}\"")]
+    NothingNamed(String),
+    #[error("request failed: {0}")]
+    RequestFailed(String),
+    #[error("unexpected response from PubChem")]
+    InvalidResponse,
+    #[error("unexpected response from CrossRef")]
+    InvalidWork,
+}
+
+/// a compound found for a reference, with where it came from
+#[derive(Debug, Clone)]
+pub struct Compound {
+    pub name: String,
+    /// the compound's structures, one to each disconnected part like the ions of a salt
+    pub structures: Vec<Structure>,
+    /// record the structure was taken from, e.g. "PubChem CID 702 (64-17-5)"
+    pub source: String,
+}
+
+/// the compounds for a DOI, found through CrossRef, or those PubChem has for a CAS number or name
+///
+/// requests are made with curl, so no http client is built in, and are waited on, so lookups are
+/// run off the window's thread
+pub fn resolve(query: &str) -> Result<Vec<Compound>> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err(Error::EmptyQuery).context("while looking up compound");
+    }
+
+    match doi(query) {
+        Some(doi) => crossref(doi).with_context(|| format!("while resolving DOI {}", doi)),
+        None => pubchem(query).context("while looking up compound"),
+    }
+}
+
+/// the DOI a query names, with or without a `doi:` or `https://doi.org/` prefix
+fn doi(query: &str) -> Option<&str> {
+    let lowercase = query.to_lowercase();
+    let prefix = ["https://doi.org/", "http://doi.org/", "https://dx.doi.org/", "doi.org/", "doi:"]
+        .into_iter()
+        .find(|prefix| lowercase.starts_with(prefix))
+        .map_or(0, str::len);
+    let doi = query[prefix..].trim();
+
+    doi.starts_with("10.").then_some(doi)
+}
+
+/// the compounds PubChem has for a CAS number or name
+fn pubchem(query: &str) -> Result<Vec<Compound>> {
+    let url = format!("{}/{}/property/Title,SMILES/CSV", PUBCHEM, percent_encode(query));
+    let csv = get(&url).map_err(|error| match error {
+        // names PubChem doesn't know are answered with not found
+        Error::RequestFailed(message) if message.contains("404") => Error::NotFound(query.to_string()),
+        error => error,
+    })?;
+
+    parse_properties(query, &csv)
+}
+
+/// the compounds PubChem finds for the words of the title of the work a DOI names, e.g.
+/// "strychnine" in "A concise total synthesis of strychnine", each with the work as its source
+///
+/// CrossRef only knows a work's bibliographic record, so its title is all there is to go on
+fn crossref(doi: &str) -> Result<Vec<Compound>> {
+    let url = format!("{}/{}", CROSSREF, percent_encode(doi).replace("%2F", "/"));
+    let json = get(&url).map_err(|error| match error {
+        Error::RequestFailed(message) if message.contains("404") => Error::DoiNotFound(doi.to_string()),
+        error => error,
+    })?;
+
+    let title = json_strings(&json, "title").into_iter().next().ok_or(Error::InvalidWork)?;
+    let title = strip_tags(&title);
+    let journal = json_strings(&json, "container-title").into_iter().next();
+    let work = match journal {
+        Some(journal) => format!("\"{}\", {}, doi:{}", title, strip_tags(&journal), doi),
+        None => format!("\"{}\", doi:{}", title, doi),
+    };
+
+    let mut compounds = vec![];
+    for name in title_names(&title) {
+        match pubchem(&name) {
+            Ok(found) => compounds.extend(found.into_iter().map(|compound| Compound {
+                source: format!("{}, named in {}", compound.source, work),
+                ..compound
+            })),
+            // most words of a title aren't compounds
+            Err(error) if matches!(error.downcast_ref(), Some(Error::NotFound(_))) => continue,
+            Err(error) => return Err(error),
+        }
+    }
+
+    match compounds.is_empty() {
+        true => Err(Error::NothingNamed(doi.to_string()).into()),
+        false => Ok(compounds),
+    }
+}
+
+/// the words of a title that could be compound names, longest first, with the punctuation around
+/// them trimmed but the brackets and dashes of names like "(−)-strychnine" kept
+fn title_names(title: &str) -> Vec<String> {
+    let mut names = vec![];
+    for word in title.split_whitespace() {
+        let word = word.trim_matches(|c: char| matches!(c, ',' | '.' | ':' | ';' | '!' | '?' | '"' | '\'')).to_lowercase();
+        if word.chars().count() >= 5 && !TITLE_STOP_WORDS.contains(&word.as_str()) && !names.contains(&word) {
+            names.push(word);
+        }
+    }
+    names.sort_by_key(|name| std::cmp::Reverse(name.chars().count()));
+    names.truncate(MAX_TITLE_NAMES);
+
+    names
+}
+
+/// the body of a successful GET request
+fn get(url: &str) -> Result<String, Error> {
+    let output = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--location", "--max-time", TIMEOUT])
+        .arg(url)
+        .output()
+        .map_err(|error| Error::RequestFailed(format!("couldn't run curl: {}", error)))?;
+
+    match output.status.success() {
+        true => Ok(String::from_utf8_lossy(&output.stdout).to_string()),
+        false => Err(Error::RequestFailed(String::from_utf8_lossy(&output.stderr).trim().to_string())),
+    }
+}
+
+/// the strings in the first array a key is given in, e.g. `"title":["..."]`, reading just enough
+/// JSON for CrossRef's answers
+fn json_strings(json: &str, key: &str) -> Vec<String> {
+    let pattern = format!("\"{}\":[", key);
+    let Some(start) = json.find(&pattern) else {
+        return vec![];
+    };
+
+    let mut strings = vec![];
+    let mut chars = json[start + pattern.len()..].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            ']' => break,
+            '"' => strings.push(json_string(&mut chars)),
+            _ => (),
+        }
+    }
+
+    strings
+}
+
+/// the rest of a JSON string after its opening quote, unescaped
+fn json_string(chars: &mut std::str::Chars) -> String {
+    let mut string = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => break,
+            '\\' => match chars.next() {
+                Some('n') => string.push('\n'),
+                Some('t') => string.push('\t'),
+                Some('u') => {
+                    let code = chars.by_ref().take(4).collect::<String>();
+                    string.extend(u32::from_str_radix(&code, 16).ok().and_then(char::from_u32));
+                }
+                Some(escaped) => string.push(escaped),
+                None => break,
+            },
+            c => string.push(c),
+        }
+    }
+
+    string
+}
+
+/// text without the markup CrossRef titles can have, e.g. `<i>trans</i>`
+fn strip_tags(text: &str) -> String {
+    let mut stripped = String::new();
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => stripped.push(c),
+            _ => (),
+        }
+    }
+
+    stripped.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// reads the CSV table of properties PubChem answers with, one compound to a row
+fn parse_properties(query: &str, csv: &str) -> Result<Vec<Compound>> {
+    let mut lines = csv.lines().filter(|line| !line.trim().is_empty());
+    let header = csv_fields(lines.next().ok_or(Error::InvalidResponse)?);
+    let column = |names: &[&str]| header.iter().position(|field| names.contains(&field.as_str()));
+
+    let cid = column(&["CID"]).ok_or(Error::InvalidResponse)?;
+    let title = column(&["Title"]);
+    // older answers name the column after the property that was asked for before it was renamed
+    let smiles = column(&["SMILES", "IsomericSMILES"]).ok_or(Error::InvalidResponse)?;
+
+    let mut compounds = vec![];
+    for line in lines {
+        let fields = csv_fields(line);
+        let (Some(cid), Some(smiles)) = (fields.get(cid), fields.get(smiles)) else {
+            return Err(Error::InvalidResponse.into());
+        };
+
+        compounds.push(Compound {
+            name: title.and_then(|title| fields.get(title)).cloned().unwrap_or_else(|| query.to_string()),
+            structures: parse_smiles(smiles).with_context(|| format!("while reading CID {}", cid))?,
+            source: format!("PubChem CID {} ({})", cid, query),
+        });
+    }
+
+    Ok(compounds)
+}
+
+/// the fields of a CSV line, which are quoted when they hold commas, with doubled quotes inside
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.trim_end().chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
+
+/// escapes everything but unreserved characters for use in a url path
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
@@ -1,8 +1,20 @@
 mod application;
 mod canvas;
+mod chemistry;
+mod config;
+mod cursor_input;
+mod inspector;
 mod molecule;
 mod toolbar;
 mod bounds;
+mod formats;
+mod icons;
+mod lookup;
+mod surface;
+mod recent;
+mod session;
+mod templates;
+mod thumbnail;
 
 pub fn main() -> iced::Result {
     tracing_subscriber::fmt::init();
@@ -1,49 +1,110 @@
+use std::borrow::Cow;
 use std::collections::VecDeque;
+use std::f32::consts::PI;
 use std::iter;
 
 use anyhow::{Context, Result};
-use atom::Direction;
-use bond::draw_bond;
 use iced::widget::canvas::path::lyon_path::math::Transform;
-use iced::widget::canvas::{Frame, Stroke};
+use iced::widget::canvas::{Frame, Path, Stroke};
+use crate::surface::Surface;
 use iced::Point;
-use iced::{Color, Vector};
-use rustc_hash::{FxHashMap, FxHashSet};
+use iced::{Color, Font, Rectangle, Vector};
+use indexmap::IndexMap;
+use rustc_hash::{FxBuildHasher, FxHashMap, FxHashSet};
 
+mod arc;
 mod atom;
 mod atom_position;
 mod bond;
+mod charge_bracket;
+mod display_mode;
 mod error;
 mod id;
+mod metadata;
 mod molecule_position;
+mod text_outline;
 
-pub use atom::Atom;
+pub use atom::{Atom, Direction, Token, GREEK_LETTERS};
 pub use atom_position::AtomPosition;
-pub use bond::{Bond, BondType};
+pub use bond::{draw_bond, Bond, BondEnd, BondType};
+pub use charge_bracket::ChargeBracket;
+pub use display_mode::DisplayMode;
 pub use error::Error;
-pub use id::{AtomId, BondId, MoleculeId};
+pub use id::{AnnotationId, AtomId, BondId, InkId, InteractionId, LayerId, MoleculeId, OrbitalId, ShapeId};
+pub use metadata::Metadata;
 pub use molecule_position::MoleculePosition;
+pub use text_outline::TextOutline;
+
+/// hash map that iterates in insertion order, so drawing, hit testing and export are repeatable
+pub type FxIndexMap<K, V> = IndexMap<K, V, FxBuildHasher>;
 
 use crate::bounds::Bounds;
-use crate::canvas::MolCanvas;
+use crate::canvas::{DocumentStyle, MolCanvas};
+use crate::chemistry::{MetalCenter, MolecularGraph};
 
 #[derive(Debug, Clone)]
 pub struct Molecule {
-    atoms: FxHashMap<AtomId, Atom>,
-    bonds: FxHashMap<BondId, Bond>,
+    atoms: FxIndexMap<AtomId, Atom>,
+    bonds: FxIndexMap<BondId, Bond>,
     local_bounds: Bounds,
     position: MoleculePosition,
+    display_mode: DisplayMode,
+    label_font: Font,
+    layer: LayerId,
+    /// locked molecules can't be selected, moved or erased
+    locked: bool,
+    /// name or compound ID, drawn under the structure when the document shows names
+    name: Option<TextOutline>,
+    /// legend drawn under the structure, wrapped to a fixed width
+    caption: Option<TextOutline>,
+    /// shares the compound number of the molecule before it, the two lettered as a series
+    letters_with_previous: bool,
+    /// brackets drawn around the whole structure with its overall charge
+    charge_bracket: Option<ChargeBracket>,
+    metadata: Metadata,
 }
 
 impl Molecule {
-    pub fn new(canvas_position: Point, atom_id: AtomId, label: String) -> Result<Self> {
-        let atom = Atom::new(label, AtomPosition::default(), Direction::default());
+    /// extra clearance given to horizontal labels when choosing a direction
+    const HORIZONTAL_LABEL_BIAS: f32 = PI / 12.0;
+    /// bonds closer than this to a label's direction push its trailing tokens aside
+    const LABEL_CLEARANCE: f32 = PI / 3.0;
+    /// largest shift of a label's trailing tokens
+    const LABEL_NUDGE: f32 = 4.0;
+    const NAME_FONT_SIZE: f32 = 8.0;
+    const CAPTION_FONT_SIZE: f32 = 7.0;
+    /// width captions wrap at
+    const CAPTION_WIDTH: f32 = 120.0;
+    /// space between the structure and the text stacked under it
+    const CAPTION_GAP: f32 = 4.0;
+    const ANNOTATION_FONT_SIZE: f32 = 7.0;
+    /// space between a bond and its annotation
+    const ANNOTATION_GAP: f32 = 2.0;
+    /// length of the wavy line marking a disconnection across a bond, how far its waves reach to
+    /// either side and how many there are
+    const DISCONNECTION_LENGTH: f32 = 16.0;
+    const DISCONNECTION_AMPLITUDE: f32 = 2.0;
+    const DISCONNECTION_WAVES: f32 = 2.5;
+    /// points the wavy line is drawn through
+    const DISCONNECTION_SEGMENTS: usize = 40;
+
+    pub fn new(canvas_position: Point, atom_id: AtomId, label: String, label_font: Font, layer: LayerId) -> Result<Self> {
+        let atom = Atom::new(label, AtomPosition::default(), Direction::default(), label_font);
 
         let mut molecule = Molecule {
-            atoms: FxHashMap::from_iter([(atom_id, atom)]),
-            bonds: FxHashMap::default(),
+            atoms: FxIndexMap::from_iter([(atom_id, atom)]),
+            bonds: FxIndexMap::default(),
             local_bounds: Bounds::default(),
             position: canvas_position.into(),
+            display_mode: DisplayMode::default(),
+            label_font,
+            layer,
+            locked: false,
+            name: None,
+            caption: None,
+            letters_with_previous: false,
+            charge_bracket: None,
+            metadata: Metadata::default(),
         };
 
         molecule.compute_bounds().context("while creating new molecule")?;
@@ -54,9 +115,9 @@ impl Molecule {
         self.atoms.iter()
     }
 
-    // pub fn bonds(&self) -> impl Iterator<Item = (&BondId, &Bond)> {
-    //     self.bonds.iter()
-    // }
+    pub fn bonds(&self) -> impl Iterator<Item = (&BondId, &Bond)> {
+        self.bonds.iter()
+    }
 
     fn compute_bounds(&mut self) -> Result<()> {
         let mut atoms = self.atoms.values();
@@ -77,14 +138,16 @@ impl Molecule {
 
     pub fn draw(
         &self,
-        frame: &mut Frame,
+        frame: &mut impl Surface,
         atom_color: &Color,
         bond_stroke: &Stroke,
         bond_color: &Color,
+        style: &DocumentStyle,
     ) -> Result<()> {
         let transform = self.position.into();
+        let atoms = self.display_atoms();
 
-        for atom in self.atoms.values() {
+        for atom in atoms.values() {
             atom.draw(frame, &transform, atom_color)?;
             // atom.bounds().draw(frame, Stroke {
             //         style: Style::Solid(Color::WHITE),
@@ -94,7 +157,7 @@ impl Molecule {
         }
 
         for bond in self.bonds.values() {
-            bond.draw(frame, &transform, &self.atoms, bond_stroke, bond_color)?;
+            bond.draw(frame, &transform, &atoms, bond_stroke, bond_color, style)?;
             // bond.bounds(&self.atoms).draw(frame, Stroke {
             //         style: Style::Solid(Color::WHITE),
             //         width: 1.0,
@@ -102,9 +165,116 @@ impl Molecule {
             //     }, 1.0, MolCanvas::SELECT_PADDING)
         }
 
+        for path in self.disconnections() {
+            frame.stroke(&path, Stroke::default().with_width(bond_stroke.width).with_color(*bond_color));
+        }
+
+        if style.bond_annotations {
+            for (annotation, position) in self.bond_annotations() {
+                annotation.draw(frame, position, *atom_color);
+            }
+        }
+
+        if style.metal_counts {
+            for (label, position) in self.metal_labels() {
+                label.draw(frame, position, *atom_color);
+            }
+        }
+
+        if let Some(charge_bracket) = &self.charge_bracket {
+            let structure = self.bounds().bounding_rectangle();
+            charge_bracket.draw(frame, structure, bond_stroke, *atom_color, self.label_font);
+        }
+
         Ok(())
     }
 
+    /// draws a single bond on top of what has already been drawn
+    pub fn draw_bond(
+        &self,
+        frame: &mut impl Surface,
+        bond_id: &BondId,
+        bond_stroke: &Stroke,
+        bond_color: &Color,
+        style: &DocumentStyle,
+    ) -> Result<()> {
+        let bond = self.get_bond(bond_id).context("while drawing bond")?;
+
+        bond.draw(frame, &self.position.into(), &self.display_atoms(), bond_stroke, bond_color, style)
+    }
+
+    /// atoms as they are drawn, with carbons labelled according to the display mode
+    fn display_atoms(&self) -> Cow<'_, FxIndexMap<AtomId, Atom>> {
+        if self.display_mode == DisplayMode::Skeletal {
+            return Cow::Borrowed(&self.atoms);
+        }
+
+        let mut atoms = self.atoms.clone();
+
+        for (atom_id, atom) in atoms.iter_mut() {
+            if !atom.label().is_empty() {
+                continue;
+            }
+
+            let orders = self
+                .attached_bonds(*atom_id)
+                .map(|(_bond_id, bond)| bond.bond_type().order())
+                .filter(|order| *order > 0)
+                .collect::<Vec<_>>();
+
+            if self.display_mode == DisplayMode::Condensed && orders.len() > 1 {
+                continue;
+            }
+
+            let valence: u32 = orders.iter().map(|order| *order as u32).sum();
+            let label = match 4u32.saturating_sub(valence) {
+                0 => "C".to_string(),
+                1 => "CH".to_string(),
+                hydrogens => format!("CH{}", hydrogens),
+            };
+
+            atom.rename(label);
+        }
+
+        Cow::Owned(atoms)
+    }
+
+    pub fn display_mode(&self) -> DisplayMode {
+        self.display_mode
+    }
+
+    pub fn set_display_mode(&mut self, display_mode: DisplayMode) {
+        self.display_mode = display_mode;
+    }
+
+    pub fn set_label_font(&mut self, label_font: Font) -> Result<()> {
+        self.label_font = label_font;
+        if let Some(name) = self.name.take() {
+            self.set_name(name.content().to_string());
+        }
+        if let Some(caption) = self.caption.take() {
+            self.set_caption(caption.content().to_string());
+        }
+        for bond in self.bonds.values_mut() {
+            if let Some(annotation) = bond.annotation() {
+                let annotation = TextOutline::new(annotation.content().to_string(), Self::ANNOTATION_FONT_SIZE, label_font);
+                bond.set_annotation(Some(annotation));
+            }
+        }
+
+        for atom in self.atoms.values_mut() {
+            atom.set_font(label_font);
+        }
+
+        let atom_ids = self.atoms.keys().copied().collect::<Vec<_>>();
+        for atom_id in atom_ids {
+            self.update_atom_label_direction(&atom_id).context("while setting label font")?;
+        }
+
+        self.compute_bounds().context("while setting label font")
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn draw_pending_bond(
         &self,
         frame: &mut Frame,
@@ -113,6 +283,7 @@ impl Molecule {
         bond_type: &BondType,
         stroke: &Stroke,
         color: &Color,
+        style: &DocumentStyle,
     ) -> Result<()> {
         draw_bond(
             frame,
@@ -122,12 +293,13 @@ impl Molecule {
             bond_type,
             stroke,
             color,
+            style,
         )
     }
 
     pub fn extend(&mut self, mut molecule: Molecule) {
         let offset: Vector = Point::from(molecule.position) - Point::from(self.position);
-        for (atom_id, mut atom) in molecule.atoms.drain() {
+        for (atom_id, mut atom) in molecule.atoms.drain(..) {
             atom.translate(offset);
             self.atoms.insert(atom_id, atom);
         }
@@ -138,6 +310,69 @@ impl Molecule {
         self.local_bounds = self.local_bounds.union(&bounds);
     }
 
+    /// takes on another molecule's atoms and bonds, placed as they are relative to it, keeping its
+    /// own position, for a linked copy of it. the maps from the other molecule's ids to this one's
+    /// are kept up to date, so atoms and bonds keep their ids from one copy to the next
+    pub fn copy_structure(
+        &mut self,
+        master: &Molecule,
+        atom_ids: &mut FxHashMap<AtomId, AtomId>,
+        bond_ids: &mut FxHashMap<BondId, BondId>,
+    ) -> Result<()> {
+        atom_ids.retain(|master_id, _| master.atoms.contains_key(master_id));
+        bond_ids.retain(|master_id, _| master.bonds.contains_key(master_id));
+
+        self.atoms = master
+            .atoms
+            .iter()
+            .map(|(master_id, atom)| (*atom_ids.entry(*master_id).or_insert_with(AtomId::new), atom.clone()))
+            .collect();
+
+        let mut bonds = FxIndexMap::default();
+        for (master_id, bond) in &master.bonds {
+            let [start, end] = [bond.start(), bond.end()].map(|atom_id| {
+                atom_ids.get(&atom_id).copied().ok_or(Error::AtomMissing(atom_id))
+            });
+            let bond = bond.with_atoms(start.context("while copying structure")?, end.context("while copying structure")?);
+            bonds.insert(*bond_ids.entry(*master_id).or_insert_with(BondId::new), bond);
+        }
+        self.bonds = bonds;
+
+        self.compute_bounds().context("while copying structure")
+    }
+
+    /// whether this molecule's atoms and bonds are the other's, placed and drawn the same, the maps
+    /// giving the other molecule's id for each of this one's
+    pub fn same_structure(
+        &self,
+        other: &Molecule,
+        atom_ids: impl Fn(&AtomId) -> Option<AtomId>,
+        bond_ids: impl Fn(&BondId) -> Option<BondId>,
+    ) -> bool {
+        if self.atoms.len() != other.atoms.len() || self.bonds.len() != other.bonds.len() {
+            return false;
+        }
+
+        let same_atoms = self.atoms.iter().all(|(atom_id, atom)| {
+            atom_ids(atom_id).and_then(|other_id| other.atoms.get(&other_id)).is_some_and(|other_atom| {
+                atom.label() == other_atom.label() && atom.position() == other_atom.position() && atom.map() == other_atom.map()
+            })
+        });
+
+        same_atoms
+            && self.bonds.iter().all(|(bond_id, bond)| {
+                bond_ids(bond_id).and_then(|other_id| other.bonds.get(&other_id)).is_some_and(|other_bond| {
+                    atom_ids(&bond.start()) == Some(other_bond.start())
+                        && atom_ids(&bond.end()) == Some(other_bond.end())
+                        && bond.bond_type() == other_bond.bond_type()
+                        && bond.waypoints() == other_bond.waypoints()
+                        && bond.curvature() == other_bond.curvature()
+                        && bond.is_disconnection() == other_bond.is_disconnection()
+                        && bond.annotation().map(TextOutline::content) == other_bond.annotation().map(TextOutline::content)
+                })
+            })
+    }
+
     pub fn rename_atom(&mut self, atom_id: &AtomId, text: String) -> Result<()> {
         let atom = self.get_atom_mut(atom_id).context("while renaming atom")?;
         atom.rename(text);
@@ -172,7 +407,7 @@ impl Molecule {
         let position = AtomPosition::from(self.position, canvas_position);
         if self
             .atoms
-            .insert(atom_id, Atom::new(label, position, Direction::default()))
+            .insert(atom_id, Atom::new(label, position, Direction::default(), self.label_font))
             .is_some()
         {
             return Err(Error::AtomCollision(atom_id)).context("while adding atom");
@@ -185,7 +420,7 @@ impl Molecule {
 
     pub fn delete_atom(&mut self, atom_id: AtomId) -> Result<impl IntoIterator<Item = Molecule>> {
         self.atoms
-            .remove(&atom_id)
+            .shift_remove(&atom_id)
             .ok_or(Error::AtomMissing(atom_id))
             .context("while deleting atom")?;
 
@@ -196,7 +431,7 @@ impl Molecule {
         let connected_atoms = self.get_directly_connected(atom_id).collect::<Vec<_>>();
 
         for bond_id in attached_bonds {
-            self.bonds.remove(&bond_id);
+            self.bonds.shift_remove(&bond_id);
         }
 
         for atom_id in &connected_atoms {
@@ -212,7 +447,7 @@ impl Molecule {
     pub fn delete_bond(&mut self, bond_id: BondId) -> Result<impl Iterator<Item = Molecule>> {
         let bond = self
             .bonds
-            .remove(&bond_id)
+            .shift_remove(&bond_id)
             .ok_or(Error::BondMissing(bond_id))
             .context("while deleting bond")?;
         let bond_atoms = bond.atom_ids().collect::<Vec<_>>();
@@ -226,7 +461,40 @@ impl Molecule {
             .map(Vec::into_iter)
     }
 
-    /// removes all non-connected fragments in the molecule and returns them as new molecules
+    /// moves one end of a bond onto another atom and returns any molecules that have become detached
+    pub fn reattach_bond(&mut self, bond_id: &BondId, end: BondEnd, atom_id: AtomId) -> Result<Vec<Molecule>> {
+        self.get_atom(&atom_id).context("while reattaching bond")?;
+        let bond = self.get_bond(bond_id).context("while reattaching bond")?;
+
+        let old_atom_id = bond.atom(end);
+        let fixed_atom_id = bond.atom(end.other());
+        if atom_id == fixed_atom_id || atom_id == old_atom_id {
+            return Ok(vec![]);
+        }
+        // the atoms are already bonded, a second bond between them would be a duplicate
+        if self.get_directly_connected(fixed_atom_id).any(|connected| connected == atom_id) {
+            return Ok(vec![]);
+        }
+
+        self.get_bond_mut(bond_id).context("while reattaching bond")?.set_atom(end, atom_id);
+
+        for atom_id in [old_atom_id, fixed_atom_id, atom_id] {
+            self.update_atom_label_direction(&atom_id).context("while reattaching bond")?;
+        }
+
+        self.split_fragments([fixed_atom_id, old_atom_id].into_iter())
+            .context("while reattaching bond")
+    }
+
+    /// splits every disconnected fragment off into its own molecule
+    pub fn split_all_fragments(&mut self) -> Result<Vec<Molecule>> {
+        let atom_ids = self.atoms.keys().copied().collect::<Vec<_>>();
+
+        self.split_fragments(atom_ids.into_iter())
+    }
+
+    /// removes all non-connected fragments in the molecule and returns them as new molecules, the
+    /// largest fragment stays as the molecule itself along with its name, caption, charge bracket and metadata
     fn split_fragments(&mut self, atom_ids: impl Iterator<Item = AtomId>) -> Result<Vec<Molecule>> {
         let atom_sets = atom_ids.map(|atom| self.get_connected(atom));
 
@@ -253,15 +521,21 @@ impl Molecule {
             return Ok(vec![]);
         }
 
+        let kept = unique_atom_sets
+            .iter()
+            .enumerate()
+            .max_by_key(|(index, atom_set)| (atom_set.len(), std::cmp::Reverse(*index)))
+            .map_or(0, |(index, _)| index);
+        unique_atom_sets.remove(kept);
+
         let mut molecules = vec![];
-        // first unque atom set is the molecule itself
-        for atom_set in &unique_atom_sets[1..] {
-            let mut atoms: FxHashMap<AtomId, Atom> = FxHashMap::default();
-            let mut bonds: FxHashMap<BondId, Bond> = FxHashMap::default();
+        for atom_set in &unique_atom_sets {
+            let mut atoms: FxIndexMap<AtomId, Atom> = FxIndexMap::default();
+            let mut bonds: FxIndexMap<BondId, Bond> = FxIndexMap::default();
             for atom_id in atom_set {
                 let atom = self
                     .atoms
-                    .remove(atom_id)
+                    .shift_remove(atom_id)
                     .ok_or(Error::AtomMissing(*atom_id))
                     .context("while removing atom from original fragment")
                     .context("while splitting fragments")?;
@@ -281,6 +555,16 @@ impl Molecule {
                 bonds,
                 local_bounds: Bounds::default(),
                 position: self.position,
+                display_mode: self.display_mode,
+                label_font: self.label_font,
+                layer: self.layer,
+                locked: self.locked,
+                // the name, caption, charge bracket and metadata stay with the original molecule
+                name: None,
+                caption: None,
+                letters_with_previous: self.letters_with_previous,
+                charge_bracket: None,
+                metadata: Metadata::default(),
             };
 
             molecule.compute_bounds().context("while splitting fragments")?;
@@ -319,13 +603,16 @@ impl Molecule {
             .filter(move |(_bond_id, bond)| bond.start() == atom_id || bond.end() == atom_id)
     }
 
-    fn get_directly_connected(&self, atom_id: AtomId) -> impl Iterator<Item = AtomId> + '_ {
+    pub fn get_directly_connected(&self, atom_id: AtomId) -> impl Iterator<Item = AtomId> + '_ {
         self.attached_bonds(atom_id)
             .flat_map(|(_bond_id, bond)| bond.atom_ids())
             .filter(move |bond_atom_id| *bond_atom_id != atom_id)
     }
 
     pub fn add_bond(&mut self, start: AtomId, end: AtomId, bond_strength: BondType) -> Result<()> {
+        if start == end {
+            return Err(Error::SelfBond(start)).context("while adding bond");
+        }
         let bond_id = BondId::new();
 
         if self
@@ -343,14 +630,16 @@ impl Molecule {
         Ok(())
     }
 
+    /// atoms whose bounds, grown by the tolerance, contain the position
     pub fn atoms_at(
         &self,
         canvas_position: Point,
+        tolerance: f32,
     ) -> impl IntoIterator<Item = (&AtomId, &Atom, Bounds)> {
         self.atoms.iter().filter_map(move |(atom_id, atom)| {
             let bounds = atom.bounds() + self.position.into();
 
-            if bounds.contains(canvas_position) {
+            if bounds.contains_within(canvas_position, tolerance) {
                 Some((atom_id, atom, bounds))
             } else {
                 None
@@ -358,24 +647,26 @@ impl Molecule {
         })
     }
 
+    /// bonds whose drawn shape, padded and grown by the tolerance, contains the position
     pub fn bonds_at(
         &self,
         canvas_position: Point,
+        tolerance: f32,
     ) -> Result<impl IntoIterator<Item = (&BondId, &Bond, Bounds)>> {
+        let position = canvas_position - Vector::from(self.position);
+
         self.bonds
             .iter()
             .filter_map(move |(bond_id, bond)| {
-                let bounds = match bond.bounds(&self.atoms) {
-                    Ok(val) => val,
+                match bond.contains_within(&self.atoms, position, tolerance) {
+                    Ok(true) => (),
+                    Ok(false) => return None,
                     Err(error) => return Some(Err(error)),
-                };
-
-                let bounds = bounds + self.position.into();
+                }
 
-                if bounds.contains(canvas_position) {
-                    Some(Ok((bond_id, bond, bounds)))
-                } else {
-                    None
+                match bond.bounds(&self.atoms) {
+                    Ok(bounds) => Some(Ok((bond_id, bond, bounds + self.position.into()))),
+                    Err(error) => Some(Err(error)),
                 }
             })
             .collect::<Result<Vec<_>>>()
@@ -389,43 +680,51 @@ impl Molecule {
         self.position += translation;
     }
 
-    pub fn move_atom(&mut self, atom_id: &AtomId, translation: Vector) -> Result<()> {
-        let atom = self.get_atom_mut(atom_id).context("while moving atom")?;
+    /// scales the distances between atoms by `factor` around a canvas point, leaving labels their
+    /// size
+    pub fn scale(&mut self, center: Point, factor: f32) -> Result<()> {
+        let position: Point = self.position.into();
+        self.position += (position - center) * (factor - 1.0);
 
-        atom.translate(translation);
-
-        self.update_atom_label_direction(atom_id).context("while moving atom")?;
-        for atom_id in self.get_directly_connected(*atom_id).collect::<Vec<_>>() {
-            self.update_atom_label_direction(&atom_id).context("while moving atom")?;
+        for atom in self.atoms.values_mut() {
+            let offset: Vector = atom.position().into();
+            atom.translate(offset * (factor - 1.0));
         }
 
-        self.compute_bounds().context("while moving atom")?;
-
-        Ok(())
+        let atom_ids = self.atoms.keys().copied().collect::<Vec<_>>();
+        self.update_label_directions(atom_ids).context("while scaling molecule")
     }
 
-    pub fn move_bond(&mut self, bond_id: &BondId, translation: Vector) -> Result<()> {
-        let bond = self.get_bond_mut(bond_id).context("while moving bond")?;
+    /// scales the distances of some atoms from a canvas point by `factor`, stretching the bonds to
+    /// the rest of the molecule
+    pub fn scale_atoms(&mut self, atom_ids: &[AtomId], center: Point, factor: f32) -> Result<()> {
+        for atom_id in atom_ids {
+            let offset = self.atom_position(atom_id).context("while scaling atoms")? - center;
+            self.get_atom_mut(atom_id).context("while scaling atoms")?.translate(offset * (factor - 1.0));
+        }
 
-        let atom_ids = bond.atom_ids();
+        self.update_label_directions(atom_ids.iter().copied()).context("while scaling atoms")
+    }
 
+    /// moves some of the atoms, each given once, leaving the rest in place
+    pub fn move_atoms(&mut self, atom_ids: &[AtomId], translation: Vector) -> Result<()> {
         let mut affected_atoms = FxHashSet::default();
 
         for atom_id in atom_ids {
-            let atom = self.get_atom_mut(&atom_id).context("while moving bond")?;
+            let atom = self.get_atom_mut(atom_id).context("while moving atoms")?;
             atom.translate(translation);
 
-            affected_atoms.insert(atom_id);
-            for atom_id in self.get_directly_connected(atom_id) {
+            affected_atoms.insert(*atom_id);
+            for atom_id in self.get_directly_connected(*atom_id) {
                 affected_atoms.insert(atom_id);
             }
         }
 
         for atom_id in affected_atoms {
-            self.update_atom_label_direction(&atom_id).context("while moving bond")?;
+            self.update_atom_label_direction(&atom_id).context("while moving atoms")?;
         }
 
-        self.compute_bounds().context("while moving bond")?;
+        self.compute_bounds().context("while moving atoms")?;
         Ok(())
     }
 
@@ -448,41 +747,313 @@ impl Molecule {
             })
             .collect();
 
-        let mut blocked_directions = FxHashSet::default();
-
-        for unit_vector in unit_direction_vectors {
-            if unit_vector.x > 0.1 {
-                blocked_directions.insert(Direction::Right);
-            } else if unit_vector.x < -0.1 {
-                blocked_directions.insert(Direction::Left);
-            }
-            if unit_vector.y > 0.1 {
-                blocked_directions.insert(Direction::Down);
-            } else if unit_vector.y < -0.1 {
-                blocked_directions.insert(Direction::Up);
-            }
-        }
+        let angle_between = |a: Vector, b: Vector| (a.x * b.x + a.y * b.y).clamp(-1.0, 1.0).acos();
+        let angle_to_nearest_bond = |direction: Vector| {
+            unit_direction_vectors
+                .iter()
+                .map(|unit_vector| angle_between(direction, *unit_vector))
+                .fold(PI, f32::min)
+        };
 
-        let direction = if !blocked_directions.contains(&Direction::Right) {
-            Direction::Right
-        } else if !blocked_directions.contains(&Direction::Left) {
-            Direction::Left
-        } else if !blocked_directions.contains(&Direction::Up) {
-            Direction::Up
-        } else if !blocked_directions.contains(&Direction::Down) {
-            Direction::Down
+        // prefer reading left to right, falling back on vertical labels only when they are clearly
+        // further from the bonds
+        let (direction, clearance) = Direction::ALL
+            .into_iter()
+            .map(|direction| {
+                let clearance = angle_to_nearest_bond(direction.unit_vector());
+                let score = clearance + if direction.is_horizontal() { Self::HORIZONTAL_LABEL_BIAS } else { 0.0 };
+                (direction, clearance, score)
+            })
+            .fold(None, |best: Option<(Direction, f32, f32)>, candidate| match best {
+                Some(best) if best.2 >= candidate.2 => Some(best),
+                _ => Some(candidate),
+            })
+            .map(|(direction, clearance, _)| (direction, clearance))
+            .unwrap_or_default();
+
+        // when a bond is close to the label, push the trailing tokens away from it
+        let offset = if clearance < Self::LABEL_CLEARANCE {
+            let along = direction.unit_vector();
+            let perpendicular = Vector::new(-along.y, along.x);
+            let nearest = unit_direction_vectors
+                .iter()
+                .copied()
+                .min_by(|a, b| angle_between(along, *a).total_cmp(&angle_between(along, *b)))
+                .unwrap_or(along);
+            let side = nearest.x * perpendicular.x + nearest.y * perpendicular.y;
+            let magnitude = Self::LABEL_NUDGE * (1.0 - clearance / Self::LABEL_CLEARANCE);
+
+            perpendicular * (-side.signum() * magnitude)
         } else {
-            Direction::default()
+            Vector::ZERO
         };
 
         let atom = self
             .get_atom_mut(atom_id)
             .context("while updating atom label direction")?;
+        atom.update_label_offset(offset);
         atom.update_label_direction(direction);
 
         Ok(())
     }
 
+    /// atoms reachable from `atom_id` without crossing the bond, or none if the bond is in a ring
+    fn bond_side(&self, bond_id: &BondId, atom_id: AtomId) -> Option<Vec<AtomId>> {
+        let mut atoms = vec![atom_id];
+        let mut atom_queue = VecDeque::from([atom_id]);
+
+        while let Some(curr_atom) = atom_queue.pop_front() {
+            for (attached_id, bond) in self.attached_bonds(curr_atom) {
+                if attached_id == bond_id {
+                    if curr_atom != atom_id {
+                        // came back around to the other side of the bond
+                        return None;
+                    }
+                    continue;
+                }
+
+                for atom in bond.atom_ids() {
+                    if !atoms.contains(&atom) {
+                        atom_queue.push_back(atom);
+                        atoms.push(atom);
+                    }
+                }
+            }
+        }
+
+        Some(atoms)
+    }
+
+    pub fn is_ring_bond(&self, bond_id: &BondId) -> bool {
+        self.get_bond(bond_id)
+            .is_ok_and(|bond| self.bond_side(bond_id, bond.start()).is_none())
+    }
+
+    /// the smaller fragment on either side of a bond and the atom it hangs from
+    fn smaller_side(&self, bond_id: &BondId) -> Result<(AtomId, Vec<AtomId>)> {
+        let bond = self.get_bond(bond_id).context("while getting smaller side of bond")?;
+
+        let sides = bond
+            .atom_ids()
+            .map(|atom_id| self.bond_side(bond_id, atom_id))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(Error::RingBond(*bond_id))
+            .context("while getting smaller side of bond")?;
+
+        let (fixed, moving) = match sides[0].len() < sides[1].len() {
+            true => (bond.end(), sides[0].clone()),
+            false => (bond.start(), sides[1].clone()),
+        };
+
+        Ok((fixed, moving))
+    }
+
+    /// atoms of the smaller fragment attached to a bond, none when the bond is in a ring
+    pub fn substituent(&self, bond_id: &BondId) -> Option<Vec<AtomId>> {
+        self.smaller_side(bond_id).ok().map(|(_fixed, moving)| moving)
+    }
+
+    pub fn bond_length(&self, bond_id: &BondId) -> Result<f32> {
+        let bond = self.get_bond(bond_id).context("while getting bond length")?;
+        let direction: Vector = (self.get_atom(&bond.end())?.position() - self.get_atom(&bond.start())?.position()).into();
+
+        Ok(direction.x.hypot(direction.y))
+    }
+
+    /// stretches a bond by moving the smaller fragment attached to it along the bond
+    pub fn set_bond_length(&mut self, bond_id: &BondId, length: f32) -> Result<()> {
+        let (fixed, moving) = self.smaller_side(bond_id).context("while setting bond length")?;
+        let moving_atom = self.get_bond(bond_id)?.atom_ids().find(|atom_id| *atom_id != fixed).unwrap_or(fixed);
+
+        let direction: Vector = (self.get_atom(&moving_atom)?.position() - self.get_atom(&fixed)?.position()).into();
+        let current = direction.x.hypot(direction.y);
+        if current < f32::EPSILON {
+            return Ok(());
+        }
+
+        let translation = direction * ((length - current) / current);
+        for atom_id in &moving {
+            self.get_atom_mut(atom_id).context("while setting bond length")?.translate(translation);
+        }
+
+        self.update_label_directions([fixed, moving_atom]).context("while setting bond length")
+    }
+
+    /// writes an atom's label in `direction`, replacing any automatic placement
+    pub fn set_label_direction(&mut self, atom_id: &AtomId, direction: Direction) -> Result<()> {
+        let atom = self.get_atom_mut(atom_id).context("while setting label direction")?;
+        atom.update_label_direction(direction);
+        atom.update_label_offset(Vector::ZERO);
+
+        self.compute_bounds().context("while setting label direction")
+    }
+
+    /// mirrors the smaller fragment attached to a bond across the bond's axis
+    pub fn flip_fragment(&mut self, bond_id: &BondId) -> Result<()> {
+        let (_fixed, moving) = self.smaller_side(bond_id).context("while flipping fragment")?;
+        let bond = self.get_bond(bond_id).context("while flipping fragment")?;
+        let start = self.get_atom(&bond.start())?.position();
+        let end = self.get_atom(&bond.end())?.position();
+        let bond_atoms = bond.atom_ids().collect::<Vec<_>>();
+
+        let axis: Vector = (end - start).into();
+        let length = axis.x.hypot(axis.y);
+        if length < f32::EPSILON {
+            return Ok(());
+        }
+        let axis = axis * (1.0 / length);
+
+        for atom_id in &moving {
+            let atom = self.get_atom_mut(atom_id).context("while flipping fragment")?;
+            let offset: Vector = (atom.position() - start).into();
+            let along = axis * (offset.x * axis.x + offset.y * axis.y);
+
+            // reflect the component perpendicular to the axis
+            atom.translate((along - offset) * 2.0);
+        }
+
+        self.update_label_directions(moving.into_iter().chain(bond_atoms))
+            .context("while flipping fragment")
+    }
+
+    /// the two bonds of an atom with exactly two bonds
+    pub fn angle_bonds(&self, atom_id: &AtomId) -> Result<[(BondId, AtomId); 2]> {
+        let bonds = self
+            .attached_bonds(*atom_id)
+            .map(|(bond_id, bond)| {
+                let other = bond.atom_ids().find(|other| other != atom_id).unwrap_or(*atom_id);
+                (*bond_id, other)
+            })
+            .collect::<Vec<_>>();
+
+        <[(BondId, AtomId); 2]>::try_from(bonds)
+            .map_err(|_| Error::NotTwoBonds(*atom_id))
+            .context("while getting angle bonds")
+    }
+
+    /// angle in degrees between the two bonds of an atom
+    pub fn bond_angle(&self, atom_id: &AtomId) -> Result<f32> {
+        let atom = self.get_atom(atom_id).context("while getting bond angle")?;
+        let [(_, first), (_, second)] = self.angle_bonds(atom_id).context("while getting bond angle")?;
+
+        let first: Vector = (self.get_atom(&first)?.position() - atom.position()).into();
+        let second: Vector = (self.get_atom(&second)?.position() - atom.position()).into();
+
+        let angle = (second.y.atan2(second.x) - first.y.atan2(first.x)).rem_euclid(2.0 * PI);
+        Ok(angle.min(2.0 * PI - angle).to_degrees())
+    }
+
+    /// smallest angle in degrees between an atom's bonds and a line from it to a canvas point, none
+    /// when the atom has no bonds
+    pub fn bond_angle_towards(&self, atom_id: &AtomId, target: Point) -> Result<Option<f32>> {
+        let origin = self.atom_position(atom_id).context("while getting bond angle")?;
+        let direction = target - origin;
+
+        let mut smallest: Option<f32> = None;
+        for (_, bond) in self.attached_bonds(*atom_id) {
+            let other = bond.atom_ids().find(|other| other != atom_id).unwrap_or(*atom_id);
+            let neighbour = self.atom_position(&other).context("while getting bond angle")? - origin;
+
+            let angle = (direction.y.atan2(direction.x) - neighbour.y.atan2(neighbour.x)).rem_euclid(2.0 * PI);
+            let angle = angle.min(2.0 * PI - angle).to_degrees();
+            smallest = Some(smallest.map_or(angle, |smallest| smallest.min(angle)));
+        }
+
+        Ok(smallest)
+    }
+
+    /// opens or closes the angle at an atom by rotating the smaller fragment around it
+    pub fn set_bond_angle(&mut self, atom_id: &AtomId, degrees: f32) -> Result<()> {
+        let center = self.get_atom(atom_id).context("while setting bond angle")?.position();
+        let [first, second] = self.angle_bonds(atom_id).context("while setting bond angle")?;
+
+        let sides = [first, second]
+            .map(|(bond_id, neighbour)| self.bond_side(&bond_id, neighbour).map(|side| (neighbour, side)));
+        let [Some(first), Some(second)] = sides else {
+            return Err(Error::RingBond(first.0)).context("while setting bond angle");
+        };
+        let ((fixed, _), (moving, fragment)) = match first.1.len() < second.1.len() {
+            true => (second, first),
+            false => (first, second),
+        };
+
+        let angle_to = |atom_id: &AtomId| -> Result<f32> {
+            let direction: Vector = (self.get_atom(atom_id)?.position() - center).into();
+            Ok(direction.y.atan2(direction.x))
+        };
+        let fixed_angle = angle_to(&fixed)?;
+        let moving_angle = angle_to(&moving)?;
+
+        // keep the moving bond on the same side of the fixed bond
+        let current = (moving_angle - fixed_angle + PI).rem_euclid(2.0 * PI) - PI;
+        let target = degrees.to_radians().copysign(current);
+        let (sin, cos) = (target - current).sin_cos();
+
+        for atom_id in &fragment {
+            let atom = self.get_atom_mut(atom_id).context("while setting bond angle")?;
+            let offset: Vector = (atom.position() - center).into();
+            let rotated = Vector::new(offset.x * cos - offset.y * sin, offset.x * sin + offset.y * cos);
+
+            atom.translate(rotated - offset);
+        }
+
+        self.update_label_directions([*atom_id, moving]).context("while setting bond angle")
+    }
+
+    /// updates the label directions of atoms and their neighbours after they have moved
+    fn update_label_directions(&mut self, atom_ids: impl IntoIterator<Item = AtomId>) -> Result<()> {
+        let mut affected_atoms = FxHashSet::default();
+        for atom_id in atom_ids {
+            affected_atoms.insert(atom_id);
+            affected_atoms.extend(self.get_directly_connected(atom_id));
+        }
+
+        for atom_id in affected_atoms {
+            self.update_atom_label_direction(&atom_id)?;
+        }
+
+        self.compute_bounds()
+    }
+
+    /// unit vector pointing away from an atom's existing bonds, used to grow a new bond
+    pub fn sprout_direction(&self, atom_id: &AtomId) -> Result<Vector> {
+        let atom = self.get_atom(atom_id).context("while getting sprout direction")?;
+
+        let mut angles = self
+            .get_directly_connected(*atom_id)
+            .map(|connected_id| {
+                let connected = self.get_atom(&connected_id)?;
+                let direction: Vector = (connected.position() - atom.position()).into();
+                Ok(direction.y.atan2(direction.x))
+            })
+            .collect::<Result<Vec<f32>>>()
+            .context("while getting sprout direction")?;
+        angles.sort_by(f32::total_cmp);
+
+        let angle = match angles.as_slice() {
+            // up and to the right, the first step of a zigzag chain
+            [] => -PI / 6.0,
+            // continue the zigzag, preferring to keep growing to the right
+            [angle] => [angle + 2.0 * PI / 3.0, angle - 2.0 * PI / 3.0]
+                .into_iter()
+                .max_by(|a, b| a.cos().total_cmp(&b.cos()))
+                .unwrap_or_default(),
+            // bisect the widest gap between neighbouring bonds
+            _ => angles
+                .iter()
+                .zip(angles.iter().cycle().skip(1))
+                .map(|(a, b)| {
+                    let gap = (b - a).rem_euclid(2.0 * PI);
+                    (a + gap / 2.0, gap)
+                })
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(angle, _gap)| angle)
+                .unwrap_or_default(),
+        };
+
+        Ok(Vector::new(angle.cos(), angle.sin()))
+    }
+
     pub fn change_bond_type(&mut self, bond_id: &BondId, bond_type: BondType) {
         let Some(bond) = self.bonds.get_mut(bond_id) else {
             return;
@@ -535,12 +1106,260 @@ impl Molecule {
         self.position
     }
 
+    pub fn layer(&self) -> LayerId {
+        self.layer
+    }
+
+    pub fn set_layer(&mut self, layer: LayerId) {
+        self.layer = layer;
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    pub fn set_locked(&mut self, locked: bool) {
+        self.locked = locked;
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(TextOutline::content)
+    }
+
+    /// names the molecule, a blank name removes it
+    pub fn set_name(&mut self, name: String) {
+        self.name = match name.trim().is_empty() {
+            true => None,
+            false => Some(TextOutline::new(name, Self::NAME_FONT_SIZE, self.label_font)),
+        };
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    pub fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+
+    pub fn letters_with_previous(&self) -> bool {
+        self.letters_with_previous
+    }
+
+    pub fn set_letters_with_previous(&mut self, letters_with_previous: bool) {
+        self.letters_with_previous = letters_with_previous;
+    }
+
+    pub fn name_outline(&self) -> Option<&TextOutline> {
+        self.name.as_ref()
+    }
+
+    pub fn caption(&self) -> Option<&str> {
+        self.caption.as_ref().map(TextOutline::content)
+    }
+
+    pub fn caption_outline(&self) -> Option<&TextOutline> {
+        self.caption.as_ref()
+    }
+
+    /// sets the legend under the molecule, a blank caption removes it
+    pub fn set_caption(&mut self, caption: String) {
+        self.caption = match caption.trim().is_empty() {
+            true => None,
+            false => Some(TextOutline::wrapped(caption, Self::CAPTION_FONT_SIZE, self.label_font, Self::CAPTION_WIDTH)),
+        };
+    }
+
+    /// bonds' annotations and where they are drawn, beside the middle of each bond
+    pub fn bond_annotations(&self) -> impl Iterator<Item = (&TextOutline, Point)> {
+        self.bonds.values().filter_map(|bond| {
+            let annotation = bond.annotation()?;
+            let (start, end) = bond.middle_segment(&self.atoms).ok()?;
+            let offset: Vector = self.position.into();
+
+            Some((annotation, annotation.beside_line(start + offset, end + offset, Self::ANNOTATION_GAP)))
+        })
+    }
+
+    /// the wavy lines drawn across the middle of bonds marked as disconnections
+    fn disconnections(&self) -> Vec<Path> {
+        let offset: Vector = self.position.into();
+
+        self.bonds
+            .values()
+            .filter(|bond| bond.is_disconnection())
+            .filter_map(|bond| {
+                let (start, end) = bond.middle_segment(&self.atoms).ok()?;
+                let length = start.distance(end).max(f32::EPSILON);
+                let along = (end - start) * (1.0 / length);
+                let across = Vector::new(-along.y, along.x);
+                let middle = start + (end - start) * 0.5 + offset;
+
+                Some(Path::new(|builder| {
+                    for index in 0..=Self::DISCONNECTION_SEGMENTS {
+                        let fraction = index as f32 / Self::DISCONNECTION_SEGMENTS as f32;
+                        let wave = (fraction * Self::DISCONNECTION_WAVES * 2.0 * PI).sin() * Self::DISCONNECTION_AMPLITUDE;
+                        let point = middle + across * ((fraction - 0.5) * Self::DISCONNECTION_LENGTH) + along * wave;
+                        match index {
+                            0 => builder.move_to(point),
+                            _ => builder.line_to(point),
+                        }
+                    }
+                }))
+            })
+            .collect()
+    }
+
+    /// atoms' map numbers and the corner they're written from, the lower right of each atom's
+    /// label or a little below and right of an unlabelled atom
+    pub fn atom_maps(&self) -> impl Iterator<Item = (AtomId, u32, Point)> + '_ {
+        let offset: Vector = self.position.into();
+
+        self.atoms.iter().filter_map(move |(atom_id, atom)| {
+            let map = atom.map()?;
+            let corner = match atom.label_bounds() {
+                Some(bounds) => Point::new(bounds.x + bounds.width, bounds.y + bounds.height),
+                None => Point::ORIGIN + Vector::from(atom.position()) + Vector::new(Self::ANNOTATION_GAP, Self::ANNOTATION_GAP),
+            };
+
+            Some((*atom_id, map, corner + offset))
+        })
+    }
+
+    /// oxidation states and electron counts of the molecule's metal atoms and where they're
+    /// written, under each atom's label
+    pub fn metal_labels(&self) -> Vec<(TextOutline, Point)> {
+        let Ok(graph) = MolecularGraph::new(self) else {
+            return vec![];
+        };
+
+        graph
+            .atoms()
+            .filter_map(|(index, graph_atom)| {
+                let metal_center = MetalCenter::from_graph(&graph, index)?;
+                let label_bounds = self.atoms.get(&graph_atom.atom_id)?.label_bounds()?;
+                let label = TextOutline::new(metal_center.to_string(), Self::ANNOTATION_FONT_SIZE, self.label_font);
+                let position = Point::new(label_bounds.center_x(), label_bounds.y + label_bounds.height + Self::ANNOTATION_GAP);
+
+                Some((label, position + self.position.into()))
+            })
+            .collect()
+    }
+
+    /// sets the text beside a bond, a blank annotation removes it
+    pub fn set_bond_annotation(&mut self, bond_id: &BondId, annotation: String) -> Result<()> {
+        let label_font = self.label_font;
+        let bond = self.get_bond_mut(bond_id).context("while setting bond annotation")?;
+
+        bond.set_annotation(match annotation.trim().is_empty() {
+            true => None,
+            false => Some(TextOutline::new(annotation, Self::ANNOTATION_FONT_SIZE, label_font)),
+        });
+
+        Ok(())
+    }
+
+    pub fn set_bond_disconnection(&mut self, bond_id: &BondId, disconnection: bool) -> Result<()> {
+        self.get_bond_mut(bond_id)
+            .context("while setting bond disconnection")?
+            .set_disconnection(disconnection);
+
+        Ok(())
+    }
+
+    pub fn charge_bracket(&self) -> Option<ChargeBracket> {
+        self.charge_bracket
+    }
+
+    pub fn set_charge_bracket(&mut self, charge_bracket: Option<ChargeBracket>) {
+        self.charge_bracket = charge_bracket;
+    }
+
+    /// area the structure covers together with its charge bracket
+    pub fn outer_rectangle(&self) -> Rectangle {
+        let structure = self.bounds().bounding_rectangle();
+
+        match &self.charge_bracket {
+            Some(charge_bracket) => charge_bracket.bounds(structure, self.label_font),
+            None => structure,
+        }
+    }
+
+    /// middle of the space under the structure, where its name, number and caption are stacked
+    pub fn caption_position(&self) -> Point {
+        let rectangle = self.outer_rectangle();
+
+        Point::new(rectangle.center_x(), rectangle.y + rectangle.height + Self::CAPTION_GAP)
+    }
+
     pub fn atom_position(&self, atom_id: &AtomId) -> Result<Point> {
         self.get_atom(atom_id)
             .map(|atom| atom.position() + self.position())
             .context("while getting atom's position")
     }
 
+    /// canvas position where a line from an atom towards a point leaves the atom's label
+    pub fn bond_start_towards(&self, atom_id: &AtomId, target: Point) -> Result<Point> {
+        let atom = self.get_atom(atom_id).context("while getting bond start")?;
+        let target = AtomPosition::from(self.position, target);
+
+        Ok(atom.bond_start(target) + self.position)
+    }
+
+    /// canvas positions a bond runs through, from its start atom over any bends to its end atom
+    pub fn bond_points(&self, bond_id: &BondId) -> Result<Vec<Point>> {
+        let bond = self.get_bond(bond_id).context("while getting bond's points")?;
+        let offset: Vector = self.position.into();
+
+        Ok(bond.points(&self.atoms).context("while getting bond's points")?.into_iter().map(|point| point + offset).collect())
+    }
+
+    /// canvas positions of a bond as it's drawn, with its ends trimmed to the atoms' labels
+    pub fn bond_path(&self, bond_id: &BondId) -> Result<Vec<Point>> {
+        let bond = self.get_bond(bond_id).context("while getting bond's path")?;
+        let offset: Vector = self.position.into();
+
+        Ok(bond.path(&self.atoms).context("while getting bond's path")?.into_iter().map(|point| point + offset).collect())
+    }
+
+    /// where a bond's bend is dragged from, its middle waypoint or the middle of a straight bond
+    pub fn bend_handle(&self, bond_id: &BondId) -> Result<Point> {
+        let points = self.bond_points(bond_id).context("while getting bend handle")?;
+
+        Ok(match points.len() {
+            2 => points[0] + (points[1] - points[0]) * 0.5,
+            length => points[length / 2],
+        })
+    }
+
+    /// bends a bond through a canvas position, or straightens it, a curved bond's curve being
+    /// changed to run through the position instead
+    pub fn bend_bond(&mut self, bond_id: &BondId, position: Option<Point>) -> Result<()> {
+        let position = position.map(|position| Point::from(AtomPosition::from(self.position, position)));
+        let bond = self.get_bond(bond_id).context("while bending bond")?;
+        let waypoints = match position {
+            Some(position) => vec![bond.waypoint_at(&self.atoms, position).context("while bending bond")?],
+            None => vec![],
+        };
+        let curved = bond.curvature() != 0.0;
+
+        let bond = self.get_bond_mut(bond_id).context("while bending bond")?;
+        match (curved, waypoints.first()) {
+            (true, Some(waypoint)) => bond.set_curvature(waypoint.y),
+            _ => bond.set_waypoints(waypoints),
+        }
+
+        Ok(())
+    }
+
+    /// curves a bond to the side by the fraction of its length, a curvature of zero straightening
+    /// it
+    pub fn curve_bond(&mut self, bond_id: &BondId, curvature: f32) -> Result<()> {
+        self.get_bond_mut(bond_id).context("while curving bond")?.set_curvature(curvature);
+
+        Ok(())
+    }
+
     pub fn bond_position(&self, bond_id: &BondId) -> Result<Point> {
         let bond = self
             .get_bond(bond_id)
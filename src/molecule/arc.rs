@@ -0,0 +1,84 @@
+use std::f32::consts::TAU;
+
+use iced::widget::canvas::path::Arc;
+use iced::widget::canvas::Path;
+use iced::{Point, Radians, Vector};
+
+/// the circular arc a curved bond is drawn along
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BondArc {
+    center: Point,
+    radius: f32,
+    start_angle: f32,
+    /// angle from the start to the end, negative when the arc runs anticlockwise
+    sweep: f32,
+}
+
+impl BondArc {
+    /// the arc from the start to the end through a point between them, none when the points are
+    /// in a line
+    pub fn through(start: Point, middle: Point, end: Point) -> Option<BondArc> {
+        let determinant = 2.0
+            * (start.x * (middle.y - end.y) + middle.x * (end.y - start.y) + end.x * (start.y - middle.y));
+        if determinant.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let squared = |point: Point| point.x.powi(2) + point.y.powi(2);
+        let center = Point::new(
+            (squared(start) * (middle.y - end.y) + squared(middle) * (end.y - start.y) + squared(end) * (start.y - middle.y))
+                / determinant,
+            (squared(start) * (end.x - middle.x) + squared(middle) * (start.x - end.x) + squared(end) * (middle.x - start.x))
+                / determinant,
+        );
+
+        let angle = |point: Point| (point.y - center.y).atan2(point.x - center.x);
+        let start_angle = angle(start);
+        let clockwise = (angle(end) - start_angle).rem_euclid(TAU);
+        // the arc runs whichever way round passes through the middle point
+        let sweep = match (angle(middle) - start_angle).rem_euclid(TAU) < clockwise {
+            true => clockwise,
+            false => clockwise - TAU,
+        };
+
+        Some(BondArc {
+            center,
+            radius: center.distance(start),
+            start_angle,
+            sweep,
+        })
+    }
+
+    pub fn length(&self) -> f32 {
+        self.radius * self.sweep.abs()
+    }
+
+    /// unit vector away from the center the fraction of the way along the arc
+    pub fn outward(&self, fraction: f32) -> Vector {
+        let angle = self.start_angle + self.sweep * fraction;
+
+        Vector::new(angle.cos(), angle.sin())
+    }
+
+    /// the point the fraction of the way along the arc, moved the offset away from the center
+    pub fn point(&self, fraction: f32, offset: f32) -> Point {
+        self.center + self.outward(fraction) * (self.radius + offset)
+    }
+
+    /// points evenly spaced along the arc, from its start to its end
+    pub fn points(&self, segments: usize) -> Vec<Point> {
+        (0..=segments).map(|n| self.point(n as f32 / segments as f32, 0.0)).collect()
+    }
+
+    /// the arc moved the offset away from its center
+    pub fn path(&self, offset: f32) -> Path {
+        Path::new(|builder| {
+            builder.arc(Arc {
+                center: self.center,
+                radius: self.radius + offset,
+                start_angle: Radians(self.start_angle),
+                end_angle: Radians(self.start_angle + self.sweep),
+            })
+        })
+    }
+}
@@ -1,3 +1,6 @@
+use std::iter::{self, Peekable};
+use std::str::Chars;
+
 use anyhow::Result;
 use iced::alignment::Horizontal;
 use iced::alignment::Vertical;
@@ -5,10 +8,11 @@ use iced::widget::canvas::path::lyon_path::geom::euclid::Point2D;
 use iced::widget::canvas::path::lyon_path::geom::Transform;
 use iced::widget::canvas::path::lyon_path::traits::PathIterator;
 use iced::widget::canvas::path::lyon_path::PathEvent;
-use iced::widget::canvas::Frame;
+use crate::surface::Surface;
 use iced::widget::canvas::{Path, Text};
 use iced::widget::text::LineHeight;
 use iced::widget::text::Shaping;
+use iced::font;
 use iced::Vector;
 use iced::{Color, Font, Pixels, Point, Rectangle, Size};
 
@@ -21,17 +25,20 @@ use super::atom_position::AtomPosition;
 pub struct Atom {
     label: Label,
     position: AtomPosition,
+    /// number shared with the atom it becomes on the other side of a reaction arrow
+    map: Option<u32>,
 }
 
 impl Atom {
-    pub fn new(label: String, position: AtomPosition, direction: Direction) -> Atom {
+    pub fn new(label: String, position: AtomPosition, direction: Direction, font: Font) -> Atom {
         Self {
-            label: Label::new(label, direction),
+            label: Label::new(label, direction, font),
             position,
+            map: None,
         }
     }
 
-    pub fn draw(&self, frame: &mut Frame, transform: &Transform<f32>, color: &Color) -> Result<()> {
+    pub fn draw(&self, frame: &mut impl Surface, transform: &Transform<f32>, color: &Color) -> Result<()> {
         let transform = <AtomPosition as Into<Transform<f32>>>::into(self.position).then(transform);
 
         if self.label.is_empty() {
@@ -51,13 +58,36 @@ impl Atom {
     }
 
     pub fn rename(&mut self, text: String) {
-        self.label = Label::new(text, self.label.direction);
+        self.label = Label::new(text, self.label.direction, self.label.font);
+    }
+
+    pub fn set_font(&mut self, font: Font) {
+        self.label = Label::new(self.label(), self.label.direction, font);
     }
 
     pub fn update_label_direction(&mut self, direction: Direction) {
         self.label.update_direction(direction);
     }
 
+    /// shifts the tokens after the first, e.g. to move them clear of a nearby bond
+    pub fn update_label_offset(&mut self, offset: Vector) {
+        self.label.update_offset(offset);
+    }
+
+    /// bounds of the label relative to the molecule, or none if it is empty
+    pub fn label_bounds(&self) -> Option<Rectangle> {
+        (!self.label.is_empty()).then(|| self.label.bounds() + Vector::from(self.position))
+    }
+
+    /// bounds the label would have if it were written in `direction`
+    pub fn label_bounds_towards(&self, direction: Direction) -> Option<Rectangle> {
+        let mut label = self.label.clone();
+        label.update_direction(direction);
+        label.update_offset(Vector::ZERO);
+
+        (!label.is_empty()).then(|| label.bounds() + Vector::from(self.position))
+    }
+
     pub fn label(&self) -> String {
         self.label.input_string.clone()
     }
@@ -66,6 +96,14 @@ impl Atom {
         self.position
     }
 
+    pub fn map(&self) -> Option<u32> {
+        self.map
+    }
+
+    pub fn set_map(&mut self, map: Option<u32>) {
+        self.map = map;
+    }
+
     pub fn translate(&mut self, translation: Vector) {
         self.position += translation;
     }
@@ -106,47 +144,121 @@ impl Atom {
     }
 }
 
+/// vertical placement of a run of text within a token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Script {
+    Normal,
+    Subscript,
+    Superscript,
+}
+
+impl Script {
+    const SCALE: f32 = 0.7;
+
+    /// size of the run relative to the text around it
+    pub(super) fn scale(&self) -> f32 {
+        match self {
+            Self::Normal => 1.0,
+            Self::Subscript | Self::Superscript => Self::SCALE,
+        }
+    }
+
+    /// offset of the run's center from the center line, relative to the size of the text around it
+    pub(super) fn shift(&self) -> f32 {
+        match self {
+            Self::Normal => 0.0,
+            Self::Subscript => 0.3,
+            Self::Superscript => -0.35,
+        }
+    }
+
+    fn size(&self) -> f32 {
+        Token::FONT_SIZE * self.scale()
+    }
+
+    /// offset of the run's center from the token's center line
+    fn offset(&self) -> f32 {
+        Token::FONT_SIZE * self.shift()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Segment {
+    pub(super) text: String,
+    pub(super) script: Script,
+    pub(super) italic: bool,
+}
+
 #[derive(Debug, Clone)]
-struct Token {
+pub struct Token {
     paths: Vec<Path>,
     bounds: Rectangle,
 }
 
 impl Token {
-    pub fn new(content: String) -> Token {
-        let paths = Self::calculate_paths(&content);
+    pub const FONT_SIZE: f32 = 10.0;
+    const SEGMENT_SEPARATION: f32 = 0.5;
+
+    fn new(segments: Vec<Segment>, font: Font) -> Token {
+        let paths = Self::calculate_paths(&segments, font);
         let bounds = Self::calculate_bounds(&paths);
 
         Self { paths, bounds }
     }
 
-    fn calculate_paths(content: &String) -> Vec<Path> {
-        let text = Text {
-            content: content.to_string(),
-            color: Color::default(),
-            position: Point::default(),
-            font: Font::DEFAULT,
-            size: Pixels(10.0),
-            line_height: LineHeight::Relative(1.2),
-            horizontal_alignment: Horizontal::Center,
-            vertical_alignment: Vertical::Center,
-            shaping: Shaping::Basic,
-        };
-
+    /// lays out segments left to right, centering the first normal segment on the origin
+    fn calculate_paths(segments: &[Segment], font: Font) -> Vec<Path> {
         let mut paths = Vec::<Path>::new();
+        let mut cursor = 0.0;
+        let mut center = None;
 
-        let draw_path = |path, _| {
-            paths.push(path);
-        };
+        for Segment { text, script, italic } in segments {
+            let font = match italic {
+                true => Font { style: font::Style::Italic, ..font },
+                false => font,
+            };
+
+            let text = Text {
+                content: text.to_string(),
+                color: Color::default(),
+                position: Point::new(cursor, script.offset()),
+                font,
+                size: Pixels(script.size()),
+                line_height: LineHeight::Relative(1.2),
+                horizontal_alignment: Horizontal::Left,
+                vertical_alignment: Vertical::Center,
+                shaping: Shaping::Basic,
+            };
+
+            let mut segment_paths = Vec::<Path>::new();
+            text.draw_with(|path, _| segment_paths.push(path));
+
+            let bounds = Self::calculate_bounds(&segment_paths);
+            if segment_paths.is_empty() || bounds.width == 0.0 {
+                // whitespace has no outline to measure
+                cursor += script.size() * 0.3;
+                continue;
+            }
 
-        text.draw_with(draw_path);
+            if center.is_none() && *script == Script::Normal {
+                center = Some(bounds.center_x());
+            }
+
+            cursor = bounds.x + bounds.width + Self::SEGMENT_SEPARATION;
+            paths.extend(segment_paths);
+        }
+
+        let Some(center) = center.or_else(|| (!paths.is_empty()).then_some(cursor / 2.0)) else {
+            return paths;
+        };
 
-        paths
+        let transform = Transform::translation(-center, 0.0);
+        paths.iter().map(|path| path.transform(&transform)).collect()
     }
 
     fn calculate_bounds(paths: &[Path]) -> Rectangle {
         let mut points = paths.iter().flat_map(|path| {
-            path.raw().iter().flattened(0.1).flat_map(|evt| match evt {
+            path.raw().iter().flattened(MolCanvas::TEXT_TOLERANCE).flat_map(|evt| match evt {
                 PathEvent::Begin { at } => {
                     vec![at]
                 }
@@ -201,23 +313,46 @@ pub enum Direction {
     Right,
 }
 
+impl Direction {
+    /// in order of preference
+    pub const ALL: [Direction; 4] = [Direction::Right, Direction::Left, Direction::Up, Direction::Down];
+
+    pub fn unit_vector(&self) -> Vector {
+        match self {
+            Direction::Up => Vector::new(0.0, -1.0),
+            Direction::Down => Vector::new(0.0, 1.0),
+            Direction::Left => Vector::new(-1.0, 0.0),
+            Direction::Right => Vector::new(1.0, 0.0),
+        }
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        matches!(self, Direction::Left | Direction::Right)
+    }
+}
+
 #[derive(Debug, Clone)]
 struct Label {
     input_string: String,
     tokens: Vec<Token>,
     bounds: Rectangle,
     direction: Direction,
+    /// shift applied to every token after the first
+    offset: Vector,
+    font: Font,
 }
 
 impl Label {
     const TOKEN_SEPARATION: f32 = 1.0;
 
-    pub fn new(input_string: String, direction: Direction) -> Self {
-        let tokens = Self::tokenize(&input_string);
+    pub fn new(input_string: String, direction: Direction, font: Font) -> Self {
+        let tokens = Self::tokenize(&input_string, font);
         let mut label = Self {
             input_string,
             tokens,
             direction,
+            offset: Vector::ZERO,
+            font,
             bounds: Rectangle::default(),
         };
 
@@ -225,7 +360,7 @@ impl Label {
         label
     }
 
-    pub fn draw(&self, frame: &mut Frame, transform: &Transform<f32>, color: &Color) {
+    pub fn draw(&self, frame: &mut impl Surface, transform: &Transform<f32>, color: &Color) {
         let mut shift = match self.direction {
             Direction::Right => Vector::new(self.bounds.x, 0.0),
             Direction::Left => Vector::new(self.bounds.x + self.bounds.width, 0.0),
@@ -233,14 +368,17 @@ impl Label {
             Direction::Up => Vector::new(0.0, self.bounds.y + self.bounds.height),
         };
 
-        for Token { paths, bounds } in &self.tokens {
+        for (i, Token { paths, bounds }) in self.tokens.iter().enumerate() {
             // shift such that drawing starts at x = shift
-            let new_shift = match self.direction {
+            let mut new_shift = match self.direction {
                 Direction::Right => shift - Vector::new(bounds.x, 0.0),
                 Direction::Left => shift - Vector::new(bounds.x + bounds.width, 0.0),
                 Direction::Down => shift - Vector::new(0.0, bounds.y),
                 Direction::Up => shift - Vector::new(0.0, bounds.y + bounds.height),
             };
+            if i > 0 {
+                new_shift = new_shift + self.offset;
+            }
 
             let transform = Transform::translation(new_shift.x, new_shift.y).then(transform);
 
@@ -275,6 +413,13 @@ impl Label {
         }
     }
 
+    pub fn update_offset(&mut self, offset: Vector) {
+        if offset != self.offset {
+            self.offset = offset;
+            self.calculate_bounds();
+        }
+    }
+
     fn calculate_bounds(&mut self) {
         if self.tokens.is_empty() {
             self.bounds = Rectangle::default();
@@ -312,42 +457,171 @@ impl Label {
             label_bounds = label_bounds.union(&bounds);
         }
 
-        self.bounds = label_bounds;
+        // the first token stays centered on the atom
+        let first = self.tokens[0].bounds;
+        let rest = Rectangle::new(label_bounds.position() + self.offset, label_bounds.size());
+        self.bounds = first.union(&rest);
     }
 
-    fn tokenize(input_string: &str) -> Vec<Token> {
+    /// splits a label into tokens starting at each capital letter
+    ///
+    /// digits after a symbol are subscripts, digits before a symbol are isotope superscripts,
+    /// signs followed by optional digits are charges and dots are radicals, `^` and `_`
+    /// mark the next character or `{group}` as superscript or subscript, `*` toggles italics
+    /// and `\name` writes a greek letter
+    fn tokenize(input_string: &str, font: Font) -> Vec<Token> {
+        Self::parse(input_string)
+            .into_iter()
+            .map(|segments| Token::new(segments, font))
+            .collect()
+    }
+
+    fn parse(input_string: &str) -> Vec<Vec<Segment>> {
         let mut tokens = Vec::new();
-        let mut current_token = String::new();
+        let mut current_token: Vec<Segment> = Vec::new();
+        let mut chars = input_string.chars().peekable();
+        let mut italic = false;
+
+        while let Some(c) = chars.next() {
+            if push_markup(c, &mut chars, &mut current_token, &mut italic) {
+                continue;
+            }
 
-        for c in input_string.chars() {
             match c {
                 _ if c.is_uppercase() => {
-                    if !current_token.is_empty() {
-                        tokens.push(Token::new(current_token));
-                        current_token = String::new();
+                    // an isotope prefix stays with the symbol it belongs to
+                    if current_token.iter().any(|segment| segment.script == Script::Normal) {
+                        tokens.push(std::mem::take(&mut current_token));
                     }
-                    current_token.push(c);
+                    push_segment(&mut current_token, c, Script::Normal, italic);
                 }
-                _ if c.is_ascii_digit() => current_token.push(match c {
-                    '0' => '₀',
-                    '1' => '₁',
-                    '2' => '₂',
-                    '3' => '₃',
-                    '4' => '₄',
-                    '5' => '₅',
-                    '6' => '₆',
-                    '7' => '₇',
-                    '8' => '₈',
-                    '9' => '₉',
-                    _ => unreachable!(),
-                }),
-                _ => current_token.push(c),
+                _ if c.is_ascii_digit() => {
+                    let mut digits = c.to_string();
+                    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                        digits.push(digit);
+                    }
+
+                    // digits after a charge are its magnitude, e.g. "Fe+3"
+                    let after_charge = current_token.last().is_some_and(|segment| {
+                        segment.script == Script::Superscript && segment.text.ends_with(['+', '-'])
+                    });
+                    let script = match current_token.is_empty() || after_charge {
+                        true => Script::Superscript,
+                        false => Script::Subscript,
+                    };
+
+                    for digit in digits.chars() {
+                        push_segment(&mut current_token, digit, script, italic);
+                    }
+                }
+                // a sign is a charge unless it joins two parts of a label, e.g. "t-Bu"
+                '+' | '-' if chars.peek().is_none_or(|c| matches!(c, '+' | '-' | '.' | '•' | ' ') || c.is_ascii_digit()) => {
+                    push_segment(&mut current_token, c, Script::Superscript, false);
+                }
+                '.' | '•' => push_segment(&mut current_token, '•', Script::Superscript, false),
+                _ => push_segment(&mut current_token, c, Script::Normal, italic),
             }
         }
         if !current_token.is_empty() {
-            tokens.push(Token::new(current_token));
+            tokens.push(current_token);
         }
 
         tokens
     }
 }
+
+/// free text with the markup labels use, but none of their chemistry, so digits, signs and
+/// capitals are left as they're written
+pub(super) fn parse_markup(text: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut italic = false;
+
+    while let Some(c) = chars.next() {
+        if !push_markup(c, &mut chars, &mut segments, &mut italic) {
+            push_segment(&mut segments, c, Script::Normal, italic);
+        }
+    }
+
+    segments
+}
+
+/// reads the markup at a character, `^` and `_` marking the next character or `{group}` as
+/// superscript or subscript, `*` toggling italics and `\name` writing a greek letter, returning
+/// whether the character was markup
+fn push_markup(c: char, chars: &mut Peekable<Chars>, segments: &mut Vec<Segment>, italic: &mut bool) -> bool {
+    match c {
+        '^' | '_' => {
+            let script = if c == '^' { Script::Superscript } else { Script::Subscript };
+
+            match chars.next() {
+                Some('{') => {
+                    for c in chars.by_ref().take_while(|c| *c != '}') {
+                        push_segment(segments, c, script, *italic);
+                    }
+                }
+                Some(c) => push_segment(segments, c, script, *italic),
+                None => (),
+            }
+        }
+        '*' => *italic = !*italic,
+        '\\' => {
+            let mut name = String::new();
+            while let Some(c) = chars.next_if(char::is_ascii_alphabetic) {
+                name.push(c);
+            }
+
+            match GREEK_LETTERS.iter().find(|(greek_name, _)| *greek_name == name) {
+                Some((_, letter)) => push_segment(segments, *letter, Script::Normal, *italic),
+                None => {
+                    for c in iter::once('\\').chain(name.chars()) {
+                        push_segment(segments, c, Script::Normal, *italic);
+                    }
+                }
+            }
+        }
+        _ => return false,
+    }
+
+    true
+}
+
+/// adds a character to the last run when it's written the same way, or starts a new one
+fn push_segment(segments: &mut Vec<Segment>, c: char, script: Script, italic: bool) {
+    match segments.last_mut() {
+        Some(segment) if segment.script == script && segment.italic == italic => segment.text.push(c),
+        _ => segments.push(Segment {
+            text: c.to_string(),
+            script,
+            italic,
+        }),
+    }
+}
+
+/// names accepted after a backslash in labels
+pub const GREEK_LETTERS: &[(&str, char)] = &[
+    ("alpha", 'α'),
+    ("beta", 'β'),
+    ("gamma", 'γ'),
+    ("delta", 'δ'),
+    ("epsilon", 'ε'),
+    ("zeta", 'ζ'),
+    ("eta", 'η'),
+    ("theta", 'θ'),
+    ("kappa", 'κ'),
+    ("lambda", 'λ'),
+    ("mu", 'μ'),
+    ("nu", 'ν'),
+    ("xi", 'ξ'),
+    ("pi", 'π'),
+    ("rho", 'ρ'),
+    ("sigma", 'σ'),
+    ("tau", 'τ'),
+    ("phi", 'φ'),
+    ("chi", 'χ'),
+    ("psi", 'ψ'),
+    ("omega", 'ω'),
+    ("Delta", 'Δ'),
+    ("Sigma", 'Σ'),
+    ("Omega", 'Ω'),
+];
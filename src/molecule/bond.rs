@@ -1,38 +1,60 @@
 use std::f32::consts::PI;
-use std::iter;
+use std::fmt;
 
 use anyhow::Context;
 use anyhow::Result;
 use iced::widget::canvas::path::lyon_path::math::Transform;
-use iced::widget::canvas::Frame;
+use crate::surface::Surface;
 use iced::widget::canvas::Path;
-use iced::widget::canvas::Stroke;
+use iced::widget::canvas::{LineDash, Stroke};
 use iced::Color;
 use iced::Point;
 use iced::Radians;
 use iced::Size;
 use iced::Vector;
-use rustc_hash::FxHashMap;
 
 use crate::bounds::Bounds;
-use crate::canvas::MolCanvas;
+use crate::canvas::{DashPattern, DocumentStyle, MolCanvas};
 
+use super::arc::BondArc;
 use super::Atom;
 use super::AtomId;
+use super::FxIndexMap;
+use super::TextOutline;
 
 #[derive(Debug, Clone)]
 pub struct Bond {
     start: AtomId,
     end: AtomId,
     bond_type: BondType,
+    /// text drawn beside the bond, such as its order or dissociation energy
+    annotation: Option<TextOutline>,
+    /// whether a retrosynthetic disconnection is marked across the bond's middle
+    disconnection: bool,
+    /// points the bond is bent through on its way between the atoms, each as the fraction of the
+    /// way along the straight bond and the fraction of its length to the side, so bends follow the
+    /// atoms when they move
+    waypoints: Vec<Vector>,
+    /// how far the middle of a curved bond is to the side, as a fraction of the bond's length,
+    /// zero for bonds that aren't curved
+    curvature: f32,
 }
 
 impl Bond {
+    /// curvature of a bond when it's first curved
+    pub const DEFAULT_CURVATURE: f32 = 0.2;
+    /// pieces a curved bond is split into when it's measured and hit tested
+    const ARC_SEGMENTS: usize = 16;
+
     pub fn new(start: AtomId, end: AtomId, bond_type: BondType) -> Bond {
         Bond {
             start,
             end,
             bond_type,
+            annotation: None,
+            disconnection: false,
+            waypoints: vec![],
+            curvature: 0.0,
         }
     }
 
@@ -46,29 +68,68 @@ impl Bond {
 
     pub fn draw(
         &self,
-        frame: &mut Frame,
+        frame: &mut impl Surface,
         transform: &Transform,
-        atoms: &FxHashMap<AtomId, Atom>,
+        atoms: &FxIndexMap<AtomId, Atom>,
         stroke: &Stroke,
-        color: &Color
+        color: &Color,
+        style: &DocumentStyle,
     ) -> Result<()> {
-        let start_atom = atoms.get(&self.start).ok_or(super::Error::AtomMissing(self.start)).context("while drawing bond")?;
-        let end_atom = atoms.get(&self.end).ok_or(super::Error::AtomMissing(self.end)).context("while drawing bond")?;
+        let path = self.path(atoms).context("while drawing bond")?;
+        let arc = self.arc(&path);
+
+        if style.lewis_structures && self.bond_type.order() > 0 && arc.is_some() {
+            // a curved bond's pairs go across its middle
+            let middle = path.len() / 2;
+            draw_electron_pairs(frame, transform, path[middle - 1], path[middle + 1], self.bond_type.order(), color);
+            return Ok(());
+        }
+        if style.lewis_structures && self.bond_type.order() > 0 {
+            // a bent bond's pairs go on its longest piece
+            let (start, end) = segments(&path)
+                .max_by(|(a, b), (c, d)| a.distance(*b).total_cmp(&c.distance(*d)))
+                .unwrap_or_default();
+            draw_electron_pairs(frame, transform, start, end, self.bond_type.order(), color);
+            return Ok(());
+        }
+
+        if let Some(arc) = arc {
+            return draw_arc_bond(frame, transform, &arc, &self.bond_type, stroke, color, style);
+        }
 
-        let start: Point = start_atom.bond_start(end_atom.position()).into();
-        let end: Point = end_atom.bond_start(start_atom.position()).into();
+        let last = path.len() - 2;
+        for (n, (start, end)) in segments(&path).enumerate() {
+            // only the last piece of a bent dative bond has the arrowhead
+            let bond_type = match self.bond_type {
+                BondType::Dative if n != last => BondType::Normal(1),
+                bond_type => bond_type,
+            };
+            draw_bond(frame, transform, start, end, &bond_type, stroke, color, style)?;
+        }
 
+        Ok(())
+    }
+
+    /// the arc a curved bond is drawn along, through the middle of its path and its trimmed ends
+    fn arc(&self, path: &[Point]) -> Option<BondArc> {
+        if self.curvature == 0.0 || path.len() < 3 {
+            return None;
+        }
 
-        draw_bond(frame, transform, start, end, &self.bond_type, stroke, color)
+        BondArc::through(path[0], path[path.len() / 2], path[path.len() - 1])
     }
 
-    pub fn bounds(&self, atoms: &FxHashMap<AtomId, Atom>) -> Result<Bounds> {
-        let start_atom = atoms.get(&self.start).ok_or(super::Error::AtomMissing(self.start)).context("while calculating bond bounds")?;
-        let end_atom = atoms.get(&self.end).ok_or(super::Error::AtomMissing(self.end)).context("while calculating bond bounds")?;
+    /// padded bounds of the bond, a bent bond's covering every piece of it
+    pub fn bounds(&self, atoms: &FxIndexMap<AtomId, Atom>) -> Result<Bounds> {
+        let path = self.path(atoms).context("while calculating bond bounds")?;
 
-        let start: Point = start_atom.bond_start(end_atom.position()).into();
-        let end: Point = end_atom.bond_start(start_atom.position()).into();
+        let mut pieces = segments(&path).map(|(start, end)| self.segment_bounds(start, end));
+        let first = pieces.next().unwrap_or_else(|| self.segment_bounds(path[0], path[0]));
 
+        Ok(pieces.fold(first, |bounds, piece| bounds.union(&piece)))
+    }
+
+    fn segment_bounds(&self, start: Point, end: Point) -> Bounds {
         let direction: Vector = end - start;
         let length = (direction.x.powi(2) + direction.y.powi(2)).sqrt();
         let unit_normal = Vector::new(direction.y, -direction.x) * length.powi(-1);
@@ -78,6 +139,10 @@ impl Bond {
             BondType::Hydrogen => MolCanvas::H_BOND_WIDTH,
             BondType::Wedge => MolCanvas::WEDGE_END_WIDTH,
             BondType::Dash => MolCanvas::DASH_END_WIDTH,
+            BondType::Bold => MolCanvas::BOLD_WIDTH,
+            BondType::Wavy => MolCanvas::WAVY_AMPLITUDE * 2.0 + MolCanvas::BOND_WIDTH,
+            BondType::PartialDouble => MolCanvas::BOND_OFFSETS + MolCanvas::BOND_WIDTH,
+            BondType::Dative => MolCanvas::DATIVE_HEAD_WIDTH,
         };
 
         let offset = start + unit_normal * (width / 2.0);
@@ -89,7 +154,38 @@ impl Bond {
         let mut bounds = Bounds::new(offset, size, angle);
         bounds.add_padding(MolCanvas::BOND_PADDING);
 
-        Ok(bounds)
+        bounds
+    }
+
+    /// whether a point, relative to the molecule, is within the tolerance of the bond's padded
+    /// bounds, which follow the taper of wedges and dashes rather than their widest end
+    pub fn contains_within(&self, atoms: &FxIndexMap<AtomId, Atom>, point: Point, tolerance: f32) -> Result<bool> {
+        let path = self.path(atoms).context("while hit testing bond")?;
+
+        let contains = segments(&path).any(|(start, end)| self.segment_contains_within(start, end, point, tolerance));
+
+        Ok(contains)
+    }
+
+    fn segment_contains_within(&self, start: Point, end: Point, point: Point, tolerance: f32) -> bool {
+        let direction: Vector = end - start;
+        let length = (direction.x.powi(2) + direction.y.powi(2)).sqrt();
+        if tapered_width(&self.bond_type, 0.0).is_none() || length < f32::EPSILON {
+            return self.segment_bounds(start, end).contains_within(point, tolerance);
+        }
+
+        let unit_direction = direction * length.powi(-1);
+        let offset = point - start;
+        let along = offset.x * unit_direction.x + offset.y * unit_direction.y;
+        let across = (offset.x * unit_direction.y - offset.y * unit_direction.x).abs();
+
+        let margin = MolCanvas::BOND_PADDING + tolerance;
+        if along < -margin || along > length + margin {
+            return false;
+        }
+
+        let width = tapered_width(&self.bond_type, (along / length).clamp(0.0, 1.0)).unwrap_or_default();
+        across <= width / 2.0 + margin
     }
 
     pub fn fixed_length(start: Point, direction: Vector, length: f32) -> Point {
@@ -102,16 +198,112 @@ impl Bond {
         }
     }
 
-    pub fn center(&self, atoms: &FxHashMap<AtomId, Atom>) -> Result<Point> {
-        let start_atom = atoms.get(&self.start).ok_or(super::Error::AtomMissing(self.start)).context("while calculating bond bounds")?;
-        let end_atom = atoms.get(&self.end).ok_or(super::Error::AtomMissing(self.end)).context("while calculating bond bounds")?;
+    /// the point halfway along the drawn bond
+    pub fn center(&self, atoms: &FxIndexMap<AtomId, Atom>) -> Result<Point> {
+        let path = self.path(atoms).context("while calculating bond center")?;
 
-        let start: Point = start_atom.bond_start(end_atom.position()).into();
-        let end: Point = end_atom.bond_start(start_atom.position()).into();
+        let length: f32 = segments(&path).map(|(start, end)| start.distance(end)).sum();
+        let mut remaining = length / 2.0;
+        for (start, end) in segments(&path) {
+            let piece = start.distance(end);
+            if remaining <= piece && piece > f32::EPSILON {
+                return Ok(start + (end - start) * (remaining / piece));
+            }
+            remaining -= piece;
+        }
 
-        let direction: Vector = end - start;
+        Ok(path[0])
+    }
+
+    /// points the bond runs through, from the start atom's position over any bends to the end
+    /// atom's, relative to the molecule, a curved bond's being spaced along its arc
+    pub fn points(&self, atoms: &FxIndexMap<AtomId, Atom>) -> Result<Vec<Point>> {
+        let start: Point = atoms.get(&self.start).ok_or(super::Error::AtomMissing(self.start)).context("while getting bond points")?.position().into();
+        let end: Point = atoms.get(&self.end).ok_or(super::Error::AtomMissing(self.end)).context("while getting bond points")?.position().into();
+
+        let direction = end - start;
+        let normal = Vector::new(direction.y, -direction.x);
+
+        let apex = start + direction * 0.5 + normal * self.curvature;
+        if let Some(arc) = BondArc::through(start, apex, end).filter(|_| self.curvature != 0.0) {
+            return Ok(arc.points(Self::ARC_SEGMENTS));
+        }
 
-        Ok(start + direction * 0.5)
+        let mut points = vec![start];
+        points.extend(self.waypoints.iter().map(|waypoint| start + direction * waypoint.x + normal * waypoint.y));
+        points.push(end);
+
+        Ok(points)
+    }
+
+    /// the bond as it's drawn, its points with the ends trimmed to the edges of the atoms' labels
+    pub fn path(&self, atoms: &FxIndexMap<AtomId, Atom>) -> Result<Vec<Point>> {
+        let start_atom = atoms.get(&self.start).ok_or(super::Error::AtomMissing(self.start)).context("while getting bond path")?;
+        let end_atom = atoms.get(&self.end).ok_or(super::Error::AtomMissing(self.end)).context("while getting bond path")?;
+
+        let mut path = self.points(atoms).context("while getting bond path")?;
+        let last = path.len() - 1;
+        path[0] = start_atom.bond_start(path[1].into()).into();
+        path[last] = end_atom.bond_start(path[last - 1].into()).into();
+
+        Ok(path)
+    }
+
+    /// the piece of the bond its middle is on, between untrimmed points
+    pub fn middle_segment(&self, atoms: &FxIndexMap<AtomId, Atom>) -> Result<(Point, Point)> {
+        let points = self.points(atoms).context("while getting middle of bond")?;
+        let middle = points.len() / 2;
+
+        Ok(match points.len() % 2 {
+            // an odd number of points puts a bend at the middle, so the straight line through
+            // its neighbours is used
+            1 => (points[middle - 1], points[middle + 1]),
+            _ => (points[middle - 1], points[middle]),
+        })
+    }
+
+    /// where a point relative to the molecule is as a waypoint of the bond
+    pub fn waypoint_at(&self, atoms: &FxIndexMap<AtomId, Atom>, point: Point) -> Result<Vector> {
+        let points = self.points(atoms).context("while getting bond waypoint")?;
+        let (start, end) = (points[0], points[points.len() - 1]);
+
+        let direction = end - start;
+        let length_squared = (direction.x.powi(2) + direction.y.powi(2)).max(f32::EPSILON);
+        let offset = point - start;
+
+        Ok(Vector::new(
+            (offset.x * direction.x + offset.y * direction.y) / length_squared,
+            (offset.x * direction.y - offset.y * direction.x) / length_squared,
+        ))
+    }
+
+    pub fn waypoints(&self) -> &[Vector] {
+        &self.waypoints
+    }
+
+    /// bends the bond through the waypoints, it's no longer curved
+    pub fn set_waypoints(&mut self, waypoints: Vec<Vector>) {
+        self.waypoints = waypoints;
+        self.curvature = 0.0;
+    }
+
+    pub fn curvature(&self) -> f32 {
+        self.curvature
+    }
+
+    /// curves the bond, which is no longer bent
+    pub fn set_curvature(&mut self, curvature: f32) {
+        self.curvature = curvature;
+        self.waypoints.clear();
+    }
+
+    /// the same bond between other atoms
+    pub fn with_atoms(&self, start: AtomId, end: AtomId) -> Bond {
+        Bond {
+            start,
+            end,
+            ..self.clone()
+        }
     }
 
     pub fn start(&self) -> AtomId {
@@ -126,12 +318,73 @@ impl Bond {
         self.bond_type
     }
 
+    pub fn annotation(&self) -> Option<&TextOutline> {
+        self.annotation.as_ref()
+    }
+
+    pub fn set_annotation(&mut self, annotation: Option<TextOutline>) {
+        self.annotation = annotation;
+    }
+
+    pub fn is_disconnection(&self) -> bool {
+        self.disconnection
+    }
+
+    pub fn set_disconnection(&mut self, disconnection: bool) {
+        self.disconnection = disconnection;
+    }
+
     pub fn atom_ids(&self) -> impl Iterator<Item = AtomId> {
         [self.start, self.end].into_iter()
     }
+
+    pub fn atom(&self, end: BondEnd) -> AtomId {
+        match end {
+            BondEnd::Start => self.start,
+            BondEnd::End => self.end,
+        }
+    }
+
+    pub fn set_atom(&mut self, end: BondEnd, atom_id: AtomId) {
+        match end {
+            BondEnd::Start => self.start = atom_id,
+            BondEnd::End => self.end = atom_id,
+        }
+    }
 }
 
-pub fn draw_bond(frame: &mut Frame, transform: &Transform, start: Point, end: Point, bond_type: &BondType, stroke: &Stroke, color: &Color) -> Result<()> {
+/// one of the two atoms a bond connects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BondEnd {
+    Start,
+    End,
+}
+
+impl BondEnd {
+    pub fn other(&self) -> BondEnd {
+        match self {
+            BondEnd::Start => BondEnd::End,
+            BondEnd::End => BondEnd::Start,
+        }
+    }
+}
+
+/// consecutive pairs of points along a path
+fn segments(path: &[Point]) -> impl Iterator<Item = (Point, Point)> + '_ {
+    path.windows(2).map(|pair| (pair[0], pair[1]))
+}
+
+/// width of a wedge or dash bond the fraction of the way from its narrow start to its wide end
+fn tapered_width(bond_type: &BondType, fraction: f32) -> Option<f32> {
+    match bond_type {
+        BondType::Wedge => Some(MolCanvas::WEDGE_START_WIDTH + fraction * (MolCanvas::WEDGE_END_WIDTH - MolCanvas::WEDGE_START_WIDTH)),
+        BondType::Dash => Some(MolCanvas::DASH_START_WIDTH + fraction * MolCanvas::DASH_END_WIDTH),
+        _ => None,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn draw_bond(frame: &mut impl Surface, transform: &Transform, start: Point, end: Point, bond_type: &BondType, stroke: &Stroke, color: &Color, style: &DocumentStyle) -> Result<()> {
     let direction: Vector = end - start;
     let length = (direction.x.powi(2) + direction.y.powi(2)).sqrt();
     let normal = Vector::new(direction.y, -direction.x);
@@ -140,46 +393,25 @@ pub fn draw_bond(frame: &mut Frame, transform: &Transform, start: Point, end: Po
 
     match bond_type {
         BondType::Normal(strength) => {
-            // offsets is an iterator of either (1, -1, 3, -3, 5, -5, ...) for even strength
-            // or (0, 2, -2, 4, -4, 6, -6, ...) for odd strength
-            let offsets = {
-                let mut curr_offset: i32 = match strength % 2 {
-                    0 => 1,
-                    1 => 0,
-                    _ => unreachable!()
-                };
-
-                iter::from_fn(move || {
-                    let old_offset = curr_offset;
-
-                    curr_offset = match curr_offset.is_positive() {
-                        _ if curr_offset == 0 => 2,
-                        true => -curr_offset,
-                        false => -curr_offset + 2
-                    };
-
-                    if old_offset > *strength as i32 {
-                        None
-                    } else {
-                        Some(old_offset)
-                    }
-                })
-            };
+            // lines are centered on the bond, e.g. (0) for single, (-0.5, 0.5) for double and
+            // (-1, 0, 1) for triple, which extends to any order
+            let offsets = (0..*strength).map(|n| n as f32 - (*strength as f32 - 1.0) / 2.0);
 
             for offset in offsets {
-                // divided by 2 to account for existing spacing of 2 between bonds
-                let offset = unit_normal * (offset as f32 * MolCanvas::BOND_OFFSETS / 2.0);
+                let offset = unit_normal * (offset * MolCanvas::BOND_OFFSETS);
                 let path = Path::line(start + offset, end + offset).transform(transform);
 
                 frame.stroke(&path, *stroke);
             }
         }
         BondType::Wedge => {
+            let start_width = tapered_width(bond_type, 0.0).unwrap_or_default();
+            let end_width = tapered_width(bond_type, 1.0).unwrap_or_default();
             let path = Path::new(|builder| {
-                builder.move_to(start - unit_normal * (MolCanvas::WEDGE_START_WIDTH / 2.0));
-                builder.line_to(start + unit_normal * (MolCanvas::WEDGE_START_WIDTH / 2.0));
-                builder.line_to(end + unit_normal * (MolCanvas::WEDGE_END_WIDTH / 2.0));
-                builder.line_to(end - unit_normal * (MolCanvas::WEDGE_END_WIDTH / 2.0));
+                builder.move_to(start - unit_normal * (start_width / 2.0));
+                builder.line_to(start + unit_normal * (start_width / 2.0));
+                builder.line_to(end + unit_normal * (end_width / 2.0));
+                builder.line_to(end - unit_normal * (end_width / 2.0));
                 builder.close();
             }).transform(transform);
 
@@ -196,9 +428,7 @@ pub fn draw_bond(frame: &mut Frame, transform: &Transform, start: Point, end: Po
             // or (0, 2, -2, 4, -4, 6, -6, ...) for odd strength
             let offsets = 0..=dashes;
 
-            let width = |n: u32| {
-                MolCanvas::DASH_START_WIDTH + (n as f32 / dashes as f32) * MolCanvas::DASH_END_WIDTH
-            };
+            let width = |n: u32| tapered_width(bond_type, n as f32 / dashes as f32).unwrap_or_default();
 
             for n in offsets {
                 let offset = unit_direction * (n as f32 * true_spacing);
@@ -232,11 +462,182 @@ pub fn draw_bond(frame: &mut Frame, transform: &Transform, start: Point, end: Po
             }
 
         }
+        BondType::PartialDouble => {
+            let offset = unit_normal * (MolCanvas::BOND_OFFSETS / 2.0);
+            let solid = Path::line(start - offset, end - offset).transform(transform);
+            let dashed = Path::line(start + offset, end + offset).transform(transform);
+
+            let DashPattern { dash, gap } = style.partial_bond_dash;
+            let segments = [dash, gap];
+
+            frame.stroke(&solid, *stroke);
+            frame.stroke(&dashed, Stroke {
+                line_dash: LineDash { segments: &segments, offset: 0 },
+                ..*stroke
+            });
+        }
+        BondType::Bold => {
+            let offset = unit_normal * (MolCanvas::BOLD_WIDTH / 2.0);
+            let path = Path::new(|builder| {
+                builder.move_to(start - offset);
+                builder.line_to(start + offset);
+                builder.line_to(end + offset);
+                builder.line_to(end - offset);
+                builder.close();
+            }).transform(transform);
+
+            frame.fill(&path, *color);
+        }
+        BondType::Dative => {
+            let base = end - unit_direction * MolCanvas::DATIVE_HEAD_LENGTH.min(length);
+            frame.stroke(&Path::line(start, base).transform(transform), *stroke);
+            frame.fill(&arrowhead(end, base, unit_normal).transform(transform), *color);
+        }
+        BondType::Wavy => {
+            // aim to have a half wave every MolCanvas::WAVY_PERIOD / 2
+            let half_waves = u32::max(f32::round(2.0 * length / MolCanvas::WAVY_PERIOD + 0.01) as u32, 1);
+            let half_length = length / half_waves as f32;
+
+            let path = Path::new(|builder| {
+                builder.move_to(start);
+                for n in 0..half_waves {
+                    let side = if n % 2 == 0 { 1.0 } else { -1.0 };
+                    let control = start
+                        + unit_direction * ((n as f32 + 0.5) * half_length)
+                        + unit_normal * (side * MolCanvas::WAVY_AMPLITUDE * 2.0);
+                    let to = start + unit_direction * ((n + 1) as f32 * half_length);
+
+                    builder.quadratic_curve_to(control, to);
+                }
+            }).transform(transform);
+
+            frame.stroke(&path, *stroke);
+        }
     }
     
     Ok(())
 }
 
+/// draws a bond along an arc, the lines of a bond running around it and the marks across it
+#[allow(clippy::too_many_arguments)]
+fn draw_arc_bond(frame: &mut impl Surface, transform: &Transform, arc: &BondArc, bond_type: &BondType, stroke: &Stroke, color: &Color, style: &DocumentStyle) -> Result<()> {
+    let length = arc.length();
+    // a filled strip along the arc, the given width at each fraction of the way along
+    let band = |width: &dyn Fn(f32) -> f32| {
+        let samples = (0..=Bond::ARC_SEGMENTS).map(|n| n as f32 / Bond::ARC_SEGMENTS as f32);
+        let outer = samples.clone().map(|fraction| arc.point(fraction, width(fraction) / 2.0));
+        let inner = samples.rev().map(|fraction| arc.point(fraction, -width(fraction) / 2.0));
+        let points = outer.chain(inner).collect::<Vec<_>>();
+
+        Path::new(|builder| {
+            builder.move_to(points[0]);
+            for point in &points[1..] {
+                builder.line_to(*point);
+            }
+            builder.close();
+        }).transform(transform)
+    };
+    // marks across the arc spaced roughly the spacing apart, each the width at its fraction
+    let mut ticks = |spacing: f32, width: &dyn Fn(f32) -> f32| {
+        let marked = length - MolCanvas::BOND_WIDTH;
+        let dashes: u32 = f32::round(marked / spacing + 0.01) as u32;
+        let true_spacing = marked / dashes.max(1) as f32;
+
+        for n in 0..=dashes {
+            let fraction = (MolCanvas::BOND_WIDTH / 2.0 + n as f32 * true_spacing) / length;
+            let width = width(n as f32 / dashes.max(1) as f32);
+            let path = Path::line(arc.point(fraction, width / 2.0), arc.point(fraction, -width / 2.0)).transform(transform);
+
+            frame.stroke(&path, *stroke);
+        }
+    };
+
+    match bond_type {
+        BondType::Normal(strength) => {
+            let offsets = (0..*strength).map(|n| n as f32 - (*strength as f32 - 1.0) / 2.0);
+
+            for offset in offsets {
+                frame.stroke(&arc.path(offset * MolCanvas::BOND_OFFSETS).transform(transform), *stroke);
+            }
+        }
+        BondType::Wedge => frame.fill(&band(&|fraction| tapered_width(bond_type, fraction).unwrap_or_default()), *color),
+        BondType::Dash => ticks(MolCanvas::DASH_BOND_OFFSETS, &|fraction| tapered_width(bond_type, fraction).unwrap_or_default()),
+        BondType::Hydrogen => ticks(MolCanvas::H_BOND_OFFSETS, &|_| MolCanvas::H_BOND_WIDTH),
+        BondType::PartialDouble => {
+            let offset = MolCanvas::BOND_OFFSETS / 2.0;
+            let DashPattern { dash, gap } = style.partial_bond_dash;
+            let segments = [dash, gap];
+
+            frame.stroke(&arc.path(-offset).transform(transform), *stroke);
+            frame.stroke(&arc.path(offset).transform(transform), Stroke {
+                line_dash: LineDash { segments: &segments, offset: 0 },
+                ..*stroke
+            });
+        }
+        BondType::Bold => frame.fill(&band(&|_| MolCanvas::BOLD_WIDTH), *color),
+        BondType::Dative => {
+            let tip = arc.point(1.0, 0.0);
+            let base = arc.point(1.0 - (MolCanvas::DATIVE_HEAD_LENGTH / length).min(1.0), 0.0);
+            let direction = tip - base;
+            let unit_normal = Vector::new(direction.y, -direction.x) * direction.x.hypot(direction.y).max(f32::EPSILON).powi(-1);
+
+            frame.stroke(&arc.path(0.0).transform(transform), *stroke);
+            frame.fill(&arrowhead(tip, base, unit_normal).transform(transform), *color);
+        }
+        BondType::Wavy => {
+            let half_waves = u32::max(f32::round(2.0 * length / MolCanvas::WAVY_PERIOD + 0.01) as u32, 1);
+            let samples = half_waves * 8;
+
+            let path = Path::new(|builder| {
+                builder.move_to(arc.point(0.0, 0.0));
+                for n in 1..=samples {
+                    let fraction = n as f32 / samples as f32;
+                    let offset = MolCanvas::WAVY_AMPLITUDE * (PI * half_waves as f32 * fraction).sin();
+                    builder.line_to(arc.point(fraction, offset));
+                }
+            }).transform(transform);
+
+            frame.stroke(&path, *stroke);
+        }
+    }
+
+    Ok(())
+}
+
+/// the filled head of a dative bond's arrow, pointing at the tip from the middle of its base
+fn arrowhead(tip: Point, base: Point, unit_normal: Vector) -> Path {
+    let half_width = unit_normal * (MolCanvas::DATIVE_HEAD_WIDTH / 2.0);
+
+    Path::new(|builder| {
+        builder.move_to(tip);
+        builder.line_to(base + half_width);
+        builder.line_to(base - half_width);
+        builder.close();
+    })
+}
+
+/// draws a bond as one pair of dots per shared electron pair, centered between the atoms
+pub fn draw_electron_pairs(frame: &mut impl Surface, transform: &Transform, start: Point, end: Point, order: u8, color: &Color) {
+    let direction: Vector = end - start;
+    let length = (direction.x.powi(2) + direction.y.powi(2)).sqrt();
+    let unit_direction = direction * length.powi(-1);
+    let unit_normal = Vector::new(unit_direction.y, -unit_direction.x);
+    let center = start + direction * 0.5;
+
+    for pair in 0..order {
+        // pairs are spaced along the bond, centered on its midpoint
+        let along = (pair as f32 - (order as f32 - 1.0) / 2.0) * MolCanvas::ELECTRON_SEPARATION;
+
+        for side in [-0.5, 0.5] {
+            let position = center
+                + unit_direction * along
+                + unit_normal * (side * MolCanvas::ELECTRON_SEPARATION);
+            let path = Path::circle(position, MolCanvas::ELECTRON_RADIUS).transform(transform);
+
+            frame.fill(&path, *color);
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BondType {
@@ -244,6 +645,61 @@ pub enum BondType {
     Wedge,
     Dash,
     Hydrogen,
+    /// whole substituent above the plane
+    Bold,
+    /// unknown stereochemistry
+    Wavy,
+    /// a single and a dashed line, for delocalised bonds and transition states
+    PartialDouble,
+    /// a coordinate bond, drawn as an arrow from the donor at its start to the acceptor at its end
+    Dative,
+}
+
+impl BondType {
+    /// number of shared electron pairs, non-covalent bonds have an order of 0 as do dative bonds,
+    /// so the donor keeps its hydrogens
+    pub fn order(&self) -> u8 {
+        match self {
+            BondType::Normal(strength) => *strength,
+            BondType::Wedge | BondType::Dash | BondType::Bold | BondType::Wavy | BondType::PartialDouble => 1,
+            BondType::Hydrogen | BondType::Dative => 0,
+        }
+    }
+}
+
+impl BondType {
+    pub const ALL: [BondType; 11] = [
+        BondType::Normal(1),
+        BondType::Normal(2),
+        BondType::Normal(3),
+        BondType::Normal(4),
+        BondType::Wedge,
+        BondType::Dash,
+        BondType::Bold,
+        BondType::Wavy,
+        BondType::PartialDouble,
+        BondType::Dative,
+        BondType::Hydrogen,
+    ];
+}
+
+impl fmt::Display for BondType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BondType::Normal(1) => write!(f, "Single"),
+            BondType::Normal(2) => write!(f, "Double"),
+            BondType::Normal(3) => write!(f, "Triple"),
+            BondType::Normal(4) => write!(f, "Quadruple"),
+            BondType::Normal(strength) => write!(f, "Order {}", strength),
+            BondType::Wedge => write!(f, "Wedge"),
+            BondType::Dash => write!(f, "Dash"),
+            BondType::Hydrogen => write!(f, "Hydrogen"),
+            BondType::Bold => write!(f, "Bold"),
+            BondType::Wavy => write!(f, "Wavy"),
+            BondType::PartialDouble => write!(f, "Partial double"),
+            BondType::Dative => write!(f, "Dative"),
+        }
+    }
 }
 
 impl Default for BondType {
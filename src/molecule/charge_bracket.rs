@@ -0,0 +1,92 @@
+use iced::widget::canvas::{Path, Stroke};
+use iced::{Color, Font, Point, Rectangle};
+
+use crate::surface::Surface;
+
+use super::TextOutline;
+
+/// square brackets around a whole molecule with its overall charge, as written for complex ions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChargeBracket {
+    pub charge: i32,
+    /// space between the structure and the brackets
+    pub padding: f32,
+}
+
+impl ChargeBracket {
+    pub const DEFAULT_PADDING: f32 = 2.0;
+    pub const MAX_PADDING: f32 = 20.0;
+    /// length of the ends turned in towards the structure
+    const TICK: f32 = 4.0;
+    const CHARGE_FONT_SIZE: f32 = 7.0;
+    /// space between the right bracket and the charge
+    const CHARGE_GAP: f32 = 1.0;
+
+    pub fn new(charge: i32) -> Self {
+        Self {
+            charge,
+            padding: Self::DEFAULT_PADDING,
+        }
+    }
+
+    /// the charge as it is written after the bracket, magnitude first and nothing when neutral
+    pub fn charge_text(&self) -> String {
+        let sign = if self.charge > 0 { '+' } else { '−' };
+
+        match self.charge.abs() {
+            0 => String::new(),
+            1 => sign.to_string(),
+            magnitude => format!("{}{}", magnitude, sign),
+        }
+    }
+
+    /// the charge outline and the middle of its top edge, level with the top of the brackets
+    fn charge_outline(&self, brackets: Rectangle, font: Font) -> Option<(TextOutline, Point)> {
+        let text = self.charge_text();
+        if text.is_empty() {
+            return None;
+        }
+
+        let outline = TextOutline::new(text, Self::CHARGE_FONT_SIZE, font);
+        let bounds = outline.bounds_at(Point::ORIGIN);
+        let position = Point::new(
+            brackets.x + brackets.width + Self::CHARGE_GAP - bounds.x,
+            brackets.y - bounds.y - bounds.height / 2.0,
+        );
+
+        Some((outline, position))
+    }
+
+    /// area the brackets and charge cover around a structure
+    pub fn bounds(&self, structure: Rectangle, font: Font) -> Rectangle {
+        let brackets = structure.expand(self.padding);
+
+        match self.charge_outline(brackets, font) {
+            Some((outline, position)) => brackets.union(&outline.bounds_at(position)),
+            None => brackets,
+        }
+    }
+
+    pub fn draw(&self, frame: &mut impl Surface, structure: Rectangle, stroke: &Stroke, color: Color, font: Font) {
+        let brackets = structure.expand(self.padding);
+        let (left, right) = (brackets.x, brackets.x + brackets.width);
+        let (top, bottom) = (brackets.y, brackets.y + brackets.height);
+
+        let path = Path::new(|builder| {
+            builder.move_to(Point::new(left + Self::TICK, top));
+            builder.line_to(Point::new(left, top));
+            builder.line_to(Point::new(left, bottom));
+            builder.line_to(Point::new(left + Self::TICK, bottom));
+
+            builder.move_to(Point::new(right - Self::TICK, top));
+            builder.line_to(Point::new(right, top));
+            builder.line_to(Point::new(right, bottom));
+            builder.line_to(Point::new(right - Self::TICK, bottom));
+        });
+        frame.stroke(&path, *stroke);
+
+        if let Some((outline, position)) = self.charge_outline(brackets, font) {
+            outline.draw(frame, position, color);
+        }
+    }
+}
@@ -0,0 +1,23 @@
+/// how carbons without a label are shown, only affects rendering
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayMode {
+    /// carbons are implied by bond vertices
+    #[default]
+    Skeletal,
+    /// terminal carbons are labelled with their hydrogens, e.g. CH3
+    Condensed,
+    /// every carbon is labelled with its hydrogens
+    Full,
+}
+
+impl DisplayMode {
+    pub const ALL: [DisplayMode; 3] = [Self::Skeletal, Self::Condensed, Self::Full];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Skeletal => "Skeletal",
+            Self::Condensed => "Condensed",
+            Self::Full => "Full",
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-use super::{AtomId, BondId, MoleculeId};
+use super::{AnnotationId, AtomId, BondId, InkId, InteractionId, LayerId, MoleculeId, OrbitalId, ShapeId};
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -16,4 +16,24 @@ pub enum Error {
     BondMissing(BondId),
     #[error("molecule not found")]
     MoleculeMissing(MoleculeId),
+    #[error("interaction not found")]
+    InteractionMissing(InteractionId),
+    #[error("ink not found")]
+    InkMissing(InkId),
+    #[error("shape not found")]
+    ShapeMissing(ShapeId),
+    #[error("annotation not found")]
+    AnnotationMissing(AnnotationId),
+    #[error("orbital not found")]
+    OrbitalMissing(OrbitalId),
+    #[error("layer not found")]
+    LayerMissing(LayerId),
+    #[error("layer is hidden or locked")]
+    LayerNotEditable(LayerId),
+    #[error("bond is part of a ring")]
+    RingBond(BondId),
+    #[error("atom does not have exactly two bonds")]
+    NotTwoBonds(AtomId),
+    #[error("atom can't be bonded to itself")]
+    SelfBond(AtomId),
 }
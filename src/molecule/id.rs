@@ -1,6 +1,8 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct AtomId(Uuid);
 impl AtomId {
     pub fn new() -> AtomId {
@@ -22,5 +24,63 @@ impl MoleculeId {
     pub fn new() -> MoleculeId {
         MoleculeId(Uuid::new_v4())
     }
+
+    /// id of a fragment split off a molecule, found from the molecule and the fragment's first atom
+    /// so splitting the same atoms off again gives back the same id
+    pub fn fragment(molecule_id: MoleculeId, atom_id: AtomId) -> MoleculeId {
+        let mut hasher = DefaultHasher::new();
+        (molecule_id, atom_id).hash(&mut hasher);
+        let high = hasher.finish();
+        high.hash(&mut hasher);
+
+        MoleculeId(Uuid::from_u64_pair(high, hasher.finish()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct LayerId(Uuid);
+impl LayerId {
+    pub fn new() -> LayerId {
+        LayerId(Uuid::new_v4())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct InteractionId(Uuid);
+impl InteractionId {
+    pub fn new() -> InteractionId {
+        InteractionId(Uuid::new_v4())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct InkId(Uuid);
+impl InkId {
+    pub fn new() -> InkId {
+        InkId(Uuid::new_v4())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct ShapeId(Uuid);
+impl ShapeId {
+    pub fn new() -> ShapeId {
+        ShapeId(Uuid::new_v4())
+    }
 }
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct AnnotationId(Uuid);
+impl AnnotationId {
+    pub fn new() -> AnnotationId {
+        AnnotationId(Uuid::new_v4())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct OrbitalId(Uuid);
+impl OrbitalId {
+    pub fn new() -> OrbitalId {
+        OrbitalId(Uuid::new_v4())
+    }
+}
@@ -0,0 +1,60 @@
+/// what's known about a compound besides its structure, kept with the document and written to
+/// SD files as data fields
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metadata {
+    /// CAS registry number, e.g. "64-17-5"
+    cas: Option<String>,
+    notes: Option<String>,
+    /// words to find and filter compounds by, trimmed and without repeats
+    tags: Vec<String>,
+    /// record the structure was taken from, such as a database entry it was looked up in
+    source: Option<String>,
+}
+
+impl Metadata {
+    pub fn cas(&self) -> Option<&str> {
+        self.cas.as_deref()
+    }
+
+    /// a blank number removes it
+    pub fn set_cas(&mut self, cas: String) {
+        self.cas = non_blank(cas);
+    }
+
+    pub fn notes(&self) -> Option<&str> {
+        self.notes.as_deref()
+    }
+
+    pub fn set_notes(&mut self, notes: String) {
+        self.notes = non_blank(notes);
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn set_tags(&mut self, tags: impl IntoIterator<Item = String>) {
+        self.tags.clear();
+        for tag in tags {
+            let tag = tag.trim();
+            if !tag.is_empty() && !self.tags.iter().any(|existing| existing == tag) {
+                self.tags.push(tag.to_string());
+            }
+        }
+    }
+
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    pub fn set_source(&mut self, source: String) {
+        self.source = non_blank(source);
+    }
+}
+
+fn non_blank(text: String) -> Option<String> {
+    match text.trim().is_empty() {
+        true => None,
+        false => Some(text),
+    }
+}
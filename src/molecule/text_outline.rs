@@ -0,0 +1,209 @@
+use iced::alignment::{Horizontal, Vertical};
+use iced::widget::canvas::path::lyon_path::geom::Transform;
+use iced::widget::canvas::path::lyon_path::traits::PathIterator;
+use iced::widget::canvas::path::lyon_path::PathEvent;
+use iced::widget::canvas::{Path, Text};
+use iced::widget::text::{LineHeight, Shaping};
+use iced::{font, Color, Font, Pixels, Point, Rectangle, Size, Vector};
+
+use crate::canvas::MolCanvas;
+use crate::surface::Surface;
+
+use super::atom::{parse_markup, Segment};
+
+/// text below a structure turned into glyph outlines, so it draws the same on the canvas and in
+/// exported images
+#[derive(Debug, Clone)]
+pub struct TextOutline {
+    content: String,
+    paths: Vec<Path>,
+    /// relative to the middle of the text's top edge
+    bounds: Rectangle,
+}
+
+impl TextOutline {
+    const LINE_HEIGHT: f32 = 1.2;
+    /// width of a space between words, relative to the font size
+    const SPACE_WIDTH: f32 = 0.3;
+    /// gap left between runs of markup written differently, relative to the font size
+    const RUN_SPACING: f32 = 0.05;
+
+    /// a single line of text
+    pub fn new(content: String, size: f32, font: Font) -> Self {
+        let paths = Self::line_paths(&content, size, font, 0.0);
+        let bounds = Self::calculate_bounds(&paths);
+
+        Self { content, paths, bounds }
+    }
+
+    /// text broken between words into centered lines no wider than `width`, words wider than
+    /// that get a line of their own
+    pub fn wrapped(content: String, size: f32, font: Font, width: f32) -> Self {
+        let space = size * Self::SPACE_WIDTH;
+        let mut lines: Vec<(String, f32)> = vec![];
+
+        for paragraph in content.lines() {
+            let mut line: Option<(String, f32)> = None;
+
+            for word in paragraph.split_whitespace() {
+                let word_width = Self::calculate_bounds(&Self::line_paths(word, size, font, 0.0)).width;
+
+                line = Some(match line {
+                    Some((text, line_width)) if line_width + space + word_width <= width => {
+                        (format!("{} {}", text, word), line_width + space + word_width)
+                    }
+                    Some(full) => {
+                        lines.push(full);
+                        (word.to_string(), word_width)
+                    }
+                    None => (word.to_string(), word_width),
+                });
+            }
+
+            // blank lines are kept as gaps between paragraphs
+            lines.push(line.unwrap_or_default());
+        }
+
+        let paths = lines
+            .iter()
+            .enumerate()
+            .flat_map(|(index, (line, _))| Self::line_paths(line, size, font, index as f32 * size * Self::LINE_HEIGHT))
+            .collect::<Vec<_>>();
+        let bounds = Self::calculate_bounds(&paths);
+
+        Self { content, paths, bounds }
+    }
+
+    /// lines of free text written with the markup labels use, see `atom::parse_markup`, each
+    /// centered on the text's middle
+    pub fn markup(content: String, size: f32, font: Font) -> Self {
+        let paths = content
+            .lines()
+            .enumerate()
+            .flat_map(|(index, line)| Self::markup_line_paths(line, size, font, index as f32 * size * Self::LINE_HEIGHT))
+            .collect::<Vec<_>>();
+        let bounds = Self::calculate_bounds(&paths);
+
+        Self { content, paths, bounds }
+    }
+
+    /// outlines of a line of markup laid out run by run, centered on x = 0 with its top at `top`
+    fn markup_line_paths(line: &str, size: f32, font: Font, top: f32) -> Vec<Path> {
+        let middle = top + size * Self::LINE_HEIGHT / 2.0;
+        let mut paths = vec![];
+        let mut cursor = 0.0;
+
+        for Segment { text, script, italic } in parse_markup(line) {
+            let font = match italic {
+                true => Font { style: font::Style::Italic, ..font },
+                false => font,
+            };
+            let run_size = size * script.scale();
+            let text = Text {
+                content: text,
+                color: Color::default(),
+                position: Point::new(cursor, middle + size * script.shift()),
+                font,
+                size: Pixels(run_size),
+                line_height: LineHeight::Relative(Self::LINE_HEIGHT),
+                horizontal_alignment: Horizontal::Left,
+                vertical_alignment: Vertical::Center,
+                shaping: Shaping::Basic,
+            };
+
+            let mut run_paths = vec![];
+            text.draw_with(|path, _| run_paths.push(path));
+
+            // whitespace has no outline to measure
+            let bounds = Self::calculate_bounds(&run_paths);
+            cursor = match bounds.width > 0.0 {
+                true => bounds.x + bounds.width + run_size * Self::RUN_SPACING,
+                false => cursor + run_size * Self::SPACE_WIDTH,
+            };
+            paths.extend(run_paths);
+        }
+
+        let transform = Transform::translation(-cursor / 2.0, 0.0);
+        paths.iter().map(|path| path.transform(&transform)).collect()
+    }
+
+    /// outlines of a line centered on x = 0, with its top at `top`
+    fn line_paths(content: &str, size: f32, font: Font, top: f32) -> Vec<Path> {
+        let text = Text {
+            content: content.to_string(),
+            color: Color::default(),
+            position: Point::new(0.0, top),
+            font,
+            size: Pixels(size),
+            line_height: LineHeight::Relative(Self::LINE_HEIGHT),
+            horizontal_alignment: Horizontal::Center,
+            vertical_alignment: Vertical::Top,
+            shaping: Shaping::Basic,
+        };
+
+        let mut paths = vec![];
+        text.draw_with(|path, _| paths.push(path));
+
+        paths
+    }
+
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// area covered when the middle of the top edge is at a point
+    pub fn bounds_at(&self, position: Point) -> Rectangle {
+        Rectangle::new(
+            Point::new(self.bounds.x + position.x, self.bounds.y + position.y),
+            self.bounds.size(),
+        )
+    }
+
+    /// position that puts the text beside the middle of a line, `gap` away on the side facing up
+    pub fn beside_line(&self, start: Point, end: Point, gap: f32) -> Point {
+        let direction = end - start;
+        let length = direction.x.hypot(direction.y).max(f32::EPSILON);
+        let normal = match direction.x >= 0.0 {
+            true => Vector::new(direction.y, -direction.x) * (1.0 / length),
+            false => Vector::new(-direction.y, direction.x) * (1.0 / length),
+        };
+
+        // half the text's extent across the line, so it clears the line at any angle
+        let extent = normal.x.abs() * self.bounds.width / 2.0 + normal.y.abs() * self.bounds.height / 2.0;
+        let center = start + direction * 0.5 + normal * (gap + extent);
+
+        center - (self.bounds.center() - Point::ORIGIN)
+    }
+
+    pub fn draw(&self, frame: &mut impl Surface, position: Point, color: Color) {
+        let transform = Transform::translation(position.x, position.y);
+
+        for path in &self.paths {
+            frame.fill(&path.transform(&transform), color);
+        }
+    }
+
+    fn calculate_bounds(paths: &[Path]) -> Rectangle {
+        let points = paths.iter().flat_map(|path| {
+            path.raw().iter().flattened(MolCanvas::TEXT_TOLERANCE).filter_map(|event| match event {
+                PathEvent::Begin { at } => Some(at),
+                PathEvent::Line { to, .. } => Some(to),
+                _ => None,
+            })
+        });
+
+        let Some((min, max)) = points.fold(None, |extent: Option<(Point, Point)>, point| {
+            Some(match extent {
+                None => (Point::new(point.x, point.y), Point::new(point.x, point.y)),
+                Some((min, max)) => (
+                    Point::new(min.x.min(point.x), min.y.min(point.y)),
+                    Point::new(max.x.max(point.x), max.y.max(point.y)),
+                ),
+            })
+        }) else {
+            return Rectangle::default();
+        };
+
+        Rectangle::new(min, Size::new(max.x - min.x, max.y - min.y))
+    }
+}
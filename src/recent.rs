@@ -0,0 +1,64 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use iced::widget::svg::Handle;
+
+use crate::{config, thumbnail};
+
+/// files kept in the recent files list
+const MAX_RECENT: usize = 8;
+
+/// a file that was opened, to open again from the inspector
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecentFile {
+    pub name: String,
+    pub path: PathBuf,
+    /// none when the file couldn't be read
+    pub thumbnail: Option<Handle>,
+}
+
+/// the list of recent files, one path to a line, most recent first
+fn file() -> Option<PathBuf> {
+    config::dir().map(|dir| dir.join("recent"))
+}
+
+fn paths() -> Vec<PathBuf> {
+    file()
+        .and_then(|file| std::fs::read_to_string(file).ok())
+        .map(|contents| contents.lines().filter(|line| !line.is_empty()).map(PathBuf::from).collect())
+        .unwrap_or_default()
+}
+
+/// the files opened most recently that are still there, none when nothing has been opened yet
+pub fn list() -> Vec<RecentFile> {
+    paths()
+        .into_iter()
+        .filter(|path| path.is_file())
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?.to_string();
+            let thumbnail = thumbnail::load(&path).ok();
+            Some(RecentFile { name, path, thumbnail })
+        })
+        .collect()
+}
+
+/// moves a file to the front of the list, dropping the oldest past the most kept
+pub fn add(path: &Path) -> Result<()> {
+    let Some(file) = file() else {
+        return Ok(());
+    };
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+    let mut paths = paths();
+    paths.retain(|recent| *recent != path);
+    paths.insert(0, path);
+    paths.truncate(MAX_RECENT);
+
+    if let Some(dir) = file.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("while creating {}", dir.display()))?;
+    }
+    let contents = paths.iter().map(|path| format!("{}\n", path.display())).collect::<String>();
+    std::fs::write(&file, contents)
+        .with_context(|| format!("while writing {}", file.display()))
+        .context("while adding recent file")
+}
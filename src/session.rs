@@ -0,0 +1,651 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use iced::{Point, Rectangle, Size, Vector};
+use thiserror::Error;
+
+use crate::canvas::{ArrowSide, InkColor, LabelFont, OrbitalKind, ShapeKind, ShapeStyle};
+use crate::config;
+use crate::formats::{self, ReactionConditions, Structure};
+use crate::molecule::Metadata;
+use crate::toolbar::Tool;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("invalid session entry \"{0}\"")]
+    InvalidEntry(String),
+    #[error("unknown tool \"{0}\"")]
+    UnknownTool(String),
+    #[error("project has no document")]
+    NoProjectDocument,
+}
+
+/// line a project's entries end on, with the molfile of its document after it
+const PROJECT_DOCUMENT: &str = "document";
+
+/// the document, view, tool and selection the app was closed with, so it opens where it was left
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub structure: Structure,
+    /// where the structure's atoms were on the canvas, which it's fitted to again since reading
+    /// the molfile scales it to the average bond length
+    pub bounds: Rectangle,
+    pub bond_length: f32,
+    pub label_font: LabelFont,
+    pub translation: Vector,
+    pub scaling: f32,
+    pub tool: Tool,
+    /// none in sessions saved before layers were kept, which open with the default layers
+    pub layers: Vec<SessionLayer>,
+    pub active_layer: usize,
+    pub selection: Vec<SessionSelection>,
+    pub compounds: Vec<SessionCompound>,
+    pub bends: Vec<SessionBend>,
+    /// bonds marked as retrosynthetic disconnections, by the indices of their atoms
+    pub disconnections: Vec<(usize, usize)>,
+    pub inks: Vec<SessionInk>,
+    pub shapes: Vec<SessionShape>,
+    pub orbitals: Vec<SessionOrbital>,
+    pub annotations: Vec<SessionAnnotation>,
+    pub versions: Vec<SessionVersion>,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            structure: Structure::default(),
+            bounds: Rectangle::default(),
+            bond_length: crate::canvas::MolCanvas::BOND_LENGTH,
+            label_font: LabelFont::default(),
+            translation: Vector::ZERO,
+            scaling: 1.0,
+            tool: Tool::default(),
+            layers: vec![],
+            active_layer: 0,
+            selection: vec![],
+            compounds: vec![],
+            bends: vec![],
+            disconnections: vec![],
+            inks: vec![],
+            shapes: vec![],
+            orbitals: vec![],
+            annotations: vec![],
+            versions: vec![],
+        }
+    }
+}
+
+/// a named copy of the document kept in the timeline, its items on the document's layers
+#[derive(Debug, Clone, Default)]
+pub struct SessionVersion {
+    pub name: String,
+    pub session: Session,
+    /// the index of each of the version's atoms that's still in the document, with the index of
+    /// the document's atom, so the two are compared atom by atom again
+    pub shared_atoms: Vec<(usize, usize)>,
+}
+
+/// a layer with whether it's shown and locked, which items name by its index
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionLayer {
+    pub name: String,
+    pub visible: bool,
+    pub locked: bool,
+}
+
+/// a selected item by the indices of its atoms in the session's structure, since ids aren't kept
+/// when the structure is read back in, items with atoms that aren't there are left out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SessionSelection {
+    /// the molecule an atom is in
+    Molecule(usize),
+    Atom(usize),
+    Bond(usize, usize),
+}
+
+/// the name, metadata, layer and lock of a molecule, found again by the index of one of its atoms
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionCompound {
+    pub atom: usize,
+    pub name: Option<String>,
+    pub metadata: Metadata,
+    pub layer: usize,
+    pub locked: bool,
+}
+
+/// the waypoints of a bent bond or the curvature of a curved one, found again by the indices of
+/// its atoms
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionBend {
+    pub start: usize,
+    pub end: usize,
+    pub waypoints: Vec<Vector>,
+    pub curvature: f32,
+}
+
+/// a freehand stroke, kept in canvas coordinates as it doesn't belong to any atom
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionInk {
+    pub width: f32,
+    pub color: InkColor,
+    pub points: Vec<Point>,
+    pub layer: usize,
+}
+
+/// a box, ellipse, line or arrow, kept in canvas coordinates like ink
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionShape {
+    pub kind: ShapeKind,
+    pub start: Point,
+    pub end: Point,
+    pub style: ShapeStyle,
+    pub conditions: ReactionConditions,
+    /// text written over and under an arrow
+    pub above: String,
+    pub below: String,
+    pub layer: usize,
+}
+
+/// an orbital found again by the index of its atom, and of its partner's for a π overlap
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionOrbital {
+    pub kind: OrbitalKind,
+    pub atom: usize,
+    pub partner: Option<usize>,
+    pub angle: f32,
+    pub size: f32,
+}
+
+/// free text with the middle of the top of its first line, kept in canvas coordinates like ink
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionAnnotation {
+    pub text: String,
+    pub position: Point,
+    pub layer: usize,
+}
+
+impl SessionCompound {
+    /// the compound kept for an atom, added when there isn't one yet
+    fn for_atom(compounds: &mut Vec<SessionCompound>, atom: usize) -> &mut SessionCompound {
+        let index = match compounds.iter().position(|compound| compound.atom == atom) {
+            Some(index) => index,
+            None => {
+                compounds.push(SessionCompound {
+                    atom,
+                    ..Default::default()
+                });
+                compounds.len() - 1
+            }
+        };
+
+        &mut compounds[index]
+    }
+}
+
+fn dir() -> Option<PathBuf> {
+    config::dir().map(|dir| dir.join("session"))
+}
+
+/// the session saved when the app was last closed, none when there isn't one
+///
+/// the document is kept in `session.mol` and everything else one entry to a line in
+/// `session.conf`, atoms, bonds, ink, shapes, annotations and layers named by their index:
+/// - the view by `bounds`, `bond_length`, `translation` and `scaling`, e.g. `scaling 1.5`
+/// - the tool by its name, e.g. `tool atom/Cl`, and the label font, e.g. `label_font Times New Roman`
+/// - the selection by what's selected, e.g. `select molecule 0` or `select bond 3 4`
+/// - compound text after an atom of the compound, e.g. `name 0 Ethanol` or `tag 0 solvent`, as
+///   are `cas`, `notes` and `source`, and locked compounds by an atom, e.g. `lock 0`
+/// - bends and curves after the atoms of their bond, e.g. `bend 3 4 0.5 0.25` or `curve 3 4 0.2`
+/// - disconnected bonds by their atoms, e.g. `disconnect 3 4`
+/// - ink by its width, colour and points, e.g. `ink 1.5 red 10 20 12 24`
+/// - shapes by their kind, width, colour, whether they're filled and their two points, e.g.
+///   `shape box 1.5 text 0 0 0 40 30` or `shape clipart/tlc-plate 1.5 text 0 0 0 45 75`
+/// - the conditions of and text on arrows after the arrow, e.g. `yield 0 85` or `above 0 H_2, Pd/C`
+/// - orbitals by their kind, angle, size and atoms, e.g. `orbital pi -1.57 18 3 4`
+/// - annotations by their position and text, e.g. `annotation 10 20 *t*-Bu ester`
+/// - layers by whether they're shown and locked and their name, e.g. `layer 1 0 Structures`,
+///   with the active one, e.g. `active_layer 1`, and what's on each after it, e.g. `on_layer 0 1`
+///   for a compound, or `ink_layer`, `shape_layer` and `annotation_layer`
+/// - timeline versions by their name, e.g. `version Before workup`, each with its document in
+///   `version-0.mol` and so on
+/// - a version's own entries after the version, e.g. `in_version 0 bond_length 30`, and the atoms
+///   it shares with the document by their indices in both, e.g. `version_atom 0 3 5`
+pub fn load() -> Result<Option<Session>> {
+    let Some(dir) = dir() else {
+        return Ok(None);
+    };
+
+    let path = dir.join("session.conf");
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => {
+            return Err(error)
+                .with_context(|| format!("while reading {}", path.display()))
+                .context("while loading session")
+        }
+    };
+
+    let structure = formats::read_file(&dir.join("session.mol"))
+        .context("while loading session")?
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+
+    let mut session = Session {
+        structure,
+        ..Default::default()
+    };
+
+    for (index, line) in text.lines().enumerate() {
+        parse_entry(&mut session, line.trim())
+            .with_context(|| format!("while reading line {} of {}", index + 1, path.display()))
+            .context("while loading session")?;
+    }
+
+    for (index, version) in session.versions.iter_mut().enumerate() {
+        version.session.structure = formats::read_file(&dir.join(format!("version-{}.mol", index)))
+            .context("while loading session")?
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+    }
+
+    Ok(Some(session))
+}
+
+fn parse_entry(session: &mut Session, line: &str) -> Result<(), Error> {
+    let invalid = || Error::InvalidEntry(line.to_string());
+    let numbers = |count: usize, values: &[&str]| -> Result<Vec<f32>, Error> {
+        let numbers = values.iter().map(|value| value.parse::<f32>()).collect::<Result<Vec<_>, _>>();
+        numbers.ok().filter(|numbers| numbers.len() == count).ok_or_else(invalid)
+    };
+    let indices = |count: usize, values: &[&str]| -> Result<Vec<usize>, Error> {
+        let indices = values.iter().map(|value| value.parse::<usize>()).collect::<Result<Vec<_>, _>>();
+        indices.ok().filter(|indices| indices.len() == count).ok_or_else(invalid)
+    };
+
+    // text runs to the end of the line, so it's split off before the rest is read as tokens
+    let text_entry = line.split_once(' ').and_then(|(key, rest)| {
+        let (atom, text) = rest.split_once(' ')?;
+        Some((key, atom.parse::<usize>().ok()?, unescape(text)))
+    });
+    if let Some((key @ ("name" | "cas" | "tag" | "notes" | "source"), atom, text)) = text_entry {
+        let compound = SessionCompound::for_atom(&mut session.compounds, atom);
+        match key {
+            "name" => compound.name = Some(text),
+            "cas" => compound.metadata.set_cas(text),
+            "tag" => {
+                let tags = compound.metadata.tags().iter().cloned().chain([text]).collect::<Vec<_>>();
+                compound.metadata.set_tags(tags);
+            }
+            "notes" => compound.metadata.set_notes(text),
+            _ => compound.metadata.set_source(text),
+        }
+        return Ok(());
+    }
+    // reaction conditions and text written on arrows name the shape they're on by its index
+    if let Some((key, shape, text)) = &text_entry {
+        if ReactionConditions::FIELDS.contains(key) {
+            let shape = session.shapes.get_mut(*shape).ok_or_else(invalid)?;
+            shape.conditions.set_field(key, text);
+            return Ok(());
+        }
+        if let Some(side) = ArrowSide::ALL.into_iter().find(|side| side.name() == *key) {
+            let shape = session.shapes.get_mut(*shape).ok_or_else(invalid)?;
+            match side {
+                ArrowSide::Above => shape.above = text.clone(),
+                ArrowSide::Below => shape.below = text.clone(),
+            }
+            return Ok(());
+        }
+    }
+
+    // a version's entries are read into it like a session of its own
+    if let Some(entry) = line.strip_prefix("in_version ") {
+        let (index, entry) = entry.split_once(' ').ok_or_else(invalid)?;
+        let version = index.parse::<usize>().ok().and_then(|index| session.versions.get_mut(index)).ok_or_else(invalid)?;
+        return parse_entry(&mut version.session, entry);
+    }
+
+    // font, layer and version names and text can have spaces in them
+    if let Some(name) = line.strip_prefix("label_font ") {
+        session.label_font = LabelFont::ALL.into_iter().find(|font| font.to_string() == name).ok_or_else(invalid)?;
+        return Ok(());
+    }
+    if let Some(name) = line.strip_prefix("version ") {
+        session.versions.push(SessionVersion {
+            name: unescape(name),
+            ..Default::default()
+        });
+        return Ok(());
+    }
+    if let Some(annotation) = line.strip_prefix("annotation ") {
+        let [x, y, text] = annotation.splitn(3, ' ').collect::<Vec<_>>()[..] else {
+            return Err(invalid());
+        };
+        let position = numbers(2, &[x, y])?;
+        session.annotations.push(SessionAnnotation {
+            text: unescape(text),
+            position: Point::new(position[0], position[1]),
+            layer: 0,
+        });
+        return Ok(());
+    }
+    if let Some(layer) = line.strip_prefix("layer ") {
+        let [visible, locked, name] = layer.splitn(3, ' ').collect::<Vec<_>>()[..] else {
+            return Err(invalid());
+        };
+        let flags = indices(2, &[visible, locked])?;
+        session.layers.push(SessionLayer {
+            name: unescape(name),
+            visible: flags[0] != 0,
+            locked: flags[1] != 0,
+        });
+        return Ok(());
+    }
+
+    let tokens = line.split_whitespace().collect::<Vec<_>>();
+    match tokens.as_slice() {
+        [] => {}
+        [comment, ..] if comment.starts_with('#') => {}
+        ["bounds", values @ ..] => {
+            let values = numbers(4, values)?;
+            session.bounds = Rectangle::new(Point::new(values[0], values[1]), Size::new(values[2], values[3]));
+        }
+        ["bond_length", values @ ..] => session.bond_length = numbers(1, values)?[0],
+        ["translation", values @ ..] => {
+            let values = numbers(2, values)?;
+            session.translation = Vector::new(values[0], values[1]);
+        }
+        ["scaling", values @ ..] => session.scaling = numbers(1, values)?[0],
+        ["tool", name] => session.tool = Tool::from_name(name).ok_or(Error::UnknownTool(name.to_string()))?,
+        ["active_layer", values @ ..] => session.active_layer = indices(1, values)?[0],
+        ["on_layer", values @ ..] => {
+            let values = indices(2, values)?;
+            SessionCompound::for_atom(&mut session.compounds, values[0]).layer = values[1];
+        }
+        ["lock", values @ ..] => SessionCompound::for_atom(&mut session.compounds, indices(1, values)?[0]).locked = true,
+        ["ink_layer", values @ ..] => {
+            let values = indices(2, values)?;
+            session.inks.get_mut(values[0]).ok_or_else(invalid)?.layer = values[1];
+        }
+        ["annotation_layer", values @ ..] => {
+            let values = indices(2, values)?;
+            session.annotations.get_mut(values[0]).ok_or_else(invalid)?.layer = values[1];
+        }
+        ["version_atom", values @ ..] => {
+            let values = indices(3, values)?;
+            let version = session.versions.get_mut(values[0]).ok_or_else(invalid)?;
+            version.shared_atoms.push((values[1], values[2]));
+        }
+        ["shape_layer", values @ ..] => {
+            let values = indices(2, values)?;
+            session.shapes.get_mut(values[0]).ok_or_else(invalid)?.layer = values[1];
+        }
+        ["select", "molecule", values @ ..] => {
+            session.selection.push(SessionSelection::Molecule(indices(1, values)?[0]));
+        }
+        ["select", "atom", values @ ..] => session.selection.push(SessionSelection::Atom(indices(1, values)?[0])),
+        ["select", "bond", values @ ..] => {
+            let values = indices(2, values)?;
+            session.selection.push(SessionSelection::Bond(values[0], values[1]));
+        }
+        ["bend", start, end, values @ ..] if !values.is_empty() && values.len() % 2 == 0 => {
+            let atoms = indices(2, &[start, end])?;
+            let values = numbers(values.len(), values)?;
+            session.bends.push(SessionBend {
+                start: atoms[0],
+                end: atoms[1],
+                waypoints: values.chunks(2).map(|pair| Vector::new(pair[0], pair[1])).collect(),
+                ..Default::default()
+            });
+        }
+        ["curve", start, end, values @ ..] => {
+            let atoms = indices(2, &[start, end])?;
+            session.bends.push(SessionBend {
+                start: atoms[0],
+                end: atoms[1],
+                curvature: numbers(1, values)?[0],
+                ..Default::default()
+            });
+        }
+        ["disconnect", values @ ..] => {
+            let values = indices(2, values)?;
+            session.disconnections.push((values[0], values[1]));
+        }
+        ["ink", width, color, values @ ..] if values.len() >= 4 && values.len() % 2 == 0 => {
+            let values = numbers(values.len(), values)?;
+            session.inks.push(SessionInk {
+                width: numbers(1, &[width])?[0],
+                color: ink_color(color).ok_or_else(invalid)?,
+                points: values.chunks(2).map(|pair| Point::new(pair[0], pair[1])).collect(),
+                layer: 0,
+            });
+        }
+        ["orbital", kind, angle, size, atoms @ ..] if matches!(atoms.len(), 1 | 2) => {
+            let atoms = indices(atoms.len(), atoms)?;
+            session.orbitals.push(SessionOrbital {
+                kind: OrbitalKind::from_name(kind).ok_or_else(invalid)?,
+                atom: atoms[0],
+                partner: atoms.get(1).copied(),
+                angle: numbers(1, &[angle])?[0],
+                size: numbers(1, &[size])?[0],
+            });
+        }
+        ["shape", kind, width, color, filled, values @ ..] => {
+            let values = numbers(4, values)?;
+            session.shapes.push(SessionShape {
+                kind: ShapeKind::from_name(kind).ok_or_else(invalid)?,
+                start: Point::new(values[0], values[1]),
+                end: Point::new(values[2], values[3]),
+                style: ShapeStyle {
+                    width: numbers(1, &[width])?[0],
+                    color: ink_color(color).ok_or_else(invalid)?,
+                    filled: indices(1, &[filled])?[0] != 0,
+                },
+                conditions: ReactionConditions::default(),
+                above: String::new(),
+                below: String::new(),
+                layer: 0,
+            });
+        }
+        _ => return Err(invalid()),
+    }
+
+    Ok(())
+}
+
+/// keeps the session to open with next time, replacing the last one
+pub fn save(session: &Session) -> Result<()> {
+    let Some(dir) = dir() else {
+        return Ok(());
+    };
+    std::fs::create_dir_all(&dir).with_context(|| format!("while creating {}", dir.display()))?;
+
+    let document = dir.join("session.mol");
+    std::fs::write(&document, formats::write_molfile(&session.structure, session.bond_length))
+        .with_context(|| format!("while writing {}", document.display()))?;
+
+    let mut text = entries(session);
+    for (index, version) in session.versions.iter().enumerate() {
+        let document = dir.join(format!("version-{}.mol", index));
+        std::fs::write(&document, formats::write_molfile(&version.session.structure, version.session.bond_length))
+            .with_context(|| format!("while writing {}", document.display()))?;
+
+        text.push_str(&format!("version {}\n", escape(&version.name)));
+        for entry in entries(&version.session).lines() {
+            text.push_str(&format!("in_version {} {}\n", index, entry));
+        }
+        for (atom, document_atom) in &version.shared_atoms {
+            text.push_str(&format!("version_atom {} {} {}\n", index, atom, document_atom));
+        }
+    }
+
+    let path = dir.join("session.conf");
+    std::fs::write(&path, text).with_context(|| format!("while writing {}", path.display()))
+}
+
+/// a document as one text, its entries like those of `session.conf` followed by its molfile, for
+/// keeping the whole of it in a file of its own such as an exported svg, leaving out its versions
+pub fn write_project(session: &Session) -> String {
+    format!(
+        "{}{}\n{}",
+        entries(session),
+        PROJECT_DOCUMENT,
+        formats::write_molfile(&session.structure, session.bond_length)
+    )
+}
+
+/// the document kept in an svg exported of a whole drawing, none for other files
+pub fn read_project(path: &Path) -> Result<Option<Session>> {
+    if !path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("svg")) {
+        return Ok(None);
+    }
+
+    let svg = std::fs::read_to_string(path).with_context(|| format!("while reading {}", path.display()))?;
+    let Some(project) = formats::embedded_project(&svg) else {
+        return Ok(None);
+    };
+    let (text, molfile) = project
+        .split_once(&format!("\n{}\n", PROJECT_DOCUMENT))
+        .ok_or(Error::NoProjectDocument)
+        .context("while reading project")?;
+
+    let mut session = Session {
+        structure: formats::parse_molfile(molfile).context("while reading project")?,
+        ..Default::default()
+    };
+    for (index, line) in text.lines().enumerate() {
+        parse_entry(&mut session, line.trim())
+            .with_context(|| format!("while reading line {} of project", index + 1))
+            .context("while reading project")?;
+    }
+
+    Ok(Some(session))
+}
+
+/// a line for each part of a session kept in `session.conf`, leaving out its versions
+fn entries(session: &Session) -> String {
+    let mut text = format!(
+        "bounds {} {} {} {}\nbond_length {}\ntranslation {} {}\nscaling {}\n",
+        session.bounds.x,
+        session.bounds.y,
+        session.bounds.width,
+        session.bounds.height,
+        session.bond_length,
+        session.translation.x,
+        session.translation.y,
+        session.scaling,
+    );
+    text.push_str(&format!("label_font {}\n", session.label_font));
+    if let Some(name) = session.tool.name() {
+        text.push_str(&format!("tool {}\n", name));
+    }
+    for layer in &session.layers {
+        text.push_str(&format!("layer {} {} {}\n", layer.visible as u8, layer.locked as u8, escape(&layer.name)));
+    }
+    text.push_str(&format!("active_layer {}\n", session.active_layer));
+    for selected in &session.selection {
+        let line = match selected {
+            SessionSelection::Molecule(atom) => format!("select molecule {}\n", atom),
+            SessionSelection::Atom(atom) => format!("select atom {}\n", atom),
+            SessionSelection::Bond(start, end) => format!("select bond {} {}\n", start, end),
+        };
+        text.push_str(&line);
+    }
+    for bend in &session.bends {
+        let line = match bend.curvature != 0.0 {
+            true => format!("curve {} {} {}\n", bend.start, bend.end, bend.curvature),
+            false => {
+                let waypoints = bend.waypoints.iter().map(|waypoint| format!(" {} {}", waypoint.x, waypoint.y)).collect::<String>();
+                format!("bend {} {}{}\n", bend.start, bend.end, waypoints)
+            }
+        };
+        text.push_str(&line);
+    }
+    for (start, end) in &session.disconnections {
+        text.push_str(&format!("disconnect {} {}\n", start, end));
+    }
+    for (index, ink) in session.inks.iter().enumerate() {
+        let points = ink.points.iter().map(|point| format!(" {} {}", point.x, point.y)).collect::<String>();
+        text.push_str(&format!("ink {} {}{}\n", ink.width, ink.color.to_string().to_lowercase(), points));
+        text.push_str(&format!("ink_layer {} {}\n", index, ink.layer));
+    }
+    for (index, shape) in session.shapes.iter().enumerate() {
+        text.push_str(&format!(
+            "shape {} {} {} {} {} {} {} {}\n",
+            shape.kind.name(),
+            shape.style.width,
+            shape.style.color.to_string().to_lowercase(),
+            shape.style.filled as u8,
+            shape.start.x,
+            shape.start.y,
+            shape.end.x,
+            shape.end.y,
+        ));
+        text.push_str(&format!("shape_layer {} {}\n", index, shape.layer));
+        for (key, value) in shape.conditions.fields() {
+            text.push_str(&format!("{} {} {}\n", key, index, escape(&value)));
+        }
+        for (side, value) in [(ArrowSide::Above, &shape.above), (ArrowSide::Below, &shape.below)] {
+            if !value.is_empty() {
+                text.push_str(&format!("{} {} {}\n", side.name(), index, escape(value)));
+            }
+        }
+    }
+    for orbital in &session.orbitals {
+        let partner = orbital.partner.map(|partner| format!(" {}", partner)).unwrap_or_default();
+        text.push_str(&format!("orbital {} {} {} {}{}\n", orbital.kind.name(), orbital.angle, orbital.size, orbital.atom, partner));
+    }
+    for (index, annotation) in session.annotations.iter().enumerate() {
+        text.push_str(&format!("annotation {} {} {}\n", annotation.position.x, annotation.position.y, escape(&annotation.text)));
+        text.push_str(&format!("annotation_layer {} {}\n", index, annotation.layer));
+    }
+    for compound in &session.compounds {
+        let metadata = &compound.metadata;
+        let entries = [("name", compound.name.as_deref()), ("cas", metadata.cas())]
+            .into_iter()
+            .chain(metadata.tags().iter().map(|tag| ("tag", Some(tag.as_str()))))
+            .chain([("notes", metadata.notes()), ("source", metadata.source())]);
+
+        for (key, value) in entries {
+            if let Some(value) = value {
+                text.push_str(&format!("{} {} {}\n", key, compound.atom, escape(value)));
+            }
+        }
+        text.push_str(&format!("on_layer {} {}\n", compound.atom, compound.layer));
+        if compound.locked {
+            text.push_str(&format!("lock {}\n", compound.atom));
+        }
+    }
+
+    text
+}
+
+/// an ink colour by its name, ignoring case
+fn ink_color(name: &str) -> Option<InkColor> {
+    InkColor::ALL.into_iter().find(|color| color.to_string().eq_ignore_ascii_case(name))
+}
+
+/// keeps text on one line
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => unescaped.push('\n'),
+                Some(escaped) => unescaped.push(escaped),
+                None => {}
+            },
+            c => unescaped.push(c),
+        }
+    }
+
+    unescaped
+}
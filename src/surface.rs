@@ -0,0 +1,18 @@
+use iced::widget::canvas::{Frame, Path, Stroke};
+use iced::Color;
+
+/// something paths can be drawn onto, the canvas or a file being exported
+pub trait Surface {
+    fn fill(&mut self, path: &Path, color: Color);
+    fn stroke(&mut self, path: &Path, stroke: Stroke<'_>);
+}
+
+impl Surface for Frame {
+    fn fill(&mut self, path: &Path, color: Color) {
+        Frame::fill(self, path, color);
+    }
+
+    fn stroke(&mut self, path: &Path, stroke: Stroke<'_>) {
+        Frame::stroke(self, path, stroke);
+    }
+}
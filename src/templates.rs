@@ -0,0 +1,62 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use iced::widget::svg::Handle;
+use thiserror::Error;
+
+use crate::{config, thumbnail};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("no config directory to keep templates in, set HOME or XDG_CONFIG_HOME")]
+    NoConfigDirectory,
+    #[error("template names can't be empty or contain slashes, found \"{0}\"")]
+    InvalidName(String),
+}
+
+/// a whole document saved to start new drawings from, kept as a png with its structure embedded
+/// so the file is its own preview
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    pub name: String,
+    pub path: PathBuf,
+    /// none when the file couldn't be read
+    pub thumbnail: Option<Handle>,
+}
+
+fn dir() -> Option<PathBuf> {
+    config::dir().map(|dir| dir.join("templates"))
+}
+
+/// the saved templates by name, none when the library hasn't been made yet
+pub fn list() -> Vec<Template> {
+    let Some(entries) = dir().and_then(|dir| std::fs::read_dir(dir).ok()) else {
+        return vec![];
+    };
+
+    let mut templates = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "png"))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?.to_string();
+            let thumbnail = thumbnail::load(&path).ok();
+            Some(Template { name, path, thumbnail })
+        })
+        .collect::<Vec<_>>();
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+
+    templates
+}
+
+/// where a template of the name is saved, making the library if needed
+pub fn path_for(name: &str) -> Result<PathBuf> {
+    let name = name.trim();
+    if name.is_empty() || name.contains(['/', '\\']) {
+        return Err(Error::InvalidName(name.to_string())).context("while naming template");
+    }
+
+    let dir = dir().ok_or(Error::NoConfigDirectory).context("while naming template")?;
+    std::fs::create_dir_all(&dir).with_context(|| format!("while creating {}", dir.display()))?;
+
+    Ok(dir.join(format!("{}.png", name)))
+}
@@ -0,0 +1,44 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use iced::widget::svg::Handle;
+
+use crate::canvas::MolCanvas;
+use crate::formats;
+
+/// pixels across the longest side of a thumbnail
+pub const SIZE: u32 = 96;
+
+/// a small preview of an svg or png file, or of the structures or reactions in a structure file,
+/// rendered offscreen apart from the canvas
+pub fn load(path: &Path) -> Result<Handle> {
+    if formats::is_image_file(path) {
+        return Ok(handle(&formats::thumbnail(path, SIZE)?));
+    }
+
+    let svg = match formats::is_reaction_file(path) {
+        true => MolCanvas::reactions_svg(formats::read_reactions(path)?),
+        false => MolCanvas::structures_svg(formats::read_file(path)?),
+    };
+
+    from_svg(&svg.with_context(|| format!("while drawing thumbnail of {}", path.display()))?)
+}
+
+/// a small preview of a drawing
+pub fn from_svg(svg: &str) -> Result<Handle> {
+    Ok(handle(&formats::svg_thumbnail(svg, SIZE)?))
+}
+
+/// without iced's image widget the png is shown through the svg widget, fitted and centred in a
+/// square so thumbnails line up
+fn handle(png: &[u8]) -> Handle {
+    let svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}"><image width="{size}" height="{size}" href="data:image/png;base64,{data}"/></svg>"#,
+        size = SIZE,
+        data = STANDARD.encode(png),
+    );
+
+    Handle::from_memory(svg.into_bytes())
+}
@@ -1,13 +1,21 @@
-use iced::widget::svg::Handle;
-use iced::widget::{button, center, column, svg, Button};
+use iced::widget::{button, center, column, container, horizontal_rule, row, svg, text, tooltip, Button, Space};
 use iced::{Border, Element, Length, Padding, Theme};
 
-use crate::canvas::{HoverSelection, MouseInteraction, Selection};
-use crate::molecule::BondType;
+use crate::canvas::{BackgroundDrag, HoverSelection, MouseInteraction, OrbitalKind, Selection, ShapeDrag, ShapeKind};
+use crate::chemistry;
+use crate::config;
+use crate::icons::IconTheme;
+use crate::molecule::{AnnotationId, BondEnd, BondId, BondType, MoleculeId, OrbitalId, ShapeId};
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone)]
 pub struct Toolbar {
     selected: Tool,
+    icons: IconTheme,
+    /// symbols of the atoms given their own buttons
+    quick_atoms: Vec<&'static str>,
+    /// atom and bond tools, most recently used first
+    recent: Vec<Tool>,
+    periodic_table_open: bool,
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
@@ -17,12 +25,142 @@ pub enum Tool {
     Pan,
     Erase,
     Bond(BondType),
+    /// links two atoms with a dashed line, without bonding them
+    Interaction,
+    /// draws freehand strokes over the structures
+    Ink,
+    /// drags out boxes, ellipses, lines and arrows
+    Shape(ShapeKind),
+    /// puts orbitals on atoms, or π overlaps across bonds, and turns them by their handles
+    Orbital(OrbitalKind),
+    /// numbers corresponding atoms on either side of a reaction arrow
+    AtomMap,
     Rename,
-    C,
+    /// writes free text, or edits the text tapped or written over or under the arrow tapped
+    Text,
+    /// places atoms of an element by its symbol
+    Atom(&'static str),
 }
 
+/// the tools in the order they're shown, with the names of their icons which also name them in
+/// the saved session, atom tools following them
+const TOOLS: [(&str, Tool); 26] = [
+    ("cursor-pointer", Tool::Cursor),
+    ("square-dashed", Tool::Select),
+    ("drag-hand-gesture", Tool::Pan),
+    ("erase-solid", Tool::Erase),
+    ("single", Tool::Bond(BondType::Normal(1))),
+    ("double", Tool::Bond(BondType::Normal(2))),
+    ("triple", Tool::Bond(BondType::Normal(3))),
+    ("quadruple", Tool::Bond(BondType::Normal(4))),
+    ("wedge", Tool::Bond(BondType::Wedge)),
+    ("dash", Tool::Bond(BondType::Dash)),
+    ("bold", Tool::Bond(BondType::Bold)),
+    ("wavy", Tool::Bond(BondType::Wavy)),
+    ("dative", Tool::Bond(BondType::Dative)),
+    ("hydrogen-bond", Tool::Bond(BondType::Hydrogen)),
+    ("interaction", Tool::Interaction),
+    ("ink", Tool::Ink),
+    ("rectangle", Tool::Shape(ShapeKind::Rectangle)),
+    ("ellipse", Tool::Shape(ShapeKind::Ellipse)),
+    ("line", Tool::Shape(ShapeKind::Line)),
+    ("arrow", Tool::Shape(ShapeKind::Arrow)),
+    ("p-orbital", Tool::Orbital(OrbitalKind::P)),
+    ("sp3-orbital", Tool::Orbital(OrbitalKind::Sp3)),
+    ("pi-orbital", Tool::Orbital(OrbitalKind::Pi)),
+    ("atom-map", Tool::AtomMap),
+    ("input-field", Tool::Rename),
+    ("text", Tool::Text),
+];
+
+/// atoms given their own buttons when the user hasn't picked any
+const DEFAULT_QUICK_ATOMS: [&str; 9] = ["C", "N", "O", "S", "P", "F", "Cl", "Br", "I"];
+
+/// the file in the config directory listing the atoms given their own buttons
+const QUICK_ATOMS_FILE: &str = "quick-atoms.conf";
+
+/// atom and bond tools kept in the recently used list
+const MAX_RECENT: usize = 4;
+
+/// periodic table cells are this many pixels square
+const CELL_SIZE: f32 = 26.0;
+
+/// keys that pick a tool when nothing else takes them, also shown in the toolbar's tooltips
+const SHORTCUTS: [(char, Tool); 16] = [
+    ('v', Tool::Cursor),
+    ('m', Tool::Select),
+    ('h', Tool::Pan),
+    ('e', Tool::Erase),
+    ('1', Tool::Bond(BondType::Normal(1))),
+    ('2', Tool::Bond(BondType::Normal(2))),
+    ('3', Tool::Bond(BondType::Normal(3))),
+    ('w', Tool::Bond(BondType::Wedge)),
+    ('d', Tool::Bond(BondType::Dash)),
+    ('i', Tool::Interaction),
+    ('p', Tool::Ink),
+    ('r', Tool::Rename),
+    ('t', Tool::Text),
+    ('c', Tool::Atom("C")),
+    ('n', Tool::Atom("N")),
+    ('o', Tool::Atom("O")),
+];
+
 impl Tool {
-    pub fn action(&self, interaction: MouseInteraction, selection: &Selection, hover_selection: &HoverSelection) -> ToolAction {
+    /// the tool a key picks, ignoring case
+    pub fn from_shortcut(key: &str) -> Option<Self> {
+        let mut chars = key.chars();
+        let key = match (chars.next(), chars.next()) {
+            (Some(key), None) => key.to_ascii_lowercase(),
+            _ => return None,
+        };
+
+        SHORTCUTS.iter().find(|(shortcut, _)| *shortcut == key).map(|(_, tool)| *tool)
+    }
+
+    pub fn shortcut(&self) -> Option<char> {
+        SHORTCUTS.iter().find(|(_, tool)| tool == self).map(|(shortcut, _)| *shortcut)
+    }
+
+    /// what the tool is called in its tooltip
+    pub fn title(&self) -> String {
+        match self {
+            Tool::Cursor => "Cursor".to_string(),
+            Tool::Select => "Select".to_string(),
+            Tool::Pan => "Pan".to_string(),
+            Tool::Erase => "Erase".to_string(),
+            Tool::Bond(BondType::Hydrogen) => "Hydrogen bond".to_string(),
+            Tool::Bond(bond_type) => format!("{} bond", bond_type),
+            Tool::Interaction => "Interaction".to_string(),
+            Tool::Ink => "Ink".to_string(),
+            Tool::Shape(kind) => kind.to_string(),
+            Tool::Orbital(kind) => kind.to_string(),
+            Tool::AtomMap => "Atom map".to_string(),
+            Tool::Rename => "Rename".to_string(),
+            Tool::Text => "Text".to_string(),
+            Tool::Atom(symbol) => chemistry::Element::from_symbol(symbol).map_or(symbol.to_string(), |element| element.name.to_string()),
+        }
+    }
+
+    /// names atom tools by their symbol, e.g. "atom/Cl"
+    pub fn name(&self) -> Option<String> {
+        match self {
+            Tool::Atom(symbol) => Some(format!("atom/{}", symbol)),
+            _ => TOOLS.iter().find(|(_, tool)| tool == self).map(|(name, _)| name.to_string()),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.strip_prefix("atom/") {
+            Some(symbol) => chemistry::Element::from_symbol(symbol).map(|element| Tool::Atom(element.symbol)),
+            // sessions from before atom tools only had carbon's
+            None if name == "letters/c" => Some(Tool::Atom("C")),
+            None => TOOLS.iter().find(|(tool_name, _)| *tool_name == name).map(|(_, tool)| *tool),
+        }
+    }
+
+    /// `over_item` is whether an atom or bond is under the cursor, rather than only the bounds of a
+    /// molecule
+    pub fn action(&self, interaction: MouseInteraction, selection: &Selection, hover_selection: &HoverSelection, over_item: bool) -> ToolAction {
         if matches!(interaction, MouseInteraction::MouseDragged) { return ToolAction::CursorDragged }
 
         match self {
@@ -32,19 +170,20 @@ impl Tool {
                         true => ToolAction::StartPan,
                         false => ToolAction::ClickSelect,
                     }
-                    MouseInteraction::MouseTapped => ToolAction::ClickSelect,
+                    MouseInteraction::MouseTapped => ToolAction::CycleSelect,
                     _ => ToolAction::None
                 }
             }
             Tool::Select => {
                 match interaction {
                     // MouseInteraction::MouseTapped => ToolAction::ClickSelect,
-                    MouseInteraction::MouseDown => match selection.contains(hover_selection) {
+                    // empty space inside a selected molecule's bounds starts a rectangle too
+                    MouseInteraction::MouseDown => match over_item && selection.contains(hover_selection) {
                         true => ToolAction::StartMove,
                         false => ToolAction::DragSelectStart,
                     }
                     MouseInteraction::MouseReleased => ToolAction::DragSelectFinish,
-                    MouseInteraction::MouseTapped => ToolAction::ClickSelect,
+                    MouseInteraction::MouseTapped => ToolAction::CycleSelect,
                     _ => ToolAction::None
                 }
             }
@@ -67,16 +206,59 @@ impl Tool {
                     _ => ToolAction::None
                 }
             }
+            Tool::Interaction => {
+                match interaction {
+                    MouseInteraction::MouseDown => ToolAction::InteractionStart,
+                    MouseInteraction::MouseReleased | MouseInteraction::MouseTapped => ToolAction::InteractionFinish,
+                    _ => ToolAction::None
+                }
+            }
+            Tool::Ink => {
+                match interaction {
+                    MouseInteraction::MouseDown => ToolAction::InkStart,
+                    MouseInteraction::MouseReleased | MouseInteraction::MouseTapped => ToolAction::InkFinish,
+                    _ => ToolAction::None
+                }
+            }
+            Tool::Shape(kind) => {
+                match interaction {
+                    MouseInteraction::MouseDown => ToolAction::ShapeStart(*kind),
+                    MouseInteraction::MouseReleased | MouseInteraction::MouseTapped => ToolAction::ShapeFinish,
+                    _ => ToolAction::None
+                }
+            }
+            Tool::Orbital(kind) => {
+                match interaction {
+                    MouseInteraction::MouseTapped => ToolAction::OrbitalPlace(*kind),
+                    MouseInteraction::MouseDown => ToolAction::StartPan,
+                    _ => ToolAction::None
+                }
+            }
+            Tool::AtomMap => {
+                match interaction {
+                    MouseInteraction::MouseDown => ToolAction::MapStart,
+                    MouseInteraction::MouseReleased | MouseInteraction::MouseTapped => ToolAction::MapFinish,
+                    // the first atom stays picked while the cursor moves to the second
+                    _ => ToolAction::Hold,
+                }
+            }
             Tool::Rename => {
                 match interaction {
-                    MouseInteraction::MouseTapped => ToolAction::Rename,
+                    MouseInteraction::MouseTapped => ToolAction::Label,
+                    MouseInteraction::MouseDown => ToolAction::StartPan,
+                    _ => ToolAction::None,
+                }
+            }
+            Tool::Text => {
+                match interaction {
+                    MouseInteraction::MouseTapped => ToolAction::Annotate,
                     MouseInteraction::MouseDown => ToolAction::StartPan,
                     _ => ToolAction::None,
                 }
             }
-            Tool::C => {
+            Tool::Atom(symbol) => {
                 match interaction {
-                    MouseInteraction::MouseTapped => ToolAction::AtomDraw("C".to_string()),
+                    MouseInteraction::MouseTapped => ToolAction::AtomDraw(symbol.to_string()),
                     MouseInteraction::MouseDown => ToolAction::StartPan,
                     _ => ToolAction::None
                 }
@@ -90,6 +272,8 @@ pub enum ToolAction {
     #[default] None,
     CursorDragged,
     ClickSelect,
+    /// selects the item under a tap, or the next one under it when tapped again in the same place
+    CycleSelect,
     DragSelectStart,
     DragSelectFinish,
     StartPan,
@@ -97,13 +281,43 @@ pub enum ToolAction {
     Erase,
     BondStart(BondType),
     BondFinish,
+    InteractionStart,
+    InteractionFinish,
+    InkStart,
+    InkFinish,
+    ShapeStart(ShapeKind),
+    ShapeFinish,
+    /// selects a shape and starts moving it or one of its handles
+    ShapeDragStart(ShapeId, ShapeDrag),
+    OrbitalPlace(OrbitalKind),
+    MapStart,
+    MapFinish,
+    /// leaves the current action as it is
+    Hold,
+    /// starts pointing an orbital by its handle
+    OrbitalTurnStart(OrbitalId),
     Rename,
+    /// relabels the hovered atom, or starts typing a label or condensed formula on empty canvas
+    Label,
     AtomDraw(String),
+    /// edits the tapped text, or starts writing new text on empty canvas
+    Annotate,
+    /// starts moving text
+    AnnotationDragStart(AnnotationId),
+    BondHandleStart(MoleculeId, BondId, BondEnd),
+    BackgroundDragStart(BackgroundDrag),
+    BondHandleFinish,
+    BendHandleStart(MoleculeId, BondId),
+    BendHandleFinish,
+    /// selects the smaller side of a bond and starts moving it
+    SelectSubstituent(MoleculeId, BondId),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Message {
     ToolChanged(Tool),
+    /// opens or closes the periodic table to pick an atom tool from
+    TogglePeriodicTable,
 }
 
 impl Default for Message {
@@ -113,32 +327,85 @@ impl Default for Message {
 }
 
 
+impl Default for Toolbar {
+    fn default() -> Self {
+        Self {
+            selected: Tool::default(),
+            icons: IconTheme::load(),
+            quick_atoms: quick_atoms(),
+            recent: vec![],
+            periodic_table_open: false,
+        }
+    }
+}
+
 impl Toolbar {
     pub fn update(&mut self, message: Message) -> Message {
         match &message {
             Message::ToolChanged(tool) => {
                 self.selected = *tool;
+                self.periodic_table_open = false;
+                self.used(*tool);
+            }
+            Message::TogglePeriodicTable => {
+                self.periodic_table_open = !self.periodic_table_open;
             }
         }
 
         message
     }
 
-    fn svg_button(&self, name: &str, tool: Tool) -> Button<Message> {
-        let selected = self.selected == tool;
+    /// moves an atom or bond tool to the front of the recently used list
+    pub fn used(&mut self, tool: Tool) {
+        if !matches!(tool, Tool::Atom(_) | Tool::Bond(_)) {
+            return;
+        }
+
+        self.recent.retain(|recent| *recent != tool);
+        self.recent.insert(0, tool);
+        self.recent.truncate(MAX_RECENT);
+    }
+
+    /// a tool's button with its name and shortcut shown on hover
+    fn tool_button<'a>(&self, button: Button<'a, Message>, tool: Tool) -> Element<'a, Message> {
+        let title = match tool.shortcut() {
+            Some(shortcut) => format!("{} ({})", tool.title(), shortcut.to_ascii_uppercase()),
+            None => tool.title(),
+        };
+
+        tooltip(button, container(text(title).size(12)).padding(4), tooltip::Position::Right)
+            .style(container::rounded_box)
+            .into()
+    }
 
-        let svg = svg(Handle::from_path(format!(
-            "{}/resources/{}.svg",
-            env!("CARGO_MANIFEST_DIR"),
-            name
-        )))
-        .style(|theme: &Theme, _status| svg::Style {
+    fn svg_button(&self, name: &str, tool: Tool) -> Button<'_, Message> {
+        let svg = svg(self.icons.icon(name)).style(|theme: &Theme, _status| svg::Style {
             color: Some(theme.palette().text),
         });
 
-        let button = button(center(svg));
-        
-        button
+        self.styled_button(center(svg).into(), tool)
+    }
+
+    /// a tool's button however it's shown in the main list
+    fn any_button(&self, tool: Tool) -> Element<'_, Message> {
+        let button = match (tool, TOOLS.iter().find(|(_, listed)| *listed == tool)) {
+            (Tool::Atom(symbol), _) => self.atom_button(symbol),
+            (_, Some((name, _))) => self.svg_button(name, tool),
+            (_, None) => self.styled_button(center(text(tool.title()).size(8)).into(), tool),
+        };
+
+        self.tool_button(button, tool)
+    }
+
+    /// atom tools are shown by their symbol rather than an icon
+    fn atom_button(&self, symbol: &'static str) -> Button<'_, Message> {
+        self.styled_button(center(text(symbol).size(12)).into(), Tool::Atom(symbol))
+    }
+
+    fn styled_button<'a>(&self, content: Element<'a, Message>, tool: Tool) -> Button<'a, Message> {
+        let selected = self.selected == tool;
+
+        button(content)
             .style(move |theme: &Theme, _status| button::Style { 
                 background: Some(iced::Background::Color(if selected {
                     theme.extended_palette().background.weak.color 
@@ -160,22 +427,118 @@ impl Toolbar {
 }
 
 
-    pub fn view(&self) -> Element<Message> {
-        Into::<Element<Message>>::into(column![
-                self.svg_button("cursor-pointer", Tool::Cursor),
-                self.svg_button("square-dashed", Tool::Select),
-                self.svg_button("drag-hand-gesture", Tool::Pan),
-                self.svg_button("erase-solid", Tool::Erase),
-                self.svg_button("single", Tool::Bond(BondType::Normal(1))),
-                self.svg_button("double", Tool::Bond(BondType::Normal(2))),
-                self.svg_button("triple", Tool::Bond(BondType::Normal(3))),
-                self.svg_button("wedge", Tool::Bond(BondType::Wedge)),
-                self.svg_button("dash", Tool::Bond(BondType::Dash)),
-                self.svg_button("hydrogen-bond", Tool::Bond(BondType::Hydrogen)),
-                self.svg_button("input-field", Tool::Rename),
-                self.svg_button("letters/c", Tool::C),
-            ]
-            .width(Length::Fixed(30.0))
+    pub fn view(&self) -> Element<'_, Message> {
+        let tools = TOOLS.iter().map(|(name, tool)| self.tool_button(self.svg_button(name, *tool), *tool));
+        let atoms = self.quick_atoms.iter().map(|symbol| self.tool_button(self.atom_button(symbol), Tool::Atom(symbol)));
+
+        let more = tooltip(
+            button(center(text("…").size(12)))
+                .style(button::text)
+                .padding(Padding::new(5.0))
+                .width(Length::Fixed(30.0))
+                .height(Length::Fixed(30.0))
+                .on_press(Message::TogglePeriodicTable),
+            container(text("More elements").size(12)).padding(4),
+            tooltip::Position::Right,
+        )
+        .style(container::rounded_box);
+
+        // recently used tools below a rule, so those picked from the table are one click away
+        let recent = self.recent.iter().map(|tool| self.any_button(*tool));
+
+        Into::<Element<Message>>::into(
+            column(tools.chain(atoms))
+                .push(more)
+                .push_maybe((!self.recent.is_empty()).then(|| horizontal_rule(1)))
+                .extend(recent)
+                .width(Length::Fixed(30.0))
         )
     }
+
+    /// every element laid out as the periodic table, shown beside the toolbar while it's open,
+    /// with elements added in the user's element file in a row of their own
+    pub fn periodic_table(&self) -> Option<Element<'_, Message>> {
+        if !self.periodic_table_open {
+            return None;
+        }
+
+        // the main table, then the lanthanides and actinides, then anything else such as
+        // deuterium or elements added in the user's element file
+        let mut cells: Vec<Vec<Option<chemistry::Element>>> = vec![vec![]; 10];
+        let mut added = vec![];
+        for element in chemistry::Element::all() {
+            let placed = cells.iter().flatten().flatten().any(|placed| placed.atomic_number == element.atomic_number);
+            match table_position(element) {
+                Some((period, group)) if !placed => {
+                    let period = &mut cells[period];
+                    if period.len() <= group {
+                        period.resize(group + 1, None);
+                    }
+                    period[group] = Some(*element);
+                }
+                _ => added.push(Some(*element)),
+            }
+        }
+        if !added.is_empty() {
+            cells.push(added);
+        }
+
+        let rows = cells.into_iter().map(|elements| {
+            let cells = elements.into_iter().map(|element| match element {
+                Some(element) => tooltip(
+                    button(center(text(element.symbol).size(10)))
+                        .style(move |theme: &Theme, status| match self.selected == Tool::Atom(element.symbol) {
+                            true => button::primary(theme, status),
+                            false => button::secondary(theme, status),
+                        })
+                        .padding(0)
+                        .width(Length::Fixed(CELL_SIZE))
+                        .height(Length::Fixed(CELL_SIZE))
+                        .on_press(Message::ToolChanged(Tool::Atom(element.symbol))),
+                    container(text(format!("{} {}", element.atomic_number, element.name)).size(12)).padding(4),
+                    tooltip::Position::Bottom,
+                )
+                .style(container::rounded_box)
+                .into(),
+                None => Space::new(Length::Fixed(CELL_SIZE), Length::Fixed(CELL_SIZE)).into(),
+            });
+
+            row(cells).spacing(1).height(Length::Fixed(CELL_SIZE)).into()
+        });
+
+        Some(
+            container(column(rows).spacing(1))
+                .style(container::rounded_box)
+                .padding(5)
+                .into(),
+        )
+    }
+}
+
+/// row and column of an element in the periodic table, the lanthanides and actinides after
+/// lanthanum and actinium going in the last two rows below the main table
+fn table_position(element: &chemistry::Element) -> Option<(usize, usize)> {
+    let number = element.atomic_number as usize;
+    match (element.group(), number) {
+        (Some(group), _) => Some((element.period() as usize - 1, group as usize - 1)),
+        (None, 58..=71) => Some((8, number - 55)),
+        (None, 90..=103) => Some((9, number - 87)),
+        _ => None,
+    }
+}
+
+/// the symbols listed in the quick atoms file of the config directory, separated by spaces or
+/// lines with `#` starting comments, or the defaults when there's no file; symbols that aren't
+/// elements are left out
+fn quick_atoms() -> Vec<&'static str> {
+    let text = config::dir().and_then(|dir| std::fs::read_to_string(dir.join(QUICK_ATOMS_FILE)).ok());
+    let Some(text) = text else {
+        return DEFAULT_QUICK_ATOMS.to_vec();
+    };
+
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .flat_map(str::split_whitespace)
+        .filter_map(|symbol| chemistry::Element::from_symbol(symbol).map(|element| element.symbol))
+        .collect()
 }